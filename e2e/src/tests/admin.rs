@@ -131,12 +131,11 @@ async fn per_user_quota_via_admin_api() {
 
     // User A: 600 KB write → OK
     let data_600k: Vec<u8> = vec![0; 600_000];
-    let resp = session_a
+    session_a
         .storage()
         .put("/pub/data", data_600k.clone())
         .await
         .unwrap();
-    assert_eq!(resp.status(), StatusCode::CREATED);
 
     // User A: another 600 KB at a different path (total 1.2 MB) → 507
     let err = session_a
@@ -151,19 +150,17 @@ async fn per_user_quota_via_admin_api() {
     ));
 
     // User B: has no custom quota (unlimited) — same 600 KB writes should both succeed
-    let resp = session_b
+    session_b
         .storage()
         .put("/pub/data", data_600k.clone())
         .await
         .unwrap();
-    assert_eq!(resp.status(), StatusCode::CREATED);
 
-    let resp = session_b
+    session_b
         .storage()
         .put("/pub/data2", data_600k)
         .await
         .unwrap();
-    assert_eq!(resp.status(), StatusCode::CREATED);
 }
 
 /// Test that per-user write speed override set via admin API throttles uploads.
@@ -226,12 +223,11 @@ async fn per_user_speed_override_throttles_via_admin_api() {
 
     // User A (1kb/s override): 3 KB upload should take >2s due to throttling
     let start = Instant::now();
-    let resp = session_a
+    session_a
         .storage()
         .put("/pub/rate_test", body.clone())
         .await
         .unwrap();
-    assert_eq!(resp.status(), StatusCode::CREATED);
     let elapsed_a = start.elapsed();
     assert!(
         elapsed_a > Duration::from_secs(2),
@@ -241,12 +237,11 @@ async fn per_user_speed_override_throttles_via_admin_api() {
 
     // User B (no override, uses default 1mb/s): same upload should be fast (<2s)
     let start = Instant::now();
-    let resp = session_b
+    session_b
         .storage()
         .put("/pub/rate_test", body)
         .await
         .unwrap();
-    assert_eq!(resp.status(), StatusCode::CREATED);
     let elapsed_b = start.elapsed();
     assert!(
         elapsed_b < Duration::from_secs(2),
@@ -381,12 +376,11 @@ async fn allowed_write_paths_enforced_via_http() {
     let pubkey_z32 = signer.public_key().z32();
 
     // Write a file to a path that will later be disallowed (before restriction is applied)
-    let resp = session
+    session
         .storage()
         .put("/pub/other/bar.json", vec![4, 5, 6])
         .await
         .unwrap();
-    assert_eq!(resp.status(), StatusCode::CREATED);
 
     // Restrict user to /pub/tokens/ only via admin API
     let admin_client = PubkyHttpClient::new().unwrap();
@@ -404,12 +398,11 @@ async fn allowed_write_paths_enforced_via_http() {
     assert_eq!(resp.status(), StatusCode::OK);
 
     // Write to allowed path → 201
-    let resp = session
+    session
         .storage()
         .put("/pub/tokens/foo.json", vec![1, 2, 3])
         .await
         .unwrap();
-    assert_eq!(resp.status(), StatusCode::CREATED);
 
     // Write to disallowed path → 403
     let err = session
@@ -454,6 +447,28 @@ async fn allowed_write_paths_enforced_via_http() {
         ),
         "Expected 403 FORBIDDEN on delete but got: {err:?}"
     );
+
+    // Directory delete inside the allowed path → succeeds
+    session
+        .storage()
+        .put("/pub/tokens/old/baz.json", vec![7])
+        .await
+        .unwrap();
+    let deleted = session.storage().delete_dir("/pub/tokens/").await.unwrap();
+    assert_eq!(deleted, 1);
+
+    // Directory delete reaching beyond the allowed path → 403, nothing deleted
+    let err = session.storage().delete_dir("/pub/").await.unwrap_err();
+    assert!(
+        matches!(
+            err,
+            Error::Request(RequestError::Server { status, .. })
+                if status == StatusCode::FORBIDDEN
+        ),
+        "Expected 403 FORBIDDEN on directory delete but got: {err:?}"
+    );
+    let resp = session.storage().get("/pub/other/bar.json").await.unwrap();
+    assert_eq!(resp.bytes().await.unwrap().as_ref(), &[4, 5, 6]);
 }
 
 /// End-to-end over raw HTTP/SSE: real `/pub/` and `/priv/` writes surface in the admin
@@ -588,3 +603,114 @@ async fn admin_event_stream_exposes_private_events() {
         "public feed must not leak private paths: {public_body}"
     );
 }
+
+/// The admin events export streams every event (public and private) as JSON lines, honours the
+/// `from`/`to` range so an interrupted export can resume, and rejects a wrong password.
+#[tokio::test]
+#[pubky_testnet::test]
+async fn admin_events_export_streams_json_lines() {
+    use futures::StreamExt;
+    use pubky_testnet::pubky::EventCursor;
+
+    let config = ConfigToml::default_test_config();
+    let admin_password = config.admin.admin_password.clone();
+
+    let mut testnet = Testnet::new().await.unwrap();
+    let pubky = testnet.sdk().unwrap();
+    let mock_dir = MockDataDir::new(config, Some(Keypair::random())).unwrap();
+    let server = testnet
+        .create_homeserver_app_with_mock(mock_dir)
+        .await
+        .unwrap();
+    let admin_url = format!(
+        "http://{}",
+        server
+            .admin_server()
+            .expect("admin server should be enabled")
+            .listen_socket()
+    );
+
+    let signer = pubky.signer(Keypair::random());
+    let session = signer
+        .signup_cookie(&server.public_key(), None)
+        .await
+        .unwrap();
+    let storage = session.storage();
+    storage.put("/pub/a.txt", vec![1]).await.unwrap();
+    storage.put("/priv/b.txt", vec![2]).await.unwrap();
+    storage.delete("/pub/a.txt").await.unwrap();
+
+    let export = |from: Option<u64>, to: Option<u64>| {
+        let mut builder = pubky.event_export(&admin_url, &admin_password);
+        if let Some(from) = from {
+            builder = builder.from(EventCursor::new(from));
+        }
+        if let Some(to) = to {
+            builder = builder.to(EventCursor::new(to));
+        }
+        async move {
+            let mut body = Vec::new();
+            let mut stream = builder.bytes_stream().await.unwrap();
+            while let Some(chunk) = stream.next().await {
+                body.extend_from_slice(&chunk.unwrap());
+            }
+            String::from_utf8(body)
+                .unwrap()
+                .lines()
+                .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+                .collect::<Vec<_>>()
+        }
+    };
+
+    // The full export holds the signup's events plus our three, private ones included.
+    let lines = export(None, None).await;
+    let ours: Vec<_> = lines
+        .iter()
+        .filter(|line| {
+            line["resource"]
+                .as_str()
+                .unwrap()
+                .starts_with(&format!("pubky://{}/", session.public_key().z32()))
+        })
+        .collect();
+    let summary: Vec<_> = ours
+        .iter()
+        .map(|line| {
+            let resource = line["resource"].as_str().unwrap();
+            let path = &resource[resource.rfind('/').unwrap()..];
+            (line["type"].as_str().unwrap(), path)
+        })
+        .collect();
+    assert_eq!(
+        &summary[summary.len() - 3..],
+        &[("PUT", "/a.txt"), ("PUT", "/b.txt"), ("DEL", "/a.txt")]
+    );
+    let put = ours[ours.len() - 3];
+    assert!(put["hash"].is_string());
+    assert!(put["timestamp"].as_i64().unwrap() > 0);
+    assert!(ours[ours.len() - 1]["hash"].is_null());
+
+    // Resuming after the first of our events, bounded by the last, yields just the rest.
+    let cursor =
+        |line: &serde_json::Value| -> u64 { line["cursor"].as_str().unwrap().parse().unwrap() };
+    let first = cursor(ours[ours.len() - 3]);
+    let last = cursor(ours[ours.len() - 1]);
+    let resumed = export(Some(first), Some(last)).await;
+    let resumed: Vec<u64> = resumed.iter().map(cursor).collect();
+    assert_eq!(resumed, vec![cursor(ours[ours.len() - 2]), last]);
+
+    // A wrong password is rejected before anything is streamed.
+    let err = pubky
+        .event_export(&admin_url, "wrong-password")
+        .bytes_stream()
+        .await
+        .err()
+        .expect("wrong password must be rejected");
+    assert!(
+        matches!(
+            err,
+            Error::Request(RequestError::Server { status, .. }) if status == StatusCode::UNAUTHORIZED
+        ),
+        "Expected 401 UNAUTHORIZED but got: {err:?}"
+    );
+}
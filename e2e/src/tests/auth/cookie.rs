@@ -27,11 +27,7 @@ async fn basic_signer_signup() {
         .put("/pub/test.app/hello.txt", b"world".to_vec())
         .await
         .unwrap();
-    assert_eq!(
-        response.status(),
-        StatusCode::CREATED,
-        "file upload should succeed"
-    );
+    assert_eq!(response.size, 5, "file upload should succeed");
 }
 
 #[tokio::test]
@@ -293,6 +289,13 @@ async fn disabled_user() {
         "Disabled user must get 403 on write"
     );
 
+    // Touching is a write too
+    let err = session.storage().touch(file_path).await.unwrap_err();
+    assert!(
+        matches!(err, Error::Request(RequestError::Server { status, .. }) if status == StatusCode::FORBIDDEN),
+        "Disabled user must get 403 on touch"
+    );
+
     // User can delete their file (deletes should still be allowed to enable cleanup by the user or test)
     let response = session.storage().delete(file_path).await.unwrap();
     assert_eq!(
@@ -354,6 +357,42 @@ async fn session_secret_export_import_restores_session() {
         .unwrap();
 }
 
+#[tokio::test]
+#[pubky_testnet::test]
+async fn cookie_session_handoff_restores_session() {
+    let testnet = build_full_testnet().await;
+    let homeserver = testnet.homeserver_app();
+    let pubky = testnet.sdk().unwrap();
+
+    let signer = pubky.signer(Keypair::random());
+    let session = signer
+        .signup_cookie(&homeserver.public_key(), None)
+        .await
+        .unwrap();
+
+    let blob = session.export_handoff().await.unwrap();
+    let restored = testnet
+        .sdk()
+        .unwrap()
+        .import_session_handoff(&blob)
+        .await
+        .unwrap();
+    assert_eq!(restored.info(), session.info());
+    restored
+        .storage()
+        .put("/pub/app/handoff.txt", "hello")
+        .await
+        .unwrap();
+
+    // Signing out in one process invalidates the blob everywhere.
+    session.signout().await.unwrap();
+    let err = pubky.import_session_handoff(&blob).await.unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Authentication(pubky_testnet::pubky::errors::AuthError::RequestExpired)
+    ));
+}
+
 #[tokio::test]
 #[pubky_testnet::test]
 async fn multiple_users() {
@@ -403,3 +442,93 @@ async fn signout_is_idempotent() {
         .await
         .expect("second signout must be idempotent");
 }
+
+#[tokio::test]
+#[pubky_testnet::test]
+#[allow(deprecated, reason = "Test exercises the deprecated cookie auth flow")]
+async fn signup_with_skewed_clock() {
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app();
+    let skewed_clock = || {
+        let clock = MockClock::default();
+        clock.advance(Duration::from_secs(10 * 60));
+        Arc::new(clock)
+    };
+
+    // A clock 10 minutes ahead gets its tokens rejected, with a diagnostic.
+    let client = testnet
+        .client_builder()
+        .time_source(skewed_clock())
+        .build()
+        .unwrap();
+    let signer = Pubky::with_client(client.clone()).signer(Keypair::random());
+    let err = signer
+        .signup_cookie(&server.public_key(), None)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(
+            err,
+            Error::Authentication(AuthError::ClockSkewDetected { offset_secs })
+                if (-610..=-590).contains(&offset_secs)
+        ),
+        "{err:?}"
+    );
+    assert!(client.clock_skew_secs().is_some());
+
+    // With correction enabled, the offset learned from the rejection fixes the clock.
+    let client = testnet
+        .client_builder()
+        .time_source(skewed_clock())
+        .correct_clock_skew(true)
+        .build()
+        .unwrap();
+    let signer = Pubky::with_client(client).signer(Keypair::random());
+    signer
+        .signup_cookie(&server.public_key(), None)
+        .await
+        .unwrap_err();
+    signer
+        .signup_cookie(&server.public_key(), None)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+#[pubky_testnet::test]
+#[allow(deprecated, reason = "Test exercises the deprecated cookie auth flow")]
+async fn measured_clock_skew_corrects_the_first_token() {
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app();
+    let clock = MockClock::default();
+    clock.advance(Duration::from_secs(10 * 60));
+
+    let pubky = testnet.sdk().unwrap();
+    let server_time = pubky.homeserver_time(&server.public_key()).await.unwrap();
+    let local_time = pubky_testnet::pubky_common::timestamp::Timestamp::now();
+    assert!(server_time.as_u64().abs_diff(local_time.as_u64()) < 5_000_000);
+
+    // Measuring before signing up spares the rejected first attempt.
+    let client = testnet
+        .client_builder()
+        .time_source(Arc::new(clock))
+        .correct_clock_skew(true)
+        .build()
+        .unwrap();
+    let pubky = Pubky::with_client(client.clone());
+    let skew = pubky
+        .measure_clock_skew(&server.public_key())
+        .await
+        .unwrap();
+    assert!((-610..=-590).contains(&skew.offset_secs()), "{skew:?}");
+    assert!(skew.round_trip < Duration::from_secs(5));
+    assert_eq!(client.clock_skew_secs(), Some(skew.offset_secs()));
+    pubky
+        .signer(Keypair::random())
+        .signup_cookie(&server.public_key(), None)
+        .await
+        .unwrap();
+
+    let unknown = Keypair::random().public_key();
+    pubky.measure_clock_skew(&unknown).await.unwrap_err();
+}
@@ -41,11 +41,198 @@ async fn signer_signup_signin_write_file() {
         .put("/pub/test.app/hello.txt", b"world".to_vec())
         .await
         .unwrap();
+    assert_eq!(response.size, 5, "file upload should succeed");
+}
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn signup_into_session_honors_options() {
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app();
+    let pubky = testnet.sdk().unwrap();
+    let signer = pubky.signer(Keypair::random());
+
+    let ttl = std::time::Duration::from_secs(10 * 60);
+    let options = SignupOptions {
+        session_ttl: Some(ttl),
+        capabilities: Some(
+            Capabilities::builder()
+                .read_write("/pub/test.app/")
+                .finish(),
+        ),
+        ..SignupOptions::default()
+    };
+    let session = signer
+        .signup_into_session(
+            &server.public_key(),
+            ClientId::new("test.app").unwrap(),
+            options,
+        )
+        .await
+        .unwrap();
+
+    let info = session.as_grant().unwrap().session_info().await;
+    let lifetime = info.grant_expires_at.abs_diff(info.created_at);
+    assert!(
+        lifetime.abs_diff(ttl.as_secs()) <= 2,
+        "lifetime was {lifetime}"
+    );
+    assert!(info.token_expires_at <= info.grant_expires_at);
+    assert!(!info.capabilities.contains(&Capability::root()));
+
+    session
+        .storage()
+        .put("/pub/test.app/hello.txt", "world")
+        .await
+        .unwrap();
+    session
+        .storage()
+        .put("/pub/other.app/hello.txt", "world")
+        .await
+        .expect_err("write outside the granted capabilities should fail");
+
+    // Out-of-bounds lifetimes are rejected before anything is sent.
+    let options = SignupOptions {
+        session_ttl: Some(std::time::Duration::from_secs(1)),
+        ..SignupOptions::default()
+    };
+    signer
+        .signup_into_session(
+            &server.public_key(),
+            ClientId::new("test.app").unwrap(),
+            options,
+        )
+        .await
+        .expect_err("a 1 second session is below the minimum");
+}
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn signup_into_session_checks_the_homeserver() {
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app();
+    let pubky = testnet.sdk().unwrap();
+    let signer = pubky.signer(Keypair::random());
+    let client_id = ClientId::new("test.app").unwrap();
+
+    // A key that is not a homeserver fails the check before anything is published.
+    let not_a_homeserver = Keypair::random().public_key();
+    let err = signer
+        .signup_into_session(
+            &not_a_homeserver,
+            client_id.clone(),
+            SignupOptions::default(),
+        )
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, Error::NotAHomeserver { public_key, .. } if **public_key == not_a_homeserver),
+        "{err:?}"
+    );
+    assert!(pubky
+        .get_homeserver_of(&signer.public_key())
+        .await
+        .is_none());
+
+    // Opting out goes straight to the account creation, which fails differently.
+    let options = SignupOptions {
+        skip_homeserver_check: true,
+        ..SignupOptions::default()
+    };
+    let err = signer
+        .signup_into_session(&not_a_homeserver, client_id.clone(), options)
+        .await
+        .unwrap_err();
+    assert!(!matches!(err, Error::NotAHomeserver { .. }), "{err:?}");
+
+    assert!(pubky.check_homeserver(&server.public_key()).await.is_ok());
+    signer
+        .signup_into_session(&server.public_key(), client_id, SignupOptions::default())
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn signup_multi_reports_per_homeserver_results() {
+    let mut testnet = build_full_testnet().await;
+    let hs1 = testnet.homeserver_app().public_key();
+    let hs2 = testnet
+        .create_random_homeserver()
+        .await
+        .unwrap()
+        .public_key();
+    let mut closed_config = ConfigToml::minimal_test_config();
+    closed_config.general.signup_mode = SignupMode::TokenRequired;
+    let closed = testnet
+        .create_random_homeserver_with_config(Some(closed_config))
+        .await
+        .unwrap()
+        .public_key();
+    let pubky = testnet.sdk().unwrap();
+    let signer = pubky.signer(Keypair::random());
+
+    // The closed homeserver comes first, so hs1 is the first successful signup.
+    let results = signer
+        .signup_multi(
+            &[closed.clone(), hs1.clone(), hs2.clone()],
+            ClientId::new("test.app").unwrap(),
+            SignupOptions::default(),
+        )
+        .await;
+
+    let keys: Vec<_> = results.iter().map(|(key, _)| key.clone()).collect();
+    assert_eq!(keys, vec![closed, hs1.clone(), hs2.clone()]);
+    assert!(results[0].1.is_err(), "signup without a token must fail");
+
+    let primary = results[1].1.as_ref().unwrap();
+    primary
+        .storage()
+        .put("/pub/test.app/hello.txt", "world")
+        .await
+        .unwrap();
+    let secondary = results[2].1.as_ref().unwrap();
+    assert!(secondary.revalidate().await.unwrap().is_some());
+
     assert_eq!(
-        response.status(),
-        StatusCode::CREATED,
-        "file upload should succeed"
+        pubky.pkdns().get_homeserver_of(&signer.public_key()).await,
+        Some(hs1.clone())
     );
+    // Every successful homeserver is in the record, the primary first.
+    assert_eq!(
+        pubky.pkdns().get_homeservers_of(&signer.public_key()).await,
+        vec![hs1, hs2]
+    );
+}
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn signup_with_selector_picks_a_reachable_homeserver() {
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app().public_key();
+    let unpublished = Keypair::random().public_key();
+    let pubky = testnet.sdk().unwrap();
+
+    let signer = pubky.signer(Keypair::random());
+    let selector =
+        LatencySelector::new([unpublished.clone(), server.clone()]).timeout(Duration::from_secs(2));
+    let chosen = signer.signup_with_selector(&selector, None).await.unwrap();
+    assert_eq!(chosen, server);
+    assert_eq!(
+        pubky.pkdns().get_homeserver_of(&signer.public_key()).await,
+        Some(server)
+    );
+
+    // No reachable candidate: nothing is signed up.
+    let err = pubky
+        .signer(Keypair::random())
+        .signup_with_selector(&LatencySelector::new([unpublished]), None)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Request(RequestError::Validation { .. })
+    ));
 }
 
 #[tokio::test]
@@ -104,6 +291,59 @@ async fn auth_flow() {
     assert_scoped_write_access(&session).await;
 }
 
+#[tokio::test]
+#[pubky_testnet::test]
+async fn auth_flow_grants_accepted_optional_capabilities_only() {
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app();
+    let pubky = testnet.sdk().unwrap();
+    let http_relay_url = testnet.http_relay().local_link_url();
+
+    let signer = pubky.signer(Keypair::random());
+    signer.signup(&server.public_key(), None).await.unwrap();
+
+    let caps = Capabilities::builder()
+        .read_write("/pub/pubky.app/")
+        .finish();
+    let optional = Capabilities::builder()
+        .read("/pub/contacts/")
+        .write("/pub/calendar/")
+        .finish();
+    let auth = PubkyGrantAuthFlow::builder(
+        &caps,
+        AuthFlowKind::signin(),
+        ClientId::new("test.app").unwrap(),
+    )
+    .optional_capabilities(&optional)
+    .relay(http_relay_url)
+    .client(pubky.client().clone())
+    .start()
+    .unwrap();
+
+    let url = auth.authorization_url();
+    let request = auth.requested_capabilities();
+    assert_eq!(request.required, caps);
+    assert_eq!(request.optional, optional);
+
+    // The user accepts the contacts capability but declines the calendar one.
+    let accepted = Capabilities::builder().read("/pub/contacts/").finish();
+    signer
+        .approve_auth_with_optional(&url, &accepted)
+        .await
+        .unwrap();
+
+    let session = auth.await_approval().await.unwrap();
+    assert_eq!(
+        Capabilities::from(session.info().capabilities().to_vec()).to_string(),
+        "/pub/pubky.app/:rw,/pub/contacts/:r"
+    );
+    assert_eq!(
+        request.granted_optional(session.info().capabilities()),
+        accepted
+    );
+    assert_scoped_write_access(&session).await;
+}
+
 #[tokio::test]
 #[pubky_testnet::test]
 async fn grant_secret_restore_mints_fresh_bearer() {
@@ -144,6 +384,33 @@ async fn grant_secret_restore_mints_fresh_bearer() {
         .unwrap();
 }
 
+#[tokio::test]
+#[pubky_testnet::test]
+async fn grant_session_handoff_restores_session() {
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app();
+    let pubky = testnet.sdk().unwrap();
+
+    let signer = pubky.signer(Keypair::random());
+    signer.signup(&server.public_key(), None).await.unwrap();
+    let session = signer
+        .signin(ClientId::new("handoff.test").unwrap())
+        .await
+        .unwrap();
+
+    let blob = session.export_handoff().await.unwrap();
+    // The helper process builds its own client.
+    let helper = testnet.sdk().unwrap();
+    let restored = helper.import_session_handoff(&blob).await.unwrap();
+
+    assert_eq!(restored.info(), session.info());
+    restored
+        .storage()
+        .put("/pub/handoff.test/hello", b"world".to_vec())
+        .await
+        .unwrap();
+}
+
 #[tokio::test]
 #[pubky_testnet::test]
 async fn grant_secret_restore_rejects_revoked_grant() {
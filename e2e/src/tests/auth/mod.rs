@@ -7,21 +7,23 @@ use super::build_full_testnet;
 use pubky_testnet::pubky::deep_links::{
     DeepLink, DeepLinkScheme, DirectSignupDeepLink, DirectSignupParams,
 };
-use pubky_testnet::pubky::errors::{Error, RequestError};
+use pubky_testnet::pubky::errors::{AuthError, Error, RequestError};
 use pubky_testnet::pubky::pkarr;
 use pubky_testnet::pubky::IntoPubkyResource;
 #[allow(deprecated, reason = "E2E tests cover the deprecated cookie flow")]
 use pubky_testnet::pubky::PubkyCookieAuthFlow;
 use pubky_testnet::pubky::{
-    AuthFlowKind, ClientId, GrantManager, Keypair, Method, PubkyGrantAuthFlow, PubkyHttpClient,
-    PubkySession, StatusCode,
+    AuthFlowKind, ClientId, GrantManager, Keypair, LatencySelector, Method, Pubky,
+    PubkyGrantAuthFlow, PubkyHttpClient, PubkySession, SignupOptions, StatusCode,
 };
 use pubky_testnet::pubky_common::capabilities::{Capabilities, Capability};
+use pubky_testnet::pubky_common::clock::MockClock;
 use pubky_testnet::{
     pubky_homeserver::{ConfigToml, SignupMode},
     EphemeralTestnet, Testnet,
 };
 use std::str::FromStr;
+use std::sync::Arc;
 use std::time::Duration;
 
 async fn assert_scoped_write_access(session: &PubkySession) {
@@ -36,16 +38,22 @@ async fn assert_scoped_write_access(session: &PubkySession) {
         .put("/pub/pubky.app", Vec::<u8>::new())
         .await
         .unwrap_err();
-    assert!(
-        matches!(err, Error::Request(RequestError::Server { status, .. }) if status == StatusCode::FORBIDDEN)
-    );
+    assert!(matches!(
+        err,
+        Error::Request(RequestError::Forbidden { .. })
+    ));
 
+    session
+        .storage()
+        .check_permission(&Method::GET, "/pub/foo.bar/file")
+        .unwrap();
     let err = session
         .storage()
         .put("/pub/foo.bar/file", Vec::<u8>::new())
         .await
         .unwrap_err();
-    assert!(
-        matches!(err, Error::Request(RequestError::Server { status, .. }) if status == StatusCode::FORBIDDEN)
-    );
+    assert!(matches!(
+        err,
+        Error::Request(RequestError::Forbidden { .. })
+    ));
 }
@@ -126,7 +126,7 @@ async fn test_republish_homeserver() {
 
     // Conditional publish with a "fresh" record should NO-OP.
     let pkdns = signer.pkdns().set_stale_after(max_record_age);
-    pkdns.publish_homeserver_if_stale(None).await.unwrap();
+    assert_eq!(pkdns.publish_homeserver_if_stale(None).await.unwrap(), None);
 
     let ts2 = pubky
         .client()
@@ -142,7 +142,11 @@ async fn test_republish_homeserver() {
     tokio::time::sleep(max_record_age + Duration::from_secs(1)).await;
 
     // Now the conditional publish should republish and bump the timestamp.
-    pkdns.publish_homeserver_if_stale(None).await.unwrap();
+    let published = pkdns
+        .publish_homeserver_if_stale(None)
+        .await
+        .unwrap()
+        .expect("stale record is republished");
 
     let ts3 = pubky
         .client()
@@ -154,4 +158,426 @@ async fn test_republish_homeserver() {
         .as_u64();
 
     assert!(ts3 > ts2, "record should be republished when stale");
+    // The publish reports the packet that resolvers now see.
+    let published_at = published
+        .record_timestamp
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap();
+    assert_eq!(published_at.as_micros(), u128::from(ts3));
+    assert_eq!(published.ttl, Duration::from_secs(60 * 60));
+}
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn publish_and_list_multiple_homeservers() {
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app();
+    let pubky = testnet.sdk().unwrap();
+
+    let signer = pubky.signer(Keypair::random());
+    let user = signer.public_key().clone();
+    let session = signer
+        .signup_cookie(&server.public_key(), None)
+        .await
+        .unwrap();
+    let pkdns = pubky.pkdns();
+    assert_eq!(
+        pkdns.get_homeservers_of(&user).await,
+        vec![server.public_key()]
+    );
+
+    // Add a secondary homeserver behind the primary.
+    let secondary = Keypair::random().public_key();
+    signer
+        .pkdns()
+        .publish_homeservers(&[server.public_key(), secondary.clone()])
+        .await
+        .unwrap();
+
+    assert_eq!(
+        pkdns.get_homeservers_of(&user).await,
+        vec![server.public_key(), secondary]
+    );
+    assert_eq!(
+        pkdns.get_homeserver_of(&user).await,
+        Some(server.public_key())
+    );
+
+    // Requests keep going to the primary.
+    session
+        .storage()
+        .put("/pub/app/multi.txt", "primary")
+        .await
+        .unwrap();
+    let body = pubky
+        .public_storage()
+        .get(format!("{user}/pub/app/multi.txt"))
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert_eq!(body, "primary");
+
+    assert!(signer.pkdns().publish_homeservers(&[]).await.is_err());
+    assert!(pkdns
+        .get_homeservers_of(&Keypair::random().public_key())
+        .await
+        .is_empty());
+}
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn public_get_fails_over_only_when_homeserver_is_unreachable() {
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app().public_key();
+    let pubky = testnet.sdk().unwrap();
+
+    let signer = pubky.signer(Keypair::random());
+    let user = signer.public_key().clone();
+    let session = signer.signup_cookie(&server, None).await.unwrap();
+    session
+        .storage()
+        .put("/pub/app/failover.txt", "hello")
+        .await
+        .unwrap();
+
+    let failover_storage = || {
+        let client = testnet
+            .client_builder()
+            .read_failover(true)
+            .build()
+            .unwrap();
+        pubky_testnet::pubky::Pubky::with_client(client).public_storage()
+    };
+    let unreachable = Keypair::random().public_key();
+
+    // An unreachable primary fails over to the next homeserver.
+    signer
+        .pkdns()
+        .publish_homeservers(&[unreachable.clone(), server.clone()])
+        .await
+        .unwrap();
+    let path = format!("{user}/pub/app/failover.txt");
+    let body = failover_storage()
+        .get(&path)
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert_eq!(body, "hello");
+
+    // Without failover the read fails.
+    let storage = testnet.sdk().unwrap().public_storage();
+    assert!(storage.get(&path).await.is_err());
+
+    // A reachable primary answering 404 is final: failing over to the unreachable
+    // secondary would have surfaced a transport error instead.
+    signer
+        .pkdns()
+        .publish_homeservers(&[server, unreachable])
+        .await
+        .unwrap();
+    let err = failover_storage()
+        .get(format!("{user}/pub/app/missing.txt"))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, Error::Request(RequestError::Server { status, .. }) if status == StatusCode::NOT_FOUND),
+        "expected a 404, got {err:?}"
+    );
+}
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn reads_tell_missing_records_from_unreachable_homeservers() {
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app().public_key();
+    let pubky = testnet.sdk().unwrap();
+    let storage = pubky.public_storage();
+
+    // A key that never published a record does not exist.
+    let unknown = Keypair::random().public_key();
+    let err = storage
+        .get(format!("{unknown}/pub/app/file.txt"))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, Error::RecordNotFound { public_key } if **public_key == unknown),
+        "expected RecordNotFound, got {err:?}"
+    );
+    let err = pubky
+        .signer(Keypair::random())
+        .pkdns()
+        .get_homeserver()
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::RecordNotFound { .. }), "got {err:?}");
+
+    // A published record pointing at a homeserver that does not answer.
+    let signer = pubky.signer(Keypair::random());
+    let user = signer.public_key().clone();
+    signer.signup_cookie(&server, None).await.unwrap();
+    signer
+        .pkdns()
+        .publish_homeservers(&[Keypair::random().public_key()])
+        .await
+        .unwrap();
+    let err = storage
+        .get(format!("{user}/pub/app/file.txt"))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, Error::HomeserverUnreachable { public_key, .. } if **public_key == user),
+        "expected HomeserverUnreachable, got {err:?}"
+    );
+}
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn ensure_published_repairs_missing_and_moved_records() {
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app().public_key();
+    let pubky = testnet.sdk().unwrap();
+    let pkdns = pubky.pkdns();
+    let backup = Keypair::random().public_key();
+
+    // A missing record is published, after which the call is a no-op.
+    let signer = pubky.signer(Keypair::random());
+    let user = signer.public_key().clone();
+    assert!(signer.ensure_published(&server).await.unwrap().is_some());
+    assert_eq!(pkdns.get_homeserver_of(&user).await, Some(server.clone()));
+    assert!(signer.ensure_published(&server).await.unwrap().is_none());
+
+    // A fresh record with the right primary is left alone, fallbacks included.
+    signer
+        .pkdns()
+        .publish_homeservers(&[server.clone(), backup.clone()])
+        .await
+        .unwrap();
+    assert!(signer.ensure_published(&server).await.unwrap().is_none());
+    assert_eq!(
+        pkdns.get_homeservers_of(&user).await,
+        vec![server.clone(), backup.clone()]
+    );
+
+    // A record pointing elsewhere is replaced.
+    signer
+        .pkdns()
+        .publish_homeserver_force(Some(&backup))
+        .await
+        .unwrap();
+    assert!(signer.ensure_published(&server).await.unwrap().is_some());
+    assert_eq!(pkdns.get_homeservers_of(&user).await, vec![server]);
+}
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn resolve_any_returns_third_party_records() {
+    use pkarr::dns::{rdata::RData, rdata::TXT, TYPE};
+
+    let testnet = build_full_testnet().await;
+    let pubky = testnet.sdk().unwrap();
+    let client = testnet.client().unwrap();
+
+    // A key that has nothing to do with pubky publishes its own records.
+    let keypair = Keypair::random();
+    let mut txt = TXT::new();
+    txt.add_string("app=interop").unwrap();
+    let packet = pkarr::SignedPacket::builder()
+        .address("node".try_into().unwrap(), [10, 0, 0, 1].into(), 60)
+        .txt("_app".try_into().unwrap(), txt, 60)
+        .sign(&keypair)
+        .unwrap();
+    client.pkarr().publish(&packet).await.unwrap();
+
+    let pkdns = pubky.pkdns();
+    let public_key = keypair.public_key();
+
+    let a_records = pkdns.resolve_any(&public_key, TYPE::A).await.unwrap();
+    assert_eq!(a_records.len(), 1);
+    assert!(matches!(a_records[0].rdata, RData::A(_)));
+
+    let txt_records = pkdns.resolve_any(&public_key, TYPE::TXT).await.unwrap();
+    assert_eq!(txt_records.len(), 1);
+
+    // No records of the requested type, and no packet at all, are both empty.
+    assert!(pkdns
+        .resolve_any(&public_key, TYPE::AAAA)
+        .await
+        .unwrap()
+        .is_empty());
+    let unknown = Keypair::random().public_key();
+    assert!(pkdns
+        .resolve_any(&unknown, TYPE::A)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn rotate_to_links_old_key_to_new_key() {
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app();
+    let pubky = testnet.sdk().unwrap();
+
+    let old = pubky.signer(Keypair::random());
+    old.signup(&server.public_key(), None).await.unwrap();
+
+    let new_keypair = Keypair::random();
+    let new_key = new_keypair.public_key();
+    let new = old.rotate_to(new_keypair, None).await.unwrap();
+    assert_eq!(new.public_key(), new_key);
+
+    // The new key lives on the same homeserver and can sign in.
+    let pkdns = pubky.pkdns();
+    assert_eq!(
+        pkdns.get_homeserver_of(&new_key).await,
+        Some(server.public_key())
+    );
+    new.signin_blocking(ClientId::new("rotation.test").unwrap())
+        .await
+        .unwrap();
+
+    // Other clients follow the link; the old key's homeserver record is kept.
+    assert_eq!(
+        pkdns.resolve_current_key(&old.public_key()).await.unwrap(),
+        new_key
+    );
+    assert_eq!(pkdns.resolve_current_key(&new_key).await.unwrap(), new_key);
+    assert_eq!(
+        pkdns.get_homeserver_of(&old.public_key()).await,
+        Some(server.public_key())
+    );
+
+    // Rotating back onto an existing account works, and the resulting loop is rejected.
+    new.rotate_to(old.keypair().clone(), None).await.unwrap();
+    let err = pkdns
+        .resolve_current_key(&old.public_key())
+        .await
+        .expect_err("rotation loop should be rejected");
+    assert!(matches!(
+        err,
+        Error::Request(RequestError::Validation { .. })
+    ));
+}
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn pinned_homeserver_changes_are_reported_until_accepted() {
+    use pubky_testnet::pubky::{MemoryPinStore, PinPolicy};
+
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app().public_key();
+    let store = Arc::new(MemoryPinStore::new());
+    // A fresh client per lookup, so no resolution is served from the Pkarr cache.
+    let pinned = || {
+        let client = testnet
+            .client_builder()
+            .pin_records(store.clone(), PinPolicy::Strict)
+            .build()
+            .unwrap();
+        Pubky::with_client(client)
+    };
+
+    let keypair = Keypair::random();
+    let user = keypair.public_key();
+    let signer = testnet.sdk().unwrap().signer(keypair.clone());
+    signer.signup_cookie(&server, None).await.unwrap();
+
+    // The first resolution pins the homeserver.
+    let resolved = pinned().pkdns().require_homeserver_of(&user).await.unwrap();
+    assert_eq!(resolved, server);
+
+    // A record published elsewhere is rejected.
+    let other = Keypair::random().public_key();
+    signer
+        .pkdns()
+        .publish_homeserver_force(Some(&other))
+        .await
+        .unwrap();
+    let err = pinned()
+        .pkdns()
+        .require_homeserver_of(&user)
+        .await
+        .unwrap_err();
+    let Error::RecordChanged(change) = err else {
+        panic!("expected a record change, got {err:?}");
+    };
+    assert_eq!((change.old, change.new), (server.clone(), other.clone()));
+    assert!(pinned().pkdns().get_homeservers_of(&user).await.is_empty());
+
+    // Accepting the migration moves the pin.
+    pinned().pkdns().accept_homeserver(&user, &other);
+    let resolved = pinned().pkdns().require_homeserver_of(&user).await.unwrap();
+    assert_eq!(resolved, other);
+
+    // Records published through a pinned client move the pin too.
+    pinned()
+        .signer(keypair)
+        .pkdns()
+        .publish_homeserver_force(Some(&server))
+        .await
+        .unwrap();
+    let resolved = pinned().pkdns().require_homeserver_of(&user).await.unwrap();
+    assert_eq!(resolved, server);
+}
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn pinned_records_guard_storage_reads() {
+    use pubky_testnet::pubky::{MemoryPinStore, PinPolicy};
+
+    let mut testnet = build_full_testnet().await;
+    let genuine = testnet.homeserver_app().public_key();
+    let hijacked = testnet
+        .create_random_homeserver()
+        .await
+        .unwrap()
+        .public_key();
+    let store = Arc::new(MemoryPinStore::new());
+    let pinned = || {
+        let client = testnet
+            .client_builder()
+            .pin_records(store.clone(), PinPolicy::Strict)
+            .build()
+            .unwrap();
+        Pubky::with_client(client).public_storage()
+    };
+
+    let keypair = Keypair::random();
+    let user = keypair.public_key();
+    let path = format!("{user}/pub/app/file.txt");
+    let signer = testnet.sdk().unwrap().signer(keypair);
+    let session = signer.signup_cookie(&genuine, None).await.unwrap();
+    session
+        .storage()
+        .put("/pub/app/file.txt", "genuine")
+        .await
+        .unwrap();
+
+    // The first read pins the genuine homeserver.
+    let body = pinned()
+        .get(path.as_str())
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert_eq!(body, "genuine");
+
+    // Someone holding the key moves the record to another homeserver.
+    signer
+        .pkdns()
+        .publish_homeserver_force(Some(&hijacked))
+        .await
+        .unwrap();
+
+    let err = pinned().get(path.as_str()).await.unwrap_err();
+    let Error::RecordChanged(change) = err else {
+        panic!("expected a record change, got {err:?}");
+    };
+    assert_eq!((change.old, change.new), (genuine, hijacked));
 }
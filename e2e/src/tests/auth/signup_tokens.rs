@@ -317,3 +317,66 @@ fn assert_signup_rejected(error: Error, expected_status: StatusCode, expected_me
         error => panic!("expected a homeserver signup error, got {error:?}"),
     }
 }
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn signup_policy_switched_at_runtime() {
+    let mut config = ConfigToml::default_test_config();
+    config.general.signup_mode = SignupMode::Open;
+
+    let testnet = EphemeralTestnet::builder()
+        .config(config)
+        .build()
+        .await
+        .unwrap();
+
+    let server = testnet.homeserver_app();
+    let admin = server
+        .admin_server()
+        .expect("admin server should be enabled");
+    let pubky = testnet.sdk().unwrap();
+
+    // Close signups: new users are rejected, existing ones can still sign in.
+    let existing = pubky.signer(Keypair::random());
+    existing.signup(&server.public_key(), None).await.unwrap();
+    assert_eq!(admin.get_signup_policy().await.unwrap(), SignupMode::Open);
+    admin.set_signup_policy(SignupMode::Closed).await.unwrap();
+    assert_eq!(admin.get_signup_policy().await.unwrap(), SignupMode::Closed);
+
+    let err = pubky
+        .signer(Keypair::random())
+        .signup(&server.public_key(), None)
+        .await
+        .expect_err("Signup should fail while signups are closed");
+    assert!(err.to_string().contains("403"));
+    assert!(err.to_string().contains("Signups are closed"));
+    existing
+        .signin(ClientId::new("signup.policy.test").unwrap())
+        .await
+        .unwrap();
+
+    // Require tokens: a generated token lets a new user in.
+    admin
+        .set_signup_policy(SignupMode::TokenRequired)
+        .await
+        .unwrap();
+    let signer = pubky.signer(Keypair::random());
+    signer
+        .signup(&server.public_key(), None)
+        .await
+        .expect_err("Signup should require a token");
+    let token = admin.create_signup_token().await.unwrap();
+    signer
+        .signup(&server.public_key(), Some(&token))
+        .await
+        .unwrap();
+
+    // Clearing the policy reopens signups, as configured.
+    admin.clear_signup_policy().await.unwrap();
+    assert_eq!(admin.get_signup_policy().await.unwrap(), SignupMode::Open);
+    pubky
+        .signer(Keypair::random())
+        .signup(&server.public_key(), None)
+        .await
+        .unwrap();
+}
@@ -1,7 +1,7 @@
 use super::*;
 use futures::StreamExt;
 use pubky_testnet::pubky::errors::{Error, RequestError};
-use pubky_testnet::pubky::{ClientId, EventCursor, PubkySession, PublicKey};
+use pubky_testnet::pubky::{ClientId, EventCursor, PubkySession, PublicKey, StreamEvent};
 use tokio::time::{timeout, Duration};
 
 /// Sign up a fresh user and return its public key plus an authenticated
@@ -586,3 +586,109 @@ async fn events_stream_sdk_cookie_live_private_receives_in_scope() {
         event.resource.path
     );
 }
+
+/// Every streamed event carries a homeserver signature that verifies against the
+/// homeserver key, and PUT events hash to the stored content.
+#[tokio::test]
+#[pubky_testnet::test]
+async fn events_stream_sdk_events_are_signed_by_homeserver() {
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app();
+    let pubky = testnet.sdk().unwrap();
+    let (user, session) = signed_in_user(&testnet, "signed.events").await;
+
+    session
+        .storage()
+        .put("/pub/signed.txt", "signed content")
+        .await
+        .unwrap();
+    session.storage().delete("/pub/signed.txt").await.unwrap();
+
+    let stream = pubky
+        .event_stream_for_user(&user, None)
+        .limit(2)
+        .subscribe()
+        .await
+        .unwrap();
+    let events: Vec<_> = stream.map(Result::unwrap).collect().await;
+    assert_eq!(events.len(), 2);
+
+    for event in &events {
+        event.verify(&server.public_key()).unwrap();
+        event.verify(&user).unwrap_err();
+    }
+    assert!(events[0].content_matches(b"signed content"));
+    assert!(!events[1].content_matches(b"signed content"));
+}
+
+/// Each event names its user's previous event matching the subscription, so a resumed
+/// gap-detecting stream sees no gaps even though global cursors are not consecutive.
+#[tokio::test]
+#[pubky_testnet::test]
+async fn events_stream_sdk_prev_cursor_chains_user_events() {
+    let testnet = build_full_testnet().await;
+    let pubky = testnet.sdk().unwrap();
+    let (user, session) = signed_in_user(&testnet, "gaps.events").await;
+    let (_, other) = signed_in_user(&testnet, "gaps.other").await;
+
+    for name in ["a", "b", "c"] {
+        session
+            .storage()
+            .put(format!("/pub/app/{name}.txt"), name)
+            .await
+            .unwrap();
+        // Unrelated events between them keep the user's cursors apart.
+        session
+            .storage()
+            .put("/pub/other/x.txt", name)
+            .await
+            .unwrap();
+        other.storage().put("/pub/app/y.txt", name).await.unwrap();
+    }
+
+    let events: Vec<_> = pubky
+        .event_stream_for_user(&user, None)
+        .path("/pub/app/")
+        .subscribe()
+        .await
+        .unwrap()
+        .map(Result::unwrap)
+        .collect()
+        .await;
+    assert_eq!(events.len(), 3);
+    assert_eq!(events[0].prev_cursor, None);
+    for pair in events.windows(2) {
+        assert_eq!(pair[1].prev_cursor, Some(pair[0].cursor));
+        assert!(pair[1].cursor.id() > pair[0].cursor.id() + 1);
+    }
+
+    let items: Vec<_> = pubky
+        .event_stream_for_user(&user, Some(events[0].cursor))
+        .path("/pub/app/")
+        .subscribe_with_gaps()
+        .await
+        .unwrap()
+        .map(Result::unwrap)
+        .collect()
+        .await;
+    let cursors: Vec<EventCursor> = items
+        .iter()
+        .map(|item| match item {
+            StreamEvent::Event(event) => event.cursor,
+            StreamEvent::Gap { .. } => panic!("unexpected gap: {item:?}"),
+        })
+        .collect();
+    assert_eq!(cursors, vec![events[1].cursor, events[2].cursor]);
+
+    let err = pubky
+        .event_stream_for_user(&user, None)
+        .reverse()
+        .subscribe_with_gaps()
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(
+        err,
+        Error::Request(RequestError::Validation { .. })
+    ));
+}
@@ -1,5 +1,8 @@
 use super::build_full_testnet;
-use pubky_testnet::pubky::Method;
+use pubky_testnet::pubky::{
+    errors::RequestError, Error, HttpVersion, IpVersionPreference, Keypair, Method,
+    ResolutionSource, SemanticVersion, StatusCode,
+};
 
 #[tokio::test]
 #[pubky_testnet::test]
@@ -19,6 +22,69 @@ async fn http_get_pubky() {
     assert_eq!(response.status(), 200)
 }
 
+#[tokio::test]
+#[pubky_testnet::test]
+async fn http_version_is_configurable() {
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app();
+    let pubky_url = format!("https://{}/", server.public_key().z32());
+
+    for (version, expected) in [
+        // The homeserver does not negotiate ALPN, so `Auto` falls back to HTTP/1.1.
+        (HttpVersion::Auto, "HTTP/1.1"),
+        (HttpVersion::Http1, "HTTP/1.1"),
+        (HttpVersion::Http2, "HTTP/2.0"),
+    ] {
+        let client = testnet
+            .client_builder()
+            .http_version(version)
+            .http2_keep_alive_interval(std::time::Duration::from_secs(10))
+            .build()
+            .unwrap();
+
+        let response = client
+            .request(Method::GET, &pubky_url)
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), 200);
+        assert_eq!(format!("{:?}", response.version()), expected, "{version:?}");
+    }
+}
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn ip_version_preference_restricts_connections() {
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app();
+    let pubky_url = format!("https://{}/", server.public_key().z32());
+
+    let client = |preference| {
+        testnet
+            .client_builder()
+            .ip_version_preference(preference)
+            .build()
+            .unwrap()
+    };
+
+    // The testnet homeserver only publishes an IPv4 address.
+    for preference in [IpVersionPreference::V4Only, IpVersionPreference::PreferV6] {
+        let response = client(preference)
+            .request(Method::GET, &pubky_url)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200, "{preference:?}");
+    }
+
+    let result = client(IpVersionPreference::V6Only)
+        .request(Method::GET, &pubky_url)
+        .send()
+        .await;
+    assert!(result.is_err());
+}
+
 #[tokio::test]
 #[pubky_testnet::test]
 async fn http_get_icann() {
@@ -36,3 +102,200 @@ async fn http_get_icann() {
 
     assert_eq!(response.status(), 200);
 }
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn homeserver_version_is_announced() {
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app();
+    let pubky = testnet.sdk().unwrap();
+
+    // The homeserver shares the workspace version with this crate.
+    let expected = SemanticVersion::parse(env!("CARGO_PKG_VERSION"));
+    assert!(expected.is_some());
+    assert_eq!(
+        pubky.homeserver_version(&server.public_key()).await,
+        expected
+    );
+
+    assert_eq!(
+        pubky.check_homeserver(&server.public_key()).await.ok(),
+        expected
+    );
+
+    // A key without a homeserver has no version rather than an error.
+    let unknown = Keypair::random().public_key();
+    assert_eq!(pubky.homeserver_version(&unknown).await, None);
+    assert!(matches!(
+        pubky.check_homeserver(&unknown).await,
+        Err(Error::NotAHomeserver { .. })
+    ));
+}
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn check_homeserver_propagates_unreachable_servers() {
+    use pubky_testnet::pubky::pkarr::{self, dns::rdata::SVCB};
+
+    let testnet = build_full_testnet().await;
+    let pubky = testnet.sdk().unwrap();
+    let client = testnet.client().unwrap();
+
+    // A key whose record points at a port nobody listens on.
+    let closed_port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let keypair = Keypair::random();
+    let mut svcb = SVCB::new(1, ".".try_into().unwrap());
+    svcb.set_port(closed_port);
+    svcb.set_ipv4hint(&[std::net::Ipv4Addr::LOCALHOST.to_bits()]);
+    let packet = pkarr::SignedPacket::builder()
+        .https(".".try_into().unwrap(), svcb, 60)
+        .sign(&keypair)
+        .unwrap();
+    client.pkarr().publish(&packet).await.unwrap();
+
+    // Failing to connect says nothing about what the key is.
+    let err = pubky
+        .check_homeserver(&keypair.public_key())
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, Error::HomeserverUnreachable { .. }),
+        "expected HomeserverUnreachable, got {err:?}"
+    );
+}
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn pubky_fetch() {
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app();
+    let pubky = testnet.sdk().unwrap();
+
+    let session = pubky
+        .signer(Keypair::random())
+        .signup_cookie(&server.public_key(), None)
+        .await
+        .unwrap();
+    let user = session.info().public_key().clone();
+    session
+        .storage()
+        .put("/pub/app/hello.txt", "hello")
+        .await
+        .unwrap();
+
+    for url in [
+        format!("pubky://{}/pub/app/hello.txt", user.z32()),
+        format!("{user}/pub/app/hello.txt"),
+        format!("https://_pubky.{}/pub/app/hello.txt", user.z32()),
+    ] {
+        // Repeated fetches reuse the cached homeserver resolution.
+        for _ in 0..2 {
+            let body = pubky.fetch(&url).await.unwrap().text().await.unwrap();
+            assert_eq!(body, "hello", "fetching {url}");
+        }
+    }
+
+    let err = pubky
+        .fetch(&format!("pubky://{}/pub/app/missing.txt", user.z32()))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Request(RequestError::Server { status, .. }) if status == StatusCode::NOT_FOUND
+    ));
+    assert!(pubky.fetch("pubky://not-a-key/pub/x").await.is_err());
+}
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn fetch_via_domain_uses_the_homeserver_domain() {
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app();
+    let pubky = testnet.sdk().unwrap();
+
+    let session = pubky
+        .signer(Keypair::random())
+        .signup_cookie(&server.public_key(), None)
+        .await
+        .unwrap();
+    let user = session.info().public_key().clone();
+    session
+        .storage()
+        .put("/pub/app/hello.txt", "hello")
+        .await
+        .unwrap();
+
+    // The testnet homeserver publishes `localhost` as its ICANN domain.
+    let addr = format!("{user}/pub/app/hello.txt");
+    let body = pubky
+        .fetch_via_domain("localhost", &addr)
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert_eq!(body, "hello");
+
+    // Domains the homeserver does not publish are rejected before connecting.
+    let err = pubky
+        .fetch_via_domain("example.com", &addr)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Request(RequestError::Validation { .. })
+    ));
+}
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn prewarm_populates_resolution_cache() {
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app();
+
+    let session = testnet
+        .sdk()
+        .unwrap()
+        .signer(Keypair::random())
+        .signup_cookie(&server.public_key(), None)
+        .await
+        .unwrap();
+    let user = session.info().public_key().clone();
+    session
+        .storage()
+        .put("/pub/app/hello.txt", "hello")
+        .await
+        .unwrap();
+
+    // A fresh client starts with empty caches.
+    let pubky = testnet.sdk().unwrap();
+    assert!(pubky.client().last_resolution_source().is_none());
+
+    // Fire-and-forget: duplicates are resolved once, and nothing is returned to await.
+    pubky.prewarm_and_connect(&[user.clone(), user.clone()]);
+    tokio::time::timeout(std::time::Duration::from_secs(10), async {
+        while pubky.client().last_resolution_source().is_none() {
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .expect("prewarm should resolve the user in the background");
+
+    let body = pubky
+        .fetch(&format!("pubky://{}/pub/app/hello.txt", user.z32()))
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert_eq!(body, "hello");
+    let info = pubky.client().last_resolution_source().unwrap();
+    assert_eq!(info.source, ResolutionSource::Cache);
+
+    // Users without a homeserver are skipped without surfacing an error.
+    pubky.prewarm(&[Keypair::random().public_key()]);
+}
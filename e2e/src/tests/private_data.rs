@@ -5,8 +5,8 @@
 
 use super::build_full_testnet;
 use pubky_testnet::pubky::{
-    AuthFlowKind, ClientId, IntoPubkyResource, Keypair, Method, PubkyGrantAuthFlow,
-    PubkyHttpClient, PubkySession, PubkySigner, StatusCode,
+    errors::RequestError, AuthFlowKind, ClientId, Error, IntoPubkyResource, Keypair, Method,
+    PubkyGrantAuthFlow, PubkyHttpClient, PubkySession, PubkySigner, StatusCode,
 };
 use pubky_testnet::pubky_common::capabilities::Capabilities;
 use pubky_testnet::pubky_common::crypto::PublicKey;
@@ -194,3 +194,37 @@ async fn cross_tenant_priv_access_is_forbidden() {
 
     assert_all_verbs_denied(pubky.client(), &owner, Some(&token), StatusCode::FORBIDDEN).await;
 }
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn write_only_priv_session_cannot_read_back() {
+    // A session with write but not read access to a private path may store data
+    // there (e.g. a drop box) but gets a 403 when reading it back.
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app();
+    let pubky = testnet.sdk().unwrap();
+
+    let signer = pubky.signer(Keypair::random());
+    signer.signup(&server.public_key(), None).await.unwrap();
+
+    let writer = grant_session(
+        &testnet,
+        &signer,
+        Capabilities::builder().write(DIR).finish(),
+    )
+    .await;
+    assert!(writer.info().can_write(SECRET));
+    assert!(!writer.info().can_read(SECRET));
+
+    writer.storage().put(SECRET, vec![1, 2, 3]).await.unwrap();
+
+    let err = writer.storage().get(SECRET).await.unwrap_err();
+    assert!(
+        matches!(
+            err,
+            Error::Request(RequestError::Server { status, .. }) if status == StatusCode::FORBIDDEN
+        ),
+        "got: {err:?}"
+    );
+    assert!(writer.storage().list(DIR).unwrap().send().await.is_err());
+}
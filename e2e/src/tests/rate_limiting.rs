@@ -1,8 +1,9 @@
 use std::time::{Duration, Instant};
 
 use pubky_testnet::pubky::{
-    errors::RequestError, Error, Keypair, Method, PubkySession, StatusCode,
+    errors::RequestError, Capability, Error, Keypair, Method, PubkySession, StatusCode,
 };
+use pubky_testnet::pubky_common::auth::AuthToken;
 use pubky_testnet::{
     pubky_homeserver::{
         quota_config::{GlobPattern, HttpMethod, LimitKey, LimitKeyType, PathLimit},
@@ -72,9 +73,10 @@ async fn test_limit_signin_get_session() {
         .signin_cookie()
         .await
         .expect_err("Second signin should be rate limited");
-    assert!(
-        matches!(err, Error::Request(RequestError::Server { status, .. }) if status == StatusCode::TOO_MANY_REQUESTS)
-    );
+    assert!(matches!(
+        err,
+        Error::Request(RequestError::TooManyAttempts { .. })
+    ));
 }
 
 #[tokio::test]
@@ -172,9 +174,10 @@ async fn test_limit_events() {
     let res = client.request(Method::GET, &url).send().await.unwrap();
     assert_eq!(res.status(), StatusCode::OK);
 
-    // Second request should be rate-limited
+    // Second request should be rate-limited, telling the client when to retry
     let res = client.request(Method::GET, &url).send().await.unwrap();
     assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(res.headers().contains_key("retry-after"));
 }
 
 #[tokio::test]
@@ -205,8 +208,7 @@ async fn test_limit_upload() {
     let body = vec![0u8; 3 * 1024];
 
     let start = Instant::now();
-    let resp = session.storage().put(path, body).await.unwrap();
-    assert_eq!(resp.status(), StatusCode::CREATED);
+    session.storage().put(path, body).await.unwrap();
     assert!(
         start.elapsed() > Duration::from_secs(2),
         "Upload should be throttled to ~1KB/s (elapsed {:?})",
@@ -343,3 +345,45 @@ async fn test_limit_signup_tokens() {
     let res = client.request(Method::GET, &url).send().await.unwrap();
     assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
 }
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn test_failed_auth_attempts_lockout() {
+    let testnet = EphemeralTestnet::builder().build().await.unwrap();
+    let server = testnet.homeserver_app();
+    let pubky = testnet.sdk().unwrap();
+    let client = pubky.client();
+
+    let url = format!("https://{}/session", server.public_key().z32());
+    let mut forged = AuthToken::sign(&Keypair::random(), vec![Capability::root()]).serialize();
+    forged[0] ^= 1;
+
+    // Failed signins from this IP, enough to trigger the first lockout.
+    for _ in 0..6 {
+        let res = client
+            .request(Method::POST, &url)
+            .body(forged.clone())
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+
+    // Signup from the same IP is locked out with a typed error.
+    let signer = pubky.signer(Keypair::random());
+    let err = signer
+        .signup_cookie(&server.public_key(), None)
+        .await
+        .expect_err("signup should be locked out");
+    let Error::Request(RequestError::TooManyAttempts { retry_after }) = err else {
+        panic!("expected TooManyAttempts, got {err:?}");
+    };
+    assert_eq!(retry_after, Duration::from_secs(1));
+
+    // Once the lockout expires the signup goes through.
+    tokio::time::sleep(retry_after).await;
+    signer
+        .signup_cookie(&server.public_key(), None)
+        .await
+        .unwrap();
+}
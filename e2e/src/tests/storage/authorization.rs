@@ -41,12 +41,11 @@ async fn unauthorized_put_delete() {
     assert!(matches!(response.status(), StatusCode::UNAUTHORIZED));
 
     // Owner writes successfully
-    let resp = owner_session
+    owner_session
         .storage()
         .put(path, vec![0, 1, 2, 3, 4])
         .await
         .unwrap();
-    assert!(resp.status().is_success());
 
     // Other tries to delete owner's file → 401 Unauthorized
     let response = pubky
@@ -90,8 +89,7 @@ async fn priv_owner_with_cap_is_authorized() {
     let content = vec![1, 2, 3];
 
     // Write, then read back the same bytes.
-    let resp = session.storage().put(path, content.clone()).await.unwrap();
-    assert_eq!(resp.status(), StatusCode::CREATED);
+    session.storage().put(path, content.clone()).await.unwrap();
     let body = session
         .storage()
         .get(path)
@@ -128,4 +126,99 @@ async fn priv_owner_with_cap_is_authorized() {
     assert!(
         matches!(err, Error::Request(RequestError::Server { status, .. }) if status == StatusCode::NOT_FOUND)
     );
+    // Unauthenticated public storage refuses private paths before any request is sent.
+    let addr = format!("{}/priv/app/", session.info().public_key());
+    let err = pubky.public_storage().get(&addr).await.unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Request(RequestError::Validation { .. })
+    ));
+    assert!(pubky.public_storage().list(&addr).is_err());
+}
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn scoped_session_enforces_the_narrower_capabilities() {
+    use pubky_testnet::pubky::{errors::AuthError, Capabilities};
+
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app();
+    let pubky = testnet.sdk().unwrap();
+
+    let owner = pubky.signer(Keypair::random());
+    let session = owner
+        .signup_cookie(&server.public_key(), None)
+        .await
+        .unwrap();
+    session
+        .storage()
+        .put("/priv/other/secret.txt", "secret")
+        .await
+        .unwrap();
+
+    let caps = Capabilities::builder()
+        .read_write("/priv/plugin/")
+        .write("/pub/plugin/")
+        .finish();
+    let plugin = session.scoped(caps.clone()).unwrap();
+    assert_eq!(plugin.public_key(), session.public_key());
+    assert_eq!(plugin.info().capabilities(), caps.iter().as_slice());
+
+    // Inside the scope the shared credential is attached as usual.
+    let storage = plugin.storage();
+    storage.put("/priv/plugin/state", "42").await.unwrap();
+    storage.put("/pub/plugin/post", "hi").await.unwrap();
+    let state = storage
+        .get("/priv/plugin/state")
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert_eq!(state, "42");
+    assert_eq!(
+        storage
+            .list("/priv/plugin/")
+            .unwrap()
+            .send()
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+
+    // The full session could do all of this; the scoped view refuses before sending.
+    let forbidden = |err: Error| matches!(err, Error::Request(RequestError::Forbidden { .. }));
+    assert!(forbidden(
+        storage.put("/pub/other/post", "hi").await.unwrap_err()
+    ));
+    assert!(forbidden(
+        storage.get("/priv/other/secret.txt").await.unwrap_err()
+    ));
+    assert!(forbidden(
+        storage
+            .metadata("/priv/other/secret.txt")
+            .await
+            .unwrap_err()
+    ));
+    assert!(forbidden(storage.list("/priv/other/").unwrap_err()));
+    // Public data stays readable.
+    storage.get("/pub/plugin/post").await.unwrap();
+
+    // A scope can only narrow.
+    let wider = Capabilities::builder().read("/pub/").finish();
+    assert!(matches!(
+        plugin.scoped(wider).unwrap_err(),
+        Error::Authentication(AuthError::Validation(_))
+    ));
+    let narrower = plugin
+        .scoped(Capabilities::builder().read("/priv/plugin/").finish())
+        .unwrap();
+    assert!(forbidden(
+        narrower
+            .storage()
+            .put("/priv/plugin/state", "43")
+            .await
+            .unwrap_err()
+    ));
 }
@@ -288,3 +288,228 @@ async fn list_shallow() {
         );
     }
 }
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn list_snapshot_pagination() {
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app();
+    let pubky = testnet.sdk().unwrap();
+
+    let owner = pubky.signer(Keypair::random());
+    let owner_session = owner
+        .signup_cookie(&server.public_key(), None)
+        .await
+        .unwrap();
+    let public_key = owner_session.public_key();
+    let storage = owner_session.storage();
+    for name in ["a", "b", "c", "d"] {
+        storage
+            .put(format!("/pub/example.com/{name}.txt"), vec![0])
+            .await
+            .unwrap();
+    }
+
+    let url = "/pub/example.com/";
+    let first = storage
+        .list(url)
+        .unwrap()
+        .limit(2)
+        .snapshot()
+        .send_page()
+        .await
+        .unwrap();
+    let token = first.snapshot.clone().expect("snapshot token");
+
+    // Concurrent writes after the first page must not perturb the iteration.
+    storage.delete("/pub/example.com/c.txt").await.unwrap();
+    storage
+        .put("/pub/example.com/bb.txt", vec![0])
+        .await
+        .unwrap();
+
+    let second = storage
+        .list(url)
+        .unwrap()
+        .limit(2)
+        .resume_snapshot(&token)
+        .cursor(&first.next_cursor().unwrap())
+        .send_page()
+        .await
+        .unwrap();
+    assert_eq!(second.snapshot.as_deref(), Some(token.as_str()));
+
+    let all: Vec<_> = first.entries.into_iter().chain(second.entries).collect();
+    assert_eq!(
+        all,
+        ["a", "b", "c", "d"]
+            .iter()
+            .map(|name| format!("{public_key}/pub/example.com/{name}.txt")
+                .parse()
+                .unwrap())
+            .collect::<Vec<_>>()
+    );
+
+    let err = storage
+        .list(url)
+        .unwrap()
+        .resume_snapshot("expired")
+        .send_page()
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, Error::Request(RequestError::SnapshotExpired)),
+        "got: {err:?}"
+    );
+}
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn dir_stats() {
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app();
+    let pubky = testnet.sdk().unwrap();
+
+    let owner = pubky.signer(Keypair::random());
+    let owner_session = owner
+        .signup_cookie(&server.public_key(), None)
+        .await
+        .unwrap();
+    let storage = owner_session.storage();
+    storage
+        .put("/pub/example.com/a.txt", vec![0; 10])
+        .await
+        .unwrap();
+    storage
+        .put("/pub/example.com/b.txt", vec![0; 20])
+        .await
+        .unwrap();
+    storage
+        .put("/pub/example.com/nested/c.txt", vec![0; 30])
+        .await
+        .unwrap();
+
+    let deep = storage
+        .dir_stats("/pub/example.com/", false)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(deep.file_count, 3);
+    assert_eq!(deep.total_bytes, 60);
+    assert!(deep.oldest_modified.unwrap() <= deep.newest_modified.unwrap());
+
+    let shallow = pubky
+        .public_storage()
+        .dir_stats(
+            format!("{}/pub/example.com/", owner_session.public_key()),
+            true,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(shallow.file_count, 2);
+    assert_eq!(shallow.total_bytes, 30);
+
+    assert_eq!(
+        storage.dir_stats("/pub/missing/", false).await.unwrap(),
+        None
+    );
+    assert!(storage.dir_stats("/pub/example.com", false).await.is_err());
+}
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn list_across_users_merges_and_reports_failures_inline() {
+    use futures::StreamExt;
+    use std::collections::HashMap;
+
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app();
+    let pubky = testnet.sdk().unwrap();
+
+    // Enough files for one user to span several pages.
+    let mut expected = HashMap::new();
+    let mut users = Vec::new();
+    for count in [1usize, 3, 120] {
+        let session = pubky
+            .signer(Keypair::random())
+            .signup_cookie(&server.public_key(), None)
+            .await
+            .unwrap();
+        for i in 0..count {
+            session
+                .storage()
+                .put(format!("/pub/social/posts/{i:03}"), vec![0])
+                .await
+                .unwrap();
+        }
+        // Outside the listed prefix.
+        session
+            .storage()
+            .put("/pub/social/profile", vec![0])
+            .await
+            .unwrap();
+        expected.insert(session.public_key(), count);
+        users.push(session.public_key());
+    }
+    let missing = Keypair::random().public_key();
+    users.push(missing.clone());
+
+    let results: Vec<_> = pubky
+        .list_across_users(users, "/pub/social/posts/")
+        .unwrap()
+        .collect()
+        .await;
+
+    let mut listed = HashMap::new();
+    let mut failed = Vec::new();
+    for (user, entry) in results {
+        match entry {
+            Ok(entry) => {
+                assert_eq!(entry.owner, user);
+                assert!(entry.path.as_str().starts_with("/pub/social/posts/"));
+                *listed.entry(user).or_insert(0) += 1;
+            }
+            Err(_) => failed.push(user),
+        }
+    }
+    assert_eq!(listed, expected);
+    assert_eq!(failed, vec![missing]);
+
+    assert!(pubky.list_across_users([], "/pub/social/posts").is_err());
+    assert!(pubky.list_across_users([], "/priv/social/").is_err());
+}
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn diff_previews_a_one_way_sync() {
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app();
+    let pubky = testnet.sdk().unwrap();
+
+    let session = pubky
+        .signer(Keypair::random())
+        .signup_cookie(&server.public_key(), None)
+        .await
+        .unwrap();
+    let storage = session.storage();
+    storage
+        .put("/pub/site/index.html", "<p>hi</p>")
+        .await
+        .unwrap();
+    storage.put("/pub/site/css/app.css", "a{}").await.unwrap();
+    storage.put("/pub/site/old.html", "old").await.unwrap();
+
+    let local = std::env::temp_dir().join(format!("pubky-e2e-diff-{}", session.public_key().z32()));
+    std::fs::create_dir_all(local.join("css")).unwrap();
+    std::fs::write(local.join("index.html"), "<p>hi</p>").unwrap();
+    std::fs::write(local.join("css/app.css"), "b{}").unwrap();
+    std::fs::write(local.join("new.html"), "new").unwrap();
+
+    let report = storage.diff(&local, "/pub/site/").await.unwrap();
+    assert_eq!(report.added, vec!["new.html"]);
+    assert_eq!(report.removed, vec!["old.html"]);
+    assert_eq!(report.modified, vec!["css/app.css"]);
+
+    std::fs::remove_dir_all(&local).unwrap();
+}
@@ -31,13 +31,20 @@ async fn put_get_delete() {
     // relative URL is always based over own user homeserver
     let path = "/pub/foo.txt";
 
-    session
+    let written = session
         .storage()
         .put(path, vec![0, 1, 2, 3, 4])
         .await
-        .unwrap()
-        .error_for_status()
         .unwrap();
+    assert_eq!(
+        written.url.as_str(),
+        format!("pubky://{}/pub/foo.txt", public_key.z32())
+    );
+    assert_eq!(
+        written.content_hash,
+        pubky_testnet::pubky_common::crypto::hash(&[0, 1, 2, 3, 4])
+    );
+    assert_eq!(written.size, 5);
 
     // Use Pubky native method to get data from homeserver
     let response = pubky
@@ -90,6 +97,131 @@ async fn put_get_delete() {
     assert!(session.storage().get(path).await.is_err());
 }
 
+#[tokio::test]
+#[pubky_testnet::test]
+async fn put_stream_hashed_reports_the_uploaded_hash() {
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app();
+    let pubky = testnet.sdk().unwrap();
+    let session = pubky
+        .signer(Keypair::random())
+        .signup_into_session(
+            &server.public_key(),
+            ClientId::new("test.app").unwrap(),
+            SignupOptions::default(),
+        )
+        .await
+        .unwrap();
+
+    // Several stream chunks worth of data.
+    let data: Vec<u8> = (0..300 * 1024).map(|i| (i % 251) as u8).collect();
+    let path = "/pub/test.app/stream.bin";
+    let stored = session
+        .storage()
+        .put_stream_hashed(path, std::io::Cursor::new(data.clone()))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        stored.url.as_str(),
+        format!(
+            "pubky://{}/pub/test.app/stream.bin",
+            session.public_key().z32()
+        )
+    );
+    assert_eq!(
+        stored.content_hash,
+        pubky_testnet::pubky_common::crypto::hash(&data)
+    );
+    assert_eq!(stored.size, data.len() as u64);
+
+    let stats = session.storage().stats(path).await.unwrap().unwrap();
+    let etag = base64::engine::general_purpose::STANDARD.encode(stored.content_hash.as_bytes());
+    assert_eq!(stats.etag, Some(etag));
+    let body = session
+        .storage()
+        .get(path)
+        .await
+        .unwrap()
+        .bytes()
+        .await
+        .unwrap();
+    assert_eq!(body, Bytes::from(data));
+}
+
+/// Reader that yields `data` and then fails.
+struct FailingReader(std::io::Cursor<Vec<u8>>);
+
+impl tokio::io::AsyncRead for FailingReader {
+    fn poll_read(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        if self.0.position() < self.0.get_ref().len() as u64 {
+            return std::pin::Pin::new(&mut self.0).poll_read(cx, buf);
+        }
+        std::task::Poll::Ready(Err(std::io::Error::other("source went away")))
+    }
+}
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn put_tee_uploads_and_caches_in_one_pass() {
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app();
+    let pubky = testnet.sdk().unwrap();
+    let session = pubky
+        .signer(Keypair::random())
+        .signup_cookie(&server.public_key(), None)
+        .await
+        .unwrap();
+    let storage = session.storage();
+
+    let dir = std::env::temp_dir().join(format!("pubky-e2e-tee-{}", session.public_key().z32()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let local = dir.join("photo.bin");
+    let partial = dir.join("photo.bin.partial");
+
+    let data: Vec<u8> = (0..200 * 1024).map(|i| (i % 251) as u8).collect();
+    let stored = storage
+        .put_tee(
+            "/pub/test.app/photo.bin",
+            std::io::Cursor::new(data.clone()),
+            &local,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        stored.content_hash,
+        pubky_testnet::pubky_common::crypto::hash(&data)
+    );
+    assert_eq!(std::fs::read(&local).unwrap(), data);
+    let remote = storage.get("/pub/test.app/photo.bin").await.unwrap();
+    assert_eq!(remote.bytes().await.unwrap(), Bytes::from(data.clone()));
+
+    // A failing source aborts the upload and keeps the previous local copy intact.
+    let failing = FailingReader(std::io::Cursor::new(vec![7; 100 * 1024]));
+    storage
+        .put_tee("/pub/test.app/other.bin", failing, &local)
+        .await
+        .unwrap_err();
+    assert_eq!(std::fs::read(&local).unwrap(), data);
+    assert!(!partial.exists());
+    assert!(!storage.exists("/pub/test.app/other.bin").await.unwrap());
+
+    // So does a rejected upload.
+    let fresh = dir.join("rejected.bin");
+    storage
+        .put_tee("/pub/test.app/", std::io::Cursor::new(data), &fresh)
+        .await
+        .unwrap_err();
+    assert!(!fresh.exists());
+    assert!(!dir.join("rejected.bin.partial").exists());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
 #[tokio::test]
 #[pubky_testnet::test]
 async fn path_collisions_return_conflict_and_recover_after_delete() {
@@ -269,6 +401,112 @@ async fn put_then_get_json_roundtrip() {
         .error_for_status()
         .unwrap();
 }
+#[tokio::test]
+#[pubky_testnet::test]
+async fn put_with_backup_replaces_and_cleans_up() {
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app();
+    let pubky = testnet.sdk().unwrap();
+
+    let session = pubky
+        .signer(Keypair::random())
+        .signup_cookie(&server.public_key(), None)
+        .await
+        .unwrap();
+    let storage = session.storage();
+
+    let path = "/pub/my-app/config.json";
+    let backup = "/pub/my-app/config.json.bak";
+
+    // First write: nothing to back up.
+    storage
+        .put_with_backup(path, r#"{"v":1}"#, ".bak")
+        .await
+        .unwrap();
+    let body = storage.get(path).await.unwrap().text().await.unwrap();
+    assert_eq!(body, r#"{"v":1}"#);
+    assert!(!storage.exists(backup).await.unwrap());
+
+    // Replacing keeps the resource and removes the backup afterwards.
+    storage
+        .put_with_backup(path, r#"{"v":2}"#, ".bak")
+        .await
+        .unwrap();
+    let body = storage.get(path).await.unwrap().text().await.unwrap();
+    assert_eq!(body, r#"{"v":2}"#);
+    assert!(!storage.exists(backup).await.unwrap());
+
+    // Invalid suffixes and directories are rejected before any request.
+    for (path, suffix) in [(path, ""), (path, "/bak"), ("/pub/my-app/", ".bak")] {
+        let err = storage
+            .put_with_backup(path, "x", suffix)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, Error::Request(RequestError::Validation { .. })),
+            "expected a validation error, got {err:?}"
+        );
+    }
+}
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn put_with_ttl_expires() {
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app();
+    let pubky = testnet.sdk().unwrap();
+
+    let session = pubky
+        .signer(Keypair::random())
+        .signup_cookie(&server.public_key(), None)
+        .await
+        .unwrap();
+    let storage = session.storage();
+
+    let expiring = "/pub/my-app/status.txt";
+    let renewed = "/pub/my-app/renewed.txt";
+    let ttl = std::time::Duration::from_secs(5);
+    storage.put_with_ttl(expiring, "online", ttl).await.unwrap();
+    storage.put_with_ttl(renewed, "online", ttl).await.unwrap();
+    // A plain overwrite drops the expiry.
+    storage.put(renewed, "forever").await.unwrap();
+
+    // Before the deadline the file reads normally.
+    let body = storage.get(expiring).await.unwrap().text().await.unwrap();
+    assert_eq!(body, "online");
+    assert_eq!(
+        storage
+            .list("/pub/my-app/")
+            .unwrap()
+            .send()
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+
+    tokio::time::sleep(ttl + std::time::Duration::from_secs(1)).await;
+
+    // After the deadline it is gone.
+    let err = storage.get(expiring).await.unwrap_err();
+    assert_server_status(err, StatusCode::NOT_FOUND);
+    assert!(!storage.exists(expiring).await.unwrap());
+    let listed = storage.list("/pub/my-app/").unwrap().send().await.unwrap();
+    assert_eq!(listed.len(), 1);
+    assert!(listed[0].path.as_str().ends_with("renewed.txt"));
+    let body = storage.get(renewed).await.unwrap().text().await.unwrap();
+    assert_eq!(body, "forever");
+
+    let err = storage
+        .put_with_ttl(expiring, "x", std::time::Duration::ZERO)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, Error::Request(RequestError::Validation { .. })),
+        "expected a validation error, got {err:?}"
+    );
+}
+
 #[tokio::test]
 #[pubky_testnet::test]
 async fn dont_delete_shared_blobs() {
@@ -485,3 +723,635 @@ async fn write_same_path_separate_users() {
     let read_bytes_b = response.bytes().await.unwrap();
     assert_eq!(read_bytes_b, content1);
 }
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn max_response_bytes_caps_downloads() {
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app();
+    let pubky = testnet.sdk().unwrap();
+
+    let session = pubky
+        .signer(Keypair::random())
+        .signup_cookie(&server.public_key(), None)
+        .await
+        .unwrap();
+    let path = "/pub/my-app/large.bin";
+    session.storage().put(path, vec![0u8; 4_096]).await.unwrap();
+    let addr = format!("{}{path}", session.info().public_key());
+
+    let client = testnet
+        .client_builder()
+        .max_response_bytes(1_024)
+        .build()
+        .unwrap();
+    let storage = pubky_testnet::pubky::Pubky::with_client(client).public_storage();
+
+    let err = storage.get(&addr).await.unwrap_err();
+    assert!(
+        matches!(err, Error::ResponseTooLarge { limit: 1_024 }),
+        "expected ResponseTooLarge, got {err:?}"
+    );
+    // HEAD responses carry no body and are not capped.
+    assert!(storage.exists(&addr).await.unwrap());
+
+    // A per-request override lifts the cap.
+    let body = storage
+        .with_max_response_bytes(0)
+        .get(&addr)
+        .await
+        .unwrap()
+        .bytes()
+        .await
+        .unwrap();
+    assert_eq!(body.len(), 4_096);
+}
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn delete_dir_removes_the_subtree_in_one_request() {
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app();
+    let pubky = testnet.sdk().unwrap();
+
+    let session = pubky
+        .signer(Keypair::random())
+        .signup_cookie(&server.public_key(), None)
+        .await
+        .unwrap();
+    let storage = session.storage();
+    for path in [
+        "/pub/my-app/cache/a.txt",
+        "/pub/my-app/cache/nested/b.txt",
+        "/pub/my-app/keep.txt",
+    ] {
+        storage.put(path, "data").await.unwrap();
+    }
+
+    let deleted = storage.delete_dir("/pub/my-app/cache/").await.unwrap();
+    assert_eq!(deleted, 2);
+    assert!(!storage.exists("/pub/my-app/cache/a.txt").await.unwrap());
+    assert!(!storage
+        .exists("/pub/my-app/cache/nested/b.txt")
+        .await
+        .unwrap());
+    assert!(storage.exists("/pub/my-app/keep.txt").await.unwrap());
+    assert!(storage
+        .dir_stats("/pub/my-app/cache/", false)
+        .await
+        .unwrap()
+        .is_none());
+
+    // Deleting it again finds nothing left.
+    let deleted = storage.delete_dir("/pub/my-app/cache/").await.unwrap();
+    assert_eq!(deleted, 0);
+}
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn touch_bumps_the_modified_time_without_rewriting() {
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app();
+    let pubky = testnet.sdk().unwrap();
+
+    let session = pubky
+        .signer(Keypair::random())
+        .signup_cookie(&server.public_key(), None)
+        .await
+        .unwrap();
+    let storage = session.storage();
+    storage.put("/pub/my-app/presence", "online").await.unwrap();
+    let etag = storage
+        .stats("/pub/my-app/presence")
+        .await
+        .unwrap()
+        .unwrap()
+        .etag;
+    let written_at = storage
+        .dir_stats("/pub/my-app/", false)
+        .await
+        .unwrap()
+        .unwrap()
+        .newest_modified
+        .unwrap();
+
+    let since = written_at + std::time::Duration::from_micros(1);
+    let listed = |since| {
+        let storage = &storage;
+        async move {
+            storage
+                .list("/pub/my-app/")
+                .unwrap()
+                .modified_since(since)
+                .send()
+                .await
+                .unwrap()
+        }
+    };
+    assert!(listed(since).await.is_empty());
+
+    storage.touch("/pub/my-app/presence").await.unwrap();
+    let touched = listed(since).await;
+    assert_eq!(touched.len(), 1);
+    assert_eq!(touched[0].path.as_str(), "/pub/my-app/presence");
+    let stats = storage
+        .stats("/pub/my-app/presence")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stats.etag, etag);
+    let body = storage.get("/pub/my-app/presence").await.unwrap();
+    assert_eq!(body.text().await.unwrap(), "online");
+
+    // Touching never creates a file.
+    let err = storage.touch("/pub/my-app/missing").await.unwrap_err();
+    assert_server_status(err, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn concurrent_appends_never_interleave() {
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app();
+    let pubky = testnet.sdk().unwrap();
+
+    let session = pubky
+        .signer(Keypair::random())
+        .signup_cookie(&server.public_key(), None)
+        .await
+        .unwrap();
+    let storage = session.storage();
+    let path = "/pub/my-app/log.txt";
+
+    // Every writer retries at the reported length until its record is in.
+    let writers = (0..5).map(|i| {
+        let storage = storage.clone();
+        async move {
+            let record = format!("record {i}\n");
+            let mut offset = 0;
+            loop {
+                match storage.append_at(path, offset, record.clone()).await {
+                    Ok(length) => return length,
+                    Err(Error::Request(RequestError::Conflict { current_length })) => {
+                        offset = current_length;
+                    }
+                    Err(err) => panic!("append failed: {err:?}"),
+                }
+            }
+        }
+    });
+    let mut lengths = futures::future::join_all(writers).await;
+    lengths.sort();
+    assert_eq!(lengths, vec![9, 18, 27, 36, 45]);
+
+    let log = storage.get(path).await.unwrap().text().await.unwrap();
+    let mut records: Vec<_> = log.lines().collect();
+    records.sort();
+    assert_eq!(
+        records,
+        vec!["record 0", "record 1", "record 2", "record 3", "record 4"]
+    );
+
+    let err = storage.append_at(path, 9, "stale\n").await.unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Request(RequestError::Conflict { current_length: 45 })
+    ));
+}
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn export_and_import_move_an_account() {
+    use pubky::{AccountExport, ArchiveFormat, CancelToken};
+
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app();
+    let pubky = testnet.sdk().unwrap();
+    let signup = || async {
+        pubky
+            .signer(Keypair::random())
+            .signup_cookie(&server.public_key(), None)
+            .await
+            .unwrap()
+    };
+
+    let source = signup().await;
+    let files = [
+        ("/pub/my-app/a.txt", "hello"),
+        ("/pub/my-app/nested/b.json", "{}"),
+        ("/priv/my-app/secret.txt", "private"),
+    ];
+    for (path, content) in files {
+        source.storage().put(path, content).await.unwrap();
+    }
+
+    // Cancelled before the first file, then resumed into the same archive.
+    let cancel = CancelToken::new();
+    cancel.cancel();
+    let mut archive = Vec::new();
+    let partial = source
+        .export_account_cancellable(&mut archive, ArchiveFormat::Tar, None, &cancel)
+        .await
+        .unwrap();
+    assert!(partial.cancelled);
+    assert_eq!((partial.exported, partial.manifest.entries.len()), (0, 3));
+    // The export state can be rebuilt from the partial archive alone.
+    let rebuilt = AccountExport::from_archive(archive.as_slice())
+        .await
+        .unwrap();
+    assert_eq!(rebuilt, partial);
+    let export = source
+        .export_account_cancellable(
+            &mut archive,
+            ArchiveFormat::Tar,
+            Some(&rebuilt),
+            &CancelToken::new(),
+        )
+        .await
+        .unwrap();
+    assert!(!export.cancelled);
+    assert_eq!(export.exported, 3);
+
+    let target = signup().await;
+    let report = target.import_account(archive.as_slice()).await.unwrap();
+    assert_eq!(report.imported.len(), 3);
+    assert!(report.missing.is_empty() && report.rejected.is_empty());
+    for (path, content) in files {
+        let body = target.storage().get(path).await.unwrap();
+        assert_eq!(body.text().await.unwrap(), content, "{path}");
+    }
+
+    // Importing again writes nothing.
+    let report = target.import_account(archive.as_slice()).await.unwrap();
+    assert!(report.imported.is_empty());
+    assert_eq!(report.unchanged.len(), 3);
+
+    // A tampered file is rejected by the homeserver, the others are still imported.
+    let position = archive.windows(7).position(|w| w == b"private").unwrap();
+    archive[position] = b'P';
+    let other = signup().await;
+    let report = other.import_account(archive.as_slice()).await.unwrap();
+    assert_eq!(report.imported.len(), 2);
+    assert_eq!(report.rejected.len(), 1);
+    assert_eq!(report.rejected[0].path.as_str(), "/priv/my-app/secret.txt");
+    assert!(
+        report.rejected[0].reason.contains("manifest hash"),
+        "{}",
+        report.rejected[0].reason
+    );
+    let err = other
+        .storage()
+        .get("/priv/my-app/secret.txt")
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Request(RequestError::Server { status, .. }) if status == StatusCode::NOT_FOUND
+    ));
+}
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn metadata_reports_the_stored_hash() {
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app();
+    let pubky = testnet.sdk().unwrap();
+
+    let session = pubky
+        .signer(Keypair::random())
+        .signup_cookie(&server.public_key(), None)
+        .await
+        .unwrap();
+    let user = session.info().public_key().clone();
+    let storage = session.storage();
+    let stored = storage.put("/pub/my-app/note.txt", "hello").await.unwrap();
+
+    let metadata = storage.metadata("/pub/my-app/note.txt").await.unwrap();
+    assert!(metadata.exists);
+    assert_eq!(metadata.size, Some(5));
+    assert_eq!(metadata.content_type.as_deref(), Some("text/plain"));
+    assert_eq!(metadata.content_hash, Some(stored.content_hash));
+    let stats = storage
+        .stats("/pub/my-app/note.txt")
+        .await
+        .unwrap()
+        .unwrap();
+    let modified = metadata.modified.unwrap();
+    let last_modified = stats.last_modified.unwrap();
+    // `Last-Modified` only has second resolution.
+    assert!(last_modified <= modified);
+    assert!(modified < last_modified + std::time::Duration::from_secs(1));
+
+    let public = pubky
+        .public_storage()
+        .metadata(format!("{user}/pub/my-app/note.txt"))
+        .await
+        .unwrap();
+    assert_eq!(public, metadata);
+
+    // Missing files are reported, not an error.
+    let missing = storage.metadata("/pub/my-app/missing.txt").await.unwrap();
+    assert!(!missing.exists);
+    assert_eq!(missing.content_hash, None);
+}
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn on_homeserver_addresses_a_specific_homeserver() {
+    let mut testnet = Testnet::new().await.unwrap();
+    let pubky = testnet.sdk().unwrap();
+    let primary = testnet
+        .create_random_homeserver()
+        .await
+        .unwrap()
+        .public_key();
+    let mirror = testnet
+        .create_random_homeserver()
+        .await
+        .unwrap()
+        .public_key();
+
+    // The same user holds an account on both; PKDNS ends up pointing at the mirror.
+    let signer = pubky.signer(Keypair::random());
+    let on_primary = signer.signup_cookie(&primary, None).await.unwrap();
+    let on_mirror = signer.signup_cookie(&mirror, None).await.unwrap();
+
+    let path = "/pub/app/where.txt";
+    on_primary
+        .storage()
+        .on_homeserver(primary.clone())
+        .put(path, "primary")
+        .await
+        .unwrap();
+    on_mirror.storage().put(path, "mirror").await.unwrap();
+
+    let primary_storage = on_primary.storage().on_homeserver(primary);
+    let text = primary_storage
+        .get(path)
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert_eq!(text, "primary");
+    let listed = primary_storage
+        .list("/pub/app/")
+        .unwrap()
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].path.as_str(), path);
+
+    let text = on_mirror
+        .storage()
+        .on_homeserver(mirror)
+        .get(path)
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert_eq!(text, "mirror");
+}
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn put_as_attachment_keeps_the_download_filename() {
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app();
+    let pubky = testnet.sdk().unwrap();
+
+    let session = pubky
+        .signer(Keypair::random())
+        .signup_cookie(&server.public_key(), None)
+        .await
+        .unwrap();
+    let storage = session.storage();
+    let path = "/pub/app/files/3f9a";
+
+    storage
+        .put_as_attachment(path, vec![1, 2, 3], "../Résumé 2026.pdf")
+        .await
+        .unwrap();
+
+    let stats = storage.stats(path).await.unwrap().unwrap();
+    assert_eq!(stats.filename.as_deref(), Some("Résumé 2026.pdf"));
+
+    let addr = format!("{}{path}", session.public_key());
+    let response = pubky.public_storage().get(&addr).await.unwrap();
+    assert_eq!(response.filename().as_deref(), Some("Résumé 2026.pdf"));
+    assert_eq!(response.bytes().await.unwrap(), vec![1, 2, 3]);
+
+    // A plain overwrite drops the filename.
+    storage.put(path, vec![4]).await.unwrap();
+    let stats = storage.stats(path).await.unwrap().unwrap();
+    assert_eq!(stats.filename, None);
+
+    let err = storage
+        .put_as_attachment(path, vec![5], "dir/")
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Request(RequestError::Validation { .. })
+    ));
+}
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn put_multipart_stores_the_file_part() {
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app();
+    let pubky = testnet.sdk().unwrap();
+
+    let session = pubky
+        .signer(Keypair::random())
+        .signup_cookie(&server.public_key(), None)
+        .await
+        .unwrap();
+    let storage = session.storage();
+    let path = "/pub/app/uploads/photo";
+    let photo: Vec<u8> = (0..3 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+
+    // Text fields and other file parts around `file` are ignored.
+    let stored = storage
+        .put_multipart(
+            path,
+            [("caption", "At the beach"), ("csrf", "token")],
+            [
+                ("thumbnail", "thumb.jpg", vec![9; 16]),
+                ("file", "../holiday.jpg", photo.clone()),
+                ("other", "other.jpg", vec![8; 16]),
+            ],
+        )
+        .await
+        .unwrap();
+    assert_eq!(stored.size, photo.len() as u64);
+
+    let stats = storage.stats(path).await.unwrap().unwrap();
+    assert_eq!(stats.filename.as_deref(), Some("holiday.jpg"));
+    assert_eq!(
+        storage.get(path).await.unwrap().bytes().await.unwrap(),
+        photo
+    );
+
+    let err = storage
+        .put_multipart(
+            path,
+            [("caption", "no file")],
+            [("photo", "a.jpg", vec![1])],
+        )
+        .await
+        .unwrap_err();
+    assert_server_status(err, StatusCode::BAD_REQUEST);
+
+    // Appends are unaffected.
+    let length = storage.append_at(path, stored.size, "tail").await.unwrap();
+    assert_eq!(length, stored.size + 4);
+}
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn delete_many_reports_each_path() {
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app();
+    let pubky = testnet.sdk().unwrap();
+
+    let session = pubky
+        .signer(Keypair::random())
+        .signup_cookie(&server.public_key(), None)
+        .await
+        .unwrap();
+    let storage = session.storage();
+    for path in [
+        "/pub/my-app/posts/1",
+        "/pub/my-app/posts/2",
+        "/pub/my-app/posts/3",
+    ] {
+        storage.put(path, "post").await.unwrap();
+    }
+
+    // One path outside the writable roots rejects the whole batch before sending.
+    let err = storage
+        .delete_many(["/pub/my-app/posts/1", "/other/file"])
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Request(RequestError::Forbidden { .. })
+    ));
+    assert!(storage.exists("/pub/my-app/posts/1").await.unwrap());
+
+    // A missing file fails on its own without stopping the others.
+    let results = storage
+        .delete_many([
+            "/pub/my-app/posts/1",
+            "/pub/my-app/posts/missing",
+            "/pub/my-app/posts/3",
+        ])
+        .await
+        .unwrap();
+    let paths: Vec<_> = results.iter().map(|(path, _)| path.as_str()).collect();
+    assert_eq!(
+        paths,
+        [
+            "/pub/my-app/posts/1",
+            "/pub/my-app/posts/missing",
+            "/pub/my-app/posts/3"
+        ]
+    );
+    assert!(results[0].1.is_ok());
+    assert!(results[2].1.is_ok());
+    let (_, missing) = results.into_iter().nth(1).unwrap();
+    assert_server_status(missing.unwrap_err(), StatusCode::NOT_FOUND);
+
+    assert!(!storage.exists("/pub/my-app/posts/1").await.unwrap());
+    assert!(storage.exists("/pub/my-app/posts/2").await.unwrap());
+    assert!(!storage.exists("/pub/my-app/posts/3").await.unwrap());
+}
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn get_lines_streams_ndjson() {
+    use futures::{StreamExt, TryStreamExt};
+
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app();
+    let pubky = testnet.sdk().unwrap();
+
+    let session = pubky
+        .signer(Keypair::random())
+        .signup_cookie(&server.public_key(), None)
+        .await
+        .unwrap();
+    let storage = session.storage();
+    let body: String = (0..1_000)
+        .map(|i| format!("{{\"seq\":{i},\"msg\":\"héllo\"}}\r\n"))
+        .collect();
+    storage.put("/pub/my-app/log.ndjson", body).await.unwrap();
+
+    let lines: Vec<String> = storage
+        .get_lines("/pub/my-app/log.ndjson")
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(lines.len(), 1_000);
+    assert_eq!(lines[999], "{\"seq\":999,\"msg\":\"héllo\"}");
+
+    // A custom limit ends the stream at the first longer line.
+    let lines: Vec<_> = storage
+        .get("/pub/my-app/log.ndjson")
+        .await
+        .unwrap()
+        .lines(8)
+        .collect()
+        .await;
+    assert_eq!(lines.len(), 1);
+    assert!(matches!(
+        lines[0],
+        Err(Error::Request(RequestError::LineTooLong { limit: 8 }))
+    ));
+}
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn public_response_cache_serves_unchanged_resources() {
+    let testnet = build_full_testnet().await;
+    let server = testnet.homeserver_app();
+    let pubky = testnet.sdk().unwrap();
+
+    let session = pubky
+        .signer(Keypair::random())
+        .signup_cookie(&server.public_key(), None)
+        .await
+        .unwrap();
+    let user = session.info().public_key().clone();
+    session
+        .storage()
+        .put("/pub/my-app/config.json", "{\"v\":1}")
+        .await
+        .unwrap();
+
+    let public = pubky.public_storage().with_response_cache(10);
+    let addr = format!("{user}/pub/my-app/config.json");
+    for _ in 0..3 {
+        let resp = public.get(&addr).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert!(resp.headers().contains_key("etag"));
+        assert_eq!(resp.text().await.unwrap(), "{\"v\":1}");
+    }
+    let stats = public.response_cache_stats().unwrap();
+    assert_eq!((stats.hits, stats.misses), (2, 1));
+
+    session
+        .storage()
+        .put("/pub/my-app/config.json", "{\"v\":2}")
+        .await
+        .unwrap();
+    let body = public.get(&addr).await.unwrap().text().await.unwrap();
+    assert_eq!(body, "{\"v\":2}");
+    assert_eq!(public.response_cache_stats().unwrap().misses, 2);
+}
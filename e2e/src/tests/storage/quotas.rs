@@ -1,4 +1,5 @@
 use super::*;
+use pubky_testnet::pubky_homeserver::{quota_config::GlobPattern, ContentTypeSizeLimit};
 
 #[tokio::test]
 #[pubky_testnet::test]
@@ -26,12 +27,10 @@ async fn put_quota_applied() {
 
     // First 600 KB → OK (201)
     let data_600k: Vec<u8> = vec![0; 600_000];
-    let resp = session.storage().put(p1, data_600k.clone()).await.unwrap();
-    assert_eq!(resp.status(), StatusCode::CREATED);
+    session.storage().put(p1, data_600k.clone()).await.unwrap();
 
     // Overwrite same 600 KB → still 201
-    let resp = session.storage().put(p1, data_600k.clone()).await.unwrap();
-    assert_eq!(resp.status(), StatusCode::CREATED);
+    session.storage().put(p1, data_600k.clone()).await.unwrap();
 
     // Write 600 KB more at a different path (total 1.2 MB) → 507
     let err = session
@@ -74,7 +73,7 @@ async fn put_quota_applied() {
     // Write exactly 1 MB (minus the same 256 fudge) → 201 (fits quota)
     let data_1mb_minus_256: Vec<u8> = vec![0; 1024 * 1024 - 256];
     let resp = session.storage().put(p1, data_1mb_minus_256).await.unwrap();
-    assert_eq!(resp.status(), StatusCode::CREATED);
+    assert_eq!(resp.size, 1024 * 1024 - 256);
 }
 /// Regression test: quota early-rejection still works when bandwidth throttling
 /// is active. The bandwidth middleware wraps the request body in a throttled
@@ -103,12 +102,11 @@ async fn put_quota_applied_with_bandwidth_throttling() {
 
     // First 600 KB → OK (201)
     let data_600k: Vec<u8> = vec![0; 600_000];
-    let resp = session
+    session
         .storage()
         .put("/pub/data", data_600k.clone())
         .await
         .unwrap();
-    assert_eq!(resp.status(), StatusCode::CREATED);
 
     // Write another 600 KB at a different path (total 1.2 MB) → should be rejected
     // early via Content-Length header check, even though the bandwidth layer
@@ -128,3 +126,66 @@ async fn put_quota_applied_with_bandwidth_throttling() {
         err
     );
 }
+
+#[tokio::test]
+#[pubky_testnet::test]
+async fn put_content_policy_applied() {
+    let mut testnet = Testnet::new().await.unwrap();
+    let pubky = testnet.sdk().unwrap();
+
+    let mut mock_dir = MockDataDir::test();
+    let policy = &mut mock_dir.config_toml.content_policy;
+    policy.deny = vec![GlobPattern::new(
+        "application/vnd.microsoft.portable-executable",
+    )];
+    policy.max_sizes = vec![ContentTypeSizeLimit {
+        content_type: GlobPattern::new("text/*"),
+        max_size_mb: 1,
+    }];
+    let server = testnet
+        .create_homeserver_app_with_mock(mock_dir)
+        .await
+        .unwrap();
+
+    let signer = pubky.signer(Keypair::random());
+    let session = signer
+        .signup_cookie(&server.public_key(), None)
+        .await
+        .unwrap();
+
+    // Executables are refused, whatever their extension.
+    let err = session
+        .storage()
+        .put("/pub/notes.txt", b"MZ\x90\x00".to_vec())
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Request(RequestError::UnsupportedMediaType { ref message })
+            if message.contains("portable-executable")
+    ));
+
+    // Text is capped at 1 MB; other types are not.
+    let text = vec![b'a'; 1024 * 1024];
+    session
+        .storage()
+        .put("/pub/notes.txt", text.clone())
+        .await
+        .unwrap();
+    let mut too_long = text.clone();
+    too_long.push(b'a');
+    let err = session
+        .storage()
+        .put("/pub/notes.txt", too_long.clone())
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Request(RequestError::UnsupportedMediaType { .. })
+    ));
+    session
+        .storage()
+        .put("/pub/notes.bin", too_long)
+        .await
+        .unwrap();
+}
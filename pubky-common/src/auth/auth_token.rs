@@ -1,17 +1,25 @@
 //! Client-server Authentication using signed timesteps
 
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
 
 use crate::{
     capabilities::Capabilities,
-    crypto::{Keypair, PublicKey, Signature},
+    clock::{SystemClock, TimeSource},
+    crypto::{decrypt, Keypair, PublicKey, Signature},
     namespaces::PUBKY_AUTH,
     timestamp::Timestamp,
 };
 
 const CURRENT_VERSION: u8 = 0;
-// 3 minutes in the past or the future
-const TIMESTAMP_WINDOW: i64 = 180 * 1_000_000;
+
+/// How far an [AuthToken]'s timestamp may lie in the past or the future of the
+/// verifier's clock, unless configured otherwise with [AuthToken::verify_with_tolerance].
+pub const DEFAULT_CLOCK_SKEW_TOLERANCE: Duration = Duration::from_secs(180);
+
+// 3 minutes in the past or the future by default
+const TIMESTAMP_WINDOW: i64 = DEFAULT_CLOCK_SKEW_TOLERANCE.as_micros() as i64;
 
 mod signature_serde {
     use core::fmt;
@@ -67,7 +75,7 @@ mod signature_serde {
     }
 }
 
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(PartialEq, Serialize, Deserialize)]
 /// Authentication token used by the Pubky Auth protocol.
 pub struct AuthToken {
     /// Signature over the token.
@@ -91,10 +99,32 @@ pub struct AuthToken {
     capabilities: Capabilities,
 }
 
+/// The signature is what makes the token usable for signin, so it is left out.
+impl std::fmt::Debug for AuthToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AuthToken")
+            .field("signature", &"<redacted>")
+            .field("version", &self.version)
+            .field("timestamp", &self.timestamp)
+            .field("public_key", &self.public_key)
+            .field("capabilities", &self.capabilities)
+            .finish_non_exhaustive()
+    }
+}
+
 impl AuthToken {
     /// Sign a new AuthToken with given capabilities.
     pub fn sign(keypair: &Keypair, capabilities: impl Into<Capabilities>) -> Self {
-        let timestamp = Timestamp::now();
+        Self::sign_with_clock(keypair, capabilities, &SystemClock)
+    }
+
+    /// Sign a new AuthToken timestamped by the given [TimeSource].
+    pub fn sign_with_clock(
+        keypair: &Keypair,
+        capabilities: impl Into<Capabilities>,
+        clock: &dyn TimeSource,
+    ) -> Self {
+        let timestamp = clock.now();
 
         let mut token = Self {
             signature: Signature::from_bytes(&[0; 64]),
@@ -133,6 +163,25 @@ impl AuthToken {
 
     /// Parse and verify an AuthToken.
     pub fn verify(bytes: &[u8]) -> Result<Self, Error> {
+        Self::verify_with_clock(bytes, &SystemClock)
+    }
+
+    /// Parse and verify an AuthToken, checking its timestamp window against the
+    /// given [TimeSource].
+    pub fn verify_with_clock(bytes: &[u8], clock: &dyn TimeSource) -> Result<Self, Error> {
+        Self::verify_with_tolerance(bytes, clock, DEFAULT_CLOCK_SKEW_TOLERANCE)
+    }
+
+    /// Parse and verify an AuthToken, accepting timestamps up to `tolerance` in
+    /// the past or the future of the given [TimeSource].
+    ///
+    /// A larger tolerance lets clients with badly set clocks sign in, at the cost
+    /// of keeping captured tokens usable for longer.
+    pub fn verify_with_tolerance(
+        bytes: &[u8],
+        clock: &dyn TimeSource,
+        tolerance: Duration,
+    ) -> Result<Self, Error> {
         if bytes[74] > CURRENT_VERSION {
             return Err(Error::UnknownVersion);
         }
@@ -141,14 +190,15 @@ impl AuthToken {
 
         match token.version {
             0 => {
-                let now = Timestamp::now();
+                let now = clock.now();
+                let window = i64::try_from(tolerance.as_micros()).unwrap_or(i64::MAX);
 
                 // Chcek timestamp;
                 let diff = token.timestamp.as_u64() as i64 - now.as_u64() as i64;
-                if diff > TIMESTAMP_WINDOW {
+                if diff > window {
                     return Err(Error::TooFarInTheFuture);
                 }
-                if diff < -TIMESTAMP_WINDOW {
+                if diff < -window {
                     return Err(Error::Expired);
                 }
 
@@ -163,6 +213,30 @@ impl AuthToken {
         }
     }
 
+    /// Decrypt and verify an AuthToken delivered through the relay channel.
+    ///
+    /// `encrypted` is the payload a Signer posts to the relay, encrypted with the
+    /// `client_secret` the requesting app generated when starting the auth flow.
+    /// This checks the version, timestamp window, and signature without any network
+    /// round trip, so apps can reject bad tokens before exchanging them for a session.
+    ///
+    /// # Replay protection
+    ///
+    /// A token is identified by its `(public_key, timestamp)` pair, which acts as its
+    /// nonce. Offline verification is stateless and cannot tell whether that pair was
+    /// already presented; the homeserver tracks it and rejects a second use with
+    /// [Error::AlreadyUsed]. A token that passes here may therefore still be refused
+    /// by the homeserver if it was replayed within [VerifiedToken::expires_at].
+    pub fn verify_with_secret(
+        encrypted: &[u8],
+        client_secret: &[u8; 32],
+    ) -> Result<VerifiedToken, Error> {
+        let bytes = decrypt(encrypted, client_secret).map_err(|_| Error::Decryption)?;
+        let token = AuthToken::verify(&bytes)?;
+
+        Ok(VerifiedToken::from(token))
+    }
+
     /// Serialize this AuthToken to its canonical binary representation.
     pub fn serialize(&self) -> Vec<u8> {
         postcard::to_allocvec(self).unwrap()
@@ -181,17 +255,59 @@ impl AuthToken {
     }
 }
 
+/// An [AuthToken] whose signature and timestamp were checked offline.
+///
+/// Returned by [AuthToken::verify_with_secret].
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerifiedToken {
+    public_key: PublicKey,
+    capabilities: Capabilities,
+    issued_at: Timestamp,
+}
+
+impl VerifiedToken {
+    /// The pubky of the Signer that issued this token.
+    pub fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    /// Capabilities granted by this token.
+    pub fn capabilities(&self) -> &Capabilities {
+        &self.capabilities
+    }
+
+    /// When the Signer issued this token.
+    pub fn issued_at(&self) -> Timestamp {
+        self.issued_at
+    }
+
+    /// The last instant at which a homeserver will still accept this token.
+    pub fn expires_at(&self) -> Timestamp {
+        self.issued_at + TIMESTAMP_WINDOW as u64
+    }
+}
+
+impl From<AuthToken> for VerifiedToken {
+    fn from(token: AuthToken) -> Self {
+        Self {
+            public_key: token.public_key,
+            capabilities: token.capabilities,
+            issued_at: token.timestamp,
+        }
+    }
+}
+
 #[derive(thiserror::Error, Debug, PartialEq, Eq)]
 /// Error verifying an [AuthToken]
 pub enum Error {
     #[error("Unknown version")]
     /// Unknown version
     UnknownVersion,
-    #[error("AuthToken has a timestamp that is more than 3 minutes in the future")]
-    /// AuthToken has a timestamp that is more than 3 minutes in the future
+    #[error("AuthToken has a timestamp beyond the clock skew tolerance in the future")]
+    /// AuthToken has a timestamp beyond the clock skew tolerance in the future
     TooFarInTheFuture,
-    #[error("AuthToken has a timestamp that is more than 3 minutes in the past")]
-    /// AuthToken has a timestamp that is more than 3 minutes in the past
+    #[error("AuthToken has a timestamp beyond the clock skew tolerance in the past")]
+    /// AuthToken has a timestamp beyond the clock skew tolerance in the past
     Expired,
     #[error("Invalid Signature")]
     /// Invalid Signature
@@ -202,13 +318,18 @@ pub enum Error {
     #[error("AuthToken already used")]
     /// AuthToken already used
     AlreadyUsed,
+    #[error("Failed to decrypt AuthToken with the given client secret")]
+    /// Failed to decrypt AuthToken with the given client secret
+    Decryption,
 }
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use crate::{
-        auth::auth_token::TIMESTAMP_WINDOW, capabilities::Capability, crypto::Keypair,
-        timestamp::Timestamp,
+        auth::auth_token::TIMESTAMP_WINDOW, capabilities::Capability, clock::MockClock,
+        crypto::Keypair, timestamp::Timestamp,
     };
 
     use super::*;
@@ -230,6 +351,16 @@ mod tests {
         assert_eq!(verified.capabilities, capabilities.into());
     }
 
+    #[test]
+    fn debug_redacts_signature() {
+        let token = AuthToken::sign(&Keypair::random(), vec![Capability::root()]);
+        let debug = format!("{token:?}");
+
+        assert!(debug.contains("<redacted>"));
+        assert!(!debug.contains(&format!("{:?}", token.signature)));
+        assert!(!debug.contains(&format!("{:?}", token.signature.to_bytes())));
+    }
+
     #[test]
     fn expired() {
         let signer = Keypair::random();
@@ -290,6 +421,67 @@ mod tests {
         AuthToken::verify(&future_token.serialize()).unwrap();
     }
 
+    #[test]
+    fn custom_tolerance() {
+        let signer = Keypair::random();
+        let clock = MockClock::default();
+        let token = AuthToken::sign_with_clock(&signer, vec![Capability::root()], &clock);
+        let serialized = token.serialize();
+        let tolerance = Duration::from_secs(600);
+
+        clock.advance(Duration::from_secs(300));
+        assert_eq!(
+            AuthToken::verify_with_clock(&serialized, &clock),
+            Err(Error::Expired)
+        );
+        AuthToken::verify_with_tolerance(&serialized, &clock, tolerance).unwrap();
+
+        clock.advance(Duration::from_secs(400));
+        assert_eq!(
+            AuthToken::verify_with_tolerance(&serialized, &clock, tolerance),
+            Err(Error::Expired)
+        );
+    }
+
+    #[test]
+    fn verify_with_secret() {
+        let signer = Keypair::random();
+        let client_secret = [7; 32];
+        let token = AuthToken::sign(&signer, vec![Capability::root()]);
+        let encrypted = crate::crypto::encrypt(&token.serialize(), &client_secret);
+
+        let verified = AuthToken::verify_with_secret(&encrypted, &client_secret).unwrap();
+
+        assert_eq!(verified.public_key(), &signer.public_key());
+        assert_eq!(verified.capabilities(), token.capabilities());
+        assert_eq!(verified.issued_at(), token.timestamp());
+        assert_eq!(
+            verified.expires_at(),
+            token.timestamp() + TIMESTAMP_WINDOW as u64
+        );
+
+        assert_eq!(
+            AuthToken::verify_with_secret(&encrypted, &[8; 32]),
+            Err(Error::Decryption)
+        );
+    }
+
+    #[test]
+    fn expires_with_mock_clock() {
+        let signer = Keypair::random();
+        let clock = MockClock::default();
+        let token = AuthToken::sign_with_clock(&signer, vec![Capability::root()], &clock);
+        let serialized = token.serialize();
+
+        AuthToken::verify_with_clock(&serialized, &clock).unwrap();
+
+        clock.advance(Duration::from_micros(TIMESTAMP_WINDOW as u64 + 1));
+        assert_eq!(
+            AuthToken::verify_with_clock(&serialized, &clock),
+            Err(Error::Expired)
+        );
+    }
+
     #[test]
     fn unknown_version() {
         let signer = Keypair::random();
@@ -6,4 +6,4 @@ pub mod grant_session_responses;
 pub mod jws;
 pub mod pop;
 
-pub use auth_token::{AuthToken, Error};
+pub use auth_token::{AuthToken, Error, VerifiedToken, DEFAULT_CLOCK_SKEW_TOLERANCE};
@@ -36,6 +36,14 @@
 //!     .read("/pub/foo.txt")
 //!     .finish();
 //! assert_eq!(caps.to_string(), "/pub/my-cool-app/:rw,/pub/foo.txt:r");
+//!
+//! // Required and optional caps, for signers that let users decline some
+//! let request = Capabilities::builder()
+//!     .read_write("/pub/my-cool-app/")
+//!     .optional_read("/pub/contacts/")
+//!     .finish_request();
+//! assert_eq!(request.required.to_string(), "/pub/my-cool-app/:rw");
+//! assert_eq!(request.optional.to_string(), "/pub/contacts/:r");
 //! ```
 
 use serde::{Deserialize, Serialize};
@@ -148,6 +156,15 @@ impl Capability {
         self.scope.ends_with('/') && path.starts_with(&self.scope)
     }
 
+    /// Whether this capability grants `action` on `path`.
+    ///
+    /// This is the rule the homeserver authorizes storage requests with: the
+    /// scope must cover the path (see [`Self::scope_covers_path`]) and the
+    /// action must be listed.
+    pub fn allows(&self, path: &str, action: Action) -> bool {
+        self.scope_covers_path(path) && self.actions.contains(&action)
+    }
+
     /// Whether this capability fully covers `other` — i.e. the scope is equal or
     /// broader, and every action (read/write) in `other` is also present in `self`.
     fn covers(&self, other: &Capability) -> bool {
@@ -384,6 +401,31 @@ impl Capabilities {
         self.0.contains(capability)
     }
 
+    /// Returns `true` if everything granted by this list is also granted by `other`.
+    ///
+    /// Each action of each capability must be allowed by a capability of `other`
+    /// whose scope covers it.
+    ///
+    /// # Examples
+    /// ```
+    /// use pubky_common::capabilities::Capabilities;
+    ///
+    /// let granted = Capabilities::builder().read_write("/pub/app/").finish();
+    /// let narrow = Capabilities::builder().read("/pub/app/posts/").finish();
+    ///
+    /// assert!(narrow.is_subset_of(&granted));
+    /// assert!(!granted.is_subset_of(&narrow));
+    /// ```
+    pub fn is_subset_of(&self, other: &Capabilities) -> bool {
+        self.0.iter().all(|cap| {
+            cap.actions.iter().all(|action| {
+                other
+                    .iter()
+                    .any(|granted| granted.allows(&cap.scope, *action))
+            })
+        })
+    }
+
     /// Returns `true` if the list is empty.
     pub fn is_empty(&self) -> bool {
         self.0.is_empty()
@@ -471,9 +513,13 @@ impl Capabilities {
 ///
 /// Build with high-level helpers (`.read()/.write()/.read_write()`), or push prebuilt
 /// capabilities with `.cap()`, or use `.capability(scope, |b| ...)` to build inline.
+///
+/// The `optional_*` helpers add capabilities the user may decline; finish with
+/// [`Self::finish_request`] to keep them apart from the required ones.
 #[derive(Default, Debug)]
 pub struct CapsBuilder {
     caps: Vec<Capability>,
+    optional: Vec<Capability>,
 }
 
 impl CapsBuilder {
@@ -530,10 +576,124 @@ impl CapsBuilder {
         self
     }
 
+    /// Add an optional capability the user may decline.
+    pub fn optional_cap(mut self, cap: Capability) -> Self {
+        self.optional.push(cap);
+        self
+    }
+
+    /// Add an optional read-only capability for `scope`.
+    pub fn optional_read(self, scope: impl Into<String>) -> Self {
+        self.optional_cap(Capability::read(scope))
+    }
+
+    /// Add an optional write-only capability for `scope`.
+    pub fn optional_write(self, scope: impl Into<String>) -> Self {
+        self.optional_cap(Capability::write(scope))
+    }
+
+    /// Add an optional read+write capability for `scope`.
+    pub fn optional_read_write(self, scope: impl Into<String>) -> Self {
+        self.optional_cap(Capability::read_write(scope))
+    }
+
     /// Finalize and produce the normalized [`Capabilities`] list.
+    ///
+    /// Only the required capabilities are returned; use [`Self::finish_request`]
+    /// if optional ones were added.
     pub fn finish(self) -> Capabilities {
         Capabilities::from(self.caps).normalize()
     }
+
+    /// Finalize into a [`CapabilityRequest`] with required and optional capabilities.
+    ///
+    /// Optional capabilities already covered by a required one are dropped.
+    pub fn finish_request(self) -> CapabilityRequest {
+        let required = Capabilities::from(self.caps).normalize();
+        let optional = Capabilities::from(self.optional)
+            .normalize()
+            .0
+            .into_iter()
+            .filter(|cap| !required.iter().any(|r| r.covers(cap)))
+            .collect::<Vec<_>>();
+        CapabilityRequest {
+            required,
+            optional: Capabilities(optional),
+        }
+    }
+}
+
+/// Capabilities an app asks for, split into required and optional ones.
+///
+/// A signer must grant all `required` capabilities and may let the user decline
+/// any of the `optional` ones. The granted set is what ends up in the session.
+///
+/// ```
+/// use pubky_common::capabilities::{Capabilities, Capability};
+///
+/// let request = Capabilities::builder()
+///     .read_write("/pub/my-cool-app/")
+///     .optional_read("/pub/contacts/")
+///     .optional_write("/pub/calendar/")
+///     .finish_request();
+///
+/// // The user accepts the contacts capability only.
+/// let granted = request.grant(&Capabilities::from(vec![Capability::read("/pub/contacts/")]));
+/// assert_eq!(granted.to_string(), "/pub/my-cool-app/:rw,/pub/contacts/:r");
+/// assert_eq!(request.granted_optional(granted.as_slice()).to_string(), "/pub/contacts/:r");
+/// ```
+#[derive(Clone, Default, Debug, PartialEq, Eq)]
+pub struct CapabilityRequest {
+    /// Capabilities the app cannot work without.
+    pub required: Capabilities,
+    /// Capabilities the user may decline.
+    pub optional: Capabilities,
+}
+
+impl CapabilityRequest {
+    /// The capabilities to grant: all required ones plus the optional ones in `accepted`.
+    ///
+    /// Entries of `accepted` that were not requested as optional are ignored.
+    pub fn grant(&self, accepted: &Capabilities) -> Capabilities {
+        let accepted = self
+            .optional
+            .iter()
+            .filter(|cap| accepted.contains(cap))
+            .cloned();
+        Capabilities::from(
+            self.required
+                .iter()
+                .cloned()
+                .chain(accepted)
+                .collect::<Vec<_>>(),
+        )
+        .normalize()
+    }
+
+    /// All requested capabilities, required and optional.
+    pub fn all(&self) -> Capabilities {
+        self.grant(&self.optional)
+    }
+
+    /// The optional capabilities covered by `granted`, e.g. a session's capabilities.
+    pub fn granted_optional(&self, granted: &[Capability]) -> Capabilities {
+        Capabilities(
+            self.optional
+                .iter()
+                .filter(|cap| granted.iter().any(|g| g.covers(cap)))
+                .cloned()
+                .collect(),
+        )
+    }
+}
+
+impl From<Capabilities> for CapabilityRequest {
+    fn from(required: Capabilities) -> Self {
+        Self {
+            required,
+            optional: Capabilities::default(),
+        }
+    }
 }
 
 impl From<Vec<Capability>> for Capabilities {
@@ -876,6 +1036,30 @@ mod tests {
         assert_eq!(back, caps);
     }
 
+    #[test]
+    fn subset_requires_every_action_to_be_covered() {
+        let granted = Capabilities::builder()
+            .read_write("/pub/app/")
+            .read("/pub/other/")
+            .finish();
+
+        let narrow = Capabilities::builder()
+            .write("/pub/app/posts/")
+            .read("/pub/other/file")
+            .finish();
+        assert!(narrow.is_subset_of(&granted));
+        assert!(Capabilities::default().is_subset_of(&granted));
+
+        let wider_action = Capabilities::builder().write("/pub/other/").finish();
+        assert!(!wider_action.is_subset_of(&granted));
+
+        let wider_scope = Capabilities::builder().read("/pub/").finish();
+        assert!(!wider_scope.is_subset_of(&granted));
+
+        let file_scope_parent = Capabilities::builder().read("/pub/app").finish();
+        assert!(!file_scope_parent.is_subset_of(&granted));
+    }
+
     // --- scope_covers_path: trailing slash semantics ---
     //
     // The trailing `/` on a scope is significant. A directory scope
@@ -948,4 +1132,48 @@ mod tests {
         assert!(root.scope_covers_path("/pub/anything"));
         assert!(root.scope_covers_path("/dav/some/file.txt"));
     }
+
+    #[test]
+    fn allows_requires_scope_and_action() {
+        let read = Capability::read("/pub/app/");
+        assert!(read.allows("/pub/app/file", Action::Read));
+        assert!(!read.allows("/pub/app/file", Action::Write));
+        assert!(!read.allows("/pub/other/file", Action::Read));
+    }
+
+    #[test]
+    fn capability_request_splits_and_grants() {
+        let request = Capabilities::builder()
+            .read_write("/pub/app/")
+            .optional_read("/pub/app/inner/")
+            .optional_read("/pub/contacts/")
+            .optional_write("/pub/calendar/")
+            .finish_request();
+        assert_eq!(request.required.to_string(), "/pub/app/:rw");
+        assert_eq!(
+            request.optional.to_string(),
+            "/pub/contacts/:r,/pub/calendar/:w",
+            "Optional caps covered by required ones are dropped"
+        );
+
+        let accepted = Capabilities::builder()
+            .read("/pub/contacts/")
+            .read("/pub/unrequested/")
+            .finish();
+        let granted = request.grant(&accepted);
+        assert_eq!(granted.to_string(), "/pub/app/:rw,/pub/contacts/:r");
+        assert_eq!(
+            request.granted_optional(granted.as_slice()).to_string(),
+            "/pub/contacts/:r"
+        );
+
+        assert_eq!(
+            request.all().to_string(),
+            "/pub/app/:rw,/pub/contacts/:r,/pub/calendar/:w"
+        );
+        assert_eq!(
+            request.granted_optional(&[Capability::root()]),
+            request.optional
+        );
+    }
 }
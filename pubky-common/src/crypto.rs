@@ -29,6 +29,51 @@ pub fn random_bytes<const N: usize>() -> [u8; N] {
     arr
 }
 
+/// First line of the signed form of an application message, separating it from
+/// any other payload signed with a Pubky key (auth tokens, grants, Pkarr packets).
+pub const MESSAGE_SIGNATURE_CONTEXT: &str = "pubky-message-v1";
+
+/// The canonical bytes signed for an application message, see [`sign_message`].
+///
+/// The domain separator is length-prefixed so no `(domain, message)` pair can
+/// produce the same bytes as another:
+/// ```text
+/// pubky-message-v1
+/// <domain separator length in bytes, decimal>:<domain separator>
+/// <message bytes>
+/// ```
+pub fn signable_message(domain_separator: &str, message: &[u8]) -> Vec<u8> {
+    let mut out = format!(
+        "{MESSAGE_SIGNATURE_CONTEXT}\n{}:{domain_separator}\n",
+        domain_separator.len()
+    )
+    .into_bytes();
+    out.extend_from_slice(message);
+    out
+}
+
+/// Sign an application message with `keypair` under `domain_separator`.
+///
+/// The domain separator names the protocol the signature is meant for (e.g.
+/// `"example.com/link-account"`), so a signature produced for one application
+/// can't be replayed as a valid one in another.
+pub fn sign_message(keypair: &Keypair, domain_separator: &str, message: &[u8]) -> Signature {
+    keypair.sign(&signable_message(domain_separator, message))
+}
+
+/// Check that `signature` was made by `public_key` over `message` under
+/// `domain_separator`, see [`sign_message`].
+pub fn verify_message(
+    public_key: &PublicKey,
+    domain_separator: &str,
+    message: &[u8],
+    signature: &Signature,
+) -> bool {
+    public_key
+        .verify(&signable_message(domain_separator, message), signature)
+        .is_ok()
+}
+
 /// Encrypt a message using `XSalsa20Poly1305`.
 pub fn encrypt(plain_text: &[u8], encryption_key: &[u8; 32]) -> Vec<u8> {
     if plain_text.is_empty() {
@@ -89,4 +134,47 @@ mod tests {
 
         assert_eq!(decrypted, plain_text.as_bytes())
     }
+
+    #[test]
+    fn sign_verify_message() {
+        let keypair = Keypair::random();
+        let signature = sign_message(&keypair, "example.com/link", b"account 42");
+
+        assert!(verify_message(
+            &keypair.public_key(),
+            "example.com/link",
+            b"account 42",
+            &signature
+        ));
+        assert!(!verify_message(
+            &keypair.public_key(),
+            "example.com/other",
+            b"account 42",
+            &signature
+        ));
+        assert!(!verify_message(
+            &keypair.public_key(),
+            "example.com/link",
+            b"account 43",
+            &signature
+        ));
+        assert!(!verify_message(
+            &Keypair::random().public_key(),
+            "example.com/link",
+            b"account 42",
+            &signature
+        ));
+    }
+
+    #[test]
+    fn signable_message_is_unambiguous() {
+        assert_ne!(
+            signable_message("a\nb", b"c"),
+            signable_message("a", b"b\nc")
+        );
+        assert_eq!(
+            signable_message("app", b"hi"),
+            b"pubky-message-v1\n3:app\nhi".to_vec()
+        );
+    }
 }
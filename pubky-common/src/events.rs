@@ -6,7 +6,7 @@
 use std::fmt::Display;
 use std::str::FromStr;
 
-use crate::crypto::Hash;
+use crate::crypto::{Hash, Keypair, PublicKey, Signature};
 
 /// Cursor for pagination in event queries.
 ///
@@ -106,6 +106,52 @@ impl Display for EventType {
     }
 }
 
+/// First line of the signed form of an event feed entry, separating it from any
+/// other payload signed with a homeserver key.
+pub const EVENT_SIGNATURE_CONTEXT: &str = "pubky-event-v1";
+
+/// The canonical bytes a homeserver signs for one event feed entry.
+///
+/// Lines separated by `\n`, without a trailing newline:
+/// ```text
+/// pubky-event-v1
+/// <PUT|DEL>
+/// <resource URI exactly as sent, e.g. pubky://<user z32>/pub/example.txt>
+/// <cursor, decimal>
+/// <content hash, lowercase hex; empty for DEL>
+/// ```
+pub fn signable_event(event_type: &EventType, resource_uri: &str, cursor: EventCursor) -> Vec<u8> {
+    let content_hash = event_type
+        .content_hash()
+        .map(|hash| hash.to_hex().to_string())
+        .unwrap_or_default();
+    format!("{EVENT_SIGNATURE_CONTEXT}\n{event_type}\n{resource_uri}\n{cursor}\n{content_hash}")
+        .into_bytes()
+}
+
+/// Sign an event feed entry with the homeserver's keypair, see [`signable_event`].
+pub fn sign_event(
+    keypair: &Keypair,
+    event_type: &EventType,
+    resource_uri: &str,
+    cursor: EventCursor,
+) -> Signature {
+    keypair.sign(&signable_event(event_type, resource_uri, cursor))
+}
+
+/// Check that `signature` was made by `homeserver` over this event feed entry.
+pub fn verify_event(
+    homeserver: &PublicKey,
+    signature: &Signature,
+    event_type: &EventType,
+    resource_uri: &str,
+    cursor: EventCursor,
+) -> bool {
+    homeserver
+        .verify(&signable_event(event_type, resource_uri, cursor), signature)
+        .is_ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -162,6 +208,58 @@ mod tests {
         assert_eq!(del.content_hash(), None);
     }
 
+    #[test]
+    fn signable_event_format() {
+        let put = EventType::Put {
+            content_hash: Hash::from_bytes([0xab; 32]),
+        };
+        let signable = signable_event(&put, "pubky://user/pub/a.txt", EventCursor::new(42));
+        assert_eq!(
+            String::from_utf8(signable).unwrap(),
+            format!(
+                "pubky-event-v1\nPUT\npubky://user/pub/a.txt\n42\n{}",
+                "ab".repeat(32)
+            )
+        );
+
+        let signable = signable_event(&EventType::Delete, "pubky://user/pub/a.txt", 7.into());
+        assert_eq!(
+            String::from_utf8(signable).unwrap(),
+            "pubky-event-v1\nDEL\npubky://user/pub/a.txt\n7\n"
+        );
+    }
+
+    #[test]
+    fn sign_and_verify_event() {
+        let keypair = Keypair::random();
+        let uri = "pubky://user/pub/a.txt";
+        let cursor = EventCursor::new(1);
+        let signature = sign_event(&keypair, &EventType::Delete, uri, cursor);
+
+        let homeserver = keypair.public_key();
+        assert!(verify_event(
+            &homeserver,
+            &signature,
+            &EventType::Delete,
+            uri,
+            cursor
+        ));
+        assert!(!verify_event(
+            &homeserver,
+            &signature,
+            &EventType::Delete,
+            uri,
+            2.into()
+        ));
+        assert!(!verify_event(
+            &Keypair::random().public_key(),
+            &signature,
+            &EventType::Delete,
+            uri,
+            cursor
+        ));
+    }
+
     #[test]
     fn cursor_parse_error() {
         assert!("abc".parse::<EventCursor>().is_err());
@@ -5,6 +5,7 @@ use core::str::FromStr;
 use std::{io, path::Path};
 
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
 type ParseError = <pkarr::PublicKey as TryFrom<String>>::Error;
 
@@ -18,6 +19,9 @@ fn parse_public_key(value: &str) -> Result<pkarr::PublicKey, ParseError> {
 }
 
 /// Wrapper around [`pkarr::Keypair`] that customizes [`PublicKey`] rendering.
+///
+/// The secret key is zeroized when the keypair is dropped, and `Debug` only shows the
+/// public key.
 #[derive(Clone)]
 pub struct Keypair(pkarr::Keypair);
 
@@ -29,6 +33,9 @@ impl Keypair {
     }
 
     /// Export the secret bytes used to derive this keypair.
+    ///
+    /// The returned copy is not zeroized on drop; wrap it in [`zeroize::Zeroizing`]
+    /// if it must not linger in memory.
     #[must_use]
     pub fn secret(&self) -> [u8; 32] {
         let mut out = [0u8; 32];
@@ -82,6 +89,17 @@ impl fmt::Debug for Keypair {
     }
 }
 
+/// Overwrites the secret key with zeros; the keypair is unusable afterwards.
+impl Zeroize for Keypair {
+    fn zeroize(&mut self) {
+        // Dropping the previous signing key zeroizes its secret.
+        *self = Self::from_secret(&[0; 32]);
+    }
+}
+
+/// The inner `ed25519_dalek::SigningKey` zeroizes its secret on drop.
+impl ZeroizeOnDrop for Keypair {}
+
 impl Deref for Keypair {
     type Target = pkarr::Keypair;
 
@@ -268,6 +286,24 @@ mod tests {
         assert_eq!(parsed, public_key);
     }
 
+    #[test]
+    fn debug_never_prints_the_secret() {
+        let keypair = Keypair::from_secret(&[7; 32]);
+        let secret = keypair.secret();
+        let debug = format!("{keypair:?} {keypair:#?}");
+
+        assert!(!debug.contains(&"07".repeat(32)));
+        assert!(!debug.contains(&format!("{secret:?}")));
+        assert!(debug.contains(&keypair.public_key().z32()));
+    }
+
+    #[test]
+    fn zeroize_replaces_the_secret() {
+        let mut keypair = Keypair::random();
+        keypair.zeroize();
+        assert_eq!(keypair.secret(), [0; 32]);
+    }
+
     #[test]
     fn public_key_display_uses_pubky_prefix() {
         let public_key = Keypair::random().public_key();
@@ -8,11 +8,13 @@
 
 pub mod auth;
 pub mod capabilities;
+pub mod clock;
 pub mod constants;
 pub mod crypto;
 pub mod events;
 mod keys;
 pub mod namespaces;
+pub mod profile;
 pub mod recovery_file;
 pub mod session;
 pub mod storage;
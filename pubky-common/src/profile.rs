@@ -0,0 +1,81 @@
+//! Conventional user profile, read by apps to show who a pubky belongs to.
+
+use serde::{Deserialize, Serialize};
+
+/// Path of a user's profile in their storage, readable by anyone.
+///
+/// Apps that let users edit their profile write a [`Profile`] here as JSON, and
+/// apps that only display users read it from here.
+pub const PROFILE_PATH: &str = "/pub/pubky.app/profile.json";
+
+/// A user's public profile, stored as JSON at [`PROFILE_PATH`].
+///
+/// Only `name` is required. Fields not listed here are ignored when reading,
+/// so apps can extend the document without breaking others.
+///
+/// # JSON representation
+/// ```json
+/// {
+///   "name": "Alice",
+///   "bio": "Building on Pubky",
+///   "image": "pubky://o1gg96ewuojmopcjbz8895478wdtxtzzuxnfjjz8o8e77csa1ngo/pub/pubky.app/files/avatar",
+///   "links": [{ "title": "Website", "url": "https://alice.example" }],
+///   "status": "Away"
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profile {
+    /// Display name.
+    pub name: String,
+    /// Short self-description.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bio: Option<String>,
+    /// Avatar URL, usually a `pubky://` URL to a file in the user's own storage.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    /// Links to show alongside the profile.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub links: Vec<ProfileLink>,
+    /// Free-form status line.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+}
+
+/// A titled link in a [`Profile`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileLink {
+    /// Label to show for the link.
+    pub title: String,
+    /// Target URL.
+    pub url: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_minimal_and_extended_profiles() {
+        let minimal: Profile = serde_json::from_str(r#"{"name":"Alice"}"#).unwrap();
+        assert_eq!(
+            minimal,
+            Profile {
+                name: "Alice".into(),
+                ..Profile::default()
+            }
+        );
+
+        let extended: Profile = serde_json::from_str(
+            r#"{"name":"Bob","image":"pubky://x/pub/a.png","links":[{"title":"Web","url":"https://bob.example"}],"theme":"dark"}"#,
+        )
+        .unwrap();
+        assert_eq!(extended.image.as_deref(), Some("pubky://x/pub/a.png"));
+        assert_eq!(extended.links[0].url, "https://bob.example");
+
+        assert!(serde_json::from_str::<Profile>(r#"{"bio":"no name"}"#).is_err());
+        assert_eq!(
+            serde_json::to_string(&minimal).unwrap(),
+            r#"{"name":"Alice"}"#
+        );
+    }
+}
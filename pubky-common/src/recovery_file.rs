@@ -1,6 +1,7 @@
 //! Tools for encrypting and decrypting a recovery file storing user's root key's secret.
 
 use argon2::Argon2;
+use zeroize::Zeroizing;
 
 use crate::crypto::{decrypt, encrypt, Keypair};
 
@@ -9,7 +10,7 @@ static SPEC_LINE: &str = "pubky.org/recovery";
 
 /// Decrypt a recovery file.
 pub fn decrypt_recovery_file(recovery_file: &[u8], passphrase: &str) -> Result<Keypair, Error> {
-    let encryption_key = recovery_file_encryption_key_from_passphrase(passphrase);
+    let encryption_key = Zeroizing::new(recovery_file_encryption_key_from_passphrase(passphrase));
 
     let newline_index = recovery_file
         .iter()
@@ -31,21 +32,23 @@ pub fn decrypt_recovery_file(recovery_file: &[u8], passphrase: &str) -> Result<K
         return Err(Error::RecoverFileMissingEncryptedSecretKey);
     };
 
-    let decrypted = decrypt(encrypted, &encryption_key)?;
-    let length = decrypted.len();
-    let secret_key: [u8; 32] = decrypted
-        .try_into()
-        .map_err(|_| Error::RecoverFileInvalidSecretKeyLength(length))?;
+    let decrypted = Zeroizing::new(decrypt(encrypted, &encryption_key)?);
+    let secret_key: Zeroizing<[u8; 32]> = Zeroizing::new(
+        decrypted
+            .as_slice()
+            .try_into()
+            .map_err(|_| Error::RecoverFileInvalidSecretKeyLength(decrypted.len()))?,
+    );
 
     Ok(Keypair::from_secret(&secret_key))
 }
 
 /// Encrypt a recovery file.
 pub fn create_recovery_file(keypair: &Keypair, passphrase: &str) -> Vec<u8> {
-    let encryption_key = recovery_file_encryption_key_from_passphrase(passphrase);
-    let secret_key = keypair.secret();
+    let encryption_key = Zeroizing::new(recovery_file_encryption_key_from_passphrase(passphrase));
+    let secret_key = Zeroizing::new(keypair.secret());
 
-    let encrypted_secret_key = encrypt(&secret_key, &encryption_key);
+    let encrypted_secret_key = encrypt(secret_key.as_slice(), &encryption_key);
 
     let mut out = Vec::with_capacity(SPEC_LINE.len() + 1 + encrypted_secret_key.len());
 
@@ -1,11 +1,375 @@
 //! Shared Pubky storage path helpers.
 
+use serde::{Deserialize, Serialize};
+
 /// Storage root for public, world-readable data.
 pub const PUBLIC_ROOT: &str = "/pub/";
 
 /// Storage root for private data.
 pub const PRIVATE_ROOT: &str = "/priv/";
 
+/// Default maximum length, in bytes, of a decoded storage path such as
+/// `/pub/my-cool-app/file`. Homeservers may configure a different limit.
+pub const DEFAULT_MAX_PATH_LENGTH: usize = 1024;
+
+/// Default maximum number of segments in a storage path; `/pub/my-cool-app/file`
+/// has three. Homeservers may configure a different limit.
+pub const DEFAULT_MAX_PATH_SEGMENTS: usize = 32;
+
+/// Response header carrying the list snapshot token.
+///
+/// Directory listings requested with `snapshot=new` return the token of the
+/// newly opened snapshot in this header. Pass it back as `snapshot=<token>`
+/// to read further pages against the same point in time.
+pub const LIST_SNAPSHOT_HEADER: &str = "pubky-list-snapshot";
+
+/// Request header setting a time-to-live, in whole seconds, on a `PUT` file.
+///
+/// Once it elapses the homeserver answers `404 Not Found` for the file, hides it
+/// from listings and eventually deletes it. A `PUT` without the header stores a
+/// file that never expires, also when it replaces one that would have.
+pub const TTL_HEADER: &str = "pubky-ttl";
+
+/// Request header of a `POST <file>` append, holding the length in bytes the file
+/// must have for the body to be appended to it.
+///
+/// The homeserver answers `409 Conflict` with a `Content-Range: bytes */<length>`
+/// header if the file has a different length, e.g. because another writer appended
+/// first. A missing file has length 0, so appending at offset 0 creates it.
+pub const APPEND_OFFSET_HEADER: &str = "pubky-append-offset";
+
+/// Name of the `multipart/form-data` part stored as the file by a form upload, a
+/// `POST <file>` without an [`APPEND_OFFSET_HEADER`].
+///
+/// Its filename becomes the download filename of the file. Every other part is
+/// ignored, so forms may carry extra inputs.
+pub const MULTIPART_FILE_FIELD: &str = "file";
+
+/// Aggregate statistics of a directory, returned by `GET <dir>/?stats`.
+///
+/// Computed by the homeserver from its entry index. With `shallow`, only files
+/// directly inside the directory are counted; otherwise the whole subtree is.
+///
+/// # JSON representation
+/// ```json
+/// {
+///   "total_bytes": 1024,
+///   "file_count": 3,
+///   "oldest_modified_at": 1700000000000000,
+///   "newest_modified_at": 1700000123000000
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DirStatsResponse {
+    /// Sum of the content lengths of all counted files.
+    pub total_bytes: u64,
+    /// Number of counted files.
+    pub file_count: u64,
+    /// Modification time of the least recently modified file (Unix microseconds).
+    pub oldest_modified_at: Option<u64>,
+    /// Modification time of the most recently modified file (Unix microseconds).
+    pub newest_modified_at: Option<u64>,
+}
+
+/// Metadata of a file, returned by `GET <file>?metadata`.
+///
+/// Unlike a `HEAD` request, this reports the content hash the homeserver stores
+/// and answers `200 OK` with `exists: false` for a missing file, so a single
+/// request tells whether a local copy is up to date. All other fields are
+/// `null` when the file does not exist.
+///
+/// # JSON representation
+/// ```json
+/// {
+///   "exists": true,
+///   "size": 1024,
+///   "content_type": "text/plain",
+///   "content_hash": "r0NJufX5oaagQE3qNtzJSZvLJcmtwRK3zJqTyuQfMmI=",
+///   "modified": 1700000000000000
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileMetadataResponse {
+    /// Whether the file exists.
+    pub exists: bool,
+    /// Size of the content in bytes.
+    pub size: Option<u64>,
+    /// Content type stored with the file.
+    pub content_type: Option<String>,
+    /// Base64 of the BLAKE3 hash of the content.
+    pub content_hash: Option<String>,
+    /// Modification time (Unix microseconds).
+    pub modified: Option<u64>,
+}
+
+/// Metadata of the file stored by `PUT <file>` or `POST <file>` (append).
+///
+/// Saves clients a `HEAD` request when they need to reference what they just
+/// uploaded. The hash is also sent as the response's `ETag`.
+///
+/// # JSON representation
+/// ```json
+/// {
+///   "content_hash": "r0NJufX5oaagQE3qNtzJSZvLJcmtwRK3zJqTyuQfMmI=",
+///   "content_length": 1024
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PutResponse {
+    /// Base64 of the BLAKE3 hash of the stored content.
+    pub content_hash: String,
+    /// Size of the stored content in bytes.
+    pub content_length: u64,
+}
+
+/// Result of deleting a whole directory, returned by `DELETE <dir>/`.
+///
+/// The homeserver removes every file under the directory in a single
+/// transaction, so either all of them are gone or none is.
+///
+/// # JSON representation
+/// ```json
+/// { "deleted": 3 }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeleteDirResponse {
+    /// Number of files that were deleted.
+    pub deleted: u64,
+}
+
+/// Name of the manifest entry, the first entry of every account archive.
+pub const ARCHIVE_MANIFEST_NAME: &str = "pubky-manifest.json";
+
+/// Version of the [`ArchiveManifest`] format.
+pub const ARCHIVE_MANIFEST_VERSION: u32 = 1;
+
+/// Manifest of an account archive, a tar file holding the files of one user.
+///
+/// The manifest is the first entry of the archive, named [`ARCHIVE_MANIFEST_NAME`].
+/// The files follow, named by their percent-encoded path without the leading `/`
+/// (e.g. `pub/my-cool-app/a.txt`). `POST /import` writes such an archive into the
+/// account of the homeserver's tenant.
+///
+/// # JSON representation
+/// ```json
+/// {
+///   "version": 1,
+///   "user": "<z32 public key>",
+///   "entries": [
+///     {
+///       "path": "/pub/my-cool-app/a.txt",
+///       "content_hash": "r0NJufX5oaagQE3qNtzJSZvLJcmtwRK3zJqTyuQfMmI=",
+///       "size": 5,
+///       "content_type": "text/plain"
+///     }
+///   ]
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    /// Format version, [`ARCHIVE_MANIFEST_VERSION`].
+    pub version: u32,
+    /// z32 public key of the user the archive was exported from.
+    pub user: String,
+    /// Every file of the archive, in archive order.
+    pub entries: Vec<ArchiveManifestEntry>,
+}
+
+/// One file of an [`ArchiveManifest`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveManifestEntry {
+    /// Percent-encoded absolute path of the file, e.g. `/pub/my-cool-app/a.txt`.
+    pub path: String,
+    /// Base64 of the BLAKE3 hash of the content.
+    pub content_hash: String,
+    /// Size of the content in bytes.
+    pub size: u64,
+    /// Content type the file was stored with, if known.
+    pub content_type: Option<String>,
+}
+
+/// What happened to one manifest entry of an imported archive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportEntryStatus {
+    /// The file was written.
+    Imported,
+    /// A file with the same content already existed at the path and was kept.
+    Unchanged,
+    /// The archive does not contain the file.
+    Missing,
+    /// The file was not written, see [`ImportEntryResult::message`].
+    Rejected,
+}
+
+/// Outcome of importing one manifest entry, see [`ImportResponse`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportEntryResult {
+    /// Path of the entry, as listed in the manifest.
+    pub path: String,
+    /// What happened to the entry.
+    pub status: ImportEntryStatus,
+    /// Why the entry was rejected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// Result of importing an account archive, returned by `POST /import`.
+///
+/// Holds one result per manifest entry, in manifest order. Rejecting an entry does
+/// not stop the import, so the other entries are still written.
+///
+/// # JSON representation
+/// ```json
+/// {
+///   "entries": [
+///     { "path": "/pub/my-cool-app/a.txt", "status": "imported" },
+///     { "path": "/pub/my-cool-app/b.txt", "status": "rejected", "message": "Disk space quota exceeded" }
+///   ]
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportResponse {
+    /// One result per manifest entry, in manifest order.
+    pub entries: Vec<ImportEntryResult>,
+}
+
+/// Longest filename, in bytes, kept by [`sanitize_filename`].
+pub const MAX_FILENAME_LENGTH: usize = 255;
+
+/// Reduce a user-supplied filename to something safe to store and send back in a
+/// `Content-Disposition` header.
+///
+/// Only the last path component is kept, control characters (including CR and LF,
+/// which could inject headers) are removed, surrounding whitespace is trimmed and
+/// the result is cut to [`MAX_FILENAME_LENGTH`] bytes. Returns `None` if nothing
+/// usable remains.
+pub fn sanitize_filename(filename: &str) -> Option<String> {
+    let name = filename.rsplit(['/', '\\']).next().unwrap_or_default();
+    let name: String = name.chars().filter(|c| !c.is_control()).collect();
+    let mut name = name.trim();
+    if name.len() > MAX_FILENAME_LENGTH {
+        let mut end = MAX_FILENAME_LENGTH;
+        while !name.is_char_boundary(end) {
+            end -= 1;
+        }
+        name = name[..end].trim_end();
+    }
+    match name {
+        "" | "." | ".." => None,
+        name => Some(name.to_string()),
+    }
+}
+
+/// Render the `Content-Disposition` value that makes browsers download a file as
+/// `filename`, after [`sanitize_filename`].
+///
+/// ASCII names are sent as a quoted `filename`. Other names are also sent as an
+/// RFC 5987 `filename*`, with non-ASCII characters replaced by `_` in the
+/// `filename` fallback.
+pub fn attachment_disposition(filename: &str) -> Option<String> {
+    let filename = sanitize_filename(filename)?;
+    let mut value = String::from("attachment; filename=\"");
+    for c in filename.chars() {
+        match c {
+            '"' | '\\' => {
+                value.push('\\');
+                value.push(c);
+            }
+            c if c.is_ascii() => value.push(c),
+            _ => value.push('_'),
+        }
+    }
+    value.push('"');
+    if !filename.is_ascii() {
+        value.push_str("; filename*=UTF-8''");
+        for byte in filename.bytes() {
+            if byte.is_ascii_alphanumeric() || b"!#$&+-.^_`|~".contains(&byte) {
+                value.push(char::from(byte));
+            } else {
+                value.push_str(&format!("%{byte:02X}"));
+            }
+        }
+    }
+    Some(value)
+}
+
+/// Read the filename of an `attachment` `Content-Disposition` header value, after
+/// [`sanitize_filename`].
+///
+/// An RFC 5987 `filename*` (UTF-8 or ISO-8859-1) is preferred over a plain
+/// `filename`. Returns `None` if the value is not an attachment (e.g. `inline`) or
+/// names no usable file.
+pub fn disposition_filename(value: &str) -> Option<String> {
+    let (disposition, mut params) = value.split_once(';')?;
+    if !disposition.trim().eq_ignore_ascii_case("attachment") {
+        return None;
+    }
+    let mut plain = None;
+    let mut extended = None;
+    while let Some((name, rest)) = params.split_once('=') {
+        let rest = rest.trim_start();
+        let (value, next) = match rest.strip_prefix('"') {
+            Some(quoted) => {
+                let mut value = String::new();
+                let mut chars = quoted.char_indices();
+                let mut end = None;
+                while let Some((i, c)) = chars.next() {
+                    match c {
+                        '\\' => value.extend(chars.next().map(|(_, c)| c)),
+                        '"' => {
+                            end = Some(i + 1);
+                            break;
+                        }
+                        c => value.push(c),
+                    }
+                }
+                let after = &quoted[end?..];
+                (value, after.split_once(';').map_or("", |(_, next)| next))
+            }
+            None => {
+                let (value, next) = rest.split_once(';').unwrap_or((rest, ""));
+                (value.trim().to_string(), next)
+            }
+        };
+        match name.trim().to_ascii_lowercase().as_str() {
+            "filename" => plain = Some(value),
+            "filename*" => extended = decode_ext_value(&value),
+            _ => {}
+        }
+        params = next;
+    }
+    extended
+        .or(plain)
+        .and_then(|filename| sanitize_filename(&filename))
+}
+
+/// Decode an RFC 5987 `ext-value` (`charset'language'percent-encoded`).
+fn decode_ext_value(value: &str) -> Option<String> {
+    let mut parts = value.splitn(3, '\'');
+    let charset = parts.next()?;
+    let encoded = parts.nth(1)?;
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut rest = encoded.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    if charset.eq_ignore_ascii_case("utf-8") {
+        String::from_utf8(bytes).ok()
+    } else if charset.eq_ignore_ascii_case("iso-8859-1") {
+        Some(bytes.into_iter().map(char::from).collect())
+    } else {
+        None
+    }
+}
+
 /// Returns whether a normalized storage path is under [`PRIVATE_ROOT`].
 pub fn is_private_path(path: &str) -> bool {
     path.starts_with(PRIVATE_ROOT)
@@ -44,7 +408,7 @@ fn normalize_path_filter(path: &str) -> Option<String> {
 
 #[cfg(test)]
 mod tests {
-    use super::is_private_path_filter;
+    use super::*;
 
     #[test]
     fn private_path_filter_matches_normalized_priv_directory() {
@@ -63,4 +427,85 @@ mod tests {
             assert_eq!(is_private_path_filter(path), expected, "{path}");
         }
     }
+
+    #[test]
+    fn sanitize_filename_keeps_a_safe_last_component() {
+        let cases = [
+            ("report.pdf", Some("report.pdf")),
+            ("../../etc/passwd", Some("passwd")),
+            ("C:\\Users\\me\\notes.txt", Some("notes.txt")),
+            (
+                "evil.txt\r\nSet-Cookie: x=1",
+                Some("evil.txtSet-Cookie: x=1"),
+            ),
+            ("  spaced.txt  ", Some("spaced.txt")),
+            ("dir/", None),
+            ("..", None),
+            ("\n", None),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(sanitize_filename(input).as_deref(), expected, "{input:?}");
+        }
+
+        let long = "é".repeat(200);
+        let cut = sanitize_filename(&long).unwrap();
+        assert!(cut.len() <= MAX_FILENAME_LENGTH);
+        assert!(cut.chars().all(|c| c == 'é'));
+    }
+
+    #[test]
+    fn attachment_disposition_round_trips() {
+        assert_eq!(
+            attachment_disposition("a \"quoted\" name.txt").unwrap(),
+            r#"attachment; filename="a \"quoted\" name.txt""#
+        );
+        assert_eq!(
+            attachment_disposition("naïve café.pdf").unwrap(),
+            "attachment; filename=\"na_ve caf_.pdf\"; filename*=UTF-8''na%C3%AFve%20caf%C3%A9.pdf"
+        );
+        for name in [
+            "plain.txt",
+            "a \"quoted\" name.txt",
+            "naïve café.pdf",
+            "日本語.txt",
+        ] {
+            let header = attachment_disposition(name).unwrap();
+            assert_eq!(
+                disposition_filename(&header).as_deref(),
+                Some(name),
+                "{header}"
+            );
+        }
+        assert_eq!(attachment_disposition("../"), None);
+    }
+
+    #[test]
+    fn disposition_filename_parses_common_forms() {
+        let cases = [
+            ("attachment; filename=report.pdf", Some("report.pdf")),
+            ("attachment; FILENAME=\"a;b.txt\"; size=3", Some("a;b.txt")),
+            (
+                "attachment; filename=\"fallback.txt\"; filename*=utf-8'en'%E2%82%AC.txt",
+                Some("€.txt"),
+            ),
+            (
+                "attachment; filename*=iso-8859-1''%E9t%E9.txt",
+                Some("été.txt"),
+            ),
+            ("attachment; filename=\"../secret\"", Some("secret")),
+            ("attachment; filename=\"unterminated", None),
+            ("attachment; filename*=UTF-8''%ZZ", None),
+            ("Attachment; filename=a.txt", Some("a.txt")),
+            ("attachment", None),
+            ("inline", None),
+            ("inline; filename=report.pdf", None),
+        ];
+        for (header, expected) in cases {
+            assert_eq!(
+                disposition_filename(header).as_deref(),
+                expected,
+                "{header}"
+            );
+        }
+    }
 }
@@ -5,14 +5,14 @@ use std::time::Duration;
 use super::routes::{
     admin_events, dav_handler, delete_entry,
     disable_users::{disable_user, enable_user},
-    generate_signup_token, info, root, signup_tokens, user_quota,
+    events_export, generate_signup_token, info, root, signup_policy, signup_tokens, user_quota,
 };
 use super::trace::with_trace_layer;
 use super::{app_state::AppState, auth_middleware::AdminAuthLayer};
 use crate::AppContext;
 #[cfg(any(test, feature = "testing"))]
 use crate::MockDataDir;
-use crate::{AppContextConversionError, PersistentDataDir};
+use crate::{AppContextConversionError, PersistentDataDir, SignupMode};
 use axum::routing::{any, delete, post};
 use axum::{routing::get, Router};
 use axum_server::Handle;
@@ -29,7 +29,14 @@ fn create_protected_router(password: &str) -> Router<AppState> {
         )
         .route("/info", get(info::info))
         .route("/events-stream", get(admin_events::feed_stream))
+        .route("/events-export", get(events_export::export_events))
         .route("/signup_tokens", get(signup_tokens::list_signup_tokens))
+        .route(
+            "/signup-policy",
+            get(signup_policy::get_signup_policy)
+                .put(signup_policy::put_signup_policy)
+                .delete(signup_policy::delete_signup_policy),
+        )
         .route("/webdav/{*entry_path}", delete(delete_entry::delete_entry))
         .route("/users/{pubkey}/disable", post(disable_user))
         .route("/users/{pubkey}/enable", post(enable_user))
@@ -126,7 +133,8 @@ impl AdminServer {
             context.keypair.public_key().z32(),
             &context.config_toml,
             env!("CARGO_PKG_VERSION"),
-        );
+        )
+        .with_signup_policy_service(context.signup_policy_service.clone());
         let socket = context.config_toml.admin.listen_socket;
         let app = create_app(state, password.as_str());
         let listener = std::net::TcpListener::bind(socket)
@@ -161,6 +169,12 @@ impl AdminServer {
         self.socket
     }
 
+    /// Shutdown the http server.
+    pub fn shutdown(&self) {
+        self.http_handle
+            .graceful_shutdown(Some(Duration::from_secs(5)));
+    }
+
     /// Create a signup token for the given homeserver.
     pub async fn create_signup_token(&self) -> anyhow::Result<String> {
         let admin_socket = self.listen_socket();
@@ -174,12 +188,54 @@ impl AdminServer {
         let body = response.text().await?;
         Ok(body)
     }
+
+    /// Get the signup mode currently in effect on the given homeserver.
+    pub async fn get_signup_policy(&self) -> anyhow::Result<SignupMode> {
+        let admin_socket = self.listen_socket();
+        let url = format!("http://{}/signup-policy", admin_socket);
+        let response = reqwest::Client::new()
+            .get(url)
+            .header("X-Admin-Password", &self.password)
+            .send()
+            .await?
+            .error_for_status()?;
+        let policy: signup_policy::SignupPolicy = serde_json::from_str(&response.text().await?)?;
+        Ok(policy.mode)
+    }
+
+    /// Set the signup policy of the given homeserver, overriding its config.
+    pub async fn set_signup_policy(&self, mode: SignupMode) -> anyhow::Result<()> {
+        let admin_socket = self.listen_socket();
+        let url = format!("http://{}/signup-policy", admin_socket);
+        reqwest::Client::new()
+            .put(url)
+            .header("X-Admin-Password", &self.password)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(serde_json::json!({ "mode": mode }).to_string())
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    /// Clear the signup policy set through [`Self::set_signup_policy`],
+    /// so the homeserver uses the signup mode from its config again.
+    pub async fn clear_signup_policy(&self) -> anyhow::Result<()> {
+        let admin_socket = self.listen_socket();
+        let url = format!("http://{}/signup-policy", admin_socket);
+        reqwest::Client::new()
+            .delete(url)
+            .header("X-Admin-Password", &self.password)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
 }
 
 impl Drop for AdminServer {
     fn drop(&mut self) {
-        self.http_handle
-            .graceful_shutdown(Some(Duration::from_secs(5)));
+        self.shutdown();
         self.join_handle.abort();
     }
 }
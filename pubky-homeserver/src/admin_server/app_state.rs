@@ -7,8 +7,9 @@ use crate::persistence::{
     files::{events::EventsService, FileService},
     sql::SqlDb,
 };
+use crate::services::signup_policy_service::SignupPolicyService;
 use crate::services::user_service::UserService;
-use crate::ConfigToml;
+use crate::{ConfigToml, SignupMode};
 
 #[derive(Clone, Default)]
 pub(crate) struct AdminMetadata {
@@ -34,6 +35,8 @@ pub(crate) struct AppState {
     /// System-wide default quotas for resolving effective values.
     pub(crate) default_storage_mb: Option<u64>,
     pub(crate) default_quotas: DefaultQuotasToml,
+    /// Runtime signup policy, shared with the client server.
+    pub(crate) signup_policy_service: SignupPolicyService,
 }
 
 impl AppState {
@@ -52,6 +55,7 @@ impl AppState {
             .strip_prefix("/dav")
             .autoindex(true)
             .build_handler();
+        let signup_policy_service = SignupPolicyService::new(sql_db.clone(), SignupMode::default());
         Self {
             sql_db,
             file_service,
@@ -63,9 +67,18 @@ impl AppState {
             metrics,
             default_storage_mb: None,
             default_quotas: DefaultQuotasToml::default(),
+            signup_policy_service,
         }
     }
 
+    pub fn with_signup_policy_service(
+        mut self,
+        signup_policy_service: SignupPolicyService,
+    ) -> Self {
+        self.signup_policy_service = signup_policy_service;
+        self
+    }
+
     pub fn with_metadata_from_config(
         mut self,
         public_key: String,
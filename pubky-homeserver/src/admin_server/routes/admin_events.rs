@@ -70,11 +70,8 @@ fn parse_admin_stream_query(query: &str) -> Result<AdminStreamParams, HttpError>
                     users.push(pk);
                 }
             }
-            "cursor" => {
-                if !value.is_empty() {
-                    cursor = Some(value.to_string());
-                }
-            }
+            "cursor" if !value.is_empty() => cursor = Some(value.to_string()),
+            "cursor" => {}
             "limit" => {
                 let parsed = value
                     .parse::<u16>()
@@ -0,0 +1,107 @@
+//! Admin-only bulk export of the event history as newline-delimited JSON, for analytics and
+//! backups. Unlike `/events-stream` it never goes live: it replays `from < cursor <= to` (both
+//! optional) and closes. The body is streamed page by page, so a client whose download breaks
+//! resumes by passing the `cursor` of the last line it received as `from`.
+
+use axum::{
+    body::Body,
+    extract::{Query, State},
+    http::{header, HeaderValue},
+    response::IntoResponse,
+};
+use bytes::Bytes;
+use futures_util::StreamExt;
+use pubky_common::events::EventCursor;
+use serde::{Deserialize, Serialize};
+
+use super::super::app_state::AppState;
+use crate::{
+    persistence::files::events::EventEntity,
+    shared::{HttpError, HttpResult},
+};
+
+/// Query parameters of the export.
+#[derive(Deserialize)]
+pub(crate) struct ExportQuery {
+    /// Exclusive lower bound. None = from the beginning.
+    from: Option<String>,
+    /// Inclusive upper bound. None = up to the latest event.
+    to: Option<String>,
+}
+
+/// One line of the export.
+#[derive(Serialize)]
+struct ExportLine {
+    cursor: String,
+    #[serde(rename = "type")]
+    event_type: String,
+    /// Full `pubky://` URI of the resource.
+    resource: String,
+    /// Base64 blake3 hash of the written content; `null` for deletions.
+    hash: Option<String>,
+    /// Creation time, in microseconds since the Unix epoch.
+    timestamp: i64,
+}
+
+impl From<&EventEntity> for ExportLine {
+    fn from(event: &EventEntity) -> Self {
+        Self {
+            cursor: event.cursor().to_string(),
+            event_type: event.event_type.to_string(),
+            resource: event.pubky_uri(),
+            hash: event.event_type.content_hash().map(|hash| {
+                base64::Engine::encode(&base64::engine::general_purpose::STANDARD, hash.as_bytes())
+            }),
+            timestamp: event.created_at.and_utc().timestamp_micros(),
+        }
+    }
+}
+
+/// Parse an optional cursor parameter, accepting the same formats as the event streams.
+async fn parse_bound(state: &AppState, value: Option<&str>) -> HttpResult<Option<EventCursor>> {
+    match value.filter(|v| !v.is_empty()) {
+        Some(c) => Ok(Some(
+            state
+                .events_service
+                .parse_cursor(c, &mut state.sql_db.pool().into())
+                .await
+                .map_err(|_| HttpError::bad_request("Invalid cursor"))?,
+        )),
+        None => Ok(None),
+    }
+}
+
+/// Stream every event (public and private) in `from < cursor <= to` as `application/x-ndjson`,
+/// oldest first. Response is `Cache-Control: no-store`.
+pub async fn export_events(
+    State(state): State<AppState>,
+    Query(params): Query<ExportQuery>,
+) -> HttpResult<impl IntoResponse> {
+    let from = parse_bound(&state, params.from.as_deref()).await?;
+    let to = parse_bound(&state, params.to.as_deref()).await?;
+
+    let lines = state
+        .events_service
+        .export_stream(state.sql_db.clone(), from, to)
+        .map(|event| {
+            let event = event.inspect_err(|e| {
+                tracing::error!("Database error while exporting events: {}", e);
+            })?;
+            let mut line = serde_json::to_vec(&ExportLine::from(&event))
+                .expect("export line is always serializable");
+            line.push(b'\n');
+            Ok::<_, sqlx::Error>(Bytes::from(line))
+        });
+
+    // The export surfaces private paths; never cache it.
+    Ok((
+        [
+            (
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/x-ndjson"),
+            ),
+            (header::CACHE_CONTROL, HeaderValue::from_static("no-store")),
+        ],
+        Body::from_stream(lines),
+    ))
+}
@@ -2,8 +2,10 @@ pub(crate) mod admin_events;
 pub(crate) mod dav_handler;
 pub(crate) mod delete_entry;
 pub(crate) mod disable_users;
+pub(crate) mod events_export;
 pub(crate) mod generate_signup_token;
 pub(crate) mod info;
 pub(crate) mod root;
+pub(crate) mod signup_policy;
 pub(crate) mod signup_tokens;
 pub(crate) mod user_quota;
@@ -0,0 +1,125 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use serde::{Deserialize, Serialize};
+
+use crate::shared::HttpResult;
+use crate::SignupMode;
+
+use super::super::app_state::AppState;
+
+/// Body of `GET /signup-policy` and `PUT /signup-policy`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SignupPolicy {
+    /// `open`, `token_required` or `closed`.
+    pub mode: SignupMode,
+}
+
+/// GET /signup-policy — return the signup mode currently in effect.
+pub async fn get_signup_policy(State(state): State<AppState>) -> HttpResult<impl IntoResponse> {
+    let mode = state
+        .signup_policy_service
+        .current(&mut state.sql_db.pool().into())
+        .await?;
+    Ok(Json(SignupPolicy { mode }))
+}
+
+/// PUT /signup-policy — switch the signup mode without a restart.
+///
+/// The policy is persisted and takes precedence over `[general].signup_mode`
+/// in the config, including after restarts.
+pub async fn put_signup_policy(
+    State(state): State<AppState>,
+    Json(policy): Json<SignupPolicy>,
+) -> HttpResult<impl IntoResponse> {
+    state.signup_policy_service.set(&policy.mode).await?;
+    tracing::info!("Signup policy set to {}", policy.mode.as_str());
+    Ok(StatusCode::OK)
+}
+
+/// DELETE /signup-policy — drop the stored policy and return to `[general].signup_mode`
+/// from the config.
+pub async fn delete_signup_policy(State(state): State<AppState>) -> HttpResult<impl IntoResponse> {
+    state.signup_policy_service.clear().await?;
+    tracing::info!("Signup policy cleared, using the config again");
+    Ok(StatusCode::OK)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum_test::TestServer;
+
+    use super::*;
+    use crate::admin_server::app::create_app;
+    use crate::persistence::files::FileService;
+    use crate::AppContext;
+
+    fn create_test_server(context: &AppContext) -> TestServer {
+        TestServer::new(create_app(
+            AppState::new(
+                context.sql_db.clone(),
+                FileService::new_from_context(context).unwrap(),
+                "",
+                context.user_service.clone(),
+                context.events_service.clone(),
+                context.metrics.clone(),
+            )
+            .with_signup_policy_service(context.signup_policy_service.clone()),
+            "test",
+        ))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn test_signup_policy_get_and_put() {
+        let context = AppContext::test().await;
+        let server = create_test_server(&context);
+
+        let response = server
+            .get("/signup-policy")
+            .add_header("X-Admin-Password", "test")
+            .expect_success()
+            .await;
+        let json: serde_json::Value = response.json();
+        assert_eq!(json["mode"], "open", "Test config default");
+
+        server
+            .put("/signup-policy")
+            .add_header("X-Admin-Password", "test")
+            .json(&serde_json::json!({ "mode": "closed" }))
+            .expect_success()
+            .await;
+        let response = server
+            .get("/signup-policy")
+            .add_header("X-Admin-Password", "test")
+            .expect_success()
+            .await;
+        let json: serde_json::Value = response.json();
+        assert_eq!(json["mode"], "closed");
+
+        server
+            .delete("/signup-policy")
+            .add_header("X-Admin-Password", "test")
+            .expect_success()
+            .await;
+        let response = server
+            .get("/signup-policy")
+            .add_header("X-Admin-Password", "test")
+            .expect_success()
+            .await;
+        let json: serde_json::Value = response.json();
+        assert_eq!(json["mode"], "open", "Back to the config");
+
+        let response = server
+            .put("/signup-policy")
+            .add_header("X-Admin-Password", "test")
+            .json(&serde_json::json!({ "mode": "invite_only" }))
+            .await;
+        assert!(response.status_code().is_client_error());
+
+        let response = server
+            .put("/signup-policy")
+            .json(&serde_json::json!({ "mode": "open" }))
+            .await;
+        response.assert_status_unauthorized();
+    }
+}
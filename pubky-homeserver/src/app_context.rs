@@ -5,6 +5,7 @@
 //! Create with a `DataDir` instance: `AppContext::try_from(data_dir)`
 //!
 
+use crate::services::signup_policy_service::SignupPolicyService;
 use crate::services::user_service::UserService;
 #[cfg(any(test, feature = "testing"))]
 use crate::MockDataDir;
@@ -83,13 +84,14 @@ pub struct AppContext {
     pub(crate) metrics: Metrics,
     /// Background listener for Postgres event notifications.
     /// Enables cross-instance event propagation for /events-stream's SSE functionality.
-    /// Kept alive for the background task, not for direct access.
-    _pg_event_listener: Arc<PgEventListener>,
+    pub(crate) pg_event_listener: Arc<PgEventListener>,
     /// Auth revocations are forwarded to private SSE streams on this instance.
     /// Its Postgres listener stops once the last clone is dropped.
     pub(crate) auth_revocation_service: AuthRevocationService,
     /// User service for quota resolution and user creation with defaults.
     pub(crate) user_service: UserService,
+    /// Signup mode in effect, as set at runtime by admins or else from the config.
+    pub(crate) signup_policy_service: SignupPolicyService,
 }
 
 impl AppContext {
@@ -131,6 +133,12 @@ impl AppContext {
             .map_err(AppContextConversionError::AuthRevocationService)?;
 
         let user_service = UserService::new(sql_db.clone());
+        let signup_policy_service =
+            SignupPolicyService::new(sql_db.clone(), conf.general.signup_mode.clone());
+        signup_policy_service
+            .warn_if_overridden()
+            .await
+            .map_err(AppContextConversionError::SqlDb)?;
 
         let file_service = FileService::new_from_config(
             &conf,
@@ -155,9 +163,10 @@ impl AppContext {
             data_dir: Arc::new(dir),
             events_service,
             metrics: Metrics::new().map_err(AppContextConversionError::Metrics)?,
-            _pg_event_listener: Arc::new(pg_event_listener),
+            pg_event_listener: Arc::new(pg_event_listener),
             auth_revocation_service,
             user_service,
+            signup_policy_service,
         })
     }
 }
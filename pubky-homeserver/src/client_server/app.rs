@@ -5,10 +5,12 @@ use crate::MockDataDir;
 
 use crate::{
     app_context::{AppContext, AppContextConversionError},
+    services::list_snapshot_service::ListSnapshotService,
     PersistentDataDir,
 };
 use anyhow::Result;
 use futures_util::TryFutureExt;
+use pubky_common::clock::SERVER_TIME_PATH;
 
 use std::net::TcpListener;
 use std::path::PathBuf;
@@ -26,12 +28,14 @@ use tower_http::cors::CorsLayer;
 
 use super::auth::{self, AuthenticationLayer};
 use super::cache_policy;
+use super::extensions::RouterExtensions;
 use super::middleware::{
-    pubky_host::PubkyHostLayer,
+    access_log::{record_attribution, with_access_log, AccessLog},
+    pubky_host::{with_dav_paths, PubkyHostLayer},
     rate_limiter::{BandwidthQuotaLimitLayer, RequestRateLimitLayer},
     trace::with_trace_layer,
 };
-use super::routes::{events, root, signup_tokens, tenants};
+use super::routes::{events, root, signup_tokens, tenants, time};
 
 /// Errors that can occur when building a `HomeserverCore`.
 #[derive(Debug, thiserror::Error)]
@@ -48,6 +52,9 @@ pub enum ClientServerBuildError {
     /// Failed to build request-count rate limit layer.
     #[error("Request-count rate limit configuration error: {0}")]
     RequestRateLimits(String),
+    /// Failed to open the access log.
+    #[error("Access log error: {0}")]
+    AccessLog(std::io::Error),
 }
 /// A Pubky homeserver with ICANN HTTP and Pubky TLS servers.
 pub struct ClientServer {
@@ -90,7 +97,16 @@ impl ClientServer {
 
     /// Start homeserver services with the given application context.
     pub async fn start(context: AppContext) -> std::result::Result<Self, ClientServerBuildError> {
-        let router = Self::create_router(&context)?;
+        Self::start_with_extensions(context, RouterExtensions::default()).await
+    }
+
+    /// Start homeserver services with extra routes and middleware composed into the router.
+    /// See [`RouterExtensions`].
+    pub async fn start_with_extensions(
+        context: AppContext,
+        extensions: RouterExtensions,
+    ) -> std::result::Result<Self, ClientServerBuildError> {
+        let router = Self::create_router_with_extensions(&context, &extensions)?;
 
         let (icann_http_handle, icann_http_socket) =
             Self::start_icann_http_server(&context, router.clone())
@@ -109,21 +125,34 @@ impl ClientServer {
         })
     }
 
+    #[cfg(test)]
     pub(crate) fn create_router(
         context: &AppContext,
+    ) -> std::result::Result<Router, ClientServerBuildError> {
+        Self::create_router_with_extensions(context, &RouterExtensions::default())
+    }
+
+    pub(crate) fn create_router_with_extensions(
+        context: &AppContext,
+        extensions: &RouterExtensions,
     ) -> std::result::Result<Router, ClientServerBuildError> {
         let state = AppState {
             auth_state: auth::AuthState::new(context),
             auth_revocation_service: context.auth_revocation_service.clone(),
             sql_db: context.sql_db.clone(),
             file_service: context.file_service.clone(),
-            signup_mode: context.config_toml.general.signup_mode.clone(),
+            signup_policy_service: context.signup_policy_service.clone(),
             metrics: context.metrics.clone(),
             events_service: context.events_service.clone(),
             user_service: context.user_service.clone(),
             default_storage_mb: context.config_toml.storage.default_quota_mb,
+            max_path_length: context.config_toml.storage.max_path_length,
+            max_path_segments: context.config_toml.storage.max_path_segments,
+            content_policy: context.config_toml.content_policy.clone(),
+            list_snapshot_service: ListSnapshotService::new(context.sql_db.clone()),
+            keypair: context.keypair.clone(),
         };
-        super::create_app(state.clone(), context)
+        super::create_app(state.clone(), context, extensions)
     }
 
     /// Start the ICANN HTTP server
@@ -210,6 +239,7 @@ fn base() -> Router<AppState> {
     Router::new()
         .route("/", get(root::handler))
         .route("/signup_tokens/{token}", get(signup_tokens::get))
+        .route(SERVER_TIME_PATH, get(time::handler))
         // Events
         .route("/events/", get(events::feed))
         .route(
@@ -226,11 +256,16 @@ fn base() -> Router<AppState> {
 pub fn create_app(
     state: AppState,
     context: &AppContext,
+    extensions: &RouterExtensions,
 ) -> std::result::Result<Router, ClientServerBuildError> {
     let auth_state = state.auth_state.clone();
     let request_rate_limit_layer =
         RequestRateLimitLayer::from_path_limits(context.config_toml.drive.rate_limits.clone())
-            .map_err(ClientServerBuildError::RequestRateLimits)?;
+            .and_then(|layer| {
+                layer.with_class_limits(context.config_toml.drive.class_rate_limits.clone())
+            })
+            .map_err(ClientServerBuildError::RequestRateLimits)?
+            .with_metrics(context.metrics.clone());
 
     let middleware = ServiceBuilder::new()
         // Request order matters: auth needs PubkyHost and CookieManager, and
@@ -239,6 +274,7 @@ pub fn create_app(
         .layer(CookieManagerLayer::new())
         .layer(request_rate_limit_layer)
         .layer(AuthenticationLayer::new(auth_state.clone()))
+        .layer(axum_middleware::from_fn(record_attribution))
         .layer(BandwidthQuotaLimitLayer::new(
             context.user_service.clone(),
             context.config_toml.default_quotas.clone(),
@@ -250,14 +286,28 @@ pub fn create_app(
         .with_state(state)
         .merge(auth::base_router(auth_state.clone()))
         .merge(auth::tenant_router(auth_state))
+        .merge(extensions.routes())
         .layer(middleware);
+    let mut app = extensions.apply_layers(app);
+
+    let access_log = AccessLog::from_config(
+        &context.config_toml.access_log,
+        &context.config_toml.drive.trusted_proxies,
+        context.data_dir.path(),
+    )
+    .map_err(ClientServerBuildError::AccessLog)?;
+    if let Some(access_log) = access_log {
+        app = with_access_log(app, access_log);
+    }
 
-    // Apply tracing to the complete router.
-    Ok(with_trace_layer(app))
+    // Apply tracing to the complete router, then expose it under `/dav/{pubkey}/` too.
+    Ok(with_dav_paths(with_trace_layer(app)))
 }
 
 #[cfg(test)]
 mod tests {
+    use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
     use axum::http::{header, Method, StatusCode};
     use axum_test::TestServer;
     use pubky_common::{auth::AuthToken, capabilities::Capability, crypto::Keypair};
@@ -265,7 +315,7 @@ mod tests {
     use crate::{
         app_context::AppContext,
         client_server::ClientServer,
-        quota_config::{GlobPattern, HttpMethod, LimitKeyType, PathLimit},
+        quota_config::{ClassLimit, GlobPattern, HttpMethod, LimitKeyType, PathLimit, RouteClass},
         ConfigToml, MockDataDir,
     };
 
@@ -306,6 +356,208 @@ mod tests {
         response.assert_status(StatusCode::TOO_MANY_REQUESTS);
     }
 
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn class_rate_limits_reject_with_retry_after_and_spare_health() {
+        let mut config = ConfigToml::minimal_test_config();
+        config.drive.class_rate_limits = vec![ClassLimit {
+            class: RouteClass::Write,
+            quota: "1r/m".parse().unwrap(),
+            key: LimitKeyType::Ip,
+            burst: None,
+            whitelist: Vec::new(),
+        }];
+        let data_dir = MockDataDir::new(config, None).unwrap();
+        let context = AppContext::read_from(data_dir).await.unwrap();
+        let router = ClientServer::create_router(&context).unwrap();
+        let server = TestServer::new(router).unwrap();
+        let user = Keypair::random().public_key();
+        let put = || {
+            server
+                .put("/pub/app/file.txt")
+                .add_header("host", user.z32())
+                .add_header("x-forwarded-for", "203.0.113.7")
+                .bytes(vec![0u8].into())
+        };
+
+        // Unauthenticated, but the write still counts against the class limit.
+        put().await.assert_status(StatusCode::UNAUTHORIZED);
+        let response = put().await;
+        response.assert_status(StatusCode::TOO_MANY_REQUESTS);
+        let retry_after: u64 = response.headers()[header::RETRY_AFTER]
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!((1..=60).contains(&retry_after), "{retry_after}");
+
+        // Reads and health checks are not in the write class.
+        for path in ["/", "/events/"] {
+            server
+                .get(path)
+                .add_header("x-forwarded-for", "203.0.113.7")
+                .expect_success()
+                .await;
+        }
+        let metrics = context.metrics.render().unwrap();
+        assert!(
+            metrics.contains("rate_limited_request_count") && metrics.contains("scope=\"write\""),
+            "{metrics}"
+        );
+    }
+
+    /// A server over real HTTP, so requests carry their peer address, and a forged
+    /// signin token.
+    async fn lockout_test_server(trusted_proxies: Vec<IpAddr>) -> (TestServer, Keypair, Vec<u8>) {
+        let mut config = ConfigToml::minimal_test_config();
+        config.drive.trusted_proxies = trusted_proxies;
+        let data_dir = MockDataDir::new(config, None).unwrap();
+        let context = AppContext::read_from(data_dir).await.unwrap();
+        let router = ClientServer::create_router(&context).unwrap();
+        let server = TestServer::builder()
+            .http_transport()
+            .build(router.into_make_service_with_connect_info::<SocketAddr>())
+            .unwrap();
+        let user = Keypair::random();
+        let mut forged = AuthToken::sign(&user, vec![Capability::root()]).serialize();
+        forged[0] ^= 1;
+        (server, user, forged)
+    }
+
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn repeated_failed_signins_are_locked_out_per_ip() {
+        let (server, user, forged) = lockout_test_server(vec![Ipv4Addr::LOCALHOST.into()]).await;
+
+        let signin = |ip: &'static str| {
+            server
+                .post("/session")
+                .add_header("host", user.public_key().z32())
+                .add_header("x-forwarded-for", ip)
+                .bytes(forged.clone().into())
+        };
+
+        for _ in 0..super::auth::lockout::FREE_ATTEMPTS + 1 {
+            signin("203.0.113.7")
+                .await
+                .assert_status(StatusCode::BAD_REQUEST);
+        }
+
+        let response = signin("203.0.113.7").await;
+        response.assert_status(StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "1");
+
+        signin("198.51.100.1")
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn spoofed_forwarding_headers_do_not_evade_the_lockout() {
+        let (server, user, forged) = lockout_test_server(vec![]).await;
+
+        let signin = |ip: String| {
+            server
+                .post("/session")
+                .add_header("host", user.public_key().z32())
+                .add_header("x-forwarded-for", ip.clone())
+                .add_header("x-real-ip", ip)
+                .bytes(forged.clone().into())
+        };
+
+        // A fresh forged address on every attempt, all from the same peer.
+        for i in 0..super::auth::lockout::FREE_ATTEMPTS + 1 {
+            signin(format!("203.0.113.{i}"))
+                .await
+                .assert_status(StatusCode::BAD_REQUEST);
+        }
+
+        signin("198.51.100.1".to_string())
+            .await
+            .assert_status(StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn extensions_add_routes_and_layers() {
+        use axum::{extract::Request, middleware::Next, response::Response, routing::get, Router};
+
+        use crate::RouterExtensions;
+
+        async fn tag_response(request: Request, next: Next) -> Response {
+            let mut response = next.run(request).await;
+            response
+                .headers_mut()
+                .insert("x-embedder", "1".parse().unwrap());
+            response
+        }
+
+        let extensions = RouterExtensions::new()
+            .merge(Router::new().route("/custom/health", get(|| async { "healthy" })))
+            .layer(axum::middleware::from_fn(tag_response));
+
+        let data_dir = MockDataDir::new(ConfigToml::minimal_test_config(), None).unwrap();
+        let context = AppContext::read_from(data_dir).await.unwrap();
+        let router = ClientServer::create_router_with_extensions(&context, &extensions).unwrap();
+        let server = TestServer::new(router).unwrap();
+
+        // The extra route is served, and the extra layer wraps it...
+        let response = server.get("/custom/health").expect_success().await;
+        response.assert_text("healthy");
+        assert_eq!(response.headers()["x-embedder"], "1");
+
+        // ...as well as the homeserver's own routes.
+        let response = server.get("/").expect_success().await;
+        assert_eq!(response.headers()["x-embedder"], "1");
+    }
+
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn dav_paths_address_the_tenant_in_the_path() {
+        let data_dir = MockDataDir::new(ConfigToml::minimal_test_config(), None).unwrap();
+        let context = AppContext::read_from(data_dir).await.unwrap();
+        let router = ClientServer::create_router(&context).unwrap();
+        let server = TestServer::new(router).unwrap();
+        let user = Keypair::random();
+        let other = Keypair::random();
+        let dav = |path: &str| format!("/dav/{}{path}", user.public_key().z32());
+
+        let cookie = signup_cookie(&server, &user).await;
+
+        // The path wins over any host, so the URL works whatever host it is sent to.
+        server
+            .put(&dav("/pub/hello.txt"))
+            .add_header("host", other.public_key().z32())
+            .add_header(header::COOKIE, cookie.clone())
+            .bytes("hello".into())
+            .expect_success()
+            .await;
+        server
+            .get(&dav("/session"))
+            .add_header(header::COOKIE, cookie)
+            .expect_success()
+            .await;
+        server
+            .get(&dav("/pub/hello.txt"))
+            .expect_success()
+            .await
+            .assert_text("hello");
+
+        // The host-addressed route serves the same entry.
+        server
+            .get("/pub/hello.txt")
+            .add_header("host", user.public_key().z32())
+            .expect_success()
+            .await
+            .assert_text("hello");
+
+        server
+            .get("/dav/not-a-key/pub/hello.txt")
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+    }
+
     async fn signup_cookie(server: &TestServer, keypair: &Keypair) -> String {
         let auth_token = AuthToken::sign(keypair, vec![Capability::root()]);
         let body_bytes: axum::body::Bytes = auth_token.serialize().into();
@@ -1,13 +1,16 @@
 use axum::extract::FromRef;
+use pubky_common::crypto::Keypair;
 
 use crate::client_server::auth::AuthRevocationService;
 use crate::client_server::auth::AuthState;
+use crate::data_directory::ContentPolicyToml;
 use crate::observability::Metrics;
 use crate::persistence::files::events::EventsService;
 use crate::persistence::files::FileService;
 use crate::persistence::sql::SqlDb;
+use crate::services::list_snapshot_service::ListSnapshotService;
+use crate::services::signup_policy_service::SignupPolicyService;
 use crate::services::user_service::UserService;
-use crate::SignupMode;
 
 #[derive(Clone, Debug)]
 pub(crate) struct AppState {
@@ -18,13 +21,24 @@ pub(crate) struct AppState {
     /// The SQL database connection.
     pub(crate) sql_db: SqlDb,
     pub(crate) file_service: FileService,
-    pub(crate) signup_mode: SignupMode,
+    /// Signup mode in effect, consulted by the signup token endpoint.
+    pub(crate) signup_policy_service: SignupPolicyService,
     pub(crate) events_service: EventsService,
     pub(crate) metrics: Metrics,
     /// User service for user lookups, creation, and cache access.
     pub(crate) user_service: UserService,
     /// Default per-user storage quota in MB (from `[storage].default_quota_mb`).
     pub(crate) default_storage_mb: Option<u64>,
+    /// Maximum decoded length of a written path (from `[storage].max_path_length`).
+    pub(crate) max_path_length: usize,
+    /// Maximum segment count of a written path (from `[storage].max_path_segments`).
+    pub(crate) max_path_segments: usize,
+    /// Content types users may store (from `[content_policy]`).
+    pub(crate) content_policy: ContentPolicyToml,
+    /// Open point-in-time snapshots for consistent paginated listings.
+    pub(crate) list_snapshot_service: ListSnapshotService,
+    /// The homeserver keypair, used to sign event feed entries.
+    pub(crate) keypair: Keypair,
 }
 
 impl FromRef<AppState> for AuthState {
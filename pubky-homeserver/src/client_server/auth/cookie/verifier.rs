@@ -1,16 +1,17 @@
 //! Server-side [AuthToken] verification with replay protection.
 
-use std::sync::{Arc, Mutex};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 use pubky_common::{
-    auth::{AuthToken, Error},
+    auth::{AuthToken, Error, DEFAULT_CLOCK_SKEW_TOLERANCE},
+    clock::SystemClock,
     crypto::PublicKey,
     timestamp::Timestamp,
 };
 
-/// 3 minutes in the past or the future (matching the AuthToken timestamp window).
-const TIMESTAMP_WINDOW: i64 = 180 * 1_000_000;
-
 /// Uniquely identifies an [AuthToken] by its timestamp and public key.
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct TokenId {
@@ -53,10 +54,10 @@ impl ReplayGuard {
         }
     }
 
-    /// Remove entries older than twice the [TIMESTAMP_WINDOW],
+    /// Remove entries older than twice the accepted timestamp `window`,
     /// since they can never be replayed.
-    fn gc(&mut self) {
-        let cutoff = Timestamp::now() - 2 * TIMESTAMP_WINDOW as u64;
+    fn gc(&mut self, window: Duration) {
+        let cutoff = Timestamp::now() - 2 * window.as_micros() as u64;
 
         let expired_count = self.seen.partition_point(|id| id.timestamp < cutoff);
 
@@ -64,17 +65,34 @@ impl ReplayGuard {
     }
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 /// Verifies [AuthToken]s and guards against replay attacks.
 pub struct CookieAuthVerifier {
     replay_guard: Arc<Mutex<ReplayGuard>>,
+    clock_skew_tolerance: Duration,
+}
+
+impl Default for CookieAuthVerifier {
+    fn default() -> Self {
+        Self::new(DEFAULT_CLOCK_SKEW_TOLERANCE)
+    }
 }
 
 impl CookieAuthVerifier {
+    /// Create a verifier accepting token timestamps up to `clock_skew_tolerance`
+    /// away from the homeserver's clock.
+    pub fn new(clock_skew_tolerance: Duration) -> Self {
+        Self {
+            replay_guard: Arc::default(),
+            clock_skew_tolerance,
+        }
+    }
+
     /// Verify an [AuthToken] by parsing it from its canonical binary representation,
     /// verifying its signature, and confirm it wasn't already used.
     pub fn verify(&self, bytes: &[u8]) -> Result<AuthToken, Error> {
-        let token = AuthToken::verify(bytes)?;
+        let token =
+            AuthToken::verify_with_tolerance(bytes, &SystemClock, self.clock_skew_tolerance)?;
 
         let id = TokenId {
             timestamp: token.timestamp(),
@@ -82,7 +100,7 @@ impl CookieAuthVerifier {
         };
 
         let mut guard = self.replay_guard.lock().unwrap_or_else(|e| e.into_inner());
-        guard.gc();
+        guard.gc(self.clock_skew_tolerance);
         guard.check_and_track(id)?;
 
         Ok(token)
@@ -124,7 +142,7 @@ mod tests {
 
         // Insert an "old" token ID (well beyond 2x the window)
         let old_id = TokenId {
-            timestamp: now - 3 * TIMESTAMP_WINDOW as u64,
+            timestamp: now - 3 * DEFAULT_CLOCK_SKEW_TOLERANCE.as_micros() as u64,
             public_key: signer.public_key(),
         };
         guard.check_and_track(old_id).unwrap();
@@ -139,7 +157,7 @@ mod tests {
         assert_eq!(guard.seen.len(), 2);
 
         // GC should remove the old entry but keep the recent one
-        guard.gc();
+        guard.gc(DEFAULT_CLOCK_SKEW_TOLERANCE);
 
         assert_eq!(guard.seen.len(), 1);
         assert_eq!(guard.seen[0], recent_id);
@@ -12,17 +12,15 @@ use pubky_common::{
     auth::{
         jws::{GrantId, PopNonce, POP_JWS_TYP},
         pop::PopProofClaims,
+        DEFAULT_CLOCK_SKEW_TOLERANCE,
     },
     crypto::PublicKey,
 };
 
 use super::jws_crypto::{self, JwsCompact};
 
-/// ±3 minutes — matches existing `AuthToken` `TIMESTAMP_WINDOW` in `pubky-common/src/auth.rs`.
-pub const POP_MAX_AGE_SECS: u64 = 180;
-
-/// Nonce GC threshold: 2x the PoP window to cover edge cases.
-pub const POP_NONCE_GC_THRESHOLD_SECS: u64 = 2 * POP_MAX_AGE_SECS;
+/// Default ±3 minutes, the [`DEFAULT_CLOCK_SKEW_TOLERANCE`] `AuthToken`s are checked with.
+pub const POP_MAX_AGE_SECS: u64 = DEFAULT_CLOCK_SKEW_TOLERANCE.as_secs();
 
 /// Verified PoP proof.
 #[derive(Clone, Debug)]
@@ -47,6 +45,8 @@ pub struct PopVerificationContext<'a> {
     pub expected_audience: &'a str,
     /// The Grant's `jti` — the PoP's `gid` must match.
     pub expected_grant_id: &'a GrantId,
+    /// How far `iat` may lie from the homeserver's clock, in seconds.
+    pub max_age_secs: u64,
 }
 
 impl PopProof {
@@ -55,7 +55,7 @@ impl PopProof {
     /// Checks:
     /// 1. Ed25519 signature is valid against `cnf_key`
     /// 2. `aud` matches the expected homeserver
-    /// 3. `iat` is within the ±3 minute window
+    /// 3. `iat` is within `max_age_secs` of now (±3 minutes by default)
     ///
     /// Nonce replay checking is done separately via the database.
     pub fn verify(compact: &JwsCompact, context: &PopVerificationContext) -> Result<Self, Error> {
@@ -63,7 +63,7 @@ impl PopProof {
         check_header_type(compact.as_str())?;
         check_audience(&raw, context.expected_audience)?;
         check_grant_binding(&raw, context.expected_grant_id)?;
-        check_timestamp(&raw)?;
+        check_timestamp(&raw, context.max_age_secs)?;
         parse_verified_pop(raw)
     }
 }
@@ -99,9 +99,9 @@ fn check_grant_binding(raw: &PopProofClaims, expected: &GrantId) -> Result<(), E
     Ok(())
 }
 
-fn check_timestamp(raw: &PopProofClaims) -> Result<(), Error> {
+fn check_timestamp(raw: &PopProofClaims, max_age_secs: u64) -> Result<(), Error> {
     let now = Utc::now().timestamp() as u64;
-    if now.abs_diff(raw.iat) > POP_MAX_AGE_SECS {
+    if now.abs_diff(raw.iat) > max_age_secs {
         return Err(Error::TimestampOutOfRange);
     }
     Ok(())
@@ -139,7 +139,7 @@ pub enum Error {
     #[error("PoP grant ID mismatch")]
     GrantIdMismatch,
 
-    /// The `iat` timestamp is outside the ±3 minute window.
+    /// The `iat` timestamp is outside the accepted window.
     #[error("PoP timestamp out of range")]
     TimestampOutOfRange,
 
@@ -188,6 +188,7 @@ mod tests {
             cnf_key: &cnf_key,
             expected_audience: &aud,
             expected_grant_id: &raw.gid,
+            max_age_secs: POP_MAX_AGE_SECS,
         };
         let pop = PopProof::verify(&compact, &context).unwrap();
         assert_eq!(pop.grant_id, raw.gid);
@@ -206,6 +207,7 @@ mod tests {
             cnf_key: &cnf_key,
             expected_audience: &aud,
             expected_grant_id: &raw.gid,
+            max_age_secs: POP_MAX_AGE_SECS,
         };
 
         let pop = PopProof::verify(&compact, &context).unwrap();
@@ -227,6 +229,7 @@ mod tests {
             cnf_key: &wrong_pk,
             expected_audience: &aud,
             expected_grant_id: &raw.gid,
+            max_age_secs: POP_MAX_AGE_SECS,
         };
 
         let result = PopProof::verify(&compact, &context);
@@ -245,6 +248,7 @@ mod tests {
             cnf_key: &cnf_key,
             expected_audience: "wrong-audience",
             expected_grant_id: &raw.gid,
+            max_age_secs: POP_MAX_AGE_SECS,
         };
 
         let result = PopProof::verify(&compact, &context);
@@ -265,6 +269,7 @@ mod tests {
             cnf_key: &cnf_key,
             expected_audience: &aud,
             expected_grant_id: &wrong_gid,
+            max_age_secs: POP_MAX_AGE_SECS,
         };
 
         let result = PopProof::verify(&compact, &context);
@@ -289,6 +294,7 @@ mod tests {
             cnf_key: &cnf_key,
             expected_audience: &aud,
             expected_grant_id: &raw.gid,
+            max_age_secs: POP_MAX_AGE_SECS,
         };
 
         let result = PopProof::verify(&compact, &context);
@@ -309,9 +315,35 @@ mod tests {
             cnf_key: &cnf_key,
             expected_audience: &aud,
             expected_grant_id: &raw.gid,
+            max_age_secs: POP_MAX_AGE_SECS,
         };
 
         let result = PopProof::verify(&compact, &context);
         assert!(matches!(result, Err(Error::TimestampOutOfRange)));
     }
+
+    #[test]
+    fn configured_window_accepts_skewed_timestamp() {
+        let client_kp = Keypair::random();
+        let hs_kp = Keypair::random();
+        let mut raw = make_valid_pop(&hs_kp);
+        raw.iat -= 10 * 60; // client clock 10 minutes behind
+        let compact = sign_pop(&client_kp, &raw);
+
+        let cnf_key = client_kp.public_key();
+        let aud = hs_kp.public_key().z32();
+        let mut context = PopVerificationContext {
+            cnf_key: &cnf_key,
+            expected_audience: &aud,
+            expected_grant_id: &raw.gid,
+            max_age_secs: POP_MAX_AGE_SECS,
+        };
+        assert!(matches!(
+            PopProof::verify(&compact, &context),
+            Err(Error::TimestampOutOfRange)
+        ));
+
+        context.max_age_secs = 15 * 60;
+        PopProof::verify(&compact, &context).unwrap();
+    }
 }
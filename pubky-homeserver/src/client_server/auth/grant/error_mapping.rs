@@ -33,6 +33,9 @@ impl From<AuthServiceError> for HttpError {
             AuthServiceError::SignupTokenAlreadyUsed => {
                 HttpError::unauthorized_with_message("Token already used")
             }
+            AuthServiceError::SignupsClosed => {
+                HttpError::new_with_message(StatusCode::FORBIDDEN, "Signups are closed")
+            }
             AuthServiceError::NonceReplay => {
                 HttpError::unauthorized_with_message("PoP nonce already used")
             }
@@ -42,7 +45,8 @@ impl From<AuthServiceError> for HttpError {
             AuthServiceError::GrantExpired => {
                 HttpError::unauthorized_with_message("Grant has expired")
             }
-            AuthServiceError::InvalidSignupGrant(message) => HttpError::bad_request(message),
+            AuthServiceError::InvalidSignupGrant(message)
+            | AuthServiceError::InvalidGrantLifetime(message) => HttpError::bad_request(message),
             AuthServiceError::SessionNotFound => {
                 HttpError::unauthorized_with_message("Session not found")
             }
@@ -130,6 +134,7 @@ mod tests {
             AuthServiceError::SignupTokenAlreadyUsed,
             StatusCode::UNAUTHORIZED,
         );
+        assert_status(AuthServiceError::SignupsClosed, StatusCode::FORBIDDEN);
     }
 
     #[test]
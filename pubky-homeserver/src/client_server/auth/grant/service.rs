@@ -20,9 +20,7 @@ use pubky_common::{
 use super::crypto::{
     grant_verifier::verify_grant,
     jws_crypto::JwsCompact,
-    pop_verifier::{
-        PopProof, PopVerificationContext, POP_MAX_AGE_SECS, POP_NONCE_GC_THRESHOLD_SECS,
-    },
+    pop_verifier::{PopProof, PopVerificationContext, POP_MAX_AGE_SECS},
     session_token::SessionBearer,
 };
 use super::persistence::{
@@ -43,6 +41,12 @@ const SIGNUP_CLIENT_ID: &str = "pubky.signup";
 /// Signup grants are single-use account creation proofs, not refresh grants.
 const MAX_SIGNUP_GRANT_LIFETIME_SECS: u64 = 5 * 60;
 
+/// Shortest accepted session grant lifetime (`exp - iat`): 1 minute.
+pub(crate) const MIN_SESSION_GRANT_LIFETIME_SECS: u64 = 60;
+
+/// Longest accepted session grant lifetime (`exp - iat`): 2 years.
+pub(crate) const MAX_SESSION_GRANT_LIFETIME_SECS: u64 = 2 * 365 * 24 * 3600;
+
 /// Facade for all grant-based auth operations.
 ///
 /// Constructed once and stored in `AppState`. Encapsulates the verify → persist
@@ -52,6 +56,8 @@ pub struct GrantAuthService {
     sql_db: SqlDb,
     homeserver_public_key: PublicKey,
     signup_service: SignupService,
+    /// Accepted distance between client timestamps (`PoP` `iat`) and our clock, in seconds.
+    clock_skew_tolerance_secs: u64,
 }
 
 impl GrantAuthService {
@@ -65,9 +71,17 @@ impl GrantAuthService {
             sql_db,
             homeserver_public_key,
             signup_service,
+            clock_skew_tolerance_secs: POP_MAX_AGE_SECS,
         }
     }
 
+    /// Accept `PoP` proofs and signup grants whose timestamps are up to `secs` away from
+    /// the homeserver's clock (default [`POP_MAX_AGE_SECS`]).
+    pub fn with_clock_skew_tolerance(mut self, secs: u64) -> Self {
+        self.clock_skew_tolerance_secs = secs;
+        self
+    }
+
     /// The homeserver's public key (used by tests as the PoP audience).
     #[cfg(test)]
     pub fn homeserver_public_key(&self) -> pubky_common::crypto::PublicKey {
@@ -247,13 +261,19 @@ impl GrantAuthService {
     }
 
     /// Shared verification pipeline: verify grant → check revocation → verify PoP → check nonce.
+    ///
+    /// Lifetime bounds only apply when a grant is first used, so grants stored before
+    /// the bounds existed can still be refreshed until they expire.
     async fn verify_grant_and_pop(
         &self,
         grant_jws: &JwsCompact,
         pop_jws: &JwsCompact,
     ) -> Result<GrantClaims, AuthServiceError> {
         let grant = self.verify_grant(grant_jws)?;
-        self.check_grant_not_revoked(&grant).await?;
+        let is_stored = self.check_grant_not_revoked(&grant).await?;
+        if !is_stored {
+            Self::require_bounded_session_lifetime(&grant)?;
+        }
         let pop = self.verify_pop_proof(pop_jws, &grant)?;
         self.check_nonce_replay(&pop).await?;
         Ok(grant)
@@ -268,7 +288,7 @@ impl GrantAuthService {
         let grant = self.verify_grant(grant_jws)?;
         Self::require_signup_client_id(&grant)?;
         Self::require_root_capability_claim(&grant)?;
-        Self::require_short_signup_lifetime(&grant)?;
+        self.require_short_signup_lifetime(&grant)?;
         let pop = self.verify_pop_proof(pop_jws, &grant)?;
         self.check_nonce_replay(&pop).await?;
         Ok(grant)
@@ -290,9 +310,9 @@ impl GrantAuthService {
         Err(AuthServiceError::RootCapabilityRequired)
     }
 
-    fn require_short_signup_lifetime(grant: &GrantClaims) -> Result<(), AuthServiceError> {
+    fn require_short_signup_lifetime(&self, grant: &GrantClaims) -> Result<(), AuthServiceError> {
         let now = Utc::now().timestamp() as u64;
-        if grant.iat > now + POP_MAX_AGE_SECS {
+        if grant.iat > now + self.clock_skew_tolerance_secs {
             return Err(AuthServiceError::InvalidSignupGrant(
                 "signup grant iat is too far in the future".into(),
             ));
@@ -305,6 +325,23 @@ impl GrantAuthService {
         Ok(())
     }
 
+    /// Session grants set the session lifetime, which must stay within
+    /// [`MIN_SESSION_GRANT_LIFETIME_SECS`] and [`MAX_SESSION_GRANT_LIFETIME_SECS`].
+    fn require_bounded_session_lifetime(grant: &GrantClaims) -> Result<(), AuthServiceError> {
+        let lifetime = grant.exp.saturating_sub(grant.iat);
+        if lifetime < MIN_SESSION_GRANT_LIFETIME_SECS {
+            return Err(AuthServiceError::InvalidGrantLifetime(format!(
+                "grant lifetime must be at least {MIN_SESSION_GRANT_LIFETIME_SECS} seconds"
+            )));
+        }
+        if lifetime > MAX_SESSION_GRANT_LIFETIME_SECS {
+            return Err(AuthServiceError::InvalidGrantLifetime(format!(
+                "grant lifetime must be at most {MAX_SESSION_GRANT_LIFETIME_SECS} seconds"
+            )));
+        }
+        Ok(())
+    }
+
     /// Shared tail: persist grant → mint grant session.
     /// No tx needed because store_grant is idempotent and mint_session only creates a session row.
     async fn store_and_mint(
@@ -349,6 +386,7 @@ impl GrantAuthService {
             cnf_key: &grant.cnf,
             expected_audience: &hs_pubkey_z32,
             expected_grant_id: &grant.jti,
+            max_age_secs: self.clock_skew_tolerance_secs,
         };
         Ok(PopProof::verify(compact, &context)?)
     }
@@ -356,7 +394,8 @@ impl GrantAuthService {
     /// Reject replayed PoP nonces. Garbage-collects expired nonces first.
     async fn check_nonce_replay(&self, pop: &PopProof) -> Result<(), AuthServiceError> {
         if let Err(e) = PopNonceRepository::garbage_collect(
-            POP_NONCE_GC_THRESHOLD_SECS,
+            // Nonces are kept for twice the window to cover edge cases.
+            2 * self.clock_skew_tolerance_secs,
             &mut self.sql_db.pool().into(),
         )
         .await
@@ -373,17 +412,19 @@ impl GrantAuthService {
     }
 
     /// Verify the grant has not been revoked. A not-yet-stored grant passes (first use).
-    async fn check_grant_not_revoked(&self, grant: &GrantClaims) -> Result<(), AuthServiceError> {
-        let is_revoked = GrantRepository::is_revoked(&grant.jti, &mut self.sql_db.pool().into())
-            .await
-            .or_else(|e| match e {
-                sqlx::Error::RowNotFound => Ok(false),
-                other => Err(AuthServiceError::Internal(other)),
-            })?;
+    ///
+    /// Returns whether the grant is already stored.
+    async fn check_grant_not_revoked(&self, grant: &GrantClaims) -> Result<bool, AuthServiceError> {
+        let is_revoked =
+            match GrantRepository::is_revoked(&grant.jti, &mut self.sql_db.pool().into()).await {
+                Ok(is_revoked) => is_revoked,
+                Err(sqlx::Error::RowNotFound) => return Ok(false),
+                Err(other) => return Err(AuthServiceError::Internal(other)),
+            };
         if is_revoked {
             return Err(AuthServiceError::GrantRevoked);
         }
-        Ok(())
+        Ok(true)
     }
 
     /// Persist the grant idempotently (ON CONFLICT DO NOTHING).
@@ -412,7 +453,8 @@ impl GrantAuthService {
         executor: &mut UnifiedExecutor<'a>,
     ) -> Result<GrantSessionResponse, AuthServiceError> {
         let now = Utc::now().timestamp() as u64;
-        let expires_at = now + DEFAULT_SESSION_TOKEN_LIFETIME_SECS;
+        // A bearer never outlives the grant it was minted from.
+        let expires_at = (now + DEFAULT_SESSION_TOKEN_LIFETIME_SECS).min(grant.exp);
         let bearer = SessionBearer::generate();
         let token_hash = bearer.hash();
 
@@ -475,6 +517,7 @@ mod tests {
         signup_code::{SignupCode, SignupCodeRepository},
         SqlDb,
     };
+    use crate::services::signup_policy_service::SignupPolicyService;
     use crate::shared::user_quota::UserQuota;
     use crate::SignupMode;
     use pubky_common::{
@@ -496,7 +539,8 @@ mod tests {
         let db = SqlDb::test().await;
         let hs_kp = Keypair::random();
         let user_service = crate::services::user_service::UserService::new(db.clone());
-        let signup_service = SignupService::new(db.clone(), signup_mode, user_service);
+        let signup_policy = SignupPolicyService::new(db.clone(), signup_mode);
+        let signup_service = SignupService::new(db.clone(), signup_policy, user_service);
         GrantAuthService::new(db, hs_kp.public_key(), signup_service)
     }
 
@@ -586,6 +630,78 @@ mod tests {
         assert_eq!(response.session.pubky, user_kp.public_key());
     }
 
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn create_grant_session_enforces_lifetime_bounds() {
+        let service = test_service().await;
+        let (user_kp, _user_id) = create_test_user(&service).await;
+        for lifetime in [
+            MIN_SESSION_GRANT_LIFETIME_SECS - 1,
+            MAX_SESSION_GRANT_LIFETIME_SECS + 1,
+        ] {
+            let (grant_jws, pop_jws, _) = sign_grant_with_client_id(
+                &user_kp,
+                &Keypair::random(),
+                &service.homeserver_public_key(),
+                "test.app",
+                lifetime,
+            );
+            let err = service
+                .create_grant_session(&grant_jws, &pop_jws)
+                .await
+                .unwrap_err();
+            assert!(matches!(err, AuthServiceError::InvalidGrantLifetime(_)));
+        }
+
+        // A short grant caps the bearer lifetime.
+        let (grant_jws, pop_jws, grant) = sign_grant_with_client_id(
+            &user_kp,
+            &Keypair::random(),
+            &service.homeserver_public_key(),
+            "test.app",
+            MIN_SESSION_GRANT_LIFETIME_SECS,
+        );
+        let response = service
+            .create_grant_session(&grant_jws, &pop_jws)
+            .await
+            .unwrap();
+        assert_eq!(response.session.token_expires_at, grant.exp);
+    }
+
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn create_grant_session_refreshes_legacy_long_lived_grant() {
+        let service = test_service().await;
+        let (user_kp, _user_id) = create_test_user(&service).await;
+        let client_kp = Keypair::random();
+        let (_, _, grant) = sign_grant_with_client_id(
+            &user_kp,
+            &client_kp,
+            &service.homeserver_public_key(),
+            "test.app",
+            MAX_SESSION_GRANT_LIFETIME_SECS + 1,
+        );
+        // Stored before the lifetime bounds were enforced.
+        let user = service.find_user(&grant).await.unwrap();
+        GrantAuthService::store_grant(&grant, &user, &mut service.sql_db.pool().into())
+            .await
+            .unwrap();
+
+        let grant_jws = sign_jws(&user_kp, GRANT_JWS_TYP, &grant);
+        let pop_claims = PopProofClaims {
+            aud: service.homeserver_public_key(),
+            gid: grant.jti.clone(),
+            nonce: PopNonce::generate(),
+            iat: Utc::now().timestamp() as u64,
+        };
+        let pop_jws = sign_jws(&client_kp, POP_JWS_TYP, &pop_claims);
+        let response = service
+            .create_grant_session(&grant_jws, &pop_jws)
+            .await
+            .unwrap();
+        assert_eq!(response.session.pubky, user_kp.public_key());
+    }
+
     #[tokio::test]
     #[pubky_test_utils::test]
     async fn create_grant_session_user_not_found() {
@@ -778,6 +894,22 @@ mod tests {
         assert!(matches!(err, AuthServiceError::InvalidSignupToken));
     }
 
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn signup_grant_account_closed() {
+        let service = test_service_with_signup_mode(SignupMode::Closed).await;
+        let user_kp = Keypair::random();
+        let client_kp = Keypair::random();
+        let (grant_jws, pop_jws, _) =
+            sign_signup_grant(&user_kp, &client_kp, &service.homeserver_public_key());
+
+        let err = service
+            .signup_grant_account(&grant_jws, &pop_jws, None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AuthServiceError::SignupsClosed));
+    }
+
     #[tokio::test]
     #[pubky_test_utils::test]
     async fn signup_grant_account_token_required_already_used() {
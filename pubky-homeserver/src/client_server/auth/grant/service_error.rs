@@ -42,6 +42,10 @@ pub enum AuthServiceError {
     #[error("Token already used")]
     SignupTokenAlreadyUsed,
 
+    /// The homeserver does not accept new users.
+    #[error("Signups are closed")]
+    SignupsClosed,
+
     /// PoP nonce was already used (replay attack).
     #[error("PoP nonce already used")]
     NonceReplay,
@@ -58,6 +62,10 @@ pub enum AuthServiceError {
     #[error("Invalid signup grant: {0}")]
     InvalidSignupGrant(String),
 
+    /// Session grant lifetime is outside the bounds accepted by the homeserver.
+    #[error("Invalid grant lifetime: {0}")]
+    InvalidGrantLifetime(String),
+
     /// Session not found for the given token ID.
     #[error("Session not found")]
     SessionNotFound,
@@ -95,6 +103,7 @@ impl From<SignupServiceError> for AuthServiceError {
             SignupServiceError::SignupTokenRequired => Self::SignupTokenRequired,
             SignupServiceError::InvalidSignupToken => Self::InvalidSignupToken,
             SignupServiceError::SignupTokenAlreadyUsed => Self::SignupTokenAlreadyUsed,
+            SignupServiceError::SignupsClosed => Self::SignupsClosed,
             SignupServiceError::Internal(e) => Self::Internal(e),
         }
     }
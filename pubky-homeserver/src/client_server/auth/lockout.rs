@@ -0,0 +1,219 @@
+//! Lockout for repeated failed signup and signin attempts.
+//!
+//! Failures are counted per client IP: the peer address, or the address a
+//! configured trusted proxy forwarded the request for. The first [`FREE_ATTEMPTS`] failures are
+//! free; every further failure locks the IP out of the signup/signin routes for
+//! [`BASE_LOCKOUT`], doubling with each failure up to [`MAX_LOCKOUT`]. A successful
+//! attempt clears the record, and so does [`FORGET_AFTER`] without failures.
+//!
+//! Attempts are deliberately not keyed by the target public key: anyone can send
+//! garbage tokens for any pubky, so a per-pubky lockout would let third parties
+//! lock users out of their own accounts.
+//!
+//! Locked-out requests are answered with `429 Too Many Requests` and a
+//! `Retry-After` header holding the remaining lockout in seconds.
+
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use dashmap::DashMap;
+
+use crate::client_server::middleware::rate_limiter::client_ip;
+use crate::shared::HttpError;
+
+/// Failed attempts allowed before the first lockout.
+pub(crate) const FREE_ATTEMPTS: u32 = 5;
+
+/// Lockout after the first failure past [`FREE_ATTEMPTS`].
+pub(crate) const BASE_LOCKOUT: Duration = Duration::from_secs(1);
+
+/// Upper bound on a single lockout.
+pub(crate) const MAX_LOCKOUT: Duration = Duration::from_secs(15 * 60);
+
+/// How long a failure record is kept after the last failure.
+pub(crate) const FORGET_AFTER: Duration = Duration::from_secs(60 * 60);
+
+/// Number of tracked IPs above which stale records are evicted on the next failure.
+const EVICTION_THRESHOLD: usize = 4096;
+
+#[derive(Debug, Clone, Copy)]
+struct FailureRecord {
+    failures: u32,
+    last_failure: Instant,
+    locked_until: Option<Instant>,
+}
+
+impl FailureRecord {
+    fn is_stale(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.last_failure) > FORGET_AFTER
+    }
+}
+
+/// Lockout duration after `failures` consecutive failed attempts.
+fn lockout_for(failures: u32) -> Option<Duration> {
+    let excess = failures.checked_sub(FREE_ATTEMPTS)?.checked_sub(1)?;
+    let lockout = BASE_LOCKOUT
+        .checked_mul(2u32.saturating_pow(excess))
+        .unwrap_or(MAX_LOCKOUT);
+    Some(lockout.min(MAX_LOCKOUT))
+}
+
+/// Tracks failed signup/signin attempts per client IP.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct AuthLockout {
+    records: Arc<DashMap<IpAddr, FailureRecord>>,
+    trusted_proxies: Arc<[IpAddr]>,
+}
+
+impl AuthLockout {
+    /// Create a lockout that takes the client IP from forwarding headers only
+    /// for requests from `trusted_proxies`.
+    pub(crate) fn new(trusted_proxies: Vec<IpAddr>) -> Self {
+        Self {
+            records: Arc::default(),
+            trusted_proxies: trusted_proxies.into(),
+        }
+    }
+
+    /// Remaining lockout for `ip`, if it is currently locked out.
+    pub(crate) fn locked_for(&self, ip: IpAddr, now: Instant) -> Option<Duration> {
+        let record = self.records.get(&ip)?;
+        let remaining = record.locked_until?.saturating_duration_since(now);
+        (!remaining.is_zero()).then_some(remaining)
+    }
+
+    /// Record a failed attempt from `ip` and return the lockout it triggered, if any.
+    pub(crate) fn record_failure(&self, ip: IpAddr, now: Instant) -> Option<Duration> {
+        if self.records.len() >= EVICTION_THRESHOLD {
+            self.records.retain(|_, record| !record.is_stale(now));
+        }
+
+        let mut record = self.records.entry(ip).or_insert(FailureRecord {
+            failures: 0,
+            last_failure: now,
+            locked_until: None,
+        });
+        if record.is_stale(now) {
+            record.failures = 0;
+        }
+        record.failures = record.failures.saturating_add(1);
+        record.last_failure = now;
+        let lockout = lockout_for(record.failures);
+        record.locked_until = lockout.map(|lockout| now + lockout);
+        lockout
+    }
+
+    /// Clear the failure record of `ip` after a successful attempt.
+    pub(crate) fn record_success(&self, ip: IpAddr) {
+        self.records.remove(&ip);
+    }
+}
+
+/// Rejects locked-out clients and records the outcome of every attempt.
+///
+/// Only rejected credentials (400, 401 and 403) count as failures; conflicts
+/// such as an already existing user do not.
+pub(crate) async fn auth_lockout(
+    State(lockout): State<AuthLockout>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(ip) = client_ip(&request, &lockout.trusted_proxies) else {
+        return next.run(request).await;
+    };
+
+    if let Some(remaining) = lockout.locked_for(ip, Instant::now()) {
+        return too_many_attempts(remaining);
+    }
+
+    let response = next.run(request).await;
+    let status = response.status();
+    if status.is_success() {
+        lockout.record_success(ip);
+    } else if matches!(
+        status,
+        StatusCode::BAD_REQUEST | StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN
+    ) {
+        if let Some(lockout) = lockout.record_failure(ip, Instant::now()) {
+            tracing::debug!("Locking out {ip} from auth routes for {lockout:?}");
+        }
+    }
+    response
+}
+
+fn too_many_attempts(remaining: Duration) -> Response {
+    // Round up so clients never retry while still locked out.
+    let secs = remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0);
+    let mut response = HttpError::new_with_message(
+        StatusCode::TOO_MANY_REQUESTS,
+        format!("Too many failed attempts, retry in {secs} seconds"),
+    )
+    .into_response();
+    response
+        .headers_mut()
+        .insert(header::RETRY_AFTER, HeaderValue::from(secs));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use super::*;
+
+    const IP: IpAddr = IpAddr::V4(Ipv4Addr::new(203, 0, 113, 7));
+
+    #[test]
+    fn lockout_doubles_after_free_attempts() {
+        assert_eq!(lockout_for(FREE_ATTEMPTS), None);
+        assert_eq!(lockout_for(FREE_ATTEMPTS + 1), Some(BASE_LOCKOUT));
+        assert_eq!(lockout_for(FREE_ATTEMPTS + 2), Some(BASE_LOCKOUT * 2));
+        assert_eq!(lockout_for(FREE_ATTEMPTS + 3), Some(BASE_LOCKOUT * 4));
+        assert_eq!(lockout_for(FREE_ATTEMPTS + 40), Some(MAX_LOCKOUT));
+        assert_eq!(lockout_for(u32::MAX), Some(MAX_LOCKOUT));
+    }
+
+    #[test]
+    fn locks_out_until_lockout_expires() {
+        let lockout = AuthLockout::default();
+        let now = Instant::now();
+
+        for _ in 0..FREE_ATTEMPTS {
+            assert_eq!(lockout.record_failure(IP, now), None);
+        }
+        assert_eq!(lockout.locked_for(IP, now), None);
+
+        assert_eq!(lockout.record_failure(IP, now), Some(BASE_LOCKOUT));
+        assert_eq!(lockout.locked_for(IP, now), Some(BASE_LOCKOUT));
+        assert_eq!(lockout.locked_for(IP, now + BASE_LOCKOUT), None);
+
+        let other = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        assert_eq!(lockout.locked_for(other, now), None);
+    }
+
+    #[test]
+    fn success_and_idle_time_reset_failures() {
+        let lockout = AuthLockout::default();
+        let now = Instant::now();
+
+        for _ in 0..=FREE_ATTEMPTS {
+            lockout.record_failure(IP, now);
+        }
+        lockout.record_success(IP);
+        assert_eq!(lockout.locked_for(IP, now), None);
+        assert_eq!(lockout.record_failure(IP, now), None);
+
+        for _ in 0..FREE_ATTEMPTS {
+            lockout.record_failure(IP, now);
+        }
+        let later = now + FORGET_AFTER + Duration::from_secs(1);
+        assert_eq!(lockout.record_failure(IP, later), None);
+    }
+}
@@ -8,12 +8,14 @@
 //! - **session**: [`AuthSession`] enum bridging both auth methods
 //! - **middleware**: Authentication layer (Bearer/Cookie) and `AuthSession` extractor
 //! - **authorization**: [`has_write_permission`] / [`has_read_permission`] predicates for handlers
+//! - **lockout**: Exponential backoff after repeated failed signup/signin attempts
 //! - **router**: Pre-configured axum routers for base and tenant routes
 //! - **state**: Auth-specific sub-state extracted via `FromRef`
 
 pub mod authorization;
 pub mod cookie;
 pub mod grant;
+pub(crate) mod lockout;
 pub mod middleware;
 pub(crate) mod revocation;
 mod router;
@@ -27,6 +29,7 @@ pub use authorization::{has_read_permission, has_write_permission};
 pub use middleware::authentication::AuthenticationLayer;
 
 pub use grant::service::GrantAuthService;
+pub(crate) use lockout::AuthLockout;
 pub(crate) use revocation::{AuthRevocation, AuthRevocationService};
 pub use router::{base_router, tenant_router};
 pub use session::AuthSession;
@@ -3,6 +3,7 @@ use std::{sync::Arc, time::Duration};
 use sqlx::{postgres::PgListener, PgPool};
 use tokio::{
     sync::{broadcast, oneshot, Mutex},
+    task::JoinHandle,
     time::Instant,
 };
 
@@ -37,6 +38,7 @@ impl AuthRevocationService {
             pool: pool.clone(),
             actor: Some(ListenerActor::spawn(pool).await?),
             replacement_cooldown: ReplacementCooldown::default(),
+            stopped: false,
         };
         Ok(Self {
             supervisor: Arc::new(Mutex::new(supervisor)),
@@ -46,6 +48,22 @@ impl AuthRevocationService {
     pub(crate) async fn subscribe(&self) -> SubscriptionResult {
         self.supervisor.lock().await.subscribe().await
     }
+
+    /// Stop the listener and wait until its Postgres connection is released.
+    ///
+    /// Open private streams are closed, and later subscriptions are refused
+    /// instead of starting a replacement listener. This affects every clone.
+    pub(crate) async fn shutdown(&self) {
+        let actor = {
+            let mut supervisor = self.supervisor.lock().await;
+            supervisor.stopped = true;
+            supervisor.actor.take()
+        };
+        if let Some(actor) = actor {
+            tracing::info!("auth revocation listener shutting down");
+            actor.shutdown().await;
+        }
+    }
 }
 
 /// Serves subscriptions from one listener actor at a time.
@@ -54,6 +72,8 @@ struct Supervisor {
     pool: PgPool,
     actor: Option<ListenerActorHandle>,
     replacement_cooldown: ReplacementCooldown,
+    /// Set on shutdown so no replacement listener is started afterwards.
+    stopped: bool,
 }
 
 #[derive(Debug, Default)]
@@ -90,7 +110,7 @@ impl Supervisor {
     }
 
     async fn replace(&mut self) -> Result<(), AuthRevocationUnavailable> {
-        if !self.replacement_cooldown.try_start(Instant::now()) {
+        if self.stopped || !self.replacement_cooldown.try_start(Instant::now()) {
             return Err(AuthRevocationUnavailable);
         }
 
@@ -123,18 +143,18 @@ impl ListenerActor {
 
         let (notifications_tx, _) = broadcast::channel(AUTH_REVOCATION_CHANNEL_CAPACITY);
         let (lifetime_tx, lifetime_rx) = oneshot::channel();
-        let handle = ListenerActorHandle {
-            notifications_tx: notifications_tx.downgrade(),
-            _lifetime_tx: lifetime_tx,
-        };
+        let weak_notifications_tx = notifications_tx.downgrade();
         let actor = Self {
             listener,
             notifications_tx,
             lifetime_rx,
         };
-        drop(tokio::spawn(actor.run()));
 
-        Ok(handle)
+        Ok(ListenerActorHandle {
+            notifications_tx: weak_notifications_tx,
+            _lifetime_tx: lifetime_tx,
+            task: tokio::spawn(actor.run()),
+        })
     }
 
     /// Forward notifications to this actor's subscribers until any gap makes
@@ -176,6 +196,7 @@ impl ListenerActor {
 struct ListenerActorHandle {
     notifications_tx: broadcast::WeakSender<AuthRevocation>,
     _lifetime_tx: oneshot::Sender<()>,
+    task: JoinHandle<()>,
 }
 
 impl ListenerActorHandle {
@@ -184,6 +205,12 @@ impl ListenerActorHandle {
             .upgrade()
             .map(|notifications_tx| notifications_tx.subscribe())
     }
+
+    /// Stop the actor and wait until it has finished.
+    async fn shutdown(self) {
+        drop(self._lifetime_tx);
+        let _ = self.task.await;
+    }
 }
 
 #[cfg(test)]
@@ -325,6 +352,31 @@ mod tests {
         expect_closed(&mut receiver).await;
     }
 
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn shutdown_releases_the_postgres_listener_and_refuses_new_streams() {
+        let db = SqlDb::test().await;
+        let service = AuthRevocationService::start(db.pool()).await.unwrap();
+        let spare = service.clone();
+        let mut receiver = service.subscribe().await.unwrap();
+        let pid = listener_backend_pid(db.pool()).await;
+
+        service.shutdown().await;
+
+        expect_closed(&mut receiver).await;
+        spare
+            .subscribe()
+            .await
+            .expect_err("no replacement listener after shutdown");
+        timeout(Duration::from_secs(5), async {
+            while listener_backend_pids(db.pool()).await.contains(&pid) {
+                sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("timed out waiting for the listener connection to be released");
+    }
+
     #[tokio::test]
     #[pubky_test_utils::test]
     async fn dropping_the_last_service_clone_releases_the_postgres_listener() {
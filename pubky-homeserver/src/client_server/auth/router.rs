@@ -8,12 +8,14 @@
 //! and its own auth-method-specific middleware.
 
 use axum::{
+    middleware,
     routing::{delete, get, post},
     Router,
 };
 
 use super::cookie;
 use super::grant;
+use super::lockout::auth_lockout;
 use crate::client_server::auth::AuthState;
 
 /// Base-level auth routes — no authentication middleware.
@@ -21,8 +23,13 @@ use crate::client_server::auth::AuthState;
 /// These are entry points for creating sessions:
 /// - `POST /signup` — cookie-based user creation (deprecated)
 /// - `POST /session` — cookie-based signin (deprecated)
+/// - `POST /auth/grant/signup` — grant-based user creation
 /// - `POST /auth/grant/session` — grant-based session creation
+///
+/// Clients that keep failing these routes are locked out with exponential
+/// backoff, keyed by client IP.
 pub fn base_router(auth_state: AuthState) -> Router<()> {
+    let lockout = auth_state.lockout.clone();
     Router::new()
         // Cookie (deprecated)
         .route("/signup", post(cookie::routes::signup))
@@ -33,6 +40,7 @@ pub fn base_router(auth_state: AuthState) -> Router<()> {
             "/auth/grant/session",
             post(grant::routes::create_grant_session),
         )
+        .route_layer(middleware::from_fn_with_state(lockout, auth_lockout))
         .with_state(auth_state)
 }
 
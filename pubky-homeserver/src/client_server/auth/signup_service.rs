@@ -6,6 +6,7 @@ use crate::persistence::sql::{
     user::{UserEntity, UserRepository},
     SqlDb,
 };
+use crate::services::signup_policy_service::SignupPolicyService;
 use crate::services::user_service::UserService;
 use crate::shared::user_quota::UserQuota;
 use crate::SignupMode;
@@ -30,6 +31,10 @@ pub enum SignupServiceError {
     #[error("Token already used")]
     SignupTokenAlreadyUsed,
 
+    /// The homeserver does not accept new users.
+    #[error("Signups are closed")]
+    SignupsClosed,
+
     /// Database or infrastructure error.
     #[error("Internal error: {0}")]
     Internal(#[from] sqlx::Error),
@@ -38,24 +43,29 @@ pub enum SignupServiceError {
 #[derive(Clone, Debug)]
 pub struct SignupService {
     sql_db: SqlDb,
-    signup_mode: SignupMode,
+    signup_policy: SignupPolicyService,
     user_service: UserService,
 }
 
 impl SignupService {
-    /// Creates a signup service with the configured signup policy.
-    pub fn new(sql_db: SqlDb, signup_mode: SignupMode, user_service: UserService) -> Self {
+    /// Creates a signup service enforcing the current signup policy.
+    pub fn new(
+        sql_db: SqlDb,
+        signup_policy: SignupPolicyService,
+        user_service: UserService,
+    ) -> Self {
         Self {
             sql_db,
-            signup_mode,
+            signup_policy,
             user_service,
         }
     }
 
     /// Creates a new user in its own transaction.
     ///
-    /// Rejects existing users and enforces signup-token validation when the
-    /// homeserver is configured with [`SignupMode::TokenRequired`].
+    /// Rejects existing users, rejects everyone when signups are
+    /// [`SignupMode::Closed`], and enforces signup-token validation in
+    /// [`SignupMode::TokenRequired`].
     pub async fn create_new_user(
         &self,
         public_key: &PublicKey,
@@ -85,10 +95,13 @@ impl SignupService {
         tx: &mut sqlx::Transaction<'static, sqlx::Postgres>,
     ) -> Result<UserEntity, SignupServiceError> {
         Self::ensure_user_not_exists(public_key, tx).await?;
-        let quota = if self.signup_mode == SignupMode::TokenRequired {
-            Self::validate_and_consume_signup_token(signup_token, public_key, tx).await?
-        } else {
-            UserQuota::default()
+        let mode = self.signup_policy.current(uexecutor!(*tx)).await?;
+        let quota = match mode {
+            SignupMode::Closed => return Err(SignupServiceError::SignupsClosed),
+            SignupMode::TokenRequired => {
+                Self::validate_and_consume_signup_token(signup_token, public_key, tx).await?
+            }
+            SignupMode::Open => UserQuota::default(),
         };
         let user = UserRepository::create(public_key, uexecutor!(*tx)).await?;
         let user = UserRepository::set_quota(user.id, &quota, uexecutor!(*tx)).await?;
@@ -1,12 +1,14 @@
 //! Auth-specific sub-state for the auth module.
 
+use std::time::Duration;
+
 use super::cookie::verifier::CookieAuthVerifier;
 use crate::app_context::AppContext;
 use crate::observability::Metrics;
 use crate::shared::HttpResult;
 
 use super::cookie::service::CookieAuthService;
-use super::{AuthSession, GrantAuthService, SignupService};
+use super::{AuthLockout, AuthSession, GrantAuthService, SignupService};
 
 /// Auth-specific state. Auth route handlers extract this instead of the
 /// global `AppState`, keeping the auth module fully self-contained.
@@ -14,6 +16,7 @@ use super::{AuthSession, GrantAuthService, SignupService};
 pub struct AuthState {
     pub(crate) grant_auth_service: GrantAuthService,
     pub(crate) cookie_auth_service: CookieAuthService,
+    pub(crate) lockout: AuthLockout,
     pub(crate) metrics: Metrics,
 }
 
@@ -21,21 +24,24 @@ impl AuthState {
     pub fn new(context: &AppContext) -> Self {
         let signup_service = SignupService::new(
             context.sql_db.clone(),
-            context.config_toml.general.signup_mode.clone(),
+            context.signup_policy_service.clone(),
             context.user_service.clone(),
         );
+        let clock_skew_tolerance = context.config_toml.general.clock_skew_tolerance;
 
         Self {
             grant_auth_service: GrantAuthService::new(
                 context.sql_db.clone(),
                 context.keypair.public_key(),
                 signup_service.clone(),
-            ),
+            )
+            .with_clock_skew_tolerance(clock_skew_tolerance),
             cookie_auth_service: CookieAuthService::new(
                 context.sql_db.clone(),
-                CookieAuthVerifier::default(),
+                CookieAuthVerifier::new(Duration::from_secs(clock_skew_tolerance)),
                 signup_service,
             ),
+            lockout: AuthLockout::new(context.config_toml.drive.trusted_proxies.clone()),
             metrics: context.metrics.clone(),
         }
     }
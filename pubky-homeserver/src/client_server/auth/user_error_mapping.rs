@@ -18,6 +18,9 @@ impl From<SignupServiceError> for HttpError {
             SignupServiceError::SignupTokenAlreadyUsed => {
                 HttpError::unauthorized_with_message("Token already used")
             }
+            SignupServiceError::SignupsClosed => {
+                HttpError::new_with_message(StatusCode::FORBIDDEN, "Signups are closed")
+            }
             SignupServiceError::Internal(e) => {
                 HttpError::internal_server_and_log(format!("Signup service: {e}"))
             }
@@ -0,0 +1,78 @@
+//! Extension point for embedding the client server in a larger axum application.
+
+use std::convert::Infallible;
+use std::fmt;
+use std::sync::Arc;
+
+use axum::{
+    extract::Request,
+    response::IntoResponse,
+    routing::{Route, Router},
+};
+use tower::{Layer, Service};
+
+type LayerFn = Arc<dyn Fn(Router) -> Router + Send + Sync>;
+
+/// Extra routes and middleware composed into the client server's router.
+///
+/// - Routes added with [`Self::merge`] are merged next to the homeserver's own routes, so
+///   they run behind the built-in middleware (pubky-host detection, cookies, rate limits,
+///   authentication, CORS) and can read its request extensions. Paths must not collide
+///   with the homeserver's routes; axum panics on overlapping routes when the server starts.
+/// - Layers added with [`Self::layer`] wrap the complete router, outside the built-in
+///   middleware, so they see every request first. Later layers wrap earlier ones.
+///
+/// Pass it to [`ClientServer::start_with_extensions`](crate::ClientServer::start_with_extensions)
+/// or [`HomeserverApp::start_with_extensions`](crate::HomeserverApp::start_with_extensions).
+#[derive(Clone, Default)]
+pub struct RouterExtensions {
+    routes: Router,
+    layers: Vec<LayerFn>,
+}
+
+impl RouterExtensions {
+    /// No extra routes or layers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Merge extra routes into the homeserver router.
+    pub fn merge(mut self, routes: Router) -> Self {
+        self.routes = self.routes.merge(routes);
+        self
+    }
+
+    /// Wrap the homeserver router in an extra [`tower::Layer`].
+    pub fn layer<L>(mut self, layer: L) -> Self
+    where
+        L: Layer<Route> + Clone + Send + Sync + 'static,
+        L::Service: Service<Request> + Clone + Send + Sync + 'static,
+        <L::Service as Service<Request>>::Response: IntoResponse + 'static,
+        <L::Service as Service<Request>>::Error: Into<Infallible> + 'static,
+        <L::Service as Service<Request>>::Future: Send + 'static,
+    {
+        self.layers
+            .push(Arc::new(move |router: Router| router.layer(layer.clone())));
+        self
+    }
+
+    /// The extra routes, to be merged before the built-in middleware is applied.
+    pub(crate) fn routes(&self) -> Router {
+        self.routes.clone()
+    }
+
+    /// Apply the extra layers to the fully built router.
+    pub(crate) fn apply_layers(&self, router: Router) -> Router {
+        self.layers
+            .iter()
+            .fold(router, |router, layer| layer(router))
+    }
+}
+
+impl fmt::Debug for RouterExtensions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RouterExtensions")
+            .field("layers", &self.layers.len())
+            .finish_non_exhaustive()
+    }
+}
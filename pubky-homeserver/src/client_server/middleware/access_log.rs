@@ -0,0 +1,399 @@
+//! Structured access log attributing every request to a tenant and a user.
+//!
+//! [`with_access_log`] wraps the complete router so requests rejected by any
+//! middleware (rate limits, unknown tenants, ...) are logged too. Tenant and user
+//! are only known once [`PubkyHostLayer`](super::pubky_host::PubkyHostLayer) and
+//! authentication ran, so [`record_attribution`] copies them from the request
+//! extensions into a slot shared with the outer layer.
+//!
+//! A record is written once the response body is fully sent (or dropped), so
+//! `bytes_out` and `latency_ms` cover the whole transfer. Records are handed to a
+//! dedicated writer thread, so a slow disk or stdout never blocks a request.
+//!
+//! `client_ip` is the address of the connection's peer, which the client cannot fake.
+//! The address a proxy forwarded the request for is logged separately as
+//! `forwarded_for`, and only if the peer is one of `[drive].trusted_proxies`.
+
+use std::fs::OpenOptions;
+use std::io::{BufWriter, Write};
+use std::net::{IpAddr, SocketAddr};
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+use axum::{
+    body::{Body, Bytes},
+    extract::{ConnectInfo, Request, State},
+    http::header,
+    middleware::{self, Next},
+    response::Response,
+    Router,
+};
+use http_body::{Frame, SizeHint};
+use pubky_common::crypto::PublicKey;
+use serde::Serialize;
+
+use super::pubky_host::PubkyHost;
+use super::rate_limiter::client_ip;
+use crate::client_server::auth::AuthSession;
+use crate::AccessLogToml;
+
+/// Records waiting for the writer thread. Records beyond it are dropped.
+const QUEUE_CAPACITY: usize = 10_000;
+
+/// Destination of the access log records.
+#[derive(Clone)]
+pub(crate) struct AccessLog {
+    sender: SyncSender<Vec<u8>>,
+    excluded_paths: Arc<Vec<String>>,
+    /// Peers whose forwarding headers are logged.
+    trusted_proxies: Arc<[IpAddr]>,
+}
+
+impl std::fmt::Debug for AccessLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AccessLog")
+            .field("excluded_paths", &self.excluded_paths)
+            .field("trusted_proxies", &self.trusted_proxies)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AccessLog {
+    /// Open the access log configured in `config`, or `None` if it is disabled.
+    /// Forwarding headers are only logged for peers in `trusted_proxies`.
+    pub(crate) fn from_config(
+        config: &AccessLogToml,
+        trusted_proxies: &[IpAddr],
+        data_dir: &Path,
+    ) -> std::io::Result<Option<Self>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+        let writer: Box<dyn Write + Send> = match &config.path {
+            Some(path) => Box::new(
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(data_dir.join(path))?,
+            ),
+            None => Box::new(std::io::stdout()),
+        };
+        Ok(Some(Self::new(
+            writer,
+            config.excluded_paths.clone(),
+            trusted_proxies,
+        )))
+    }
+
+    /// Write records to `writer` from a new thread, which ends once every clone of the
+    /// returned log is dropped.
+    fn new(
+        writer: Box<dyn Write + Send>,
+        excluded_paths: Vec<String>,
+        trusted_proxies: &[IpAddr],
+    ) -> Self {
+        let (sender, receiver) = mpsc::sync_channel(QUEUE_CAPACITY);
+        std::thread::spawn(move || write_records(receiver, writer));
+        Self {
+            sender,
+            excluded_paths: Arc::new(excluded_paths),
+            trusted_proxies: trusted_proxies.into(),
+        }
+    }
+
+    /// Queue `record` for writing without blocking.
+    fn write(&self, record: &AccessLogRecord) {
+        let Ok(mut line) = serde_json::to_vec(record) else {
+            return;
+        };
+        line.push(b'\n');
+        if let Err(TrySendError::Full(_)) = self.sender.try_send(line) {
+            tracing::warn!("Access log writer is behind, dropping a record");
+        }
+    }
+}
+
+/// Write the queued records, flushing whenever the queue is drained.
+fn write_records(receiver: Receiver<Vec<u8>>, writer: Box<dyn Write + Send>) {
+    let mut writer = BufWriter::new(writer);
+    while let Ok(line) = receiver.recv() {
+        let mut result = writer.write_all(&line);
+        while let Ok(line) = receiver.try_recv() {
+            result = result.and_then(|_| writer.write_all(&line));
+        }
+        if let Err(e) = result.and_then(|_| writer.flush()) {
+            tracing::warn!("Failed to write access log records: {e}");
+        }
+    }
+}
+
+/// One line of the access log.
+#[derive(Debug, Serialize)]
+struct AccessLogRecord {
+    /// When the request was received (RFC 3339, UTC).
+    timestamp: String,
+    /// Address of the connection's peer.
+    client_ip: Option<String>,
+    /// Address a trusted proxy forwarded the request for, if it named one.
+    forwarded_for: Option<String>,
+    /// The tenant addressed through the host.
+    tenant: Option<String>,
+    /// The user authenticated by the request's session, if any.
+    pubky: Option<String>,
+    method: String,
+    path: String,
+    status: u16,
+    /// Request body size announced by `Content-Length`.
+    bytes_in: Option<u64>,
+    /// Response body bytes sent.
+    bytes_out: u64,
+    latency_ms: u64,
+}
+
+/// Tenant and user of a request, filled in by [`record_attribution`].
+#[derive(Debug, Clone, Default)]
+struct Attribution(Arc<Mutex<(Option<PublicKey>, Option<PublicKey>)>>);
+
+/// Log every request handled by `router` to `access_log`.
+pub(crate) fn with_access_log(router: Router, access_log: AccessLog) -> Router {
+    router.layer(middleware::from_fn_with_state(access_log, log_access))
+}
+
+async fn log_access(State(access_log): State<AccessLog>, mut req: Request, next: Next) -> Response {
+    let started = Instant::now();
+    let timestamp = chrono::Utc::now().to_rfc3339();
+    let attribution = Attribution::default();
+    req.extensions_mut().insert(attribution.clone());
+    let peer_ip = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+    let forwarded_for = client_ip(&req, &access_log.trusted_proxies)
+        .filter(|ip| Some(*ip) != peer_ip)
+        .map(|ip| ip.to_string());
+    let client_ip = peer_ip.map(|ip| ip.to_string());
+    let method = req.method().to_string();
+    let path = req.uri().path().to_string();
+    let bytes_in = req
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok());
+
+    let response = next.run(req).await;
+
+    let status = response.status();
+    if !status.is_success() || !access_log.excluded_paths.contains(&path) {
+        let (tenant, user) = attribution
+            .0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        let record = AccessLogRecord {
+            timestamp,
+            client_ip,
+            forwarded_for,
+            tenant: tenant.map(|key| key.z32()),
+            pubky: user.map(|key| key.z32()),
+            method,
+            path,
+            status: status.as_u16(),
+            bytes_in,
+            bytes_out: 0,
+            latency_ms: 0,
+        };
+        let pending = PendingRecord {
+            access_log,
+            record,
+            started,
+        };
+        return response.map(|inner| Body::new(LoggedBody { inner, pending }));
+    }
+    response
+}
+
+/// Copy the request's tenant and authenticated user to the outer access log layer.
+///
+/// Must run after `PubkyHostLayer` and authentication.
+pub(crate) async fn record_attribution(req: Request, next: Next) -> Response {
+    if let Some(attribution) = req.extensions().get::<Attribution>() {
+        let mut slot = attribution.0.lock().unwrap_or_else(PoisonError::into_inner);
+        slot.0 = req
+            .extensions()
+            .get::<PubkyHost>()
+            .map(|host| host.public_key().clone());
+        slot.1 = req
+            .extensions()
+            .get::<AuthSession>()
+            .map(|session| session.user_key().clone());
+    }
+    next.run(req).await
+}
+
+/// A record waiting for its response body to finish; written on drop.
+struct PendingRecord {
+    access_log: AccessLog,
+    record: AccessLogRecord,
+    started: Instant,
+}
+
+impl Drop for PendingRecord {
+    fn drop(&mut self) {
+        self.record.latency_ms = self.started.elapsed().as_millis() as u64;
+        self.access_log.write(&self.record);
+    }
+}
+
+/// Response body counting the bytes sent for its [`PendingRecord`].
+struct LoggedBody {
+    inner: Body,
+    pending: PendingRecord,
+}
+
+impl http_body::Body for LoggedBody {
+    type Data = Bytes;
+    type Error = axum::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = &mut *self;
+        let poll = Pin::new(&mut this.inner).poll_frame(cx);
+        if let Poll::Ready(Some(Ok(frame))) = &poll {
+            if let Some(data) = frame.data_ref() {
+                this.pending.record.bytes_out += data.len() as u64;
+            }
+        }
+        poll
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::routing::get;
+    use axum_test::TestServer;
+
+    use super::*;
+
+    /// Collects written records in memory.
+    #[derive(Clone, Default)]
+    struct Records(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Records {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Records {
+        /// Wait for the writer thread to write `count` records and return them.
+        async fn lines(&self, count: usize) -> Vec<serde_json::Value> {
+            for _ in 0..100 {
+                if self.parse().len() >= count {
+                    break;
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+            self.parse()
+        }
+
+        fn parse(&self) -> Vec<serde_json::Value> {
+            let bytes = self.0.lock().unwrap().clone();
+            String::from_utf8(bytes)
+                .unwrap()
+                .lines()
+                .map(|line| serde_json::from_str(line).unwrap())
+                .collect()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_logs_requests_with_attribution() {
+        let records = Records::default();
+        let tenant = pubky_common::crypto::Keypair::random().public_key();
+        let host = PubkyHost(tenant.clone());
+        let router = Router::new()
+            .route("/pub/file.txt", get(|| async { "hello" }))
+            .route("/events/", get(|| async { "events" }))
+            .layer(middleware::from_fn(record_attribution))
+            .layer(middleware::from_fn(move |mut req: Request, next: Next| {
+                req.extensions_mut().insert(host.clone());
+                next.run(req)
+            }));
+        let access_log = AccessLog::new(Box::new(records.clone()), vec!["/events/".into()], &[]);
+        let server = TestServer::new(with_access_log(router, access_log)).unwrap();
+
+        server.get("/pub/file.txt").await.assert_status_ok();
+        server.get("/events/").await.assert_status_ok();
+        server.get("/events/?cursor=x").await.assert_status_ok();
+        server.get("/missing").await.assert_status_not_found();
+
+        let lines = records.lines(2).await;
+        assert_eq!(lines.len(), 2, "Successful /events/ requests are excluded");
+        assert_eq!(lines[0]["method"], "GET");
+        assert_eq!(lines[0]["path"], "/pub/file.txt");
+        assert_eq!(lines[0]["status"], 200);
+        assert_eq!(lines[0]["bytes_out"], 5);
+        assert_eq!(lines[0]["tenant"], tenant.z32());
+        assert_eq!(lines[0]["pubky"], serde_json::Value::Null);
+        assert_eq!(lines[1]["path"], "/missing");
+        assert_eq!(lines[1]["status"], 404);
+    }
+
+    #[tokio::test]
+    async fn test_forwarding_headers_are_logged_only_from_trusted_proxies() {
+        let logged_request = |trusted_proxies: &[IpAddr]| {
+            let records = Records::default();
+            let router = Router::new().route("/", get(|| async { "hello" }));
+            let access_log = AccessLog::new(Box::new(records.clone()), vec![], trusted_proxies);
+            let server = TestServer::builder()
+                .http_transport()
+                .build(
+                    with_access_log(router, access_log)
+                        .into_make_service_with_connect_info::<SocketAddr>(),
+                )
+                .unwrap();
+            (records, server)
+        };
+        let localhost: IpAddr = "127.0.0.1".parse().unwrap();
+
+        // A client naming another address itself.
+        let (records, server) = logged_request(&[]);
+        server
+            .get("/")
+            .add_header("x-forwarded-for", "198.51.100.1")
+            .await
+            .assert_status_ok();
+        let lines = records.lines(1).await;
+        assert_eq!(lines[0]["client_ip"], "127.0.0.1");
+        assert_eq!(lines[0]["forwarded_for"], serde_json::Value::Null);
+
+        // A trusted proxy forwarding a request.
+        let (records, server) = logged_request(&[localhost]);
+        server
+            .get("/")
+            .add_header("x-forwarded-for", "198.51.100.1")
+            .await
+            .assert_status_ok();
+        let lines = records.lines(1).await;
+        assert_eq!(lines[0]["client_ip"], "127.0.0.1");
+        assert_eq!(lines[0]["forwarded_for"], "198.51.100.1");
+    }
+}
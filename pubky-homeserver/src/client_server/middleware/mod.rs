@@ -1,5 +1,6 @@
 //! Request middleware for the client server.
 //!
+//! - [`access_log`]: Structured per-request access log attributed to tenant and user.
 //! - [`pubky_host`]: Extracts the tenant public key from the request Host header (TLS SNI).
 //! - [`rate_limiter`]: Configurable per-path request rate limiting, keyed by IP or user,
 //!   with optional per-user speed overrides resolved from DB.
@@ -7,6 +8,7 @@
 //!
 //! Authentication and authorization middleware live in [`crate::client_server::auth::middleware`].
 
+pub mod access_log;
 pub mod pubky_host;
 pub mod rate_limiter;
 pub mod trace;
@@ -6,12 +6,17 @@
 //! which tenant is being addressed.
 //!
 //! The [`PubkyHost`] extractor retrieves it from request extensions.
+//!
+//! [`with_dav_paths`] additionally serves every tenant route under
+//! `/dav/{pubkey}/...`, taking the tenant from the path instead of the host.
 
 use axum::{
     body::Body,
     extract::FromRequestParts,
-    http::{request::Parts, Request, StatusCode},
+    http::{request::Parts, uri::PathAndQuery, Request, StatusCode, Uri},
+    middleware::{self, Next},
     response::{IntoResponse, Response},
+    Router,
 };
 use futures_util::future::BoxFuture;
 use pubky_common::crypto::PublicKey;
@@ -74,7 +79,8 @@ where
 /// Tower [`Layer`] that extracts the tenant's public key from every incoming
 /// request and injects it as a [`PubkyHost`] extension.
 ///
-/// Resolution order:
+/// Requests that already carry a [`PubkyHost`] (see [`with_dav_paths`]) are
+/// left untouched. Otherwise the resolution order is:
 /// 1. `Host` header — the z32-encoded key set via TLS SNI on Pubky sockets.
 /// 2. `pubky-host` header — explicit override (takes precedence over `Host`).
 /// 3. `?pubky-host=<z32>` query parameter — fallback when headers are absent.
@@ -123,7 +129,9 @@ where
     }
 
     fn call(&mut self, mut req: Request<Body>) -> Self::Future {
-        if let Some(public_key) = extract_pubky(&req) {
+        // A tenant taken from a `/dav/{pubkey}/` path wins over any host.
+        let addressed = req.extensions().get::<PubkyHost>().is_some();
+        if let Some(public_key) = extract_pubky(&req).filter(|_| !addressed) {
             req.extensions_mut().insert(PubkyHost(public_key));
         }
         let mut inner = self.inner.clone();
@@ -169,3 +177,42 @@ fn extract_pubky(req: &Request<Body>) -> Option<PublicKey> {
     }
     pubky
 }
+
+// ── Dav paths ───────────────────────────────────────────────────────────────
+
+/// Path prefix that addresses a tenant independently of the host.
+const DAV_PREFIX: &str = "/dav/";
+
+/// Serve `router` under `/dav/{pubkey}/{*path}` as well.
+///
+/// `https://{homeserver}/dav/{user}/pub/file.txt` is handled exactly like
+/// `/pub/file.txt` addressed to `user`, so the URL stays valid across domain
+/// changes and redirects, and picks which homeserver serves a mirrored user.
+///
+/// The prefix is stripped *before* routing, so every tenant route (storage,
+/// sessions, ...) is reachable this way without a route of its own.
+pub fn with_dav_paths(router: Router) -> Router {
+    Router::new().fallback_service(middleware::from_fn(route_dav_path).layer(router))
+}
+
+async fn route_dav_path(mut req: Request<Body>, next: Next) -> Response {
+    let Some(rest) = req.uri().path().strip_prefix(DAV_PREFIX) else {
+        return next.run(req).await;
+    };
+    let (key, path) = rest.find('/').map_or((rest, "/"), |i| rest.split_at(i));
+    let Ok(public_key) = PublicKey::try_from_z32(key) else {
+        return (StatusCode::BAD_REQUEST, "Invalid public key in dav path").into_response();
+    };
+    let path_and_query = match req.uri().query() {
+        Some(query) => format!("{path}?{query}"),
+        None => path.to_string(),
+    };
+    let mut parts = req.uri().clone().into_parts();
+    parts.path_and_query = PathAndQuery::try_from(path_and_query).ok();
+    let Ok(uri) = Uri::from_parts(parts) else {
+        return (StatusCode::BAD_REQUEST, "Invalid dav path").into_response();
+    };
+    *req.uri_mut() = uri;
+    req.extensions_mut().insert(PubkyHost(public_key));
+    next.run(req).await
+}
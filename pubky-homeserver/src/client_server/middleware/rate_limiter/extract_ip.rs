@@ -35,3 +35,82 @@ pub fn extract_ip<T>(req: &Request<T>) -> anyhow::Result<IpAddr> {
         .or_else(|| maybe_connect_info(req))
         .ok_or(anyhow::anyhow!("Failed to extract ip."))
 }
+
+/// The client IP of `req`: the peer address, or the address a trusted proxy in
+/// `trusted_proxies` forwarded the request for.
+///
+/// `X-Forwarded-For` is read from the right, skipping further trusted proxies, as
+/// every hop appends the address it received the request from and anything to the
+/// left of a trusted hop may be made up by the client.
+pub fn client_ip<T>(req: &Request<T>, trusted_proxies: &[IpAddr]) -> Option<IpAddr> {
+    let peer = maybe_connect_info(req)?;
+    if !trusted_proxies.contains(&peer) {
+        return Some(peer);
+    }
+    let headers = req.headers();
+    let forwarded = headers
+        .get(X_FORWARDED_FOR)
+        .and_then(|hv| hv.to_str().ok())
+        .and_then(|s| {
+            s.rsplit(',')
+                .map_while(|s| s.trim().parse::<IpAddr>().ok())
+                .find(|ip| !trusted_proxies.contains(ip))
+        });
+    Some(
+        forwarded
+            .or_else(|| maybe_x_real_ip(headers))
+            .unwrap_or(peer),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::ConnectInfo;
+
+    fn request(peer: &str, headers: &[(&str, &str)]) -> Request<()> {
+        let mut builder = Request::builder();
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        let mut req = builder.body(()).unwrap();
+        let peer: SocketAddr = format!("{peer}:4000").parse().unwrap();
+        req.extensions_mut().insert(ConnectInfo(peer));
+        req
+    }
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn client_ip_ignores_headers_from_untrusted_peers() {
+        let req = request(
+            "203.0.113.7",
+            &[
+                (X_FORWARDED_FOR, "198.51.100.1"),
+                (X_REAL_IP, "198.51.100.2"),
+            ],
+        );
+        assert_eq!(client_ip(&req, &[]), Some(ip("203.0.113.7")));
+        assert_eq!(client_ip(&req, &[ip("10.0.0.1")]), Some(ip("203.0.113.7")));
+    }
+
+    #[test]
+    fn client_ip_reads_headers_from_trusted_proxies() {
+        let proxies = [ip("10.0.0.1"), ip("10.0.0.2")];
+
+        // The client-supplied left entry is ignored, trusted hops are skipped.
+        let req = request(
+            "10.0.0.1",
+            &[(X_FORWARDED_FOR, "192.0.2.9, 198.51.100.1, 10.0.0.2")],
+        );
+        assert_eq!(client_ip(&req, &proxies), Some(ip("198.51.100.1")));
+
+        let req = request("10.0.0.1", &[(X_REAL_IP, "198.51.100.2")]);
+        assert_eq!(client_ip(&req, &proxies), Some(ip("198.51.100.2")));
+
+        let req = request("10.0.0.1", &[]);
+        assert_eq!(client_ip(&req, &proxies), Some(ip("10.0.0.1")));
+    }
+}
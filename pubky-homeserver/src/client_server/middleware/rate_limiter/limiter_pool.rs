@@ -14,7 +14,7 @@ use governor::state::keyed::DashMapStateStore;
 use governor::{Quota, RateLimiter};
 
 use crate::data_directory::quota_config::BandwidthQuota;
-use crate::quota_config::{LimitKey, LimitKeyType, PathLimit};
+use crate::quota_config::{ClassLimit, LimitKey, LimitKeyType, PathLimit, RouteClass};
 
 use super::extract_ip::extract_ip;
 use super::CLEANUP_INTERVAL_SECS;
@@ -77,16 +77,68 @@ impl LimiterPool {
     }
 }
 
-/// A path limit paired with its governor rate limiter instance.
+/// A configured request-count limit: either on a path and method, or on a route class.
+#[derive(Debug, Clone)]
+pub(super) enum RequestLimit {
+    Path(PathLimit),
+    Class(ClassLimit),
+}
+
+impl RequestLimit {
+    pub fn key_type(&self) -> &LimitKeyType {
+        match self {
+            RequestLimit::Path(limit) => &limit.key,
+            RequestLimit::Class(limit) => &limit.key,
+        }
+    }
+
+    /// Check if the key is whitelisted.
+    pub fn is_whitelisted(&self, key: &LimitKey) -> bool {
+        match self {
+            RequestLimit::Path(limit) => limit.is_whitelisted(key),
+            RequestLimit::Class(limit) => limit.is_whitelisted(key),
+        }
+    }
+
+    /// Label of the limit in metrics: the route class, or `path` for path limits.
+    pub fn scope(&self) -> &'static str {
+        match self {
+            RequestLimit::Path(_) => "path",
+            RequestLimit::Class(limit) => limit.class.as_str(),
+        }
+    }
+}
+
+impl std::fmt::Display for RequestLimit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RequestLimit::Path(limit) => limit.fmt(f),
+            RequestLimit::Class(limit) => limit.fmt(f),
+        }
+    }
+}
+
+impl TryFrom<RequestLimit> for Quota {
+    type Error = String;
+
+    fn try_from(value: RequestLimit) -> Result<Self, Self::Error> {
+        match value {
+            RequestLimit::Path(limit) => Quota::try_from(limit),
+            RequestLimit::Class(limit) => Quota::try_from(limit),
+        }
+    }
+}
+
+/// A request limit paired with its governor rate limiter instance.
 #[derive(Debug, Clone)]
 pub(super) struct LimitTuple {
-    pub limit: PathLimit,
+    pub limit: RequestLimit,
     pub limiter: Arc<KeyedRateLimiter>,
 }
 
 impl LimitTuple {
-    pub fn new(path_limit: PathLimit) -> Result<Self, String> {
-        let quota = Quota::try_from(path_limit.clone())?;
+    pub fn new(limit: RequestLimit) -> Result<Self, String> {
+        let quota = Quota::try_from(limit.clone())?;
         let limiter = Arc::new(RateLimiter::keyed(quota));
 
         // Forget keys that are not used anymore. This is to prevent memory leaks.
@@ -105,10 +157,7 @@ impl LimitTuple {
             }
         });
 
-        Ok(Self {
-            limit: path_limit,
-            limiter,
-        })
+        Ok(Self { limit, limiter })
     }
 
     /// Extract the key from the request.
@@ -116,7 +165,7 @@ impl LimitTuple {
     /// The key is either the ip address of the client
     /// or the user pubkey.
     pub fn extract_key(&self, req: &Request<Body>) -> anyhow::Result<LimitKey> {
-        match self.limit.key {
+        match self.limit.key_type() {
             LimitKeyType::Ip => extract_ip(req).map(LimitKey::Ip),
             LimitKeyType::User => {
                 // Extract the user pubkey from the request.
@@ -131,9 +180,12 @@ impl LimitTuple {
     /// Check if the request matches the limit.
     pub fn is_match(&self, req: &Request<Body>) -> bool {
         let path = req.uri().path();
-        let glob_match = self.limit.path.is_match(path);
-        let method_match = self.limit.method.0 == req.method();
-        glob_match && method_match
+        match &self.limit {
+            RequestLimit::Path(limit) => {
+                limit.path.is_match(path) && limit.method.0 == req.method()
+            }
+            RequestLimit::Class(limit) => RouteClass::of(req.method(), path) == Some(limit.class),
+        }
     }
 }
 
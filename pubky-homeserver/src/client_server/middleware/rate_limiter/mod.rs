@@ -10,6 +10,7 @@ mod request_rate_limit;
 mod throttle;
 
 pub use bandwidth_rate_limit::BandwidthQuotaLimitLayer;
+pub(crate) use extract_ip::client_ip;
 pub use request_rate_limit::RequestRateLimitLayer;
 
 #[cfg(test)]
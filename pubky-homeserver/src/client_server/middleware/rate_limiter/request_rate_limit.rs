@@ -1,32 +1,37 @@
 //! Request-count rate limiting layer.
 //!
-//! Enforces per-path request-count quotas (`[[drive.rate_limits]]` in config).
-//! Each path+method pattern has a governor rate limiter keyed by IP or user.
+//! Enforces per-path request-count quotas (`[[drive.rate_limits]]` in config) and
+//! per-route-class quotas (`[[drive.class_rate_limits]]`). Each limit has a governor
+//! rate limiter keyed by IP or user.
 
 use axum::response::{IntoResponse, Response};
 use axum::{
     body::Body,
-    http::{Request, StatusCode},
+    http::{header::RETRY_AFTER, HeaderValue, Request, StatusCode},
 };
 use futures_util::future::BoxFuture;
+use governor::clock::Clock;
 use std::{convert::Infallible, task::Poll};
 use tower::{Layer, Service};
 
-use crate::quota_config::PathLimit;
+use crate::observability::Metrics;
+use crate::quota_config::{ClassLimit, PathLimit};
 use crate::shared::HttpError;
 
-use super::limiter_pool::LimitTuple;
+use super::limiter_pool::{LimitTuple, RequestLimit};
 
 /// A Tower Layer for request-count rate limiting.
 ///
-/// Matches requests by path and method against configured limits and
-/// returns 429 TOO MANY REQUESTS when a limit is exceeded.
+/// Matches requests by path and method, or by route class, against configured
+/// limits and returns 429 TOO MANY REQUESTS with a `Retry-After` header when a
+/// limit is exceeded.
 ///
 /// Returns 400 BAD REQUEST if the rate-limit key (IP or pubky-host)
 /// cannot be extracted.
 #[derive(Debug, Clone)]
 pub struct RequestRateLimitLayer {
     limits: Vec<LimitTuple>,
+    metrics: Option<Metrics>,
 }
 
 impl RequestRateLimitLayer {
@@ -43,9 +48,35 @@ impl RequestRateLimitLayer {
         }
         let limits = limits
             .into_iter()
-            .map(LimitTuple::new)
+            .map(|limit| LimitTuple::new(RequestLimit::Path(limit)))
             .collect::<Result<Vec<_>, _>>()?;
-        Ok(Self { limits })
+        Ok(Self {
+            limits,
+            metrics: None,
+        })
+    }
+
+    /// Also limit whole route classes (`[[drive.class_rate_limits]]`).
+    pub fn with_class_limits(mut self, limits: Vec<ClassLimit>) -> Result<Self, String> {
+        if !limits.is_empty() {
+            let limits_str = limits
+                .iter()
+                .map(|limit| format!("\"{limit}\""))
+                .collect::<Vec<_>>()
+                .join(", ");
+            tracing::info!("Route-class rate limits configured: {limits_str}");
+        }
+        for limit in limits {
+            self.limits
+                .push(LimitTuple::new(RequestLimit::Class(limit))?);
+        }
+        Ok(self)
+    }
+
+    /// Count rejected requests in `metrics`.
+    pub fn with_metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = Some(metrics);
+        self
     }
 }
 
@@ -53,8 +84,11 @@ impl<S> Layer<S> for RequestRateLimitLayer {
     type Service = RequestRateLimitMiddleware<S>;
 
     fn layer(&self, inner: S) -> Self::Service {
-        let limits = self.limits.clone();
-        RequestRateLimitMiddleware { inner, limits }
+        RequestRateLimitMiddleware {
+            inner,
+            limits: self.limits.clone(),
+            metrics: self.metrics.clone(),
+        }
     }
 }
 
@@ -62,6 +96,7 @@ impl<S> Layer<S> for RequestRateLimitLayer {
 pub struct RequestRateLimitMiddleware<S> {
     inner: S,
     limits: Vec<LimitTuple>,
+    metrics: Option<Metrics>,
 }
 
 impl<S> Service<Request<Body>> for RequestRateLimitMiddleware<S>
@@ -85,9 +120,10 @@ where
         }
 
         let limits = self.limits.clone();
+        let metrics = self.metrics.clone();
 
         Box::pin(async move {
-            if let Err(resp) = check_request_count_limits(&limits, &req) {
+            if let Err(resp) = check_request_count_limits(&limits, metrics.as_ref(), &req) {
                 return Ok(resp);
             }
             inner.call(req).await
@@ -97,7 +133,11 @@ where
 
 /// Check request-count path limits. Returns an error response if any limit is exceeded.
 #[allow(clippy::result_large_err)]
-fn check_request_count_limits(limits: &[LimitTuple], req: &Request<Body>) -> Result<(), Response> {
+fn check_request_count_limits(
+    limits: &[LimitTuple],
+    metrics: Option<&Metrics>,
+    req: &Request<Body>,
+) -> Result<(), Response> {
     for limit in limits {
         if !limit.is_match(req) {
             continue;
@@ -106,9 +146,8 @@ fn check_request_count_limits(limits: &[LimitTuple], req: &Request<Body>) -> Res
             Ok(key) => key,
             Err(e) => {
                 tracing::warn!(
-                    "{} {} Failed to extract key for rate limiting: {}",
-                    limit.limit.path.0,
-                    limit.limit.method.0,
+                    "{} Failed to extract key for rate limiting: {}",
+                    limit.limit,
                     e
                 );
                 return Err(HttpError::new_with_message(
@@ -122,21 +161,28 @@ fn check_request_count_limits(limits: &[LimitTuple], req: &Request<Body>) -> Res
             continue;
         }
         if let Err(e) = limit.limiter.check_key(&key) {
-            tracing::debug!(
-                "Rate limit of {} exceeded for {key}: {}",
-                limit.limit.quota,
-                e
-            );
-            return Err(HttpError::new_with_message(
-                StatusCode::TOO_MANY_REQUESTS,
-                "Rate limit exceeded",
-            )
-            .into_response());
+            tracing::debug!("Rate limit \"{}\" exceeded for {key}: {}", limit.limit, e);
+            if let Some(metrics) = metrics {
+                metrics.record_rate_limited(limit.limit.scope(), limit.limit.key_type().as_str());
+            }
+            let wait = e.wait_time_from(limit.limiter.clock().now());
+            let mut resp =
+                HttpError::new_with_message(StatusCode::TOO_MANY_REQUESTS, "Rate limit exceeded")
+                    .into_response();
+            resp.headers_mut()
+                .insert(RETRY_AFTER, HeaderValue::from(retry_after_secs(wait)));
+            return Err(resp);
         }
     }
     Ok(())
 }
 
+/// Whole seconds until a request is allowed again, rounded up and at least one.
+fn retry_after_secs(wait: std::time::Duration) -> u64 {
+    let secs = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+    secs.max(1)
+}
+
 #[cfg(test)]
 mod tests {
     use std::net::{IpAddr, Ipv4Addr, SocketAddr};
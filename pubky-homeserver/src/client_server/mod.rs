@@ -8,6 +8,7 @@ mod app;
 pub(crate) mod app_state;
 pub(crate) mod auth;
 pub(crate) mod cache_policy;
+mod extensions;
 mod middleware;
 mod query_params;
 pub(crate) mod routes;
@@ -15,3 +16,4 @@ pub(crate) mod routes;
 pub use app::create_app;
 pub use app::{ClientServer, ClientServerBuildError};
 pub(crate) use app_state::AppState;
+pub use extensions::RouterExtensions;
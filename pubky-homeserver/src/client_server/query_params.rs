@@ -1,8 +1,9 @@
 use std::collections::HashMap;
 
 use axum::{
+    body::Body,
     extract::{FromRequestParts, Query},
-    http::request::Parts,
+    http::{request::Parts, StatusCode},
     response::{IntoResponse, Response},
     RequestPartsExt,
 };
@@ -15,6 +16,22 @@ pub struct ListQueryParams {
     pub cursor: Option<String>,
     pub shallow: bool,
     pub reverse: bool,
+    pub snapshot: Option<ListSnapshotParam>,
+    /// Only list entries modified at or after this Unix timestamp in microseconds.
+    pub modified_since: Option<u64>,
+    /// Return aggregate directory statistics instead of a listing.
+    pub stats: bool,
+    /// Return the metadata of a file as JSON instead of its content.
+    pub metadata: bool,
+}
+
+/// The `snapshot` query parameter of a directory listing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListSnapshotParam {
+    /// `snapshot=new`: open a new snapshot and serve this page from it.
+    New,
+    /// `snapshot=<token>`: serve this page from a previously opened snapshot.
+    Resume(String),
 }
 
 impl ListQueryParams {
@@ -34,6 +51,16 @@ impl ListQueryParams {
         }
         Some(value.to_string())
     }
+
+    /// Extracts the snapshot parameter.
+    /// If the value is empty, returns None.
+    pub fn extract_snapshot(params: &Query<HashMap<String, String>>) -> Option<ListSnapshotParam> {
+        match params.get("snapshot")?.as_str() {
+            "" => None,
+            "new" => Some(ListSnapshotParam::New),
+            token => Some(ListSnapshotParam::Resume(token.to_string())),
+        }
+    }
 }
 
 impl<S> FromRequestParts<S> for ListQueryParams
@@ -58,18 +85,44 @@ where
             false
         };
 
+        let stats = if let Some(stats) = params.get("stats") {
+            parse_bool(stats).map_err(|e| *e)?
+        } else {
+            false
+        };
+
+        let metadata = if let Some(metadata) = params.get("metadata") {
+            parse_bool(metadata).map_err(|e| *e)?
+        } else {
+            false
+        };
+
         let limit = params
             .get("limit")
             // Treat `limit=` as None
             .filter(|&l| !l.is_empty())
             .and_then(|l| l.parse::<u16>().ok());
         let cursor = Self::extract_cursor(&params);
+        let snapshot = Self::extract_snapshot(&params);
+        let modified_since = match params.get("modified_since").filter(|v| !v.is_empty()) {
+            Some(value) => Some(value.parse::<u64>().map_err(|_| {
+                Response::builder()
+                    .status(StatusCode::BAD_REQUEST)
+                    .body(Body::from("Invalid modified_since parameter"))
+                    .unwrap()
+            })?),
+            None => None,
+        };
 
         Ok(ListQueryParams {
             shallow,
             limit,
             cursor,
             reverse,
+            snapshot,
+            modified_since,
+            stats,
+            metadata,
         })
     }
 }
@@ -345,7 +345,24 @@ pub async fn feed(
 /// data: pubky://user_pubkey/pub/example.txt
 /// data: cursor: 42
 /// data: content_hash: r0NJufX5oaagQE3qNtzJSZvLJcmtwRK3zJqTyuQfMmI=
+/// data: signature: <base64 Ed25519 signature>
 /// ```
+///
+/// The `signature` is made with the homeserver keypair over
+/// [`pubky_common::events::signable_event`], so mirrors can verify entries without
+/// trusting the connection.
+///
+/// ## Cursor Contiguity
+/// Cursors are global event IDs, so a user's cursors are increasing but not
+/// consecutive. Instead, every forward-ordered event carries a `prev_cursor` line:
+/// ```text
+/// data: prev_cursor: 41
+/// ```
+/// It is the cursor of the user's previous event matching the subscription's paths,
+/// read from the database rather than from what this stream has sent, so no event of
+/// that user matching the paths lies between `prev_cursor` and `cursor`. The line is
+/// omitted when the user has no earlier matching event, and for `reverse=true` streams.
+/// A client whose last seen cursor is behind `prev_cursor` has missed events.
 pub async fn feed_stream(
     State(state): State<AppState>,
     session: Option<AuthSession>,
@@ -433,6 +450,9 @@ pub async fn feed_stream(
             state.metrics.record_event_stream_db_query(query_start.elapsed().as_millis());
 
             let event_count = events.len();
+            // A batch holds every matching event of a user past their cursor, so only
+            // a user's first event in it needs its predecessor from the database.
+            let mut batch_prev: HashMap<i32, EventCursor> = HashMap::new();
 
             // Stream each historical event
             for event in events {
@@ -442,10 +462,32 @@ pub async fn feed_stream(
 
                 // Update the cursor for this specific user
                 user_cursor_map.insert(event.user_id, Some(event.cursor()));
+                let prev = if params.reverse {
+                    None
+                } else if let Some(prev) = batch_prev.insert(event.user_id, event.cursor()) {
+                    Some(prev)
+                } else {
+                    match state
+                        .events_service
+                        .get_prev_cursor(
+                            event.user_id,
+                            event.cursor(),
+                            &allowed_paths,
+                            &mut state.sql_db.pool().into(),
+                        )
+                        .await
+                    {
+                        Ok(prev) => prev,
+                        Err(e) => {
+                            tracing::error!("Database error while fetching previous event: {}", e);
+                            return;
+                        }
+                    }
+                };
 
                 yield Ok(Event::default()
                     .event(event.event_type.to_string())
-                    .data(event.to_sse_data()));
+                    .data(event.to_stream_sse_data(&state.keypair, prev)));
 
                 total_sent += 1;
 
@@ -497,10 +539,28 @@ pub async fn feed_stream(
 
                         // Update this user's cursor
                         user_cursor_map.insert(event.user_id, Some(event.cursor()));
+                        // Read from the database, so a missed broadcast shows up as a
+                        // `prev_cursor` ahead of the previous event sent.
+                        let prev = match state
+                            .events_service
+                            .get_prev_cursor(
+                                event.user_id,
+                                event.cursor(),
+                                &allowed_paths,
+                                &mut state.sql_db.pool().into(),
+                            )
+                            .await
+                        {
+                            Ok(prev) => prev,
+                            Err(e) => {
+                                tracing::error!("Database error while fetching previous event: {}", e);
+                                return;
+                            }
+                        };
 
                         yield Ok(Event::default()
                             .event(event.event_type.to_string())
-                            .data(event.to_sse_data()));
+                            .data(event.to_stream_sse_data(&state.keypair, prev)));
 
                         total_sent += 1;
 
@@ -636,11 +696,21 @@ fn should_include_live_event(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::app_context::AppContext;
     use crate::client_server::auth::cookie::persistence::{SessionEntity, SessionSecret};
     use crate::client_server::auth::grant::session::GrantSession;
+    use crate::client_server::ClientServer;
+    use crate::persistence::sql::user::{UserEntity, UserRepository};
+    use crate::shared::webdav::EntryPath;
+    use axum::body::Body;
+    use axum::http::Request;
+    use http_body_util::BodyExt;
     use pubky_common::auth::jws::GrantId;
     use pubky_common::capabilities::{Capabilities, Capability};
     use pubky_common::crypto::Keypair;
+    use pubky_common::events::EventType;
+    use std::time::Duration;
+    use tower::ServiceExt;
 
     fn pk() -> PublicKey {
         Keypair::random().public_key()
@@ -840,4 +910,73 @@ mod tests {
         let filters = authorize(&[wd("/pub/")], &cursors(&[&a, &b]), None).unwrap();
         assert_eq!(filters, vec![pf("/pub/")]);
     }
+
+    /// Store an event without broadcasting it.
+    async fn store_event(context: &AppContext, user: &UserEntity, path: &str) -> EventEntity {
+        let path = EntryPath::new(user.public_key.clone(), wd(path));
+        context
+            .events_service
+            .create_event(
+                user.id,
+                EventType::Delete,
+                &path,
+                &mut context.sql_db.pool().into(),
+            )
+            .await
+            .unwrap()
+    }
+
+    /// Read the next SSE message off a streaming body.
+    async fn next_sse_message(body: &mut Body) -> String {
+        let mut message = String::new();
+        while !message.ends_with("\n\n") {
+            let frame = body.frame().await.expect("stream ended").unwrap();
+            let data = frame.into_data().expect("data frame");
+            message.push_str(std::str::from_utf8(&data).unwrap());
+        }
+        message
+    }
+
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn live_prev_cursor_reveals_an_event_missed_by_the_broadcast() {
+        let context = AppContext::test().await;
+        let router = ClientServer::create_router(&context).unwrap();
+        let user = UserRepository::create(
+            &Keypair::random().public_key(),
+            &mut context.sql_db.pool().into(),
+        )
+        .await
+        .unwrap();
+        let first = store_event(&context, &user, "/pub/a").await;
+
+        let uri = format!(
+            "/events-stream?user={}:{}&live=true",
+            user.public_key.z32(),
+            first.id
+        );
+        let response = router
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let mut body = response.into_body();
+
+        // The subscriber is caught up at `first` and idles in live mode.
+        let idle = tokio::time::timeout(Duration::from_millis(500), body.frame()).await;
+        assert!(idle.is_err(), "unexpected message: {idle:?}");
+
+        // `missed` is stored but never broadcast, so the stream skips it.
+        let missed = store_event(&context, &user, "/pub/b").await;
+        let next = store_event(&context, &user, "/pub/c").await;
+        context.events_service.broadcast_event(next.clone());
+
+        let message = next_sse_message(&mut body).await;
+        assert!(message.contains(&format!("data: cursor: {}\n", next.id)));
+        // Gap-detecting clients compare this with the last cursor they saw, `first`.
+        assert!(
+            message.contains(&format!("data: prev_cursor: {}\n", missed.id)),
+            "{message}"
+        );
+    }
 }
@@ -4,6 +4,7 @@
 //! - [`root`]: Server info endpoint.
 //! - [`signup_tokens`]: Signup token validation.
 //! - [`tenants`]: Per-user data routes (read, write).
+//! - [`time`]: Server clock, for clients to measure their clock skew.
 //!
 //! Auth routes (signup, signin, session management) live in [`crate::client_server::auth::routes`].
 
@@ -11,3 +12,4 @@ pub(crate) mod events;
 pub(crate) mod root;
 pub(crate) mod signup_tokens;
 pub(crate) mod tenants;
+pub(crate) mod time;
@@ -1,5 +1,32 @@
+use axum::http::header;
 use axum::response::IntoResponse;
 
+/// Product token announced in the root `Server` header, e.g. `pubky.org@0.9.3`.
+const SERVER: &str = concat!("pubky.org@", env!("CARGO_PKG_VERSION"));
+
 pub async fn handler() -> Result<impl IntoResponse, String> {
-    Ok("Pubky Homeserver")
+    Ok(([(header::SERVER, SERVER)], "Pubky Homeserver"))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request, routing::get, Router};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn root_announces_the_version() {
+        let response = Router::new()
+            .route("/", get(handler))
+            .oneshot(Request::get("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert!(response.status().is_success());
+        assert_eq!(
+            response.headers().get(header::SERVER).unwrap(),
+            concat!("pubky.org@", env!("CARGO_PKG_VERSION"))
+        );
+    }
 }
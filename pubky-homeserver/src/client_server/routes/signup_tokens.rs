@@ -35,7 +35,11 @@ pub async fn get(
     State(state): State<AppState>,
     Path(token): Path<String>,
 ) -> HttpResult<impl IntoResponse> {
-    if state.signup_mode != SignupMode::TokenRequired {
+    let mode = state
+        .signup_policy_service
+        .current(&mut state.sql_db.pool().into())
+        .await?;
+    if mode != SignupMode::TokenRequired {
         return Err(HttpError::new_with_message(
             StatusCode::BAD_REQUEST,
             "Signup tokens not required",
@@ -0,0 +1,400 @@
+//! Bulk import of an account archive, see [`ArchiveManifest`].
+
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, SeekFrom};
+
+use axum::{body::Body, extract::State, http::StatusCode, Json};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bytes::Bytes;
+use futures_util::{Stream, StreamExt};
+use http_body_util::{LengthLimitError, Limited};
+use percent_encoding::percent_decode_str;
+use pubky_common::crypto::{Hash, Hasher, PublicKey};
+use pubky_common::storage::{
+    ArchiveManifest, ArchiveManifestEntry, ImportEntryResult, ImportEntryStatus, ImportResponse,
+    ARCHIVE_MANIFEST_NAME, ARCHIVE_MANIFEST_VERSION,
+};
+
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio_util::io::ReaderStream;
+
+use super::write::{apply_content_policy, check_path_limits};
+use super::MAX_BODY_BYTES;
+use crate::{
+    client_server::{
+        auth::{has_write_permission, AuthSession},
+        middleware::pubky_host::PubkyHost,
+        AppState,
+    },
+    persistence::{
+        files::{
+            user_quota_layer::{resolve_storage_max_bytes, would_exceed_limit},
+            FileIoError, ReceivedUpload, WriteMetadata, WriteStreamError,
+        },
+        sql::entry::EntryRepository,
+    },
+    services::user_service::FILE_METADATA_SIZE,
+    shared::{
+        webdav::{EntryPath, WebDavPath},
+        HttpError, HttpResult,
+    },
+};
+
+/// A file of an account archive, located in the received body.
+#[derive(Debug)]
+struct ArchiveFile {
+    /// Offset of the content in the body.
+    offset: u64,
+    size: u64,
+    content_hash: Hash,
+}
+
+/// A manifest entry that passed every check and must be written, changing the user's
+/// storage usage by `bytes_delta`.
+struct PendingWrite {
+    path: EntryPath,
+    file: ArchiveFile,
+    bytes_delta: i64,
+}
+
+/// Write the files of an account archive into the tenant's account.
+///
+/// The body is a tar archive starting with an [`ArchiveManifest`]. Every entry is
+/// checked before anything is written: its content must match the manifest hash, and
+/// its path must be writable by the session and pass the path limits and the content
+/// policy. Files that already have the same content are left untouched, and entries
+/// are accepted in manifest order for as long as they fit the user's storage quota.
+///
+/// The body is received into a temporary file first, up to [`MAX_BODY_BYTES`], and
+/// every file is read back from there, so an archive is never held in memory. Each file
+/// is written under the lock appends take, so it never lands between an append's length
+/// check and its write.
+///
+/// A rejected entry does not stop the import. Answers `200 OK` with an
+/// [`ImportResponse`] holding the outcome of every manifest entry, or
+/// `400 Bad Request` if the archive itself is malformed.
+pub async fn import(
+    State(state): State<AppState>,
+    session: AuthSession,
+    pubky: PubkyHost,
+    body: Body,
+) -> HttpResult<Json<ImportResponse>> {
+    let public_key = pubky.public_key();
+    let user = state
+        .user_service
+        .get_or_http_error(public_key, true)
+        .await?;
+    let archive = receive_archive(body, MAX_BODY_BYTES).await?;
+    let mut reader = archive.try_clone().await?.into_std().await;
+    let (manifest, mut files) = tokio::task::spawn_blocking(move || read_archive(&mut reader))
+        .await
+        .map_err(HttpError::internal_server_and_log)??;
+
+    let max_bytes = resolve_storage_max_bytes(&user, state.default_storage_mb);
+    let mut used_bytes = user.used_bytes;
+    let mut entries = Vec::with_capacity(manifest.entries.len());
+    let mut writes = Vec::new();
+    for entry in manifest.entries {
+        let Some(file) = files.remove(&entry.path) else {
+            entries.push(entry_result(entry.path, Ok(ImportEntryStatus::Missing)));
+            continue;
+        };
+        let status = match check_entry(&state, &session, public_key, &archive, &entry, file).await {
+            Ok(None) => Ok(ImportEntryStatus::Unchanged),
+            Ok(Some(write)) if would_exceed_limit(used_bytes, write.bytes_delta, max_bytes) => {
+                Err(HttpError::insufficient_storage())
+            }
+            Ok(Some(write)) => {
+                used_bytes = used_bytes.saturating_add_signed(write.bytes_delta);
+                writes.push((entries.len(), write));
+                Ok(ImportEntryStatus::Imported)
+            }
+            Err(e) => Err(e),
+        };
+        entries.push(entry_result(entry.path, status));
+    }
+
+    for (index, write) in writes {
+        let written = match read_file(&archive, &write.file).await {
+            Ok(content) => match state.file_service.lock_path(&write.path).await {
+                Ok(lock) => {
+                    state
+                        .file_service
+                        .write_locked(lock, content, WriteMetadata::default())
+                        .await
+                }
+                Err(e) => Err(e),
+            },
+            Err(e) => Err(e),
+        };
+        if let Err(e) = written {
+            let entry = &mut entries[index];
+            *entry = entry_result(std::mem::take(&mut entry.path), Err(e.into()));
+        }
+    }
+    Ok(Json(ImportResponse { entries }))
+}
+
+/// Receive an archive of at most `limit` bytes into a temporary file.
+///
+/// The body is read directly, so the route's `DefaultBodyLimit` does not apply to it.
+async fn receive_archive(body: Body, limit: usize) -> HttpResult<tokio::fs::File> {
+    let body = Body::new(Limited::new(body, limit))
+        .into_data_stream()
+        .map(|chunk_result| chunk_result.map_err(WriteStreamError::Axum));
+    match ReceivedUpload::receive(body, None).await {
+        Ok(archive) => Ok(archive.into_file()),
+        Err(FileIoError::StreamBroken(WriteStreamError::Axum(e)))
+            if std::error::Error::source(&e).is_some_and(|e| e.is::<LengthLimitError>()) =>
+        {
+            Err(HttpError::new_with_message(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                format!("Archives are limited to {limit} bytes"),
+            ))
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Check whether `entry` may be imported with `file` from `archive`.
+///
+/// Returns `None` if the file already has this content.
+async fn check_entry(
+    state: &AppState,
+    session: &AuthSession,
+    public_key: &PublicKey,
+    archive: &tokio::fs::File,
+    entry: &ArchiveManifestEntry,
+    file: ArchiveFile,
+) -> HttpResult<Option<PendingWrite>> {
+    let path = percent_decode_str(&entry.path)
+        .decode_utf8()
+        .ok()
+        .and_then(|path| WebDavPath::new(&path).ok())
+        .filter(WebDavPath::is_file)
+        .ok_or_else(|| HttpError::bad_request("Path is not a valid file path"))?;
+    has_write_permission(session, public_key, &path)?;
+    check_path_limits(
+        path.as_str(),
+        state.max_path_length,
+        state.max_path_segments,
+    )?;
+    // Imports write through the same storage layers as a PUT, but are checked up front
+    // so a forbidden entry does not take quota from the entries after it.
+    let quota = state.user_service.resolve_quota(public_key).await?;
+    if quota.is_some_and(|quota| !quota.is_write_path_allowed(path.as_str())) {
+        return Err(FileIoError::WritePathForbidden.into());
+    }
+
+    let content_hash = file.content_hash;
+    let manifest_hash = STANDARD.decode(&entry.content_hash).unwrap_or_default();
+    if file.size != entry.size || manifest_hash != content_hash.as_bytes() {
+        return Err(HttpError::bad_request(
+            "Content does not match the manifest hash",
+        ));
+    }
+    // The size is known, so the policy is fully applied without reading the stream.
+    let _ = apply_content_policy(
+        &state.content_policy,
+        path.as_str(),
+        Some(entry.size),
+        read_file(archive, &file).await?,
+    )
+    .await?;
+
+    let path = EntryPath::new(public_key.clone(), path);
+    let existing = match EntryRepository::get_by_path(&path, &mut state.sql_db.pool().into()).await
+    {
+        Ok(existing) => Some(existing),
+        Err(sqlx::Error::RowNotFound) => None,
+        Err(e) => return Err(e.into()),
+    };
+    let bytes_delta = match existing {
+        // Rewriting an expired file clears its expiry.
+        Some(existing) if existing.content_hash == content_hash && !existing.is_expired() => {
+            return Ok(None);
+        }
+        Some(existing) => entry.size as i64 - existing.content_length as i64,
+        None => (entry.size + FILE_METADATA_SIZE) as i64,
+    };
+    Ok(Some(PendingWrite {
+        path,
+        file,
+        bytes_delta,
+    }))
+}
+
+/// Stream the content of `file` from `archive`.
+async fn read_file(
+    archive: &tokio::fs::File,
+    file: &ArchiveFile,
+) -> Result<impl Stream<Item = Result<Bytes, WriteStreamError>> + Unpin + Send, FileIoError> {
+    let mut reader = archive.try_clone().await?;
+    reader.seek(SeekFrom::Start(file.offset)).await?;
+    Ok(ReaderStream::new(reader.take(file.size))
+        .map(|chunk| chunk.map_err(|e| WriteStreamError::Other(e.into()))))
+}
+
+fn entry_result(path: String, status: HttpResult<ImportEntryStatus>) -> ImportEntryResult {
+    match status {
+        Ok(status) => ImportEntryResult {
+            path,
+            status,
+            message: None,
+        },
+        Err(e) => ImportEntryResult {
+            path,
+            status: ImportEntryStatus::Rejected,
+            message: Some(e.message().to_string()),
+        },
+    }
+}
+
+/// Split an account archive into its manifest and the location of its files, keyed by
+/// manifest path. Files are hashed while they are read, never held in memory.
+///
+/// An archive may end without the tar trailer, as a cancelled export leaves it.
+fn read_archive(body: impl Read) -> HttpResult<(ArchiveManifest, HashMap<String, ArchiveFile>)> {
+    let invalid = |message: String| HttpError::bad_request(format!("Invalid archive: {message}"));
+    let mut archive = tar::Archive::new(body);
+    let mut manifest: Option<(ArchiveManifest, HashSet<String>)> = None;
+    let mut files = HashMap::new();
+    for entry in archive.entries().map_err(|e| invalid(e.to_string()))? {
+        let mut entry = entry.map_err(|e| invalid(e.to_string()))?;
+        if entry.header().entry_type() != tar::EntryType::Regular {
+            continue;
+        }
+        let name = String::from_utf8_lossy(&entry.path_bytes()).into_owned();
+
+        let Some((_, paths)) = &manifest else {
+            if name != ARCHIVE_MANIFEST_NAME {
+                return Err(invalid(format!(
+                    "the first file is {name}, not {ARCHIVE_MANIFEST_NAME}"
+                )));
+            }
+            let mut content = Vec::new();
+            entry
+                .read_to_end(&mut content)
+                .map_err(|e| invalid(e.to_string()))?;
+            let parsed: ArchiveManifest =
+                serde_json::from_slice(&content).map_err(|e| invalid(e.to_string()))?;
+            if parsed.version != ARCHIVE_MANIFEST_VERSION {
+                return Err(invalid(format!(
+                    "unsupported manifest version {}",
+                    parsed.version
+                )));
+            }
+            let paths = parsed.entries.iter().map(|e| e.path.clone()).collect();
+            manifest = Some((parsed, paths));
+            continue;
+        };
+        let path = format!("/{name}");
+        if !paths.contains(&path) {
+            return Err(invalid(format!("{path} is not in the manifest")));
+        }
+        let offset = entry.raw_file_position();
+        let mut hasher = Hasher::new();
+        let size = std::io::copy(&mut entry, &mut hasher).map_err(|e| invalid(e.to_string()))?;
+        let file = ArchiveFile {
+            offset,
+            size,
+            content_hash: hasher.finalize(),
+        };
+        files.insert(path, file);
+    }
+    let (manifest, _) = manifest.ok_or_else(|| invalid("the archive is empty".into()))?;
+    Ok((manifest, files))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pubky_common::crypto::hash;
+
+    fn archive(files: &[(&str, &[u8])], trailer: bool) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (name, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            builder.append_data(&mut header, name, *content).unwrap();
+        }
+        if trailer {
+            builder.into_inner().unwrap()
+        } else {
+            std::mem::take(builder.get_mut())
+        }
+    }
+
+    fn manifest(paths: &[&str]) -> Vec<u8> {
+        serde_json::to_vec(&ArchiveManifest {
+            version: ARCHIVE_MANIFEST_VERSION,
+            user: "user".into(),
+            entries: paths
+                .iter()
+                .map(|path| ArchiveManifestEntry {
+                    path: path.to_string(),
+                    content_hash: STANDARD.encode(hash(b"hello").as_bytes()),
+                    size: 5,
+                    content_type: None,
+                })
+                .collect(),
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_receive_archive_is_limited() {
+        let archive = receive_archive(Body::from(vec![1; 10]), 10).await;
+        assert!(archive.is_ok());
+
+        let err = receive_archive(Body::from(vec![1; 11]), 10)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            axum::response::IntoResponse::into_response(err).status(),
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+    }
+
+    #[test]
+    fn test_read_archive() {
+        let long = format!("/pub/app/{}.txt", "a".repeat(150));
+        let manifest_json = manifest(&["/pub/app/a.txt", &long, "/pub/app/missing.txt"]);
+        for trailer in [true, false] {
+            let body = archive(
+                &[
+                    (ARCHIVE_MANIFEST_NAME, &manifest_json),
+                    ("pub/app/a.txt", b"hello"),
+                    (&long[1..], b"hello"),
+                ],
+                trailer,
+            );
+            let (manifest, files) = read_archive(body.as_slice()).unwrap();
+            assert_eq!(manifest.entries.len(), 3);
+            assert_eq!(files.len(), 2);
+            for file in [&files["/pub/app/a.txt"], &files[&long]] {
+                let offset = file.offset as usize;
+                assert_eq!(&body[offset..offset + file.size as usize], b"hello");
+                assert_eq!(file.content_hash, hash(b"hello"));
+            }
+        }
+
+        let invalid = [
+            archive(&[], true),
+            archive(&[("pub/app/a.txt", b"hello")], true),
+            archive(
+                &[
+                    (ARCHIVE_MANIFEST_NAME, &manifest_json),
+                    ("pub/app/other.txt", b"hello"),
+                ],
+                true,
+            ),
+            archive(&[(ARCHIVE_MANIFEST_NAME, b"not json")], true),
+            b"not a tar archive".to_vec(),
+        ];
+        for body in invalid {
+            assert!(read_archive(body.as_slice()).is_err());
+        }
+    }
+}
@@ -7,28 +7,41 @@
 //! [`crate::client_server::auth::tenant_router`].
 //! Write handlers call [`crate::client_server::auth::has_write_permission`] and
 //! read handlers call [`crate::client_server::auth::has_read_permission`] to
-//! enforce capability-based access control.
+//! enforce capability-based access control. [`import::import`] checks every
+//! file of an imported archive the same way.
 
-use axum::{extract::DefaultBodyLimit, middleware, routing::get, Router};
+use axum::{
+    extract::DefaultBodyLimit,
+    middleware,
+    routing::{get, post},
+    Router,
+};
 
 use crate::client_server::{cache_policy::private_cache_policy, AppState};
 
+pub mod import;
 pub mod read;
 pub mod write;
 
+/// Largest request body accepted by the tenant routes, in bytes.
+pub(crate) const MAX_BODY_BYTES: usize = 100 * 1024 * 1024;
+
 pub fn router() -> Router<AppState> {
     // Data routes — `/pub/` reads need no auth; `/priv/` reads gate on
     // `has_read_permission` and writes gate on `has_write_permission`, each
     // after extracting the session.
     Router::new()
+        .route("/import", post(import::import))
         .route(
             "/{*path}",
             get(read::get)
                 .head(read::head)
                 .put(write::put)
+                .post(write::append)
+                .patch(write::touch)
                 .delete(write::delete),
         )
         // TODO: different max size for sessions and other routes?
-        .layer(DefaultBodyLimit::max(100 * 1024 * 1024))
+        .layer(DefaultBodyLimit::max(MAX_BODY_BYTES))
         .layer(middleware::from_fn(private_cache_policy))
 }
@@ -1,10 +1,12 @@
+use crate::persistence::files::FileIoError;
 use crate::persistence::sql::entry::{EntryEntity, EntryRepository};
+use crate::persistence::sql::UnifiedExecutor;
 use crate::shared::{HttpError, HttpResult};
 use crate::{
     client_server::{
         auth::{has_read_permission, AuthSession},
         middleware::pubky_host::PubkyHost,
-        query_params::ListQueryParams,
+        query_params::{ListQueryParams, ListSnapshotParam},
         AppState,
     },
     shared::webdav::{EntryPath, WebDavPathAxum},
@@ -14,9 +16,13 @@ use axum::{
     extract::{Path, State},
     http::{header, HeaderMap, HeaderValue, Response, StatusCode},
     response::IntoResponse,
+    Json,
 };
 use httpdate::HttpDate;
-use sqlx::types::chrono::{DateTime, Utc};
+use pubky_common::storage::{
+    attachment_disposition, DirStatsResponse, FileMetadataResponse, LIST_SNAPSHOT_HEADER,
+};
+use sqlx::types::chrono::{DateTime, NaiveDateTime, Utc};
 use std::str::FromStr;
 use std::time::SystemTime;
 
@@ -57,7 +63,19 @@ pub async fn get(
     let public_key = pubky.public_key().clone();
     let entry_path = EntryPath::new(public_key.clone(), path.inner().clone());
     if entry_path.path().is_directory() {
-        return list(state, &entry_path, params).await;
+        if params.stats {
+            return dir_stats(state, &entry_path, params.shallow).await;
+        }
+        if params.metadata {
+            return Err(HttpError::new_with_message(
+                StatusCode::BAD_REQUEST,
+                "Metadata is only available for files",
+            ));
+        }
+        return list(state, session.as_ref(), &entry_path, params).await;
+    }
+    if params.metadata {
+        return file_metadata(state, &entry_path).await;
     }
 
     let entry = state
@@ -105,18 +123,10 @@ pub async fn get(
 
 async fn list(
     state: AppState,
+    session: Option<&AuthSession>,
     entry_path: &EntryPath,
     params: ListQueryParams,
 ) -> HttpResult<Response<Body>> {
-    let contains_dir =
-        EntryRepository::contains_directory(entry_path, &mut state.sql_db.pool().into()).await?;
-    if !contains_dir {
-        return Err(HttpError::new_with_message(
-            StatusCode::NOT_FOUND,
-            "Directory Not Found",
-        ));
-    }
-
     let parsed_cursor = match parse_cursor(params.cursor) {
         Ok(cursor) => cursor,
         Err(_) => {
@@ -127,13 +137,63 @@ async fn list(
         }
     };
 
+    // With a snapshot, every page is read from the same point in time.
+    // Opening one holds a database connection, so it is reserved to signed-in users.
+    let owner = entry_path.pubkey();
+    let snapshot_token = match params.snapshot {
+        Some(ListSnapshotParam::New) => {
+            let session = session.ok_or_else(|| {
+                HttpError::unauthorized_with_message(
+                    "Authentication required to open a list snapshot",
+                )
+            })?;
+            let service = &state.list_snapshot_service;
+            Some(service.create(owner, session.user_key()).await?)
+        }
+        Some(ListSnapshotParam::Resume(token)) => Some(token),
+        None => None,
+    };
+    let mut snapshot_tx = match &snapshot_token {
+        Some(token) => Some(state.list_snapshot_service.begin(owner, token).await?),
+        None => None,
+    };
+    let pool = state.sql_db.pool();
+    let mut executor = match snapshot_tx.as_mut() {
+        Some(tx) => UnifiedExecutor::from_tx(tx),
+        None => pool.into(),
+    };
+
+    let contains_dir = EntryRepository::contains_directory(entry_path, &mut executor).await?;
+    if !contains_dir {
+        return Err(HttpError::new_with_message(
+            StatusCode::NOT_FOUND,
+            "Directory Not Found",
+        ));
+    }
+
+    let modified_since = match params.modified_since {
+        Some(micros) => Some(
+            i64::try_from(micros)
+                .ok()
+                .and_then(DateTime::<Utc>::from_timestamp_micros)
+                .ok_or_else(|| {
+                    HttpError::new_with_message(
+                        StatusCode::BAD_REQUEST,
+                        "Invalid modified_since parameter",
+                    )
+                })?
+                .naive_utc(),
+        ),
+        None => None,
+    };
     let entries = if params.shallow {
         EntryRepository::list_shallow(
             entry_path,
             params.limit,
             parsed_cursor,
             params.reverse,
-            &mut state.sql_db.pool().into(),
+            modified_since,
+            &mut executor,
         )
         .await?
     } else {
@@ -142,7 +202,8 @@ async fn list(
             params.limit,
             parsed_cursor,
             params.reverse,
-            &mut state.sql_db.pool().into(),
+            modified_since,
+            &mut executor,
         )
         .await?
     };
@@ -151,10 +212,66 @@ async fn list(
         .map(|entry| format!("pubky://{}", entry))
         .collect::<Vec<_>>();
 
-    Ok(Response::builder()
+    let mut response = Response::builder()
         .status(StatusCode::OK)
-        .header(header::CONTENT_TYPE, "text/plain")
-        .body(Body::from(pubky_urls.join("\n")))?)
+        .header(header::CONTENT_TYPE, "text/plain");
+    if let Some(token) = snapshot_token {
+        response = response.header(LIST_SNAPSHOT_HEADER, token);
+    }
+    Ok(response.body(Body::from(pubky_urls.join("\n")))?)
+}
+
+/// Aggregate statistics of a directory, computed from the entry index.
+async fn dir_stats(
+    state: AppState,
+    entry_path: &EntryPath,
+    shallow: bool,
+) -> HttpResult<Response<Body>> {
+    let contains_dir =
+        EntryRepository::contains_directory(entry_path, &mut state.sql_db.pool().into()).await?;
+    if !contains_dir {
+        return Err(HttpError::new_with_message(
+            StatusCode::NOT_FOUND,
+            "Directory Not Found",
+        ));
+    }
+
+    let stats =
+        EntryRepository::dir_stats(entry_path, shallow, &mut state.sql_db.pool().into()).await?;
+    let to_micros = |date: NaiveDateTime| date.and_utc().timestamp_micros() as u64;
+    let body = DirStatsResponse {
+        total_bytes: stats.total_bytes,
+        file_count: stats.file_count,
+        oldest_modified_at: stats.oldest_modified_at.map(to_micros),
+        newest_modified_at: stats.newest_modified_at.map(to_micros),
+    };
+    Ok(Json(body).into_response())
+}
+
+/// Metadata of a file, reporting a missing or expired file as `exists: false`.
+async fn file_metadata(state: AppState, entry_path: &EntryPath) -> HttpResult<Response<Body>> {
+    let entry = match state
+        .file_service
+        .get_info(entry_path, &mut state.sql_db.pool().into())
+        .await
+    {
+        Ok(entry) => entry,
+        Err(FileIoError::NotFound) => {
+            return Ok(Json(FileMetadataResponse::default()).into_response())
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let body = FileMetadataResponse {
+        exists: true,
+        size: Some(entry.content_length),
+        content_type: Some(entry.content_type).filter(|content_type| !content_type.is_empty()),
+        content_hash: Some(base64::Engine::encode(
+            &base64::engine::general_purpose::STANDARD,
+            entry.content_hash.as_bytes(),
+        )),
+        modified: Some(entry.modified_at.and_utc().timestamp_micros() as u64),
+    };
+    Ok(Json(body).into_response())
 }
 
 /// Parse the cursor if it is present.
@@ -195,7 +312,7 @@ fn not_modified_response(entry: &EntryEntity) -> HttpResult<Response<Body>> {
 }
 
 /// Convert a `NaiveDateTime` to a `HttpDate`.
-fn to_http_date(date: &sqlx::types::chrono::NaiveDateTime) -> HttpDate {
+pub(crate) fn to_http_date(date: &sqlx::types::chrono::NaiveDateTime) -> HttpDate {
     let sys_datetime = SystemTime::from(DateTime::<Utc>::from_naive_utc_and_offset(*date, Utc));
     httpdate::HttpDate::from(sys_datetime)
 }
@@ -229,6 +346,14 @@ impl EntryEntity {
             .try_into()
             .expect("base64 string is valid"),
         );
+        if let Some(disposition) = self
+            .filename
+            .as_deref()
+            .and_then(attachment_disposition)
+            .and_then(|value| HeaderValue::from_str(&value).ok())
+        {
+            headers.insert(header::CONTENT_DISPOSITION, disposition);
+        }
         // tenant-aware caching
         headers.insert(header::VARY, HeaderValue::from_static("pubky-host"));
         headers.insert(
@@ -250,6 +375,8 @@ mod tests {
         crypto::{Keypair, PublicKey},
     };
 
+    use pubky_common::storage::LIST_SNAPSHOT_HEADER;
+
     use crate::app_context::AppContext;
     use crate::client_server::ClientServer;
 
@@ -462,6 +589,63 @@ mod tests {
 
         response.assert_header(header::CONTENT_TYPE, "text/plain");
     }
+
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn content_disposition_is_stored_and_replaced() {
+        let (_, _, server, public_key, cookie) = create_environment().await.unwrap();
+        let put = |disposition: Option<&'static str>| {
+            let request = server
+                .put("/pub/upload.bin")
+                .add_header("host", public_key.z32())
+                .add_header(header::COOKIE, cookie.clone())
+                .bytes(vec![1_u8, 2, 3].into());
+            match disposition {
+                Some(value) => request.add_header(header::CONTENT_DISPOSITION, value),
+                None => request,
+            }
+        };
+
+        put(Some("attachment; filename*=UTF-8''r%C3%A9sum%C3%A9.pdf"))
+            .expect_success()
+            .await;
+        for method in [Method::GET, Method::HEAD] {
+            let response = server
+                .method(method, "/pub/upload.bin")
+                .add_header("host", public_key.z32())
+                .expect_success()
+                .await;
+            response.assert_header(
+                header::CONTENT_DISPOSITION,
+                "attachment; filename=\"r_sum_.pdf\"; filename*=UTF-8''r%C3%A9sum%C3%A9.pdf",
+            );
+        }
+
+        // Quotes in the name stay escaped, so they cannot inject parameters.
+        put(Some(r#"attachment; filename="a\"; x=\"b.txt""#))
+            .expect_success()
+            .await;
+        let response = server
+            .get("/pub/upload.bin")
+            .add_header("host", public_key.z32())
+            .await;
+        response.assert_header(
+            header::CONTENT_DISPOSITION,
+            r#"attachment; filename="a\"; x=\"b.txt""#,
+        );
+
+        put(Some("inline"))
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+
+        // A plain overwrite drops the filename again.
+        put(None).expect_success().await;
+        let response = server
+            .get("/pub/upload.bin")
+            .add_header("host", public_key.z32())
+            .await;
+        assert!(!response.headers().contains_key(header::CONTENT_DISPOSITION));
+    }
     #[tokio::test]
     async fn if_none_match_precedes_if_modified_since() {
         let (_, _, server, public_key, cookie) = create_environment().await.unwrap();
@@ -865,4 +1049,195 @@ mod tests {
             .await;
         assert!(header_value(listing.headers(), header::CACHE_CONTROL).is_none());
     }
+
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn snapshot_listing_ignores_concurrent_writes() {
+        let (_, _, server, public_key, cookie) = create_environment().await.unwrap();
+
+        for name in ["a.txt", "b.txt", "c.txt"] {
+            server
+                .put(&format!("/pub/app/{name}"))
+                .add_header("host", public_key.z32())
+                .add_header(header::COOKIE, cookie.clone())
+                .bytes(Vec::from("x").into())
+                .expect_success()
+                .await;
+        }
+
+        // Opening a snapshot requires a session, resuming it does not.
+        server
+            .get("/pub/app/?limit=1&snapshot=new")
+            .add_header("host", public_key.z32())
+            .expect_failure()
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+        let first = server
+            .get("/pub/app/?limit=1&snapshot=new")
+            .add_header("host", public_key.z32())
+            .add_header(header::COOKIE, cookie.clone())
+            .expect_success()
+            .await;
+        let token = header_value(first.headers(), LIST_SNAPSHOT_HEADER.parse().unwrap())
+            .expect("snapshot header")
+            .to_string();
+        let cursor = first.text();
+        assert!(cursor.ends_with("/pub/app/a.txt"), "got: {cursor}");
+
+        // Writes after the snapshot are invisible to it.
+        server
+            .delete("/pub/app/b.txt")
+            .add_header("host", public_key.z32())
+            .add_header(header::COOKIE, cookie.clone())
+            .expect_success()
+            .await;
+        server
+            .put("/pub/app/d.txt")
+            .add_header("host", public_key.z32())
+            .add_header(header::COOKIE, cookie)
+            .bytes(Vec::from("x").into())
+            .expect_success()
+            .await;
+
+        let rest = server
+            .get(&format!("/pub/app/?snapshot={token}&cursor={cursor}"))
+            .add_header("host", public_key.z32())
+            .expect_success()
+            .await;
+        assert_eq!(
+            header_value(rest.headers(), LIST_SNAPSHOT_HEADER.parse().unwrap()),
+            Some(token.as_str())
+        );
+        let rest = rest.text();
+        let rest: Vec<_> = rest.lines().collect();
+        assert_eq!(rest.len(), 2, "got: {rest:?}");
+        assert!(rest[0].ends_with("/pub/app/b.txt"));
+        assert!(rest[1].ends_with("/pub/app/c.txt"));
+
+        // A live listing sees the writes.
+        let live = server
+            .get("/pub/app/")
+            .add_header("host", public_key.z32())
+            .expect_success()
+            .await
+            .text();
+        assert!(!live.contains("b.txt"));
+        assert!(live.contains("d.txt"));
+    }
+
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn unknown_snapshot_is_gone() {
+        let (_, _, server, public_key, cookie) = create_environment().await.unwrap();
+
+        server
+            .put("/pub/app/a.txt")
+            .add_header("host", public_key.z32())
+            .add_header(header::COOKIE, cookie)
+            .bytes(Vec::from("x").into())
+            .expect_success()
+            .await;
+
+        server
+            .get("/pub/app/?snapshot=0123456789abcdef")
+            .add_header("host", public_key.z32())
+            .await
+            .assert_status(StatusCode::GONE);
+    }
+
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn listing_modified_since_filters_older_entries() {
+        let (_, _, server, public_key, cookie) = create_environment().await.unwrap();
+
+        server
+            .put("/pub/app/a.txt")
+            .add_header("host", public_key.z32())
+            .add_header(header::COOKIE, cookie.clone())
+            .bytes(Vec::from("x").into())
+            .expect_success()
+            .await;
+        let stats: pubky_common::storage::DirStatsResponse = server
+            .get("/pub/app/?stats")
+            .add_header("host", public_key.z32())
+            .expect_success()
+            .await
+            .json();
+        let a_modified = stats.newest_modified_at.unwrap();
+        server
+            .put("/pub/app/b.txt")
+            .add_header("host", public_key.z32())
+            .add_header(header::COOKIE, cookie)
+            .bytes(Vec::from("x").into())
+            .expect_success()
+            .await;
+
+        let inclusive = server
+            .get(&format!("/pub/app/?modified_since={a_modified}"))
+            .add_header("host", public_key.z32())
+            .expect_success()
+            .await
+            .text();
+        assert_eq!(inclusive.lines().count(), 2, "got: {inclusive}");
+
+        let after_a = server
+            .get(&format!("/pub/app/?modified_since={}", a_modified + 1))
+            .add_header("host", public_key.z32())
+            .expect_success()
+            .await
+            .text();
+        let after_a: Vec<_> = after_a.lines().collect();
+        assert_eq!(after_a.len(), 1, "got: {after_a:?}");
+        assert!(after_a[0].ends_with("/pub/app/b.txt"));
+
+        server
+            .get("/pub/app/?modified_since=yesterday")
+            .add_header("host", public_key.z32())
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn file_metadata_reports_the_stored_hash() {
+        let (_, _, server, public_key, cookie) = create_environment().await.unwrap();
+
+        server
+            .put("/pub/app/a.txt")
+            .add_header("host", public_key.z32())
+            .add_header(header::COOKIE, cookie)
+            .bytes(Vec::from("hello").into())
+            .expect_success()
+            .await;
+
+        let metadata: pubky_common::storage::FileMetadataResponse = server
+            .get("/pub/app/a.txt?metadata")
+            .add_header("host", public_key.z32())
+            .expect_success()
+            .await
+            .json();
+        let expected_hash = base64::Engine::encode(
+            &base64::engine::general_purpose::STANDARD,
+            pubky_common::crypto::hash(b"hello").as_bytes(),
+        );
+        assert!(metadata.exists);
+        assert_eq!(metadata.size, Some(5));
+        assert_eq!(metadata.content_type.as_deref(), Some("text/plain"));
+        assert_eq!(metadata.content_hash, Some(expected_hash));
+        assert!(metadata.modified.is_some());
+
+        let missing: pubky_common::storage::FileMetadataResponse = server
+            .get("/pub/app/missing.txt?metadata")
+            .add_header("host", public_key.z32())
+            .expect_success()
+            .await
+            .json();
+        assert_eq!(missing, Default::default());
+
+        server
+            .get("/pub/app/?metadata")
+            .add_header("host", public_key.z32())
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+    }
 }
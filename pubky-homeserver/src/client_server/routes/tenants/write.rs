@@ -1,38 +1,57 @@
-use axum::http::HeaderMap;
+use axum::http::{header, HeaderMap};
 use axum::{
     body::Body,
     extract::{Path, State},
     http::StatusCode,
-    response::IntoResponse,
+    response::{IntoResponse, Response},
+    Json,
+};
+use bytes::Bytes;
+use chrono::{NaiveDateTime, TimeDelta, Utc};
+use futures_util::stream::{self, Stream, StreamExt};
+use pubky_common::storage::{
+    disposition_filename, sanitize_filename, DeleteDirResponse, PutResponse, APPEND_OFFSET_HEADER,
+    MULTIPART_FILE_FIELD, TTL_HEADER,
 };
-use futures_util::stream::StreamExt;
 
 use crate::{
     client_server::{
         auth::{has_write_permission, AuthSession},
         middleware::pubky_host::PubkyHost,
+        routes::tenants::read::to_http_date,
         AppState,
     },
+    data_directory::ContentPolicyToml,
     persistence::{
         files::{
             user_quota_layer::{resolve_storage_max_bytes, would_exceed_limit},
-            WriteStreamError,
+            FileIoError, FileMetadataBuilder, ReceivedUpload, WriteMetadata, WriteStreamError,
+        },
+        sql::{
+            entry::{EntryEntity, EntryRepository},
+            user::UserEntity,
+            UnifiedExecutor,
         },
-        sql::{entry::EntryRepository, user::UserEntity, UnifiedExecutor},
     },
     services::user_service::FILE_METADATA_SIZE,
     shared::{
-        webdav::{EntryPath, WebDavFilePathAxum},
+        webdav::{EntryPath, WebDavFilePathAxum, WebDavPathAxum},
         HttpError, HttpResult,
     },
 };
 
+/// Delete a file, or every file below a directory if the path ends with `/`.
+///
+/// A directory is deleted in a single transaction and answered with the
+/// number of deleted files. A user with restricted write paths can only delete a
+/// directory if every file below it is writable, i.e. it lies inside an allowed
+/// directory.
 pub async fn delete(
     State(state): State<AppState>,
     session: AuthSession,
     pubky: PubkyHost,
-    Path(path): Path<WebDavFilePathAxum>,
-) -> HttpResult<impl IntoResponse> {
+    Path(path): Path<WebDavPathAxum>,
+) -> HttpResult<Response> {
     has_write_permission(&session, pubky.public_key(), path.inner())?;
 
     let public_key = pubky.public_key();
@@ -42,8 +61,18 @@ pub async fn delete(
         .await?;
     let entry_path = EntryPath::new(public_key.clone(), path.inner().to_owned());
 
+    if entry_path.path().is_directory() {
+        // Deleting a directory skips the storage layers, so apply their write-path
+        // restriction here.
+        let quota = state.user_service.resolve_quota(public_key).await?;
+        if quota.is_some_and(|quota| !quota.is_write_path_allowed(path.inner().as_str())) {
+            return Err(FileIoError::WritePathForbidden.into());
+        }
+        let deleted = state.file_service.delete_dir(&entry_path).await?;
+        return Ok(Json(DeleteDirResponse { deleted }).into_response());
+    }
     state.file_service.delete(&entry_path).await?;
-    Ok((StatusCode::NO_CONTENT, ()))
+    Ok((StatusCode::NO_CONTENT, ()).into_response())
 }
 
 pub async fn put(
@@ -53,8 +82,13 @@ pub async fn put(
     Path(path): Path<WebDavFilePathAxum>,
     headers: HeaderMap,
     body: Body,
-) -> HttpResult<impl IntoResponse> {
+) -> HttpResult<Response> {
     has_write_permission(&session, pubky.public_key(), path.inner())?;
+    check_path_limits(
+        path.inner().as_str(),
+        state.max_path_length,
+        state.max_path_segments,
+    )?;
 
     let public_key = pubky.public_key();
     let user = state
@@ -62,6 +96,10 @@ pub async fn put(
         .get_or_http_error(public_key, true)
         .await?;
     let entry_path = EntryPath::new(public_key.clone(), path.inner().to_owned());
+    let metadata = WriteMetadata {
+        expires_at: expires_at_from_headers(&headers)?,
+        filename: filename_from_headers(&headers)?,
+    };
 
     // Early fail: check Content-Length header against the user's storage quota
     // so we can reject before streaming the entire body.
@@ -82,12 +120,395 @@ pub async fn put(
     let body_stream = body.into_data_stream();
     let converted_stream =
         body_stream.map(|chunk_result| chunk_result.map_err(WriteStreamError::Axum));
+    let converted_stream = apply_content_policy(
+        &state.content_policy,
+        entry_path.path().as_str(),
+        content_length,
+        converted_stream,
+    )
+    .await?;
+
+    let max_length = upload_limit(&state, &user, &entry_path).await?;
+    let entry = write_locked(&state, &entry_path, converted_stream, max_length, metadata).await?;
+    Ok(written(StatusCode::CREATED, &entry))
+}
+
+/// Append the body to a file if it is exactly as long as the [`APPEND_OFFSET_HEADER`]
+/// says, creating the file when the offset is 0.
+///
+/// Appends and `PUT`s to the same file are serialized, so of several writers appending
+/// at the same offset exactly one succeeds. The others get `409 Conflict` with the current
+/// length in a `Content-Range: bytes */<length>` header, and may retry at that offset.
+/// On success answers `200 OK` with the hash and length of the whole file.
+///
+/// A `multipart/form-data` body without the offset header is a form upload instead,
+/// see [`upload_form`].
+pub async fn append(
+    State(state): State<AppState>,
+    session: AuthSession,
+    pubky: PubkyHost,
+    Path(path): Path<WebDavFilePathAxum>,
+    headers: HeaderMap,
+    body: Body,
+) -> HttpResult<Response> {
+    has_write_permission(&session, pubky.public_key(), path.inner())?;
+    check_path_limits(
+        path.inner().as_str(),
+        state.max_path_length,
+        state.max_path_segments,
+    )?;
+    if !headers.contains_key(APPEND_OFFSET_HEADER) {
+        if let Some(boundary) = form_boundary_from_headers(&headers) {
+            return upload_form(&state, &pubky, path, &headers, body, boundary).await;
+        }
+    }
+    let offset = append_offset_from_headers(&headers)?;
+
+    let public_key = pubky.public_key();
+    let user = state
+        .user_service
+        .get_or_http_error(public_key, true)
+        .await?;
+    let entry_path = EntryPath::new(public_key.clone(), path.inner().to_owned());
+    let content_length =
+        content_length_from_headers(&headers).map(|appended| offset.saturating_add(appended));
+    fail_if_size_hint_exceeds_quota(
+        content_length,
+        &user,
+        state.default_storage_mb,
+        &entry_path,
+        &mut state.sql_db.pool().into(),
+    )
+    .await?;
+
+    // Received before locking, so a slow client never holds the lock.
+    let appended = body
+        .into_data_stream()
+        .map(|chunk_result| chunk_result.map_err(WriteStreamError::Axum));
+    let max_length = resolve_storage_max_bytes(&user, state.default_storage_mb)
+        .map(|max| max.saturating_sub(user.used_bytes));
+    let appended = ReceivedUpload::receive(appended, max_length).await?;
+
+    let lock = state.file_service.lock_path(&entry_path).await?;
+    let current_length = lock.length();
+    if current_length != offset {
+        return Ok((
+            StatusCode::CONFLICT,
+            [(header::CONTENT_RANGE, format!("bytes */{current_length}"))],
+            format!("File is {current_length} bytes long, not {offset}"),
+        )
+            .into_response());
+    }
 
+    // The whole file is rewritten, so the content policy sees it from the start.
+    let content_length = current_length + appended.length();
+    let existing = state
+        .file_service
+        .get_locked_stream(&lock)
+        .await?
+        .map(|chunk| chunk.map_err(|e| WriteStreamError::Other(e.into())));
+    let content = existing.chain(appended.into_stream());
+    let content = apply_content_policy(
+        &state.content_policy,
+        entry_path.path().as_str(),
+        Some(content_length),
+        content,
+    )
+    .await?;
+
+    let entry = state.file_service.finish_append(lock, content).await?;
+    Ok(written(StatusCode::OK, &entry))
+}
+
+/// Store the [`MULTIPART_FILE_FIELD`] part of a `multipart/form-data` body, as sent by
+/// HTML forms and upload widgets, like a `PUT` of that part.
+///
+/// The part is streamed to storage without buffering and its filename is kept as the
+/// download filename. Parts before it are skipped and parts after it are never read.
+/// Answers `201 Created` with the hash and length of the file, or `400 Bad Request`
+/// if the form has no such part.
+async fn upload_form(
+    state: &AppState,
+    pubky: &PubkyHost,
+    path: WebDavFilePathAxum,
+    headers: &HeaderMap,
+    body: Body,
+    boundary: String,
+) -> HttpResult<Response> {
+    let public_key = pubky.public_key();
+    let user = state
+        .user_service
+        .get_or_http_error(public_key, true)
+        .await?;
+    let entry_path = EntryPath::new(public_key.clone(), path.inner().to_owned());
+    let expires_at = expires_at_from_headers(headers)?;
+
+    let mut form = multer::Multipart::new(body.into_data_stream(), boundary);
+    let invalid_form = |e: multer::Error| HttpError::bad_request(format!("Invalid form: {e}"));
+    let part = loop {
+        match form.next_field().await.map_err(invalid_form)? {
+            Some(part) if part.name() == Some(MULTIPART_FILE_FIELD) => break part,
+            Some(_) => continue,
+            None => {
+                return Err(HttpError::bad_request(format!(
+                    "Form has no `{MULTIPART_FILE_FIELD}` part"
+                )))
+            }
+        }
+    };
+    let metadata = WriteMetadata {
+        expires_at,
+        filename: part.file_name().and_then(sanitize_filename),
+    };
+
+    // The length of the part is unknown, so quota and content policy limits are
+    // enforced while streaming.
+    let content = part.map(|chunk| chunk.map_err(|e| WriteStreamError::Other(e.into())));
+    let content = apply_content_policy(
+        &state.content_policy,
+        entry_path.path().as_str(),
+        None,
+        content,
+    )
+    .await?;
+    let max_length = upload_limit(state, &user, &entry_path).await?;
+    let entry = write_locked(state, &entry_path, content, max_length, metadata).await?;
+    Ok(written(StatusCode::CREATED, &entry))
+}
+
+/// Receive `content` and write it while holding the lock appends take, so a `PUT`
+/// never lands between an append's length check and its write.
+///
+/// The content is received into a temporary file first, up to `max_length` bytes, so
+/// the lock is not held while waiting for the client.
+async fn write_locked(
+    state: &AppState,
+    entry_path: &EntryPath,
+    content: impl Stream<Item = Result<Bytes, WriteStreamError>> + Unpin + Send,
+    max_length: Option<u64>,
+    metadata: WriteMetadata,
+) -> Result<EntryEntity, FileIoError> {
+    let content = ReceivedUpload::receive(content, max_length).await?;
+    let lock = state.file_service.lock_path(entry_path).await?;
     state
         .file_service
-        .write_stream(&entry_path, converted_stream)
+        .write_locked(lock, content.into_stream(), metadata)
+        .await
+}
+
+/// Most bytes `user` may write to `entry_path`, replacing the file there, without
+/// exceeding their storage quota. `None` if the quota is unlimited.
+///
+/// Bounds how much of a body is received before the quota layer checks the write.
+async fn upload_limit(
+    state: &AppState,
+    user: &UserEntity,
+    entry_path: &EntryPath,
+) -> HttpResult<Option<u64>> {
+    let Some(max_bytes) = resolve_storage_max_bytes(user, state.default_storage_mb) else {
+        return Ok(None);
+    };
+    let replaced_length =
+        match EntryRepository::get_by_path(entry_path, &mut state.sql_db.pool().into()).await {
+            Ok(entry) => entry.content_length,
+            Err(sqlx::Error::RowNotFound) => 0,
+            Err(e) => return Err(e.into()),
+        };
+    Ok(Some(
+        max_bytes
+            .saturating_sub(user.used_bytes)
+            .saturating_add(replaced_length),
+    ))
+}
+
+/// Answer a write with the hash and length of the stored file, also sending the hash
+/// as `ETag`.
+fn written(status: StatusCode, entry: &EntryEntity) -> Response {
+    let content_hash = base64::Engine::encode(
+        &base64::engine::general_purpose::STANDARD,
+        entry.content_hash.as_bytes(),
+    );
+    (
+        status,
+        [(header::ETAG, format!("\"{content_hash}\""))],
+        Json(PutResponse {
+            content_hash,
+            content_length: entry.content_length,
+        }),
+    )
+        .into_response()
+}
+
+/// Bump the modification time of an existing file without uploading its content again.
+///
+/// Answers `204 No Content` with the new `Last-Modified`, or `404 Not Found` if the
+/// file does not exist; a touch never creates a file. The request body is ignored.
+pub async fn touch(
+    State(state): State<AppState>,
+    session: AuthSession,
+    pubky: PubkyHost,
+    Path(path): Path<WebDavFilePathAxum>,
+) -> HttpResult<Response> {
+    has_write_permission(&session, pubky.public_key(), path.inner())?;
+
+    let public_key = pubky.public_key();
+    state
+        .user_service
+        .get_or_http_error(public_key, true)
         .await?;
-    Ok((StatusCode::CREATED, ()))
+    // Touching skips the storage layers, so apply their write-path restriction here.
+    let quota = state.user_service.resolve_quota(public_key).await?;
+    if quota.is_some_and(|quota| !quota.is_write_path_allowed(path.inner().as_str())) {
+        return Err(FileIoError::WritePathForbidden.into());
+    }
+    let entry_path = EntryPath::new(public_key.clone(), path.inner().to_owned());
+    let entry = state.file_service.touch(&entry_path).await?;
+    Ok((
+        StatusCode::NO_CONTENT,
+        [(
+            header::LAST_MODIFIED,
+            to_http_date(&entry.modified_at).to_string(),
+        )],
+    )
+        .into_response())
+}
+
+/// How much of an upload [`apply_content_policy`] buffers to detect its content type.
+const CONTENT_SNIFF_BYTES: usize = 8 * 1024;
+
+/// Reject the upload with `415 Unsupported Media Type` if the content policy forbids
+/// its content type or caps its size below the upload.
+///
+/// The content type is detected from the first [`CONTENT_SNIFF_BYTES`] of the body (or
+/// all of a shorter body), which are then passed on as a single chunk so storage detects
+/// the same type. Uploads without a `Content-Length` are cut off once they outgrow the
+/// limit.
+pub(super) async fn apply_content_policy(
+    policy: &ContentPolicyToml,
+    path: &str,
+    content_length: Option<u64>,
+    mut body: impl Stream<Item = Result<Bytes, WriteStreamError>> + Unpin + Send,
+) -> HttpResult<impl Stream<Item = Result<Bytes, WriteStreamError>> + Unpin + Send> {
+    let mut head = Vec::new();
+    while head.len() < CONTENT_SNIFF_BYTES {
+        match body.next().await {
+            Some(chunk) => head.extend_from_slice(&chunk.map_err(FileIoError::from)?),
+            None => break,
+        }
+    }
+    let first_chunk = Bytes::from(head);
+
+    let content_type = FileMetadataBuilder::detect_content_type(path, &first_chunk);
+    if !policy.is_allowed(&content_type) {
+        return Err(HttpError::unsupported_media_type(format!(
+            "Content type {content_type} is not allowed"
+        )));
+    }
+    let max_bytes = policy.max_bytes(&content_type);
+    let too_large = format!(
+        "Content type {content_type} is limited to {} bytes",
+        max_bytes.unwrap_or(u64::MAX)
+    );
+    if let (Some(length), Some(max)) = (content_length, max_bytes) {
+        if length > max {
+            return Err(HttpError::unsupported_media_type(too_large));
+        }
+    }
+
+    let mut received = 0u64;
+    Ok(stream::iter([Ok(first_chunk)])
+        .chain(body)
+        .map(move |chunk| {
+            let chunk = chunk?;
+            received += chunk.len() as u64;
+            match max_bytes {
+                Some(max) if received > max => {
+                    Err(WriteStreamError::UnsupportedMediaType(too_large.clone()))
+                }
+                _ => Ok(chunk),
+            }
+        }))
+}
+
+/// Reject paths longer than `max_length` bytes with `414 URI Too Long`, and paths
+/// with more than `max_segments` segments with `400 Bad Request`.
+pub(super) fn check_path_limits(
+    path: &str,
+    max_length: usize,
+    max_segments: usize,
+) -> HttpResult<()> {
+    if path.len() > max_length {
+        return Err(HttpError::uri_too_long(format!(
+            "Path is {} bytes long, the maximum is {max_length}",
+            path.len()
+        )));
+    }
+    let segments = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .count();
+    if segments > max_segments {
+        return Err(HttpError::bad_request(format!(
+            "Path has {segments} segments, the maximum is {max_segments}"
+        )));
+    }
+    Ok(())
+}
+
+/// Turn the optional `pubky-ttl` header (whole seconds) into an expiry deadline (UTC).
+fn expires_at_from_headers(headers: &HeaderMap) -> HttpResult<Option<NaiveDateTime>> {
+    let Some(value) = headers.get(TTL_HEADER) else {
+        return Ok(None);
+    };
+    let invalid =
+        || HttpError::bad_request(format!("{TTL_HEADER} must be a positive number of seconds"));
+    let seconds: i64 = value
+        .to_str()
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+        .filter(|seconds| *seconds > 0)
+        .ok_or_else(invalid)?;
+    let expires_at = TimeDelta::try_seconds(seconds)
+        .and_then(|ttl| Utc::now().naive_utc().checked_add_signed(ttl))
+        .ok_or_else(invalid)?;
+    Ok(Some(expires_at))
+}
+
+/// Read the length the file must have from the required [`APPEND_OFFSET_HEADER`].
+fn append_offset_from_headers(headers: &HeaderMap) -> HttpResult<u64> {
+    headers
+        .get(APPEND_OFFSET_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse().ok())
+        .ok_or_else(|| {
+            HttpError::bad_request(format!(
+                "{APPEND_OFFSET_HEADER} must be the current length of the file in bytes"
+            ))
+        })
+}
+
+/// Read the boundary of a `multipart/form-data` body from the `Content-Type` header,
+/// returning `None` for any other content type.
+fn form_boundary_from_headers(headers: &HeaderMap) -> Option<String> {
+    let content_type = headers.get(header::CONTENT_TYPE)?.to_str().ok()?;
+    multer::parse_boundary(content_type).ok()
+}
+
+/// Read the download filename from the optional `Content-Disposition` header.
+///
+/// The name is sanitized before it is stored; a header that names no usable file is
+/// rejected rather than silently dropped.
+fn filename_from_headers(headers: &HeaderMap) -> HttpResult<Option<String>> {
+    let Some(value) = headers.get(header::CONTENT_DISPOSITION) else {
+        return Ok(None);
+    };
+    std::str::from_utf8(value.as_bytes())
+        .ok()
+        .and_then(disposition_filename)
+        .map(Some)
+        .ok_or_else(|| {
+            HttpError::bad_request("Content-Disposition must be an attachment with a filename")
+        })
 }
 
 /// Parse the `Content-Length` header into a `u64`, returning `None` if absent or unparseable.
@@ -140,6 +561,7 @@ mod tests {
     use crate::persistence::sql::user::UserRepository;
     use crate::persistence::sql::SqlDb;
     use crate::shared::webdav::WebDavPath;
+    use axum::http::HeaderValue;
 
     use super::*;
 
@@ -237,4 +659,169 @@ mod tests {
             .await
             .expect("unlimited quota should accept any size");
     }
+
+    async fn collect_policy_stream(
+        policy: &ContentPolicyToml,
+        path: &str,
+        content_length: Option<u64>,
+        chunks: Vec<&'static [u8]>,
+    ) -> HttpResult<Result<Vec<u8>, WriteStreamError>> {
+        let body = stream::iter(
+            chunks
+                .into_iter()
+                .map(|chunk| Ok(Bytes::from_static(chunk))),
+        );
+        let mut checked = apply_content_policy(policy, path, content_length, body).await?;
+        let mut collected = Vec::new();
+        while let Some(chunk) = checked.next().await {
+            match chunk {
+                Ok(chunk) => collected.extend_from_slice(&chunk),
+                Err(e) => return Ok(Err(e)),
+            }
+        }
+        Ok(Ok(collected))
+    }
+
+    #[tokio::test]
+    async fn test_content_policy() {
+        let policy: ContentPolicyToml = toml::from_str(
+            r#"
+            deny = ["application/vnd.microsoft.portable-executable"]
+
+            [[max_sizes]]
+            content_type = "text/*"
+            max_size_mb = 1
+            "#,
+        )
+        .unwrap();
+        let one_mb = 1024 * 1024;
+
+        // Passes the body through unchanged, skipping empty leading chunks.
+        let body = collect_policy_stream(&policy, "/pub/a.txt", None, vec![b"", b"ab", b"c"])
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(body, b"abc");
+
+        // Detected from magic bytes, whatever the extension.
+        let err = collect_policy_stream(&policy, "/pub/a.txt", None, vec![b"MZ\x90\x00"])
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.into_response().status(),
+            StatusCode::UNSUPPORTED_MEDIA_TYPE
+        );
+
+        // Magic bytes split across small chunks are still detected.
+        let err = collect_policy_stream(&policy, "/pub/a.txt", None, vec![b"M", b"Z", b"\x90\x00"])
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.into_response().status(),
+            StatusCode::UNSUPPORTED_MEDIA_TYPE
+        );
+
+        // Size limit from the Content-Length header.
+        let err = collect_policy_stream(&policy, "/pub/a.txt", Some(one_mb + 1), vec![b"a"])
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.into_response().status(),
+            StatusCode::UNSUPPORTED_MEDIA_TYPE
+        );
+
+        // Size limit while streaming.
+        let big: &'static [u8] = Box::leak(vec![b'a'; one_mb as usize].into_boxed_slice());
+        let result = collect_policy_stream(&policy, "/pub/a.txt", None, vec![big, b"a"])
+            .await
+            .unwrap();
+        assert!(matches!(
+            result,
+            Err(WriteStreamError::UnsupportedMediaType(_))
+        ));
+        let result = collect_policy_stream(&policy, "/pub/a.bin", None, vec![big, b"a"])
+            .await
+            .unwrap();
+        assert!(result.is_ok(), "Other types are not limited");
+    }
+
+    #[test]
+    fn test_check_path_limits() {
+        check_path_limits("/pub/app/file.txt", 17, 3).unwrap();
+        check_path_limits("/pub/app/dir/", 13, 3).unwrap();
+
+        let err = check_path_limits("/pub/app/file.txt", 16, 3).unwrap_err();
+        assert_eq!(err.into_response().status(), StatusCode::URI_TOO_LONG);
+        let err = check_path_limits("/pub/app/file.txt", 17, 2).unwrap_err();
+        assert_eq!(err.into_response().status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn test_filename_from_headers() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(filename_from_headers(&headers).unwrap(), None);
+
+        headers.insert(
+            header::CONTENT_DISPOSITION,
+            "attachment; filename=\"../report.pdf\"".parse().unwrap(),
+        );
+        assert_eq!(
+            filename_from_headers(&headers).unwrap().as_deref(),
+            Some("report.pdf")
+        );
+
+        headers.insert(
+            header::CONTENT_DISPOSITION,
+            HeaderValue::from_bytes("attachment; filename=\"café.txt\"".as_bytes()).unwrap(),
+        );
+        assert_eq!(
+            filename_from_headers(&headers).unwrap().as_deref(),
+            Some("café.txt")
+        );
+
+        for invalid in ["inline", "attachment; filename=\"..\""] {
+            headers.insert(header::CONTENT_DISPOSITION, invalid.parse().unwrap());
+            filename_from_headers(&headers).expect_err(invalid);
+        }
+    }
+
+    #[test]
+    fn test_form_boundary_from_headers() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(form_boundary_from_headers(&headers), None);
+
+        headers.insert(
+            header::CONTENT_TYPE,
+            "multipart/form-data; boundary=abc123".parse().unwrap(),
+        );
+        assert_eq!(
+            form_boundary_from_headers(&headers).as_deref(),
+            Some("abc123")
+        );
+
+        for other in [
+            "multipart/form-data",
+            "multipart/mixed; boundary=abc123",
+            "application/octet-stream",
+        ] {
+            headers.insert(header::CONTENT_TYPE, other.parse().unwrap());
+            assert_eq!(form_boundary_from_headers(&headers), None, "{other}");
+        }
+    }
+
+    #[test]
+    fn test_expires_at_from_headers() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(expires_at_from_headers(&headers).unwrap(), None);
+
+        headers.insert(TTL_HEADER, "60".parse().unwrap());
+        let expires_at = expires_at_from_headers(&headers).unwrap().unwrap();
+        let remaining = expires_at - Utc::now().naive_utc();
+        assert!(remaining > TimeDelta::seconds(55) && remaining <= TimeDelta::seconds(60));
+
+        for invalid in ["0", "-5", "soon", "1.5", &u64::MAX.to_string()] {
+            headers.insert(TTL_HEADER, invalid.parse().unwrap());
+            expires_at_from_headers(&headers).expect_err(invalid);
+        }
+    }
 }
@@ -0,0 +1,48 @@
+use axum::{http::header, response::IntoResponse, Json};
+use pubky_common::{clock::ServerTimeResponse, timestamp::Timestamp};
+
+use crate::client_server::cache_policy::CACHE_CONTROL_NO_STORE;
+
+/// Answer with the homeserver's current time, so clients can measure how far their
+/// clock is off before signing auth tokens.
+///
+/// Never cached, since a stored answer would report a time in the past.
+pub async fn handler() -> impl IntoResponse {
+    (
+        [(header::CACHE_CONTROL, CACHE_CONTROL_NO_STORE)],
+        Json(ServerTimeResponse {
+            now: Timestamp::now().as_u64(),
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request, routing::get, Router};
+    use pubky_common::clock::SERVER_TIME_PATH;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn time_reports_the_current_time() {
+        let before = Timestamp::now().as_u64();
+        let response = Router::new()
+            .route(SERVER_TIME_PATH, get(handler))
+            .oneshot(Request::get(SERVER_TIME_PATH).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let after = Timestamp::now().as_u64();
+
+        assert!(response.status().is_success());
+        assert_eq!(
+            response.headers().get(header::CACHE_CONTROL).unwrap(),
+            "no-store"
+        );
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let time: ServerTimeResponse = serde_json::from_slice(&body).unwrap();
+        assert!((before..=after).contains(&time.now));
+    }
+}
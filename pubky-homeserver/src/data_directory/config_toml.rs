@@ -7,8 +7,9 @@
 #[cfg(any(test, feature = "testing"))]
 use super::storage_config::StorageConfigToml;
 use super::{
+    content_policy::ContentPolicyToml,
     domain_port::DomainPort,
-    quota_config::{BandwidthQuota, PathLimit},
+    quota_config::{BandwidthQuota, ClassLimit, PathLimit},
     storage_config::StorageToml,
     Domain, SignupMode,
 };
@@ -23,8 +24,8 @@ use std::{
     fmt::Debug,
     fs,
     net::{IpAddr, SocketAddr},
-    num::{NonZeroU32, NonZeroU64},
-    path::Path,
+    num::{NonZeroU32, NonZeroU64, NonZeroU8},
+    path::{Path, PathBuf},
     str::FromStr,
 };
 use url::Url;
@@ -61,6 +62,32 @@ pub struct PkdnsToml {
     pub dht_bootstrap_nodes: Option<Vec<DomainPort>>,
     pub dht_relay_nodes: Option<Vec<Url>>,
     pub dht_request_timeout_ms: Option<NonZeroU64>,
+    /// How the homeserver republishes its own pkarr packet.
+    pub key_republisher: KeyRepublisherToml,
+}
+
+/// Schedule and retry behavior of the homeserver's own pkarr packet republishing.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct KeyRepublisherToml {
+    /// Seconds between two republishes.
+    pub interval: NonZeroU64,
+    /// Maximum duration of a single publish attempt in milliseconds.
+    pub attempt_timeout_ms: NonZeroU64,
+    /// Publish attempts per republish, including the first one.
+    pub max_attempts: NonZeroU8,
+    /// Delay before the first retry in milliseconds. Doubles with every retry.
+    pub initial_retry_delay_ms: u64,
+    /// Cap on the retry delay in milliseconds.
+    pub max_retry_delay_ms: u64,
+    /// Only publish to the first `max_relays` of `dht_relay_nodes`. `0` publishes to the
+    /// DHT only. Omit to use all relays.
+    pub max_relays: Option<usize>,
+}
+
+impl Default for KeyRepublisherToml {
+    fn default() -> Self {
+        ConfigToml::default().pkdns.key_republisher
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
@@ -69,6 +96,12 @@ pub struct DriveToml {
     pub icann_listen_socket: SocketAddr,
     /// Per-path request-count rate limits.
     pub rate_limits: Vec<PathLimit>,
+    /// Request-count rate limits per route class (reads, writes, auth).
+    #[serde(default)]
+    pub class_rate_limits: Vec<ClassLimit>,
+    /// Reverse proxies allowed to name the client IP in `X-Forwarded-For` or `X-Real-IP`.
+    #[serde(default)]
+    pub trusted_proxies: Vec<IpAddr>,
 }
 
 /// Default bandwidth limits for the rate limiter.
@@ -125,6 +158,9 @@ pub struct GeneralToml {
     #[serde(default)]
     pub user_storage_quota_mb: u64,
     pub database_url: ConnectionString,
+    /// Seconds the timestamp of an auth token or grant proof may lie in the past or
+    /// the future of the homeserver's clock before signin is refused.
+    pub clock_skew_tolerance: u64,
 }
 
 /// A config for Homeserver tracing subscriber configuration
@@ -145,6 +181,18 @@ pub struct MetricsToml {
     pub listen_socket: SocketAddr,
 }
 
+/// Structured access log configuration for the client server.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AccessLogToml {
+    /// Write one JSON record per request.
+    pub enabled: bool,
+    /// File the records are appended to. Relative paths are resolved against the
+    /// data directory. Omit to write to stdout.
+    pub path: Option<PathBuf>,
+    /// Request paths that are only logged when the response is not a success.
+    pub excluded_paths: Vec<String>,
+}
+
 /// The overall application configuration, composed of several subsections.
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct ConfigToml {
@@ -156,10 +204,14 @@ pub struct ConfigToml {
     pub storage: StorageToml,
     /// Default bandwidth limits for the rate limiter. Overridable per-user via admin API.
     pub default_quotas: DefaultQuotasToml,
+    /// Content types users may store, and per-type size limits.
+    pub content_policy: ContentPolicyToml,
     /// Administrative API configuration.
     pub admin: AdminToml,
     /// Metrics server configuration.
     pub metrics: MetricsToml,
+    /// Structured per-request access log.
+    pub access_log: AccessLogToml,
     /// Peer‐to‐peer DHT / PKDNS settings (public endpoints, bootstrap, relays).
     pub pkdns: PkdnsToml,
     /// Logging configuration. If provided, the homeserver instance attempts to init
@@ -185,6 +237,12 @@ impl Default for AdminToml {
     }
 }
 
+impl Default for AccessLogToml {
+    fn default() -> Self {
+        ConfigToml::default().access_log
+    }
+}
+
 impl Default for PkdnsToml {
     fn default() -> Self {
         ConfigToml::default().pkdns
@@ -349,11 +407,33 @@ mod tests {
         assert_eq!(c.pkdns.public_pubky_tls_port, None);
         assert_eq!(c.pkdns.public_icann_http_port, None);
         assert_eq!(c.pkdns.user_keys_republisher_interval, 14400);
+        assert_eq!(
+            c.general.clock_skew_tolerance,
+            pubky_common::auth::DEFAULT_CLOCK_SKEW_TOLERANCE.as_secs()
+        );
+        assert_eq!(c.storage.expiry_sweep_interval, 60);
+        assert_eq!(c.storage.expiry_sweep_batch_size.get(), 500);
+        assert_eq!(
+            c.storage.max_path_length,
+            pubky_common::storage::DEFAULT_MAX_PATH_LENGTH
+        );
+        assert_eq!(
+            c.storage.max_path_segments,
+            pubky_common::storage::DEFAULT_MAX_PATH_SEGMENTS
+        );
         assert_eq!(c.pkdns.dht_bootstrap_nodes, None);
         assert_eq!(c.pkdns.dht_request_timeout_ms, None);
+        assert_eq!(c.pkdns.key_republisher.interval.get(), 3600);
+        assert_eq!(c.pkdns.key_republisher.attempt_timeout_ms.get(), 30_000);
+        assert_eq!(c.pkdns.key_republisher.max_attempts.get(), 3);
+        assert_eq!(c.pkdns.key_republisher.max_relays, None);
         assert_eq!(c.drive.rate_limits.len(), 1);
         assert_eq!(c.drive.rate_limits[0].path.0, "/signup_tokens/*");
         assert_eq!(c.default_quotas, DefaultQuotasToml::default());
+        assert_eq!(c.content_policy, ContentPolicyToml::default());
+        assert!(!c.access_log.enabled);
+        assert_eq!(c.access_log.path, None);
+        assert_eq!(c.access_log.excluded_paths, vec!["/events/".to_string()]);
         assert_eq!(c.storage.default_quota_mb, None);
         assert_eq!(c.storage.backend, StorageConfigToml::FileSystem);
         assert_eq!(
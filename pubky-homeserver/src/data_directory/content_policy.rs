@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+
+use super::quota_config::GlobPattern;
+
+/// The `[content_policy]` TOML section: which content types users may store.
+///
+/// Content types are matched as detected by the homeserver (magic bytes first,
+/// then the file extension), not as announced by the client. Patterns are globs
+/// over the lowercase MIME type, e.g. `"image/*"` or `"application/x-msdownload"`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct ContentPolicyToml {
+    /// If set, only content types matching one of these patterns are accepted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allow: Option<Vec<GlobPattern>>,
+    /// Content types matching one of these patterns are rejected, even if allowed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deny: Vec<GlobPattern>,
+    /// Maximum file size per content type. The first matching limit applies.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub max_sizes: Vec<ContentTypeSizeLimit>,
+}
+
+/// A maximum file size for the content types matching `content_type`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ContentTypeSizeLimit {
+    /// Glob pattern over the MIME type.
+    pub content_type: GlobPattern,
+    /// Maximum file size in MB.
+    pub max_size_mb: u64,
+}
+
+impl ContentPolicyToml {
+    /// Whether files of `content_type` may be stored.
+    pub fn is_allowed(&self, content_type: &str) -> bool {
+        let content_type = content_type.to_ascii_lowercase();
+        let allowed = self
+            .allow
+            .as_ref()
+            .is_none_or(|allow| allow.iter().any(|pattern| pattern.is_match(&content_type)));
+        allowed
+            && !self
+                .deny
+                .iter()
+                .any(|pattern| pattern.is_match(&content_type))
+    }
+
+    /// The maximum size in bytes of files of `content_type`, if limited.
+    pub fn max_bytes(&self, content_type: &str) -> Option<u64> {
+        let content_type = content_type.to_ascii_lowercase();
+        self.max_sizes
+            .iter()
+            .find(|limit| limit.content_type.is_match(&content_type))
+            .map(|limit| limit.max_size_mb.saturating_mul(1024 * 1024))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allow_deny_and_limits() {
+        let policy: ContentPolicyToml = toml::from_str(
+            r#"
+            allow = ["image/*", "text/*", "application/json"]
+            deny = ["image/svg+xml"]
+
+            [[max_sizes]]
+            content_type = "image/*"
+            max_size_mb = 5
+            "#,
+        )
+        .unwrap();
+
+        assert!(policy.is_allowed("image/png"));
+        assert!(policy.is_allowed("Text/Plain"));
+        assert!(policy.is_allowed("application/json"));
+        assert!(!policy.is_allowed("image/svg+xml"));
+        assert!(!policy.is_allowed("application/x-msdownload"));
+
+        assert_eq!(policy.max_bytes("image/png"), Some(5 * 1024 * 1024));
+        assert_eq!(policy.max_bytes("text/plain"), None);
+    }
+
+    #[test]
+    fn test_default_allows_everything() {
+        let policy = ContentPolicyToml::default();
+        assert!(policy.is_allowed("application/octet-stream"));
+        assert_eq!(policy.max_bytes("video/mp4"), None);
+    }
+}
@@ -6,6 +6,7 @@
 //! (listen addresses, signup mode, storage backend, rate limits, logging, etc.).
 
 mod config_toml;
+mod content_policy;
 mod data_dir;
 mod domain;
 mod domain_port;
@@ -20,8 +21,10 @@ pub mod storage_config;
 
 mod log_level;
 pub use config_toml::{
-    AdminToml, ConfigReadError, ConfigToml, DefaultQuotasToml, LoggingToml, MetricsToml,
+    AccessLogToml, AdminToml, ConfigReadError, ConfigToml, DefaultQuotasToml, KeyRepublisherToml,
+    LoggingToml, MetricsToml,
 };
+pub use content_policy::{ContentPolicyToml, ContentTypeSizeLimit};
 pub use data_dir::DataDir;
 pub use domain::Domain;
 pub use domain_port::DomainPort;
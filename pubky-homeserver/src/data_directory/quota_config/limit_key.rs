@@ -82,16 +82,19 @@ pub enum LimitKeyType {
     Ip,
 }
 
+impl LimitKeyType {
+    /// The key type name as used in the config.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LimitKeyType::User => "user",
+            LimitKeyType::Ip => "ip",
+        }
+    }
+}
+
 impl fmt::Display for LimitKeyType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                LimitKeyType::User => "user",
-                LimitKeyType::Ip => "ip",
-            }
-        )
+        write!(f, "{}", self.as_str())
     }
 }
 
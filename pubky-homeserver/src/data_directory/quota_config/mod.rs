@@ -5,6 +5,7 @@ mod limit_key;
 mod path_limit;
 pub(crate) mod rate_unit;
 mod request_count_quota;
+mod route_class;
 mod time_unit;
 
 pub use bandwidth_quota::BandwidthQuota;
@@ -13,4 +14,5 @@ pub use http_method::HttpMethod;
 pub use limit_key::{LimitKey, LimitKeyType};
 pub use path_limit::*;
 pub use request_count_quota::RequestCountQuota;
+pub use route_class::{ClassLimit, RouteClass};
 pub use time_unit::TimeUnit;
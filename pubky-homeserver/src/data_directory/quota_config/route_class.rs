@@ -0,0 +1,203 @@
+use std::fmt;
+use std::num::NonZeroU32;
+use std::str::FromStr;
+
+use axum::http::Method;
+use serde::{Deserialize, Serialize};
+use serde_valid::Validate;
+
+use super::{limit_key::LimitKey, LimitKeyType, RequestCountQuota};
+
+/// Paths that are never subject to route-class limits so health checks keep working
+/// while a client is throttled.
+const HEALTH_PATHS: &[&str] = &["/"];
+
+/// A class of client routes that share a request-count limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RouteClass {
+    /// `GET`, `HEAD` and `OPTIONS` requests outside the auth routes.
+    Read,
+    /// Every other method outside the auth routes.
+    Write,
+    /// Signup, signin and session routes (`/signup`, `/session`, `/auth/*`).
+    Auth,
+}
+
+impl RouteClass {
+    /// Classify a request, or `None` for health endpoints, which are exempt.
+    pub fn of(method: &Method, path: &str) -> Option<Self> {
+        if HEALTH_PATHS.contains(&path) {
+            return None;
+        }
+        if path == "/signup" || path == "/session" || path.starts_with("/auth/") {
+            return Some(RouteClass::Auth);
+        }
+        if method == Method::GET || method == Method::HEAD || method == Method::OPTIONS {
+            Some(RouteClass::Read)
+        } else {
+            Some(RouteClass::Write)
+        }
+    }
+
+    /// The class name as used in the config.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RouteClass::Read => "read",
+            RouteClass::Write => "write",
+            RouteClass::Auth => "auth",
+        }
+    }
+}
+
+impl fmt::Display for RouteClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for RouteClass {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "read" => Ok(RouteClass::Read),
+            "write" => Ok(RouteClass::Write),
+            "auth" => Ok(RouteClass::Auth),
+            _ => Err(format!("Invalid route class: {}", s)),
+        }
+    }
+}
+
+impl serde::Serialize for RouteClass {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for RouteClass {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        RouteClass::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Make sure all whitelist keys are of the same type as the limit key type.
+fn serde_validate_class_limit(limit: &ClassLimit) -> Result<(), serde_valid::validation::Error> {
+    limit
+        .validate()
+        .map_err(|e| serde_valid::validation::Error::Custom(e.to_string()))?;
+    Ok(())
+}
+
+/// A limit on every route of a [`RouteClass`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Hash, Validate)]
+#[validate(custom = serde_validate_class_limit)]
+pub struct ClassLimit {
+    /// The route class to limit.
+    pub class: RouteClass,
+    /// The request-count quota to apply (e.g. "600r/m").
+    pub quota: RequestCountQuota,
+    /// The key to limit.
+    pub key: LimitKeyType,
+    /// The burst to apply.
+    pub burst: Option<NonZeroU32>,
+    /// The whitelist of keys to limit.
+    #[serde(default)]
+    pub whitelist: Vec<LimitKey>,
+}
+
+impl ClassLimit {
+    /// Check if the key is whitelisted.
+    pub fn is_whitelisted(&self, key: &LimitKey) -> bool {
+        self.whitelist.contains(key)
+    }
+
+    /// Validate the class limit.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if let Some(k) = self.whitelist.iter().find(|k| k.get_type() != self.key) {
+            let should_type = self.key.to_string();
+            let is_type = k.get_type().to_string();
+            anyhow::bail!("Whitelist key type mismatch for '{k}'. Expected type '{should_type}' but got '{is_type}'. Full class limit: {self}");
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ClassLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let burst_str = self
+            .burst
+            .map(|b| format!(" burst {b}"))
+            .unwrap_or_default();
+        write!(
+            f,
+            "{} routes: {}{burst_str} by {}.whitelist: {:?}",
+            self.class, self.quota, self.key, self.whitelist
+        )
+    }
+}
+
+impl TryFrom<ClassLimit> for governor::Quota {
+    type Error = String;
+
+    fn try_from(value: ClassLimit) -> Result<Self, Self::Error> {
+        let quota = Self::try_from(value.quota)?;
+        let quota = match value.burst {
+            Some(burst) => quota.allow_burst(burst),
+            None => quota,
+        };
+        Ok(quota)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_routes() {
+        assert_eq!(
+            RouteClass::of(&Method::GET, "/pub/app/file"),
+            Some(RouteClass::Read)
+        );
+        assert_eq!(
+            RouteClass::of(&Method::HEAD, "/events/"),
+            Some(RouteClass::Read)
+        );
+        assert_eq!(
+            RouteClass::of(&Method::PUT, "/pub/app/file"),
+            Some(RouteClass::Write)
+        );
+        assert_eq!(
+            RouteClass::of(&Method::DELETE, "/pub/app/"),
+            Some(RouteClass::Write)
+        );
+        assert_eq!(
+            RouteClass::of(&Method::POST, "/session"),
+            Some(RouteClass::Auth)
+        );
+        assert_eq!(
+            RouteClass::of(&Method::GET, "/auth/grant/sessions"),
+            Some(RouteClass::Auth)
+        );
+        assert_eq!(RouteClass::of(&Method::GET, "/"), None);
+    }
+
+    #[test]
+    fn test_class_limit_from_toml() {
+        let limit: ClassLimit =
+            toml::from_str("class = \"write\"\nquota = \"60r/m\"\nkey = \"user\"").unwrap();
+        assert_eq!(limit.class, RouteClass::Write);
+        assert_eq!(limit.key, LimitKeyType::User);
+        assert!(toml::from_str::<ClassLimit>(
+            "class = \"admin\"\nquota = \"60r/m\"\nkey = \"user\""
+        )
+        .is_err());
+    }
+}
@@ -1,3 +1,5 @@
+use std::str::FromStr;
+
 use serde::{Deserialize, Serialize};
 
 /// The mode of signup.
@@ -9,6 +11,32 @@ pub enum SignupMode {
     /// Only users with a valid token can signup.
     #[default]
     TokenRequired,
+    /// Nobody can signup. Existing users can still sign in.
+    Closed,
+}
+
+impl SignupMode {
+    /// The name used in the config file and the admin API.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SignupMode::Open => "open",
+            SignupMode::TokenRequired => "token_required",
+            SignupMode::Closed => "closed",
+        }
+    }
+}
+
+impl FromStr for SignupMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "open" => Ok(SignupMode::Open),
+            "token_required" => Ok(SignupMode::TokenRequired),
+            "closed" => Ok(SignupMode::Closed),
+            other => Err(format!("Unknown signup mode: {other}")),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -34,5 +62,18 @@ mod tests {
 
         let test_toml_3: TestToml = toml::from_str("\n").unwrap();
         assert_eq!(test_toml_3.signup_mode, SignupMode::TokenRequired);
+
+        for mode in [
+            SignupMode::Open,
+            SignupMode::TokenRequired,
+            SignupMode::Closed,
+        ] {
+            let toml_str = toml::to_string(&TestToml {
+                signup_mode: mode.clone(),
+            })
+            .unwrap();
+            assert_eq!(toml_str, format!("signup_mode = \"{}\"\n", mode.as_str()));
+            assert_eq!(SignupMode::from_str(mode.as_str()), Ok(mode));
+        }
     }
 }
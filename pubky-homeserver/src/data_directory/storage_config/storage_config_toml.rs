@@ -1,3 +1,8 @@
+use std::{
+    num::NonZeroU32,
+    path::{Path, PathBuf},
+};
+
 #[cfg(feature = "storage-gcs")]
 use super::google_bucket_config::GoogleBucketConfig;
 
@@ -25,4 +30,34 @@ pub struct StorageToml {
     /// Default per-user storage quota in MB.
     /// Omit for unlimited. `0` means zero storage (not unlimited).
     pub default_quota_mb: Option<u64>,
+    /// Store identical file content only once across all users.
+    #[serde(default)]
+    pub deduplicate_blobs: bool,
+    /// `file_system` only: directories to spread user files across, by a stable hash of the
+    /// user key. Relative paths are resolved against the data directory. Empty keeps
+    /// everything in `data/files`. Only ever append to this list, then run
+    /// `pubky-homeserver rebalance-shards`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub shards: Vec<PathBuf>,
+    /// Seconds between sweeps that delete expired (TTL) entries. `0` disables the sweeper;
+    /// expired entries then stay hidden but keep using storage.
+    pub expiry_sweep_interval: u64,
+    /// Maximum number of expired entries deleted per batch.
+    pub expiry_sweep_batch_size: NonZeroU32,
+    /// Maximum length in bytes of a decoded file path. Longer writes are refused
+    /// with `414 URI Too Long`.
+    pub max_path_length: usize,
+    /// Maximum number of segments in a file path. Deeper writes are refused with
+    /// `400 Bad Request`.
+    pub max_path_segments: usize,
+}
+
+impl StorageToml {
+    /// The configured shard directories, with relative paths resolved against `data_directory`.
+    pub fn shard_dirs(&self, data_directory: &Path) -> Vec<PathBuf> {
+        self.shards
+            .iter()
+            .map(|dir| data_directory.join(dir))
+            .collect()
+    }
 }
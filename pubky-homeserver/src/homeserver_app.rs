@@ -6,11 +6,12 @@
 //! migration) and graceful shutdown.
 
 use crate::admin_server::{AdminServer, AdminServerBuildError};
-use crate::client_server::{ClientServer, ClientServerBuildError};
+use crate::client_server::{ClientServer, ClientServerBuildError, RouterExtensions};
 use crate::metrics_server::{MetricsServer, MetricsServerBuildError};
 use crate::republishers::{
     HomeserverKeyRepublisher, KeyRepublisherBuildError, UserKeysRepublisherJob,
 };
+use crate::services::expired_entries_sweeper::ExpiredEntriesSweeper;
 use crate::tracing::init_tracing_logs_with_config_if_set;
 #[cfg(any(test, feature = "testing"))]
 use crate::MockDataDir;
@@ -37,7 +38,8 @@ pub enum HomeserverAppBuildError {
 /// Homeserver with all bells and whistles.
 /// Core + Admin + Metrics servers.
 ///
-/// When dropped, the homeserver will stop.
+/// When dropped, the homeserver will stop. Background tasks are aborted without
+/// waiting for them; use [`Self::shutdown`] to wait.
 pub struct HomeserverApp {
     context: AppContext,
 
@@ -45,10 +47,13 @@ pub struct HomeserverApp {
     client_server: ClientServer,
 
     // Republishing is stopped when the UserKeysRepublisherJob is dropped.
-    _user_keys_republisher_job: Option<UserKeysRepublisherJob>,
+    user_keys_republisher_job: Option<UserKeysRepublisherJob>,
 
     // Republishing is stopped when the HomeserverKeyRepublisher is dropped.
-    _key_republisher: HomeserverKeyRepublisher,
+    key_republisher: HomeserverKeyRepublisher,
+
+    // Sweeping is stopped when the ExpiredEntriesSweeper is dropped.
+    expired_entries_sweeper: Option<ExpiredEntriesSweeper>,
 
     #[allow(dead_code)] // Keep this alive. When dropped, the admin server will stop.
     admin_server: Option<AdminServer>,
@@ -80,6 +85,15 @@ impl HomeserverApp {
 
     /// Run a Homeserver
     pub async fn start(context: AppContext) -> Result<Self> {
+        Self::start_with_extensions(context, RouterExtensions::default()).await
+    }
+
+    /// Run a Homeserver with extra routes and middleware composed into the client server's
+    /// router. See [`RouterExtensions`].
+    pub async fn start_with_extensions(
+        context: AppContext,
+        extensions: RouterExtensions,
+    ) -> Result<Self> {
         // Tracing Subscriber initialization based on the config file.
         let _ = init_tracing_logs_with_config_if_set(&context.config_toml);
 
@@ -95,6 +109,8 @@ impl HomeserverApp {
             republish_interval,
         );
 
+        let expired_entries_sweeper = ExpiredEntriesSweeper::start(&context);
+
         let admin_server = if context.config_toml.admin.enabled {
             Some(AdminServer::start(&context).await?)
         } else {
@@ -105,7 +121,8 @@ impl HomeserverApp {
         } else {
             None
         };
-        let client_server = ClientServer::start(context.clone()).await?;
+        let client_server =
+            ClientServer::start_with_extensions(context.clone(), extensions).await?;
 
         let key_republisher = HomeserverKeyRepublisher::start(
             &context,
@@ -120,11 +137,36 @@ impl HomeserverApp {
             client_server,
             admin_server,
             metrics_server,
-            _user_keys_republisher_job: user_keys_republisher_job,
-            _key_republisher: key_republisher,
+            user_keys_republisher_job,
+            key_republisher,
+            expired_entries_sweeper,
         })
     }
 
+    /// Stop the servers and the background tasks, waiting until the tasks have finished.
+    ///
+    /// The servers finish in-flight requests for up to 5 seconds in the background.
+    /// Event streams stop receiving new events, and private streams are closed,
+    /// also on other users of the same [`AppContext`].
+    pub async fn shutdown(mut self) {
+        self.client_server.shutdown();
+        if let Some(admin_server) = &self.admin_server {
+            admin_server.shutdown();
+        }
+        if let Some(metrics_server) = &self.metrics_server {
+            metrics_server.shutdown();
+        }
+        self.key_republisher.shutdown().await;
+        if let Some(job) = &mut self.user_keys_republisher_job {
+            job.shutdown().await;
+        }
+        if let Some(sweeper) = &mut self.expired_entries_sweeper {
+            sweeper.shutdown().await;
+        }
+        self.context.pg_event_listener.shutdown().await;
+        self.context.auth_revocation_service.shutdown().await;
+    }
+
     /// Get the core of the homeserver app.
     pub fn client_server(&self) -> &ClientServer {
         &self.client_server
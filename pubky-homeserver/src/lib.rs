@@ -27,8 +27,9 @@ pub mod tracing;
 
 pub use admin_server::{AdminServer, AdminServerBuildError};
 pub use app_context::{AppContext, AppContextConversionError};
-pub use client_server::{ClientServer, ClientServerBuildError};
+pub use client_server::{ClientServer, ClientServerBuildError, RouterExtensions};
 pub use data_directory::*;
 pub use homeserver_app::{HomeserverApp, HomeserverAppBuildError};
 pub use metrics_server::{MetricsServer, MetricsServerBuildError};
+pub use persistence::files::sharded_storage::{rebalance_shards, shard_index, RebalanceReport};
 pub use persistence::sql::ConnectionString;
@@ -3,7 +3,7 @@ use std::path::PathBuf;
 use anyhow::Result;
 use clap::{Parser, Subcommand};
 use pubky_homeserver::{
-    tracing::init_tracing_logs_if_set, DataDir, HomeserverApp, PersistentDataDir,
+    rebalance_shards, tracing::init_tracing_logs_if_set, DataDir, HomeserverApp, PersistentDataDir,
 };
 
 fn default_config_dir_path() -> PathBuf {
@@ -35,6 +35,9 @@ struct Cli {
 enum Command {
     /// Initialize the data directory (config and keypair) without starting the server.
     Init,
+    /// Move stored files to the shard they belong to after changing `[storage].shards`.
+    /// Stop the homeserver first.
+    RebalanceShards,
 }
 
 #[tokio::main]
@@ -50,6 +53,18 @@ async fn main() -> Result<()> {
                 data_dir.path().display()
             );
         }
+        Some(Command::RebalanceShards) => {
+            init_tracing_logs_if_set(&args.data_dir)?;
+            let data_dir = PersistentDataDir::new(args.data_dir);
+            let config = data_dir.read_or_create_config_file()?;
+            let shard_dirs = config.storage.shard_dirs(data_dir.path());
+            if shard_dirs.is_empty() {
+                println!("No [storage].shards configured, nothing to rebalance.");
+                return Ok(());
+            }
+            let report = rebalance_shards(&shard_dirs)?;
+            println!("Rebalanced {} shards: {report}.", shard_dirs.len());
+        }
         None => {
             init_tracing_logs_if_set(&args.data_dir)?;
 
@@ -86,6 +101,7 @@ async fn main() -> Result<()> {
             tokio::signal::ctrl_c().await?;
 
             tracing::info!("Shutting down Homeserver");
+            server.shutdown().await;
         }
     }
 
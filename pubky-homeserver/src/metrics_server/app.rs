@@ -77,12 +77,17 @@ impl MetricsServer {
     pub fn listen_socket(&self) -> SocketAddr {
         self.socket
     }
+
+    /// Shutdown the http server.
+    pub fn shutdown(&self) {
+        self.http_handle
+            .graceful_shutdown(Some(Duration::from_secs(5)));
+    }
 }
 
 impl Drop for MetricsServer {
     fn drop(&mut self) {
-        self.http_handle
-            .graceful_shutdown(Some(Duration::from_secs(5)));
+        self.shutdown();
         self.join_handle.abort();
     }
 }
@@ -5,7 +5,8 @@
 //! server route. The `metrics_server` *serves* this over HTTP; subsystems (`persistence`,
 //! `client_server`, …) only *record* into it.
 
-use opentelemetry::metrics::{Counter, Histogram, Meter, MeterProvider, UpDownCounter};
+use opentelemetry::metrics::{Counter, Gauge, Histogram, Meter, MeterProvider, UpDownCounter};
+use opentelemetry::KeyValue;
 use opentelemetry_sdk::metrics::SdkMeterProvider;
 use prometheus::{Encoder, Registry, TextEncoder};
 use std::sync::Arc;
@@ -25,6 +26,13 @@ pub const EVENT_STREAM_BROADCAST_HALF_FULL_COUNT: &str = "event_stream_broadcast
 pub const EVENT_STREAM_ACTIVE_CONNECTIONS: &str = "event_stream_active_connections";
 pub const EVENT_STREAM_CONNECTION_DURATION: &str = "event_stream_connection_duration_ms";
 pub const SIGNUP_COUNT: &str = "signup_count";
+pub const EXPIRY_SWEEP_ENTRIES_COUNT: &str = "expiry_sweep_entries_count";
+pub const EXPIRY_SWEEP_BYTES_COUNT: &str = "expiry_sweep_bytes_count";
+pub const EXPIRY_SWEEP_LAST_RUN: &str = "expiry_sweep_last_run_timestamp_seconds";
+pub const KEY_REPUBLISH_SUCCESS_COUNT: &str = "key_republish_success_count";
+pub const KEY_REPUBLISH_FAILURE_COUNT: &str = "key_republish_failure_count";
+pub const KEY_REPUBLISH_LAST_SUCCESS: &str = "key_republish_last_success_timestamp_seconds";
+pub const RATE_LIMITED_REQUEST_COUNT: &str = "rate_limited_request_count";
 
 #[derive(Clone, Debug)]
 pub struct Metrics {
@@ -37,6 +45,13 @@ pub struct Metrics {
     event_stream_active_connections: UpDownCounter<i64>,
     event_stream_connection_duration: Histogram<f64>,
     signup_count: Counter<u64>,
+    expiry_sweep_entries_count: Counter<u64>,
+    expiry_sweep_bytes_count: Counter<u64>,
+    expiry_sweep_last_run: Gauge<u64>,
+    key_republish_success_count: Counter<u64>,
+    key_republish_failure_count: Counter<u64>,
+    key_republish_last_success: Gauge<u64>,
+    rate_limited_request_count: Counter<u64>,
 }
 
 impl Metrics {
@@ -81,6 +96,45 @@ impl Metrics {
             .with_description("Total number of successful signups")
             .build();
 
+        let expiry_sweep_entries_count = meter
+            .u64_counter(EXPIRY_SWEEP_ENTRIES_COUNT)
+            .with_description("Total number of expired entries deleted by the sweeper")
+            .build();
+
+        let expiry_sweep_bytes_count = meter
+            .u64_counter(EXPIRY_SWEEP_BYTES_COUNT)
+            .with_description("Total content bytes reclaimed by the expiry sweeper")
+            .build();
+
+        let expiry_sweep_last_run = meter
+            .u64_gauge(EXPIRY_SWEEP_LAST_RUN)
+            .with_description("Unix time of the last completed expiry sweep")
+            .build();
+
+        let key_republish_success_count = meter
+            .u64_counter(KEY_REPUBLISH_SUCCESS_COUNT)
+            .with_description("Number of successful republishes of the homeserver's pkarr packet")
+            .build();
+
+        let key_republish_failure_count = meter
+            .u64_counter(KEY_REPUBLISH_FAILURE_COUNT)
+            .with_description(
+                "Number of republishes of the homeserver's pkarr packet that failed after all retries",
+            )
+            .build();
+
+        let key_republish_last_success = meter
+            .u64_gauge(KEY_REPUBLISH_LAST_SUCCESS)
+            .with_description(
+                "Unix time of the last successful republish of the homeserver's pkarr packet",
+            )
+            .build();
+
+        let rate_limited_request_count = meter
+            .u64_counter(RATE_LIMITED_REQUEST_COUNT)
+            .with_description("Number of requests rejected with 429 by a request-count rate limit")
+            .build();
+
         Ok(Self {
             registry: Arc::new(registry),
             _provider: Arc::new(provider),
@@ -91,6 +145,13 @@ impl Metrics {
             event_stream_active_connections,
             event_stream_connection_duration,
             signup_count,
+            expiry_sweep_entries_count,
+            expiry_sweep_bytes_count,
+            expiry_sweep_last_run,
+            key_republish_success_count,
+            key_republish_failure_count,
+            key_republish_last_success,
+            rate_limited_request_count,
         })
     }
 
@@ -135,6 +196,39 @@ impl Metrics {
         self.signup_count.add(1, &[]);
     }
 
+    // === expiry sweeper metrics ===
+
+    pub fn record_expiry_sweep(&self, entries: u64, bytes: u64) {
+        self.expiry_sweep_entries_count.add(entries, &[]);
+        self.expiry_sweep_bytes_count.add(bytes, &[]);
+    }
+
+    pub fn record_expiry_sweep_run(&self, unix_seconds: u64) {
+        self.expiry_sweep_last_run.record(unix_seconds, &[]);
+    }
+
+    // === key republisher metrics ===
+
+    pub fn record_key_republish_success(&self, unix_seconds: u64) {
+        self.key_republish_success_count.add(1, &[]);
+        self.key_republish_last_success.record(unix_seconds, &[]);
+    }
+
+    pub fn record_key_republish_failure(&self) {
+        self.key_republish_failure_count.add(1, &[]);
+    }
+
+    // === rate limiter metrics ===
+
+    /// Count a request rejected by a rate limit. `scope` is the route class of the
+    /// limit (or `path`), `key` what it is keyed by (`ip` or `user`).
+    pub fn record_rate_limited(&self, scope: &'static str, key: &'static str) {
+        self.rate_limited_request_count.add(
+            1,
+            &[KeyValue::new("scope", scope), KeyValue::new("key", key)],
+        );
+    }
+
     /// Render Prometheus metrics in text format
     pub fn render(&self) -> Result<String, String> {
         let metric_families = self.registry.gather();
@@ -295,6 +389,11 @@ mod tests {
         metrics.record_broadcast_half_full();
         metrics.record_connection_closed(30);
         metrics.record_signup();
+        metrics.record_expiry_sweep(3, 1024);
+        metrics.record_expiry_sweep_run(1_700_000_000);
+        metrics.record_key_republish_success(1_700_000_000);
+        metrics.record_key_republish_failure();
+        metrics.record_rate_limited("write", "ip");
 
         let output = metrics.render().expect("Failed to render metrics");
 
@@ -344,5 +443,16 @@ mod tests {
             SIGNUP_COUNT,
             output
         );
+        for name in [
+            EXPIRY_SWEEP_ENTRIES_COUNT,
+            EXPIRY_SWEEP_BYTES_COUNT,
+            EXPIRY_SWEEP_LAST_RUN,
+            KEY_REPUBLISH_SUCCESS_COUNT,
+            KEY_REPUBLISH_FAILURE_COUNT,
+            KEY_REPUBLISH_LAST_SUCCESS,
+            RATE_LIMITED_REQUEST_COUNT,
+        ] {
+            assert!(output.contains(name), "Missing {} in: {}", name, output);
+        }
     }
 }
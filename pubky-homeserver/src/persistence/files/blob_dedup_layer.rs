@@ -0,0 +1,613 @@
+use std::collections::{HashSet, VecDeque};
+use std::sync::Arc;
+
+use opendal::raw::oio::{Delete, List};
+use opendal::raw::*;
+use opendal::{EntryMode, Metadata, Result};
+use pubky_common::crypto::{Hash, Hasher};
+use uuid::Uuid;
+
+use crate::persistence::sql::blob::{BlobEntity, BlobRepository};
+use crate::persistence::sql::{uexecutor, SqlDb};
+
+/// Storage prefix under which deduplicated blobs are kept.
+/// It is not a public key, so user-facing paths can never point into it.
+pub(crate) const BLOB_PREFIX: &str = "_blobs/";
+
+/// Layer that stores identical file content only once, no matter how many paths hold it.
+///
+/// Every write is streamed to a fresh key under [`BLOB_PREFIX`] while being hashed.
+/// On close, the path is pointed at the blob with that hash and the upload is dropped
+/// again if an identical blob already exists. Blobs are reference counted in the
+/// database and removed from storage once no path references them anymore.
+///
+/// Files written before the layer was enabled have no reference and keep being
+/// served from their own path.
+#[derive(Clone)]
+pub struct BlobDedupLayer {
+    db: SqlDb,
+}
+
+impl BlobDedupLayer {
+    pub fn new(db: SqlDb) -> Self {
+        Self { db }
+    }
+}
+
+impl<A: Access> Layer<A> for BlobDedupLayer {
+    type LayeredAccess = BlobDedupAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccess {
+        BlobDedupAccessor {
+            store: BlobStore {
+                inner: Arc::new(inner),
+                db: self.db.clone(),
+            },
+        }
+    }
+}
+
+fn db_error(e: sqlx::Error) -> opendal::Error {
+    opendal::Error::new(opendal::ErrorKind::Unexpected, e.to_string())
+}
+
+/// Outcome of removing the reference of a path.
+enum Unlinked {
+    /// The path referenced a blob. Holds the blob's storage key if that was its last reference.
+    Blob(Option<String>),
+    /// The path referenced no blob. Its content, if any, is stored at the path itself.
+    Unreferenced,
+}
+
+/// The inner accessor plus the reference bookkeeping shared by the accessor, writers and deleters.
+#[derive(Debug)]
+struct BlobStore<A: Access> {
+    inner: Arc<A>,
+    db: SqlDb,
+}
+
+impl<A: Access> Clone for BlobStore<A> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            db: self.db.clone(),
+        }
+    }
+}
+
+impl<A: Access> BlobStore<A> {
+    async fn lookup(&self, path: &str) -> Result<Option<BlobEntity>> {
+        BlobRepository::get_by_path(path, &mut self.db.pool().into())
+            .await
+            .map_err(db_error)
+    }
+
+    /// The key the content of `path` is stored under.
+    async fn storage_key(&self, path: &str) -> Result<String> {
+        if path.ends_with('/') {
+            return Ok(path.to_string());
+        }
+        Ok(match self.lookup(path).await? {
+            Some(blob) => blob.storage_key,
+            None => path.to_string(),
+        })
+    }
+
+    /// Point `path` at the blob with `hash`, whose content was just uploaded to `staging_key`.
+    ///
+    /// Returns the storage keys that are no longer referenced.
+    async fn commit_write(
+        &self,
+        path: &str,
+        hash: &Hash,
+        staging_key: &str,
+        content_length: u64,
+    ) -> Result<Vec<String>> {
+        let mut tx = self.db.pool().begin().await.map_err(db_error)?;
+        BlobRepository::lock_path(path, uexecutor!(tx))
+            .await
+            .map_err(db_error)?;
+        let blob = BlobRepository::acquire(hash, staging_key, content_length, uexecutor!(tx))
+            .await
+            .map_err(db_error)?;
+        let previous = BlobRepository::set_ref(path, hash, uexecutor!(tx))
+            .await
+            .map_err(db_error)?;
+        let released = match &previous {
+            Some(previous) => BlobRepository::release(previous, uexecutor!(tx))
+                .await
+                .map_err(db_error)?,
+            None => None,
+        };
+        tx.commit().await.map_err(db_error)?;
+
+        let mut obsolete = Vec::new();
+        if blob.storage_key != staging_key {
+            // An identical blob already existed.
+            obsolete.push(staging_key.to_string());
+        }
+        match previous {
+            Some(_) => obsolete.extend(released),
+            // The path may still hold content written before deduplication was enabled.
+            None => obsolete.push(path.to_string()),
+        }
+        Ok(obsolete)
+    }
+
+    /// Point `to` at the blob referenced by `from`, removing the reference of `from`
+    /// unless `keep_source` is set.
+    ///
+    /// Returns `None` if `from` references no blob. Otherwise returns the storage keys
+    /// that are no longer referenced.
+    async fn link(&self, from: &str, to: &str, keep_source: bool) -> Result<Option<Vec<String>>> {
+        let mut tx = self.db.pool().begin().await.map_err(db_error)?;
+        // Lock in a stable order so concurrent links between the same paths cannot deadlock.
+        let mut locks = [from, to];
+        locks.sort_unstable();
+        for path in locks {
+            BlobRepository::lock_path(path, uexecutor!(tx))
+                .await
+                .map_err(db_error)?;
+        }
+        let Some(blob) = BlobRepository::get_by_path(from, uexecutor!(tx))
+            .await
+            .map_err(db_error)?
+        else {
+            return Ok(None);
+        };
+
+        if keep_source {
+            BlobRepository::acquire(
+                &blob.hash,
+                &blob.storage_key,
+                blob.content_length,
+                uexecutor!(tx),
+            )
+            .await
+            .map_err(db_error)?;
+        } else {
+            BlobRepository::remove_ref(from, uexecutor!(tx))
+                .await
+                .map_err(db_error)?;
+        }
+        let previous = BlobRepository::set_ref(to, &blob.hash, uexecutor!(tx))
+            .await
+            .map_err(db_error)?;
+        let released = match &previous {
+            Some(previous) => BlobRepository::release(previous, uexecutor!(tx))
+                .await
+                .map_err(db_error)?,
+            None => None,
+        };
+        tx.commit().await.map_err(db_error)?;
+
+        Ok(Some(match previous {
+            Some(_) => released.into_iter().collect(),
+            None => vec![to.to_string()],
+        }))
+    }
+
+    /// Remove the reference of `path`.
+    async fn unlink(&self, path: &str) -> Result<Unlinked> {
+        let mut tx = self.db.pool().begin().await.map_err(db_error)?;
+        BlobRepository::lock_path(path, uexecutor!(tx))
+            .await
+            .map_err(db_error)?;
+        let Some(hash) = BlobRepository::remove_ref(path, uexecutor!(tx))
+            .await
+            .map_err(db_error)?
+        else {
+            return Ok(Unlinked::Unreferenced);
+        };
+        let released = BlobRepository::release(&hash, uexecutor!(tx))
+            .await
+            .map_err(db_error)?;
+        tx.commit().await.map_err(db_error)?;
+        Ok(Unlinked::Blob(released))
+    }
+
+    async fn delete_key(&self, key: &str, args: OpDelete) -> Result<()> {
+        let (_, mut deleter) = self.inner.delete().await?;
+        deleter.delete(key, args)?;
+        deleter.flush().await?;
+        Ok(())
+    }
+
+    /// Delete storage keys that are no longer referenced.
+    ///
+    /// The references are already committed at this point, so failures only leave
+    /// unreachable content behind and are logged instead of failing the operation.
+    async fn delete_obsolete(&self, keys: Vec<String>) {
+        for key in keys {
+            if let Err(e) = self.delete_key(&key, OpDelete::default()).await {
+                tracing::warn!("Failed to delete unreferenced content at {key}: {e}");
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BlobDedupAccessor<A: Access> {
+    store: BlobStore<A>,
+}
+
+impl<A: Access> LayeredAccess for BlobDedupAccessor<A> {
+    type Inner = A;
+    type Reader = A::Reader;
+    type Writer = WriterWrapper<A::Writer, A>;
+    type Lister = ListerWrapper;
+    type Deleter = DeleterWrapper<A>;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.store.inner
+    }
+
+    async fn create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
+        self.store.inner.create_dir(path, args).await
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        let key = self.store.storage_key(path).await?;
+        self.store.inner.read(&key, args).await
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        let staging_key = format!("{BLOB_PREFIX}{}", Uuid::new_v4());
+        let (rp, writer) = self.store.inner.write(&staging_key, args).await?;
+        Ok((
+            rp,
+            WriterWrapper {
+                inner: writer,
+                store: self.store.clone(),
+                path: path.to_string(),
+                staging_key,
+                hasher: Hasher::new(),
+                bytes_count: 0,
+            },
+        ))
+    }
+
+    async fn copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        match self.store.link(from, to, true).await? {
+            Some(obsolete) => {
+                self.store.delete_obsolete(obsolete).await;
+                Ok(RpCopy::default())
+            }
+            None => {
+                let rp = self.store.inner.copy(from, to, args).await?;
+                self.drop_shadowing_ref(to).await?;
+                Ok(rp)
+            }
+        }
+    }
+
+    async fn rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        match self.store.link(from, to, false).await? {
+            Some(obsolete) => {
+                self.store.delete_obsolete(obsolete).await;
+                Ok(RpRename::default())
+            }
+            None => {
+                let rp = self.store.inner.rename(from, to, args).await?;
+                self.drop_shadowing_ref(to).await?;
+                Ok(rp)
+            }
+        }
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        if !path.ends_with('/') {
+            let key = self.store.storage_key(path).await?;
+            return self.store.inner.stat(&key, args).await;
+        }
+        match self.store.inner.stat(path, args).await {
+            Err(e) if e.kind() == opendal::ErrorKind::NotFound => {
+                // Directories holding only deduplicated files do not exist in the backend.
+                let has_refs =
+                    BlobRepository::has_refs_with_prefix(path, &mut self.store.db.pool().into())
+                        .await
+                        .map_err(db_error)?;
+                if has_refs {
+                    Ok(RpStat::new(Metadata::new(EntryMode::DIR)))
+                } else {
+                    Err(e)
+                }
+            }
+            res => res,
+        }
+    }
+
+    async fn delete(&self) -> Result<(RpDelete, Self::Deleter)> {
+        Ok((
+            RpDelete::default(),
+            DeleterWrapper {
+                store: self.store.clone(),
+                path_queue: Vec::new(),
+            },
+        ))
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Lister)> {
+        let recursive = args.recursive();
+        let (rp, mut lister) = self.store.inner.list(path, args).await?;
+        let mut entries = VecDeque::new();
+        let mut seen = HashSet::new();
+        while let Some(entry) = lister.next().await? {
+            if entry.path().starts_with(BLOB_PREFIX) {
+                continue;
+            }
+            seen.insert(entry.path().to_string());
+            entries.push_back(entry);
+        }
+
+        // Add the deduplicated files, which only exist as references.
+        let prefix = if path == "/" { "" } else { path };
+        let refs = BlobRepository::list_refs_by_prefix(prefix, &mut self.store.db.pool().into())
+            .await
+            .map_err(db_error)?;
+        let mut referenced = Vec::new();
+        for (ref_path, content_length) in refs {
+            let entry = match ref_path[prefix.len()..].split_once('/') {
+                Some((dir, _)) if !recursive => {
+                    oio::Entry::new(&format!("{prefix}{dir}/"), Metadata::new(EntryMode::DIR))
+                }
+                _ => oio::Entry::new(
+                    &ref_path,
+                    Metadata::new(EntryMode::FILE).with_content_length(content_length),
+                ),
+            };
+            if seen.insert(entry.path().to_string()) {
+                referenced.push(entry);
+            }
+        }
+        // Listings start with the directory itself, which may only exist implicitly.
+        if !referenced.is_empty() && seen.insert(path.to_string()) {
+            entries.push_front(oio::Entry::new(path, Metadata::new(EntryMode::DIR)));
+        }
+        entries.extend(referenced);
+        Ok((rp, ListerWrapper { entries }))
+    }
+
+    async fn presign(&self, path: &str, args: OpPresign) -> Result<RpPresign> {
+        self.store.inner.presign(path, args).await
+    }
+}
+
+impl<A: Access> BlobDedupAccessor<A> {
+    /// After a plain copy or rename onto `to`, drop the blob reference `to` may still
+    /// hold so the new content is not hidden behind it.
+    async fn drop_shadowing_ref(&self, to: &str) -> Result<()> {
+        if let Unlinked::Blob(Some(key)) = self.store.unlink(to).await? {
+            self.store.delete_obsolete(vec![key]).await;
+        }
+        Ok(())
+    }
+}
+
+/// Wrapper around the writer that hashes the content and links the path to its blob on close.
+pub struct WriterWrapper<R, A: Access> {
+    inner: R,
+    store: BlobStore<A>,
+    path: String,
+    staging_key: String,
+    hasher: Hasher,
+    bytes_count: u64,
+}
+
+impl<R: oio::Write, A: Access> oio::Write for WriterWrapper<R, A> {
+    async fn write(&mut self, bs: opendal::Buffer) -> Result<()> {
+        self.hasher.update(&bs.to_vec());
+        self.bytes_count += bs.len() as u64;
+        self.inner.write(bs).await
+    }
+
+    async fn abort(&mut self) -> Result<()> {
+        self.inner.abort().await
+    }
+
+    async fn close(&mut self) -> Result<opendal::Metadata> {
+        let metadata = self.inner.close().await?;
+        let hash = self.hasher.finalize();
+        let obsolete = match self
+            .store
+            .commit_write(&self.path, &hash, &self.staging_key, self.bytes_count)
+            .await
+        {
+            Ok(obsolete) => obsolete,
+            Err(e) => {
+                // Nothing references the upload.
+                self.store
+                    .delete_obsolete(vec![self.staging_key.clone()])
+                    .await;
+                return Err(e);
+            }
+        };
+        self.store.delete_obsolete(obsolete).await;
+        Ok(metadata)
+    }
+}
+
+/// Lister over the backend entries merged with the deduplicated files under a path.
+pub struct ListerWrapper {
+    entries: VecDeque<oio::Entry>,
+}
+
+impl oio::List for ListerWrapper {
+    async fn next(&mut self) -> Result<Option<oio::Entry>> {
+        Ok(self.entries.pop_front())
+    }
+}
+
+/// Deleter that removes path references and deletes blobs once they are unreferenced.
+pub struct DeleterWrapper<A: Access> {
+    store: BlobStore<A>,
+    path_queue: Vec<(String, OpDelete)>,
+}
+
+impl<A: Access> oio::Delete for DeleterWrapper<A> {
+    fn delete(&mut self, path: &str, args: OpDelete) -> Result<()> {
+        self.path_queue.push((path.to_string(), args));
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<usize> {
+        let mut deleted_count = 0;
+        for (path, args) in std::mem::take(&mut self.path_queue) {
+            match self.store.unlink(&path).await? {
+                Unlinked::Blob(Some(key)) => {
+                    self.store.delete_key(&key, OpDelete::default()).await?
+                }
+                Unlinked::Blob(None) => {}
+                Unlinked::Unreferenced => self.store.delete_key(&path, args).await?,
+            }
+            deleted_count += 1;
+        }
+        Ok(deleted_count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::persistence::files::opendal::opendal_test_operators::OpendalTestOperators;
+    use crate::persistence::sql::SqlDb;
+
+    use super::*;
+
+    async fn blob_count(db: &SqlDb) -> i64 {
+        sqlx::query_scalar("SELECT COUNT(*) FROM blobs")
+            .fetch_one(db.pool())
+            .await
+            .unwrap()
+    }
+
+    async fn stored_blobs(operator: &opendal::Operator) -> usize {
+        operator
+            .list(BLOB_PREFIX)
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|entry| entry.metadata().is_file())
+            .count()
+    }
+
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn test_identical_content_is_stored_once() {
+        for (_scheme, raw) in OpendalTestOperators::new().operators() {
+            let db = SqlDb::test().await;
+            let operator = raw.clone().layer(BlobDedupLayer::new(db.clone()));
+
+            operator.write("alice/pub/a.txt", "same").await.unwrap();
+            operator.write("bob/pub/b.txt", "same").await.unwrap();
+            assert_eq!(blob_count(&db).await, 1);
+            assert_eq!(stored_blobs(&raw).await, 1);
+            assert_eq!(
+                operator.read("bob/pub/b.txt").await.unwrap().to_vec(),
+                b"same"
+            );
+            assert_eq!(
+                operator
+                    .stat("alice/pub/a.txt")
+                    .await
+                    .unwrap()
+                    .content_length(),
+                4
+            );
+
+            // Deleting one path keeps the content for the other.
+            operator.delete("alice/pub/a.txt").await.unwrap();
+            let err = operator.stat("alice/pub/a.txt").await.unwrap_err();
+            assert_eq!(err.kind(), opendal::ErrorKind::NotFound);
+            assert_eq!(
+                operator.read("bob/pub/b.txt").await.unwrap().to_vec(),
+                b"same"
+            );
+
+            // Deleting the last path removes the blob.
+            operator.delete("bob/pub/b.txt").await.unwrap();
+            assert_eq!(blob_count(&db).await, 0);
+            assert_eq!(stored_blobs(&raw).await, 0);
+        }
+    }
+
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn test_overwrite_releases_previous_blob() {
+        for (_scheme, raw) in OpendalTestOperators::new().operators() {
+            let db = SqlDb::test().await;
+            let operator = raw.clone().layer(BlobDedupLayer::new(db.clone()));
+
+            operator.write("alice/pub/a.txt", "v1").await.unwrap();
+            operator.write("alice/pub/a.txt", "v2").await.unwrap();
+            assert_eq!(blob_count(&db).await, 1);
+            assert_eq!(stored_blobs(&raw).await, 1);
+            assert_eq!(
+                operator.read("alice/pub/a.txt").await.unwrap().to_vec(),
+                b"v2"
+            );
+
+            // Writing the same content again keeps the single blob.
+            operator.write("alice/pub/a.txt", "v2").await.unwrap();
+            assert_eq!(blob_count(&db).await, 1);
+            assert_eq!(stored_blobs(&raw).await, 1);
+        }
+    }
+
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn test_unreferenced_files_are_still_served() {
+        for (_scheme, raw) in OpendalTestOperators::new().operators() {
+            let db = SqlDb::test().await;
+            raw.write("alice/pub/old.txt", "legacy").await.unwrap();
+            let operator = raw.clone().layer(BlobDedupLayer::new(db.clone()));
+
+            assert_eq!(
+                operator.read("alice/pub/old.txt").await.unwrap().to_vec(),
+                b"legacy"
+            );
+
+            // Overwriting moves the content into a blob and removes the old file.
+            operator.write("alice/pub/old.txt", "new").await.unwrap();
+            assert!(!raw.exists("alice/pub/old.txt").await.unwrap());
+            assert_eq!(
+                operator.read("alice/pub/old.txt").await.unwrap().to_vec(),
+                b"new"
+            );
+        }
+    }
+
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn test_list_includes_deduplicated_files() {
+        for (_scheme, raw) in OpendalTestOperators::new().operators() {
+            let db = SqlDb::test().await;
+            let operator = raw.clone().layer(BlobDedupLayer::new(db.clone()));
+            operator.write("alice/pub/a.txt", "a").await.unwrap();
+            operator.write("alice/pub/dir/b.txt", "b").await.unwrap();
+
+            let mut paths: Vec<String> = operator
+                .list("alice/pub/")
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|entry| entry.path().to_string())
+                .collect();
+            paths.sort();
+            assert_eq!(
+                paths,
+                vec!["alice/pub/", "alice/pub/a.txt", "alice/pub/dir/"]
+            );
+            assert!(operator.stat("alice/pub/dir/").await.unwrap().is_dir());
+
+            let roots: Vec<String> = operator
+                .list("/")
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|entry| entry.path().to_string())
+                .collect();
+            assert!(roots.iter().all(|path| !path.starts_with(BLOB_PREFIX)));
+            assert!(roots.contains(&"alice/".to_string()));
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use super::entry_service::EntryService;
 
-use crate::persistence::files::FileMetadataBuilder;
+use crate::persistence::files::{FileMetadataBuilder, WriteMetadata};
 use crate::persistence::sql::{SqlDb, UnifiedExecutor};
 use crate::shared::webdav::EntryPath;
 use opendal::raw::*;
@@ -56,6 +56,12 @@ impl<A: Access> LayeredAccess for EntryAccessor<A> {
 
     async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
         let entry_path = EntryPath::parse_opendal(path)?;
+        // Per-write metadata is stored with the entry, not passed on to the backend.
+        let write_metadata = args.user_metadata().map(WriteMetadata::from_user_metadata);
+        let args = match write_metadata {
+            Some(_) => args.with_user_metadata(Default::default()),
+            None => args,
+        };
         let (rp, writer) = self.inner.write(path, args).await?;
         Ok((
             rp,
@@ -64,6 +70,7 @@ impl<A: Access> LayeredAccess for EntryAccessor<A> {
                 entry_service: self.entry_service.clone(),
                 entry_path,
                 metadata_builder: FileMetadataBuilder::default(),
+                write_metadata,
             },
         ))
     }
@@ -107,6 +114,9 @@ pub struct WriterWrapper<R> {
     entry_service: EntryService,
     entry_path: EntryPath,
     metadata_builder: FileMetadataBuilder,
+    /// Replaces the per-write metadata of the entry if set, see
+    /// [`FileService::write_stream_with_metadata`](crate::persistence::files::FileService::write_stream_with_metadata).
+    write_metadata: Option<WriteMetadata>,
 }
 
 impl<R: oio::Write> oio::Write for WriterWrapper<R> {
@@ -136,7 +146,12 @@ impl<R: oio::Write> oio::Write for WriterWrapper<R> {
         let mut executor: UnifiedExecutor<'_> = (&mut tx).into();
         match self
             .entry_service
-            .write_entry(&self.entry_path, &file_metadata, &mut executor)
+            .write_entry(
+                &self.entry_path,
+                &file_metadata,
+                self.write_metadata.as_ref(),
+                &mut executor,
+            )
             .await
         {
             Ok(_) => {
@@ -1,6 +1,6 @@
 use crate::{
     persistence::{
-        files::{FileIoError, FileMetadata},
+        files::{FileIoError, FileMetadata, WriteMetadata},
         sql::{
             entry::{EntryEntity, EntryRepository},
             user::UserRepository,
@@ -25,13 +25,15 @@ impl EntryService {
         &self.db
     }
 
-    /// Write an entry to the database.
+    /// Write an entry to the database, replacing its per-write metadata with
+    /// `write_metadata` if given.
     ///
     /// Returns the entry.
     pub async fn write_entry<'a>(
         &self,
         path: &EntryPath,
         metadata: &FileMetadata,
+        write_metadata: Option<&WriteMetadata>,
         executor: &mut UnifiedExecutor<'a>,
     ) -> Result<EntryEntity, FileIoError> {
         let existing_entry = match EntryRepository::get_by_path(path, executor).await {
@@ -41,13 +43,30 @@ impl EntryService {
         };
 
         // Create/Update entry
-        let entry = if let Some(existing_entry) = existing_entry {
+        let mut entry = if let Some(existing_entry) = existing_entry {
             self.update_entry(existing_entry, metadata, executor)
                 .await?
         } else {
             self.create_entry(path, metadata, executor).await?
         };
 
+        if let Some(write_metadata) = write_metadata {
+            if entry.expires_at != write_metadata.expires_at {
+                EntryRepository::set_expires_at(entry.id, write_metadata.expires_at, executor)
+                    .await?;
+                entry.expires_at = write_metadata.expires_at;
+            }
+            if entry.filename != write_metadata.filename {
+                EntryRepository::set_filename(
+                    entry.id,
+                    write_metadata.filename.as_deref(),
+                    executor,
+                )
+                .await?;
+                entry.filename = write_metadata.filename.clone();
+            }
+        }
+
         Ok(entry)
     }
 
@@ -1,6 +1,6 @@
 use pubky_common::crypto::Hash;
-use pubky_common::crypto::PublicKey;
-use pubky_common::events::{EventCursor, EventType};
+use pubky_common::crypto::{Keypair, PublicKey};
+use pubky_common::events::{sign_event, EventCursor, EventType};
 use sea_query::Iden;
 use sqlx::{postgres::PgRow, types::chrono::NaiveDateTime, FromRow, Row};
 
@@ -40,6 +40,32 @@ impl EventEntity {
         }
         lines.join("\n")
     }
+
+    /// [`Self::to_sse_data`] plus a `signature:` line: the base64 Ed25519 signature of the
+    /// homeserver `keypair` over [`pubky_common::events::signable_event`].
+    pub(crate) fn to_signed_sse_data(&self, keypair: &Keypair) -> String {
+        let signature = sign_event(keypair, &self.event_type, &self.pubky_uri(), self.cursor());
+        let signature_base64 = base64::Engine::encode(
+            &base64::engine::general_purpose::STANDARD,
+            signature.to_bytes(),
+        );
+        format!("{}\nsignature: {signature_base64}", self.to_sse_data())
+    }
+
+    /// [`Self::to_signed_sse_data`] plus a `prev_cursor:` line naming the user's previous
+    /// event matching the subscription, if any. Not covered by the signature, as it
+    /// depends on the subscription rather than the event.
+    pub(crate) fn to_stream_sse_data(
+        &self,
+        keypair: &Keypair,
+        prev: Option<EventCursor>,
+    ) -> String {
+        let data = self.to_signed_sse_data(keypair);
+        match prev {
+            Some(prev) => format!("{data}\nprev_cursor: {prev}"),
+            None => data,
+        }
+    }
 }
 
 impl FromRow<'_, PgRow> for EventEntity {
@@ -263,6 +263,38 @@ impl EventRepository {
         Ok(events)
     }
 
+    /// Get the cursor of the user's last event before `before` whose path matches
+    /// at least one of `allowed_paths` (an empty list applies no path restriction).
+    /// Returns `None` if the user has no such event.
+    /// The executor can either be db.pool() or a transaction.
+    pub async fn get_prev_cursor<'a>(
+        user_id: i32,
+        before: EventCursor,
+        allowed_paths: &[PathFilter],
+        executor: &mut UnifiedExecutor<'a>,
+    ) -> Result<Option<EventCursor>, sqlx::Error> {
+        let mut statement = Query::select()
+            .expr(Expr::col((EVENT_TABLE, EventIden::Id)).max())
+            .from(EVENT_TABLE)
+            .and_where(Expr::col((EVENT_TABLE, EventIden::User)).eq(user_id))
+            .and_where(Expr::col((EVENT_TABLE, EventIden::Id)).lt(before.id()))
+            .to_owned();
+
+        if !allowed_paths.is_empty() {
+            let mut path_condition = Condition::any();
+            for filter in allowed_paths {
+                path_condition = path_condition.add(filter.to_condition());
+            }
+            statement = statement.cond_where(path_condition).to_owned();
+        }
+
+        let (query, values) = statement.build_sqlx(PostgresQueryBuilder);
+        let con = executor.get_con().await?;
+        let row: PgRow = sqlx::query_with(&query, values).fetch_one(con).await?;
+        let prev_id: Option<i64> = row.try_get(0)?;
+        Ok(prev_id.map(|id| EventCursor::new(id as u64)))
+    }
+
     /// Get a list of events by the cursor.
     /// The limit is the maximum number of events to return.
     /// `visibility` selects which storage roots are returned (see [`EventVisibility`]).
@@ -736,6 +768,58 @@ mod tests {
         assert_eq!(got, vec!["/pub/b", "/pub/a"]);
     }
 
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn test_get_prev_cursor_skips_other_users_and_paths() {
+        let db = SqlDb::test().await;
+        let user_pubkey = Keypair::random().public_key();
+        let user = UserRepository::create(&user_pubkey, &mut db.pool().into())
+            .await
+            .unwrap();
+        let other_pubkey = Keypair::random().public_key();
+        let other = UserRepository::create(&other_pubkey, &mut db.pool().into())
+            .await
+            .unwrap();
+
+        // ids 1..=4
+        let events = [
+            (&user, &user_pubkey, "/pub/a"),   // 1
+            (&user, &user_pubkey, "/priv/x"),  // 2 outside the filter
+            (&other, &other_pubkey, "/pub/b"), // 3 other user
+            (&user, &user_pubkey, "/pub/c"),   // 4
+        ];
+        for (owner, pubkey, p) in events {
+            let path = EntryPath::new((*pubkey).clone(), WebDavPath::new(p).unwrap());
+            EventRepository::create(owner.id, EventType::Delete, &path, &mut db.pool().into())
+                .await
+                .unwrap();
+        }
+
+        let filters = vec![pf("/pub/")];
+        for (before, expected) in [(4, Some(1)), (1, None)] {
+            let prev = EventRepository::get_prev_cursor(
+                user.id,
+                EventCursor::new(before),
+                &filters,
+                &mut db.pool().into(),
+            )
+            .await
+            .unwrap();
+            assert_eq!(prev, expected.map(EventCursor::new));
+        }
+
+        // Without a path restriction the private event counts too.
+        let prev = EventRepository::get_prev_cursor(
+            user.id,
+            EventCursor::new(4),
+            &[],
+            &mut db.pool().into(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(prev, Some(EventCursor::new(2)));
+    }
+
     #[tokio::test]
     #[pubky_test_utils::test]
     async fn test_get_by_user_cursors_escapes_like_metacharacters() {
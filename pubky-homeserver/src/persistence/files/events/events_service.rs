@@ -4,6 +4,7 @@ use futures_util::Stream;
 use sqlx::PgPool;
 use tokio::sync::broadcast;
 
+use crate::constants::DEFAULT_MAX_LIST_LIMIT;
 use crate::observability::{ConnectionGuard, Metrics};
 use crate::persistence::{
     files::events::{
@@ -207,6 +208,18 @@ impl EventsService {
         EventRepository::get_by_user_cursors(user_cursors, reverse, allowed_paths, executor).await
     }
 
+    /// Get the cursor of the user's last event before `before` that matches
+    /// `allowed_paths`, the `prev_cursor` of an event on `/events-stream`.
+    pub async fn get_prev_cursor<'a>(
+        &self,
+        user_id: i32,
+        before: EventCursor,
+        allowed_paths: &[PathFilter],
+        executor: &mut UnifiedExecutor<'a>,
+    ) -> Result<Option<EventCursor>, sqlx::Error> {
+        EventRepository::get_prev_cursor(user_id, before, allowed_paths, executor).await
+    }
+
     /// Stream **all** events (the admin firehose): replay history over a single advancing global
     /// cursor, then — in `ForwardLive` mode — stay open on the broadcast channel. Yields domain
     /// [`EventEntity`]s for the caller to frame (e.g. SSE). Exposes private paths, so only
@@ -311,6 +324,52 @@ impl EventsService {
             }
         }
     }
+
+    /// Page through **all** events (public and private) with `from < cursor <= to`, oldest
+    /// first, one bounded DB query at a time so the whole history is never held in memory.
+    /// Ends after the last matching event, or with the error of a failed query. Backs the
+    /// admin events export; exposes private paths.
+    pub(crate) fn export_stream(
+        &self,
+        sql_db: SqlDb,
+        from: Option<EventCursor>,
+        to: Option<EventCursor>,
+    ) -> impl Stream<Item = Result<EventEntity, sqlx::Error>> {
+        let service = self.clone();
+
+        async_stream::stream! {
+            let mut last_cursor = from;
+            loop {
+                let events = match service
+                    .get_all_events(
+                        last_cursor,
+                        Some(DEFAULT_MAX_LIST_LIMIT),
+                        false,
+                        &[],
+                        None,
+                        &mut sql_db.pool().into(),
+                    )
+                    .await
+                {
+                    Ok(events) => events,
+                    Err(e) => {
+                        yield Err(e);
+                        return;
+                    }
+                };
+                if events.is_empty() {
+                    return;
+                }
+                for event in events {
+                    if to.is_some_and(|to| event.cursor() > to) {
+                        return;
+                    }
+                    last_cursor = Some(event.cursor());
+                    yield Ok(event);
+                }
+            }
+        }
+    }
 }
 
 /// Whether a live broadcast event belongs in an all-events stream: not already sent (cursor dedup),
@@ -543,4 +602,45 @@ mod tests {
             .unwrap();
         assert_eq!(events.len(), 5);
     }
+
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn test_events_service_export_stream_range() {
+        use futures_util::StreamExt;
+
+        let db = SqlDb::test().await;
+        let events_service = EventsService::new(100);
+
+        let user_pubkey = Keypair::random().public_key();
+        let user = UserRepository::create(&user_pubkey, &mut db.pool().into())
+            .await
+            .unwrap();
+
+        // ids 1=/pub/a, 2=/priv/x, 3=/pub/b, 4=/priv/y, 5=/pub/c.
+        let paths = ["/pub/a", "/priv/x", "/pub/b", "/priv/y", "/pub/c"];
+        for p in paths {
+            let path = EntryPath::new(user_pubkey.clone(), WebDavPath::new(p).unwrap());
+            events_service
+                .create_event(user.id, EventType::Delete, &path, &mut db.pool().into())
+                .await
+                .unwrap();
+        }
+
+        let export = |from, to| {
+            events_service
+                .export_stream(db.clone(), from, to)
+                .map(|event| event.unwrap().id)
+                .collect::<Vec<_>>()
+        };
+
+        // Unbounded: the whole history, private events included.
+        assert_eq!(export(None, None).await, vec![1, 2, 3, 4, 5]);
+        // `from` is exclusive, `to` inclusive.
+        assert_eq!(
+            export(Some(EventCursor::new(1)), Some(EventCursor::new(4))).await,
+            vec![2, 3, 4]
+        );
+        // Resuming past the end yields nothing.
+        assert!(export(Some(EventCursor::new(5)), None).await.is_empty());
+    }
 }
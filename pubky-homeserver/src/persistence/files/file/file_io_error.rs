@@ -49,4 +49,7 @@ pub enum WriteStreamError {
     Axum(#[from] axum::Error),
     #[error("Other error: {0}")]
     Other(#[from] anyhow::Error),
+    /// The content breaks the homeserver's content policy.
+    #[error("{0}")]
+    UnsupportedMediaType(String),
 }
@@ -42,6 +42,16 @@ impl FileMetadataBuilder {
         self.path_content_type = Some(content_type);
     }
 
+    /// The content type a file at `path` starting with `first_chunk` is stored with.
+    pub fn detect_content_type(path: &str, first_chunk: &[u8]) -> String {
+        let mut builder = Self::default();
+        builder.guess_mime_type_from_path(path);
+        if let Some(ctype) = infer::get(first_chunk) {
+            builder.magic_bytes_content_type = Some(ctype.mime_type().to_string());
+        }
+        builder.derived_content_type()
+    }
+
     /// Derives the content type from the magic bytes or the path.
     /// If both methods detect a type, the magic bytes method takes precedence.
     /// Defaults to application/octet-stream if no type is detected.
@@ -2,13 +2,17 @@
 use crate::AppContext;
 use crate::{
     persistence::{
-        files::events::EventsService,
+        files::events::{EventRepository, EventType, EventsService},
         sql::{
+            blob::BlobRepository,
             entry::{EntryEntity, EntryRepository},
+            uexecutor,
+            user::{UserEntity, UserRepository},
             SqlDb, UnifiedExecutor,
         },
     },
-    shared::webdav::EntryPath,
+    services::user_service::FILE_METADATA_SIZE,
+    shared::webdav::{EntryPath, WebDavPath},
     ConfigToml,
 };
 use bytes::Bytes;
@@ -17,9 +21,73 @@ use futures_util::Stream;
 use futures_util::StreamExt;
 #[cfg(test)]
 use opendal::Buffer;
+use pubky_common::crypto::PublicKey;
+use sqlx::types::chrono::{DateTime, NaiveDateTime};
+use sqlx::{Postgres, Transaction};
+use std::collections::HashMap;
 use std::path::Path;
 
-use super::super::{FileIoError, FileStream, OpendalService, WriteStreamError};
+use super::super::{FileIoError, FileStream, OpendalService, ReceivedUpload, WriteStreamError};
+
+/// Metadata set by a single write, see [`FileService::write_stream_with_metadata`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WriteMetadata {
+    /// Expiry deadline (UTC) of the entry.
+    pub expires_at: Option<NaiveDateTime>,
+    /// Filename to download the entry as.
+    pub filename: Option<String>,
+}
+
+/// OpenDAL user metadata key of [`WriteMetadata::expires_at`], in microseconds since the epoch.
+const EXPIRES_AT_KEY: &str = "pubky-expires-at";
+/// OpenDAL user metadata key of [`WriteMetadata::filename`].
+const FILENAME_KEY: &str = "pubky-filename";
+
+impl WriteMetadata {
+    /// Encode as OpenDAL user metadata, which carries it through the storage layers to
+    /// the entry layer.
+    pub(crate) fn to_user_metadata(&self) -> HashMap<String, String> {
+        let mut user_metadata = HashMap::new();
+        if let Some(expires_at) = self.expires_at {
+            let micros = expires_at.and_utc().timestamp_micros();
+            user_metadata.insert(EXPIRES_AT_KEY.to_string(), micros.to_string());
+        }
+        if let Some(filename) = &self.filename {
+            user_metadata.insert(FILENAME_KEY.to_string(), filename.clone());
+        }
+        user_metadata
+    }
+
+    /// Decode from the OpenDAL user metadata of a write, see [`Self::to_user_metadata`].
+    pub(crate) fn from_user_metadata(user_metadata: &HashMap<String, String>) -> Self {
+        Self {
+            expires_at: user_metadata
+                .get(EXPIRES_AT_KEY)
+                .and_then(|micros| micros.parse().ok())
+                .and_then(DateTime::from_timestamp_micros)
+                .map(|expires_at| expires_at.naive_utc()),
+            filename: user_metadata.get(FILENAME_KEY).cloned(),
+        }
+    }
+}
+
+/// A file locked against other locked writes, see [`FileService::lock_path`].
+///
+/// Other writes to the same file wait until it is passed to
+/// [`FileService::write_locked`] or [`FileService::finish_append`], or dropped.
+pub struct PathLock {
+    tx: Transaction<'static, Postgres>,
+    path: EntryPath,
+    /// The file when it was locked, `None` if it did not exist or expired.
+    entry: Option<EntryEntity>,
+}
+
+impl PathLock {
+    /// Length of the file when it was locked, 0 if it did not exist.
+    pub fn length(&self) -> u64 {
+        self.entry.as_ref().map_or(0, |entry| entry.content_length)
+    }
+}
 
 /// The file service creates an abstraction layer over the SqlDb and OpenDAL services.
 /// This way, files can be managed in a unified way.
@@ -55,12 +123,14 @@ impl FileService {
     }
 
     /// Get the metadata of a file.
+    /// Expired entries are reported as not found.
     pub async fn get_info(
         &self,
         path: &EntryPath,
         executor: &mut UnifiedExecutor<'_>,
     ) -> Result<EntryEntity, FileIoError> {
         match EntryRepository::get_by_path(path, executor).await {
+            Ok(entry) if entry.is_expired() => Err(FileIoError::NotFound),
             Ok(entry) => Ok(entry),
             Err(sqlx::Error::RowNotFound) => Err(FileIoError::NotFound),
             Err(e) => Err(e.into()),
@@ -76,12 +146,20 @@ impl FileService {
     }
 
     /// Write a file to the database and storage depending on the selected target location.
-    pub async fn write_stream(
+    /// `metadata` replaces the per-write metadata of the file it overwrites, so fields left
+    /// `None` are cleared: the entry never expires and has no download filename. It is
+    /// stored in the same transaction as the new content, so the file is never visible
+    /// with the metadata of the content it replaced.
+    /// After an expiry deadline the entry reads as not found until the
+    /// [`ExpiredEntriesSweeper`](crate::services::expired_entries_sweeper::ExpiredEntriesSweeper)
+    /// deletes it.
+    pub async fn write_stream_with_metadata(
         &self,
         path: &EntryPath,
         stream: impl Stream<Item = Result<Bytes, WriteStreamError>> + Unpin + Send,
+        metadata: WriteMetadata,
     ) -> Result<EntryEntity, FileIoError> {
-        self.opendal.write_stream(path, stream).await?;
+        self.opendal.write_stream(path, stream, &metadata).await?;
         match EntryRepository::get_by_path(path, &mut self.db.pool().into()).await {
             Ok(entry) => Ok(entry),
             Err(sqlx::Error::RowNotFound) => Err(FileIoError::NotFound),
@@ -89,6 +167,108 @@ impl FileService {
         }
     }
 
+    /// Bump the modification time of an existing file without rewriting its content.
+    ///
+    /// Emits a `PUT` event with the unchanged content hash, so event subscribers and
+    /// `modified_since` listings see the file as written again. Missing and expired
+    /// files are reported as not found; touching never creates a file.
+    pub async fn touch(&self, path: &EntryPath) -> Result<EntryEntity, FileIoError> {
+        let mut tx = self.db.pool().begin().await?;
+        let entry = match EntryRepository::get_by_path(path, uexecutor!(tx)).await {
+            Ok(entry) if entry.is_expired() => return Err(FileIoError::NotFound),
+            Ok(entry) => entry,
+            Err(sqlx::Error::RowNotFound) => return Err(FileIoError::NotFound),
+            Err(e) => return Err(e.into()),
+        };
+        EntryRepository::update(&entry, uexecutor!(tx)).await?;
+        EventRepository::create(
+            entry.user_id,
+            EventType::Put {
+                content_hash: entry.content_hash,
+            },
+            path,
+            uexecutor!(tx),
+        )
+        .await?;
+        let entry = EntryRepository::get(entry.id, uexecutor!(tx)).await?;
+        tx.commit().await?;
+
+        EventsService::notify_event(self.db.pool()).await;
+        Ok(entry)
+    }
+
+    /// Lock the file at `path` against other locked writes.
+    ///
+    /// `PUT`s and appends to the same path are serialized by a Postgres advisory lock,
+    /// held until the returned [`PathLock`] is written or dropped, so an append can check
+    /// [`PathLock::length`] and rewrite the file without racing other writers. The lock
+    /// is held on a connection of [`SqlDb::lock_pool`], as the write itself needs
+    /// connections of the main pool. Receive the uploaded content before locking, e.g.
+    /// with [`ReceivedUpload::receive`], so the lock is never held while waiting for a
+    /// client.
+    pub async fn lock_path(&self, path: &EntryPath) -> Result<PathLock, FileIoError> {
+        let mut tx = self.db.lock_pool().begin().await?;
+        EntryRepository::lock_path(path, uexecutor!(tx)).await?;
+        let entry = match self.get_info(path, &mut self.db.pool().into()).await {
+            Ok(entry) => Some(entry),
+            Err(FileIoError::NotFound) => None,
+            Err(e) => return Err(e),
+        };
+        Ok(PathLock {
+            tx,
+            path: path.clone(),
+            entry,
+        })
+    }
+
+    /// Write the file locked by `lock` like [`Self::write_stream_with_metadata`] and
+    /// release the lock.
+    pub async fn write_locked(
+        &self,
+        lock: PathLock,
+        stream: impl Stream<Item = Result<Bytes, WriteStreamError>> + Unpin + Send,
+        metadata: WriteMetadata,
+    ) -> Result<EntryEntity, FileIoError> {
+        let entry = self
+            .write_stream_with_metadata(&lock.path, stream, metadata)
+            .await?;
+        lock.tx.commit().await?;
+        Ok(entry)
+    }
+
+    /// Stream the content the file locked by `lock` had when it was locked, nothing if
+    /// it did not exist.
+    pub async fn get_locked_stream(&self, lock: &PathLock) -> Result<FileStream, FileIoError> {
+        match lock.entry {
+            Some(_) => self.get_stream(&lock.path).await,
+            None => Ok(Box::new(futures_util::stream::empty())),
+        }
+    }
+
+    /// Replace the file locked by `lock` with `stream`, which must start with the
+    /// content of [`Self::get_locked_stream`], and release the lock.
+    ///
+    /// The new content is received into a temporary file first, as storage may rewrite
+    /// the file in place while it is still being read. The file keeps its expiry
+    /// deadline and download filename.
+    pub async fn finish_append(
+        &self,
+        lock: PathLock,
+        stream: impl Stream<Item = Result<Bytes, WriteStreamError>> + Unpin + Send,
+    ) -> Result<EntryEntity, FileIoError> {
+        let content = ReceivedUpload::receive(stream, None).await?;
+        let metadata = lock
+            .entry
+            .as_ref()
+            .map(|entry| WriteMetadata {
+                expires_at: entry.expires_at,
+                filename: entry.filename.clone(),
+            })
+            .unwrap_or_default();
+        self.write_locked(lock, content.into_stream(), metadata)
+            .await
+    }
+
     /// Delete a file.
     pub async fn delete(&self, path: &EntryPath) -> Result<(), FileIoError> {
         if !self.opendal.exists(path).await? {
@@ -98,6 +278,92 @@ impl FileService {
         Ok(())
     }
 
+    /// Delete every file below the directory `path` in a single transaction.
+    ///
+    /// Entries, blob references, the user's storage usage and the `DEL` events
+    /// are all updated atomically, so a failure leaves the directory untouched.
+    /// Content no longer referenced by any path is removed from storage after
+    /// the commit.
+    /// Returns the number of deleted files.
+    pub async fn delete_dir(&self, path: &EntryPath) -> Result<u64, FileIoError> {
+        let mut tx = self.db.pool().begin().await?;
+        let user = match UserRepository::get_for_update(path.pubkey(), uexecutor!(tx)).await {
+            Ok(user) => user,
+            Err(sqlx::Error::RowNotFound) => return Err(FileIoError::NotFound),
+            Err(e) => return Err(e.into()),
+        };
+        // Ordered by path, so concurrent deletes take the blob locks in the same order.
+        let deleted = EntryRepository::delete_dir(user.id, path.path(), uexecutor!(tx)).await?;
+        if deleted.is_empty() {
+            return Ok(0);
+        }
+        let unreferenced = Self::release_deleted(user, path.pubkey(), &deleted, &mut tx).await?;
+        tx.commit().await?;
+
+        EventsService::notify_event(self.db.pool()).await;
+        self.opendal.delete_unreferenced(unreferenced).await;
+        Ok(deleted.len() as u64)
+    }
+
+    /// Delete `entry`, listed by [`EntryRepository::list_expired`], if it is still expired.
+    ///
+    /// Like [`Self::delete_dir`], the entry, its blob reference, the user's storage usage
+    /// and the `DEL` event are updated in a single transaction, which first checks that
+    /// the entry still has the listed, expiring version. An entry rewritten since it was
+    /// listed is kept, so its new content is not released. Returns whether the entry was
+    /// deleted.
+    pub async fn delete_expired(&self, entry: &EntryEntity) -> Result<bool, FileIoError> {
+        let mut tx = self.db.pool().begin().await?;
+        let user = match UserRepository::get_for_update(entry.path.pubkey(), uexecutor!(tx)).await {
+            Ok(user) => user,
+            Err(sqlx::Error::RowNotFound) => return Ok(false),
+            Err(e) => return Err(e.into()),
+        };
+        let Some(deleted) = EntryRepository::delete_expired(entry, uexecutor!(tx)).await? else {
+            return Ok(false);
+        };
+        let unreferenced =
+            Self::release_deleted(user, entry.path.pubkey(), &[deleted], &mut tx).await?;
+        tx.commit().await?;
+
+        EventsService::notify_event(self.db.pool()).await;
+        self.opendal.delete_unreferenced(unreferenced).await;
+        Ok(true)
+    }
+
+    /// Release the content of the `deleted` files of `user`, emit their `DEL` events and
+    /// subtract them from the user's storage usage, all within `tx`.
+    ///
+    /// Returns the storage keys no path references anymore, to be removed after the commit.
+    async fn release_deleted(
+        mut user: UserEntity,
+        pubkey: &PublicKey,
+        deleted: &[(WebDavPath, u64)],
+        tx: &mut Transaction<'static, Postgres>,
+    ) -> Result<Vec<String>, FileIoError> {
+        let mut unreferenced = Vec::new();
+        let mut deleted_bytes = 0;
+        for (file_path, content_length) in deleted {
+            let file_path = EntryPath::new(pubkey.clone(), file_path.clone());
+            BlobRepository::lock_path(file_path.as_str(), uexecutor!(*tx)).await?;
+            let hash = BlobRepository::remove_ref(file_path.as_str(), uexecutor!(*tx)).await?;
+            match hash {
+                Some(hash) => {
+                    unreferenced.extend(BlobRepository::release(&hash, uexecutor!(*tx)).await?)
+                }
+                // Stored at its own path, deduplicated or not.
+                None => unreferenced.push(file_path.as_str().to_string()),
+            }
+            EventRepository::create(user.id, EventType::Delete, &file_path, uexecutor!(*tx))
+                .await?;
+            deleted_bytes += content_length + FILE_METADATA_SIZE;
+        }
+
+        user.used_bytes = user.used_bytes.saturating_sub(deleted_bytes);
+        UserRepository::update(&user, uexecutor!(*tx)).await?;
+        Ok(unreferenced)
+    }
+
     /// Delete a file bypassing write-path restrictions.
     /// Used by the admin `/webdav` REST delete route; the `/dav` WebDAV handler
     /// already uses `admin_operator` directly and does not need this.
@@ -131,6 +397,17 @@ impl FileService {
         Ok(Bytes::from(collected_data))
     }
 
+    /// Write a file to the database and storage depending on the selected target location.
+    /// The written entry has no per-write metadata, even if the file it replaces did.
+    pub async fn write_stream(
+        &self,
+        path: &EntryPath,
+        stream: impl Stream<Item = Result<Bytes, WriteStreamError>> + Unpin + Send,
+    ) -> Result<EntryEntity, FileIoError> {
+        self.write_stream_with_metadata(path, stream, WriteMetadata::default())
+            .await
+    }
+
     /// Write a file to the database and storage depending on the selected target location.
     pub async fn write(&self, path: &EntryPath, data: Buffer) -> Result<EntryEntity, FileIoError> {
         let stream = futures_util::stream::iter(vec![Ok(Bytes::from(data.to_vec()))]);
@@ -518,4 +795,298 @@ mod tests {
             test_data.len() as u64 + FILE_METADATA_SIZE
         );
     }
+
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn test_delete_dir() {
+        let context = AppContext::test().await;
+        let file_service = FileService::new_from_context(&context).unwrap();
+        let db = context.sql_db.clone();
+
+        let pubkey = pubky_common::crypto::Keypair::random().public_key();
+        UserRepository::create(&pubkey, &mut db.pool().into())
+            .await
+            .unwrap();
+        let entry_path =
+            |path: &str| EntryPath::new(pubkey.clone(), WebDavPath::new(path).unwrap());
+        for path in ["/pub/dir/a.txt", "/pub/dir/sub/b.txt", "/pub/dir2/c.txt"] {
+            file_service
+                .write(&entry_path(path), Buffer::from(vec![1u8; 100]))
+                .await
+                .unwrap();
+        }
+
+        let deleted = file_service
+            .delete_dir(&entry_path("/pub/dir/"))
+            .await
+            .unwrap();
+        assert_eq!(deleted, 2);
+        for path in ["/pub/dir/a.txt", "/pub/dir/sub/b.txt"] {
+            assert!(matches!(
+                file_service.get(&entry_path(path)).await,
+                Err(FileIoError::NotFound)
+            ));
+        }
+        assert_eq!(
+            file_service
+                .get(&entry_path("/pub/dir2/c.txt"))
+                .await
+                .unwrap()
+                .as_ref(),
+            &[1u8; 100][..]
+        );
+
+        let user = UserRepository::get(&pubkey, &mut db.pool().into())
+            .await
+            .unwrap();
+        assert_eq!(user.used_bytes, 100 + FILE_METADATA_SIZE);
+        let delete_events: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM events WHERE type = 'DEL'")
+                .fetch_one(db.pool())
+                .await
+                .unwrap();
+        assert_eq!(delete_events, 2);
+
+        // Deleting an empty directory is not an error.
+        let deleted = file_service
+            .delete_dir(&entry_path("/pub/dir/"))
+            .await
+            .unwrap();
+        assert_eq!(deleted, 0);
+    }
+
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn test_touch() {
+        let context = AppContext::test().await;
+        let file_service = FileService::new_from_context(&context).unwrap();
+        let db = context.sql_db.clone();
+
+        let pubkey = pubky_common::crypto::Keypair::random().public_key();
+        let user = UserRepository::create(&pubkey, &mut db.pool().into())
+            .await
+            .unwrap();
+        let path = EntryPath::new(pubkey.clone(), WebDavPath::new("/pub/alive").unwrap());
+        let written = file_service
+            .write(&path, Buffer::from(vec![1u8; 10]))
+            .await
+            .unwrap();
+
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        let touched = file_service.touch(&path).await.unwrap();
+        assert!(touched.modified_at > written.modified_at);
+        assert_eq!(touched.content_hash, written.content_hash);
+        assert_eq!(touched.created_at, written.created_at);
+        assert_eq!(file_service.get(&path).await.unwrap().as_ref(), &[1u8; 10]);
+
+        // Only the storage usage of the original write is accounted.
+        let used_bytes = UserRepository::get(&pubkey, &mut db.pool().into())
+            .await
+            .unwrap()
+            .used_bytes;
+        assert_eq!(used_bytes, 10 + FILE_METADATA_SIZE);
+        let put_events: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM events WHERE type = 'PUT' AND \"user\" = $1")
+                .bind(user.id)
+                .fetch_one(db.pool())
+                .await
+                .unwrap();
+        assert_eq!(put_events, 2);
+
+        let missing = EntryPath::new(pubkey, WebDavPath::new("/pub/missing").unwrap());
+        assert!(matches!(
+            file_service.touch(&missing).await,
+            Err(FileIoError::NotFound)
+        ));
+    }
+
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn test_write_metadata_is_stored_with_the_entry() {
+        let context = AppContext::test().await;
+        let file_service = FileService::new_from_context(&context).unwrap();
+        let db = context.sql_db.clone();
+
+        let pubkey = pubky_common::crypto::Keypair::random().public_key();
+        UserRepository::create(&pubkey, &mut db.pool().into())
+            .await
+            .unwrap();
+        let path = EntryPath::new(pubkey, WebDavPath::new("/pub/report.pdf").unwrap());
+        let content = || futures_util::stream::iter([Ok(Bytes::from_static(b"%PDF"))]);
+
+        let expires_at = DateTime::from_timestamp_micros(4_102_444_800_123_456)
+            .unwrap()
+            .naive_utc();
+        let metadata = WriteMetadata {
+            expires_at: Some(expires_at),
+            filename: Some("report.pdf".to_string()),
+        };
+        assert_eq!(
+            WriteMetadata::from_user_metadata(&metadata.to_user_metadata()),
+            metadata
+        );
+        let entry = file_service
+            .write_stream_with_metadata(&path, content(), metadata.clone())
+            .await
+            .unwrap();
+        assert_eq!(entry.expires_at, Some(expires_at));
+        assert_eq!(entry.filename.as_deref(), Some("report.pdf"));
+
+        // A write without metadata clears it.
+        let entry = file_service
+            .write_stream_with_metadata(&path, content(), WriteMetadata::default())
+            .await
+            .unwrap();
+        assert_eq!(entry.expires_at, None);
+        assert_eq!(entry.filename, None);
+    }
+
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn test_append() {
+        let context = AppContext::test().await;
+        let file_service = FileService::new_from_context(&context).unwrap();
+        let db = context.sql_db.clone();
+
+        let pubkey = pubky_common::crypto::Keypair::random().public_key();
+        UserRepository::create(&pubkey, &mut db.pool().into())
+            .await
+            .unwrap();
+        let path = EntryPath::new(pubkey.clone(), WebDavPath::new("/pub/log").unwrap());
+        let append = |lock: PathLock, record: &'static [u8]| {
+            let file_service = file_service.clone();
+            async move {
+                let existing = file_service.get_locked_stream(&lock).await.unwrap();
+                let content = existing
+                    .map(|chunk| chunk.map_err(|e| WriteStreamError::Other(e.into())))
+                    .chain(futures_util::stream::iter([Ok(Bytes::from_static(record))]));
+                file_service.finish_append(lock, content).await
+            }
+        };
+
+        // A missing file is empty, and appending to it creates it.
+        let lock = file_service.lock_path(&path).await.unwrap();
+        assert_eq!(lock.length(), 0);
+        append(lock, b"one\n").await.unwrap();
+
+        // A second append waits for the lock of the first.
+        let first = file_service.lock_path(&path).await.unwrap();
+        let second = tokio::spawn({
+            let file_service = file_service.clone();
+            let path = path.clone();
+            async move { file_service.lock_path(&path).await.unwrap().length() }
+        });
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        assert!(!second.is_finished());
+        let entry = append(first, b"two\n").await.unwrap();
+        assert_eq!(entry.content_length, 8);
+        assert_eq!(second.await.unwrap(), 8);
+        assert_eq!(
+            file_service.get(&path).await.unwrap().as_ref(),
+            b"one\ntwo\n"
+        );
+
+        // A locked PUT waits for the lock too.
+        let first = file_service.lock_path(&path).await.unwrap();
+        let put = tokio::spawn({
+            let file_service = file_service.clone();
+            let path = path.clone();
+            async move {
+                let lock = file_service.lock_path(&path).await.unwrap();
+                let content = futures_util::stream::iter([Ok(Bytes::from_static(b"new\n"))]);
+                file_service
+                    .write_locked(lock, content, WriteMetadata::default())
+                    .await
+                    .unwrap()
+            }
+        });
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        assert!(!put.is_finished());
+        append(first, b"three\n").await.unwrap();
+        assert_eq!(put.await.unwrap().content_length, 4);
+        assert_eq!(file_service.get(&path).await.unwrap().as_ref(), b"new\n");
+    }
+
+    /// Locks never take the connections their writes need.
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn test_locked_writes_beyond_pool_size() {
+        let context = AppContext::test().await;
+        let file_service = FileService::new_from_context(&context).unwrap();
+        let db = context.sql_db.clone();
+
+        let pubkey = pubky_common::crypto::Keypair::random().public_key();
+        UserRepository::create(&pubkey, &mut db.pool().into())
+            .await
+            .unwrap();
+        let mut locks = Vec::new();
+        for i in 0..db.pool().options().get_max_connections() {
+            let path = WebDavPath::new(&format!("/pub/file{i}")).unwrap();
+            let lock = file_service
+                .lock_path(&EntryPath::new(pubkey.clone(), path))
+                .await
+                .unwrap();
+            locks.push(lock);
+        }
+        let writes = locks.into_iter().map(|lock| {
+            let content = futures_util::stream::iter([Ok(Bytes::from_static(b"hello"))]);
+            file_service.write_locked(lock, content, WriteMetadata::default())
+        });
+        let written = tokio::time::timeout(
+            std::time::Duration::from_secs(10),
+            futures_util::future::join_all(writes),
+        )
+        .await
+        .expect("Writes should not wait for connections held by locks");
+        assert!(written.iter().all(Result::is_ok));
+    }
+
+    /// Deleting a directory only removes the blobs no other path references anymore.
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn test_delete_dir_keeps_shared_blobs() {
+        let mut context = AppContext::test().await;
+        context.config_toml.storage.deduplicate_blobs = true;
+        let file_service = FileService::new_from_context(&context).unwrap();
+        let db = context.sql_db.clone();
+
+        let mut dirs = Vec::new();
+        for _ in 0..2 {
+            let pubkey = pubky_common::crypto::Keypair::random().public_key();
+            UserRepository::create(&pubkey, &mut db.pool().into())
+                .await
+                .unwrap();
+            let file = EntryPath::new(
+                pubkey.clone(),
+                WebDavPath::new("/pub/dir/same.txt").unwrap(),
+            );
+            file_service
+                .write(&file, Buffer::from(vec![7u8; 1024]))
+                .await
+                .unwrap();
+            let dir = EntryPath::new(pubkey, WebDavPath::new("/pub/dir/").unwrap());
+            dirs.push((dir, file));
+        }
+        let blob_count = || async {
+            sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM blobs")
+                .fetch_one(db.pool())
+                .await
+                .unwrap()
+        };
+        assert_eq!(blob_count().await, 1);
+
+        assert_eq!(file_service.delete_dir(&dirs[0].0).await.unwrap(), 1);
+        assert_eq!(blob_count().await, 1);
+        assert_eq!(
+            file_service.get(&dirs[1].1).await.unwrap().as_ref(),
+            &[7u8; 1024][..]
+        );
+
+        assert_eq!(file_service.delete_dir(&dirs[1].0).await.unwrap(), 1);
+        assert_eq!(blob_count().await, 0);
+        assert!(matches!(
+            file_service.get(&dirs[1].1).await,
+            Err(FileIoError::NotFound)
+        ));
+    }
 }
@@ -8,3 +8,4 @@ pub mod file_io_error;
 pub mod file_metadata;
 pub mod file_service;
 pub mod file_stream_type;
+pub mod received_upload;
@@ -0,0 +1,79 @@
+use bytes::Bytes;
+use futures_util::{Stream, StreamExt};
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+use tokio_util::io::ReaderStream;
+
+use super::file_io_error::{FileIoError, WriteStreamError};
+
+/// Content received into an anonymous temporary file, so it can be written to storage
+/// without waiting for the client.
+///
+/// The file is removed by the OS once it is dropped.
+pub struct ReceivedUpload {
+    file: tokio::fs::File,
+    length: u64,
+}
+
+impl ReceivedUpload {
+    /// Receive `stream` in full, failing with the first error it yields.
+    ///
+    /// Fails with [`FileIoError::DiskSpaceQuotaExceeded`] as soon as more than
+    /// `max_length` bytes arrive, so content over the user's quota is not spooled.
+    pub async fn receive(
+        mut stream: impl Stream<Item = Result<Bytes, WriteStreamError>> + Unpin + Send,
+        max_length: Option<u64>,
+    ) -> Result<Self, FileIoError> {
+        let file = tokio::task::spawn_blocking(tempfile::tempfile)
+            .await
+            .map_err(std::io::Error::other)??;
+        let mut file = tokio::fs::File::from_std(file);
+        let mut length = 0;
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk?;
+            length += chunk.len() as u64;
+            if max_length.is_some_and(|max| length > max) {
+                return Err(FileIoError::DiskSpaceQuotaExceeded);
+            }
+            file.write_all(&chunk).await?;
+        }
+        file.flush().await?;
+        file.rewind().await?;
+        Ok(Self { file, length })
+    }
+
+    /// Length of the received content in bytes.
+    pub fn length(&self) -> u64 {
+        self.length
+    }
+
+    /// The file holding the received content, positioned at its start.
+    pub fn into_file(self) -> tokio::fs::File {
+        self.file
+    }
+
+    /// Read the received content back.
+    pub fn into_stream(self) -> impl Stream<Item = Result<Bytes, WriteStreamError>> + Unpin + Send {
+        ReaderStream::new(self.file)
+            .map(|chunk| chunk.map_err(|e| WriteStreamError::Other(e.into())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_receive_stops_at_max_length() {
+        let content = || {
+            futures_util::stream::iter([
+                Ok(Bytes::from_static(b"hello ")),
+                Ok(Bytes::from_static(b"world")),
+            ])
+        };
+        let received = ReceivedUpload::receive(content(), Some(11)).await.unwrap();
+        assert_eq!(received.length(), 11);
+
+        let err = ReceivedUpload::receive(content(), Some(10)).await.err();
+        assert!(matches!(err, Some(FileIoError::DiskSpaceQuotaExceeded)));
+    }
+}
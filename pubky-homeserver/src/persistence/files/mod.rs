@@ -8,7 +8,9 @@
 //! 3. **[`events`]** — creates event records (PUT/DEL) after inner layers complete on close.
 //! 4. **[`entry`]** — updates file metadata (blake3 hash, size, MIME type) in Postgres.
 //! 5. **[`user_quota_layer`]** — enforces per-user storage quotas.
-//! 6. **OpenDAL base** — physical storage I/O.
+//! 6. **[`blob_dedup_layer`]** — stores identical content once (only if `[storage].deduplicate_blobs` is set).
+//! 7. **OpenDAL base** — physical storage I/O, routed across [`sharded_storage`] directories
+//!    if `[storage].shards` is set.
 //!
 //! [`file`] provides the high-level [`FileService`](file::file_service::FileService)
 //! used by route handlers.
@@ -18,13 +20,16 @@ mod file;
 mod layer_domain_error;
 mod opendal;
 
+pub(crate) mod blob_dedup_layer;
 pub(crate) mod events;
 pub(crate) mod path_collision_layer;
+pub mod sharded_storage;
 pub(crate) mod user_quota_layer;
 pub(crate) mod write_path_layer;
 
 pub use file::file_io_error::{FileIoError, WriteStreamError};
 pub(crate) use file::file_metadata::{FileMetadata, FileMetadataBuilder};
-pub use file::file_service::FileService;
+pub use file::file_service::{FileService, WriteMetadata};
 pub use file::file_stream_type::FileStream;
+pub use file::received_upload::ReceivedUpload;
 pub use opendal::opendal_service::OpendalService;
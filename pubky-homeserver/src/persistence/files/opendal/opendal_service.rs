@@ -5,9 +5,11 @@ use crate::AppContext;
 use crate::{
     persistence::{
         files::{
+            blob_dedup_layer::BlobDedupLayer,
             entry::entry_layer::EntryLayer,
             events::{EventsLayer, EventsService},
             path_collision_layer::PathCollisionLayer,
+            sharded_storage::build_sharded_operator,
             user_quota_layer::UserQuotaLayer,
             write_path_layer::WritePathLayer,
         },
@@ -23,12 +25,15 @@ use futures_util::{stream::StreamExt, Stream};
 use opendal::Buffer;
 use opendal::Operator;
 
-use super::super::{FileIoError, FileMetadata, FileMetadataBuilder, FileStream, WriteStreamError};
+use super::super::{
+    FileIoError, FileMetadata, FileMetadataBuilder, FileStream, WriteMetadata, WriteStreamError,
+};
 
-/// Build the base storage operator (with quota, entry, and events layers)
-/// and a second operator that additionally includes the `WritePathLayer`.
+/// Build the base storage operator (with quota, entry, and events layers),
+/// a second operator that additionally includes the `WritePathLayer`,
+/// and the raw storage backend without any layer.
 ///
-/// Both operators share the same underlying storage backend, which is
+/// All operators share the same underlying storage backend, which is
 /// important for backends like `InMemory` where separate instances would
 /// have independent data.
 pub fn build_storage_operators(
@@ -37,7 +42,7 @@ pub fn build_storage_operators(
     db: &SqlDb,
     events_service: EventsService,
     user_service: UserService,
-) -> Result<(Operator, Operator), FileIoError> {
+) -> Result<(Operator, Operator, Operator), FileIoError> {
     let user_quota_layer =
         UserQuotaLayer::new(user_service.clone(), storage_config.default_quota_mb);
     let entry_layer = EntryLayer::new(db.clone());
@@ -47,7 +52,19 @@ pub fn build_storage_operators(
     // then path_collision_layer rejects file/folder collisions
     // before they reach storage. events_layer runs after entry_layer.close()
     // completes, guaranteeing the file is written before the Event is created.
-    let admin_operator = match &storage_config.backend {
+    if !storage_config.shards.is_empty() && storage_config.backend != StorageConfigToml::FileSystem
+    {
+        return Err(FileIoError::OpenDAL(opendal::Error::new(
+            opendal::ErrorKind::ConfigInvalid,
+            "[storage].shards is only supported by the file_system storage",
+        )));
+    }
+    let base_operator = match &storage_config.backend {
+        StorageConfigToml::FileSystem if !storage_config.shards.is_empty() => {
+            let shard_dirs = storage_config.shard_dirs(data_directory);
+            tracing::info!("Store files across {} shards", shard_dirs.len());
+            build_sharded_operator(&shard_dirs)?
+        }
         StorageConfigToml::FileSystem => {
             let files_dir = match data_directory.join("data/files").to_str() {
                 Some(path) => path.to_string(),
@@ -59,11 +76,7 @@ pub fn build_storage_operators(
                 }
             };
             let builder = opendal::services::Fs::default().root(files_dir.as_str());
-            opendal::Operator::new(builder)?
-                .layer(user_quota_layer)
-                .layer(entry_layer)
-                .layer(events_layer)
-                .finish()
+            opendal::Operator::new(builder)?.finish()
         }
         #[cfg(feature = "storage-gcs")]
         StorageConfigToml::GoogleBucket(config) => {
@@ -72,36 +85,39 @@ pub fn build_storage_operators(
                 config.bucket_name
             );
             let builder = config.to_builder()?;
-            opendal::Operator::new(builder)?
-                .layer(user_quota_layer)
-                .layer(entry_layer)
-                .layer(events_layer)
-                .finish()
+            opendal::Operator::new(builder)?.finish()
         }
         #[cfg(any(feature = "storage-memory", test))]
         StorageConfigToml::InMemory => {
             tracing::info!("Store files in memory");
             let builder = opendal::services::Memory::default();
-            opendal::Operator::new(builder)?
-                .layer(user_quota_layer)
-                .layer(entry_layer)
-                .layer(events_layer)
-                .finish()
+            opendal::Operator::new(builder)?.finish()
         }
     };
 
+    let storage_operator = base_operator.clone();
+    let base_operator = if storage_config.deduplicate_blobs {
+        base_operator.layer(BlobDedupLayer::new(db.clone()))
+    } else {
+        base_operator
+    };
+    let admin_operator = base_operator
+        .layer(user_quota_layer)
+        .layer(entry_layer)
+        .layer(events_layer);
+
     let operator = admin_operator
         .clone()
         .layer(PathCollisionLayer::new(db.clone()))
         .layer(WritePathLayer::new(user_service));
-    Ok((operator, admin_operator))
+    Ok((operator, admin_operator, storage_operator))
 }
 
 /// Build the storage operators from an `AppContext` (test-only convenience).
 #[cfg(test)]
 pub fn build_storage_operators_from_context(
     context: &AppContext,
-) -> Result<(Operator, Operator), FileIoError> {
+) -> Result<(Operator, Operator, Operator), FileIoError> {
     build_storage_operators(
         &context.config_toml.storage,
         context.data_dir.path(),
@@ -125,6 +141,9 @@ pub struct OpendalService {
     pub(crate) operator: Operator,
     /// Operator without `WritePathLayer` (for admin operations that bypass write-path restrictions).
     pub(crate) admin_operator: Operator,
+    /// The raw storage backend, below all layers.
+    /// Only used to delete content whose database rows are already gone.
+    pub(crate) storage_operator: Operator,
 }
 
 impl OpendalService {
@@ -135,7 +154,7 @@ impl OpendalService {
         events_service: EventsService,
         user_service: UserService,
     ) -> Result<Self, FileIoError> {
-        let (operator, admin_operator) = build_storage_operators(
+        let (operator, admin_operator, storage_operator) = build_storage_operators(
             storage_config,
             data_directory,
             db,
@@ -145,6 +164,7 @@ impl OpendalService {
         Ok(Self {
             operator,
             admin_operator,
+            storage_operator,
         })
     }
 
@@ -160,13 +180,31 @@ impl OpendalService {
         Ok(self.admin_operator.delete(path.as_str()).await?)
     }
 
-    /// Write a stream to the storage.
+    /// Delete content straight from the storage backend, bypassing all layers.
+    ///
+    /// Used after the database rows of the content were removed in a transaction
+    /// of its own, so failures only leave unreachable content behind and are
+    /// logged instead of returned.
+    pub async fn delete_unreferenced(&self, keys: Vec<String>) {
+        for key in keys {
+            if let Err(e) = self.storage_operator.delete(&key).await {
+                tracing::warn!("Failed to delete unreferenced content at {key}: {e}");
+            }
+        }
+    }
+
+    /// Write a stream to the storage, storing `metadata` with the entry.
     pub async fn write_stream(
         &self,
         path: &EntryPath,
         mut stream: impl Stream<Item = Result<Bytes, WriteStreamError>> + Unpin + Send,
+        metadata: &WriteMetadata,
     ) -> Result<FileMetadata, FileIoError> {
-        let mut writer = self.operator.writer(path.as_str()).await?;
+        let mut writer = self
+            .operator
+            .writer_with(path.as_str())
+            .user_metadata(metadata.to_user_metadata())
+            .await?;
         let mut metadata_builder = FileMetadataBuilder::default();
         metadata_builder.guess_mime_type_from_path(path.path().as_str());
 
@@ -220,10 +258,12 @@ impl OpendalService {
 #[cfg(test)]
 impl OpendalService {
     pub fn new(context: &AppContext) -> Result<Self, FileIoError> {
-        let (operator, admin_operator) = build_storage_operators_from_context(context)?;
+        let (operator, admin_operator, storage_operator) =
+            build_storage_operators_from_context(context)?;
         Ok(Self {
             operator,
             admin_operator,
+            storage_operator,
         })
     }
 
@@ -232,6 +272,7 @@ impl OpendalService {
     pub fn new_from_operator(operator: Operator) -> Self {
         Self {
             admin_operator: operator.clone(),
+            storage_operator: operator.clone(),
             operator,
         }
     }
@@ -263,7 +304,8 @@ impl OpendalService {
         // Create a single-item stream from the buffer
         let stream = Box::pin(futures_util::stream::once(async move { Ok(bytes) }));
         // Use the existing streaming implementation
-        self.write_stream(path, stream).await
+        self.write_stream(path, stream, &WriteMetadata::default())
+            .await
     }
 }
 
@@ -272,6 +314,7 @@ mod tests {
     use super::*;
     use crate::persistence::files::opendal::opendal_test_operators::OpendalTestOperators;
     use crate::persistence::sql::user::UserRepository;
+    use crate::services::user_service::FILE_METADATA_SIZE;
     use crate::shared::webdav::WebDavPath;
 
     #[tokio::test]
@@ -309,6 +352,73 @@ mod tests {
         ));
     }
 
+    /// With deduplication enabled, identical uploads of different users share one blob
+    /// while each user is still charged for their own copy.
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn test_deduplicate_blobs() {
+        let mut context = AppContext::test().await;
+        context.config_toml.storage.deduplicate_blobs = true;
+        let service =
+            OpendalService::new(&context).expect("Failed to create OpenDAL service for testing");
+
+        let mut paths = Vec::new();
+        for _ in 0..2 {
+            let pubky = pubky_common::crypto::Keypair::random().public_key();
+            UserRepository::create(&pubky, &mut context.sql_db.pool().into())
+                .await
+                .unwrap();
+            let path = EntryPath::new(pubky, WebDavPath::new("/pub/same.txt").unwrap());
+            service.write(&path, vec![7u8; 1024]).await.unwrap();
+            paths.push(path);
+        }
+        let blob_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM blobs")
+            .fetch_one(context.sql_db.pool())
+            .await
+            .unwrap();
+        assert_eq!(blob_count, 1);
+        for path in &paths {
+            let user = UserRepository::get(path.pubkey(), &mut context.sql_db.pool().into())
+                .await
+                .unwrap();
+            assert_eq!(user.used_bytes, 1024 + FILE_METADATA_SIZE);
+        }
+
+        service.delete(&paths[0]).await.unwrap();
+        assert!(!service.exists(&paths[0]).await.unwrap());
+        assert_eq!(
+            service.get(&paths[1]).await.unwrap().as_ref(),
+            &[7u8; 1024][..]
+        );
+    }
+
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn test_sharded_file_system() {
+        let mut context = AppContext::test().await;
+        context.config_toml.storage.backend = StorageConfigToml::FileSystem;
+        context.config_toml.storage.shards = vec!["shard-a".into(), "shard-b".into()];
+        let service =
+            OpendalService::new(&context).expect("Failed to create OpenDAL service for testing");
+        let shard_dirs = context
+            .config_toml
+            .storage
+            .shard_dirs(context.data_dir.path());
+
+        for _ in 0..4 {
+            let pubky = pubky_common::crypto::Keypair::random().public_key();
+            UserRepository::create(&pubky, &mut context.sql_db.pool().into())
+                .await
+                .unwrap();
+            let path = EntryPath::new(pubky.clone(), WebDavPath::new("/pub/a.txt").unwrap());
+            service.write(&path, vec![1u8; 16]).await.unwrap();
+
+            let shard = crate::shard_index(&pubky.z32(), shard_dirs.len());
+            assert!(shard_dirs[shard].join(path.as_str()).is_file());
+            assert_eq!(service.get(&path).await.unwrap().as_ref(), &[1u8; 16][..]);
+        }
+    }
+
     /// Test the chunked reading of a file.
     #[tokio::test]
     #[pubky_test_utils::test]
@@ -398,7 +508,10 @@ mod tests {
             let stream = futures_util::stream::iter(chunks);
 
             // Write the stream to storage
-            file_service.write_stream(&path, stream).await.unwrap();
+            file_service
+                .write_stream(&path, stream, &WriteMetadata::default())
+                .await
+                .unwrap();
 
             // Read the content back and verify it matches
             let read_content = file_service.get(&path).await.unwrap();
@@ -0,0 +1,507 @@
+//! Spread file storage across several directories (typically one per disk).
+//!
+//! Every storage key has an owner: the user's z32 public key for `<pubkey>/pub/...`, or
+//! `_blobs/<name>` for a deduplicated blob. All keys with the same owner live in the same
+//! shard, so a user's files never straddle two disks and renames within a user stay on one
+//! filesystem, while blobs, which hold most of the data, spread over every shard.
+//!
+//! The shard of a key is computed by [`shard_index`], whose definition is stable across
+//! releases. Shards are identified by their position in `[storage].shards`, so new
+//! directories must be appended at the end and existing ones never reordered or removed.
+//! After changing the list, stop the homeserver and run `pubky-homeserver rebalance-shards`
+//! (see [`rebalance_shards`]) to move existing data.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use opendal::raw::*;
+use opendal::{Error, ErrorKind, Operator, Result};
+
+/// The shard a storage key owned by `owner` (see [`owner_of`]) is stored on, out of
+/// `shards`.
+///
+/// This function is part of the on-disk format and never changes:
+///
+/// 1. `h` = the first 8 bytes of `BLAKE3(owner)` (the UTF-8 bytes of the owner), read as a
+///    little-endian `u64`.
+/// 2. The shard is `jump_consistent_hash(h, shards)` as described by Lamping and Veach,
+///    "A Fast, Minimal Memory, Consistent Hash Algorithm" (2014).
+///
+/// When a shard is appended, jump hashing only moves keys *to the new shard*, roughly
+/// `1 / shards` of them.
+///
+/// # Panics
+/// Panics if `shards` is 0.
+pub fn shard_index(owner: &str, shards: usize) -> usize {
+    assert!(shards > 0, "at least one shard is required");
+    let hash = pubky_common::crypto::hash(owner.as_bytes());
+    let mut key_bytes = [0u8; 8];
+    key_bytes.copy_from_slice(&hash.as_bytes()[..8]);
+    jump_consistent_hash(u64::from_le_bytes(key_bytes), shards)
+}
+
+/// Jump consistent hash (Lamping & Veach, 2014).
+fn jump_consistent_hash(mut key: u64, buckets: usize) -> usize {
+    let mut b: i64 = -1;
+    let mut j: i64 = 0;
+    while j < buckets as i64 {
+        b = j;
+        key = key.wrapping_mul(2_862_933_555_777_941_757).wrapping_add(1);
+        j = ((b + 1) as f64 * ((1u64 << 31) as f64 / ((key >> 33) + 1) as f64)) as i64;
+    }
+    b as usize
+}
+
+/// Top-level directory holding the deduplicated blobs, whose keys are `_blobs/<name>`.
+const BLOBS_DIR: &str = "_blobs";
+
+/// The owner of an OpenDAL path, which decides its shard: the first segment, or the first
+/// two segments for a blob.
+///
+/// Returns `None` for paths spanning every shard: the storage root and the `_blobs/`
+/// directory itself.
+fn owner_of(path: &str) -> Option<&str> {
+    let path = path.trim_start_matches('/');
+    let first_len = path.find('/').unwrap_or(path.len());
+    let first = &path[..first_len];
+    if first.is_empty() {
+        return None;
+    }
+    if first != BLOBS_DIR {
+        return Some(first);
+    }
+    let rest = path.get(first_len + 1..).unwrap_or_default();
+    let name_len = rest.find('/').unwrap_or(rest.len());
+    if name_len == 0 {
+        return None;
+    }
+    Some(&path[..first_len + 1 + name_len])
+}
+
+/// Build an operator that routes every path to one of `shard_dirs` by [`shard_index`].
+///
+/// Each shard is a plain file system backend rooted at its directory. Listing the storage
+/// root merges all shards; every other operation touches exactly one shard.
+pub fn build_sharded_operator(shard_dirs: &[PathBuf]) -> Result<Operator> {
+    if shard_dirs.is_empty() {
+        return Err(Error::new(
+            ErrorKind::ConfigInvalid,
+            "at least one storage shard is required",
+        ));
+    }
+    let shards = shard_dirs
+        .iter()
+        .map(|dir| {
+            let root = dir.to_str().ok_or_else(|| {
+                Error::new(ErrorKind::ConfigInvalid, "Invalid path")
+                    .with_context("shard", dir.display())
+            })?;
+            let builder = opendal::services::Fs::default().root(root);
+            Ok(Operator::new(builder)?.finish().into_inner())
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let info = shards[0].info();
+    Ok(Operator::from_inner(Arc::new(ShardedAccessor {
+        shards,
+        info,
+    })))
+}
+
+/// OpenDAL accessor dispatching to one inner accessor per shard.
+#[derive(Debug)]
+pub struct ShardedAccessor {
+    shards: Vec<Accessor>,
+    info: Arc<AccessorInfo>,
+}
+
+impl ShardedAccessor {
+    /// Index of the shard owning `path`, or `None` for paths spanning every shard.
+    fn index_of(&self, path: &str) -> Option<usize> {
+        owner_of(path).map(|owner| shard_index(owner, self.shards.len()))
+    }
+
+    /// The shard owning `path`, or `None` for paths spanning every shard.
+    fn shard_of(&self, path: &str) -> Option<&Accessor> {
+        self.index_of(path).map(|index| &self.shards[index])
+    }
+
+    /// The shard owning `path`; the first shard stands in for paths spanning every shard.
+    fn shard_or_first(&self, path: &str) -> &Accessor {
+        self.shard_of(path).unwrap_or(&self.shards[0])
+    }
+
+    /// The common shard of `from` and `to`. Moving data between shards is the job of
+    /// [`rebalance_shards`], not of regular operations.
+    fn same_shard(&self, from: &str, to: &str) -> Result<&Accessor> {
+        match (self.index_of(from), self.index_of(to)) {
+            (Some(a), Some(b)) if a == b => Ok(&self.shards[a]),
+            _ => Err(Error::new(
+                ErrorKind::Unsupported,
+                "copy and rename across storage shards are not supported",
+            )
+            .with_context("from", from)
+            .with_context("to", to)),
+        }
+    }
+}
+
+impl Access for ShardedAccessor {
+    type Reader = oio::Reader;
+    type Writer = oio::Writer;
+    type Lister = oio::Lister;
+    type Deleter = oio::Deleter;
+
+    fn info(&self) -> Arc<AccessorInfo> {
+        self.info.clone()
+    }
+
+    async fn create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
+        match self.shard_of(path) {
+            Some(shard) => shard.create_dir(path, args).await,
+            None => {
+                for shard in &self.shards {
+                    shard.create_dir(path, args.clone()).await?;
+                }
+                Ok(RpCreateDir::default())
+            }
+        }
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        self.shard_or_first(path).stat(path, args).await
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        self.shard_or_first(path).read(path, args).await
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        self.shard_or_first(path).write(path, args).await
+    }
+
+    async fn copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        self.same_shard(from, to)?.copy(from, to, args).await
+    }
+
+    async fn rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        self.same_shard(from, to)?.rename(from, to, args).await
+    }
+
+    async fn delete(&self) -> Result<(RpDelete, Self::Deleter)> {
+        let mut deleters = Vec::with_capacity(self.shards.len());
+        for shard in &self.shards {
+            deleters.push(shard.delete().await?.1);
+        }
+        Ok((RpDelete::default(), Box::new(ShardedDeleter { deleters })))
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Lister)> {
+        let shards: Vec<&Accessor> = match self.shard_of(path) {
+            Some(shard) => vec![shard],
+            None => self.shards.iter().collect(),
+        };
+        let mut listers = VecDeque::with_capacity(shards.len());
+        for shard in shards {
+            listers.push_back(shard.list(path, args.clone()).await?.1);
+        }
+        Ok((
+            RpList::default(),
+            Box::new(ShardedLister {
+                path: path.to_string(),
+                listers,
+                seen_path: false,
+            }),
+        ))
+    }
+
+    async fn presign(&self, path: &str, args: OpPresign) -> Result<RpPresign> {
+        self.shard_or_first(path).presign(path, args).await
+    }
+}
+
+/// Lists the shards one after another. The listed directory itself is reported by every
+/// shard; only its first occurrence is kept.
+pub struct ShardedLister {
+    path: String,
+    listers: VecDeque<oio::Lister>,
+    seen_path: bool,
+}
+
+impl oio::List for ShardedLister {
+    async fn next(&mut self) -> Result<Option<oio::Entry>> {
+        while let Some(lister) = self.listers.front_mut() {
+            match lister.next().await? {
+                Some(entry) if entry.path() == self.path => {
+                    if !std::mem::replace(&mut self.seen_path, true) {
+                        return Ok(Some(entry));
+                    }
+                }
+                Some(entry) => return Ok(Some(entry)),
+                None => {
+                    self.listers.pop_front();
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Queues each deletion on the deleter of the path's shard.
+pub struct ShardedDeleter {
+    deleters: Vec<oio::Deleter>,
+}
+
+impl oio::Delete for ShardedDeleter {
+    fn delete(&mut self, path: &str, args: OpDelete) -> Result<()> {
+        let shard = owner_of(path).map_or(0, |owner| shard_index(owner, self.deleters.len()));
+        self.deleters[shard].delete(path, args)
+    }
+
+    async fn flush(&mut self) -> Result<usize> {
+        let mut deleted = 0;
+        for deleter in &mut self.deleters {
+            deleted += deleter.flush().await?;
+        }
+        Ok(deleted)
+    }
+}
+
+/// Outcome of [`rebalance_shards`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RebalanceReport {
+    /// Owners (users and blobs) moved to another shard.
+    pub moved: usize,
+    /// Owners already on their shard.
+    pub unchanged: usize,
+}
+
+impl fmt::Display for RebalanceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} moved, {} already in place",
+            self.moved, self.unchanged
+        )
+    }
+}
+
+/// Move every user directory and blob of `shard_dirs` to the shard [`shard_index`] assigns
+/// it.
+///
+/// Run it after appending shards, while the homeserver is stopped. Entries are renamed
+/// when source and destination share a filesystem, and copied then deleted otherwise. A
+/// destination that already exists, e.g. from an interrupted run, is merged into, so the
+/// rebalance can simply be restarted.
+pub fn rebalance_shards(shard_dirs: &[PathBuf]) -> io::Result<RebalanceReport> {
+    // Plan every move before touching anything, so moved entries aren't visited twice.
+    let mut moves = Vec::new();
+    let mut report = RebalanceReport::default();
+    for (index, dir) in shard_dirs.iter().enumerate() {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        };
+        let mut owners = Vec::new();
+        for entry in entries {
+            let name = entry?.file_name().into_string();
+            match name {
+                Ok(name) if name == BLOBS_DIR => owners.extend(blob_owners(&dir.join(BLOBS_DIR))?),
+                Ok(name) => owners.push(name),
+                Err(name) => tracing::warn!("Skipping non UTF-8 entry {:?}", dir.join(name)),
+            }
+        }
+        for owner in owners {
+            let target = shard_index(&owner, shard_dirs.len());
+            if target == index {
+                report.unchanged += 1;
+            } else {
+                moves.push((owner, index, target));
+            }
+        }
+    }
+
+    for (owner, from, to) in moves {
+        let destination = shard_dirs[to].join(&owner);
+        if let Some(parent) = destination.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        move_path(&shard_dirs[from].join(&owner), &destination)?;
+        tracing::info!("Moved {} from shard {} to shard {}", owner, from, to);
+        report.moved += 1;
+    }
+    Ok(report)
+}
+
+/// The owners (`_blobs/<name>`) of the blobs in the `_blobs` directory `blobs_dir`.
+fn blob_owners(blobs_dir: &Path) -> io::Result<Vec<String>> {
+    let mut owners = Vec::new();
+    for entry in std::fs::read_dir(blobs_dir)? {
+        match entry?.file_name().into_string() {
+            Ok(name) => owners.push(format!("{BLOBS_DIR}/{name}")),
+            Err(name) => tracing::warn!("Skipping non UTF-8 entry {:?}", blobs_dir.join(name)),
+        }
+    }
+    Ok(owners)
+}
+
+/// Move `from` to `to`, merging into `to` if it is an existing directory and falling back
+/// to copy-then-delete across filesystems.
+fn move_path(from: &Path, to: &Path) -> io::Result<()> {
+    let is_dir = std::fs::symlink_metadata(from)?.is_dir();
+    if !to.exists() && std::fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    if is_dir {
+        std::fs::create_dir_all(to)?;
+        for entry in std::fs::read_dir(from)? {
+            let entry = entry?;
+            move_path(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        std::fs::remove_dir(from)
+    } else {
+        std::fs::copy(from, to)?;
+        std::fs::remove_file(from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::TryStreamExt;
+
+    #[test]
+    fn shard_index_is_stable() {
+        // Pinned values: changing them would misplace every stored file.
+        assert_eq!(shard_index("_blobs/a", 1), 0);
+        let key = "8pinxxgqs41n4aididenw5apqp1urfmzdztr8jt4abrkdn435ewo";
+        let pinned: Vec<usize> = (1..=8).map(|n| shard_index(key, n)).collect();
+        assert_eq!(pinned, PINNED_KEY_SHARDS);
+        let blob = "_blobs/67e55044-10b1-426f-9247-bb680e5fe0c8";
+        let pinned: Vec<usize> = (1..=8).map(|n| shard_index(blob, n)).collect();
+        assert_eq!(pinned, PINNED_BLOB_SHARDS);
+    }
+
+    const PINNED_KEY_SHARDS: [usize; 8] = [0, 1, 1, 1, 1, 1, 1, 1];
+    const PINNED_BLOB_SHARDS: [usize; 8] = [0, 0, 0, 3, 4, 4, 4, 4];
+
+    #[test]
+    fn blobs_are_owned_by_their_name() {
+        assert_eq!(owner_of("user/pub/a.txt"), Some("user"));
+        assert_eq!(owner_of("/user/"), Some("user"));
+        assert_eq!(owner_of("_blobs/abc"), Some("_blobs/abc"));
+        assert_eq!(owner_of("_blobs/abc/"), Some("_blobs/abc"));
+        assert_eq!(owner_of("_blobs/"), None);
+        assert_eq!(owner_of("_blobs"), None);
+        assert_eq!(owner_of("/"), None);
+
+        let shards: std::collections::HashSet<usize> = (0..100)
+            .map(|i| shard_index(&format!("_blobs/{i}"), 4))
+            .collect();
+        assert_eq!(shards.len(), 4);
+    }
+
+    #[test]
+    fn appending_a_shard_only_moves_keys_to_it() {
+        for i in 0..1000 {
+            let key = format!("user{i}");
+            let (before, after) = (shard_index(&key, 3), shard_index(&key, 4));
+            assert!(after == before || after == 3);
+        }
+    }
+
+    #[tokio::test]
+    async fn operator_routes_by_first_segment() {
+        let dirs: Vec<tempfile::TempDir> = (0..3).map(|_| tempfile::tempdir().unwrap()).collect();
+        let paths: Vec<PathBuf> = dirs.iter().map(|d| d.path().to_path_buf()).collect();
+        let operator = build_sharded_operator(&paths).unwrap();
+
+        let users: Vec<String> = (0..6).map(|i| format!("user{i}")).collect();
+        for user in &users {
+            let path = format!("{user}/pub/file.txt");
+            operator.write(&path, user.clone()).await.unwrap();
+            let shard = shard_index(user, paths.len());
+            assert!(paths[shard].join(&path).is_file());
+            let content = operator.read(&path).await.unwrap().to_vec();
+            assert_eq!(content, user.as_bytes());
+        }
+
+        let mut listed: Vec<String> = operator
+            .lister_with("/")
+            .recursive(true)
+            .await
+            .unwrap()
+            .map_ok(|entry| entry.path().to_string())
+            .try_filter(|path| futures_util::future::ready(path.ends_with(".txt")))
+            .try_collect()
+            .await
+            .unwrap();
+        listed.sort();
+        let expected: Vec<String> = users.iter().map(|u| format!("{u}/pub/file.txt")).collect();
+        assert_eq!(listed, expected);
+
+        let blobs: Vec<String> = (0..6).map(|i| format!("_blobs/blob{i}")).collect();
+        for blob in &blobs {
+            operator.write(blob, blob.clone()).await.unwrap();
+            assert!(paths[shard_index(blob, paths.len())].join(blob).is_file());
+        }
+        let mut listed: Vec<String> = operator
+            .list("_blobs/")
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|entry| entry.metadata().is_file())
+            .map(|entry| entry.path().to_string())
+            .collect();
+        listed.sort();
+        assert_eq!(listed, blobs);
+
+        operator.delete("user0/pub/file.txt").await.unwrap();
+        assert!(!operator.exists("user0/pub/file.txt").await.unwrap());
+        operator
+            .rename("user1/pub/file.txt", "user1/pub/moved.txt")
+            .await
+            .unwrap();
+        assert!(operator.exists("user1/pub/moved.txt").await.unwrap());
+    }
+
+    #[test]
+    fn rebalance_moves_entries_to_their_shard() {
+        let dirs: Vec<tempfile::TempDir> = (0..3).map(|_| tempfile::tempdir().unwrap()).collect();
+        let paths: Vec<PathBuf> = dirs.iter().map(|d| d.path().to_path_buf()).collect();
+
+        // Everything starts on the first shard, as after sharding a single directory.
+        let users: Vec<String> = (0..10).map(|i| format!("user{i}")).collect();
+        for user in &users {
+            let dir = paths[0].join(user).join("pub");
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join("file.txt"), user).unwrap();
+        }
+
+        let blobs: Vec<String> = (0..10).map(|i| format!("_blobs/blob{i}")).collect();
+        std::fs::create_dir_all(paths[0].join("_blobs")).unwrap();
+        for blob in &blobs {
+            std::fs::write(paths[0].join(blob), blob).unwrap();
+        }
+
+        let report = rebalance_shards(&paths).unwrap();
+        assert_eq!(report.moved + report.unchanged, users.len() + blobs.len());
+        assert!(report.moved > 0);
+        for user in &users {
+            let file = paths[shard_index(user, paths.len())]
+                .join(user)
+                .join("pub/file.txt");
+            assert_eq!(std::fs::read_to_string(file).unwrap(), *user);
+        }
+        for blob in &blobs {
+            let file = paths[shard_index(blob, paths.len())].join(blob);
+            assert_eq!(std::fs::read_to_string(file).unwrap(), *blob);
+        }
+
+        let report = rebalance_shards(&paths).unwrap();
+        assert_eq!(report.moved, 0);
+    }
+}
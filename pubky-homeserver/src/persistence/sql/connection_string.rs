@@ -28,8 +28,6 @@ impl ConnectionString {
     }
 
     /// Get the database name
-    /// For postgres, this is the database name directly
-    /// For sqlite, this is the path to the database file
     pub fn database_name(&self) -> &str {
         self.0.path().trim_start_matches("/")
     }
@@ -0,0 +1,330 @@
+use pubky_common::crypto::Hash;
+use sea_query::Iden;
+use sqlx::{postgres::PgRow, FromRow, Row};
+
+use crate::persistence::sql::UnifiedExecutor;
+
+pub const BLOB_TABLE: &str = "blobs";
+pub const BLOB_REF_TABLE: &str = "blob_refs";
+
+/// Repository that handles all the queries regarding content-addressed blobs
+/// and the storage paths referencing them.
+///
+/// Every method that changes a reference must run inside a transaction that
+/// first called [`BlobRepository::lock_path`] for the affected paths, so the
+/// reference counts stay consistent under concurrent writes and deletes.
+pub struct BlobRepository;
+
+impl BlobRepository {
+    /// Serialize reference changes on `path` until the surrounding transaction ends.
+    pub async fn lock_path<'a>(
+        path: &str,
+        executor: &mut UnifiedExecutor<'a>,
+    ) -> Result<(), sqlx::Error> {
+        let con = executor.get_con().await?;
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+            .bind(path)
+            .execute(con)
+            .await?;
+        Ok(())
+    }
+
+    /// Get the blob referenced by `path`, if any.
+    /// The executor can either be db.pool() or a transaction.
+    pub async fn get_by_path<'a>(
+        path: &str,
+        executor: &mut UnifiedExecutor<'a>,
+    ) -> Result<Option<BlobEntity>, sqlx::Error> {
+        let con = executor.get_con().await?;
+        sqlx::query_as(
+            r#"
+            SELECT blobs.hash, blobs.storage_key, blobs.content_length, blobs.ref_count
+            FROM blob_refs
+            JOIN blobs ON blobs.hash = blob_refs.hash
+            WHERE blob_refs.path = $1
+            "#,
+        )
+        .bind(path)
+        .fetch_optional(con)
+        .await
+    }
+
+    /// Take a reference on the blob with `hash`.
+    ///
+    /// If no such blob exists yet, it is created with `storage_key` as its location.
+    /// Otherwise its reference count is incremented and the existing blob is returned,
+    /// in which case the content at `storage_key` is redundant.
+    pub async fn acquire<'a>(
+        hash: &Hash,
+        storage_key: &str,
+        content_length: u64,
+        executor: &mut UnifiedExecutor<'a>,
+    ) -> Result<BlobEntity, sqlx::Error> {
+        let con = executor.get_con().await?;
+        sqlx::query_as(
+            r#"
+            INSERT INTO blobs (hash, storage_key, content_length, ref_count)
+            VALUES ($1, $2, $3, 1)
+            ON CONFLICT (hash) DO UPDATE SET ref_count = blobs.ref_count + 1
+            RETURNING hash, storage_key, content_length, ref_count
+            "#,
+        )
+        .bind(hash.as_bytes().to_vec())
+        .bind(storage_key)
+        .bind(content_length as i64)
+        .fetch_one(con)
+        .await
+    }
+
+    /// Drop a reference on the blob with `hash`.
+    ///
+    /// Returns the storage key of the blob if this was its last reference. The blob
+    /// row is deleted in that case and the caller must delete the stored content
+    /// once the transaction is committed.
+    pub async fn release<'a>(
+        hash: &Hash,
+        executor: &mut UnifiedExecutor<'a>,
+    ) -> Result<Option<String>, sqlx::Error> {
+        let con = executor.get_con().await?;
+        let row: Option<PgRow> = sqlx::query(
+            "UPDATE blobs SET ref_count = ref_count - 1 WHERE hash = $1 RETURNING storage_key, ref_count",
+        )
+        .bind(hash.as_bytes().to_vec())
+        .fetch_optional(&mut *con)
+        .await?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let ref_count: i64 = row.try_get(BlobIden::RefCount.to_string().as_str())?;
+        if ref_count > 0 {
+            return Ok(None);
+        }
+        sqlx::query("DELETE FROM blobs WHERE hash = $1")
+            .bind(hash.as_bytes().to_vec())
+            .execute(con)
+            .await?;
+        Ok(Some(
+            row.try_get(BlobIden::StorageKey.to_string().as_str())?,
+        ))
+    }
+
+    /// Point `path` at the blob with `hash`.
+    /// Returns the hash `path` referenced before, if any.
+    pub async fn set_ref<'a>(
+        path: &str,
+        hash: &Hash,
+        executor: &mut UnifiedExecutor<'a>,
+    ) -> Result<Option<Hash>, sqlx::Error> {
+        let con = executor.get_con().await?;
+        let previous: Option<Vec<u8>> =
+            sqlx::query_scalar("SELECT hash FROM blob_refs WHERE path = $1")
+                .bind(path)
+                .fetch_optional(&mut *con)
+                .await?;
+        sqlx::query(
+            r#"
+            INSERT INTO blob_refs (path, hash) VALUES ($1, $2)
+            ON CONFLICT (path) DO UPDATE SET hash = EXCLUDED.hash
+            "#,
+        )
+        .bind(path)
+        .bind(hash.as_bytes().to_vec())
+        .execute(con)
+        .await?;
+        previous.map(decode_hash).transpose()
+    }
+
+    /// Remove the reference of `path`.
+    /// Returns the hash `path` referenced, if any.
+    pub async fn remove_ref<'a>(
+        path: &str,
+        executor: &mut UnifiedExecutor<'a>,
+    ) -> Result<Option<Hash>, sqlx::Error> {
+        let con = executor.get_con().await?;
+        let previous: Option<Vec<u8>> =
+            sqlx::query_scalar("DELETE FROM blob_refs WHERE path = $1 RETURNING hash")
+                .bind(path)
+                .fetch_optional(con)
+                .await?;
+        previous.map(decode_hash).transpose()
+    }
+
+    /// List all referencing paths starting with `prefix` together with their content length,
+    /// ordered by path.
+    /// The executor can either be db.pool() or a transaction.
+    pub async fn list_refs_by_prefix<'a>(
+        prefix: &str,
+        executor: &mut UnifiedExecutor<'a>,
+    ) -> Result<Vec<(String, u64)>, sqlx::Error> {
+        let pattern = like_prefix_pattern(prefix);
+        let con = executor.get_con().await?;
+        let rows: Vec<(String, i64)> = sqlx::query_as(
+            r#"
+            SELECT blob_refs.path, blobs.content_length
+            FROM blob_refs
+            JOIN blobs ON blobs.hash = blob_refs.hash
+            WHERE blob_refs.path LIKE $1 ESCAPE '\'
+            ORDER BY blob_refs.path
+            "#,
+        )
+        .bind(pattern)
+        .fetch_all(con)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|(path, length)| (path, length as u64))
+            .collect())
+    }
+
+    /// Check whether any path starting with `prefix` references a blob.
+    /// The executor can either be db.pool() or a transaction.
+    pub async fn has_refs_with_prefix<'a>(
+        prefix: &str,
+        executor: &mut UnifiedExecutor<'a>,
+    ) -> Result<bool, sqlx::Error> {
+        let con = executor.get_con().await?;
+        sqlx::query_scalar(
+            r#"SELECT EXISTS (SELECT 1 FROM blob_refs WHERE path LIKE $1 ESCAPE '\')"#,
+        )
+        .bind(like_prefix_pattern(prefix))
+        .fetch_one(con)
+        .await
+    }
+}
+
+/// Build a `LIKE` pattern matching every string that starts with `prefix`.
+fn like_prefix_pattern(prefix: &str) -> String {
+    let escaped = prefix
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("{escaped}%")
+}
+
+fn decode_hash(bytes: Vec<u8>) -> Result<Hash, sqlx::Error> {
+    let bytes: [u8; 32] = bytes
+        .try_into()
+        .map_err(|_| sqlx::Error::Decode("Blob hash must be exactly 32 bytes".into()))?;
+    Ok(Hash::from_bytes(bytes))
+}
+
+/// Content stored once and shared by every path with the same content hash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlobEntity {
+    pub hash: Hash,
+    /// Where the content lives in the storage backend.
+    pub storage_key: String,
+    pub content_length: u64,
+    /// Number of paths referencing this blob.
+    pub ref_count: u64,
+}
+
+impl FromRow<'_, PgRow> for BlobEntity {
+    fn from_row(row: &PgRow) -> Result<Self, sqlx::Error> {
+        let hash: Vec<u8> = row.try_get(BlobIden::Hash.to_string().as_str())?;
+        let content_length: i64 = row.try_get(BlobIden::ContentLength.to_string().as_str())?;
+        let ref_count: i64 = row.try_get(BlobIden::RefCount.to_string().as_str())?;
+        Ok(BlobEntity {
+            hash: decode_hash(hash)?,
+            storage_key: row.try_get(BlobIden::StorageKey.to_string().as_str())?,
+            content_length: content_length as u64,
+            ref_count: ref_count as u64,
+        })
+    }
+}
+
+#[derive(Iden)]
+pub enum BlobIden {
+    Hash,
+    StorageKey,
+    ContentLength,
+    RefCount,
+    CreatedAt,
+}
+
+#[derive(Iden)]
+pub enum BlobRefIden {
+    Path,
+    Hash,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::sql::{uexecutor, SqlDb};
+
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn test_ref_counting() {
+        let db = SqlDb::test().await;
+        let hash = Hash::from_bytes([7; 32]);
+
+        let mut tx = db.pool().begin().await.unwrap();
+        let blob = BlobRepository::acquire(&hash, "_blobs/a", 3, uexecutor!(tx))
+            .await
+            .unwrap();
+        assert_eq!(blob.storage_key, "_blobs/a");
+        assert_eq!(blob.ref_count, 1);
+        BlobRepository::set_ref("alice/pub/a.txt", &hash, uexecutor!(tx))
+            .await
+            .unwrap();
+        let blob = BlobRepository::acquire(&hash, "_blobs/b", 3, uexecutor!(tx))
+            .await
+            .unwrap();
+        assert_eq!(blob.storage_key, "_blobs/a", "Existing blob is kept");
+        assert_eq!(blob.ref_count, 2);
+        BlobRepository::set_ref("bob/pub/b.txt", &hash, uexecutor!(tx))
+            .await
+            .unwrap();
+        tx.commit().await.unwrap();
+
+        let found = BlobRepository::get_by_path("bob/pub/b.txt", &mut db.pool().into())
+            .await
+            .unwrap();
+        assert_eq!(found, Some(blob));
+        let listed = BlobRepository::list_refs_by_prefix("alice/", &mut db.pool().into())
+            .await
+            .unwrap();
+        assert_eq!(listed, vec![("alice/pub/a.txt".to_string(), 3)]);
+
+        let mut tx = db.pool().begin().await.unwrap();
+        let removed = BlobRepository::remove_ref("alice/pub/a.txt", uexecutor!(tx))
+            .await
+            .unwrap();
+        assert_eq!(removed, Some(hash));
+        let released = BlobRepository::release(&hash, uexecutor!(tx))
+            .await
+            .unwrap();
+        assert_eq!(released, None, "Bob still references the blob");
+        BlobRepository::remove_ref("bob/pub/b.txt", uexecutor!(tx))
+            .await
+            .unwrap();
+        let released = BlobRepository::release(&hash, uexecutor!(tx))
+            .await
+            .unwrap();
+        assert_eq!(released.as_deref(), Some("_blobs/a"));
+        tx.commit().await.unwrap();
+    }
+
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn test_list_refs_escapes_like_patterns() {
+        let db = SqlDb::test().await;
+        let hash = Hash::from_bytes([1; 32]);
+        let mut tx = db.pool().begin().await.unwrap();
+        for path in ["u/a_b/x", "u/aXb/y"] {
+            BlobRepository::acquire(&hash, "_blobs/k", 1, uexecutor!(tx))
+                .await
+                .unwrap();
+            BlobRepository::set_ref(path, &hash, uexecutor!(tx))
+                .await
+                .unwrap();
+        }
+        tx.commit().await.unwrap();
+
+        let listed = BlobRepository::list_refs_by_prefix("u/a_b/", &mut db.pool().into())
+            .await
+            .unwrap();
+        assert_eq!(listed, vec![("u/a_b/x".to_string(), 1)]);
+    }
+}
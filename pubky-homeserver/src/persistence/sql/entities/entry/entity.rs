@@ -17,6 +17,27 @@ pub struct EntryEntity {
     pub content_type: String,
     pub modified_at: sqlx::types::chrono::NaiveDateTime,
     pub created_at: sqlx::types::chrono::NaiveDateTime,
+    /// Deadline (UTC) after which the entry is treated as gone. `None` never expires.
+    pub expires_at: Option<sqlx::types::chrono::NaiveDateTime>,
+    /// Filename to download the entry as, sent back as `Content-Disposition`.
+    pub filename: Option<String>,
+}
+
+impl EntryEntity {
+    /// Whether the entry's expiry deadline has passed.
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| expires_at <= sqlx::types::chrono::Utc::now().naive_utc())
+    }
+}
+
+/// Aggregate statistics over the entries of a directory.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct EntryDirStats {
+    pub file_count: u64,
+    pub total_bytes: u64,
+    pub oldest_modified_at: Option<sqlx::types::chrono::NaiveDateTime>,
+    pub newest_modified_at: Option<sqlx::types::chrono::NaiveDateTime>,
 }
 
 impl FromRow<'_, PgRow> for EntryEntity {
@@ -43,6 +64,9 @@ impl FromRow<'_, PgRow> for EntryEntity {
             row.try_get(EntryIden::ModifiedAt.to_string().as_str())?;
         let created_at: sqlx::types::chrono::NaiveDateTime =
             row.try_get(EntryIden::CreatedAt.to_string().as_str())?;
+        let expires_at: Option<sqlx::types::chrono::NaiveDateTime> =
+            row.try_get(EntryIden::ExpiresAt.to_string().as_str())?;
+        let filename: Option<String> = row.try_get(EntryIden::Filename.to_string().as_str())?;
         Ok(EntryEntity {
             id,
             user_id,
@@ -52,6 +76,8 @@ impl FromRow<'_, PgRow> for EntryEntity {
             content_type,
             modified_at,
             created_at,
+            expires_at,
+            filename,
         })
     }
 }
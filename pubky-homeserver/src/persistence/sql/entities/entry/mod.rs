@@ -1,5 +1,5 @@
 mod entity;
 mod repository;
 
-pub use entity::EntryEntity;
+pub use entity::{EntryDirStats, EntryEntity};
 pub use repository::{EntryIden, EntryRepository};
@@ -1,5 +1,5 @@
 use crate::constants::{DEFAULT_LIST_LIMIT, DEFAULT_MAX_LIST_LIMIT};
-use crate::persistence::sql::entry::EntryEntity;
+use crate::persistence::sql::entry::{EntryDirStats, EntryEntity};
 use crate::{
     persistence::sql::{
         entities::user::{UserIden, USER_TABLE},
@@ -9,6 +9,7 @@ use crate::{
 };
 use sea_query::{Alias, Expr, Iden, Order, PostgresQueryBuilder, Query, SimpleExpr};
 use sea_query_binder::SqlxBinder;
+use sqlx::types::chrono::{NaiveDateTime, Utc};
 use sqlx::{postgres::PgRow, Row};
 
 pub const ENTRY_TABLE: &str = "entries";
@@ -72,6 +73,8 @@ impl EntryRepository {
                 (ENTRY_TABLE, EntryIden::ContentType),
                 (ENTRY_TABLE, EntryIden::ModifiedAt),
                 (ENTRY_TABLE, EntryIden::CreatedAt),
+                (ENTRY_TABLE, EntryIden::ExpiresAt),
+                (ENTRY_TABLE, EntryIden::Filename),
             ])
             .column((USER_TABLE, UserIden::PublicKey))
             .left_join(
@@ -103,6 +106,8 @@ impl EntryRepository {
                 (ENTRY_TABLE, EntryIden::ContentType),
                 (ENTRY_TABLE, EntryIden::ModifiedAt),
                 (ENTRY_TABLE, EntryIden::CreatedAt),
+                (ENTRY_TABLE, EntryIden::ExpiresAt),
+                (ENTRY_TABLE, EntryIden::Filename),
             ])
             .column((USER_TABLE, UserIden::PublicKey))
             .left_join(
@@ -147,6 +152,140 @@ impl EntryRepository {
         Ok(())
     }
 
+    /// Serialize locked writes to `path` until the surrounding transaction ends.
+    ///
+    /// Uses the two-key advisory lock space, so it never contends with
+    /// [`BlobRepository::lock_path`](crate::persistence::sql::blob::BlobRepository::lock_path)
+    /// taken by the write itself.
+    pub async fn lock_path<'a>(
+        path: &EntryPath,
+        executor: &mut UnifiedExecutor<'a>,
+    ) -> Result<(), sqlx::Error> {
+        let con = executor.get_con().await?;
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext('append'), hashtext($1))")
+            .bind(path.as_str())
+            .execute(con)
+            .await?;
+        Ok(())
+    }
+
+    /// Set or clear the expiry deadline (UTC) of an entry.
+    /// The executor can either be db.pool() or a transaction.
+    pub async fn set_expires_at<'a>(
+        id: i64,
+        expires_at: Option<NaiveDateTime>,
+        executor: &mut UnifiedExecutor<'a>,
+    ) -> Result<(), sqlx::Error> {
+        let statement = Query::update()
+            .table(ENTRY_TABLE)
+            .values(vec![(EntryIden::ExpiresAt, expires_at.into())])
+            .and_where(Expr::col((ENTRY_TABLE, EntryIden::Id)).eq(id))
+            .to_owned();
+        let (query, values) = statement.build_sqlx(PostgresQueryBuilder);
+        let con = executor.get_con().await?;
+        sqlx::query_with(&query, values).execute(con).await?;
+        Ok(())
+    }
+
+    /// Set or clear the download filename of an entry.
+    /// The executor can either be db.pool() or a transaction.
+    pub async fn set_filename<'a>(
+        id: i64,
+        filename: Option<&str>,
+        executor: &mut UnifiedExecutor<'a>,
+    ) -> Result<(), sqlx::Error> {
+        let statement = Query::update()
+            .table(ENTRY_TABLE)
+            .values(vec![(EntryIden::Filename, filename.into())])
+            .and_where(Expr::col((ENTRY_TABLE, EntryIden::Id)).eq(id))
+            .to_owned();
+        let (query, values) = statement.build_sqlx(PostgresQueryBuilder);
+        let con = executor.get_con().await?;
+        sqlx::query_with(&query, values).execute(con).await?;
+        Ok(())
+    }
+
+    /// List up to `limit` entries whose expiry deadline has passed, oldest deadline first.
+    /// The executor can either be db.pool() or a transaction.
+    pub async fn list_expired<'a>(
+        limit: u64,
+        executor: &mut UnifiedExecutor<'a>,
+    ) -> Result<Vec<EntryEntity>, sqlx::Error> {
+        let statement = Query::select()
+            .from(ENTRY_TABLE)
+            .columns([
+                (ENTRY_TABLE, EntryIden::Id),
+                (ENTRY_TABLE, EntryIden::User),
+                (ENTRY_TABLE, EntryIden::Path),
+                (ENTRY_TABLE, EntryIden::ContentHash),
+                (ENTRY_TABLE, EntryIden::ContentLength),
+                (ENTRY_TABLE, EntryIden::ContentType),
+                (ENTRY_TABLE, EntryIden::ModifiedAt),
+                (ENTRY_TABLE, EntryIden::CreatedAt),
+                (ENTRY_TABLE, EntryIden::ExpiresAt),
+                (ENTRY_TABLE, EntryIden::Filename),
+            ])
+            .column((USER_TABLE, UserIden::PublicKey))
+            .inner_join(
+                USER_TABLE,
+                Expr::col((ENTRY_TABLE, EntryIden::User)).eq(Expr::col((USER_TABLE, UserIden::Id))),
+            )
+            .and_where(Expr::col((ENTRY_TABLE, EntryIden::ExpiresAt)).lte(Utc::now().naive_utc()))
+            .order_by((ENTRY_TABLE, EntryIden::ExpiresAt), Order::Asc)
+            .limit(limit)
+            .to_owned();
+        let (query, values) = statement.build_sqlx(PostgresQueryBuilder);
+        let con = executor.get_con().await?;
+        sqlx::query_as_with(&query, values).fetch_all(con).await
+    }
+
+    /// Delete `entry` if it still has the listed version and its expiry deadline has
+    /// passed, returning its path and content length.
+    ///
+    /// The version is the content hash and modification time. Returns `None` if the entry
+    /// is gone or was rewritten since it was listed by [`Self::list_expired`], even if the
+    /// rewrite expired too: its content is not the one the caller is about to release.
+    pub async fn delete_expired<'a>(
+        entry: &EntryEntity,
+        executor: &mut UnifiedExecutor<'a>,
+    ) -> Result<Option<(WebDavPath, u64)>, sqlx::Error> {
+        let con = executor.get_con().await?;
+        let row: Option<(String, i64)> = sqlx::query_as(
+            r#"
+            DELETE FROM entries
+            WHERE entries.id = $1
+              AND entries.content_hash = $2
+              AND entries.modified_at = $3
+              AND entries.expires_at <= $4
+            RETURNING entries.path, entries.content_length
+            "#,
+        )
+        .bind(entry.id)
+        .bind(entry.content_hash.as_bytes().to_vec())
+        .bind(entry.modified_at)
+        .bind(Utc::now().naive_utc())
+        .fetch_optional(con)
+        .await?;
+
+        row.map(|(path, length)| {
+            let path = WebDavPath::new(&path).map_err(|e| sqlx::Error::Decode(e.into()))?;
+            Ok((path, length as u64))
+        })
+        .transpose()
+    }
+
+    /// Condition that keeps only entries whose expiry deadline, if any, is still ahead.
+    fn not_expired() -> SimpleExpr {
+        Expr::col((ENTRY_TABLE, EntryIden::ExpiresAt))
+            .is_null()
+            .or(Expr::col((ENTRY_TABLE, EntryIden::ExpiresAt)).gt(Utc::now().naive_utc()))
+    }
+
+    /// Filter on entries modified at or after `since`.
+    fn modified_since(since: NaiveDateTime) -> SimpleExpr {
+        Expr::col((ENTRY_TABLE, EntryIden::ModifiedAt)).gte(since)
+    }
+
     /// Delete an entry by its id.
     /// The executor can either be db.pool() or a transaction.
     pub async fn delete<'a>(
@@ -193,6 +332,46 @@ impl EntryRepository {
         Ok(())
     }
 
+    /// Delete all entries of a user below a folder, expired ones included.
+    /// Path is the path to the folder.
+    /// Returns the path and content length of every deleted entry, ordered by path.
+    /// The executor can either be db.pool() or a transaction.
+    pub async fn delete_dir<'a>(
+        user_id: i32,
+        path: &WebDavPath,
+        executor: &mut UnifiedExecutor<'a>,
+    ) -> Result<Vec<(WebDavPath, u64)>, sqlx::Error> {
+        let mut dir_path = path.to_string();
+        if !dir_path.ends_with("/") {
+            // Make sure the path is a folder
+            dir_path.push('/');
+        }
+
+        let con = executor.get_con().await?;
+        let rows: Vec<(String, i64)> = sqlx::query_as(
+            r#"
+            DELETE FROM entries
+            WHERE entries."user" = $1
+              AND substr(entries.path, 1, length($2)) = $2
+            RETURNING entries.path, entries.content_length
+            "#,
+        )
+        .bind(user_id)
+        .bind(&dir_path)
+        .fetch_all(con)
+        .await?;
+
+        let mut entries = rows
+            .into_iter()
+            .map(|(path, length)| {
+                let path = WebDavPath::new(&path).map_err(|e| sqlx::Error::Decode(e.into()))?;
+                Ok((path, length as u64))
+            })
+            .collect::<Result<Vec<_>, sqlx::Error>>()?;
+        entries.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+        Ok(entries)
+    }
+
     /// Check if a directory exists.
     /// Path is the path to the folder.
     pub async fn contains_directory<'a>(
@@ -214,6 +393,7 @@ impl EntryRepository {
             )
             .and_where(Expr::col((ENTRY_TABLE, EntryIden::Path)).like(format!("{}%", full_path))) // Everything that starts with the path
             .and_where(Expr::col((USER_TABLE, UserIden::PublicKey)).eq(path.pubkey().z32()))
+            .and_where(Self::not_expired())
             .limit(1)
             .to_owned();
 
@@ -226,6 +406,55 @@ impl EntryRepository {
         Ok(count > 0)
     }
 
+    /// Aggregate file count, total size and modification time range of a directory.
+    /// Path is the path to the folder.
+    /// With `shallow`, only files directly inside the folder are counted,
+    /// otherwise every file in the subtree is.
+    pub async fn dir_stats<'a>(
+        path: &EntryPath,
+        shallow: bool,
+        executor: &mut UnifiedExecutor<'a>,
+    ) -> Result<EntryDirStats, sqlx::Error> {
+        let mut dir_path = path.path().to_string();
+        if !dir_path.ends_with("/") {
+            // Make sure the path is a folder
+            dir_path.push('/');
+        }
+
+        let con = executor.get_con().await?;
+        let row: PgRow = sqlx::query(
+            r#"
+            SELECT
+                COUNT(entries.id) AS file_count,
+                COALESCE(SUM(entries.content_length), 0)::BIGINT AS total_bytes,
+                MIN(entries.modified_at) AS oldest_modified_at,
+                MAX(entries.modified_at) AS newest_modified_at
+            FROM entries
+            JOIN users
+              ON users.id = entries."user"
+            WHERE users.public_key = $1
+              AND substr(entries.path, 1, length($2)) = $2
+              AND (NOT $3 OR strpos(substr(entries.path, length($2) + 1), '/') = 0)
+              AND (entries.expires_at IS NULL OR entries.expires_at > $4)
+            "#,
+        )
+        .bind(path.pubkey().z32())
+        .bind(&dir_path)
+        .bind(shallow)
+        .bind(Utc::now().naive_utc())
+        .fetch_one(con)
+        .await?;
+
+        let file_count: i64 = row.try_get("file_count")?;
+        let total_bytes: i64 = row.try_get("total_bytes")?;
+        Ok(EntryDirStats {
+            file_count: file_count as u64,
+            total_bytes: total_bytes as u64,
+            oldest_modified_at: row.try_get("oldest_modified_at")?,
+            newest_modified_at: row.try_get("newest_modified_at")?,
+        })
+    }
+
     /// Check if writing `path` would make an exact file path collide with an
     /// implicit folder path for the same user.
     pub async fn has_file_folder_collision<'a>(
@@ -298,11 +527,14 @@ impl EntryRepository {
     /// Path is the path to the folder.
     /// Limit is the maximum number of entries to return.
     /// Cursor is path of the entry to start from. Set it to None to start from the beginning.
+    /// With `modified_since`, only files modified at or after it are considered, so a
+    /// folder is only listed if it contains at least one of them.
     pub async fn list_shallow<'a>(
         path: &EntryPath,
         limit: Option<u16>,
         cursor: Option<EntryPath>,
         reverse: bool,
+        modified_since: Option<NaiveDateTime>,
         executor: &mut UnifiedExecutor<'a>,
     ) -> Result<Vec<EntryPath>, sqlx::Error> {
         let mut dir_path = path.path().to_string();
@@ -313,7 +545,7 @@ impl EntryRepository {
         // Use this regex to get the distinct paths
         // ^(?'fixed_directory'\/test\/)(?'path_segment'[^\/]*)(?'opt_slash_indicating_dir'\/?)(?'rest_of_path'.*)$
         // DISTINCT ON makes sure that the same path is only returned once.
-        let inner_statement = Query::select()
+        let mut inner_statement = Query::select()
             .from(ENTRY_TABLE)
             .expr(Expr::cust_with_values(
                 "DISTINCT ON (regpath) regexp_replace(entries.path, '^'||$1||'([^/]*)(\\/?)(.*)?$', $1||'\\1'||'\\2') as regpath",
@@ -325,7 +557,13 @@ impl EntryRepository {
             )
             .and_where(Expr::col((ENTRY_TABLE, EntryIden::Path)).like(format!("{}%", dir_path))) // Everything that starts with the path
             .and_where(Expr::col((USER_TABLE, UserIden::PublicKey)).eq(path.pubkey().z32()))
+            .and_where(Self::not_expired())
             .to_owned();
+        if let Some(since) = modified_since {
+            inner_statement = inner_statement
+                .and_where(Self::modified_since(since))
+                .to_owned();
+        }
 
         // Use a select in select to filter the previous regex regpath
         // to make the cursor and limit work.
@@ -389,11 +627,13 @@ impl EntryRepository {
     /// Path is the path to the folder.
     /// Limit is the maximum number of entries to return.
     /// Cursor is the id of the entry to start from (non-inclusive). Set it to None to start from the beginning.
+    /// With `modified_since`, only files modified at or after it are returned.
     pub async fn list_deep<'a>(
         path: &EntryPath,
         limit: Option<u16>,
         cursor: Option<EntryPath>,
         reverse: bool,
+        modified_since: Option<NaiveDateTime>,
         executor: &mut UnifiedExecutor<'a>,
     ) -> Result<Vec<EntryPath>, sqlx::Error> {
         let mut full_path = path.path().to_string();
@@ -412,7 +652,11 @@ impl EntryRepository {
             )
             .and_where(Expr::col((ENTRY_TABLE, EntryIden::Path)).like(format!("{}%", full_path))) // Everything that starts with the path
             .and_where(Expr::col((USER_TABLE, UserIden::PublicKey)).eq(path.pubkey().z32()))
+            .and_where(Self::not_expired())
             .to_owned();
+        if let Some(since) = modified_since {
+            statement = statement.and_where(Self::modified_since(since)).to_owned();
+        }
 
         if reverse {
             statement = statement
@@ -476,6 +720,8 @@ pub enum EntryIden {
     ContentType,
     ModifiedAt,
     CreatedAt,
+    ExpiresAt,
+    Filename,
 }
 
 #[cfg(test)]
@@ -705,10 +951,16 @@ mod tests {
 
         // Test list shallow basic
         let entry_path = EntryPath::new(user_pubkey.clone(), WebDavPath::new("/test/").unwrap());
-        let entries =
-            EntryRepository::list_shallow(&entry_path, None, None, false, &mut db.pool().into())
-                .await
-                .unwrap();
+        let entries = EntryRepository::list_shallow(
+            &entry_path,
+            None,
+            None,
+            false,
+            None,
+            &mut db.pool().into(),
+        )
+        .await
+        .unwrap();
         assert_eq!(entries.len(), 6);
         assert_eq!(
             entries[0],
@@ -736,10 +988,16 @@ mod tests {
         );
 
         // Test list shallow with limit
-        let entries =
-            EntryRepository::list_shallow(&entry_path, Some(2), None, false, &mut db.pool().into())
-                .await
-                .unwrap();
+        let entries = EntryRepository::list_shallow(
+            &entry_path,
+            Some(2),
+            None,
+            false,
+            None,
+            &mut db.pool().into(),
+        )
+        .await
+        .unwrap();
         assert_eq!(entries.len(), 2);
         assert_eq!(
             entries[0],
@@ -759,6 +1017,7 @@ mod tests {
                 WebDavPath::new("/test/3.txt").unwrap(),
             )),
             false,
+            None,
             &mut db.pool().into(),
         )
         .await
@@ -786,6 +1045,7 @@ mod tests {
                 WebDavPath::new("/test/3.txt").unwrap(),
             )),
             false,
+            None,
             &mut db.pool().into(),
         )
         .await
@@ -811,6 +1071,7 @@ mod tests {
                 Some(2),
                 last_cursor,
                 false,
+                None,
                 &mut db.pool().into(),
             )
             .await
@@ -866,10 +1127,16 @@ mod tests {
 
         // Regular order aka reverse false
         let entry_path = EntryPath::new(user_pubkey.clone(), WebDavPath::new("/test/").unwrap());
-        let entries =
-            EntryRepository::list_shallow(&entry_path, None, None, false, &mut db.pool().into())
-                .await
-                .unwrap();
+        let entries = EntryRepository::list_shallow(
+            &entry_path,
+            None,
+            None,
+            false,
+            None,
+            &mut db.pool().into(),
+        )
+        .await
+        .unwrap();
         assert_eq!(entries.len(), 6);
         assert_eq!(
             entries[0],
@@ -898,10 +1165,16 @@ mod tests {
 
         // Reverse order aka reverse true
         let entry_path = EntryPath::new(user_pubkey.clone(), WebDavPath::new("/test/").unwrap());
-        let entries =
-            EntryRepository::list_shallow(&entry_path, None, None, true, &mut db.pool().into())
-                .await
-                .unwrap();
+        let entries = EntryRepository::list_shallow(
+            &entry_path,
+            None,
+            None,
+            true,
+            None,
+            &mut db.pool().into(),
+        )
+        .await
+        .unwrap();
         assert_eq!(entries.len(), 6);
         assert_eq!(
             entries[5],
@@ -936,6 +1209,7 @@ mod tests {
             None,
             Some(cursor),
             true,
+            None,
             &mut db.pool().into(),
         )
         .await
@@ -987,16 +1261,22 @@ mod tests {
         // Test basic
         let entry_path = EntryPath::new(user_pubkey.clone(), WebDavPath::new("/test/").unwrap());
         let entries =
-            EntryRepository::list_deep(&entry_path, None, None, false, &mut db.pool().into())
+            EntryRepository::list_deep(&entry_path, None, None, false, None, &mut db.pool().into())
                 .await
                 .unwrap();
         assert_eq!(entries.len(), 7);
 
         // Test with limit
-        let entries =
-            EntryRepository::list_shallow(&entry_path, Some(2), None, false, &mut db.pool().into())
-                .await
-                .unwrap();
+        let entries = EntryRepository::list_shallow(
+            &entry_path,
+            Some(2),
+            None,
+            false,
+            None,
+            &mut db.pool().into(),
+        )
+        .await
+        .unwrap();
         assert_eq!(entries.len(), 2);
         assert_eq!(
             entries[0],
@@ -1016,6 +1296,7 @@ mod tests {
                 WebDavPath::new("/test/3.txt").unwrap(),
             )),
             false,
+            None,
             &mut db.pool().into(),
         )
         .await
@@ -1059,6 +1340,7 @@ mod tests {
                 WebDavPath::new("/test/3.txt").unwrap(),
             )),
             false,
+            None,
             &mut db.pool().into(),
         )
         .await
@@ -1088,6 +1370,7 @@ mod tests {
                 Some(2),
                 last_cursor.clone(),
                 false,
+                None,
                 &mut db.pool().into(),
             )
             .await
@@ -1140,7 +1423,7 @@ mod tests {
         // Reverse order aka reverse true
         let entry_path = EntryPath::new(user_pubkey.clone(), WebDavPath::new("/test/").unwrap());
         let entries =
-            EntryRepository::list_deep(&entry_path, None, None, true, &mut db.pool().into())
+            EntryRepository::list_deep(&entry_path, None, None, true, None, &mut db.pool().into())
                 .await
                 .unwrap();
         assert_eq!(entries.len(), 7);
@@ -1193,6 +1476,7 @@ mod tests {
             None,
             Some(cursor),
             true,
+            None,
             &mut db.pool().into(),
         )
         .await
@@ -1208,6 +1492,75 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn test_list_modified_since() {
+        let db = SqlDb::test().await;
+        let user_pubkey = Keypair::random().public_key();
+        let user = UserRepository::create(&user_pubkey, &mut db.pool().into())
+            .await
+            .unwrap();
+        create_entry_for_path(&db, user.id, "/test/1.txt").await;
+        create_entry_for_path(&db, user.id, "/test/sub1/2.txt").await;
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        create_entry_for_path(&db, user.id, "/test/3.txt").await;
+        create_entry_for_path(&db, user.id, "/test/sub2/4.txt").await;
+
+        let since = EntryRepository::get_by_path(
+            &EntryPath::new(user_pubkey.clone(), WebDavPath::new("/test/3.txt").unwrap()),
+            &mut db.pool().into(),
+        )
+        .await
+        .unwrap()
+        .modified_at;
+        let entry_path = EntryPath::new(user_pubkey.clone(), WebDavPath::new("/test/").unwrap());
+        let as_paths = |entries: Vec<EntryPath>| {
+            entries
+                .iter()
+                .map(|entry| entry.path().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        // The bound is inclusive, so the entry modified exactly at `since` is listed.
+        let deep = EntryRepository::list_deep(
+            &entry_path,
+            None,
+            None,
+            false,
+            Some(since),
+            &mut db.pool().into(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(as_paths(deep), vec!["/test/3.txt", "/test/sub2/4.txt"]);
+
+        // Folders are only listed if they hold a matching file.
+        let shallow = EntryRepository::list_shallow(
+            &entry_path,
+            None,
+            None,
+            false,
+            Some(since),
+            &mut db.pool().into(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(as_paths(shallow), vec!["/test/3.txt", "/test/sub2/"]);
+
+        let later = since + chrono::Duration::microseconds(1);
+        let deep = EntryRepository::list_deep(
+            &entry_path,
+            None,
+            None,
+            false,
+            Some(later),
+            &mut db.pool().into(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(as_paths(deep), vec!["/test/sub2/4.txt"]);
+    }
+
     #[tokio::test]
     #[pubky_test_utils::test]
     async fn test_contains_directory() {
@@ -1266,4 +1619,101 @@ mod tests {
         .unwrap();
         assert!(!exists);
     }
+
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn test_dir_stats() {
+        let db = SqlDb::test().await;
+        let user_pubkey = Keypair::random().public_key();
+        let user = UserRepository::create(&user_pubkey, &mut db.pool().into())
+            .await
+            .unwrap();
+        let other_user =
+            UserRepository::create(&Keypair::random().public_key(), &mut db.pool().into())
+                .await
+                .unwrap();
+
+        let entries = [
+            (user.id, "/test/1.txt", 10),
+            (user.id, "/test/2.txt", 20),
+            (user.id, "/test/sub/3.txt", 30),
+            (user.id, "/test_sibling/4.txt", 40),
+            (other_user.id, "/test/5.txt", 50),
+        ];
+        for (user_id, path, length) in entries {
+            EntryRepository::create(
+                user_id,
+                &WebDavPath::new(path).unwrap(),
+                &pubky_common::crypto::Hash::from_bytes([0; 32]),
+                length,
+                "text/plain",
+                &mut db.pool().into(),
+            )
+            .await
+            .unwrap();
+        }
+
+        let dir = EntryPath::new(user_pubkey.clone(), WebDavPath::new("/test/").unwrap());
+        let deep = EntryRepository::dir_stats(&dir, false, &mut db.pool().into())
+            .await
+            .unwrap();
+        assert_eq!(deep.file_count, 3);
+        assert_eq!(deep.total_bytes, 60);
+        assert!(deep.oldest_modified_at.unwrap() <= deep.newest_modified_at.unwrap());
+
+        let shallow = EntryRepository::dir_stats(&dir, true, &mut db.pool().into())
+            .await
+            .unwrap();
+        assert_eq!(shallow.file_count, 2);
+        assert_eq!(shallow.total_bytes, 30);
+
+        let empty = EntryPath::new(user_pubkey, WebDavPath::new("/nope/").unwrap());
+        let empty = EntryRepository::dir_stats(&empty, false, &mut db.pool().into())
+            .await
+            .unwrap();
+        assert_eq!(empty.file_count, 0);
+        assert_eq!(empty.total_bytes, 0);
+        assert_eq!(empty.newest_modified_at, None);
+    }
+
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn test_delete_dir() {
+        let db = SqlDb::test().await;
+        let user_pubkey = Keypair::random().public_key();
+        let user = UserRepository::create(&user_pubkey, &mut db.pool().into())
+            .await
+            .unwrap();
+        let other_pubkey = Keypair::random().public_key();
+        let other = UserRepository::create(&other_pubkey, &mut db.pool().into())
+            .await
+            .unwrap();
+        create_entry_for_path(&db, user.id, "/test/b.txt").await;
+        create_entry_for_path(&db, user.id, "/test/a/1.txt").await;
+        create_entry_for_path(&db, user.id, "/test2/c.txt").await;
+        create_entry_for_path(&db, other.id, "/test/d.txt").await;
+
+        let deleted = EntryRepository::delete_dir(
+            user.id,
+            &WebDavPath::new("/test/").unwrap(),
+            &mut db.pool().into(),
+        )
+        .await
+        .unwrap();
+        let deleted: Vec<_> = deleted
+            .iter()
+            .map(|(path, length)| (path.as_str(), *length))
+            .collect();
+        assert_eq!(deleted, vec![("/test/a/1.txt", 100), ("/test/b.txt", 100)]);
+
+        // Siblings sharing the prefix and other users are untouched.
+        let sibling = EntryPath::new(user_pubkey, WebDavPath::new("/test2/c.txt").unwrap());
+        EntryRepository::get_by_path(&sibling, &mut db.pool().into())
+            .await
+            .unwrap();
+        let foreign = EntryPath::new(other_pubkey, WebDavPath::new("/test/d.txt").unwrap());
+        EntryRepository::get_by_path(&foreign, &mut db.pool().into())
+            .await
+            .unwrap();
+    }
 }
@@ -5,7 +5,11 @@
 //! - [`user`]: User accounts keyed by Ed25519 public key, with quota tracking.
 //! - [`entry`]: File metadata (path, content hash, MIME type, timestamps).
 //! - [`signup_code`]: Token-gated registration codes.
+//! - [`blob`]: Content-addressed blobs and the storage paths referencing them.
+//! - [`server_setting`]: Server-wide settings changed at runtime.
 
+pub mod blob;
 pub mod entry;
+pub mod server_setting;
 pub mod signup_code;
 pub mod user;
@@ -0,0 +1,101 @@
+use sea_query::Iden;
+
+use crate::persistence::sql::UnifiedExecutor;
+
+pub const SERVER_SETTING_TABLE: &str = "server_settings";
+
+/// Repository for server-wide settings changed at runtime, stored as key/value text pairs.
+pub struct ServerSettingRepository;
+
+impl ServerSettingRepository {
+    /// Get the value stored for `key`, if any.
+    /// The executor can either be db.pool() or a transaction.
+    pub async fn get<'a>(
+        key: &str,
+        executor: &mut UnifiedExecutor<'a>,
+    ) -> Result<Option<String>, sqlx::Error> {
+        let con = executor.get_con().await?;
+        sqlx::query_scalar("SELECT value FROM server_settings WHERE key = $1")
+            .bind(key)
+            .fetch_optional(con)
+            .await
+    }
+
+    /// Store `value` for `key`, replacing any previous value.
+    /// The executor can either be db.pool() or a transaction.
+    pub async fn set<'a>(
+        key: &str,
+        value: &str,
+        executor: &mut UnifiedExecutor<'a>,
+    ) -> Result<(), sqlx::Error> {
+        let con = executor.get_con().await?;
+        sqlx::query(
+            r#"
+            INSERT INTO server_settings (key, value)
+            VALUES ($1, $2)
+            ON CONFLICT (key) DO UPDATE SET value = EXCLUDED.value, updated_at = now()
+            "#,
+        )
+        .bind(key)
+        .bind(value)
+        .execute(con)
+        .await?;
+        Ok(())
+    }
+
+    /// Remove the value stored for `key`, if any.
+    /// The executor can either be db.pool() or a transaction.
+    pub async fn delete<'a>(
+        key: &str,
+        executor: &mut UnifiedExecutor<'a>,
+    ) -> Result<(), sqlx::Error> {
+        let con = executor.get_con().await?;
+        sqlx::query("DELETE FROM server_settings WHERE key = $1")
+            .bind(key)
+            .execute(con)
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+pub enum ServerSettingIden {
+    Key,
+    Value,
+    UpdatedAt,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::sql::SqlDb;
+
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn test_set_and_get() {
+        let db = SqlDb::test().await;
+        let value = ServerSettingRepository::get("a", &mut db.pool().into())
+            .await
+            .unwrap();
+        assert_eq!(value, None);
+
+        ServerSettingRepository::set("a", "1", &mut db.pool().into())
+            .await
+            .unwrap();
+        ServerSettingRepository::set("a", "2", &mut db.pool().into())
+            .await
+            .unwrap();
+        let value = ServerSettingRepository::get("a", &mut db.pool().into())
+            .await
+            .unwrap();
+        assert_eq!(value.as_deref(), Some("2"));
+
+        ServerSettingRepository::delete("a", &mut db.pool().into())
+            .await
+            .unwrap();
+        let value = ServerSettingRepository::get("a", &mut db.pool().into())
+            .await
+            .unwrap();
+        assert_eq!(value, None);
+    }
+}
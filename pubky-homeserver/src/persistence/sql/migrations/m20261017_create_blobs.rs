@@ -0,0 +1,95 @@
+use async_trait::async_trait;
+use sea_query::{ColumnDef, Expr, ForeignKey, ForeignKeyAction, PostgresQueryBuilder, Table};
+use sqlx::Transaction;
+
+use crate::persistence::sql::{
+    blob::{BlobIden, BlobRefIden, BLOB_REF_TABLE, BLOB_TABLE},
+    migration::MigrationTrait,
+};
+
+/// Creates the tables backing content-addressed blob deduplication.
+pub struct M20261017CreateBlobsMigration;
+
+#[async_trait]
+impl MigrationTrait for M20261017CreateBlobsMigration {
+    async fn up(&self, tx: &mut Transaction<'static, sqlx::Postgres>) -> anyhow::Result<()> {
+        self.create_blobs_table(tx).await?;
+        self.create_blob_refs_table(tx).await?;
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "m20261017_create_blobs"
+    }
+}
+
+impl M20261017CreateBlobsMigration {
+    async fn create_blobs_table(
+        &self,
+        tx: &mut Transaction<'static, sqlx::Postgres>,
+    ) -> anyhow::Result<()> {
+        let statement = Table::create()
+            .table(BLOB_TABLE)
+            .if_not_exists()
+            .col(
+                ColumnDef::new(BlobIden::Hash)
+                    .blob()
+                    .not_null()
+                    .primary_key(),
+            )
+            .col(ColumnDef::new(BlobIden::StorageKey).text().not_null())
+            .col(
+                ColumnDef::new(BlobIden::ContentLength)
+                    .big_integer()
+                    .not_null(),
+            )
+            .col(ColumnDef::new(BlobIden::RefCount).big_integer().not_null())
+            .col(
+                ColumnDef::new(BlobIden::CreatedAt)
+                    .timestamp()
+                    .not_null()
+                    .default(Expr::current_timestamp()),
+            )
+            .to_owned();
+        let query = statement.build(PostgresQueryBuilder);
+        sqlx::query(query.as_str()).execute(&mut **tx).await?;
+        Ok(())
+    }
+
+    async fn create_blob_refs_table(
+        &self,
+        tx: &mut Transaction<'static, sqlx::Postgres>,
+    ) -> anyhow::Result<()> {
+        let statement = Table::create()
+            .table(BLOB_REF_TABLE)
+            .if_not_exists()
+            .col(
+                ColumnDef::new(BlobRefIden::Path)
+                    .text()
+                    .not_null()
+                    .primary_key(),
+            )
+            .col(ColumnDef::new(BlobRefIden::Hash).blob().not_null())
+            .to_owned();
+        let query = statement.build(PostgresQueryBuilder);
+        sqlx::query(query.as_str()).execute(&mut **tx).await?;
+
+        // Foreign key: hash → blobs.hash. Refs must be removed before their blob is.
+        let fk = ForeignKey::create()
+            .name("fk_blob_ref_hash")
+            .from(BLOB_REF_TABLE, BlobRefIden::Hash)
+            .to(BLOB_TABLE, BlobIden::Hash)
+            .on_delete(ForeignKeyAction::Restrict)
+            .to_owned();
+        let query = fk.build(PostgresQueryBuilder);
+        sqlx::query(query.as_str()).execute(&mut **tx).await?;
+
+        // Listing a directory scans refs by path prefix.
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_blob_refs_path_prefix ON blob_refs (path text_pattern_ops)",
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+}
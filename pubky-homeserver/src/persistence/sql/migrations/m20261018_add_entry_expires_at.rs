@@ -0,0 +1,26 @@
+use async_trait::async_trait;
+use sqlx::Transaction;
+
+use crate::persistence::sql::migration::MigrationTrait;
+
+/// Adds the optional expiry deadline of an entry and an index for the expiry sweeper.
+pub struct M20261018AddEntryExpiresAtMigration;
+
+#[async_trait]
+impl MigrationTrait for M20261018AddEntryExpiresAtMigration {
+    async fn up(&self, tx: &mut Transaction<'static, sqlx::Postgres>) -> anyhow::Result<()> {
+        sqlx::query("ALTER TABLE entries ADD COLUMN IF NOT EXISTS expires_at TIMESTAMP")
+            .execute(&mut **tx)
+            .await?;
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_entries_expires_at ON entries (expires_at) WHERE expires_at IS NOT NULL",
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "m20261018_add_entry_expires_at"
+    }
+}
@@ -0,0 +1,41 @@
+use async_trait::async_trait;
+use sea_query::{ColumnDef, Expr, PostgresQueryBuilder, Table};
+use sqlx::Transaction;
+
+use crate::persistence::sql::{
+    migration::MigrationTrait,
+    server_setting::{ServerSettingIden, SERVER_SETTING_TABLE},
+};
+
+/// Creates the table holding server settings changed at runtime.
+pub struct M20261019CreateServerSettingsMigration;
+
+#[async_trait]
+impl MigrationTrait for M20261019CreateServerSettingsMigration {
+    async fn up(&self, tx: &mut Transaction<'static, sqlx::Postgres>) -> anyhow::Result<()> {
+        let statement = Table::create()
+            .table(SERVER_SETTING_TABLE)
+            .if_not_exists()
+            .col(
+                ColumnDef::new(ServerSettingIden::Key)
+                    .text()
+                    .not_null()
+                    .primary_key(),
+            )
+            .col(ColumnDef::new(ServerSettingIden::Value).text().not_null())
+            .col(
+                ColumnDef::new(ServerSettingIden::UpdatedAt)
+                    .timestamp()
+                    .not_null()
+                    .default(Expr::current_timestamp()),
+            )
+            .to_owned();
+        let query = statement.build(PostgresQueryBuilder);
+        sqlx::query(query.as_str()).execute(&mut **tx).await?;
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "m20261019_create_server_settings"
+    }
+}
@@ -0,0 +1,21 @@
+use async_trait::async_trait;
+use sqlx::Transaction;
+
+use crate::persistence::sql::migration::MigrationTrait;
+
+/// Adds the optional download filename of an entry, sent back as `Content-Disposition`.
+pub struct M20261020AddEntryFilenameMigration;
+
+#[async_trait]
+impl MigrationTrait for M20261020AddEntryFilenameMigration {
+    async fn up(&self, tx: &mut Transaction<'static, sqlx::Postgres>) -> anyhow::Result<()> {
+        sqlx::query("ALTER TABLE entries ADD COLUMN IF NOT EXISTS filename TEXT")
+            .execute(&mut **tx)
+            .await?;
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "m20261020_add_entry_filename"
+    }
+}
@@ -8,6 +8,10 @@ pub(crate) mod m20260325_create_grant_sessions;
 mod m20260327_add_quota_columns;
 mod m20260507_add_allowed_write_paths;
 mod m20260609_add_signup_code_used_at;
+mod m20261017_create_blobs;
+mod m20261018_add_entry_expires_at;
+mod m20261019_create_server_settings;
+mod m20261020_add_entry_filename;
 
 pub(crate) use m20250806_create_user::M20250806CreateUserMigration;
 pub(crate) use m20250812_create_signup_code::M20250812CreateSignupCodeMigration;
@@ -19,3 +23,7 @@ pub(crate) use m20260325_create_grant_sessions::M20260325CreateGrantSessionsMigr
 pub(crate) use m20260327_add_quota_columns::M20260327AddQuotaColumnsMigration;
 pub(crate) use m20260507_add_allowed_write_paths::M20260507AddAllowedWritePathsMigration;
 pub(crate) use m20260609_add_signup_code_used_at::M20260609AddSignupCodeUsedAtMigration;
+pub(crate) use m20261017_create_blobs::M20261017CreateBlobsMigration;
+pub(crate) use m20261018_add_entry_expires_at::M20261018AddEntryExpiresAtMigration;
+pub(crate) use m20261019_create_server_settings::M20261019CreateServerSettingsMigration;
+pub(crate) use m20261020_add_entry_filename::M20261020AddEntryFilenameMigration;
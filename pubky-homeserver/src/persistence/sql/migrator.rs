@@ -10,6 +10,8 @@ use crate::persistence::sql::{
         M20250815CreateEntryMigration, M20251014EventsTableIndexAndContentHashMigration,
         M20260325CreateGrantSessionsMigration, M20260327AddQuotaColumnsMigration,
         M20260507AddAllowedWritePathsMigration, M20260609AddSignupCodeUsedAtMigration,
+        M20261017CreateBlobsMigration, M20261018AddEntryExpiresAtMigration,
+        M20261019CreateServerSettingsMigration, M20261020AddEntryFilenameMigration,
     },
     sql_db::SqlDb,
 };
@@ -43,6 +45,10 @@ impl<'a> Migrator<'a> {
             Box::new(M20260327AddQuotaColumnsMigration),
             Box::new(M20260507AddAllowedWritePathsMigration),
             Box::new(M20260609AddSignupCodeUsedAtMigration),
+            Box::new(M20261017CreateBlobsMigration),
+            Box::new(M20261018AddEntryExpiresAtMigration),
+            Box::new(M20261019CreateServerSettingsMigration),
+            Box::new(M20261020AddEntryFilenameMigration),
         ]
     }
 
@@ -14,7 +14,7 @@
 //! but is the correct trade-off for horizontal scalability - every instance sees every
 //! event without needing its own broadcast path.
 
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
 use std::time::Duration;
 
 use sqlx::{postgres::PgListener, PgPool};
@@ -44,8 +44,8 @@ const _: () = assert!(
 /// always the source of truth - events are read sequentially by ID, guaranteeing
 /// no gaps even if NOTIFYs are lost.
 pub struct PgEventListener {
-    poll_handle: Option<JoinHandle<()>>,
-    listen_handle: Option<JoinHandle<()>>,
+    /// The poll and LISTEN tasks, until shut down.
+    handles: Mutex<Vec<JoinHandle<()>>>,
     cancel: CancellationToken,
 }
 
@@ -126,8 +126,7 @@ impl PgEventListener {
         };
 
         Ok(Self {
-            poll_handle: Some(poll_handle),
-            listen_handle: Some(listen_handle),
+            handles: Mutex::new(vec![poll_handle, listen_handle]),
             cancel,
         })
     }
@@ -267,16 +266,35 @@ impl PgEventListener {
     }
 }
 
+impl PgEventListener {
+    /// Stop both tasks and wait until they have finished.
+    ///
+    /// Dropping the listener stops them too, without waiting.
+    pub async fn shutdown(&self) {
+        tracing::info!("PgEventListener shutting down");
+        self.cancel.cancel();
+        let handles = std::mem::take(&mut *self.lock_handles());
+        for handle in handles {
+            let _ = handle.await;
+        }
+    }
+
+    fn lock_handles(&self) -> MutexGuard<'_, Vec<JoinHandle<()>>> {
+        self.handles.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
 impl Drop for PgEventListener {
     fn drop(&mut self) {
+        let handles = std::mem::take(&mut *self.lock_handles());
+        if handles.is_empty() {
+            return;
+        }
         tracing::info!("PgEventListener shutting down");
         // Signal both tasks to exit their loops gracefully.
         self.cancel.cancel();
         // Abort as a fallback in case a task is blocked on a non-cancellation-aware future.
-        if let Some(handle) = self.poll_handle.take() {
-            handle.abort();
-        }
-        if let Some(handle) = self.listen_handle.take() {
+        for handle in handles {
             handle.abort();
         }
     }
@@ -448,6 +466,25 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn test_shutdown_joins_tasks() {
+        let db = SqlDb::test().await;
+        let listener = PgEventListener::start(db.pool(), EventsService::new(100))
+            .await
+            .unwrap();
+        let tasks: Vec<_> = listener
+            .lock_handles()
+            .iter()
+            .map(|handle| handle.abort_handle())
+            .collect();
+        assert_eq!(tasks.len(), 2);
+        assert!(tasks.iter().all(|task| !task.is_finished()));
+
+        listener.shutdown().await;
+        assert!(tasks.iter().all(|task| task.is_finished()));
+    }
+
     /// Test that get_max_id returns 0 on an empty table and the correct ID after inserts.
     #[tokio::test]
     #[pubky_test_utils::test]
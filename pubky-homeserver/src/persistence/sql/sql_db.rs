@@ -1,4 +1,4 @@
-use sqlx::postgres::PgPool;
+use sqlx::postgres::{PgPool, PgPoolOptions};
 
 use crate::persistence::sql::connection_string::ConnectionString;
 
@@ -14,6 +14,9 @@ use crate::persistence::sql::connection_string::ConnectionString;
 pub struct SqlDb {
     /// Connection pool to the database
     pool: PgPool,
+    /// Connections that hold advisory locks while a write runs on [`Self::pool`].
+    /// Kept apart, so lock holders never wait for connections other lock holders hold.
+    lock_pool: PgPool,
     /// Test helper for postgres to drop the test database after the test
     #[cfg(any(test, feature = "testing"))]
     db_dropper: Option<std::sync::Arc<TestDbDropper>>,
@@ -39,8 +42,12 @@ impl SqlDb {
     /// Connect to the database. directly without any test db logic.
     async fn connect_inner(con_string: &ConnectionString) -> Result<Self, sqlx::Error> {
         let pool: PgPool = PgPool::connect(con_string.as_str()).await?;
+        let lock_pool = PgPoolOptions::new()
+            .max_connections(pool.options().get_max_connections())
+            .connect_lazy_with(pool.connect_options().as_ref().clone());
         Ok(Self {
             pool,
+            lock_pool,
             #[cfg(any(test, feature = "testing"))]
             db_dropper: None,
         })
@@ -50,6 +57,13 @@ impl SqlDb {
     pub fn pool(&self) -> &PgPool {
         &self.pool
     }
+
+    /// Get the connection pool for transactions that only hold advisory locks.
+    ///
+    /// Queries that other lock holders may wait for must use [`Self::pool`] instead.
+    pub fn lock_pool(&self) -> &PgPool {
+        &self.lock_pool
+    }
 }
 
 /// Helper struct to drop the postgres test database after the db connection is dropped.
@@ -150,18 +164,18 @@ impl SqlDb {
             .expect("Default test connection string is valid")
     }
 
-    /// Create a test database without running migrations
-    /// If the DB_CONNECTION_STRING environment variable is not set, a temporary directory is used for the sqlite database
-    /// If the DB_CONNECTION_STRING environment variable is set, the test database is created on the existing database
+    /// Create a test database without running migrations.
+    /// The database is created on the postgres server named by the TEST_PUBKY_CONNECTION_STRING
+    /// environment variable, or on the default test connection string if it is not set.
+    /// There is no in-memory backend: a running postgres server is required.
     pub async fn test_without_migrations() -> Self {
         Self::test_postgres_db(None)
             .await
             .expect("Failed to create test database")
     }
 
-    /// Create a test database and run migrations
-    /// If the DB_CONNECTION_STRING environment variable is not set, a temporary directory is used for the sqlite database
-    /// If the DB_CONNECTION_STRING environment variable is set, the migrations are run on the existing database
+    /// Create a test database and run migrations.
+    /// See [`Self::test_without_migrations`] for where the database is created.
     pub async fn test() -> Self {
         use crate::persistence::sql::migrator::Migrator;
         let db = Self::test_without_migrations().await;
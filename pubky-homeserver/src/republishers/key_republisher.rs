@@ -3,23 +3,27 @@
 //! This task is started by the [crate::HomeserverCore] and runs until the homeserver is stopped.
 //!
 //! The task is responsible for:
-//! - Republishing the homeserver's pkarr packet to the DHT every hour.
+//! - Republishing the homeserver's pkarr packet to the DHT every `[pkdns.key_republisher].interval`.
+//! - Retrying failed publishes and recording the outcome in the metrics.
 //! - Stopping the task when the homeserver is stopped.
 
 use std::borrow::Cow;
 use std::net::IpAddr;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use backon::{ExponentialBuilder, Retryable};
 use pkarr::dns::Name;
-use pkarr::errors::PublishError;
 use pkarr::{
     dns::rdata::{SVCParam, SVCB},
     SignedPacket,
 };
 
 use crate::app_context::AppContext;
+use crate::observability::Metrics;
+use crate::KeyRepublisherToml;
 use tokio::task::JoinHandle;
-use tokio::time::{interval, Duration};
+use tokio::time::{interval, timeout, Duration};
 
 /// Errors that can occur when building a `Republishers`.
 #[derive(Debug, thiserror::Error)]
@@ -29,7 +33,7 @@ pub enum KeyRepublisherBuildError {
     KeyRepublisher(anyhow::Error),
 }
 
-/// Republishes the homeserver's pkarr packet to the DHT every hour.
+/// Republishes the homeserver's pkarr packet to the DHT periodically.
 pub(crate) struct HomeserverKeyRepublisher {
     join_handle: JoinHandle<()>,
 }
@@ -41,46 +45,37 @@ impl HomeserverKeyRepublisher {
         pubky_tls_port: u16,
     ) -> Result<Self> {
         let signed_packet = create_signed_packet(context, icann_http_port, pubky_tls_port)?;
-        let join_handle =
-            Self::start_periodic_republish(context.pkarr_client.clone(), &signed_packet).await?;
+        let publisher = KeyPublisher {
+            client: publish_client(context)?,
+            settings: context.config_toml.pkdns.key_republisher.clone(),
+            metrics: context.metrics.clone(),
+        };
+        let join_handle = Self::start_periodic_republish(publisher, &signed_packet).await?;
         Ok(Self { join_handle })
     }
 
-    async fn publish_once(
-        client: &pkarr::Client,
-        signed_packet: &SignedPacket,
-    ) -> Result<(), PublishError> {
-        let res = client.publish(signed_packet).await;
-        if let Err(e) = &res {
-            tracing::warn!("Failed to publish the homeserver's pkarr packet to the DHT: {e}",);
-        } else {
-            tracing::info!("Published the homeserver's pkarr packet to the DHT.");
-        }
-        res.map(|_| ())
-    }
-
-    /// Start the periodic republish task which will republish the server packet to the DHT every hour.
+    /// Start the periodic republish task which will republish the server packet to the DHT
+    /// every configured interval.
     ///
     /// # Errors
     /// - Throws an error if the initial publish fails.
-    /// - Throws an error if the periodic republish task is already running.
     async fn start_periodic_republish(
-        client: pkarr::Client,
+        publisher: KeyPublisher,
         signed_packet: &SignedPacket,
     ) -> anyhow::Result<JoinHandle<()>> {
         // Publish once to make sure the packet is published to the DHT before this
         // function returns.
         // Throws an error if the packet is not published to the DHT.
-        Self::publish_once(&client, signed_packet).await?;
+        publisher.publish(signed_packet).await?;
 
         // Start the periodic republish task.
         let signed_packet = signed_packet.clone();
         let handle = tokio::spawn(async move {
-            let mut interval = interval(Duration::from_secs(60 * 60)); // 1 hour in seconds
+            let mut interval = interval(Duration::from_secs(publisher.settings.interval.get()));
             interval.tick().await; // This ticks immediatly. Wait for first interval before starting the loop.
             loop {
                 interval.tick().await;
-                let _ = Self::publish_once(&client, &signed_packet).await;
+                let _ = publisher.publish(&signed_packet).await;
             }
         });
 
@@ -91,6 +86,12 @@ impl HomeserverKeyRepublisher {
     pub fn stop(&self) {
         self.join_handle.abort();
     }
+
+    /// Stop republishing and wait until the task has finished.
+    pub async fn shutdown(&mut self) {
+        self.stop();
+        let _ = (&mut self.join_handle).await;
+    }
 }
 
 impl Drop for HomeserverKeyRepublisher {
@@ -99,6 +100,83 @@ impl Drop for HomeserverKeyRepublisher {
     }
 }
 
+/// The pkarr client to publish with, limited to `max_relays` relays if configured.
+fn publish_client(context: &AppContext) -> Result<pkarr::Client> {
+    let settings = &context.config_toml.pkdns.key_republisher;
+    let (Some(max_relays), Some(relays)) = (
+        settings.max_relays,
+        &context.config_toml.pkdns.dht_relay_nodes,
+    ) else {
+        return Ok(context.pkarr_client.clone());
+    };
+    let mut builder = context.pkarr_builder.clone();
+    if max_relays == 0 {
+        builder.no_relays();
+    } else {
+        let relays = &relays[..max_relays.min(relays.len())];
+        builder.relays(relays)?;
+    }
+    Ok(builder.build()?)
+}
+
+/// Publishes the homeserver's packet with a timeout and retries.
+struct KeyPublisher {
+    client: pkarr::Client,
+    settings: KeyRepublisherToml,
+    metrics: Metrics,
+}
+
+impl KeyPublisher {
+    fn backoff(&self) -> ExponentialBuilder {
+        let max_retry_delay = Duration::from_millis(self.settings.max_retry_delay_ms);
+        ExponentialBuilder::default()
+            .with_factor(2.0)
+            .with_jitter()
+            .with_min_delay(
+                Duration::from_millis(self.settings.initial_retry_delay_ms).min(max_retry_delay),
+            )
+            .with_max_delay(max_retry_delay)
+            // BackON counts delayed retries; our setting counts the initial attempt too.
+            .with_max_times(usize::from(self.settings.max_attempts.get() - 1))
+    }
+
+    /// Publish the packet once, retrying failed attempts, and record the outcome.
+    async fn publish(&self, signed_packet: &SignedPacket) -> Result<()> {
+        let attempt_timeout = Duration::from_millis(self.settings.attempt_timeout_ms.get());
+        let max_retry_delay = Duration::from_millis(self.settings.max_retry_delay_ms);
+        let res = (|| async {
+            timeout(attempt_timeout, self.client.publish(signed_packet))
+                .await
+                .map_err(|_| anyhow!("publish timed out after {attempt_timeout:?}"))?
+                .map(|_| ())
+                .map_err(anyhow::Error::from)
+        })
+        .retry(self.backoff())
+        // BackON adds jitter after applying its maximum delay.
+        .adjust(|_, delay| delay.map(|delay| delay.min(max_retry_delay)))
+        .notify(|error, delay| {
+            tracing::debug!(%error, ?delay, "retrying publish of the homeserver's pkarr packet");
+        })
+        .await;
+
+        match &res {
+            Ok(()) => {
+                tracing::info!("Published the homeserver's pkarr packet to the DHT.");
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or_default();
+                self.metrics.record_key_republish_success(now);
+            }
+            Err(e) => {
+                tracing::warn!("Failed to publish the homeserver's pkarr packet to the DHT: {e}");
+                self.metrics.record_key_republish_failure();
+            }
+        }
+        res
+    }
+}
+
 pub fn create_signed_packet(
     context: &AppContext,
     local_icann_http_port: u16,
@@ -169,6 +247,7 @@ mod tests {
     use futures_lite::StreamExt;
     use pkarr::{extra::endpoints::Endpoint, ResolvePolicy};
     use std::net::{Ipv4Addr, SocketAddr};
+    use std::num::{NonZeroU64, NonZeroU8};
 
     use super::*;
     use crate::republishers::pkarr_republisher::test_client_builder;
@@ -208,6 +287,50 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn test_publish_records_metrics() {
+        let (context, _dht) = test_context().await;
+        let signed_packet = create_signed_packet(&context, 8080, 8080).unwrap();
+        let settings = KeyRepublisherToml {
+            max_attempts: NonZeroU8::new(2).unwrap(),
+            initial_retry_delay_ms: 10,
+            max_retry_delay_ms: 10,
+            ..Default::default()
+        };
+
+        let publisher = KeyPublisher {
+            client: context.pkarr_client.clone(),
+            settings: settings.clone(),
+            metrics: context.metrics.clone(),
+        };
+        publisher.publish(&signed_packet).await.unwrap();
+
+        let mut builder = pkarr::ClientBuilder::default();
+        builder
+            .no_default_network()
+            .relays(&["http://127.0.0.1:1"])
+            .unwrap();
+        let failing = KeyPublisher {
+            client: builder.build().unwrap(),
+            settings: KeyRepublisherToml {
+                attempt_timeout_ms: NonZeroU64::new(500).unwrap(),
+                ..settings
+            },
+            metrics: context.metrics.clone(),
+        };
+        assert!(failing.publish(&signed_packet).await.is_err());
+
+        let output = context.metrics.render().unwrap();
+        for name in [
+            crate::observability::KEY_REPUBLISH_SUCCESS_COUNT,
+            crate::observability::KEY_REPUBLISH_FAILURE_COUNT,
+            crate::observability::KEY_REPUBLISH_LAST_SUCCESS,
+        ] {
+            assert!(output.contains(name), "Missing {name} in: {output}");
+        }
+    }
+
     #[tokio::test]
     #[pubky_test_utils::test]
     async fn test_endpoints() {
@@ -69,6 +69,12 @@ impl UserKeysRepublisherJob {
         });
         Some(Self { handle })
     }
+
+    /// Stop republishing and wait until the task has finished.
+    pub async fn shutdown(&mut self) {
+        self.handle.abort();
+        let _ = (&mut self.handle).await;
+    }
 }
 
 impl Drop for UserKeysRepublisherJob {
@@ -126,8 +132,9 @@ mod tests {
     use crate::persistence::sql::user::UserRepository;
     use crate::persistence::sql::SqlDb;
     use crate::republishers::pkarr_republisher::test_client_builder;
-    use crate::republishers::user_keys_republisher::UserKeysRepublisher;
+    use crate::republishers::user_keys_republisher::{UserKeysRepublisher, UserKeysRepublisherJob};
     use pubky_common::crypto::Keypair;
+    use std::time::Duration;
 
     async fn init_db_with_users(count: usize) -> SqlDb {
         let db = SqlDb::test().await;
@@ -140,6 +147,20 @@ mod tests {
         db
     }
 
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn test_shutdown_joins_task() {
+        let db = SqlDb::test().await;
+        let mut job =
+            UserKeysRepublisherJob::start(db, pkarr::Client::builder(), Duration::from_secs(3600))
+                .unwrap();
+        let task = job.handle.abort_handle();
+        assert!(!task.is_finished());
+
+        job.shutdown().await;
+        assert!(task.is_finished());
+    }
+
     /// Test that the republisher tries to republish all keys passed.
     #[tokio::test]
     #[pubky_test_utils::test]
@@ -0,0 +1,326 @@
+//! Background deletion of entries whose expiry deadline has passed.
+//!
+//! Expired entries already read as not found (see
+//! [`FileService::get_info`](crate::persistence::files::FileService::get_info)); the
+//! sweeper reclaims their storage. Each entry is deleted in its own short transaction
+//! that also releases its content, updates the user's storage usage and emits the `DEL`
+//! event, and only if it still has the listed, expired version, so a file rewritten
+//! since it was listed is kept.
+//!
+//! Entries are deleted in batches of `[storage].expiry_sweep_batch_size`. Between
+//! batches the sweeper pauses for as long as the batch took, and it defers the rest of a
+//! sweep while every database connection is in use, so foreground requests keep
+//! priority. Failed sweeps are retried with an
+//! exponential backoff.
+
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use tokio::task::JoinHandle;
+
+use crate::{
+    app_context::AppContext,
+    observability::Metrics,
+    persistence::{
+        files::{FileIoError, FileService},
+        sql::{entry::EntryRepository, SqlDb},
+    },
+};
+
+/// Upper bound of the retry backoff after failed sweeps, as a multiple of the interval.
+const MAX_BACKOFF_FACTOR: u32 = 32;
+
+/// Periodically deletes expired entries. The task is aborted when this is dropped;
+/// [`Self::shutdown`] also waits for it to finish.
+pub(crate) struct ExpiredEntriesSweeper {
+    handle: JoinHandle<()>,
+}
+
+impl ExpiredEntriesSweeper {
+    /// Spawn the sweeper configured by `[storage].expiry_sweep_interval`.
+    /// Returns `None` if the interval is `0`.
+    pub fn start(context: &AppContext) -> Option<Self> {
+        let interval = Duration::from_secs(context.config_toml.storage.expiry_sweep_interval);
+        if interval.is_zero() {
+            tracing::info!("Expired entries sweeper is disabled.");
+            return None;
+        }
+        let sweeper = Sweeper::new(context);
+        let handle = tokio::spawn(async move {
+            let mut wait = interval;
+            loop {
+                tokio::time::sleep(wait).await;
+                match sweeper.sweep().await {
+                    Ok(report) => {
+                        wait = interval;
+                        if report.entries > 0 || report.deferred {
+                            tracing::debug!(?report, "Swept expired entries");
+                        }
+                    }
+                    Err(e) => {
+                        wait = (wait * 2).min(interval * MAX_BACKOFF_FACTOR);
+                        tracing::error!(
+                            "Failed to delete expired entries, retrying in {wait:?}: {e}"
+                        );
+                    }
+                }
+            }
+        });
+        Some(Self { handle })
+    }
+
+    /// Stop sweeping and wait until the task has finished.
+    pub async fn shutdown(&mut self) {
+        self.handle.abort();
+        let _ = (&mut self.handle).await;
+    }
+}
+
+impl Drop for ExpiredEntriesSweeper {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+/// Outcome of a single sweep.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct SweepReport {
+    /// Number of deleted entries.
+    pub entries: u64,
+    /// Content bytes of the deleted entries.
+    pub bytes: u64,
+    /// The sweep stopped early because the database was busy.
+    pub deferred: bool,
+}
+
+/// Deletes expired entries in batches and records the sweeper metrics.
+pub(crate) struct Sweeper {
+    db: SqlDb,
+    file_service: FileService,
+    metrics: Metrics,
+    batch_size: u64,
+}
+
+impl Sweeper {
+    pub fn new(context: &AppContext) -> Self {
+        Self {
+            db: context.sql_db.clone(),
+            file_service: context.file_service.clone(),
+            metrics: context.metrics.clone(),
+            batch_size: context.config_toml.storage.expiry_sweep_batch_size.get() as u64,
+        }
+    }
+
+    /// Delete the currently expired entries, batch by batch.
+    pub async fn sweep(&self) -> Result<SweepReport, FileIoError> {
+        let mut report = SweepReport::default();
+        let result = self.sweep_batches(&mut report).await;
+        // Entries deleted before a failure still count.
+        self.metrics
+            .record_expiry_sweep(report.entries, report.bytes);
+        if result.is_ok() {
+            self.metrics
+                .record_expiry_sweep_run(Utc::now().timestamp().max(0) as u64);
+        }
+        result.map(|()| report)
+    }
+
+    async fn sweep_batches(&self, report: &mut SweepReport) -> Result<(), FileIoError> {
+        loop {
+            if self.db_is_busy() {
+                report.deferred = true;
+                return Ok(());
+            }
+            let started = Instant::now();
+            let expired =
+                EntryRepository::list_expired(self.batch_size, &mut self.db.pool().into()).await?;
+            let batch_len = expired.len() as u64;
+            let mut batch_deleted = 0;
+            for entry in expired {
+                // Skipped if deleted or rewritten by the user or another instance since.
+                if self.file_service.delete_expired(&entry).await? {
+                    batch_deleted += 1;
+                    report.bytes += entry.content_length;
+                }
+            }
+            report.entries += batch_deleted;
+            // Stop on a partial batch, or when nothing could be deleted so the same rows
+            // would be listed again.
+            if batch_len < self.batch_size || batch_deleted == 0 {
+                return Ok(());
+            }
+            // Leave the database to foreground requests for as long as the batch took.
+            tokio::time::sleep(started.elapsed()).await;
+        }
+    }
+
+    /// Whether every pooled connection is checked out, i.e. requests are queueing.
+    fn db_is_busy(&self) -> bool {
+        let pool = self.db.pool();
+        pool.num_idle() == 0 && pool.size() >= pool.options().get_max_connections()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeDelta;
+    use opendal::Buffer;
+    use pubky_common::crypto::Keypair;
+
+    use super::*;
+    use crate::{
+        persistence::sql::user::UserRepository,
+        shared::webdav::{EntryPath, WebDavPath},
+    };
+
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn test_expired_entries_are_hidden_then_swept() {
+        let mut context = AppContext::test().await;
+        // Two batches for the two expired entries below.
+        context.config_toml.storage.expiry_sweep_batch_size = 1.try_into().unwrap();
+        let sweeper = Sweeper::new(&context);
+        let file_service = &context.file_service;
+        let db = context.sql_db.clone();
+        let pubkey = Keypair::random().public_key();
+        UserRepository::create(&pubkey, &mut db.pool().into())
+            .await
+            .unwrap();
+
+        let path = |p: &str| EntryPath::new(pubkey.clone(), WebDavPath::new(p).unwrap());
+        let expiring = [
+            path("/pub/app/expiring1.txt"),
+            path("/pub/app/expiring2.txt"),
+        ];
+        let permanent = path("/pub/app/permanent.txt");
+        let mut entries = Vec::new();
+        for expiring in &expiring {
+            let entry = file_service
+                .write(expiring, Buffer::from("soon gone"))
+                .await
+                .unwrap();
+            entries.push(entry);
+        }
+        file_service
+            .write(&permanent, Buffer::from("here to stay"))
+            .await
+            .unwrap();
+
+        // Not yet expired: reads and listings behave normally.
+        let deadline = Utc::now().naive_utc() + TimeDelta::hours(1);
+        for entry in &entries {
+            EntryRepository::set_expires_at(entry.id, Some(deadline), &mut db.pool().into())
+                .await
+                .unwrap();
+        }
+        let info = file_service
+            .get_info(&expiring[0], &mut db.pool().into())
+            .await
+            .unwrap();
+        assert!(info.expires_at.is_some() && !info.is_expired());
+        assert_eq!(sweeper.sweep().await.unwrap(), SweepReport::default());
+
+        // Past the deadline: hidden from reads and listings, then deleted by the sweep.
+        let deadline = Utc::now().naive_utc() - TimeDelta::seconds(1);
+        for entry in &entries {
+            EntryRepository::set_expires_at(entry.id, Some(deadline), &mut db.pool().into())
+                .await
+                .unwrap();
+        }
+        assert!(matches!(
+            file_service
+                .get_info(&expiring[0], &mut db.pool().into())
+                .await,
+            Err(FileIoError::NotFound)
+        ));
+        let listed = EntryRepository::list_deep(
+            &path("/pub/"),
+            None,
+            None,
+            false,
+            None,
+            &mut db.pool().into(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(listed, vec![permanent.clone()]);
+
+        let report = sweeper.sweep().await.unwrap();
+        assert_eq!(
+            report,
+            SweepReport {
+                entries: 2,
+                bytes: 2 * "soon gone".len() as u64,
+                deferred: false,
+            }
+        );
+        for expiring in &expiring {
+            assert!(!file_service.opendal.exists(expiring).await.unwrap());
+        }
+        assert!(file_service.opendal.exists(&permanent).await.unwrap());
+        assert_eq!(sweeper.sweep().await.unwrap(), SweepReport::default());
+
+        let metrics = context.metrics.render().unwrap();
+        assert!(metrics.contains("expiry_sweep_entries_count"), "{metrics}");
+        assert!(
+            metrics.contains("expiry_sweep_last_run_timestamp_seconds"),
+            "{metrics}"
+        );
+    }
+
+    /// An entry rewritten after the sweeper listed it is not deleted.
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn test_rewritten_entries_are_kept() {
+        let context = AppContext::test().await;
+        let file_service = &context.file_service;
+        let db = context.sql_db.clone();
+        let pubkey = Keypair::random().public_key();
+        UserRepository::create(&pubkey, &mut db.pool().into())
+            .await
+            .unwrap();
+        let path = EntryPath::new(pubkey.clone(), WebDavPath::new("/pub/app/a.txt").unwrap());
+
+        let entry = file_service
+            .write(&path, Buffer::from("soon gone"))
+            .await
+            .unwrap();
+        let deadline = Utc::now().naive_utc() - TimeDelta::seconds(1);
+        EntryRepository::set_expires_at(entry.id, Some(deadline), &mut db.pool().into())
+            .await
+            .unwrap();
+        let listed = EntryRepository::list_expired(10, &mut db.pool().into())
+            .await
+            .unwrap();
+        let listed = listed.into_iter().find(|e| e.id == entry.id).unwrap();
+
+        // Rewritten without an expiry deadline before the sweeper gets to it.
+        file_service
+            .write(&path, Buffer::from("here to stay"))
+            .await
+            .unwrap();
+        assert!(!file_service.delete_expired(&listed).await.unwrap());
+        assert_eq!(
+            file_service.get(&path).await.unwrap().as_ref(),
+            b"here to stay"
+        );
+
+        // Expired again, the rewrite is still not the listed version.
+        EntryRepository::set_expires_at(entry.id, Some(deadline), &mut db.pool().into())
+            .await
+            .unwrap();
+        assert!(!file_service.delete_expired(&listed).await.unwrap());
+
+        // Listed again, it is deleted along with its storage usage.
+        let listed = EntryRepository::list_expired(10, &mut db.pool().into())
+            .await
+            .unwrap();
+        let listed = listed.into_iter().find(|e| e.id == entry.id).unwrap();
+        assert!(file_service.delete_expired(&listed).await.unwrap());
+        assert!(!file_service.opendal.exists(&path).await.unwrap());
+        let user = UserRepository::get(&pubkey, &mut db.pool().into())
+            .await
+            .unwrap();
+        assert_eq!(user.used_bytes, 0);
+    }
+}
@@ -0,0 +1,328 @@
+//! Point-in-time snapshots for paginated directory listings.
+//!
+//! A regular paginated `LIST` re-reads the live `entries` table on every page, so
+//! concurrent writes can make a full iteration skip or repeat entries.
+//! A list snapshot pins the database state at the moment the first page is requested:
+//! the service keeps a `REPEATABLE READ` transaction open on a dedicated connection and
+//! exports its snapshot with `pg_export_snapshot()`. Every page of the listing then runs
+//! in a fresh read-only transaction that imports that snapshot.
+//!
+//! Snapshots are a scarce resource (one open database connection each and they hold back
+//! vacuum), so they are short-lived and limited:
+//! - A snapshot expires after [`SNAPSHOT_IDLE_TTL`] without a page being requested.
+//! - A snapshot expires after [`SNAPSHOT_MAX_AGE`] regardless of activity.
+//! - Expired snapshots are closed every [`EVICT_INTERVAL`], whether or not new ones are opened.
+//! - Only signed-in users open snapshots, at most [`MAX_SNAPSHOTS_PER_USER`] each.
+//! - At most [`MAX_OPEN_SNAPSHOTS`] snapshots are open at the same time.
+//!
+//! Clients must restart their listing without a snapshot token when it expires.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use dashmap::DashMap;
+use pubky_common::crypto::PublicKey;
+use sqlx::{Connection, PgConnection, Postgres, Transaction};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::persistence::sql::SqlDb;
+
+/// How long a snapshot stays open without any page being requested.
+pub(crate) const SNAPSHOT_IDLE_TTL: Duration = Duration::from_secs(60);
+
+/// Hard upper bound on the lifetime of a snapshot, even if it is actively used.
+pub(crate) const SNAPSHOT_MAX_AGE: Duration = Duration::from_secs(10 * 60);
+
+/// Maximum number of concurrently open snapshots on this homeserver.
+pub(crate) const MAX_OPEN_SNAPSHOTS: usize = 32;
+
+/// Maximum number of concurrently open snapshots opened by the same user.
+pub(crate) const MAX_SNAPSHOTS_PER_USER: usize = 4;
+
+/// How often expired snapshots are closed in the background.
+pub(crate) const EVICT_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum ListSnapshotError {
+    /// The snapshot token is unknown, expired, or belongs to another tenant.
+    #[error("List snapshot expired")]
+    Expired,
+    /// The homeserver has reached [`MAX_OPEN_SNAPSHOTS`], or the user [`MAX_SNAPSHOTS_PER_USER`].
+    #[error("Too many open list snapshots")]
+    TooManySnapshots,
+    #[error("DB error: {0}")]
+    SqlDb(#[from] sqlx::Error),
+}
+
+/// A reserved place for one open snapshot, counted against both limits until dropped.
+#[derive(Debug)]
+struct SnapshotSlot {
+    holder: PublicKey,
+    per_user: Arc<DashMap<PublicKey, usize>>,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl Drop for SnapshotSlot {
+    fn drop(&mut self) {
+        self.per_user.remove_if_mut(&self.holder, |_, count| {
+            *count -= 1;
+            *count == 0
+        });
+    }
+}
+
+/// An exported snapshot and the connection whose open transaction keeps it alive.
+#[derive(Debug)]
+struct HeldSnapshot {
+    owner: PublicKey,
+    snapshot_id: String,
+    created_at: Instant,
+    last_used_at: Instant,
+    /// Dropping the connection aborts the exporting transaction and releases the snapshot.
+    _connection: Mutex<PgConnection>,
+    _slot: SnapshotSlot,
+}
+
+impl HeldSnapshot {
+    fn is_expired(&self) -> bool {
+        self.last_used_at.elapsed() > SNAPSHOT_IDLE_TTL
+            || self.created_at.elapsed() > SNAPSHOT_MAX_AGE
+    }
+}
+
+/// Registry of open list snapshots, keyed by the opaque token handed to clients.
+#[derive(Debug, Clone)]
+pub(crate) struct ListSnapshotService {
+    sql_db: SqlDb,
+    snapshots: Arc<DashMap<String, HeldSnapshot>>,
+    /// One permit per snapshot that may still be opened.
+    slots: Arc<Semaphore>,
+    /// Number of open snapshots per user that opened them.
+    per_user: Arc<DashMap<PublicKey, usize>>,
+}
+
+impl ListSnapshotService {
+    /// Create the registry and spawn a task closing expired snapshots every
+    /// [`EVICT_INTERVAL`]. The task stops once the registry is dropped.
+    pub fn new(sql_db: SqlDb) -> Self {
+        let snapshots: Arc<DashMap<String, HeldSnapshot>> = Arc::new(DashMap::new());
+
+        let weak = Arc::downgrade(&snapshots);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(EVICT_INTERVAL);
+            interval.tick().await; // skip first immediate tick
+            loop {
+                interval.tick().await;
+                let Some(snapshots) = weak.upgrade() else {
+                    break;
+                };
+                snapshots.retain(|_, held| !held.is_expired());
+            }
+        });
+
+        Self {
+            sql_db,
+            snapshots,
+            slots: Arc::new(Semaphore::new(MAX_OPEN_SNAPSHOTS)),
+            per_user: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// Open a new snapshot of `owner`'s storage for the signed-in user `holder` and
+    /// return its token.
+    pub async fn create(
+        &self,
+        owner: &PublicKey,
+        holder: &PublicKey,
+    ) -> Result<String, ListSnapshotError> {
+        self.evict_expired();
+        let slot = self.reserve_slot(holder)?;
+
+        // A dedicated connection so open snapshots never starve the shared pool.
+        let options = self.sql_db.pool().connect_options();
+        let mut connection = PgConnection::connect_with(&options).await?;
+        sqlx::query("BEGIN ISOLATION LEVEL REPEATABLE READ READ ONLY")
+            .execute(&mut connection)
+            .await?;
+        let snapshot_id: String = sqlx::query_scalar("SELECT pg_export_snapshot()")
+            .fetch_one(&mut connection)
+            .await?;
+
+        let token = uuid::Uuid::new_v4().as_simple().to_string();
+        let now = Instant::now();
+        self.snapshots.insert(
+            token.clone(),
+            HeldSnapshot {
+                owner: owner.clone(),
+                snapshot_id,
+                created_at: now,
+                last_used_at: now,
+                _connection: Mutex::new(connection),
+                _slot: slot,
+            },
+        );
+        Ok(token)
+    }
+
+    /// Reserve a place for a snapshot opened by `holder`, counted against both limits
+    /// until the returned slot is dropped.
+    fn reserve_slot(&self, holder: &PublicKey) -> Result<SnapshotSlot, ListSnapshotError> {
+        let permit = Arc::clone(&self.slots)
+            .try_acquire_owned()
+            .map_err(|_| ListSnapshotError::TooManySnapshots)?;
+        let mut count = self.per_user.entry(holder.clone()).or_insert(0);
+        if *count >= MAX_SNAPSHOTS_PER_USER {
+            return Err(ListSnapshotError::TooManySnapshots);
+        }
+        *count += 1;
+        Ok(SnapshotSlot {
+            holder: holder.clone(),
+            per_user: Arc::clone(&self.per_user),
+            _permit: permit,
+        })
+    }
+
+    /// Begin a read-only transaction that sees the database as of the snapshot `token`.
+    ///
+    /// Refreshes the idle timeout of the snapshot.
+    pub async fn begin(
+        &self,
+        owner: &PublicKey,
+        token: &str,
+    ) -> Result<Transaction<'static, Postgres>, ListSnapshotError> {
+        let snapshot_id = {
+            let mut held = self
+                .snapshots
+                .get_mut(token)
+                .ok_or(ListSnapshotError::Expired)?;
+            if &held.owner != owner {
+                return Err(ListSnapshotError::Expired);
+            }
+            if held.is_expired() {
+                drop(held);
+                self.snapshots.remove(token);
+                return Err(ListSnapshotError::Expired);
+            }
+            held.last_used_at = Instant::now();
+            held.snapshot_id.clone()
+        };
+
+        let mut tx = self.sql_db.pool().begin().await?;
+        sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+            .execute(&mut *tx)
+            .await?;
+        // `SET TRANSACTION SNAPSHOT` does not accept bind parameters.
+        // The id was generated by postgres itself so it is safe to inline.
+        let import = format!("SET TRANSACTION SNAPSHOT '{snapshot_id}'");
+        if let Err(e) = sqlx::query(&import).execute(&mut *tx).await {
+            // The exporting connection died. The snapshot is gone for good.
+            tracing::debug!("Failed to import list snapshot {token}: {e}");
+            self.snapshots.remove(token);
+            return Err(ListSnapshotError::Expired);
+        }
+        Ok(tx)
+    }
+
+    fn evict_expired(&self) {
+        self.snapshots.retain(|_, held| !held.is_expired());
+    }
+
+    #[cfg(test)]
+    fn force_expire(&self, token: &str) {
+        if let Some(mut held) = self.snapshots.get_mut(token) {
+            held.last_used_at = Instant::now() - SNAPSHOT_IDLE_TTL - Duration::from_secs(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pubky_common::crypto::Keypair;
+
+    use super::*;
+
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn snapshot_hides_later_writes() {
+        let db = SqlDb::test().await;
+        let service = ListSnapshotService::new(db.clone());
+        let owner = Keypair::random().public_key();
+
+        sqlx::query("CREATE TABLE snapshot_probe (id INT)")
+            .execute(db.pool())
+            .await
+            .unwrap();
+        let token = service.create(&owner, &owner).await.unwrap();
+        sqlx::query("INSERT INTO snapshot_probe VALUES (1)")
+            .execute(db.pool())
+            .await
+            .unwrap();
+
+        let mut tx = service.begin(&owner, &token).await.unwrap();
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM snapshot_probe")
+            .fetch_one(&mut *tx)
+            .await
+            .unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn snapshot_expires() {
+        let db = SqlDb::test().await;
+        let service = ListSnapshotService::new(db);
+        let owner = Keypair::random().public_key();
+
+        assert!(matches!(
+            service.begin(&owner, "unknown").await,
+            Err(ListSnapshotError::Expired)
+        ));
+
+        let token = service.create(&owner, &owner).await.unwrap();
+        assert!(matches!(
+            service.begin(&Keypair::random().public_key(), &token).await,
+            Err(ListSnapshotError::Expired)
+        ));
+        service.begin(&owner, &token).await.unwrap();
+
+        service.force_expire(&token);
+        assert!(matches!(
+            service.begin(&owner, &token).await,
+            Err(ListSnapshotError::Expired)
+        ));
+        assert!(service.snapshots.is_empty());
+    }
+
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn snapshots_are_limited_per_user() {
+        let db = SqlDb::test().await;
+        let service = ListSnapshotService::new(db);
+        let owner = Keypair::random().public_key();
+        let holder = Keypair::random().public_key();
+
+        let mut tokens = Vec::new();
+        for _ in 0..MAX_SNAPSHOTS_PER_USER {
+            tokens.push(service.create(&owner, &holder).await.unwrap());
+        }
+        assert!(matches!(
+            service.create(&owner, &holder).await,
+            Err(ListSnapshotError::TooManySnapshots)
+        ));
+        // Other users are not affected.
+        service.create(&owner, &owner).await.unwrap();
+        assert_eq!(
+            service.slots.available_permits(),
+            MAX_OPEN_SNAPSHOTS - MAX_SNAPSHOTS_PER_USER - 1
+        );
+
+        // Closing an expired snapshot frees its slot.
+        service.force_expire(&tokens[0]);
+        service.evict_expired();
+        service.create(&owner, &holder).await.unwrap();
+        assert_eq!(
+            service.slots.available_permits(),
+            MAX_OPEN_SNAPSHOTS - MAX_SNAPSHOTS_PER_USER - 1
+        );
+    }
+}
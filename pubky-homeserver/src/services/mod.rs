@@ -1,3 +1,6 @@
 //! Application services — business logic and coordination.
 
+pub(crate) mod expired_entries_sweeper;
+pub(crate) mod list_snapshot_service;
+pub mod signup_policy_service;
 pub mod user_service;
@@ -0,0 +1,95 @@
+//! Signup policy that admins can change at runtime.
+//!
+//! The homeserver starts with `[general].signup_mode` from the config. Once an admin
+//! sets a policy through `PUT /signup-policy`, it is stored in the database and takes
+//! precedence over the config, including after restarts, until it is cleared again
+//! through `DELETE /signup-policy`.
+
+use crate::persistence::sql::{server_setting::ServerSettingRepository, SqlDb, UnifiedExecutor};
+use crate::SignupMode;
+
+/// Key of the stored policy in the `server_settings` table.
+const SIGNUP_MODE_SETTING: &str = "signup_mode";
+
+#[derive(Clone, Debug)]
+pub struct SignupPolicyService {
+    sql_db: SqlDb,
+    /// Mode from the config, used until an admin sets one.
+    configured: SignupMode,
+}
+
+impl SignupPolicyService {
+    pub fn new(sql_db: SqlDb, configured: SignupMode) -> Self {
+        Self { sql_db, configured }
+    }
+
+    /// The signup mode currently in effect.
+    /// The executor can either be db.pool() or a transaction.
+    pub async fn current<'a>(
+        &self,
+        executor: &mut UnifiedExecutor<'a>,
+    ) -> Result<SignupMode, sqlx::Error> {
+        let Some(stored) = ServerSettingRepository::get(SIGNUP_MODE_SETTING, executor).await?
+        else {
+            return Ok(self.configured.clone());
+        };
+        Ok(stored.parse().unwrap_or_else(|e| {
+            tracing::warn!("Ignoring stored signup policy: {e}");
+            self.configured.clone()
+        }))
+    }
+
+    /// Persist `mode` as the signup mode, overriding the config.
+    pub async fn set(&self, mode: &SignupMode) -> Result<(), sqlx::Error> {
+        ServerSettingRepository::set(
+            SIGNUP_MODE_SETTING,
+            mode.as_str(),
+            &mut self.sql_db.pool().into(),
+        )
+        .await
+    }
+
+    /// Remove the stored signup mode, so the config applies again.
+    pub async fn clear(&self) -> Result<(), sqlx::Error> {
+        ServerSettingRepository::delete(SIGNUP_MODE_SETTING, &mut self.sql_db.pool().into()).await
+    }
+
+    /// Warn when a stored policy overrides a different mode from the config,
+    /// so operators are not surprised by a config change that has no effect.
+    pub async fn warn_if_overridden(&self) -> Result<(), sqlx::Error> {
+        let current = self.current(&mut self.sql_db.pool().into()).await?;
+        if current != self.configured {
+            tracing::warn!(
+                "Signup mode {} set through the admin API overrides {} from the config. Clear it with DELETE /signup-policy to use the config again.",
+                current.as_str(),
+                self.configured.as_str()
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    #[pubky_test_utils::test]
+    async fn stored_policy_overrides_config() {
+        let db = SqlDb::test().await;
+        let service = SignupPolicyService::new(db.clone(), SignupMode::TokenRequired);
+        let current = service.current(&mut db.pool().into()).await.unwrap();
+        assert_eq!(current, SignupMode::TokenRequired);
+
+        service.set(&SignupMode::Closed).await.unwrap();
+
+        // A restarted homeserver reads the stored policy back.
+        let restarted = SignupPolicyService::new(db.clone(), SignupMode::Open);
+        let current = restarted.current(&mut db.pool().into()).await.unwrap();
+        assert_eq!(current, SignupMode::Closed);
+
+        restarted.clear().await.unwrap();
+        let current = restarted.current(&mut db.pool().into()).await.unwrap();
+        assert_eq!(current, SignupMode::Open);
+    }
+}
@@ -1,7 +1,8 @@
 //! Server error
 use axum::{http::StatusCode, response::IntoResponse};
 
-use crate::persistence::files::FileIoError;
+use crate::persistence::files::{FileIoError, WriteStreamError};
+use crate::services::list_snapshot_service::ListSnapshotError;
 
 pub(crate) type HttpResult<T, E = HttpError> = core::result::Result<T, E>;
 
@@ -48,6 +49,14 @@ impl HttpError {
         Self::new_with_message(StatusCode::BAD_REQUEST, message)
     }
 
+    pub fn uri_too_long(message: impl ToString) -> HttpError {
+        Self::new_with_message(StatusCode::URI_TOO_LONG, message)
+    }
+
+    pub fn unsupported_media_type(message: impl ToString) -> HttpError {
+        Self::new_with_message(StatusCode::UNSUPPORTED_MEDIA_TYPE, message)
+    }
+
     pub fn insufficient_storage() -> HttpError {
         Self::new_with_message(
             StatusCode::INSUFFICIENT_STORAGE,
@@ -66,6 +75,14 @@ impl HttpError {
     pub fn unauthorized_with_message(message: impl ToString) -> HttpError {
         Self::new_with_message(StatusCode::UNAUTHORIZED, message)
     }
+
+    /// The message sent to the client, or the status reason if there is none.
+    pub fn message(&self) -> &str {
+        self.detail
+            .as_deref()
+            .or(self.status.canonical_reason())
+            .unwrap_or_default()
+    }
 }
 
 impl IntoResponse for HttpError {
@@ -125,12 +142,27 @@ impl From<FileIoError> for HttpError {
             FileIoError::PathCollision => {
                 Self::new_with_message(StatusCode::CONFLICT, "File/folder path collision")
             }
+            FileIoError::StreamBroken(WriteStreamError::UnsupportedMediaType(message)) => {
+                Self::unsupported_media_type(message)
+            }
             FileIoError::StreamBroken(_) => Self::bad_request("Stream broken"),
             e => Self::internal_server_and_log(format!("FileIoError: {}", e)),
         }
     }
 }
 
+impl From<ListSnapshotError> for HttpError {
+    fn from(error: ListSnapshotError) -> Self {
+        match error {
+            ListSnapshotError::Expired => Self::new_with_message(StatusCode::GONE, error),
+            ListSnapshotError::TooManySnapshots => {
+                Self::new_with_message(StatusCode::SERVICE_UNAVAILABLE, error)
+            }
+            ListSnapshotError::SqlDb(e) => e.into(),
+        }
+    }
+}
+
 impl From<pubky_common::auth::Error> for HttpError {
     fn from(error: pubky_common::auth::Error) -> Self {
         Self::bad_request(error)
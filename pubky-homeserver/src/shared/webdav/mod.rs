@@ -9,7 +9,8 @@
 ///
 /// # How to fix it
 /// ## `pubky-host`
-/// We need to stop using the pubky-host hack. Instead the api should look like this:
+/// We need to stop using the pubky-host hack. Instead the api should look like this (served by
+/// `with_dav_paths` in the `pubky_host` middleware, which new clients use):
 /// `https://qtnyghnq9swketdtj9drc7rs5pfnxhs61gq4jwd317ezdegcrbco/dav/qtnyghnq9swketdtj9drc7rs5pfnxhs61gq4jwd317ezdegcrbco/pub/test.txt`
 /// The public key is duplicated in this example. This way, even though the domain name changes (in the browser for example or through a redirect), the path is still valid
 /// This solves the need for nginx hacks and simplifies the homeserver code.
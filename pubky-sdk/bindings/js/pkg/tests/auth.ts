@@ -169,6 +169,30 @@ test("Grant auth: 3rd party signin", async (t) => {
   t.end();
 });
 
+test("Grant auth: pollIntervalMs still delivers the approval", async (t) => {
+  const sdk = Pubky.testnet();
+
+  const signer = sdk.signer(Keypair.random());
+  const signupToken = await createSignupToken();
+  await signer.signup(HOMESERVER_PUBLICKEY, signupToken);
+
+  const flow = await sdk.startGrantAuthFlow(
+    "/pub/pubky.app/:rw",
+    AuthFlowKind.signin(),
+    { clientId: "grant-poll-interval.test", relay: TESTNET_HTTP_RELAY, pollIntervalMs: 100 },
+  );
+  await signer.approveAuthRequest(flow.authorizationUrl);
+  const session = await flow.awaitApproval();
+
+  t.equal(
+    session.info.publicKey.z32(),
+    signer.publicKey.z32(),
+    "paced grant flow yields the signer's session",
+  );
+
+  t.end();
+});
+
 test("Grant auth: 3rd party signup", async (t) => {
   const sdk = Pubky.testnet();
 
@@ -1,6 +1,6 @@
 import test from "tape";
 
-import { Keypair, PublicKey } from "../index.js";
+import { Keypair, PublicKey, Signer, verifyMessage } from "../index.js";
 import { Assert, IsExact } from "./utils.js";
 
 type KeypairClass = typeof Keypair;
@@ -52,3 +52,32 @@ test("PublicKey from and toUint8Array", async (t) => {
     ]),
   );
 });
+
+test("signMessage and verifyMessage", async (t) => {
+  const signer = Signer.fromKeypair(Keypair.random());
+  const message = new TextEncoder().encode("account 42");
+  const signature = signer.signMessage("example.com/link-account", message);
+
+  t.is(signature.length, 64);
+  t.ok(
+    verifyMessage(
+      signer.publicKey,
+      "example.com/link-account",
+      message,
+      signature,
+    ),
+  );
+  t.notOk(
+    verifyMessage(signer.publicKey, "example.com/other", message, signature),
+  );
+  t.throws(
+    () =>
+      verifyMessage(
+        signer.publicKey,
+        "example.com/link-account",
+        message,
+        signature.slice(1),
+      ),
+    /signature must be 64 bytes long/,
+  );
+});
@@ -123,6 +123,57 @@ test("session: putText/getText/delete, public: getText", async (t) => {
   t.end();
 });
 
+test("session: onHomeserver addresses the homeserver through /dav/ paths", async (t) => {
+  const sdk = Pubky.testnet();
+
+  const signer = sdk.signer(Keypair.random());
+  const signupToken = await createSignupToken();
+  await signer.signup(HOMESERVER_PUBLICKEY, signupToken);
+  const session = await signer.signin("storage.test");
+
+  const storage = session.storage.onHomeserver(HOMESERVER_PUBLICKEY);
+  const path: Path = "/pub/example.com/dav.txt";
+
+  await storage.putText(path, "via dav");
+  t.equal(await storage.getText(path), "via dav", "dav getText matches");
+  t.equal(
+    await session.storage.getText(path),
+    "via dav",
+    "host-addressed getText sees the same entry",
+  );
+
+  await storage.delete(path);
+  t.end();
+});
+
+test("session: putBytes with a filename sets Content-Disposition", async (t) => {
+  const sdk = Pubky.testnet();
+
+  const signer = sdk.signer(Keypair.random());
+  const signupToken = await createSignupToken();
+  await signer.signup(HOMESERVER_PUBLICKEY, signupToken);
+  const session = await signer.signin("storage.test");
+
+  const userPk = session.info.publicKey.z32();
+  const path: Path = "/pub/example.com/upload.bin";
+  const addr = toAddress(userPk, path);
+
+  await session.storage.putBytes(path, Uint8Array.from([1, 2, 3]), "résumé.pdf");
+
+  const stats = await sdk.publicStorage.stats(addr);
+  t.equal(stats?.filename, "résumé.pdf", "stats expose the decoded filename");
+
+  const response = await sdk.publicStorage.get(addr);
+  t.equal(
+    response.headers.get("content-disposition"),
+    "attachment; filename=\"r_sum_.pdf\"; filename*=UTF-8''r%C3%A9sum%C3%A9.pdf",
+    "read emits an RFC 5987 Content-Disposition",
+  );
+
+  await session.storage.delete(path);
+  t.end();
+});
+
 test("session: putBytes/getBytes/delete, public: getBytes", async (t) => {
   const sdk = Pubky.testnet();
 
@@ -163,6 +214,27 @@ test("session: putBytes/getBytes/delete, public: getBytes", async (t) => {
   t.end();
 });
 
+test("RecordNotFound: reading from a key without a record", async (t) => {
+  const sdk = Pubky.testnet();
+
+  const unknown = Keypair.random().publicKey;
+  const addr = toAddress(unknown.z32(), "/pub/example.com/file.txt");
+  try {
+    await sdk.publicStorage.getText(addr);
+    t.fail("reading from an unpublished key should fail");
+  } catch (error) {
+    assertPubkyError(t, error);
+    t.equal(error.name, "RecordNotFound", "mapped error name");
+    t.deepEqual(
+      error.data,
+      { publicKey: unknown.z32() },
+      "error data names the key",
+    );
+  }
+
+  t.end();
+});
+
 test("forbidden: writing outside /pub and /priv returns 403", async (t) => {
   const sdk = Pubky.testnet();
 
@@ -190,6 +262,31 @@ test("forbidden: writing outside /pub and /priv returns 403", async (t) => {
   t.end();
 });
 
+test("checkPermission: rejects writes outside /pub and /priv locally", async (t) => {
+  const sdk = Pubky.testnet();
+
+  const signer = sdk.signer(Keypair.random());
+  const signupToken = await createSignupToken();
+  await signer.signup(HOMESERVER_PUBLICKEY, signupToken);
+  const session = await signer.signin("storage.test");
+
+  session.storage.checkPermission("PUT", "/pub/example.com/file");
+  session.storage.checkPermission("get", "/priv/example.com/file");
+  try {
+    session.storage.checkPermission(
+      "DELETE",
+      "/foo/example.com/arbitrary" as unknown as Path,
+    );
+    t.fail("checkPermission outside /pub and /priv should throw");
+  } catch (error) {
+    assertPubkyError(t, error);
+    t.equal(error.name, "RequestError", "mapped error name");
+    t.equal(getStatusCode(error), 403, "status code 403");
+  }
+
+  t.end();
+});
+
 test("session: putText/getText/delete round-trip under /priv", async (t) => {
   const sdk = Pubky.testnet();
 
@@ -30,6 +30,12 @@ impl SignupDeepLink {
         self.0.params().capabilities.to_string()
     }
 
+    /// Capabilities the user may decline. Empty if none were requested.
+    #[wasm_bindgen(js_name = "optionalCapabilities", getter)]
+    pub fn optional_capabilities(&self) -> String {
+        self.0.params().optional_capabilities.to_string()
+    }
+
     #[wasm_bindgen(js_name = "baseRelayUrl", getter)]
     pub fn base_relay_url(&self) -> String {
         self.0.params().relay.to_string()
@@ -42,6 +42,12 @@ impl SignupGrantDeepLink {
         self.0.params().capabilities.to_string()
     }
 
+    /// Capabilities the user may decline. Empty if none were requested.
+    #[wasm_bindgen(js_name = "optionalCapabilities", getter)]
+    pub fn optional_capabilities(&self) -> String {
+        self.0.params().optional_capabilities.to_string()
+    }
+
     /// Base HTTP relay inbox URL used by this auth request.
     ///
     /// @returns {string}
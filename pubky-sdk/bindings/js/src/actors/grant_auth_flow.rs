@@ -1,7 +1,7 @@
 use pubky::{DelegatedGrantAuthFlowState, GrantAuthFlowState, PubkyGrantAuthFlow};
 use pubky_common::{auth::jws::ClientId, capabilities::Capabilities};
 use serde::{Deserialize, Serialize};
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, rc::Rc, time::Duration};
 use tsify::Tsify;
 use url::Url;
 
@@ -29,6 +29,13 @@ pub struct GrantAuthFlowOptions {
     /// Defaults to the default Synonym-hosted relay when omitted.
     #[tsify(optional, type = "string | null")]
     pub(crate) relay: Option<String>,
+    /// Optional comma-separated capabilities the user may decline, e.g. `"/pub/contacts/:r"`.
+    #[tsify(optional, type = "Capabilities | null")]
+    pub(crate) optional_capabilities: Option<String>,
+    /// Optional pause in milliseconds between relay poll requests, to reduce relay load.
+    /// By default the relay is re-polled as soon as a long poll ends.
+    #[tsify(optional, type = "number | null")]
+    pub(crate) poll_interval_ms: Option<u32>,
 }
 
 /// Start and control a grant-backed pubkyauth authorization flow.
@@ -67,7 +74,7 @@ impl GrantAuthFlow {
     /// The kind of authentication flow to perform.
     ///
     /// @param {GrantAuthFlowOptions} options
-    /// Options for the grant flow: `{ clientId, relay? }`.
+    /// Options for the grant flow: `{ clientId, relay?, optionalCapabilities?, pollIntervalMs? }`.
     ///
     /// @returns {GrantAuthFlow}
     /// A running grant auth flow. Call `authorizationUrl()` to show the deep link,
@@ -102,6 +109,13 @@ impl GrantAuthFlow {
         if let Some(r) = options.relay {
             builder = builder.relay(Url::parse(&r)?);
         }
+        if let Some(optional) = &options.optional_capabilities {
+            let normalized = validate_capabilities(optional.as_str())?;
+            builder = builder.optional_capabilities(&Capabilities::try_from(normalized.as_str())?);
+        }
+        if let Some(ms) = options.poll_interval_ms {
+            builder = builder.poll_interval(Duration::from_millis(u64::from(ms)));
+        }
 
         let flow = builder.start()?;
         Ok(flow.into())
@@ -147,6 +161,13 @@ impl GrantAuthFlow {
         if let Some(r) = options.relay {
             builder = builder.relay(Url::parse(&r)?);
         }
+        if let Some(optional) = &options.optional_capabilities {
+            let normalized = validate_capabilities(optional.as_str())?;
+            builder = builder.optional_capabilities(&Capabilities::try_from(normalized.as_str())?);
+        }
+        if let Some(ms) = options.poll_interval_ms {
+            builder = builder.poll_interval(Duration::from_millis(u64::from(ms)));
+        }
 
         let flow = builder.start()?;
         Ok(flow.into())
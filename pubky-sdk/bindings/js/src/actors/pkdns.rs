@@ -37,12 +37,30 @@ impl Pkdns {
             .map(Into::into)
     }
 
+    /// Resolve every homeserver listed for a given public key, primary first (read-only).
+    ///
+    /// @param {PublicKey} user
+    /// @returns {Promise<PublicKey[]>} Homeserver public keys; empty if none were found.
+    #[wasm_bindgen(js_name = "getHomeserversOf")]
+    pub async fn get_homeservers_of(&self, pubky: &PublicKey) -> Vec<PublicKey> {
+        self.0
+            .get_homeservers_of(pubky.as_inner())
+            .await
+            .into_iter()
+            .map(Into::into)
+            .collect()
+    }
+
     /// Resolve the homeserver for **this** user (requires keypair).
     ///
     /// @returns {Promise<PublicKey|undefined>} Homeserver public key or `undefined` if not found.
     #[wasm_bindgen(js_name = "getHomeserver")]
     pub async fn get_homeserver(&self) -> JsResult<Option<PublicKey>> {
-        Ok(self.0.get_homeserver().await?.map(Into::into))
+        match self.0.get_homeserver().await {
+            Ok(homeserver) => Ok(Some(homeserver.into())),
+            Err(pubky::Error::RecordNotFound { .. }) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
     }
 
     // -------------------- Publishing --------------------
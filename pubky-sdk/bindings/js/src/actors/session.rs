@@ -4,7 +4,8 @@ use wasm_bindgen::prelude::*;
 use super::{cookie_session::CookieSession, grant_session::GrantSession, storage::SessionStorage};
 use crate::client::constructor::Client;
 use crate::js_error::{JsResult, PubkyError, PubkyErrorName};
-use crate::wrappers::session_info::SessionInfo;
+use crate::wrappers::{capabilities::validate_capabilities, session_info::SessionInfo};
+use pubky::Capabilities;
 
 /// An authenticated context “as the user”.
 /// - Use `storage` for reads/writes (absolute paths like `/pub/app/file.txt`)
@@ -30,6 +31,24 @@ impl Session {
         SessionStorage(pubky::SessionStorage::new(&self.0))
     }
 
+    /// Derive a view of this session restricted to a subset of its capabilities.
+    ///
+    /// The view shares this session's credential; its `storage` refuses reads and writes
+    /// outside `capabilities` before sending them. The narrowing is client-side only.
+    ///
+    /// @param {string} capabilities Comma-separated capabilities, e.g. `"/pub/my-plugin/:rw"`.
+    /// @returns {Session}
+    /// @throws {PubkyError} `AuthenticationError` if `capabilities` is not a subset of the session's.
+    #[wasm_bindgen]
+    pub fn scoped(
+        &self,
+        #[wasm_bindgen(unchecked_param_type = "Capabilities")] capabilities: String,
+    ) -> JsResult<Session> {
+        let normalized = validate_capabilities(capabilities.as_str())?;
+        let capabilities = Capabilities::try_from(normalized.as_str())?;
+        Ok(Session(self.0.scoped(capabilities)?))
+    }
+
     /// Grant-only management view for grant-backed sessions.
     ///
     /// Cookie-backed sessions return `undefined`.
@@ -1,9 +1,10 @@
+use js_sys::Uint8Array;
 use wasm_bindgen::prelude::*;
 
 use super::{pkdns::Pkdns, session::Session};
 use crate::js_error::JsResult;
-use crate::wrappers::{keys::Keypair, keys::PublicKey};
-use pubky::ClientId;
+use crate::wrappers::{capabilities::validate_capabilities, keys::Keypair, keys::PublicKey};
+use pubky::{Capabilities, ClientId};
 
 /// Holds a user’s `Keypair` and performs identity operations:
 /// - `signup` creates a new homeserver user.
@@ -33,6 +34,20 @@ impl Signer {
         self.0.public_key().into()
     }
 
+    /// Sign an application message, e.g. to prove control of this key to a third party.
+    ///
+    /// The domain separator names the protocol the proof is for and keeps the signature
+    /// from being reused elsewhere. Check it with `verifyMessage`.
+    ///
+    /// @param {string} domainSeparator e.g. `"example.com/link-account"`.
+    /// @param {Uint8Array} message
+    /// @returns {Uint8Array} The 64-byte Ed25519 signature.
+    #[wasm_bindgen(js_name = "signMessage")]
+    pub fn sign_message(&self, domain_separator: &str, message: &[u8]) -> Uint8Array {
+        let signature = self.0.sign_message(domain_separator, message);
+        Uint8Array::from(signature.to_bytes().as_ref())
+    }
+
     /// Sign up at a homeserver.
     ///
     /// Creates the account and publishes PKDNS. Call `signin(clientId)` to create a session.
@@ -123,6 +138,25 @@ impl Signer {
         Ok(())
     }
 
+    /// Approve a `pubkyauth://` request URL, granting all required capabilities but
+    /// only the optional ones in `acceptedOptional`.
+    ///
+    /// @param {string} pubkyauthUrl
+    /// @param {string} acceptedOptional Comma-separated capabilities, e.g. `"/pub/contacts/:r"`.
+    #[wasm_bindgen(js_name = "approveAuthRequestWithOptional")]
+    pub async fn approve_auth_with_optional(
+        &self,
+        pubkyauth_url: String,
+        #[wasm_bindgen(unchecked_param_type = "Capabilities")] accepted_optional: String,
+    ) -> JsResult<()> {
+        let normalized = validate_capabilities(accepted_optional.as_str())?;
+        let accepted = Capabilities::try_from(normalized.as_str())?;
+        self.0
+            .approve_auth_with_optional(&pubkyauth_url, &accepted)
+            .await?;
+        Ok(())
+    }
+
     /// Handle a `pubkyauth://` deep link.
     ///
     /// Auth requests are approved through their relay. A `direct_signup` link
@@ -133,6 +167,23 @@ impl Signer {
         Ok(())
     }
 
+    /// Make sure the `_pubky` record points to `homeserver`, republishing it only if it
+    /// is missing, stale or names another homeserver. Safe to call on every launch.
+    ///
+    /// @param {PublicKey} homeserver The homeserver the account lives on.
+    /// @returns {Promise<boolean>} Whether the record was republished.
+    ///
+    /// @throws {PubkyError}
+    /// - `PkarrError` (publishing failed)
+    #[wasm_bindgen(js_name = "ensurePublished")]
+    pub async fn ensure_published(&self, homeserver: &PublicKey) -> JsResult<bool> {
+        Ok(self
+            .0
+            .ensure_published(homeserver.as_inner())
+            .await?
+            .is_some())
+    }
+
     /// Get a PKDNS actor bound to this signer's client & keypair (publishing enabled).
     ///
     /// @returns {Pkdns}
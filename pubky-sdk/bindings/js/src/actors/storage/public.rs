@@ -17,7 +17,7 @@ pub struct PublicStorage(pub(crate) pubky::PublicStorage);
 
 #[wasm_bindgen]
 impl PublicStorage {
-    /// Construct PublicStorage using global client (mainline relays).
+    /// Construct PublicStorage with its own new client (mainline relays).
     #[wasm_bindgen(constructor)]
     pub fn new() -> JsResult<PublicStorage> {
         Ok(PublicStorage(pubky::PublicStorage::new()?))
@@ -5,7 +5,8 @@ use wasm_bindgen::prelude::*;
 use web_sys::Response;
 
 use super::stats::ResourceStats;
-use crate::js_error::JsResult;
+use crate::js_error::{JsResult, PubkyError, PubkyErrorName};
+use crate::wrappers::keys::PublicKey;
 
 #[wasm_bindgen(typescript_custom_section)]
 const TS_PATH: &'static str = r#"export type Path = `/pub/${string}` | `/priv/${string}`;"#;
@@ -16,6 +17,17 @@ pub struct SessionStorage(pub(crate) pubky::SessionStorage);
 
 #[wasm_bindgen]
 impl SessionStorage {
+    /// Send requests of this handle to a specific homeserver, e.g. a mirror of your data.
+    ///
+    /// Requests are addressed as `https://<homeserver>/dav/<you>/<path>`.
+    ///
+    /// @param {PublicKey} homeserver
+    /// @returns {SessionStorage}
+    #[wasm_bindgen(js_name = "onHomeserver")]
+    pub fn on_homeserver(&self, homeserver: &PublicKey) -> SessionStorage {
+        SessionStorage(self.0.clone().on_homeserver(homeserver.0.clone()))
+    }
+
     /// List a directory (absolute session path). Returns `pubky://…` URLs.
     ///
     /// @param {Path} path Must end with `/`.
@@ -119,18 +131,51 @@ impl SessionStorage {
         }
     }
 
+    /// Check locally, without a request, whether this session's capabilities permit
+    /// an HTTP `method` on `path`. Writes run the same check before sending.
+    ///
+    /// @param {string} method HTTP method, e.g. `"PUT"`.
+    /// @param {Path} path
+    /// @returns {void}
+    /// @throws {PubkyError} `RequestError` with `statusCode` 403 if not permitted;
+    /// `InvalidInput` for an invalid method or path.
+    #[wasm_bindgen(js_name = "checkPermission")]
+    pub fn check_permission(
+        &self,
+        method: &str,
+        #[wasm_bindgen(unchecked_param_type = "Path")] path: String,
+    ) -> JsResult<()> {
+        let method = pubky::Method::from_bytes(method.to_ascii_uppercase().as_bytes())
+            .map_err(|e| PubkyError::new(PubkyErrorName::InvalidInput, e))?;
+        Ok(self.0.check_permission(&method, path)?)
+    }
+
     /// PUT binary at an absolute session path.
     ///
+    /// With `filename`, readers download the file under that name
+    /// (`Content-Disposition: attachment`).
+    ///
     /// @param {Path} path
     /// @param {Uint8Array} bytes
+    /// @param {string=} filename Optional download filename.
     /// @returns {Promise<void>}
     #[wasm_bindgen(js_name = "putBytes")]
     pub async fn put_bytes(
         &self,
         #[wasm_bindgen(unchecked_param_type = "Path")] path: String,
         body: &[u8],
+        filename: Option<String>,
     ) -> JsResult<()> {
-        self.0.put(path, body.to_vec()).await?;
+        match filename {
+            Some(filename) => {
+                self.0
+                    .put_as_attachment(path, body.to_vec(), &filename)
+                    .await?;
+            }
+            None => {
+                self.0.put(path, body.to_vec()).await?;
+            }
+        }
         Ok(())
     }
 
@@ -165,6 +210,52 @@ impl SessionStorage {
         Ok(())
     }
 
+    /// Bump the modification time of an existing file without uploading it again.
+    ///
+    /// Rejects with a 404 `RequestError` if the file does not exist.
+    ///
+    /// @param {Path} path
+    /// @returns {Promise<void>}
+    #[wasm_bindgen]
+    pub async fn touch(
+        &self,
+        #[wasm_bindgen(unchecked_param_type = "Path")] path: String,
+    ) -> JsResult<()> {
+        self.0.touch(path).await?;
+        Ok(())
+    }
+
+    /// Append bytes to a file if it is exactly `offset` bytes long, resolving to its new
+    /// length.
+    ///
+    /// Of several writers appending at the same offset, exactly one succeeds. The others
+    /// reject with a 409 `RequestError` whose `data.currentLength` is the offset to retry
+    /// at. Appending at offset 0 creates a missing file.
+    ///
+    /// @param {Path} path
+    /// @param {number} offset Length the file must have.
+    /// @param {Uint8Array} bytes
+    /// @returns {Promise<number>} The new length of the file.
+    #[wasm_bindgen(js_name = "appendAt")]
+    pub async fn append_at(
+        &self,
+        #[wasm_bindgen(unchecked_param_type = "Path")] path: String,
+        offset: f64,
+        bytes: &[u8],
+    ) -> JsResult<f64> {
+        if !(offset >= 0.0 && offset.fract() == 0.0) {
+            return Err(PubkyError::new(
+                PubkyErrorName::InvalidInput,
+                "offset must be a non-negative integer",
+            ));
+        }
+        let length = self
+            .0
+            .append_at(path, offset as u64, bytes.to_vec())
+            .await?;
+        Ok(length as f64)
+    }
+
     /// Delete a path (file or empty directory).
     ///
     /// @param {Path} path
@@ -177,4 +268,18 @@ impl SessionStorage {
         self.0.delete(path).await?;
         Ok(())
     }
+
+    /// Delete every file below a directory, atomically on homeservers that support it.
+    ///
+    /// Older homeservers fall back to deleting the files one by one.
+    ///
+    /// @param {Path} path Absolute directory path ending with `/`.
+    /// @returns {Promise<number>} Number of deleted files.
+    #[wasm_bindgen(js_name = "deleteDir")]
+    pub async fn delete_dir(
+        &self,
+        #[wasm_bindgen(unchecked_param_type = "Path")] path: String,
+    ) -> JsResult<f64> {
+        Ok(self.0.delete_dir(path).await? as f64)
+    }
 }
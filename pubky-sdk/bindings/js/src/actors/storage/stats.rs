@@ -9,6 +9,7 @@ use wasm_bindgen::prelude::*;
 /// @property {string=} contentType    Media type (e.g. "application/json; charset=utf-8").
 /// @property {number=} lastModifiedMs Unix epoch milliseconds.
 /// @property {string=} etag           Opaque server ETag for the current version.
+/// @property {string=} filename       Download filename set with `putBytes(path, bytes, filename)`.
 ///
 /// @example
 /// const stats = await pubky.publicStorage.stats(`${user}/pub/app/file.json`);
@@ -43,6 +44,11 @@ pub struct ResourceStats {
     #[tsify(optional)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub etag: Option<String>,
+
+    /// Filename the object downloads as, from `Content-Disposition`.
+    #[tsify(optional)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
 }
 
 impl From<pubky::ResourceStats> for ResourceStats {
@@ -57,6 +63,7 @@ impl From<pubky::ResourceStats> for ResourceStats {
             content_type: s.content_type,
             last_modified_ms,
             etag: s.etag,
+            filename: s.filename,
         }
     }
 }
@@ -33,11 +33,11 @@ pub(crate) async fn apply_list_options(
     Ok(urls)
 }
 
-pub(crate) fn response_to_web_response(resp: reqwest::Response) -> JsResult<Response> {
+pub(crate) fn response_to_web_response(resp: pubky::PubkyResponse) -> JsResult<Response> {
     let status = resp.status();
     let headers_map = resp.headers().clone();
 
-    let stream = resp.bytes_stream().map(|chunk| match chunk {
+    let stream = resp.stream().map(|chunk| match chunk {
         Ok(bytes) => Ok(JsValue::from(Uint8Array::from(bytes.as_ref()))),
         Err(err) => Err(JsValue::from_str(&err.to_string())),
     });
@@ -10,7 +10,7 @@ use wasm_bindgen::describe::WasmDescribe;
 use wasm_bindgen::prelude::*;
 
 use pkarr::errors::PublicKeyError;
-use pubky::errors::{BuildError, RequestError};
+use pubky::errors::{AuthError, BuildError, RequestError};
 use pubky_common::auth::Error as AuthTokenError;
 use pubky_common::capabilities::Error as CapabilitiesError;
 use pubky_common::recovery_file::Error as RecoveryFileError;
@@ -37,9 +37,23 @@ pub enum PubkyErrorName {
     /// The error was caused by invalid user input, such as a malformed URL.
     InvalidInput,
     /// An error occurred during login, signup, or session validation.
+    ///
+    /// Failures of an auth flow carry the stage in `data.kind`: `RelayUnreachable`,
+    /// `TokenMalformed`, `TokenRejected`, `InsufficientCapabilities`, `SessionRefused`,
+    /// `TimedOut` or `Cancelled`.
     AuthenticationError,
     /// A failure in the underlying Pkarr DHT protocol.
     PkarrError,
+    /// The key has no Pkarr record, so the account does not exist.
+    RecordNotFound,
+    /// The key's record resolved, but its homeserver did not answer. Usually temporary.
+    HomeserverUnreachable,
+    /// The key's record names another homeserver than the pinned one.
+    RecordChanged,
+    /// The key was expected to be a homeserver but does not answer as one.
+    NotAHomeserver,
+    /// The homeserver runs a version older than the SDK supports.
+    IncompatibleVersion,
     /// An error related to client state, like a corrupt recovery file.
     ClientStateError,
     /// An unexpected or internal error occurred. This may indicate a bug.
@@ -53,6 +67,11 @@ impl PubkyErrorName {
             PubkyErrorName::InvalidInput => "InvalidInput",
             PubkyErrorName::AuthenticationError => "AuthenticationError",
             PubkyErrorName::PkarrError => "PkarrError",
+            PubkyErrorName::RecordNotFound => "RecordNotFound",
+            PubkyErrorName::HomeserverUnreachable => "HomeserverUnreachable",
+            PubkyErrorName::RecordChanged => "RecordChanged",
+            PubkyErrorName::NotAHomeserver => "NotAHomeserver",
+            PubkyErrorName::IncompatibleVersion => "IncompatibleVersion",
             PubkyErrorName::ClientStateError => "ClientStateError",
             PubkyErrorName::InternalError => "InternalError",
         }
@@ -185,16 +204,105 @@ impl From<pubky::Error> for PubkyError {
             pubky::Error::Authentication(_) => PubkyErrorName::AuthenticationError,
             pubky::Error::Pkarr(_) => PubkyErrorName::PkarrError,
             pubky::Error::Build(_) => PubkyErrorName::InternalError,
+            pubky::Error::ResponseTooLarge { .. } => PubkyErrorName::RequestError,
+            pubky::Error::RecordNotFound { .. } => PubkyErrorName::RecordNotFound,
+            pubky::Error::HomeserverUnreachable { .. } => PubkyErrorName::HomeserverUnreachable,
+            pubky::Error::RecordChanged(_) => PubkyErrorName::RecordChanged,
+            pubky::Error::NotAHomeserver { .. } => PubkyErrorName::NotAHomeserver,
+            pubky::Error::IncompatibleVersion { .. } => PubkyErrorName::IncompatibleVersion,
         };
 
         // If this was a server error, attach status_code; else leave it None.
         if let pubky::Error::Request(RequestError::Server { status, .. }) = &err {
             return Self::new_with_status(name, &err, status.as_u16());
         }
+        // A write the session's capabilities do not cover, rejected before sending.
+        if let pubky::Error::Request(RequestError::Forbidden { .. }) = &err {
+            return Self::new_with_status(name, &err, 403);
+        }
+        // A write refused by the homeserver's content policy.
+        if let pubky::Error::Request(RequestError::UnsupportedMediaType { .. }) = &err {
+            return Self::new_with_status(name, &err, 415);
+        }
+        // A lost compare-and-append race surfaces as 409 plus the length to retry at.
+        if let pubky::Error::Request(RequestError::Conflict { current_length }) = &err {
+            return Self::new(name, &err)
+                .with_data(json!({ "currentLength": current_length }))
+                .with_status(409);
+        }
+        // An expired list snapshot surfaces as the homeserver's 410 Gone.
+        if let pubky::Error::Request(RequestError::SnapshotExpired) = &err {
+            return Self::new_with_status(name, &err, 410);
+        }
+        // A signup/signin lockout surfaces as 429 plus the delay in seconds.
+        if let pubky::Error::Request(RequestError::TooManyAttempts { retry_after }) = &err {
+            return Self::new(name, &err)
+                .with_data(json!({ "retryAfter": retry_after.as_secs() }))
+                .with_status(429);
+        }
+        if let pubky::Error::ResponseTooLarge { limit } = &err {
+            return Self::new(name, &err).with_data(json!({ "limit": limit }));
+        }
+        if let pubky::Error::RecordNotFound { public_key }
+        | pubky::Error::HomeserverUnreachable { public_key, .. }
+        | pubky::Error::NotAHomeserver { public_key, .. } = &err
+        {
+            return Self::new(name, &err).with_data(json!({ "publicKey": public_key.z32() }));
+        }
+        if let pubky::Error::IncompatibleVersion {
+            public_key,
+            version,
+            minimum,
+        } = &err
+        {
+            return Self::new(name, &err).with_data(json!({
+                "publicKey": public_key.z32(),
+                "version": version.to_string(),
+                "minimum": minimum.to_string(),
+            }));
+        }
+        if let pubky::Error::Authentication(auth) = &err
+            && let Some(data) = auth_flow_data(auth)
+        {
+            let error = Self::new(name, &err).with_data(data);
+            return match auth {
+                AuthError::SessionRefused { status, .. } => error.with_status(status.as_u16()),
+                _ => error,
+            };
+        }
+        if let pubky::Error::RecordChanged(change) = &err {
+            return Self::new(name, &err).with_data(json!({
+                "publicKey": change.user.z32(),
+                "old": change.old.z32(),
+                "new": change.new.z32(),
+            }));
+        }
         Self::new(name, err)
     }
 }
 
+/// `data` of an `AuthenticationError` raised by an auth flow stage: the variant as
+/// `kind`, plus its context.
+fn auth_flow_data(err: &AuthError) -> Option<Value> {
+    let data = match err {
+        AuthError::RelayUnreachable { relay, .. } => {
+            json!({ "kind": "RelayUnreachable", "relay": relay })
+        }
+        AuthError::TokenMalformed(_) => json!({ "kind": "TokenMalformed" }),
+        AuthError::TokenRejected(_) => json!({ "kind": "TokenRejected" }),
+        AuthError::InsufficientCapabilities { required, granted } => json!({
+            "kind": "InsufficientCapabilities",
+            "required": required.to_string(),
+            "granted": granted.to_string(),
+        }),
+        AuthError::SessionRefused { .. } => json!({ "kind": "SessionRefused" }),
+        AuthError::TimedOut => json!({ "kind": "TimedOut" }),
+        AuthError::Cancelled => json!({ "kind": "Cancelled" }),
+        _ => return None,
+    };
+    Some(data)
+}
+
 /// Converts a `pubky::BuildError` into a `PubkyError`.
 impl From<BuildError> for PubkyError {
     fn from(err: BuildError) -> Self {
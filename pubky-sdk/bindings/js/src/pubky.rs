@@ -8,7 +8,7 @@ use crate::actors::{
     session::Session,
     session_store::BrowserSessionStore,
     signer::Signer,
-    storage::PublicStorage,
+    storage::{PublicStorage, utils::response_to_web_response},
 };
 use crate::wrappers::keys::PublicKey;
 use crate::{client::constructor::Client, js_error::JsResult, wrappers::keys::Keypair};
@@ -272,6 +272,83 @@ impl Pubky {
             .map(Into::into)
     }
 
+    /// `GET` an addressed resource through an ICANN domain of the owner's homeserver.
+    ///
+    /// `domain` must be one the homeserver publishes in its Pkarr record. The connection
+    /// is authenticated by the domain's TLS certificate rather than the homeserver's key.
+    ///
+    /// @param {string} domain
+    /// @param {Address} address
+    /// @returns {Promise<Response>}
+    ///
+    /// @example
+    /// const text = await (await pubky.fetchViaDomain("homeserver.example.com", `${user}/pub/app/file.txt`)).text();
+    #[wasm_bindgen(js_name = "fetchViaDomain")]
+    pub async fn fetch_via_domain(
+        &self,
+        domain: String,
+        #[wasm_bindgen(unchecked_param_type = "Address")] address: String,
+    ) -> JsResult<web_sys::Response> {
+        let resp = self.0.fetch_via_domain(&domain, address).await?;
+        response_to_web_response(resp)
+    }
+
+    /// Ask a homeserver which version it runs, as `major.minor.patch`.
+    ///
+    /// @param {PublicKey} homeserver
+    /// @returns {Promise<string|undefined>} The version (e.g. `"0.9.3"`), or `undefined` if the
+    /// homeserver is unreachable or does not announce one.
+    #[wasm_bindgen(js_name = "homeserverVersion")]
+    pub async fn homeserver_version(&self, homeserver: &PublicKey) -> Option<String> {
+        self.0
+            .homeserver_version(homeserver.as_inner())
+            .await
+            .map(|version| version.to_string())
+    }
+
+    /// Check that a homeserver is reachable and runs a version this SDK can create
+    /// accounts on.
+    ///
+    /// @param {PublicKey} homeserver
+    /// @returns {Promise<string>} The announced version, e.g. `"0.9.3"`.
+    ///
+    /// @throws {PubkyError}
+    /// - `NotAHomeserver` if the key does not answer as a homeserver.
+    /// - `IncompatibleVersion` if it runs an unsupported version.
+    #[wasm_bindgen(js_name = "checkHomeserver")]
+    pub async fn check_homeserver(&self, homeserver: &PublicKey) -> JsResult<String> {
+        let version = self.0.check_homeserver(homeserver.as_inner()).await?;
+        Ok(version.to_string())
+    }
+
+    /// Ask a homeserver for its current time.
+    ///
+    /// @param {PublicKey} homeserver
+    /// @returns {Promise<number>} The homeserver's time, in Unix milliseconds (as `Date.now()`).
+    ///
+    /// @throws {PubkyError}
+    /// - `RequestError` if the homeserver is unreachable or has no time endpoint.
+    #[wasm_bindgen(js_name = "homeserverTime")]
+    pub async fn homeserver_time(&self, homeserver: &PublicKey) -> JsResult<f64> {
+        let time = self.0.homeserver_time(homeserver.as_inner()).await?;
+        Ok(time.as_u64() as f64 / 1000.0)
+    }
+
+    /// Measure how far the local clock is off a homeserver's clock, accounting for the
+    /// round trip. Useful to tell users to fix their clock when sign-in fails with an
+    /// `AuthenticationError` about clock skew.
+    ///
+    /// @param {PublicKey} homeserver
+    /// @returns {Promise<number>} Server time minus local time, in milliseconds.
+    ///
+    /// @throws {PubkyError}
+    /// - `RequestError` if the homeserver is unreachable or has no time endpoint.
+    #[wasm_bindgen(js_name = "measureClockSkew")]
+    pub async fn measure_clock_skew(&self, homeserver: &PublicKey) -> JsResult<f64> {
+        let skew = self.0.measure_clock_skew(homeserver.as_inner()).await?;
+        Ok(skew.offset_micros as f64 / 1000.0)
+    }
+
     /// Access the underlying HTTP client (advanced).
     ///
     /// @returns {Client}
@@ -73,3 +73,12 @@ pub fn set_log_level(level: Level) -> Result<(), JsValue> {
 pub fn resolve_pubky(identifier: &str) -> JsResult<String> {
     Ok(pubky::resolve_pubky(identifier)?.to_string())
 }
+
+/// Canonicalize a link to a user's resource, to deduplicate equivalent links.
+///
+/// @param {string} link `pubky://`, `pubky<pk>/…`, `https://<pk>/…`, `https://_pubky.<pk>/…` or `<pk>/…`.
+/// @returns {string} The canonical `pubky://<pk>/…` URL.
+#[wasm_bindgen(js_name = "canonicalizeUrl")]
+pub fn canonicalize_url(link: &str) -> JsResult<String> {
+    Ok(pubky::canonicalize_url(link)?.to_string())
+}
@@ -120,6 +120,35 @@ impl PublicKey {
     }
 }
 
+/// Verify a signature made with `Signer.signMessage`.
+///
+/// @param {PublicKey} publicKey The key that supposedly signed.
+/// @param {string} domainSeparator Must match the one used for signing.
+/// @param {Uint8Array} message
+/// @param {Uint8Array} signature 64-byte Ed25519 signature.
+/// @returns {boolean}
+/// @throws {PubkyError} `{ name: "InvalidInput" }` if `signature` is not 64 bytes long.
+#[wasm_bindgen(js_name = "verifyMessage")]
+pub fn verify_message(
+    public_key: &PublicKey,
+    domain_separator: &str,
+    message: &[u8],
+    signature: &[u8],
+) -> JsResult<bool> {
+    let signature: [u8; 64] = signature.try_into().map_err(|_err| {
+        PubkyError::new(
+            PubkyErrorName::InvalidInput,
+            "signature must be 64 bytes long",
+        )
+    })?;
+    Ok(pubky::verify_message(
+        &public_key.0,
+        domain_separator,
+        message,
+        &pubky::Signature::from_bytes(&signature),
+    ))
+}
+
 impl PublicKey {
     pub fn as_inner(&self) -> &NativePublicKey {
         &self.0
@@ -1,7 +1,10 @@
 #[allow(deprecated, reason = "Internal use of deprecated public API")]
 use crate::AuthToken;
+use crate::actors::auth::failure::{ensure_granted, token_failure};
 use crate::actors::auth::relay::AuthRelayMessage;
 use crate::errors::Result;
+use pubky_common::capabilities::Capabilities;
+use pubky_common::clock::TimeSource;
 
 /// Verified legacy auth token delivered through the relay channel.
 #[allow(deprecated, reason = "Internal use of deprecated public API")]
@@ -9,17 +12,24 @@ use crate::errors::Result;
 pub(crate) struct CookieApproval(pub(crate) AuthToken);
 
 impl CookieApproval {
-    /// Verify a relay message as a postcard-encoded [`AuthToken`].
-    pub(crate) fn decode(message: &AuthRelayMessage) -> Result<Self> {
+    /// Verify a relay message as a postcard-encoded [`AuthToken`], checking its
+    /// timestamp window against `clock` and that it grants every `required` capability.
+    pub(crate) fn decode(
+        message: &AuthRelayMessage,
+        clock: &dyn TimeSource,
+        required: &Capabilities,
+    ) -> Result<Self> {
         #[allow(deprecated, reason = "Internal use of deprecated public API")]
-        let token = AuthToken::verify(message.as_bytes())?;
+        let token = AuthToken::verify_with_clock(message.as_bytes(), clock)
+            .map_err(|e| token_failure(&e))?;
+        ensure_granted(required, &Capabilities::from(token.capabilities().to_vec()))?;
         Ok(Self(token))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use pubky_common::capabilities::Capabilities;
+    use pubky_common::clock::SystemClock;
 
     use super::*;
     use crate::Keypair;
@@ -31,7 +41,8 @@ mod tests {
         let token = AuthToken::sign(&keypair, Capabilities::default());
         let message = AuthRelayMessage::new(token.serialize());
 
-        let approval = CookieApproval::decode(&message).unwrap();
+        let approval =
+            CookieApproval::decode(&message, &SystemClock, &Capabilities::default()).unwrap();
 
         assert_eq!(approval.0, token);
     }
@@ -18,19 +18,34 @@ use crate::{Capabilities, PubkyHttpClient};
 /// The signer returns a [`pubky_common::auth::AuthToken`] which the SDK
 /// exchanges for a session cookie. For long-lived, mirror-friendly
 /// sessions, prefer [`crate::PubkyGrantAuthFlow`].
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct CookieAuthFlowBuilder {
     caps: Capabilities,
+    optional_caps: Capabilities,
     base_relay: Url,
     client: Option<PubkyHttpClient>,
     auth_kind: AuthFlowKind,
     client_secret: [u8; 32],
 }
 
+impl std::fmt::Debug for CookieAuthFlowBuilder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CookieAuthFlowBuilder")
+            .field("caps", &self.caps)
+            .field("optional_caps", &self.optional_caps)
+            .field("base_relay", &self.base_relay)
+            .field("client", &self.client)
+            .field("auth_kind", &self.auth_kind)
+            .field("client_secret", &"<redacted>")
+            .finish()
+    }
+}
+
 impl CookieAuthFlowBuilder {
     pub(crate) fn new(caps: Capabilities, auth_kind: AuthFlowKind) -> Self {
         Self {
             caps,
+            optional_caps: Capabilities::default(),
             base_relay: Url::parse(DEFAULT_HTTP_RELAY_INBOX)
                 .expect("Should be able to parse the default HTTP relay"),
             client: None,
@@ -39,6 +54,18 @@ impl CookieAuthFlowBuilder {
         }
     }
 
+    /// Request `caps` as optional: the signer may let the user decline them.
+    ///
+    /// They are sent as `ocaps` next to the required capabilities; signers that do not
+    /// know `ocaps` only grant the required ones. See
+    /// [`CapabilityRequest::granted_optional`](crate::CapabilityRequest::granted_optional)
+    /// to find out which ones the user granted.
+    #[must_use]
+    pub fn optional_capabilities(mut self, caps: &Capabilities) -> Self {
+        self.optional_caps = caps.clone();
+        self
+    }
+
     /// Set a custom relay base URL. Trailing slash optional.
     #[must_use]
     pub fn relay(mut self, url: Url) -> Self {
@@ -70,6 +97,7 @@ impl CookieAuthFlowBuilder {
     pub fn start(self) -> Result<PubkyCookieAuthFlow> {
         let Self {
             caps,
+            optional_caps,
             base_relay,
             client,
             auth_kind,
@@ -86,6 +114,7 @@ impl CookieAuthFlowBuilder {
                 DeepLinkScheme::PubkyAuth,
                 SigninParams {
                     capabilities: caps,
+                    optional_capabilities: optional_caps,
                     relay: base_relay.clone(),
                     secret: client_secret,
                 },
@@ -97,6 +126,7 @@ impl CookieAuthFlowBuilder {
                 DeepLinkScheme::PubkyAuth,
                 SignupParams {
                     capabilities: caps,
+                    optional_capabilities: optional_caps,
                     relay: base_relay.clone(),
                     secret: client_secret,
                     homeserver: *homeserver_public_key,
@@ -23,17 +23,23 @@
 
 use std::any::Any;
 use std::sync::{Arc, RwLock};
+use std::time::Duration;
 
 use async_trait::async_trait;
 use pubky_common::{auth::AuthToken, crypto::PublicKey, session::CookieSessionRecord};
 
 use reqwest::{Method, RequestBuilder, Response};
+use web_time::{SystemTime, UNIX_EPOCH};
 
 use crate::actors::session::core::PubkySession;
 use crate::actors::session::credential::{SessionCredential, credential_session_missing};
 use crate::{
-    PubkyHttpClient, actors::session::SessionInfo, actors::storage::resource::resolve_pubky,
-    cross_log, errors::Result, util::check_http_status,
+    PubkyHttpClient,
+    actors::session::SessionInfo,
+    actors::storage::resource::resolve_pubky,
+    cross_log,
+    errors::Result,
+    util::{check_auth_attempt_status, check_http_status},
 };
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -47,7 +53,7 @@ const SESSION_PATH: &str = "/session";
 /// and replays it on every request. On browser WASM the secret is
 /// inaccessible (the fetch spec hides `Set-Cookie`) and the browser cookie
 /// jar handles attachment automatically — `cookie` is `None`.
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct CookieCredential {
     /// User public key — used to name the `Cookie` header.
     user: PublicKey,
@@ -56,10 +62,25 @@ pub struct CookieCredential {
     /// Cookie secret captured from `Set-Cookie`. `None` only on browser
     /// WASM where the value is hidden by the fetch spec.
     cookie: Option<String>,
+    /// When the cookie expires, per its `Set-Cookie` attributes. `None` when
+    /// unknown, e.g. after restoring from a secret token.
+    expires: Option<SystemTime>,
     /// Homeserver this cookie may attach to.
     homeserver: Arc<RwLock<Option<PublicKey>>>,
 }
 
+impl std::fmt::Debug for CookieCredential {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CookieCredential")
+            .field("user", &self.user)
+            .field("record", &self.record)
+            .field("cookie", &self.cookie.as_ref().map(|_| "<redacted>"))
+            .field("expires", &self.expires)
+            .field("homeserver", &self.homeserver)
+            .finish()
+    }
+}
+
 impl CookieCredential {
     /// Create a cookie credential from a [`CookieSessionRecord`].
     pub(crate) fn new(
@@ -72,17 +93,23 @@ impl CookieCredential {
             user,
             record: Arc::new(RwLock::new(record)),
             cookie,
+            expires: None,
             homeserver: Arc::new(RwLock::new(homeserver)),
         }
     }
 
+    const fn with_expiry(mut self, expires: Option<SystemTime>) -> Self {
+        self.expires = expires;
+        self
+    }
+
     pub(crate) fn set_homeserver(&self, homeserver: PublicKey) {
         if let Ok(mut hs) = self.homeserver.write() {
             *hs = Some(homeserver);
         }
     }
 
-    fn bound_homeserver(&self) -> Option<PublicKey> {
+    pub(crate) fn bound_homeserver(&self) -> Option<PublicKey> {
         self.homeserver.read().ok().and_then(|hs| hs.clone())
     }
 
@@ -98,11 +125,12 @@ impl CookieCredential {
         let record = CookieSessionRecord::deserialize(&bytes)?;
         let user = record.public_key().clone();
         let cookie_name = user.z32();
-        let cookie = raw_set_cookies
+        let parsed = raw_set_cookies
             .iter()
             .filter_map(|raw| cookie::Cookie::parse(raw.clone()).ok())
-            .find(|c| c.name() == cookie_name)
-            .map(|c| c.value().to_string());
+            .find(|c| c.name() == cookie_name);
+        let expires = parsed.as_ref().and_then(expiry_of);
+        let cookie = parsed.map(|c| c.value().to_string());
 
         #[cfg(not(target_arch = "wasm32"))]
         {
@@ -122,7 +150,7 @@ impl CookieCredential {
         }
 
         cross_log!(info, "Hydrated cookie credential for {}", user);
-        Ok(Self::new(user, cookie, record, homeserver))
+        Ok(Self::new(user, cookie, record, homeserver).with_expiry(expires))
     }
 
     /// Establish a cookie credential from a signed [`AuthToken`] (legacy flow).
@@ -143,9 +171,9 @@ impl CookieCredential {
             homeserver.as_ref(),
         )
         .await?;
-        let response = request.body(token.serialize()).send().await?;
+        let response = client.send(request.body(token.serialize())).await?;
 
-        let response = check_http_status(response).await?;
+        let response = check_auth_attempt_status(client, response).await?;
         cross_log!(
             info,
             "Session exchange for {} succeeded; constructing credential",
@@ -160,6 +188,11 @@ impl CookieCredential {
         self.cookie.as_deref()
     }
 
+    /// When the cookie expires, if the homeserver said so when setting it.
+    pub(crate) const fn cookie_expiry(&self) -> Option<SystemTime> {
+        self.expires
+    }
+
     /// Returns a clone of the stored [`CookieSessionRecord`].
     pub(crate) fn cookie_record(&self) -> CookieSessionRecord {
         self.record
@@ -196,6 +229,17 @@ async fn session_request(
     client.cross_request(method, resolved).await
 }
 
+/// When a `Set-Cookie` cookie expires. `Max-Age` wins over `Expires`, as in browsers.
+fn expiry_of(cookie: &cookie::Cookie<'_>) -> Option<SystemTime> {
+    if let Some(max_age) = cookie.max_age() {
+        let seconds = u64::try_from(max_age.whole_seconds()).unwrap_or(0);
+        return Some(SystemTime::now() + Duration::from_secs(seconds));
+    }
+    let expires = cookie.expires_datetime()?;
+    let seconds = u64::try_from(expires.unix_timestamp()).ok()?;
+    Some(UNIX_EPOCH + Duration::from_secs(seconds))
+}
+
 /// Cross-target reader for `Set-Cookie` response header values.
 fn collect_set_cookies(response: &Response) -> Vec<String> {
     let mut out = Vec::new();
@@ -233,7 +277,7 @@ impl SessionCredential for CookieCredential {
         };
         let rb = session_request(client, Method::DELETE, &self.user, homeserver.as_ref()).await?;
         let rb = self.attach(rb, client).await?;
-        let response = rb.send().await.map_err(crate::Error::from)?;
+        let response = client.send(rb).await?;
         check_http_status(response).await?;
         Ok(())
     }
@@ -284,7 +328,7 @@ impl SessionCredential for CookieCredential {
         };
         let rb = session_request(client, Method::GET, user, homeserver.as_ref()).await?;
         let rb = self.attach(rb, client).await?;
-        let response = rb.send().await.map_err(crate::Error::from)?;
+        let response = client.send(rb).await?;
         if credential_session_missing(&response) {
             cross_log!(info, "Cookie session missing on revalidate");
             return Ok(None);
@@ -338,6 +382,64 @@ mod tests {
         )
     }
 
+    fn session_response(user: &PublicKey, set_cookie: &str) -> Response {
+        let record =
+            CookieSessionRecord::new(user, Capabilities::from(vec![Capability::root()]), None);
+        http::Response::builder()
+            .header(reqwest::header::SET_COOKIE, set_cookie)
+            .body(record.serialize())
+            .unwrap()
+            .into()
+    }
+
+    #[tokio::test]
+    async fn from_response_captures_cookie_expiry() {
+        let user = Keypair::random().public_key();
+        let name = user.z32();
+
+        let response = session_response(
+            &user,
+            &format!("{name}=secret; Expires=Wed, 21 Oct 2037 07:28:00 GMT; HttpOnly"),
+        );
+        let credential = CookieCredential::from_response(response, None)
+            .await
+            .unwrap();
+        assert_eq!(credential.cookie_secret(), Some("secret"));
+        assert_eq!(
+            credential.cookie_expiry(),
+            Some(UNIX_EPOCH + Duration::from_secs(2_139_722_880))
+        );
+
+        let response = session_response(
+            &user,
+            &format!("{name}=secret; Max-Age=60; Expires=Wed, 21 Oct 2037 07:28:00 GMT"),
+        );
+        let credential = CookieCredential::from_response(response, None)
+            .await
+            .unwrap();
+        let remaining = credential
+            .cookie_expiry()
+            .unwrap()
+            .duration_since(SystemTime::now())
+            .unwrap();
+        assert!(remaining <= Duration::from_secs(60) && remaining > Duration::from_secs(50));
+
+        let response = session_response(&user, &format!("{name}=secret"));
+        let credential = CookieCredential::from_response(response, None)
+            .await
+            .unwrap();
+        assert_eq!(credential.cookie_expiry(), None);
+    }
+
+    #[test]
+    fn debug_redacts_cookie_secret() {
+        let credential = cookie_credential(&Keypair::random().public_key(), None);
+        let debug = format!("{credential:?}");
+
+        assert!(!debug.contains("cookie-secret"));
+        assert!(debug.contains("<redacted>"));
+    }
+
     #[tokio::test]
     async fn can_attach_to_only_matches_bound_homeserver() {
         let user = Keypair::random().public_key();
@@ -55,10 +55,11 @@ use crate::actors::auth::cookie::approval::CookieApproval;
 use crate::actors::auth::cookie::builder::CookieAuthFlowBuilder;
 use crate::actors::auth::cookie::credential::CookieCredential;
 use crate::actors::auth::deep_links::DeepLink;
+use crate::actors::auth::failure::session_failure;
 use crate::actors::auth::kind::AuthFlowKind;
 use crate::actors::auth::relay::auth_relay_listener::AuthRelayListener;
 use crate::errors::Result;
-use crate::{Capabilities, PubkyHttpClient, PubkySession};
+use crate::{Capabilities, CapabilityRequest, PubkyHttpClient, PubkySession};
 
 /// End-to-end **legacy (cookie) auth flow** handle.
 ///
@@ -73,8 +74,9 @@ use crate::{Capabilities, PubkyHttpClient, PubkySession};
 ///    [`try_poll_credential_once`](Self::try_poll_credential_once).
 ///
 /// Background polling **starts immediately** at construction. Dropping this
-/// value cancels the background task; the relay channel itself expires
-/// server-side after its TTL.
+/// value cancels the background task best-effort; [`shutdown`](Self::shutdown)
+/// also waits for it to finish. The relay channel itself expires server-side
+/// after its TTL.
 #[deprecated(
     note = "Use PubkyGrantAuthFlow instead. Cookie-backed sessions are being phased out in favor of grant-backed, self-refreshing sessions."
 )]
@@ -123,6 +125,15 @@ impl PubkyCookieAuthFlow {
         self.auth_url.clone().into()
     }
 
+    /// The required and optional capabilities this flow asks for.
+    ///
+    /// Compare with the session's capabilities via
+    /// [`CapabilityRequest::granted_optional`] to learn which optional ones were granted.
+    #[must_use]
+    pub fn requested_capabilities(&self) -> CapabilityRequest {
+        self.auth_url.capability_request().unwrap_or_default()
+    }
+
     /// Block until the signer approves and return a ready-to-use
     /// [`PubkySession`].
     ///
@@ -139,10 +150,13 @@ impl PubkyCookieAuthFlow {
     /// authenticate private event streams.
     ///
     /// # Errors
-    /// - Returns [`crate::errors::Error::Authentication`] if the relay channel
-    ///   expires before approval.
-    /// - Propagates HTTP/transport failures while polling the relay or
-    ///   exchanging the token at `/session`.
+    /// Each stage fails with its own [`AuthError`](crate::errors::AuthError) variant:
+    /// - `TimedOut` or `Cancelled` if no approval arrives, and `RelayUnreachable` if
+    ///   polling the relay keeps failing.
+    /// - `TokenMalformed` or `TokenRejected` if the delivered token does not decode or
+    ///   verify, and `InsufficientCapabilities` if it lacks a required capability.
+    /// - `SessionRefused` if the homeserver rejects the token at `/session`. Other
+    ///   transport failures of the exchange are propagated.
     pub async fn await_approval(self) -> Result<PubkySession> {
         let client = self.client.clone();
         let credential = self.await_credential().await?;
@@ -165,13 +179,16 @@ impl PubkyCookieAuthFlow {
     /// - See [`await_approval`](Self::await_approval).
     pub async fn await_credential(self) -> Result<CookieCredential> {
         let homeserver = self.target_homeserver();
+        let required = self.requested_capabilities().required;
         let Self {
             relay_listener,
             client,
             ..
         } = self;
-        let approval = Self::await_decoded_approval(relay_listener).await?;
-        CookieCredential::from_auth_token(&approval.0, &client, homeserver).await
+        let approval = Self::await_decoded_approval(relay_listener, &client, &required).await?;
+        CookieCredential::from_auth_token(&approval.0, &client, homeserver)
+            .await
+            .map_err(session_failure)
     }
 
     /// Block until the signer approves and we receive an [`AuthToken`].
@@ -181,10 +198,18 @@ impl PubkyCookieAuthFlow {
     ///   expires before approval.
     /// - Propagates HTTP/transport failures encountered while polling the relay.
     pub async fn await_token(self) -> Result<AuthToken> {
-        let approval = Self::await_decoded_approval(self.relay_listener).await?;
+        let required = self.requested_capabilities().required;
+        let approval =
+            Self::await_decoded_approval(self.relay_listener, &self.client, &required).await?;
         Ok(approval.0)
     }
 
+    /// Abandon the flow: cancel the background relay polling and wait until the
+    /// task has finished.
+    pub async fn shutdown(self) {
+        self.relay_listener.shutdown().await;
+    }
+
     /// Non-blocking probe (single step) that **consumes any ready token** and returns:
     /// - `Ok(Some(session))` when a token was delivered and the session established.
     /// - `Ok(None)` if no payload yet (keep polling later).
@@ -218,7 +243,8 @@ impl PubkyCookieAuthFlow {
         };
         Ok(Some(
             CookieCredential::from_auth_token(&approval.0, &self.client, self.target_homeserver())
-                .await?,
+                .await
+                .map_err(session_failure)?,
         ))
     }
 
@@ -249,16 +275,24 @@ impl PubkyCookieAuthFlow {
         }
     }
 
-    async fn await_decoded_approval(relay_listener: AuthRelayListener) -> Result<CookieApproval> {
+    async fn await_decoded_approval(
+        relay_listener: AuthRelayListener,
+        client: &PubkyHttpClient,
+        required: &Capabilities,
+    ) -> Result<CookieApproval> {
         let message = relay_listener.await_message().await?;
-        CookieApproval::decode(&message)
+        CookieApproval::decode(&message, client.time_source(), required)
     }
 
     fn try_decoded_approval(&self) -> Result<Option<CookieApproval>> {
         let Some(message) = self.relay_listener.try_message() else {
             return Ok(None);
         };
-        Ok(Some(CookieApproval::decode(&message?)?))
+        Ok(Some(CookieApproval::decode(
+            &message?,
+            self.client.time_source(),
+            &self.requested_capabilities().required,
+        )?))
     }
 }
 
@@ -15,4 +15,4 @@ pub mod view;
 pub use credential::CookieCredential;
 #[allow(deprecated, reason = "Re-exporting deprecated public API")]
 pub use flow::PubkyCookieAuthFlow;
-pub use view::CookieSessionView;
+pub use view::{CookieSessionView, SessionCookie};
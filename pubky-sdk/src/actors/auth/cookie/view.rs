@@ -8,10 +8,12 @@
 //! read it (the WHATWG fetch spec hides `Set-Cookie` from clients):
 //! [`CookieSessionView::export_secret`] returns `None` in that case. On
 //! native and Node.js WASM the SDK owns the secret and `export_secret`
-//! always returns `Some`.
+//! always returns `Some`. For the same reason
+//! [`CookieSessionView::debug_cookies`] is empty on browser WASM.
 
 use base64::{Engine as _, engine::general_purpose::STANDARD};
 use pubky_common::session::CookieSessionRecord;
+use web_time::SystemTime;
 
 use super::credential::CookieCredential;
 use crate::actors::session::core::PubkySession;
@@ -23,6 +25,29 @@ pub struct CookieSessionView<'a> {
     credential: &'a CookieCredential,
 }
 
+/// A cookie a session sends with its requests, reported by
+/// [`CookieSessionView::debug_cookies`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SessionCookie {
+    /// Cookie name: the user's public key in z-base-32.
+    pub name: String,
+    /// Cookie value. `"<redacted>"` unless read with
+    /// [`CookieSessionView::debug_cookies_dangerous`].
+    pub value: String,
+    /// When the cookie expires, as set by the homeserver. `None` when unknown,
+    /// e.g. for sessions restored from a secret token.
+    pub expires: Option<SystemTime>,
+}
+
+impl SessionCookie {
+    /// Whether [`Self::expires`] is known and in the past.
+    #[must_use]
+    pub fn is_expired(&self) -> bool {
+        self.expires
+            .is_some_and(|expires| expires <= SystemTime::now())
+    }
+}
+
 impl PubkySession {
     /// Returns a [`CookieSessionView`] if this session is cookie-backed.
     ///
@@ -57,6 +82,52 @@ impl<'a> CookieSessionView<'a> {
         self.credential.cookie_record()
     }
 
+    /// The homeserver this cookie is bound to, once known.
+    pub(crate) fn bound_homeserver(&self) -> Option<pubky_common::crypto::PublicKey> {
+        self.credential.bound_homeserver()
+    }
+
+    /// The cookies this session sends, with values redacted.
+    ///
+    /// For debugging sessions that suddenly answer `401 Unauthorized`: confirm the
+    /// session cookie is present and has not expired. Empty on browser WASM, where
+    /// the runtime cookie jar holds the cookie out of the SDK's reach.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # fn ex(session: pubky::PubkySession) {
+    /// if let Some(cookie_session) = session.as_cookie() {
+    ///     for cookie in cookie_session.debug_cookies() {
+    ///         println!("{} expires {:?}", cookie.name, cookie.expires);
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn debug_cookies(&self) -> Vec<SessionCookie> {
+        self.cookies(|_| "<redacted>".to_string())
+    }
+
+    /// Like [`Self::debug_cookies`], but with the **secret** cookie values.
+    ///
+    /// Anyone holding a value can act as this session until it expires. Do not log
+    /// or persist the result.
+    #[must_use]
+    pub fn debug_cookies_dangerous(&self) -> Vec<SessionCookie> {
+        self.cookies(ToString::to_string)
+    }
+
+    fn cookies(&self, value: impl Fn(&str) -> String) -> Vec<SessionCookie> {
+        let Some(secret) = self.credential.cookie_secret() else {
+            return Vec::new();
+        };
+        vec![SessionCookie {
+            name: self.session.info().public_key().z32(),
+            value: value(secret),
+            expires: self.credential.cookie_expiry(),
+        }]
+    }
+
     /// Export session metadata for rehydrating after a tab refresh or process restart.
     ///
     /// The returned string contains **no secrets**; it is a base64 encoding of the
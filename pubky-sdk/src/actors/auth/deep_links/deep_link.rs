@@ -1,5 +1,6 @@
 use std::{fmt::Display, str::FromStr};
 
+use pubky_common::capabilities::{Capabilities, CapabilityRequest};
 use url::Url;
 
 use crate::actors::auth::deep_links::{
@@ -42,6 +43,29 @@ impl DeepLink {
             DeepLink::SeedExport(seed_export) => seed_export.into(),
         }
     }
+
+    /// The capabilities an auth deep link asks for, split into required and optional.
+    ///
+    /// Signers use this to render the consent screen. `None` for links that do not
+    /// request capabilities (`direct_signup`, `secret_export`).
+    #[must_use]
+    pub fn capability_request(&self) -> Option<CapabilityRequest> {
+        let (required, optional) = match self {
+            DeepLink::Signin(d) => (&d.params().capabilities, &d.params().optional_capabilities),
+            DeepLink::Signup(d) => (&d.params().capabilities, &d.params().optional_capabilities),
+            DeepLink::SigninGrant(d) => {
+                (&d.params().capabilities, &d.params().optional_capabilities)
+            }
+            DeepLink::SignupGrant(d) => {
+                (&d.params().capabilities, &d.params().optional_capabilities)
+            }
+            DeepLink::DirectSignup(_) | DeepLink::SeedExport(_) => return None,
+        };
+        Some(CapabilityRequest {
+            required: Capabilities::clone(required),
+            optional: Capabilities::clone(optional),
+        })
+    }
 }
 
 impl Display for DeepLink {
@@ -171,6 +195,21 @@ mod tests {
         assert!(matches!(parsed, DeepLink::Signin(_)));
     }
 
+    #[test]
+    fn test_capability_request_reads_optional_caps() {
+        let deep_link: DeepLink = "pubkyauth://signin_grant?caps=/pub/pubky.app/:rw&ocaps=/pub/contacts/:r&secret=kqnceEMgrNQM_xi06oQXjA3cJHX_RQmw1BY6JE1bse8&relay=https://httprelay.pubky.app/inbox&cid=franky.pubky.app&cpk=5jsjx1o6fzu6aeeo697r3i5rx15zq41kikcye8wtwdqm4nb4tryo"
+            .parse()
+            .unwrap();
+        let request = deep_link.capability_request().unwrap();
+        assert_eq!(request.required.to_string(), "/pub/pubky.app/:rw");
+        assert_eq!(request.optional.to_string(), "/pub/contacts/:r");
+
+        let without: DeepLink = "pubkyauth://signin?caps=/pub/pubky.app/:rw&secret=kqnceEMgrNQM_xi06oQXjA3cJHX_RQmw1BY6JE1bse8&relay=https://httprelay.pubky.app/inbox"
+            .parse()
+            .unwrap();
+        assert!(without.capability_request().unwrap().optional.is_empty());
+    }
+
     #[test]
     fn test_parse_deep_link_seed_export() {
         let deep_link =
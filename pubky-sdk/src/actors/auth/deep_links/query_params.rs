@@ -13,6 +13,15 @@ pub(super) fn parse_capabilities(url: &Url) -> Result<Capabilities, DeepLinkPars
         .map_err(|e| DeepLinkParseError::InvalidQueryParameter("caps", Box::new(e)))
 }
 
+/// Optional capabilities from `ocaps`; empty when absent.
+pub(super) fn parse_optional_capabilities(url: &Url) -> Result<Capabilities, DeepLinkParseError> {
+    optional_query(url, "ocaps")
+        .unwrap_or_default()
+        .as_str()
+        .try_into()
+        .map_err(|e| DeepLinkParseError::InvalidQueryParameter("ocaps", Box::new(e)))
+}
+
 pub(super) fn parse_relay(url: &Url) -> Result<Url, DeepLinkParseError> {
     Url::parse(&required_query(url, "relay")?)
         .map_err(|e| DeepLinkParseError::InvalidQueryParameter("relay", Box::new(e)))
@@ -61,11 +70,17 @@ pub(super) fn optional_query(url: &Url, key: &str) -> Option<String> {
 pub(super) fn append_signin_params(
     url: &mut Url,
     capabilities: &Capabilities,
+    optional_capabilities: &Capabilities,
     relay: &Url,
     secret: &[u8; 32],
 ) {
-    url.query_pairs_mut()
-        .append_pair("caps", &capabilities.to_string())
+    let mut query = url.query_pairs_mut();
+    query.append_pair("caps", &capabilities.to_string());
+    // Only emitted when needed so links stay readable by signers predating `ocaps`.
+    if !optional_capabilities.is_empty() {
+        query.append_pair("ocaps", &optional_capabilities.to_string());
+    }
+    query
         .append_pair("relay", relay.as_str())
         .append_pair("secret", &URL_SAFE_NO_PAD.encode(secret));
 }
@@ -73,12 +88,13 @@ pub(super) fn append_signin_params(
 pub(super) fn append_signup_params(
     url: &mut Url,
     capabilities: &Capabilities,
+    optional_capabilities: &Capabilities,
     relay: &Url,
     secret: &[u8; 32],
     homeserver: &PublicKey,
     signup_token: Option<&str>,
 ) {
-    append_signin_params(url, capabilities, relay, secret);
+    append_signin_params(url, capabilities, optional_capabilities, relay, secret);
     let mut query = url.query_pairs_mut();
     query.append_pair("hs", &homeserver.z32());
     if let Some(signup_token) = signup_token {
@@ -3,7 +3,10 @@ use url::Url;
 
 use super::{
     DeepLinkParseError,
-    query_params::{append_signin_params, parse_capabilities, parse_relay, parse_secret},
+    query_params::{
+        append_signin_params, parse_capabilities, parse_optional_capabilities, parse_relay,
+        parse_secret,
+    },
     typed_deep_link::{DeepLinkIntent, DeepLinkParams, TypedDeepLink},
 };
 
@@ -20,6 +23,8 @@ impl DeepLinkIntent for SigninIntent {
 pub struct SigninParams {
     /// Capabilities requested by the app.
     pub capabilities: Capabilities,
+    /// Capabilities the user may decline. Encoded as `ocaps`, omitted when empty.
+    pub optional_capabilities: Capabilities,
     /// Base HTTP relay URL.
     pub relay: Url,
     /// Secret used to derive the encrypted relay channel.
@@ -30,13 +35,20 @@ impl DeepLinkParams for SigninParams {
     fn parse(url: &Url) -> Result<Self, DeepLinkParseError> {
         Ok(Self {
             capabilities: parse_capabilities(url)?,
+            optional_capabilities: parse_optional_capabilities(url)?,
             relay: parse_relay(url)?,
             secret: parse_secret(url)?,
         })
     }
 
     fn append_query_pairs(&self, url: &mut Url) {
-        append_signin_params(url, &self.capabilities, &self.relay, &self.secret);
+        append_signin_params(
+            url,
+            &self.capabilities,
+            &self.optional_capabilities,
+            &self.relay,
+            &self.secret,
+        );
     }
 }
 
@@ -87,11 +99,14 @@ mod tests {
             DeepLinkScheme::PubkyAuth,
             SigninParams {
                 capabilities,
+                optional_capabilities: Capabilities::builder().read("/contacts/").finish(),
                 relay,
                 secret,
             },
         );
-        let parsed_again = SigninDeepLink::parse_url(&deep_link.to_url()).unwrap();
+        let url = deep_link.to_url();
+        assert!(url.as_str().contains("ocaps="));
+        let parsed_again = SigninDeepLink::parse_url(&url).unwrap();
 
         assert_eq!(parsed_again, deep_link);
     }
@@ -5,7 +5,7 @@ use super::{
     DeepLinkParseError,
     query_params::{
         append_grant_params, append_signin_params, parse_capabilities, parse_client_id,
-        parse_client_pk, parse_relay, parse_secret,
+        parse_client_pk, parse_optional_capabilities, parse_relay, parse_secret,
     },
     typed_deep_link::{DeepLinkIntent, DeepLinkParams, TypedDeepLink},
 };
@@ -23,6 +23,8 @@ impl DeepLinkIntent for SigninGrantIntent {
 pub struct SigninGrantParams {
     /// Capabilities requested by the app.
     pub capabilities: Capabilities,
+    /// Capabilities the user may decline. Encoded as `ocaps`, omitted when empty.
+    pub optional_capabilities: Capabilities,
     /// Base HTTP relay URL.
     pub relay: Url,
     /// Secret used to derive the encrypted relay channel.
@@ -37,6 +39,7 @@ impl DeepLinkParams for SigninGrantParams {
     fn parse(url: &Url) -> Result<Self, DeepLinkParseError> {
         Ok(Self {
             capabilities: parse_capabilities(url)?,
+            optional_capabilities: parse_optional_capabilities(url)?,
             relay: parse_relay(url)?,
             secret: parse_secret(url)?,
             client_id: parse_client_id(url)?,
@@ -45,7 +48,13 @@ impl DeepLinkParams for SigninGrantParams {
     }
 
     fn append_query_pairs(&self, url: &mut Url) {
-        append_signin_params(url, &self.capabilities, &self.relay, &self.secret);
+        append_signin_params(
+            url,
+            &self.capabilities,
+            &self.optional_capabilities,
+            &self.relay,
+            &self.secret,
+        );
         append_grant_params(url, &self.client_id, &self.client_pk);
     }
 }
@@ -86,6 +95,7 @@ mod tests {
             DeepLinkScheme::PubkyAuth,
             SigninGrantParams {
                 capabilities,
+                optional_capabilities: Capabilities::default(),
                 relay,
                 secret: [42; 32],
                 client_id,
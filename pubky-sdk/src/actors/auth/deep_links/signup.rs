@@ -4,8 +4,8 @@ use url::Url;
 use super::{
     DeepLinkParseError,
     query_params::{
-        append_signup_params, optional_query, parse_capabilities, parse_homeserver, parse_relay,
-        parse_secret,
+        append_signup_params, optional_query, parse_capabilities, parse_homeserver,
+        parse_optional_capabilities, parse_relay, parse_secret,
     },
     typed_deep_link::{DeepLinkIntent, DeepLinkParams, TypedDeepLink},
 };
@@ -23,6 +23,8 @@ impl DeepLinkIntent for SignupIntent {
 pub struct SignupParams {
     /// Capabilities requested by the app.
     pub capabilities: Capabilities,
+    /// Capabilities the user may decline. Encoded as `ocaps`, omitted when empty.
+    pub optional_capabilities: Capabilities,
     /// Base HTTP relay URL.
     pub relay: Url,
     /// Secret used to derive the encrypted relay channel.
@@ -37,6 +39,7 @@ impl DeepLinkParams for SignupParams {
     fn parse(url: &Url) -> Result<Self, DeepLinkParseError> {
         Ok(Self {
             capabilities: parse_capabilities(url)?,
+            optional_capabilities: parse_optional_capabilities(url)?,
             relay: parse_relay(url)?,
             secret: parse_secret(url)?,
             homeserver: parse_homeserver(url)?,
@@ -48,6 +51,7 @@ impl DeepLinkParams for SignupParams {
         append_signup_params(
             url,
             &self.capabilities,
+            &self.optional_capabilities,
             &self.relay,
             &self.secret,
             &self.homeserver,
@@ -113,6 +117,7 @@ mod tests {
             DeepLinkScheme::PubkyAuth,
             SignupParams {
                 capabilities,
+                optional_capabilities: Capabilities::default(),
                 relay,
                 secret: [123; 32],
                 homeserver,
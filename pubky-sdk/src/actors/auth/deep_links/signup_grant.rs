@@ -5,7 +5,8 @@ use super::{
     DeepLinkParseError,
     query_params::{
         append_grant_params, append_signup_params, optional_query, parse_capabilities,
-        parse_client_id, parse_client_pk, parse_homeserver, parse_relay, parse_secret,
+        parse_client_id, parse_client_pk, parse_homeserver, parse_optional_capabilities,
+        parse_relay, parse_secret,
     },
     typed_deep_link::{DeepLinkIntent, DeepLinkParams, TypedDeepLink},
 };
@@ -23,6 +24,8 @@ impl DeepLinkIntent for SignupGrantIntent {
 pub struct SignupGrantParams {
     /// Capabilities requested by the app.
     pub capabilities: Capabilities,
+    /// Capabilities the user may decline. Encoded as `ocaps`, omitted when empty.
+    pub optional_capabilities: Capabilities,
     /// Base HTTP relay URL.
     pub relay: Url,
     /// Secret used to derive the encrypted relay channel.
@@ -41,6 +44,7 @@ impl DeepLinkParams for SignupGrantParams {
     fn parse(url: &Url) -> Result<Self, DeepLinkParseError> {
         Ok(Self {
             capabilities: parse_capabilities(url)?,
+            optional_capabilities: parse_optional_capabilities(url)?,
             relay: parse_relay(url)?,
             secret: parse_secret(url)?,
             homeserver: parse_homeserver(url)?,
@@ -54,6 +58,7 @@ impl DeepLinkParams for SignupGrantParams {
         append_signup_params(
             url,
             &self.capabilities,
+            &self.optional_capabilities,
             &self.relay,
             &self.secret,
             &self.homeserver,
@@ -117,6 +122,7 @@ mod tests {
             DeepLinkScheme::PubkyAuth,
             SignupGrantParams {
                 capabilities,
+                optional_capabilities: Capabilities::default(),
                 relay,
                 secret: [42; 32],
                 homeserver,
@@ -0,0 +1,127 @@
+//! Classification of auth flow failures into the stage-specific [`AuthError`] variants.
+
+use std::fmt::Display;
+
+use pubky_common::auth::Error as TokenError;
+use pubky_common::capabilities::Capabilities;
+use reqwest::StatusCode;
+
+use crate::errors::{AuthError, Error, RequestError, Result};
+
+/// Failure while waiting on the relay channel `relay`.
+///
+/// Undecryptable payloads are malformed tokens; any other request failure means the
+/// relay could not be reached.
+pub(crate) fn relay_failure(relay: &dyn Display, error: Error) -> Error {
+    match error {
+        Error::Authentication(AuthError::DecryptError(e)) => {
+            AuthError::TokenMalformed(format!("cannot decrypt the relay message: {e}")).into()
+        }
+        Error::Request(e) => AuthError::RelayUnreachable {
+            relay: relay.to_string(),
+            reason: e.to_string(),
+        }
+        .into(),
+        other => other,
+    }
+}
+
+/// Failure verifying a legacy [`AuthToken`](crate::AuthToken).
+pub(crate) fn token_failure(error: &TokenError) -> AuthError {
+    match error {
+        TokenError::UnknownVersion | TokenError::Parsing(_) => {
+            AuthError::TokenMalformed(error.to_string())
+        }
+        _ => AuthError::TokenRejected(error.to_string()),
+    }
+}
+
+/// Fail unless `granted` covers every `required` capability.
+pub(crate) fn ensure_granted(required: &Capabilities, granted: &Capabilities) -> Result<()> {
+    if required.is_subset_of(granted) {
+        return Ok(());
+    }
+    Err(AuthError::InsufficientCapabilities {
+        required: required.clone(),
+        granted: granted.clone(),
+    }
+    .into())
+}
+
+/// Failure exchanging an approval for a session.
+///
+/// Client errors from the homeserver (other than rate limiting, which keeps its own
+/// variant) mean it refused the session; everything else is passed through.
+pub(crate) fn session_failure(error: Error) -> Error {
+    match error {
+        Error::Request(RequestError::Server { status, message })
+            if status.is_client_error() && status != StatusCode::TOO_MANY_REQUESTS =>
+        {
+            AuthError::SessionRefused { status, message }.into()
+        }
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_flow_failures() {
+        let relay = "https://relay.example/inbox/abc";
+        let unreachable = relay_failure(
+            &relay,
+            RequestError::Validation {
+                message: "boom".into(),
+            }
+            .into(),
+        );
+        assert!(matches!(
+            unreachable,
+            Error::Authentication(AuthError::RelayUnreachable { relay: r, .. }) if r == relay
+        ));
+
+        assert!(matches!(
+            token_failure(&TokenError::InvalidSignature),
+            AuthError::TokenRejected(_)
+        ));
+        assert!(matches!(
+            token_failure(&TokenError::UnknownVersion),
+            AuthError::TokenMalformed(_)
+        ));
+
+        let required = Capabilities::builder().read_write("/pub/app/").finish();
+        let granted = Capabilities::builder().read("/pub/app/").finish();
+        ensure_granted(&required, &required).unwrap();
+        assert!(matches!(
+            ensure_granted(&required, &granted),
+            Err(Error::Authentication(
+                AuthError::InsufficientCapabilities { .. }
+            ))
+        ));
+
+        let server = |status| {
+            session_failure(
+                RequestError::Server {
+                    status,
+                    message: "no".into(),
+                }
+                .into(),
+            )
+        };
+        assert!(matches!(
+            server(StatusCode::FORBIDDEN),
+            Error::Authentication(AuthError::SessionRefused { status, .. })
+                if status == StatusCode::FORBIDDEN
+        ));
+        assert!(matches!(
+            server(StatusCode::TOO_MANY_REQUESTS),
+            Error::Request(RequestError::Server { .. })
+        ));
+        assert!(matches!(
+            server(StatusCode::BAD_GATEWAY),
+            Error::Request(RequestError::Server { .. })
+        ));
+    }
+}
@@ -1,5 +1,7 @@
 use pubky_common::auth::grant::GrantClaims;
+use pubky_common::capabilities::Capabilities;
 
+use crate::actors::auth::failure::ensure_granted;
 use crate::actors::auth::relay::AuthRelayMessage;
 use crate::errors::{AuthError, Result};
 
@@ -11,12 +13,15 @@ pub(crate) struct GrantApproval {
 }
 
 impl GrantApproval {
-    /// Decode a relay message as a UTF-8 `pubky-grant` JWS.
-    pub(crate) fn decode(message: &AuthRelayMessage) -> Result<Self> {
-        let text = std::str::from_utf8(message.as_bytes())
-            .map_err(|e| AuthError::Validation(format!("invalid grant payload encoding: {e}")))?;
+    /// Decode a relay message as a UTF-8 `pubky-grant` JWS granting every `required`
+    /// capability.
+    pub(crate) fn decode(message: &AuthRelayMessage, required: &Capabilities) -> Result<Self> {
+        let text = std::str::from_utf8(message.as_bytes()).map_err(|e| {
+            AuthError::TokenMalformed(format!("invalid grant payload encoding: {e}"))
+        })?;
         let claims = GrantClaims::decode(text)
-            .map_err(|e| AuthError::Validation(format!("invalid grant payload: {e}")))?;
+            .map_err(|e| AuthError::TokenMalformed(format!("invalid grant payload: {e}")))?;
+        ensure_granted(required, &Capabilities::from(claims.caps.clone()))?;
         Ok(Self {
             jws: text.to_string(),
             claims,
@@ -26,10 +31,7 @@ impl GrantApproval {
 
 #[cfg(test)]
 mod tests {
-    use pubky_common::{
-        auth::jws::{ClientId, GRANT_JWS_TYP, GrantId, sign_jws},
-        capabilities::Capabilities,
-    };
+    use pubky_common::auth::jws::{ClientId, GRANT_JWS_TYP, GrantId, sign_jws};
 
     use super::*;
     use crate::Keypair;
@@ -50,7 +52,7 @@ mod tests {
         let grant_jws = sign_jws(&user_keypair, GRANT_JWS_TYP, &claims);
         let message = AuthRelayMessage::new(grant_jws.clone().into_bytes());
 
-        let approval = GrantApproval::decode(&message).unwrap();
+        let approval = GrantApproval::decode(&message, &Capabilities::default()).unwrap();
 
         assert_eq!(approval.jws, grant_jws);
         assert_eq!(approval.claims, claims);
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use url::Url;
 
 use pubky_common::{
@@ -14,6 +16,7 @@ use crate::actors::auth::grant::flow::PubkyGrantAuthFlow;
 use crate::actors::auth::grant::pop_signer::{DelegatedSignFn, GrantPopSigner};
 use crate::actors::auth::kind::AuthFlowKind;
 use crate::actors::auth::relay::auth_relay_listener::AuthRelayListener;
+use crate::actors::auth::relay::backoff::BackoffConfig;
 use crate::errors::Result;
 use crate::{Capabilities, PubkyHttpClient};
 
@@ -23,21 +26,40 @@ use crate::{Capabilities, PubkyHttpClient};
 /// - The signer signs a `pubky-grant` JWS instead of a legacy `AuthToken`.
 /// - The resulting [`PubkyGrantAuthFlow`] yields a grant-backed session that
 ///   self-refreshes.
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct GrantAuthFlowBuilder {
     caps: Capabilities,
+    optional_caps: Capabilities,
     base_relay: Url,
     client: Option<PubkyHttpClient>,
     auth_kind: AuthFlowKind,
     client_secret: [u8; 32],
     client_id: ClientId,
     client_signer: GrantPopSigner,
+    backoff: BackoffConfig,
+}
+
+impl std::fmt::Debug for GrantAuthFlowBuilder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GrantAuthFlowBuilder")
+            .field("caps", &self.caps)
+            .field("optional_caps", &self.optional_caps)
+            .field("base_relay", &self.base_relay)
+            .field("client", &self.client)
+            .field("auth_kind", &self.auth_kind)
+            .field("client_secret", &"<redacted>")
+            .field("client_id", &self.client_id)
+            .field("client_signer", &self.client_signer)
+            .field("backoff", &self.backoff)
+            .finish()
+    }
 }
 
 impl GrantAuthFlowBuilder {
     pub(crate) fn new(caps: Capabilities, auth_kind: AuthFlowKind, client_id: ClientId) -> Self {
         Self {
             caps,
+            optional_caps: Capabilities::default(),
             base_relay: Url::parse(DEFAULT_HTTP_RELAY_INBOX)
                 .expect("Should be able to parse the default HTTP relay"),
             client: None,
@@ -45,9 +67,22 @@ impl GrantAuthFlowBuilder {
             client_secret: random_bytes::<32>(),
             client_id,
             client_signer: GrantPopSigner::local(Keypair::random()),
+            backoff: BackoffConfig::NONE,
         }
     }
 
+    /// Request `caps` as optional: the signer may let the user decline them.
+    ///
+    /// They are sent as `ocaps` next to the required capabilities; signers that do not
+    /// know `ocaps` only grant the required ones. See
+    /// [`CapabilityRequest::granted_optional`](crate::CapabilityRequest::granted_optional)
+    /// to find out which ones the user granted.
+    #[must_use]
+    pub fn optional_capabilities(mut self, caps: &Capabilities) -> Self {
+        self.optional_caps = caps.clone();
+        self
+    }
+
     /// Set a custom relay base URL. Trailing slash optional.
     #[must_use]
     pub fn relay(mut self, url: Url) -> Self {
@@ -62,6 +97,35 @@ impl GrantAuthFlowBuilder {
         self
     }
 
+    /// Wait `interval` between relay poll requests. Shorthand for
+    /// [`backoff`](Self::backoff) with [`BackoffConfig::constant`].
+    #[must_use]
+    pub fn poll_interval(self, interval: Duration) -> Self {
+        self.backoff(BackoffConfig::constant(interval))
+    }
+
+    /// Pace relay poll requests with `backoff`, trading approval latency for relay load.
+    /// By default the relay is re-polled as soon as a long poll ends.
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use pubky::{AuthFlowKind, BackoffConfig, Capabilities, ClientId, PubkyGrantAuthFlow};
+    /// # fn run() -> pubky::Result<()> {
+    /// let caps = Capabilities::builder().read_write("/pub/my.app/").finish();
+    /// let flow = PubkyGrantAuthFlow::builder(&caps, AuthFlowKind::signin(), ClientId::new("my.app").unwrap())
+    ///     .backoff(BackoffConfig {
+    ///         jitter: 0.2,
+    ///         ..BackoffConfig::exponential(Duration::from_secs(1), Duration::from_secs(30))
+    ///     })
+    ///     .start()?;
+    /// # Ok(()) }
+    /// ```
+    #[must_use]
+    pub fn backoff(mut self, backoff: BackoffConfig) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
     /// Override the random `client_secret`. By default, a fresh 32-byte secret is generated.
     #[must_use]
     pub fn client_secret(mut self, client_secret: [u8; 32]) -> Self {
@@ -99,12 +163,14 @@ impl GrantAuthFlowBuilder {
     pub fn start(self) -> Result<PubkyGrantAuthFlow> {
         let Self {
             caps,
+            optional_caps,
             base_relay,
             client,
             auth_kind,
             client_secret,
             client_id,
             client_signer,
+            backoff,
         } = self;
 
         let client = match client {
@@ -119,6 +185,7 @@ impl GrantAuthFlowBuilder {
                 DeepLinkScheme::PubkyAuth,
                 SigninGrantParams {
                     capabilities: caps,
+                    optional_capabilities: optional_caps,
                     relay: base_relay.clone(),
                     secret: client_secret,
                     client_id,
@@ -134,6 +201,7 @@ impl GrantAuthFlowBuilder {
                     DeepLinkScheme::PubkyAuth,
                     SignupGrantParams {
                         capabilities: caps,
+                        optional_capabilities: optional_caps,
                         relay: base_relay.clone(),
                         secret: client_secret,
                         homeserver: hs_pk,
@@ -148,6 +216,7 @@ impl GrantAuthFlowBuilder {
         let relay_listener = AuthRelayListener::builder(client_secret)
             .relay_base_url(base_relay)
             .client(client.clone())
+            .backoff(backoff)
             .start()?;
 
         Ok(PubkyGrantAuthFlow::new(
@@ -1,2 +1,8 @@
 /// Default lifetime for issued grants: 2 years (per proposal v4-pop §"Token Lifetime").
 pub(crate) const DEFAULT_GRANT_LIFETIME_SECS: u64 = 2 * 365 * 24 * 3600;
+
+/// Shortest grant lifetime homeservers accept for sessions: 1 minute.
+pub const MIN_GRANT_LIFETIME_SECS: u64 = 60;
+
+/// Longest grant lifetime homeservers accept for sessions: 2 years.
+pub const MAX_GRANT_LIFETIME_SECS: u64 = DEFAULT_GRANT_LIFETIME_SECS;
@@ -46,6 +46,7 @@ const STORED_GRANT_CREDENTIAL_PREFIX: &str = "pubky-grant-credential-v1";
 const STORED_GRANT_CREDENTIAL_PREFIX_FAMILY: &str = "pubky-grant-credential-";
 
 /// Current Unix timestamp in seconds, cross-target.
+#[cfg(test)]
 pub(crate) fn now_unix() -> u64 {
     web_time::SystemTime::now()
         .duration_since(web_time::UNIX_EPOCH)
@@ -57,7 +58,6 @@ pub(crate) fn now_unix() -> u64 {
 ///
 /// Refresh paths take the mutex and hold it across the HTTP call so
 /// concurrent refreshes don't run into a race condition.
-#[derive(Debug)]
 pub(crate) struct GrantCredentialState {
     /// Current opaque bearer token (homeserver-issued).
     pub bearer: String,
@@ -75,6 +75,20 @@ pub(crate) struct GrantCredentialState {
     pub session: GrantSessionInfo,
 }
 
+impl fmt::Debug for GrantCredentialState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GrantCredentialState")
+            .field("bearer", &"<redacted>")
+            .field("token_expires_at", &self.token_expires_at)
+            .field("grant_jws", &"<redacted>")
+            .field("grant_claims", &self.grant_claims)
+            .field("client_signer", &self.client_signer)
+            .field("homeserver_pk", &self.homeserver_pk)
+            .field("session", &self.session)
+            .finish()
+    }
+}
+
 impl GrantCredentialState {
     fn is_near_expiry(&self, now: u64, slack: u64) -> bool {
         self.token_expires_at.saturating_sub(slack) <= now
@@ -274,7 +288,8 @@ impl GrantCredential {
     /// - Propagates HTTP/server errors from `POST /auth/grant/session`.
     pub async fn import_secret(token: &str, client: &PubkyHttpClient) -> Result<Self> {
         let saved = StoredGrantCredential::decode(token)?;
-        let (grant_jws, grant_claims, client_signer, homeserver_pk) = restore_material(saved)?;
+        let (grant_jws, grant_claims, client_signer, homeserver_pk) =
+            restore_material(saved, client.time_source().now_secs())?;
         credential_from_grant_exchange(
             client,
             grant_jws,
@@ -298,7 +313,7 @@ impl GrantCredential {
         sign: DelegatedSignFn,
     ) -> Result<Self> {
         let (grant_jws, grant_claims, client_signer, homeserver_pk) =
-            restore_delegated_material(state, sign)?;
+            restore_delegated_material(state, sign, client.time_source().now_secs())?;
         credential_from_grant_exchange(
             client,
             grant_jws,
@@ -320,7 +335,7 @@ impl GrantCredential {
         // Double-check pattern: by the time we acquired the lock, another
         // task may have already refreshed. Skip the network call if the
         // bearer is comfortably fresh now.
-        if !state.is_near_expiry(now_unix(), REFRESH_SLACK_SECS / 2) {
+        if !state.is_near_expiry(client.time_source().now_secs(), REFRESH_SLACK_SECS / 2) {
             return Ok(());
         }
 
@@ -328,11 +343,12 @@ impl GrantCredential {
             &state.client_signer,
             &state.homeserver_pk,
             &state.grant_claims.jti,
+            client.time_source().now_secs(),
         )
         .await?;
         let body = serde_json::json!({ "grant": &state.grant_jws, "pop": pop_jws });
 
-        let resp = client
+        let rb = client
             .cross_request_via_homeserver(
                 Method::POST,
                 &state.homeserver_pk,
@@ -340,9 +356,8 @@ impl GrantCredential {
                 GRANT_SESSION_PATH,
             )
             .await?
-            .json(&body)
-            .send()
-            .await?;
+            .json(&body);
+        let resp = client.send(rb).await?;
         let resp = check_http_status(resp).await?;
         let parsed: GrantSessionResponse =
             resp.json().await.map_err(|e| RequestError::DecodeJson {
@@ -383,13 +398,11 @@ impl SessionCredential for GrantCredential {
 
     async fn signout(&self, client: &PubkyHttpClient) -> Result<()> {
         let bearer = self.current_bearer().await;
-        let response = self
+        let rb = self
             .grant_session_request(client, Method::DELETE)
             .await?
-            .bearer_auth(&bearer)
-            .send()
-            .await
-            .map_err(crate::Error::from)?;
+            .bearer_auth(&bearer);
+        let response = client.send(rb).await?;
         check_http_status(response).await?;
         Ok(())
     }
@@ -399,7 +412,7 @@ impl SessionCredential for GrantCredential {
         // network call when no refresh is needed.
         let needs_refresh = {
             let grant_state = self.state.lock().await;
-            grant_state.is_near_expiry(now_unix(), REFRESH_SLACK_SECS)
+            grant_state.is_near_expiry(client.time_source().now_secs(), REFRESH_SLACK_SECS)
         };
         if needs_refresh {
             self.refresh(client).await?;
@@ -418,13 +431,11 @@ impl SessionCredential for GrantCredential {
         _user: &PublicKey,
     ) -> Result<Option<SessionInfo>> {
         let bearer = self.current_bearer().await;
-        let response = self
+        let rb = self
             .grant_session_request(client, Method::GET)
             .await?
-            .bearer_auth(&bearer)
-            .send()
-            .await
-            .map_err(crate::Error::from)?;
+            .bearer_auth(&bearer);
+        let response = client.send(rb).await?;
         if credential_session_missing(&response) {
             return Ok(None);
         }
@@ -482,11 +493,12 @@ fn to_session_info(session: &GrantSessionInfo) -> SessionInfo {
 
 fn restore_material(
     saved: StoredGrantCredential,
+    now: u64,
 ) -> Result<(String, GrantClaims, GrantPopSigner, PublicKey)> {
     let grant_claims = GrantClaims::decode(&saved.grant_jws).map_err(|err| {
         AuthError::Validation(format!("invalid stored grant credential grant JWS: {err}"))
     })?;
-    if grant_claims.exp <= now_unix() {
+    if grant_claims.exp <= now {
         return Err(AuthError::Validation("stored grant credential has expired".into()).into());
     }
 
@@ -509,13 +521,14 @@ fn restore_material(
 fn restore_delegated_material(
     saved: DelegatedGrantCredentialState,
     sign: DelegatedSignFn,
+    now: u64,
 ) -> Result<(String, GrantClaims, GrantPopSigner, PublicKey)> {
     let grant_claims = GrantClaims::decode(&saved.grant_jws).map_err(|err| {
         AuthError::Validation(format!(
             "invalid delegated grant credential grant JWS: {err}"
         ))
     })?;
-    if grant_claims.exp <= now_unix() {
+    if grant_claims.exp <= now {
         return Err(AuthError::Validation("delegated grant credential has expired".into()).into());
     }
 
@@ -544,17 +557,18 @@ fn invalid_stored_grant() -> AuthError {
 ///
 /// Builds the canonical `pubky-pop` claims (`aud`, `gid`, `nonce`, `iat`)
 /// and signs them with the client keypair via
-/// [`pubky_common::auth::jws::sign_jws`].
+/// [`pubky_common::auth::jws::sign_jws`]. `iat` is in Unix seconds.
 pub(crate) async fn sign_pop_for_grant(
     client_signer: &GrantPopSigner,
     homeserver_pk: &PublicKey,
     grant_id: &pubky_common::auth::jws::GrantId,
+    iat: u64,
 ) -> Result<String> {
     let claims = PopProofClaims {
         aud: homeserver_pk.clone(),
         gid: grant_id.clone(),
         nonce: PopNonce::generate(),
-        iat: now_unix(),
+        iat,
     };
     client_signer.sign_jws(POP_JWS_TYP, &claims).await
 }
@@ -567,6 +581,7 @@ mod tests {
     use pubky_common::{
         auth::jws::{ClientId, GRANT_JWS_TYP, GrantId},
         capabilities::Capability,
+        clock::MockClock,
     };
 
     use super::*;
@@ -586,7 +601,9 @@ mod tests {
         let (mut stored, _claims) = stored_credential(now_unix() + 3600);
         stored.client_key_secret = Keypair::random().secret();
 
-        let error = restore_material(stored).unwrap_err().to_string();
+        let error = restore_material(stored, now_unix())
+            .unwrap_err()
+            .to_string();
 
         assert!(error.contains("client key does not match"));
     }
@@ -601,7 +618,7 @@ mod tests {
             client_pk: Keypair::random().public_key(),
         };
 
-        let error = restore_delegated_material(saved, test_delegated_signer())
+        let error = restore_delegated_material(saved, test_delegated_signer(), now_unix())
             .unwrap_err()
             .to_string();
 
@@ -618,7 +635,7 @@ mod tests {
             client_pk: claims.cnf,
         };
 
-        let error = restore_delegated_material(saved, test_delegated_signer())
+        let error = restore_delegated_material(saved, test_delegated_signer(), now_unix())
             .unwrap_err()
             .to_string();
 
@@ -647,7 +664,28 @@ mod tests {
     fn restore_material_rejects_expired_grant() {
         let (stored, _claims) = stored_credential(now_unix().saturating_sub(1));
 
-        let error = restore_material(stored).unwrap_err().to_string();
+        let error = restore_material(stored, now_unix())
+            .unwrap_err()
+            .to_string();
+
+        assert!(error.contains("has expired"));
+    }
+
+    #[tokio::test]
+    async fn import_secret_uses_client_time_source() {
+        let (stored, _claims) = stored_credential(now_unix() + 3600);
+        let clock = Arc::new(MockClock::default());
+        let mut builder = PubkyHttpClient::builder();
+        builder
+            .isolated_pkarr_test()
+            .time_source(Arc::<MockClock>::clone(&clock));
+        let client = builder.build().unwrap();
+
+        clock.advance(std::time::Duration::from_secs(3601));
+        let error = GrantCredential::import_secret(&stored.encode(), &client)
+            .await
+            .unwrap_err()
+            .to_string();
 
         assert!(error.contains("has expired"));
     }
@@ -753,7 +791,7 @@ mod tests {
         let mut builder = PubkyHttpClient::builder();
         builder
             .isolated_pkarr_test()
-            .pkarr(|b| b.cache(cache.clone()));
+            .pkarr(|b| b.cache(Arc::<InMemoryCache>::clone(&cache)));
         let client = builder.build().unwrap();
         cache.put(&homeserver_keypair.public_key().into(), &homeserver_packet);
 
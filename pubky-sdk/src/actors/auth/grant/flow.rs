@@ -58,6 +58,7 @@ use url::Url;
 
 use crate::actors::Pkdns;
 use crate::actors::auth::deep_links::DeepLink;
+use crate::actors::auth::failure::session_failure;
 use crate::actors::auth::grant::approval::GrantApproval;
 use crate::actors::auth::grant::builder::GrantAuthFlowBuilder;
 use crate::actors::auth::grant::credential::GrantCredential;
@@ -66,7 +67,7 @@ use crate::actors::auth::grant::pop_signer::{DelegatedSignFn, GrantPopSigner};
 use crate::actors::auth::kind::AuthFlowKind;
 use crate::actors::auth::relay::auth_relay_listener::AuthRelayListener;
 use crate::errors::{AuthError, Result};
-use crate::{Capabilities, PubkyHttpClient, PubkySession};
+use crate::{Capabilities, CapabilityRequest, PubkyHttpClient, PubkySession};
 
 /// Serializable state for resuming a pending grant auth flow.
 ///
@@ -118,8 +119,9 @@ impl fmt::Debug for GrantAuthFlowState {
 ///    [`try_poll_credential_once`](Self::try_poll_credential_once).
 ///
 /// Background polling **starts immediately** at construction. Dropping this
-/// value cancels the background task; the relay channel itself expires
-/// server-side after its TTL.
+/// value cancels the background task best-effort; [`shutdown`](Self::shutdown)
+/// also waits for it to finish. The relay channel itself expires server-side
+/// after its TTL.
 pub struct PubkyGrantAuthFlow {
     relay_listener: AuthRelayListener,
     client: PubkyHttpClient,
@@ -185,6 +187,34 @@ impl PubkyGrantAuthFlow {
         self.auth_url.clone()
     }
 
+    /// The required and optional capabilities this flow asks for.
+    ///
+    /// Keep it before awaiting approval to find out which optional capabilities
+    /// the user granted:
+    ///
+    /// ```no_run
+    /// # use pubky::{Capabilities, PubkyGrantAuthFlow, AuthFlowKind, ClientId};
+    /// # async fn run() -> pubky::Result<()> {
+    /// let caps = Capabilities::builder().read_write("/pub/my.app/").finish();
+    /// let optional = Capabilities::builder().read("/pub/contacts/").finish();
+    /// let flow = PubkyGrantAuthFlow::builder(&caps, AuthFlowKind::signin(), ClientId::new("my.app").unwrap())
+    ///     .optional_capabilities(&optional)
+    ///     .start()?;
+    /// let request = flow.requested_capabilities();
+    /// let session = flow.await_approval().await?;
+    /// let granted = request.granted_optional(session.info().capabilities());
+    /// # Ok(()) }
+    /// ```
+    #[must_use]
+    pub fn requested_capabilities(&self) -> CapabilityRequest {
+        self.auth_url
+            .as_str()
+            .parse::<DeepLink>()
+            .ok()
+            .and_then(|deep_link| deep_link.capability_request())
+            .unwrap_or_default()
+    }
+
     /// Save the sensitive state required to restore this pending local grant flow.
     ///
     /// The returned state is only useful while the relay inbox still exists.
@@ -303,11 +333,14 @@ impl PubkyGrantAuthFlow {
     /// inspect or persist the credential before building a session.
     ///
     /// # Errors
-    /// - Returns [`crate::errors::Error::Authentication`] if the relay channel
-    ///   expires before approval.
-    /// - Propagates HTTP/transport failures while polling the relay or
-    ///   exchanging the grant for a bearer.
-    /// - Returns [`crate::errors::Error::Authentication`] if the issuer's
+    /// Each stage fails with its own [`AuthError`] variant:
+    /// - `TimedOut` or `Cancelled` if no approval arrives, and `RelayUnreachable` if
+    ///   polling the relay keeps failing.
+    /// - `TokenMalformed` if the delivered grant does not decode, and
+    ///   `InsufficientCapabilities` if it lacks a required capability.
+    /// - `SessionRefused` if the homeserver rejects the grant. Other transport
+    ///   failures of the exchange are propagated.
+    /// - Returns [`crate::errors::Error::RecordNotFound`] if the issuer's
     ///   homeserver cannot be resolved via PKARR (sign-in only).
     pub async fn await_approval(self) -> Result<PubkySession> {
         let client = self.client.clone();
@@ -324,16 +357,30 @@ impl PubkyGrantAuthFlow {
     /// # Errors
     /// - See [`await_approval`](Self::await_approval).
     pub async fn await_credential(self) -> Result<GrantCredential> {
+        let required = self.requested_capabilities().required;
         let Self {
             relay_listener,
             client,
             client_signer,
             ..
         } = self;
-        let approval = Self::await_decoded_approval(relay_listener).await?;
+        let approval = Self::await_decoded_approval(relay_listener, &required).await?;
         Self::exchange_for_credential(&client, approval, client_signer).await
     }
 
+    /// How many poll requests were sent to the relay so far, e.g. to show progress
+    /// while waiting. Restored flows count from zero.
+    #[must_use]
+    pub fn poll_attempts(&self) -> u32 {
+        self.relay_listener.poll_attempts()
+    }
+
+    /// Abandon the flow: cancel the background relay polling and wait until the
+    /// task has finished.
+    pub async fn shutdown(self) {
+        self.relay_listener.shutdown().await;
+    }
+
     /// Non-blocking probe (single step) that **consumes any ready grant** and
     /// returns:
     /// - `Ok(Some(session))` when a grant was delivered and the session was
@@ -382,19 +429,27 @@ impl PubkyGrantAuthFlow {
 
         let pkdns = Pkdns::with_client(client.clone());
         let hs_pk = pkdns.require_homeserver_of(&claims.iss).await?;
-        credential_from_grant_exchange(client, jws, claims, client_signer, hs_pk).await
+        credential_from_grant_exchange(client, jws, claims, client_signer, hs_pk)
+            .await
+            .map_err(session_failure)
     }
 
-    async fn await_decoded_approval(relay_listener: AuthRelayListener) -> Result<GrantApproval> {
+    async fn await_decoded_approval(
+        relay_listener: AuthRelayListener,
+        required: &Capabilities,
+    ) -> Result<GrantApproval> {
         let message = relay_listener.await_message().await?;
-        GrantApproval::decode(&message)
+        GrantApproval::decode(&message, required)
     }
 
     fn try_decoded_approval(&self) -> Result<Option<GrantApproval>> {
         let Some(message) = self.relay_listener.try_message() else {
             return Ok(None);
         };
-        Ok(Some(GrantApproval::decode(&message?)?))
+        Ok(Some(GrantApproval::decode(
+            &message?,
+            &self.requested_capabilities().required,
+        )?))
     }
 }
 
@@ -494,6 +549,7 @@ mod tests {
             DeepLinkScheme::PubkyAuth,
             SigninParams {
                 capabilities: Capabilities::default(),
+                optional_capabilities: Capabilities::default(),
                 relay: Url::parse("http://localhost/inbox").unwrap(),
                 secret: [7; 32],
             },
@@ -519,6 +575,7 @@ mod tests {
             DeepLinkScheme::PubkyAuth,
             SigninGrantParams {
                 capabilities: Capabilities::default(),
+                optional_capabilities: Capabilities::default(),
                 relay: Url::parse("http://localhost/inbox").unwrap(),
                 secret: [7; 32],
                 client_id: ClientId::new("mismatch.test").unwrap(),
@@ -19,9 +19,8 @@ use super::{
     credential::{GrantCredential, sign_pop_for_grant},
     pop_signer::GrantPopSigner,
 };
-use crate::actors::storage::resource::resolve_pubky;
 use crate::errors::{RequestError, Result};
-use crate::util::check_http_status;
+use crate::util::check_auth_attempt_status;
 use crate::{PubkyHttpClient, cross_log};
 
 /// Establish a grant-backed session by exchanging a user-signed grant for
@@ -73,7 +72,13 @@ pub(crate) async fn signup_account_from_grant(
     homeserver_pk: &PublicKey,
     signup_token: Option<&str>,
 ) -> Result<()> {
-    let pop_jws = sign_pop_for_grant(client_signer, homeserver_pk, &grant_claims.jti).await?;
+    let pop_jws = sign_pop_for_grant(
+        client_signer,
+        homeserver_pk,
+        &grant_claims.jti,
+        client.time_source().now_secs(),
+    )
+    .await?;
     let body = serde_json::json!({ "grant": grant_jws, "pop": pop_jws });
     let mut url = url::Url::parse(&format!(
         "https://{}/auth/grant/signup",
@@ -85,13 +90,9 @@ pub(crate) async fn signup_account_from_grant(
     if let Some(token) = signup_token {
         url.query_pairs_mut().append_pair("signup_token", token);
     }
-    let resp = client
-        .cross_request(Method::POST, url)
-        .await?
-        .json(&body)
-        .send()
-        .await?;
-    check_http_status(resp).await?;
+    let rb = client.cross_request(Method::POST, url).await?.json(&body);
+    let resp = client.send(rb).await?;
+    check_auth_attempt_status(client, resp).await?;
     Ok(())
 }
 
@@ -103,18 +104,28 @@ async fn post_grant_session(
     client_signer: &GrantPopSigner,
     homeserver_pk: &PublicKey,
 ) -> Result<GrantSessionResponse> {
-    let pop_jws = sign_pop_for_grant(client_signer, homeserver_pk, &grant_claims.jti).await?;
+    let pop_jws = sign_pop_for_grant(
+        client_signer,
+        homeserver_pk,
+        &grant_claims.jti,
+        client.time_source().now_secs(),
+    )
+    .await?;
     let body = serde_json::json!({ "grant": grant_jws, "pop": pop_jws });
 
-    let url = format!("pubky://{}/auth/grant/session", grant_claims.iss.z32());
-    let resolved = resolve_pubky(&url)?;
-    let resp = client
-        .cross_request(Method::POST, resolved)
+    // Address the homeserver directly: the user's `_pubky` record may not point
+    // at it (yet), e.g. right after signup or for a secondary homeserver.
+    let rb = client
+        .cross_request_via_homeserver(
+            Method::POST,
+            homeserver_pk,
+            &grant_claims.iss,
+            "/auth/grant/session",
+        )
         .await?
-        .json(&body)
-        .send()
-        .await?;
-    let resp = check_http_status(resp).await?;
+        .json(&body);
+    let resp = client.send(rb).await?;
+    let resp = check_auth_attempt_status(client, resp).await?;
     resp.json().await.map_err(|e| {
         RequestError::DecodeJson {
             message: format!("decoding grant session response: {e}"),
@@ -48,12 +48,8 @@ impl GrantManager {
         let url = format!("pubky://{}/auth/grant/sessions", self.user.z32());
         let resolved = resolve_pubky(&url)?;
         let rb = self.client.cross_request(Method::GET, resolved).await?;
-        let resp = self
-            .credential
-            .attach(rb, &self.client)
-            .await?
-            .send()
-            .await?;
+        let rb = self.credential.attach(rb, &self.client).await?;
+        let resp = self.client.send(rb).await?;
         let resp = check_http_status(resp).await?;
         let grants: Vec<GrantInfo> = resp.json().await.map_err(|e| RequestError::DecodeJson {
             message: format!("decoding /auth/grant/sessions response: {e}"),
@@ -77,12 +73,8 @@ impl GrantManager {
         );
         let resolved = resolve_pubky(&url)?;
         let rb = self.client.cross_request(Method::DELETE, resolved).await?;
-        let resp = self
-            .credential
-            .attach(rb, &self.client)
-            .await?
-            .send()
-            .await?;
+        let rb = self.credential.attach(rb, &self.client).await?;
+        let resp = self.client.send(rb).await?;
         check_http_status(resp).await?;
         Ok(())
     }
@@ -66,6 +66,11 @@ impl<'a> GrantSessionView<'a> {
         self.credential.export_delegated_restore_state().await
     }
 
+    /// The homeserver this grant is bound to (its `PoP` audience).
+    pub(crate) async fn homeserver(&self) -> pubky_common::crypto::PublicKey {
+        self.credential.state.lock().await.homeserver_pk.clone()
+    }
+
     /// Returns the current opaque bearer for this session.
     pub async fn current_bearer(&self) -> String {
         self.credential.current_bearer().await
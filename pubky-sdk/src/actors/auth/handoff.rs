@@ -0,0 +1,288 @@
+//! Hand a [`PubkySession`] over to another process.
+//!
+//! A desktop app that spawns a helper process can pass the helper a handoff blob
+//! instead of running a second auth flow. The blob carries everything needed to act as
+//! the same user against the same homeserver: the credential secret (cookie secret or
+//! grant refresh material), the user key, the homeserver the session is bound to and
+//! the session's capabilities.
+//!
+//! The blob is a bearer credential; see [`PubkySession::export_handoff`].
+//!
+//! # Format
+//!
+//! ```text
+//! pubky-session-handoff-v1:<kind>:<user z32>:<homeserver z32 or "-">:<base64url capabilities>:<secret>
+//! ```
+//!
+//! `<kind>` is `cookie` or `grant`. The version prefix changes whenever the layout does;
+//! [`PubkySession::import_handoff`] rejects versions it does not know with a clear error
+//! instead of misreading them.
+
+use std::sync::Arc;
+
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use pubky_common::{capabilities::Capabilities, crypto::PublicKey, session::CookieSessionRecord};
+
+use super::cookie::CookieCredential;
+use super::grant::GrantCredential;
+use crate::actors::session::core::PubkySession;
+use crate::actors::session::credential::SessionCredential;
+use crate::errors::{AuthError, RequestError};
+use crate::{PubkyHttpClient, Result, cross_log};
+
+const HANDOFF_PREFIX: &str = "pubky-session-handoff-v1";
+const HANDOFF_PREFIX_FAMILY: &str = "pubky-session-handoff-";
+const NO_HOMESERVER: &str = "-";
+
+const KIND_COOKIE: &str = "cookie";
+const KIND_GRANT: &str = "grant";
+
+fn invalid_handoff(reason: &str) -> RequestError {
+    RequestError::Validation {
+        message: format!("invalid session handoff: {reason}"),
+    }
+}
+
+/// Decoded handoff blob.
+struct Handoff<'a> {
+    kind: &'a str,
+    user: PublicKey,
+    homeserver: Option<PublicKey>,
+    capabilities: Capabilities,
+    secret: &'a str,
+}
+
+impl<'a> Handoff<'a> {
+    fn encode(&self) -> String {
+        let homeserver = self
+            .homeserver
+            .as_ref()
+            .map_or_else(|| NO_HOMESERVER.to_string(), PublicKey::z32);
+        let capabilities = URL_SAFE_NO_PAD.encode(self.capabilities.to_string());
+        format!(
+            "{HANDOFF_PREFIX}:{}:{}:{homeserver}:{capabilities}:{}",
+            self.kind,
+            self.user.z32(),
+            self.secret
+        )
+    }
+
+    fn decode(blob: &'a str) -> Result<Self> {
+        let mut parts = blob.trim().splitn(6, ':');
+        let mut next = |field: &str| {
+            parts
+                .next()
+                .ok_or_else(|| invalid_handoff(&format!("missing {field}")))
+        };
+
+        let prefix = next("version")?;
+        if prefix != HANDOFF_PREFIX {
+            let message = if prefix.starts_with(HANDOFF_PREFIX_FAMILY) {
+                format!(
+                    "unsupported session handoff version `{prefix}`; expected `{HANDOFF_PREFIX}`"
+                )
+            } else {
+                "not a session handoff blob".to_string()
+            };
+            return Err(RequestError::Validation { message }.into());
+        }
+        let kind = next("kind")?;
+        let user = PublicKey::try_from_z32(next("user")?)
+            .map_err(|_err| invalid_handoff("invalid user public key"))?;
+        let homeserver = match next("homeserver")? {
+            NO_HOMESERVER => None,
+            z32 => Some(
+                PublicKey::try_from_z32(z32)
+                    .map_err(|_err| invalid_handoff("invalid homeserver public key"))?,
+            ),
+        };
+        let capabilities = URL_SAFE_NO_PAD
+            .decode(next("capabilities")?)
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .and_then(|caps| Capabilities::try_from(caps.as_str()).ok())
+            .ok_or_else(|| invalid_handoff("invalid capabilities"))?;
+        let secret = next("secret")?;
+        if secret.is_empty() {
+            return Err(invalid_handoff("missing secret").into());
+        }
+
+        Ok(Self {
+            kind,
+            user,
+            homeserver,
+            capabilities,
+            secret,
+        })
+    }
+}
+
+impl PubkySession {
+    /// Export this session as a versioned, secret handoff blob for another process.
+    ///
+    /// Restore it there with [`Self::import_handoff`]. Unlike the cookie-only
+    /// [`CookieSessionView::export_secret`](super::cookie::CookieSessionView::export_secret),
+    /// this works for cookie and grant sessions alike and also carries the capabilities
+    /// and the homeserver the session is bound to.
+    ///
+    /// # Security
+    /// The blob is a **bearer credential**: whoever holds it can act as the user, with the
+    /// session's capabilities, until the session is signed out, the grant is revoked or it
+    /// expires. Pass it over a private channel (an inherited pipe, stdin, a `0o600` file)
+    /// rather than command-line arguments or environment variables, never log it, and sign
+    /// the session out when the helper is done.
+    ///
+    /// The blob starts with a version prefix (`pubky-session-handoff-v1:`); SDKs reject
+    /// versions they don't know instead of misreading them.
+    ///
+    /// # Errors
+    /// - Returns [`crate::errors::RequestError::Validation`] if the session's secret is
+    ///   not extractable: cookies managed by the browser's cookie jar (browser WASM) or
+    ///   grants whose `PoP` key is held by the browser.
+    pub async fn export_handoff(&self) -> Result<String> {
+        let info = self.info();
+        let (kind, homeserver, secret) = if let Some(cookie) = self.as_cookie() {
+            let secret = cookie
+                .export_secret()
+                .ok_or_else(|| RequestError::Validation {
+                    message: "session handoff is unsupported for browser-managed cookies".into(),
+                })?;
+            // `export_secret` is `<user>:<cookie>`; the blob carries the user on its own.
+            let cookie_secret = secret
+                .split_once(':')
+                .map_or(secret.as_str(), |(_, cookie)| cookie)
+                .to_string();
+            (KIND_COOKIE, cookie.bound_homeserver(), cookie_secret)
+        } else if let Some(grant) = self.as_grant() {
+            let secret =
+                grant
+                    .export_local_secret()
+                    .await
+                    .ok_or_else(|| RequestError::Validation {
+                        message: "session handoff is unsupported for browser-held grant keys"
+                            .into(),
+                    })?;
+            (KIND_GRANT, Some(grant.homeserver().await), secret)
+        } else {
+            return Err(RequestError::Validation {
+                message: "session handoff is unsupported for this credential".into(),
+            }
+            .into());
+        };
+
+        cross_log!(info, "Exporting session handoff for {}", info.public_key());
+        Ok(Handoff {
+            kind,
+            user: info.public_key().clone(),
+            homeserver,
+            capabilities: Capabilities::from(info.capabilities().to_vec()),
+            secret: &secret,
+        }
+        .encode())
+    }
+
+    /// Restore a session from a blob produced by [`Self::export_handoff`], typically in a
+    /// helper process.
+    ///
+    /// The credential is checked with the homeserver before the session is returned:
+    /// cookie sessions are revalidated against the carried homeserver, grant sessions
+    /// exchange their grant for a fresh bearer.
+    ///
+    /// # Errors
+    /// - Returns [`crate::errors::RequestError::Validation`] if the blob is malformed or
+    ///   uses a handoff version this SDK doesn't know.
+    /// - Returns [`crate::errors::AuthError::RequestExpired`] if the session has expired
+    ///   or was signed out.
+    /// - Propagates transport and server errors while validating the credential.
+    pub async fn import_handoff(blob: &str, client: Option<PubkyHttpClient>) -> Result<Self> {
+        let handoff = Handoff::decode(blob)?;
+        let client = match client {
+            Some(c) => c,
+            None => PubkyHttpClient::new()?,
+        };
+        cross_log!(info, "Importing session handoff for {}", handoff.user);
+
+        match handoff.kind {
+            KIND_COOKIE => {
+                let record =
+                    CookieSessionRecord::new(&handoff.user, handoff.capabilities.clone(), None);
+                let credential: Arc<dyn SessionCredential> = Arc::new(CookieCredential::new(
+                    handoff.user.clone(),
+                    Some(handoff.secret.to_string()),
+                    record,
+                    handoff.homeserver.clone(),
+                ));
+                let session = Self::from_credential(client, credential);
+                session
+                    .revalidate()
+                    .await?
+                    .ok_or(AuthError::RequestExpired)?;
+                Ok(session)
+            }
+            KIND_GRANT => {
+                let credential = GrantCredential::import_secret(handoff.secret, &client).await?;
+                let session = Self::from_credential(client, Arc::new(credential));
+                if session.public_key() != handoff.user {
+                    return Err(invalid_handoff("grant does not belong to the handoff user").into());
+                }
+                Ok(session)
+            }
+            other => Err(invalid_handoff(&format!("unknown credential kind `{other}`")).into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pubky_common::{capabilities::Capability, crypto::Keypair};
+
+    use super::*;
+
+    #[test]
+    fn handoff_round_trips() {
+        let user = Keypair::random().public_key();
+        let homeserver = Keypair::random().public_key();
+        let capabilities = Capabilities::from(vec![
+            Capability::read_write("/pub/app/"),
+            Capability::read("/priv/app/"),
+        ]);
+        let blob = Handoff {
+            kind: KIND_GRANT,
+            user: user.clone(),
+            homeserver: Some(homeserver.clone()),
+            capabilities: capabilities.clone(),
+            secret: "pubky-grant-credential-v1:abc:def:ghi",
+        }
+        .encode();
+
+        let decoded = Handoff::decode(&blob).unwrap();
+        assert_eq!(decoded.kind, KIND_GRANT);
+        assert_eq!(decoded.user, user);
+        assert_eq!(decoded.homeserver, Some(homeserver));
+        assert_eq!(decoded.capabilities, capabilities);
+        assert_eq!(decoded.secret, "pubky-grant-credential-v1:abc:def:ghi");
+
+        let blob = Handoff {
+            kind: KIND_COOKIE,
+            user,
+            homeserver: None,
+            capabilities: Capabilities::default(),
+            secret: "cookie",
+        }
+        .encode();
+        assert!(Handoff::decode(&blob).unwrap().homeserver.is_none());
+    }
+
+    #[test]
+    fn handoff_rejects_unknown_versions_and_garbage() {
+        let err = Handoff::decode("pubky-session-handoff-v9:cookie:a:b:c:d")
+            .err()
+            .unwrap();
+        assert!(
+            err.to_string()
+                .contains("unsupported session handoff version")
+        );
+        assert!(Handoff::decode("not a blob").is_err());
+        assert!(Handoff::decode("pubky-session-handoff-v1:cookie").is_err());
+    }
+}
@@ -1,6 +1,8 @@
 pub mod cookie;
 pub mod deep_links;
+mod failure;
 pub mod grant;
+mod handoff;
 pub mod kind;
 pub mod relay;
 
@@ -1,25 +1,26 @@
 use std::fmt;
 
+use futures_util::FutureExt;
 use futures_util::future::{AbortHandle, Abortable};
 
 use url::Url;
 
 #[allow(deprecated, reason = "Internal use of deprecated public API")]
 use super::{
-    AuthRelayMessage, http_relay_inbox_channel::EncryptedHttpRelayInboxChannel,
+    AuthRelayMessage,
+    backoff::{BackoffConfig, PollSchedule},
+    http_relay_inbox_channel::EncryptedHttpRelayInboxChannel,
     http_relay_link_channel::EncryptedHttpRelayLinkChannel,
 };
 #[allow(deprecated, reason = "Internal use of deprecated public API")]
 use crate::{
     PubkyHttpClient,
     actors::DEFAULT_HTTP_RELAY_INBOX,
+    actors::auth::failure::relay_failure,
     cross_log,
     errors::{AuthError, Result},
 };
 
-#[cfg(target_arch = "wasm32")]
-use futures_util::FutureExt; // for `.map(|_| ())` in WASM spawn
-
 /// Internal dispatch between inbox and link channel implementations.
 ///
 /// The variant is chosen automatically based on the relay URL path:
@@ -39,10 +40,11 @@ impl EncryptedAuthChannel {
         &self,
         client: &PubkyHttpClient,
         timeout: Option<std::time::Duration>,
+        schedule: &PollSchedule,
     ) -> Result<Option<Vec<u8>>> {
         match self {
-            Self::Inbox(ch) => Ok(ch.poll(client, timeout).await?),
-            Self::Link(ch) => Ok(ch.poll(client, timeout).await?),
+            Self::Inbox(ch) => Ok(ch.poll_scheduled(client, timeout, schedule).await?),
+            Self::Link(ch) => Ok(ch.poll_scheduled(client, timeout, schedule).await?),
         }
     }
 
@@ -91,11 +93,15 @@ fn is_link_url(url: &Url) -> bool {
 ///    or [`try_message`](Self::try_message).
 ///
 /// Background polling **starts immediately** at construction. Dropping this value cancels
-/// the background task; the relay channel itself expires server-side after its TTL.
+/// the background task best-effort; [`shutdown`](Self::shutdown) also waits for it to
+/// finish. The relay channel itself expires server-side after its TTL.
 #[derive(Debug)]
 pub struct AuthRelayListener {
     rx: flume::Receiver<Result<AuthRelayMessage>>,
     abort: AbortHandle,
+    /// Disconnects once the background task has finished.
+    done: flume::Receiver<()>,
+    schedule: PollSchedule,
 }
 
 impl AuthRelayListener {
@@ -108,8 +114,10 @@ impl AuthRelayListener {
     /// auth message.
     ///
     /// # Errors
-    /// - Returns [`crate::errors::Error::Authentication`] if the relay channel expires before approval.
-    /// - Propagates HTTP/transport failures while polling the relay.
+    /// - Returns [`AuthError::TimedOut`] if the relay channel expires before approval,
+    ///   and [`AuthError::Cancelled`] if polling was stopped.
+    /// - Returns [`AuthError::RelayUnreachable`] if polling the relay keeps failing, and
+    ///   [`AuthError::TokenMalformed`] if the message cannot be decrypted.
     pub(crate) async fn await_message(self) -> Result<AuthRelayMessage> {
         self.recv_message().await
     }
@@ -118,7 +126,20 @@ impl AuthRelayListener {
         self.rx
             .recv_async()
             .await
-            .map_err(|_err| AuthError::RequestExpired)?
+            .map_err(|_err| AuthError::Cancelled)?
+    }
+
+    /// Cancel the background polling and wait until the task has finished.
+    pub async fn shutdown(self) {
+        self.abort.abort();
+        // Never sent on, only disconnected when the task ends.
+        let _ = self.done.recv_async().await;
+    }
+
+    /// How many poll requests the background task has sent to the relay so far.
+    #[must_use]
+    pub fn poll_attempts(&self) -> u32 {
+        self.schedule.attempts()
     }
 
     /// Non-blocking check for a ready relay message.
@@ -134,6 +155,7 @@ impl AuthRelayListener {
     async fn poll_for_approval_loop(
         client: PubkyHttpClient,
         encrypted_channel: EncryptedAuthChannel,
+        schedule: PollSchedule,
         tx: flume::Sender<Result<AuthRelayMessage>>,
     ) {
         cross_log!(
@@ -141,7 +163,7 @@ impl AuthRelayListener {
             "Starting auth flow polling for relay channel {}",
             encrypted_channel
         );
-        let result = Self::poll_for_message(&client, &encrypted_channel).await;
+        let result = Self::poll_for_message(&client, &encrypted_channel, &schedule).await;
 
         if result.is_ok() {
             cross_log!(
@@ -157,11 +179,13 @@ impl AuthRelayListener {
     async fn poll_for_message(
         client: &PubkyHttpClient,
         encrypted_channel: &EncryptedAuthChannel,
+        schedule: &PollSchedule,
     ) -> Result<AuthRelayMessage> {
         let response = encrypted_channel
-            .poll(client, None)
-            .await?
-            .ok_or(AuthError::RequestExpired)?;
+            .poll(client, None, schedule)
+            .await
+            .map_err(|e| relay_failure(encrypted_channel, e))?
+            .ok_or(AuthError::TimedOut)?;
 
         // ACK: confirms receipt for inbox channels, no-op for link.
         // Best-effort: a failed ACK should not invalidate a delivered payload.
@@ -182,12 +206,24 @@ impl Drop for AuthRelayListener {
 
 /// Builder for [`AuthRelayListener`].
 ///
-/// Use to override the HTTP relay and/or the `PubkyHttpClient`.
-#[derive(Debug, Clone)]
+/// Use to override the HTTP relay, the `PubkyHttpClient` and/or the polling pace.
+#[derive(Clone)]
 pub struct AuthRelayListenerBuilder {
     relay_base_url: Url,
     secret: [u8; 32],
     client: Option<PubkyHttpClient>,
+    backoff: BackoffConfig,
+}
+
+impl std::fmt::Debug for AuthRelayListenerBuilder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AuthRelayListenerBuilder")
+            .field("relay_base_url", &self.relay_base_url)
+            .field("secret", &"<redacted>")
+            .field("client", &self.client)
+            .field("backoff", &self.backoff)
+            .finish()
+    }
 }
 
 #[allow(deprecated, reason = "Internal use of deprecated public API")]
@@ -197,6 +233,7 @@ impl AuthRelayListenerBuilder {
             relay_base_url: Url::parse(DEFAULT_HTTP_RELAY_INBOX).expect("Always valid"),
             secret,
             client: None,
+            backoff: BackoffConfig::NONE,
         }
     }
 
@@ -212,30 +249,49 @@ impl AuthRelayListenerBuilder {
         self
     }
 
+    /// Pace poll requests with `backoff`. By default the relay is re-polled right away.
+    pub fn backoff(mut self, backoff: BackoffConfig) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
     // Spawn background polling (single-shot delivery)
     fn spawn_background_polling(
         encrypted_channel: EncryptedAuthChannel,
         client: &PubkyHttpClient,
+        backoff: BackoffConfig,
     ) -> AuthRelayListener {
         let (tx, rx) = flume::bounded(1);
         let (abort_handle, abort_reg) = AbortHandle::new_pair();
+        let (done_tx, done_rx) = flume::bounded::<()>(0);
         let bg_client = client.clone();
+        let schedule = PollSchedule::new(backoff);
+        let bg_schedule = schedule.clone();
 
         let fut = async move {
             cross_log!(info, "Spawning auth flow polling task");
-            AuthRelayListener::poll_for_approval_loop(bg_client, encrypted_channel.clone(), tx)
-                .await;
+            AuthRelayListener::poll_for_approval_loop(
+                bg_client,
+                encrypted_channel.clone(),
+                bg_schedule,
+                tx,
+            )
+            .await;
         };
 
+        let task = Abortable::new(fut, abort_reg).map(move |_| drop(done_tx));
+
         #[cfg(not(target_arch = "wasm32"))]
-        tokio::spawn(Abortable::new(fut, abort_reg));
+        tokio::spawn(task);
 
         #[cfg(target_arch = "wasm32")]
-        wasm_bindgen_futures::spawn_local(Abortable::new(fut, abort_reg).map(|_| ()));
+        wasm_bindgen_futures::spawn_local(task);
 
         AuthRelayListener {
             rx,
             abort: abort_handle,
+            done: done_rx,
+            schedule,
         }
     }
 
@@ -263,7 +319,11 @@ impl AuthRelayListenerBuilder {
             )?)
         };
 
-        Ok(Self::spawn_background_polling(encrypted_channel, &client))
+        Ok(Self::spawn_background_polling(
+            encrypted_channel,
+            &client,
+            self.backoff,
+        ))
     }
 }
 
@@ -345,8 +405,12 @@ mod tests {
             .unwrap();
         let poll_handle = tokio::spawn(async move {
             let response = listener.await_message().await.unwrap();
-            let approval =
-                crate::actors::auth::cookie::approval::CookieApproval::decode(&response).unwrap();
+            let approval = crate::actors::auth::cookie::approval::CookieApproval::decode(
+                &response,
+                &pubky_common::clock::SystemClock,
+                &Capabilities::default(),
+            )
+            .unwrap();
             assert_eq!(approval.0, token);
         });
 
@@ -354,4 +418,62 @@ mod tests {
         producer_result.unwrap();
         poll_result.unwrap();
     }
+
+    #[tokio::test]
+    async fn shutdown_joins_the_polling_task() {
+        let relay = http_relay::HttpRelay::builder()
+            .http_port(0)
+            .run()
+            .await
+            .unwrap();
+        let inbox_base = relay.local_url().join("inbox").unwrap();
+        let metrics = tokio::runtime::Handle::current().metrics();
+        let before = metrics.num_alive_tasks();
+
+        let listener = AuthRelayListener::builder([7; 32])
+            .relay_base_url(inbox_base)
+            .client(PubkyHttpClient::new().unwrap())
+            .start()
+            .unwrap();
+        assert_eq!(metrics.num_alive_tasks(), before + 1);
+        // Let the task start its long poll.
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+        let polling = metrics.num_alive_tasks();
+        listener.shutdown().await;
+        assert!(metrics.num_alive_tasks() < polling, "polling task joined");
+        // The aborted long poll's connection tasks wind down on their own shortly after.
+        let settled = tokio::time::timeout(std::time::Duration::from_secs(2), async {
+            while metrics.num_alive_tasks() > before {
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+        })
+        .await;
+        assert!(settled.is_ok(), "background tasks leaked after shutdown");
+    }
+
+    #[tokio::test]
+    async fn backoff_spaces_out_failed_polls() {
+        // A port nobody listens on: every poll fails right away.
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let relay = Url::parse(&format!("http://127.0.0.1:{port}/inbox")).unwrap();
+        let interval = std::time::Duration::from_millis(200);
+
+        let start = web_time::Instant::now();
+        let listener = AuthRelayListener::builder([7; 32])
+            .relay_base_url(relay)
+            .client(PubkyHttpClient::new().unwrap())
+            .backoff(BackoffConfig::constant(interval))
+            .start()
+            .unwrap();
+        listener.recv_message().await.unwrap_err();
+
+        // Three failed attempts with a pause after each of the first two.
+        assert_eq!(listener.poll_attempts(), 3);
+        assert!(start.elapsed() >= interval * 2);
+    }
 }
@@ -0,0 +1,174 @@
+//! Pacing of relay poll requests.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+use pubky_common::crypto::random_bytes;
+
+/// How long an auth flow waits between relay poll requests.
+///
+/// Relays answer every poll as a long poll, so by default the next request is sent
+/// as soon as the previous one times out or fails. A backoff spaces requests out:
+/// the first retry waits `initial`, every further one `multiplier` times longer, up
+/// to `max`. Each delay is then shortened by a random share of up to `jitter`
+/// (between `0.0` and `1.0`), so many clients waiting at once do not poll in lockstep.
+///
+/// # Examples
+/// ```
+/// # use std::time::Duration;
+/// # use pubky::BackoffConfig;
+/// // 500ms, 1s, 2s, ... capped at 30s, each up to 20% shorter.
+/// let backoff = BackoffConfig {
+///     jitter: 0.2,
+///     ..BackoffConfig::exponential(Duration::from_millis(500), Duration::from_secs(30))
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BackoffConfig {
+    /// Delay before the first retry.
+    pub initial: Duration,
+    /// Upper bound of any delay.
+    pub max: Duration,
+    /// Growth factor between consecutive delays. Values below `1.0` count as `1.0`.
+    pub multiplier: f64,
+    /// Largest share of a delay that is randomly cut off, from `0.0` to `1.0`.
+    pub jitter: f64,
+}
+
+impl BackoffConfig {
+    /// Re-poll right away. This is the default.
+    pub const NONE: Self = Self::constant(Duration::ZERO);
+
+    /// Wait `interval` between every two polls.
+    #[must_use]
+    pub const fn constant(interval: Duration) -> Self {
+        Self {
+            initial: interval,
+            max: interval,
+            multiplier: 1.0,
+            jitter: 0.0,
+        }
+    }
+
+    /// Start at `initial` and double the delay after every retry, up to `max`.
+    #[must_use]
+    pub const fn exponential(initial: Duration, max: Duration) -> Self {
+        Self {
+            initial,
+            max,
+            multiplier: 2.0,
+            jitter: 0.0,
+        }
+    }
+
+    /// Delay before retry number `retry`, counting from zero.
+    pub(crate) fn delay(&self, retry: u32) -> Duration {
+        let growth = self
+            .multiplier
+            .max(1.0)
+            .powi(i32::try_from(retry).unwrap_or(i32::MAX));
+        let delay = Duration::try_from_secs_f64(self.initial.as_secs_f64() * growth)
+            .unwrap_or(self.max)
+            .min(self.max);
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        if jitter == 0.0 || delay.is_zero() {
+            return delay;
+        }
+        let unit = f64::from(u32::from_le_bytes(random_bytes::<4>())) / f64::from(u32::MAX);
+        delay.mul_f64(1.0 - jitter * unit)
+    }
+}
+
+impl Default for BackoffConfig {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+/// Backoff of a relay polling loop, plus a shared count of the requests it sent.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PollSchedule {
+    backoff: BackoffConfig,
+    attempts: Arc<AtomicU32>,
+}
+
+impl PollSchedule {
+    pub(crate) fn new(backoff: BackoffConfig) -> Self {
+        Self {
+            backoff,
+            attempts: Arc::default(),
+        }
+    }
+
+    /// Record a new poll request and return how many were sent so far.
+    pub(crate) fn next_attempt(&self) -> u32 {
+        self.attempts
+            .fetch_add(1, Ordering::Relaxed)
+            .saturating_add(1)
+    }
+
+    /// How many poll requests were sent so far.
+    pub(crate) fn attempts(&self) -> u32 {
+        self.attempts.load(Ordering::Relaxed)
+    }
+
+    /// Wait before retry number `retry`, but no longer than `remaining`.
+    pub(crate) async fn wait(&self, retry: u32, remaining: Option<Duration>) {
+        let delay = self.backoff.delay(retry);
+        let delay = remaining.map_or(delay, |remaining| delay.min(remaining));
+        if delay.is_zero() {
+            return;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        tokio::time::sleep(delay).await;
+        #[cfg(target_arch = "wasm32")]
+        futures_timer::Delay::new(delay).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_does_not_wait() {
+        assert_eq!(BackoffConfig::default().delay(0), Duration::ZERO);
+        assert_eq!(BackoffConfig::default().delay(10), Duration::ZERO);
+    }
+
+    #[test]
+    fn exponential_grows_up_to_max() {
+        let backoff =
+            BackoffConfig::exponential(Duration::from_millis(100), Duration::from_millis(500));
+        let delays: Vec<_> = (0..5).map(|retry| backoff.delay(retry)).collect();
+        assert_eq!(
+            delays,
+            [100, 200, 400, 500, 500]
+                .map(Duration::from_millis)
+                .to_vec()
+        );
+        assert_eq!(backoff.delay(u32::MAX), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn jitter_only_shortens_delays() {
+        let backoff = BackoffConfig {
+            jitter: 0.5,
+            ..BackoffConfig::constant(Duration::from_secs(1))
+        };
+        for retry in 0..50 {
+            let delay = backoff.delay(retry);
+            assert!(delay >= Duration::from_millis(500) && delay <= Duration::from_secs(1));
+        }
+    }
+
+    #[test]
+    fn attempts_are_shared_between_clones() {
+        let schedule = PollSchedule::default();
+        let observer = schedule.clone();
+        assert_eq!(schedule.next_attempt(), 1);
+        assert_eq!(schedule.next_attempt(), 2);
+        assert_eq!(observer.attempts(), 2);
+    }
+}
@@ -5,6 +5,7 @@ use pubky_common::crypto::hash;
 use reqwest::{Method, StatusCode};
 use url::Url;
 
+use super::backoff::PollSchedule;
 use crate::{PubkyHttpClient, cross_log, util::check_http_status};
 
 /// Default HTTP relay inbox base when none is supplied.
@@ -113,10 +114,10 @@ impl HttpRelayInboxChannel {
             Some(timeout) => request.timeout(timeout),
             None => request,
         };
-        let response = match request.send().await {
+        let response = match client.send(request).await {
             Ok(response) => response,
             Err(err) if err.is_timeout() => return Err(PollError::Timeout),
-            Err(err) => return Err(PollError::Failure(err.into())),
+            Err(err) => return Err(PollError::Failure(err)),
         };
 
         // The inbox server returns 408 Request Timeout when no message
@@ -144,20 +145,31 @@ impl HttpRelayInboxChannel {
         &self,
         client: &PubkyHttpClient,
         timeout: Option<Duration>,
+    ) -> crate::errors::Result<Option<Vec<u8>>> {
+        self.poll_scheduled(client, timeout, &PollSchedule::default())
+            .await
+    }
+
+    /// [`Self::poll`], pacing retries and counting requests with `schedule`.
+    pub(crate) async fn poll_scheduled(
+        &self,
+        client: &PubkyHttpClient,
+        timeout: Option<Duration>,
+        schedule: &PollSchedule,
     ) -> crate::errors::Result<Option<Vec<u8>>> {
         const MAX_FAILURES: usize = 3;
         let start = web_time::Instant::now();
-        let mut attempt = 0;
+        let remaining = || timeout.map(|t| t.checked_sub(start.elapsed()).unwrap_or_default());
+        let mut retries = 0;
         let mut consecutive_failures = 0;
         loop {
-            attempt += 1;
             if let Some(timeout) = timeout
                 && start.elapsed() >= timeout
             {
                 return Ok(None);
             }
-            let poll_timeout = timeout.map(|t| t.checked_sub(start.elapsed()).unwrap_or_default());
-            match self.poll_once(client, poll_timeout).await {
+            let attempt = schedule.next_attempt();
+            match self.poll_once(client, remaining()).await {
                 Ok(response) => {
                     cross_log!(
                         debug,
@@ -183,6 +195,8 @@ impl HttpRelayInboxChannel {
                     }
                 },
             }
+            schedule.wait(retries, remaining()).await;
+            retries += 1;
         }
     }
 
@@ -201,7 +215,7 @@ impl HttpRelayInboxChannel {
     ) -> crate::errors::Result<()> {
         let request = client.cross_request(Method::POST, self.to_url()).await?;
         let request = request.body(body.to_vec());
-        let response = request.send().await?;
+        let response = client.send(request).await?;
         check_http_status(response).await?;
         Ok(())
     }
@@ -215,7 +229,7 @@ impl HttpRelayInboxChannel {
     /// Returns an error if the HTTP request fails or the server returns an unexpected status.
     pub async fn ack(&self, client: &PubkyHttpClient) -> crate::errors::Result<bool> {
         let request = client.cross_request(Method::DELETE, self.to_url()).await?;
-        let response = request.send().await?;
+        let response = client.send(request).await?;
         match response.status() {
             StatusCode::OK => Ok(true),
             StatusCode::NOT_FOUND => Ok(false),
@@ -235,7 +249,7 @@ impl HttpRelayInboxChannel {
     /// Returns an error if the HTTP request fails or the server returns an unexpected status.
     pub async fn check_ack(&self, client: &PubkyHttpClient) -> crate::errors::Result<Option<bool>> {
         let request = client.cross_request(Method::GET, self.ack_url()).await?;
-        let response = request.send().await?;
+        let response = client.send(request).await?;
         match response.status() {
             StatusCode::OK => {
                 let body = response.text().await?;
@@ -266,10 +280,10 @@ impl HttpRelayInboxChannel {
             Some(timeout) => request.timeout(timeout),
             None => request,
         };
-        let response = match request.send().await {
+        let response = match client.send(request).await {
             Ok(response) => response,
             Err(err) if err.is_timeout() => return Ok(Some(false)),
-            Err(err) => return Err(err.into()),
+            Err(err) => return Err(err),
         };
         match response.status() {
             StatusCode::OK => Ok(Some(true)),
@@ -321,12 +335,21 @@ impl FromStr for HttpRelayInboxChannel {
 
 /// An encrypted HTTP relay inbox channel that encrypts/decrypts messages
 /// using a shared secret, with store-and-forward semantics.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq)]
 pub struct EncryptedHttpRelayInboxChannel {
     channel: HttpRelayInboxChannel,
     secret: [u8; 32],
 }
 
+impl std::fmt::Debug for EncryptedHttpRelayInboxChannel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EncryptedHttpRelayInboxChannel")
+            .field("channel", &self.channel)
+            .field("secret", &"<redacted>")
+            .finish()
+    }
+}
+
 impl EncryptedHttpRelayInboxChannel {
     /// Create a new encrypted inbox channel.
     ///
@@ -382,7 +405,22 @@ impl EncryptedHttpRelayInboxChannel {
         client: &PubkyHttpClient,
         timeout: Option<Duration>,
     ) -> crate::errors::Result<Option<Vec<u8>>> {
-        let Some(response) = self.channel.poll(client, timeout).await? else {
+        self.poll_scheduled(client, timeout, &PollSchedule::default())
+            .await
+    }
+
+    /// [`Self::poll`], pacing retries and counting requests with `schedule`.
+    pub(crate) async fn poll_scheduled(
+        &self,
+        client: &PubkyHttpClient,
+        timeout: Option<Duration>,
+        schedule: &PollSchedule,
+    ) -> crate::errors::Result<Option<Vec<u8>>> {
+        let Some(response) = self
+            .channel
+            .poll_scheduled(client, timeout, schedule)
+            .await?
+        else {
             return Ok(None);
         };
         let decrypted = pubky_common::crypto::decrypt(&response, &self.secret)?;
@@ -441,6 +479,16 @@ mod tests {
         );
     }
 
+    #[test]
+    fn encrypted_channel_debug_redacts_secret() {
+        let base_url = Url::parse(DEFAULT_HTTP_RELAY_INBOX).unwrap();
+        let channel = EncryptedHttpRelayInboxChannel::new(base_url, [9; 32]).unwrap();
+        let debug = format!("{channel:?}");
+
+        assert!(!debug.contains(&format!("{:?}", [9u8; 32])));
+        assert!(debug.contains(&channel.channel.channel_id));
+    }
+
     #[test]
     fn test_from_str() {
         let channel = "https://httprelay.pubky.app/inbox/1234567890"
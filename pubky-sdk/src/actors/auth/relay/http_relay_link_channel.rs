@@ -9,6 +9,7 @@ use pubky_common::crypto::hash;
 use reqwest::Method;
 use url::Url;
 
+use super::backoff::PollSchedule;
 use crate::{PubkyHttpClient, cross_log, util::check_http_status};
 
 /// Default HTTP relay base when none is supplied.
@@ -90,10 +91,10 @@ impl HttpRelayLinkChannel {
             Some(timeout) => request.timeout(timeout),
             None => request,
         };
-        let response = match request.send().await {
+        let response = match client.send(request).await {
             Ok(response) => response,
             Err(err) if err.is_timeout() => return Err(PollError::Timeout),
-            Err(err) => return Err(PollError::Failure(err.into())),
+            Err(err) => return Err(PollError::Failure(err)),
         };
 
         let response = match check_http_status(response).await {
@@ -106,24 +107,36 @@ impl HttpRelayLinkChannel {
     /// This poll will retry until a message is received or the timeout is reached.
     /// If the timeout is reached, Ok(None) is returned.
     /// Any underlying network errors will be retried.
+    #[cfg(test)]
     pub async fn poll(
         &self,
         client: &PubkyHttpClient,
         timeout: Option<Duration>,
+    ) -> crate::errors::Result<Option<Vec<u8>>> {
+        self.poll_scheduled(client, timeout, &PollSchedule::default())
+            .await
+    }
+
+    /// [`Self::poll`], pacing retries and counting requests with `schedule`.
+    pub(crate) async fn poll_scheduled(
+        &self,
+        client: &PubkyHttpClient,
+        timeout: Option<Duration>,
+        schedule: &PollSchedule,
     ) -> crate::errors::Result<Option<Vec<u8>>> {
         const MAX_FAILURES: usize = 3;
         let start = web_time::Instant::now();
-        let mut attempt = 0;
+        let remaining = || timeout.map(|t| t.checked_sub(start.elapsed()).unwrap_or_default());
+        let mut retries = 0;
         let mut consecutive_failures = 0;
         loop {
-            attempt += 1;
             if let Some(timeout) = timeout
                 && start.elapsed() >= timeout
             {
                 return Ok(None);
             }
-            let poll_timeout = timeout.map(|t| t.checked_sub(start.elapsed()).unwrap_or_default());
-            match self.poll_once(client, poll_timeout).await {
+            let attempt = schedule.next_attempt();
+            match self.poll_once(client, remaining()).await {
                 Ok(response) => {
                     cross_log!(
                         debug,
@@ -147,6 +160,8 @@ impl HttpRelayLinkChannel {
                     }
                 },
             }
+            schedule.wait(retries, remaining()).await;
+            retries += 1;
         }
     }
 
@@ -164,7 +179,7 @@ impl HttpRelayLinkChannel {
     ) -> std::result::Result<(), crate::errors::Error> {
         let request = client.cross_request(Method::POST, self.to_url()).await?;
         let request = request.body(body.to_vec());
-        let response = request.send().await?;
+        let response = client.send(request).await?;
         response.error_for_status()?;
         Ok(())
     }
@@ -210,13 +225,23 @@ impl FromStr for HttpRelayLinkChannel {
 /// A encrypted HTTP relay channel that can produce and consume an encrypted message.
 /// Deprecated, use `EncryptedHttpRelayInboxChannel` instead.
 #[deprecated(note = "Use `EncryptedHttpRelayInboxChannel` instead")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq)]
 #[allow(deprecated, reason = "Internal use of deprecated public API")]
 pub struct EncryptedHttpRelayLinkChannel {
     channel: HttpRelayLinkChannel,
     secret: [u8; 32],
 }
 
+#[allow(deprecated, reason = "Internal use of deprecated public API")]
+impl std::fmt::Debug for EncryptedHttpRelayLinkChannel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EncryptedHttpRelayLinkChannel")
+            .field("channel", &self.channel)
+            .field("secret", &"<redacted>")
+            .finish()
+    }
+}
+
 #[allow(dead_code, deprecated, reason = "Internal use only")]
 impl EncryptedHttpRelayLinkChannel {
     pub fn new(relay_base_url: Url, secret: [u8; 32]) -> crate::errors::Result<Self> {
@@ -263,7 +288,22 @@ impl EncryptedHttpRelayLinkChannel {
         client: &PubkyHttpClient,
         timeout: Option<Duration>,
     ) -> std::result::Result<Option<Vec<u8>>, crate::errors::Error> {
-        let Some(response) = self.channel.poll(client, timeout).await? else {
+        self.poll_scheduled(client, timeout, &PollSchedule::default())
+            .await
+    }
+
+    /// [`Self::poll`], pacing retries and counting requests with `schedule`.
+    pub(crate) async fn poll_scheduled(
+        &self,
+        client: &PubkyHttpClient,
+        timeout: Option<Duration>,
+        schedule: &PollSchedule,
+    ) -> crate::errors::Result<Option<Vec<u8>>> {
+        let Some(response) = self
+            .channel
+            .poll_scheduled(client, timeout, schedule)
+            .await?
+        else {
             return Ok(None);
         };
         let decrypted = pubky_common::crypto::decrypt(&response, &self.secret)?;
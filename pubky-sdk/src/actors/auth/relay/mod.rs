@@ -1,4 +1,5 @@
 pub mod auth_relay_listener;
+pub mod backoff;
 pub mod http_relay_inbox_channel;
 pub mod http_relay_link_channel;
 
@@ -0,0 +1,184 @@
+//! Bulk export of a homeserver's event history through its admin API.
+//!
+//! Unlike [`EventStreamBuilder`](super::EventStreamBuilder), which follows a few users live,
+//! the export replays every event (public and private) of a cursor range and ends. It is
+//! meant for operators backing up or analysing a homeserver, so it talks to the admin
+//! server and needs the admin password.
+//!
+//! The body is newline-delimited JSON, one event per line:
+//! ```text
+//! {"cursor":"42","type":"PUT","resource":"pubky://<user>/pub/a.txt","hash":"<base64>","timestamp":1700000000000000}
+//! {"cursor":"43","type":"DEL","resource":"pubky://<user>/pub/a.txt","hash":null,"timestamp":1700000000000001}
+//! ```
+//! `timestamp` is in microseconds since the Unix epoch. Lines are streamed as the homeserver
+//! pages through its database, so neither side holds the whole history in memory. If the
+//! transfer breaks, resume with [`EventExportBuilder::from`] set to the last received cursor.
+//!
+//! # Example
+//! ```no_run
+//! use pubky::Pubky;
+//! use futures_util::StreamExt;
+//!
+//! # async fn example() -> pubky::Result<()> {
+//! let pubky = Pubky::new()?;
+//! let mut export = pubky
+//!     .event_export("http://127.0.0.1:6288", "admin-password")
+//!     .bytes_stream()
+//!     .await?;
+//!
+//! while let Some(chunk) = export.next().await {
+//!     let chunk = chunk?;
+//!     // Append `chunk` to a file, feed it to a JSON-lines parser, ...
+//! #   drop(chunk);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::pin::Pin;
+
+use bytes::Bytes;
+use futures_util::{Stream, StreamExt};
+use reqwest::Method;
+use url::Url;
+
+use crate::{
+    EventCursor, PubkyHttpClient, cross_log,
+    errors::{Error, Result},
+    util::check_http_status,
+};
+
+/// Builder for a bulk export of a homeserver's events. Created by
+/// [`Pubky::event_export`](crate::Pubky::event_export).
+#[derive(Clone)]
+pub struct EventExportBuilder {
+    client: PubkyHttpClient,
+    admin_url: String,
+    admin_password: String,
+    from: Option<EventCursor>,
+    to: Option<EventCursor>,
+}
+
+impl std::fmt::Debug for EventExportBuilder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventExportBuilder")
+            .field("admin_url", &self.admin_url)
+            .field("admin_password", &"<redacted>")
+            .field("from", &self.from)
+            .field("to", &self.to)
+            .finish_non_exhaustive()
+    }
+}
+
+impl EventExportBuilder {
+    pub(crate) fn new(client: PubkyHttpClient, admin_url: &str, admin_password: &str) -> Self {
+        Self {
+            client,
+            admin_url: admin_url.to_string(),
+            admin_password: admin_password.to_string(),
+            from: None,
+            to: None,
+        }
+    }
+
+    /// Only export events after `cursor` (exclusive). Pass the last cursor received to
+    /// resume an interrupted export.
+    #[must_use]
+    pub const fn from(mut self, cursor: EventCursor) -> Self {
+        self.from = Some(cursor);
+        self
+    }
+
+    /// Only export events up to `cursor` (inclusive).
+    #[must_use]
+    pub const fn to(mut self, cursor: EventCursor) -> Self {
+        self.to = Some(cursor);
+        self
+    }
+
+    /// Build the export URL: `{admin_url}/events-export?from=..&to=..`.
+    fn build_request_url(&self) -> Result<Url> {
+        let mut url = Url::parse(&self.admin_url)?;
+        url.path_segments_mut()
+            .map_err(|()| url::ParseError::RelativeUrlWithCannotBeABaseBase)?
+            .pop_if_empty()
+            .push("events-export");
+        let bounds = [("from", self.from), ("to", self.to)];
+        for (key, cursor) in bounds {
+            if let Some(cursor) = cursor {
+                url.query_pairs_mut().append_pair(key, &cursor.to_string());
+            }
+        }
+        cross_log!(debug, "Event export URL: {}", url);
+        Ok(url)
+    }
+
+    async fn bytes_stream_internal(self) -> Result<impl Stream<Item = Result<Bytes>>> {
+        let url = self.build_request_url()?;
+        let request = self
+            .client
+            .cross_request_anonymous(Method::GET, url)
+            .await?
+            .header("X-Admin-Password", &self.admin_password);
+        let response = check_http_status(self.client.send(request).await?).await?;
+        Ok(response
+            .bytes_stream()
+            .map(|chunk| chunk.map_err(Error::from)))
+    }
+
+    /// Start the export and stream its raw newline-delimited JSON body.
+    ///
+    /// Chunks do not follow line boundaries; split on `\n` to get one event per line.
+    ///
+    /// # Errors
+    /// - Returns [`crate::errors::Error::Parse`] if the admin URL is invalid.
+    /// - Returns [`crate::errors::RequestError::Server`] if the homeserver rejects the
+    ///   request, e.g. `401` for a wrong password or `400` for an invalid cursor.
+    /// - Propagates HTTP transport errors; the stream itself yields them too if the
+    ///   transfer breaks midway.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn bytes_stream(self) -> Result<Pin<Box<dyn Stream<Item = Result<Bytes>> + Send>>> {
+        let stream = self.bytes_stream_internal().await?;
+        Ok(Box::pin(stream))
+    }
+
+    /// Start the export and stream its raw newline-delimited JSON body (WASM version).
+    ///
+    /// Chunks do not follow line boundaries; split on `\n` to get one event per line.
+    ///
+    /// # Errors
+    /// - Returns [`crate::errors::Error::Parse`] if the admin URL is invalid.
+    /// - Returns [`crate::errors::RequestError::Server`] if the homeserver rejects the
+    ///   request, e.g. `401` for a wrong password or `400` for an invalid cursor.
+    /// - Propagates HTTP transport errors; the stream itself yields them too if the
+    ///   transfer breaks midway.
+    #[cfg(target_arch = "wasm32")]
+    pub async fn bytes_stream(self) -> Result<Pin<Box<dyn Stream<Item = Result<Bytes>>>>> {
+        let stream = self.bytes_stream_internal().await?;
+        Ok(Box::pin(stream))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_request_url_appends_endpoint_and_range() {
+        let client = PubkyHttpClient::new().unwrap();
+        let url = EventExportBuilder::new(client.clone(), "http://127.0.0.1:6288", "pw")
+            .build_request_url()
+            .unwrap();
+        assert_eq!(url.as_str(), "http://127.0.0.1:6288/events-export");
+
+        let url = EventExportBuilder::new(client, "http://localhost:6288/admin/", "pw")
+            .from(EventCursor::new(10))
+            .to(EventCursor::new(20))
+            .build_request_url()
+            .unwrap();
+        assert_eq!(
+            url.as_str(),
+            "http://localhost:6288/admin/events-export?from=10&to=20"
+        );
+    }
+}
@@ -84,6 +84,7 @@
 //! # }
 //! ```
 
+use std::collections::HashMap;
 use std::pin::Pin;
 use std::sync::Arc;
 
@@ -91,7 +92,11 @@ use crate::PublicKey;
 use base64::Engine;
 use eventsource_stream::Eventsource;
 use futures_util::{Stream, StreamExt};
-use pubky_common::{crypto::Hash, storage};
+use pubky_common::{
+    crypto::{Hash, Signature},
+    events::verify_event,
+    storage,
+};
 use reqwest::Method;
 use url::Url;
 
@@ -114,6 +119,144 @@ pub struct Event {
     pub resource: PubkyResource,
     /// Cursor for pagination (event ID).
     pub cursor: EventCursor,
+    /// Cursor of the previous event of this event's user that matches the subscription,
+    /// as stored by the homeserver. `None` when the user has no earlier matching event,
+    /// for reverse streams, and from homeservers that do not report it.
+    /// See [`EventStreamBuilder::subscribe_with_gaps`].
+    pub prev_cursor: Option<EventCursor>,
+    /// Homeserver signature over this event, if the homeserver sent one.
+    /// Check it with [`Event::verify`].
+    pub signature: Option<EventSignature>,
+}
+
+impl Event {
+    /// Verify that this event was signed by `homeserver`.
+    ///
+    /// Mirrors can use this to trust events pulled over an untrusted connection. The
+    /// signature covers the event type, resource, cursor and content hash, using the
+    /// canonical form of [`pubky_common::events::signable_event`].
+    ///
+    /// # Errors
+    /// - Returns [`crate::errors::RequestError::Validation`] if the event carries no
+    ///   signature, its signed resource differs from [`Self::resource`], or the
+    ///   signature does not verify against `homeserver`.
+    pub fn verify(&self, homeserver: &PublicKey) -> Result<()> {
+        let invalid = |message: &str| {
+            Error::from(RequestError::Validation {
+                message: format!("event {}: {message}", self.cursor),
+            })
+        };
+        let signed = self
+            .signature
+            .as_ref()
+            .ok_or_else(|| invalid("missing homeserver signature"))?;
+        let signed_resource: PubkyResource = signed
+            .resource_uri
+            .parse()
+            .map_err(|_err| invalid("signed resource is not a valid pubky URI"))?;
+        if signed_resource != self.resource {
+            return Err(invalid("signed resource does not match the event"));
+        }
+        if !verify_event(
+            homeserver,
+            &signed.signature,
+            &self.event_type,
+            &signed.resource_uri,
+            self.cursor,
+        ) {
+            return Err(invalid("invalid homeserver signature"));
+        }
+        Ok(())
+    }
+
+    /// Whether `content` hashes to this event's content hash.
+    ///
+    /// Always `false` for DELETE events, which carry no content.
+    #[must_use]
+    pub fn content_matches(&self, content: &[u8]) -> bool {
+        self.event_type
+            .content_hash()
+            .is_some_and(|hash| *hash == pubky_common::crypto::hash(content))
+    }
+}
+
+/// Homeserver signature of an [`Event`], see [`Event::verify`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventSignature {
+    signature: Signature,
+    /// The resource URI exactly as sent and signed by the homeserver.
+    resource_uri: String,
+}
+
+impl EventSignature {
+    /// The Ed25519 signature.
+    #[must_use]
+    pub const fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    /// The resource URI exactly as signed by the homeserver.
+    #[must_use]
+    pub fn resource_uri(&self) -> &str {
+        &self.resource_uri
+    }
+}
+
+/// An item of a gap-detecting event stream, see [`EventStreamBuilder::subscribe_with_gaps`].
+#[derive(Debug, Clone)]
+pub enum StreamEvent {
+    /// An event, in the same order as [`EventStreamBuilder::subscribe`] yields them.
+    Event(Event),
+    /// Events of `user` with cursors after `from`, up to and including `to`, were not
+    /// delivered, e.g. because the homeserver no longer holds them. Reconcile by
+    /// re-listing the paths the subscription covers.
+    ///
+    /// Yielded right before the event that revealed the gap.
+    Gap {
+        /// Owner of the missed events.
+        user: PublicKey,
+        /// Last cursor seen for `user` (or subscribed from); `0` for the beginning.
+        from: EventCursor,
+        /// Cursor of the last missed event, as reported by the homeserver.
+        to: EventCursor,
+    },
+}
+
+/// Last cursor seen per user, checked against each event's [`Event::prev_cursor`].
+#[derive(Debug)]
+struct GapDetector {
+    last: HashMap<PublicKey, EventCursor>,
+}
+
+impl GapDetector {
+    fn new(users: &[(PublicKey, Option<EventCursor>)]) -> Self {
+        let last = users
+            .iter()
+            .map(|(user, cursor)| (user.clone(), cursor.unwrap_or(EventCursor::new(0))))
+            .collect();
+        Self { last }
+    }
+
+    /// The items to yield for `event`: a [`StreamEvent::Gap`] if it does not follow the
+    /// last cursor seen for its user, then the event itself.
+    ///
+    /// A `prev_cursor` behind the last cursor seen means a replay, not a gap.
+    fn observe(&mut self, event: Event) -> Vec<StreamEvent> {
+        let user = event.resource.owner.clone();
+        let from = self
+            .last
+            .insert(user.clone(), event.cursor)
+            .unwrap_or(EventCursor::new(0));
+        let mut items = Vec::with_capacity(2);
+        if let Some(to) = event.prev_cursor
+            && to > from
+        {
+            cross_log!(warn, "Event stream gap for {}: {} to {}", user, from, to);
+            items.push(StreamEvent::Gap { user, from, to });
+        }
+        items.push(StreamEvent::Event(event));
+        items
+    }
 }
 
 /// Builder for creating an event stream subscription.
@@ -449,6 +592,20 @@ impl EventStreamBuilder {
             .credential
             .as_ref()
             .filter(|credential| self.should_attach_credential(credential.as_ref()));
+        if let Some(credential) = credential
+            && credential.is_scoped()
+        {
+            let info = credential.info();
+            if let Some(path) = self
+                .paths
+                .iter()
+                .find(|path| storage::is_private_path_filter(path) && !info.can_read(path.as_str()))
+            {
+                return Err(Error::from(RequestError::Forbidden {
+                    message: format!("Session does not have read access to {path}"),
+                }));
+            }
+        }
         let can_attach_credential = match credential {
             Some(credential) => credential.can_attach_to(&homeserver).await,
             None => false,
@@ -467,7 +624,7 @@ impl EventStreamBuilder {
         if let Some(credential) = credential {
             request = credential.attach(request, &self.client).await?;
         }
-        let response = request.send().await?;
+        let response = self.client.send(request).await?;
 
         // Surface homeserver rejections (e.g. 401/403/400 for private-path
         // authorization) as a typed `RequestError::Server` carrying the status
@@ -500,6 +657,24 @@ impl EventStreamBuilder {
         Ok(event_stream)
     }
 
+    /// Shared logic of [`Self::subscribe_with_gaps`].
+    async fn subscribe_with_gaps_internal(self) -> Result<impl Stream<Item = Result<StreamEvent>>> {
+        if self.reverse {
+            return Err(Error::from(RequestError::Validation {
+                message: "Cannot detect gaps with reverse ordering".into(),
+            }));
+        }
+        let mut detector = GapDetector::new(&self.users);
+        let stream = self.subscribe_internal().await?;
+        Ok(stream.flat_map(move |result| {
+            let items: Vec<Result<StreamEvent>> = match result {
+                Ok(event) => detector.observe(event).into_iter().map(Ok).collect(),
+                Err(e) => vec![Err(e)],
+            };
+            futures_util::stream::iter(items)
+        }))
+    }
+
     /// Subscribe to the event stream.
     ///
     /// This performs the following steps:
@@ -511,7 +686,7 @@ impl EventStreamBuilder {
     /// The native version returns a `Send`-compatible stream for use in multi-threaded contexts.
     ///
     /// # Errors
-    /// - Returns [`Error::Request`] if the homeserver cannot be resolved
+    /// - Returns [`Error::RecordNotFound`] if the homeserver cannot be resolved
     /// - Returns [`Error::Request`] if `live=true` and `reverse=true` (invalid combination)
     /// - Propagates HTTP request errors
     #[cfg(not(target_arch = "wasm32"))]
@@ -531,7 +706,7 @@ impl EventStreamBuilder {
     /// The WASM version returns a stream without the `Send` bound, as WASM is single-threaded.
     ///
     /// # Errors
-    /// - Returns [`Error::Request`] if the homeserver cannot be resolved
+    /// - Returns [`Error::RecordNotFound`] if the homeserver cannot be resolved
     /// - Returns [`Error::Request`] if `live=true` and `reverse=true` (invalid combination)
     /// - Propagates HTTP request errors
     #[cfg(target_arch = "wasm32")]
@@ -539,6 +714,74 @@ impl EventStreamBuilder {
         let stream = self.subscribe_internal().await?;
         Ok(Box::pin(stream))
     }
+
+    /// Subscribe to the event stream, reporting missed events as [`StreamEvent::Gap`].
+    ///
+    /// Cursors are global event IDs, so a user's cursors increase but are not
+    /// consecutive. Instead, the homeserver tags each event with the
+    /// [`prev_cursor`](Event::prev_cursor) of the user's previous matching event it
+    /// stores, so no event of that user matching the subscription lies between the two. This
+    /// stream remembers the last cursor seen per user, starting from the cursors given
+    /// to the builder, and yields a [`StreamEvent::Gap`] whenever an event's
+    /// `prev_cursor` is ahead of it. Together with persisting each handled cursor, this
+    /// makes it safe to build stateful projections on the stream: resume from the last
+    /// cursor, and re-list the affected paths when a gap shows up.
+    ///
+    /// Events from homeservers that do not report `prev_cursor` are passed through
+    /// unchecked.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use pubky::{EventCursor, Pubky, PublicKey, StreamEvent};
+    /// use futures_util::StreamExt;
+    ///
+    /// # async fn example(pubky: Pubky, user: PublicKey, saved: EventCursor) -> pubky::Result<()> {
+    /// let mut stream = pubky
+    ///     .event_stream_for_user(&user, Some(saved))
+    ///     .live()
+    ///     .subscribe_with_gaps()
+    ///     .await?;
+    ///
+    /// while let Some(item) = stream.next().await {
+    ///     match item? {
+    ///         StreamEvent::Event(event) => println!("{} at {}", event.cursor, event.resource),
+    ///         StreamEvent::Gap { user, from, to } => {
+    ///             println!("missed events of {user} in ({from}, {to}], re-listing");
+    ///         }
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    /// - Returns [`Error::Request`] if `reverse=true`, as gaps are only defined for
+    ///   forward ordering
+    /// - Otherwise the same as [`Self::subscribe`]
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn subscribe_with_gaps(
+        self,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamEvent>> + Send>>> {
+        let stream = self.subscribe_with_gaps_internal().await?;
+        Ok(Box::pin(stream))
+    }
+
+    /// Subscribe to the event stream, reporting missed events as [`StreamEvent::Gap`]
+    /// (WASM version).
+    ///
+    /// See the native version for details.
+    ///
+    /// # Errors
+    /// - Returns [`Error::Request`] if `reverse=true`, as gaps are only defined for
+    ///   forward ordering
+    /// - Otherwise the same as [`Self::subscribe`]
+    #[cfg(target_arch = "wasm32")]
+    pub async fn subscribe_with_gaps(
+        self,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamEvent>>>>> {
+        let stream = self.subscribe_with_gaps_internal().await?;
+        Ok(Box::pin(stream))
+    }
 }
 
 /// Parse a Server-Sent Event into our Event type.
@@ -549,12 +792,16 @@ impl EventStreamBuilder {
 /// data: pubky://user_pubkey/pub/example.txt
 /// data: cursor: 42
 /// data: content_hash: <base64 of raw 32-byte blake3 digest> (required for PUT events)
+/// data: signature: <base64 of the 64-byte homeserver signature> (optional)
+/// data: prev_cursor: 41 (optional)
 /// ```
 fn parse_sse_event(sse: &eventsource_stream::Event) -> Result<Event> {
     // Parse SSE data by prefix
     let mut path: Option<String> = None;
     let mut cursor: Option<EventCursor> = None;
+    let mut prev_cursor: Option<EventCursor> = None;
     let mut content_hash_base64: Option<String> = None;
+    let mut signature_base64: Option<&str> = None;
 
     for (i, line) in sse.data.lines().enumerate() {
         if let Some(cursor_str) = line.strip_prefix("cursor: ") {
@@ -563,8 +810,16 @@ fn parse_sse_event(sse: &eventsource_stream::Event) -> Result<Event> {
                     message: format!("Invalid cursor format '{cursor_str}': {e}"),
                 })
             })?);
+        } else if let Some(prev_str) = line.strip_prefix("prev_cursor: ") {
+            prev_cursor = Some(prev_str.parse::<EventCursor>().map_err(|e| {
+                Error::from(RequestError::Validation {
+                    message: format!("Invalid prev_cursor format '{prev_str}': {e}"),
+                })
+            })?);
         } else if let Some(hash) = line.strip_prefix("content_hash: ") {
             content_hash_base64 = Some(hash.to_string());
+        } else if let Some(signature) = line.strip_prefix("signature: ") {
+            signature_base64 = Some(signature);
         } else if i == 0 {
             // First line without a known prefix is the path
             path = Some(line.to_string());
@@ -578,6 +833,15 @@ fn parse_sse_event(sse: &eventsource_stream::Event) -> Result<Event> {
         })
     })?;
 
+    let signature = signature_base64
+        .map(|b64| {
+            Ok::<_, Error>(EventSignature {
+                signature: decode_signature(b64)?,
+                resource_uri: path.clone(),
+            })
+        })
+        .transpose()?;
+
     let resource: PubkyResource = path.parse().map_err(|e| {
         Error::from(RequestError::Validation {
             message: format!("Invalid resource path '{path}': {e}"),
@@ -607,9 +871,31 @@ fn parse_sse_event(sse: &eventsource_stream::Event) -> Result<Event> {
         event_type,
         resource,
         cursor,
+        prev_cursor,
+        signature,
     })
 }
 
+/// Decode a base64-encoded Ed25519 signature.
+fn decode_signature(b64: &str) -> Result<Signature> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(b64)
+        .map_err(|e| {
+            Error::from(RequestError::Validation {
+                message: format!("Invalid signature base64 encoding: {e}"),
+            })
+        })?;
+    let bytes: [u8; 64] = bytes.try_into().map_err(|bytes: Vec<u8>| {
+        Error::from(RequestError::Validation {
+            message: format!(
+                "signature must be exactly 64 bytes, got {} bytes",
+                bytes.len()
+            ),
+        })
+    })?;
+    Ok(Signature::from_bytes(&bytes))
+}
+
 /// Decode a base64-encoded content hash into a Hash.
 fn decode_content_hash(content_hash_base64: Option<&str>) -> Result<Hash> {
     let b64 = content_hash_base64
@@ -701,6 +987,92 @@ mod tests {
         assert_eq!(event.event_type.content_hash(), None);
     }
 
+    #[test]
+    fn signed_event_verifies_against_its_homeserver() {
+        let homeserver = pubky_common::crypto::Keypair::random();
+        let content = b"hello";
+        let event_type = EventType::Put {
+            content_hash: pubky_common::crypto::hash(content),
+        };
+        let uri = "pubky://o1gg96ewuojmopcjbz8895478wdtxtzzuxnfjjz8o8e77csa1ngo/pub/file.txt";
+        let signature =
+            pubky_common::events::sign_event(&homeserver, &event_type, uri, EventCursor::new(7));
+        let data = format!(
+            "{uri}\ncursor: 7\ncontent_hash: {}\nsignature: {}",
+            encode_hash(*event_type.content_hash().unwrap().as_bytes()),
+            base64::engine::general_purpose::STANDARD.encode(signature.to_bytes())
+        );
+
+        let event = parse_sse_event(&make_sse("PUT", &data)).unwrap();
+        event.verify(&homeserver.public_key()).unwrap();
+        assert!(event.content_matches(content));
+        assert!(!event.content_matches(b"tampered"));
+
+        let other = pubky_common::crypto::Keypair::random().public_key();
+        event.verify(&other).unwrap_err();
+
+        let mut moved = event.clone();
+        moved.cursor = EventCursor::new(8);
+        moved.verify(&homeserver.public_key()).unwrap_err();
+
+        let unsigned = Event {
+            signature: None,
+            ..event
+        };
+        unsigned.verify(&homeserver.public_key()).unwrap_err();
+    }
+
+    #[test]
+    fn gap_detector_reports_skipped_cursors() {
+        let user = "o1gg96ewuojmopcjbz8895478wdtxtzzuxnfjjz8o8e77csa1ngo";
+        let event = |cursor: u64, prev: Option<u64>| {
+            let prev = prev.map_or(String::new(), |p| format!("\nprev_cursor: {p}"));
+            parse_sse_event(&make_sse(
+                "DEL",
+                &format!("pubky://{user}/pub/a.txt\ncursor: {cursor}{prev}"),
+            ))
+            .unwrap()
+        };
+        let owner = PublicKey::try_from(user).unwrap();
+        let mut detector = GapDetector::new(&[(owner.clone(), Some(EventCursor::new(10)))]);
+
+        // Contiguous, and homeservers without `prev_cursor`, pass straight through.
+        assert!(matches!(
+            detector.observe(event(12, Some(10))).as_slice(),
+            [StreamEvent::Event(_)]
+        ));
+        assert!(matches!(
+            detector.observe(event(15, None)).as_slice(),
+            [StreamEvent::Event(_)]
+        ));
+
+        let items = detector.observe(event(30, Some(20)));
+        assert!(matches!(
+            items.as_slice(),
+            [StreamEvent::Gap { user, from, to }, StreamEvent::Event(e)]
+                if *user == owner
+                    && *from == EventCursor::new(15)
+                    && *to == EventCursor::new(20)
+                    && e.cursor == EventCursor::new(30)
+        ));
+
+        // Replays from before the last seen cursor are not gaps.
+        assert!(matches!(
+            detector.observe(event(25, Some(12))).as_slice(),
+            [StreamEvent::Event(_)]
+        ));
+    }
+
+    #[test]
+    fn parse_event_rejects_malformed_signature() {
+        let sse = make_sse(
+            "DEL",
+            "pubky://o1gg96ewuojmopcjbz8895478wdtxtzzuxnfjjz8o8e77csa1ngo/pub/deleted.txt\ncursor: 100\nsignature: AAAA",
+        );
+        let err = parse_sse_event(&sse).unwrap_err();
+        assert!(err.to_string().contains("64 bytes"), "{err}");
+    }
+
     #[test]
     fn parse_event_with_unknown_prefixed_lines_for_forward_compatibility() {
         let hash_bytes = [2u8; 32];
@@ -1,21 +1,25 @@
 pub(crate) mod auth;
+pub mod event_export;
 pub mod event_stream;
 pub mod pkdns;
-mod session;
+pub(crate) mod profile;
+pub(crate) mod session;
 mod signer;
 pub mod storage;
 
 pub use auth::AuthFlowKind;
 #[allow(deprecated, reason = "Re-exporting deprecated public API")]
 pub use auth::cookie::PubkyCookieAuthFlow;
-pub use auth::cookie::{CookieCredential, CookieSessionView};
+pub use auth::cookie::{CookieCredential, CookieSessionView, SessionCookie};
 pub use auth::deep_links;
+pub use auth::grant::constants::{MAX_GRANT_LIFETIME_SECS, MIN_GRANT_LIFETIME_SECS};
 #[doc(hidden)]
 pub use auth::grant::pop_signer::{DelegatedSignFn, delegated_sign_callback};
 pub use auth::grant::{DelegatedGrantAuthFlowState, GrantAuthFlowState, PubkyGrantAuthFlow};
 pub use auth::grant::{
     DelegatedGrantCredentialState, GrantCredential, GrantManager, GrantSessionView,
 };
+pub use auth::relay::backoff::BackoffConfig;
 pub use auth::relay::http_relay_inbox_channel::{
     DEFAULT_HTTP_RELAY_INBOX, EncryptedHttpRelayInboxChannel, HttpRelayInboxChannel,
 };
@@ -24,9 +28,13 @@ pub use auth::relay::http_relay_inbox_channel::{
     reason = "Re-exporting deprecated public API for backwards compat"
 )]
 pub use auth::relay::http_relay_link_channel::DEFAULT_HTTP_RELAY;
-pub use event_stream::{Event, EventCursor, EventStreamBuilder, EventType};
+pub use event_export::EventExportBuilder;
+pub use event_stream::{
+    Event, EventCursor, EventSignature, EventStreamBuilder, EventType, StreamEvent,
+};
 pub use pkdns::Pkdns;
+pub use profile::PROFILE_CACHE_TTL;
 pub use session::SessionInfo;
 pub use session::core::PubkySession;
-pub use signer::PubkySigner;
+pub use signer::{FixedSelector, HomeserverSelector, LatencySelector, PubkySigner, SignupOptions};
 pub use storage::core::{PublicStorage, SessionStorage};
@@ -5,11 +5,14 @@
 //!
 //! Reads do not require a session or keys. Publishing requires a `Keypair`.
 
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 
 use pkarr::{
     ResolvePolicy, SignedPacket, Timestamp,
-    dns::rdata::{RData, SVCB},
+    dns::{
+        ResourceRecord, TYPE,
+        rdata::{RData, SVCB},
+    },
 };
 
 use crate::{
@@ -17,6 +20,13 @@ use crate::{
     errors::{AuthError, Error, PkarrError, RequestError, Result},
 };
 
+mod endpoint;
+pub mod pinning;
+pub mod rotation;
+
+pub use endpoint::HomeserverEndpoint;
+pub(crate) use endpoint::resolve_https_endpoints;
+
 /// Default staleness window for homeserver `_pubky` Pkarr records (1 hour).
 ///
 /// Used by [`crate::Pkdns::publish_homeserver_if_stale`] to decide when a record
@@ -26,6 +36,41 @@ use crate::{
 /// You can override this per instance via [`crate::Pkdns::set_stale_after`] (mutable setter).
 pub const DEFAULT_STALE_AFTER: Duration = Duration::from_secs(60 * 60);
 
+/// What a `_pubky` publish wrote, as returned by the `publish_homeserver*` methods of
+/// [`crate::Pkdns`].
+///
+/// Describes the packet that was accepted, so callers can log or check a publish
+/// without resolving the record again.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PublishResult {
+    /// Timestamp of the signed packet; resolvers prefer the packet with the newest one.
+    pub record_timestamp: SystemTime,
+    /// Lowest TTL of the published `_pubky` records.
+    pub ttl: Duration,
+    /// Number of DHT nodes that stored the packet, as reported by Pkarr.
+    ///
+    /// When publishing through relays this is the highest count any relay reported.
+    /// Pkarr does not report which relays accepted the packet, only that at least one
+    /// backend did.
+    pub dht_nodes: u32,
+}
+
+impl PublishResult {
+    fn new(packet: &SignedPacket, dht_nodes: u32) -> Self {
+        let ttl = packet
+            .resource_records("_pubky")
+            .map(|record| record.ttl)
+            .min()
+            .unwrap_or_default();
+        Self {
+            record_timestamp: SystemTime::UNIX_EPOCH
+                + Duration::from_micros(packet.timestamp().as_u64()),
+            ttl: Duration::from_secs(ttl.into()),
+            dht_nodes,
+        }
+    }
+}
+
 /// PKDNS actor: resolve & publish `_pubky` PKARR records.
 ///
 /// Construct it **without** a keypair for read-only queries:
@@ -44,7 +89,7 @@ pub const DEFAULT_STALE_AFTER: Duration = Duration::from_secs(60 * 60);
 /// let pkdns = pubky::Pkdns::new_with_keypair(kp)?;
 /// // Self-lookup (requires keypair on this Pkdns)
 /// let my_host = pkdns.get_homeserver().await?;
-/// println!("my homeserver: {my_host:?}");
+/// println!("my homeserver: {my_host}");
 ///
 /// // Publish if stale
 /// pkdns.publish_homeserver_if_stale(None).await?;
@@ -139,63 +184,196 @@ impl Pkdns {
 
     // -------------------- Reads --------------------
 
-    /// Resolve a user's homeserver public key via Pkarr.
+    /// Resolve a user's (primary) homeserver public key via Pkarr.
     ///
-    /// Returns `None` for missing records and for domain-only `_pubky` targets.
+    /// Returns `None` for missing records, for domain-only `_pubky` targets and when
+    /// resolution fails. See [`Self::get_homeservers_of`] for all homeservers of a user.
     pub async fn get_homeserver_of(&self, user_public_key: &PublicKey) -> Option<PublicKey> {
+        self.require_homeserver_of(user_public_key).await.ok()
+    }
+
+    /// Resolve all homeservers hosting a user, primary first.
+    ///
+    /// A `_pubky` record may list several homeservers (see [`Self::publish_homeservers`]).
+    /// They are ordered by the `SvcPriority` of their `HTTPS`/`SVCB` records, lowest first;
+    /// the first entry is the one [`Self::get_homeserver_of`] returns. Clients can fall
+    /// back to later entries when the primary is unreachable.
+    ///
+    /// Returns an empty list for missing records. Domain-only targets are skipped.
+    /// With [`PinPolicy::Strict`](pinning::PinPolicy::Strict) pinning, a primary that
+    /// differs from the pinned homeserver also yields an empty list.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async fn example(user: pubky::PublicKey) -> pubky::Result<()> {
+    /// let pkdns = pubky::Pkdns::new()?;
+    /// for homeserver in pkdns.get_homeservers_of(&user).await {
+    ///     println!("hosted on {homeserver}");
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub async fn get_homeservers_of(&self, user_public_key: &PublicKey) -> Vec<PublicKey> {
         cross_log!(
             info,
-            "Resolving homeserver for public key {} via PKARR",
+            "Resolving all homeservers for public key {} via PKARR",
             user_public_key
         );
-        let packet = self
+        let Ok(packet) = self
             .client
             .pkarr()
             .resolve(user_public_key, ResolvePolicy::CacheFirst)
             .await
-            .ok()?;
-        let s = extract_host_from_packet(&packet)?;
-        let result = PublicKey::try_from_z32(&s).ok();
+        else {
+            return Vec::new();
+        };
+        let homeservers: Vec<PublicKey> = extract_hosts_from_packet(&packet)
+            .iter()
+            .filter_map(|host| PublicKey::try_from_z32(host).ok())
+            .collect();
         cross_log!(
             debug,
             "Homeserver resolution for {} yielded {:?}",
             user_public_key,
-            result
+            homeservers
         );
-        result
+        if let (Some(pins), Some(primary)) = (&self.client.record_pins, homeservers.first())
+            && pins.check(user_public_key, primary).is_err()
+        {
+            return Vec::new();
+        }
+        homeservers
     }
 
-    pub(crate) async fn require_homeserver_of(
+    /// Resolve all records of `record_type` published by any Pkarr key.
+    ///
+    /// Unlike [`Self::get_homeserver_of`], this is not limited to `_pubky`: it returns
+    /// every record of the requested type (A, AAAA, TXT, SVCB, …) in the key's signed
+    /// packet, under any name, using this client's relays and DHT.
+    ///
+    /// Returns an empty list if the key has no packet or no records of that type.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async fn example(key: pubky::PublicKey) -> pubky::Result<()> {
+    /// use pubky::pkarr::dns::{TYPE, rdata::RData};
+    ///
+    /// let pkdns = pubky::Pkdns::new()?;
+    /// for record in pkdns.resolve_any(&key, TYPE::TXT).await? {
+    ///     if let RData::TXT(txt) = &record.rdata {
+    ///         println!("{}: {:?}", record.name, txt.attributes());
+    ///     }
+    /// }
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// - Returns [`crate::errors::Error::Pkarr`] if resolution fails for any reason other
+    ///   than the packet not being found.
+    pub async fn resolve_any(
         &self,
-        user_public_key: &PublicKey,
-    ) -> Result<PublicKey> {
-        self.get_homeserver_of(user_public_key)
+        public_key: &PublicKey,
+        record_type: TYPE,
+    ) -> Result<Vec<ResourceRecord<'static>>> {
+        cross_log!(
+            info,
+            "Resolving {:?} records for public key {} via PKARR",
+            record_type,
+            public_key
+        );
+        match self
+            .client
+            .pkarr()
+            .resolve(public_key, ResolvePolicy::CacheFirst)
             .await
-            .ok_or_else(|| {
-                RequestError::Validation {
-                    message: format!("could not resolve homeserver for {}", user_public_key.z32()),
-                }
-                .into()
-            })
+        {
+            Ok(packet) => Ok(records_of_type(&packet, record_type)),
+            Err(pkarr::errors::ResolveError::NotFound) => Ok(Vec::new()),
+            Err(err) => Err(err.into()),
+        }
     }
 
-    /// Convenience: resolve the homeserver for **this** user (requires keypair on `Pkdns`).
+    /// Pin `homeserver` for `user`, accepting a migration reported as
+    /// [`RecordChanged`](pinning::RecordChanged).
+    ///
+    /// Does nothing unless the client was built with
+    /// [`PubkyHttpClientBuilder::pin_records`](crate::PubkyHttpClientBuilder::pin_records).
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async fn example(pubky: pubky::Pubky, user: pubky::PublicKey) -> pubky::Result<()> {
+    /// let pkdns = pubky.pkdns();
+    /// match pkdns.require_homeserver_of(&user).await {
+    ///     Err(pubky::Error::RecordChanged(change)) => {
+    ///         // After the user confirmed the move:
+    ///         pkdns.accept_homeserver(&change.user, &change.new);
+    ///     }
+    ///     other => drop(other),
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub fn accept_homeserver(&self, user: &PublicKey, homeserver: &PublicKey) {
+        if let Some(pins) = &self.client.record_pins {
+            cross_log!(info, "Accepting homeserver {} for {}", homeserver, user);
+            pins.store.pin(user, homeserver);
+        }
+    }
+
+    /// Resolve a user's (primary) homeserver, like [`Self::get_homeserver_of`] but
+    /// reporting why resolution failed.
     ///
-    /// Returns:
-    /// - `Ok(Some(host))` if resolvable,
-    /// - `Ok(None)` if no record is found,
-    /// - `Err(_)` only for transport errors.
+    /// # Errors
+    /// - Returns [`Error::RecordNotFound`] if the user has no record naming a homeserver.
+    /// - Returns [`Error::RecordChanged`] if it differs from the pinned homeserver under
+    ///   [`PinPolicy::Strict`](pinning::PinPolicy::Strict).
+    /// - Returns [`Error::Pkarr`] if the record could not be resolved for another reason.
+    pub async fn require_homeserver_of(&self, user_public_key: &PublicKey) -> Result<PublicKey> {
+        cross_log!(
+            info,
+            "Resolving homeserver for public key {} via PKARR",
+            user_public_key
+        );
+        let not_found = || Error::RecordNotFound {
+            public_key: Box::new(user_public_key.clone()),
+        };
+        let packet = self
+            .client
+            .pkarr()
+            .resolve(user_public_key, ResolvePolicy::CacheFirst)
+            .await
+            .map_err(|err| match err {
+                pkarr::errors::ResolveError::NotFound => not_found(),
+                err => err.into(),
+            })?;
+        let result =
+            extract_host_from_packet(&packet).and_then(|host| PublicKey::try_from_z32(&host).ok());
+        cross_log!(
+            debug,
+            "Homeserver resolution for {} yielded {:?}",
+            user_public_key,
+            result
+        );
+        let homeserver = result.ok_or_else(not_found)?;
+        if let Some(pins) = &self.client.record_pins {
+            pins.check(user_public_key, &homeserver)?;
+        }
+        Ok(homeserver)
+    }
+
+    /// Convenience: resolve the homeserver for **this** user (requires keypair on `Pkdns`).
     ///
     /// # Errors
     /// - Returns [`crate::errors::Error::Authentication`] if called without an attached keypair.
-    /// - Propagates transport failures from PKARR resolution.
-    pub async fn get_homeserver(&self) -> Result<Option<PublicKey>> {
+    /// - Returns [`crate::errors::Error::RecordNotFound`] if no `_pubky` record names a
+    ///   homeserver for this user, e.g. before signing up.
+    /// - Returns [`crate::errors::Error::Pkarr`] if the record could not be resolved
+    ///   for another reason.
+    pub async fn get_homeserver(&self) -> Result<PublicKey> {
         let kp = self.keypair.as_ref().ok_or_else(|| {
             Error::from(AuthError::Validation(
                 "get_homeserver() requires a keypair; use Pkdns::new_with_keypair() or signer.pkdns()".into(),
             ))
         })?;
-        Ok(self.get_homeserver_of(&kp.public_key()).await)
+        self.require_homeserver_of(&kp.public_key()).await
     }
 
     // -------------------- Publishing (requires keypair) --------------------
@@ -204,10 +382,15 @@ impl Pkdns {
     ///
     /// If `host_override` is `None`, reuses the host found in the existing record (if any).
     ///
+    /// Returns what was published, or `None` if there was no host to publish.
+    ///
     /// # Errors
     /// - [`crate::errors::Error::Authentication`] if called without a keypair or validation fails.
     /// - [`crate::errors::Error::Pkarr`] if PKARR/DHT resolution or publish fails.
-    pub async fn publish_homeserver_force(&self, host_override: Option<&PublicKey>) -> Result<()> {
+    pub async fn publish_homeserver_force(
+        &self,
+        host_override: Option<&PublicKey>,
+    ) -> Result<Option<PublishResult>> {
         self.publish_homeserver(host_override, PublishMode::Force)
             .await
     }
@@ -216,24 +399,100 @@ impl Pkdns {
     ///
     /// If `host_override` is `None`, reuses the host found in the existing record (if any).
     ///
+    /// Returns what was published, or `None` if the record was fresh or there was no
+    /// host to publish.
+    ///
     /// # Errors
     /// - [`crate::errors::Error::Authentication`] if called without a keypair or validation fails.
     /// - [`crate::errors::Error::Pkarr`] if PKARR/DHT resolution or publish fails.
     pub async fn publish_homeserver_if_stale(
         &self,
         host_override: Option<&PublicKey>,
-    ) -> Result<()> {
+    ) -> Result<Option<PublishResult>> {
         self.publish_homeserver(host_override, PublishMode::IfStale)
             .await
     }
 
+    /// Publish `_pubky` listing several homeservers, **forcing** a refresh.
+    ///
+    /// `homeservers` are in priority order: the first is the primary that
+    /// [`Self::get_homeserver_of`] and request routing use, the rest are fallbacks
+    /// returned by [`Self::get_homeservers_of`]. Entry `i` is written as an `HTTPS`
+    /// record with `SvcPriority` `i`, so a single homeserver produces the same record as
+    /// [`Self::publish_homeserver_force`]. Duplicates are dropped. The list replaces any
+    /// previously published homeservers; other records in the packet are kept.
+    ///
+    /// Republishing with a `None` host ([`Self::publish_homeserver_if_stale`]) keeps the
+    /// whole list, while a host override replaces it with that single host.
+    ///
+    /// Returns what was published.
+    ///
+    /// # Errors
+    /// - [`crate::errors::Error::Request`] with [`RequestError::Validation`] if
+    ///   `homeservers` is empty.
+    /// - [`crate::errors::Error::Authentication`] if called without a keypair.
+    /// - [`crate::errors::Error::Pkarr`] if PKARR/DHT resolution or publish fails.
+    pub async fn publish_homeservers(&self, homeservers: &[PublicKey]) -> Result<PublishResult> {
+        let mut hosts: Vec<String> = Vec::with_capacity(homeservers.len());
+        for host in homeservers.iter().map(PublicKey::z32) {
+            if !hosts.contains(&host) {
+                hosts.push(host);
+            }
+        }
+        if hosts.is_empty() {
+            return Err(RequestError::Validation {
+                message: "at least one homeserver is required".into(),
+            }
+            .into());
+        }
+        let kp = self.keypair_ref()?;
+        let pubky = kp.public_key();
+        let existing = self.resolve_existing(&pubky).await;
+        self.publish_with_retries(kp, &pubky, &hosts, existing)
+            .await
+    }
+
+    /// Publish `_pubky` with `homeserver` as primary unless the record already has it and
+    /// is fresh, see [`PubkySigner::ensure_published`].
+    pub(crate) async fn ensure_homeserver(
+        &self,
+        homeserver: &PublicKey,
+    ) -> Result<Option<PublishResult>> {
+        let kp = self.keypair_ref()?;
+        let pubky = kp.public_key();
+        let existing = self.resolve_existing(&pubky).await;
+        let current = existing
+            .as_ref()
+            .map(extract_hosts_from_packet)
+            .unwrap_or_default();
+
+        let hosts = if current.first() == Some(&homeserver.z32()) {
+            if self.should_skip_due_to_age(PublishMode::IfStale, existing.as_ref(), &pubky) {
+                return Ok(None);
+            }
+            current
+        } else {
+            cross_log!(
+                info,
+                "`_pubky` record of {} lists {:?} instead of {}; republishing",
+                pubky,
+                current,
+                homeserver
+            );
+            vec![homeserver.z32()]
+        };
+        self.publish_with_retries(kp, &pubky, &hosts, existing)
+            .await
+            .map(Some)
+    }
+
     // ---- internals ----
 
     async fn publish_homeserver(
         &self,
         host_override: Option<&PublicKey>,
         mode: PublishMode,
-    ) -> Result<()> {
+    ) -> Result<Option<PublishResult>> {
         let kp = self.keypair_ref()?;
         let pubky = kp.public_key();
 
@@ -244,10 +503,30 @@ impl Pkdns {
             pubky,
             mode
         );
+        let existing = self.resolve_existing(&pubky).await;
+
+        // 2) Decide hosts to publish.
+        let Some(hosts) = Self::select_hosts(&pubky, host_override, existing.as_ref()) else {
+            return Ok(None);
+        };
+
+        // 3) Age check (for IfStale).
+        if self.should_skip_due_to_age(mode, existing.as_ref(), &pubky) {
+            return Ok(None);
+        }
+
+        // 4) Publish with small retry loop on retryable pkarr errors.
+        self.publish_with_retries(kp, &pubky, &hosts, existing)
+            .await
+            .map(Some)
+    }
+
+    /// Resolve the most recent `_pubky` packet of `pubky` as the basis for a write.
+    async fn resolve_existing(&self, pubky: &PublicKey) -> Option<SignedPacket> {
         let resolved = self
             .client
             .pkarr()
-            .resolve(&pubky, ResolvePolicy::NetworkOnly)
+            .resolve(pubky, ResolvePolicy::NetworkOnly)
             .await
             .ok();
         // `NetworkOnly` can observe an older packet while a newer packet is still
@@ -256,42 +535,29 @@ impl Pkdns {
         let cached = self
             .client
             .pkarr()
-            .resolve(&pubky, ResolvePolicy::CacheOnly)
+            .resolve(pubky, ResolvePolicy::CacheOnly)
             .await
             .ok();
-        let existing = most_recent_packet(resolved, cached);
-
-        // 2) Decide host string to publish.
-        let Some(host_str) = Self::select_host(&pubky, host_override, existing.as_ref()) else {
-            return Ok(());
-        };
-
-        // 3) Age check (for IfStale).
-        if self.should_skip_due_to_age(mode, existing.as_ref(), &pubky) {
-            return Ok(());
-        }
-
-        // 4) Publish with small retry loop on retryable pkarr errors.
-        self.publish_with_retries(kp, &pubky, &host_str, existing)
-            .await
+        most_recent_packet(resolved, cached)
     }
 
     async fn publish_homeserver_inner(
         &self,
         keypair: &Keypair,
-        host: &str,
+        hosts: &[String],
         existing: Option<SignedPacket>,
-    ) -> Result<()> {
-        let signed_packet = Self::build_homeserver_packet(keypair, host, existing.as_ref())?;
+    ) -> Result<PublishResult> {
+        let signed_packet = Self::build_homeserver_packet(keypair, hosts, existing.as_ref())?;
 
         cross_log!(
             debug,
-            "Publishing `_pubky` packet for {} targeting host {}",
+            "Publishing `_pubky` packet for {} targeting hosts {:?}",
             keypair.public_key(),
-            host
+            hosts
         );
 
-        self.client
+        let dht_nodes = self
+            .client
             .pkarr()
             .publish(&signed_packet)
             .await
@@ -299,10 +565,11 @@ impl Pkdns {
 
         cross_log!(
             info,
-            "Successfully published `_pubky` packet for {}",
-            keypair.public_key()
+            "Successfully published `_pubky` packet for {} (stored on {} DHT nodes)",
+            keypair.public_key(),
+            dht_nodes
         );
-        Ok(())
+        Ok(PublishResult::new(&signed_packet, dht_nodes))
     }
 
     fn keypair_ref(&self) -> Result<&Keypair> {
@@ -313,30 +580,27 @@ impl Pkdns {
         })
     }
 
-    fn select_host(
+    fn select_hosts(
         pubky: &PublicKey,
         host_override: Option<&PublicKey>,
         existing: Option<&SignedPacket>,
-    ) -> Option<String> {
-        determine_host(host_override, existing).map_or_else(
-            || {
-                cross_log!(
-                    info,
-                    "No existing host found for {}; skipping publish",
-                    pubky
-                );
-                None
-            },
-            |h| {
-                cross_log!(
-                    info,
-                    "Selected host {} for `_pubky` publish of {}",
-                    h,
-                    pubky
-                );
-                Some(h)
-            },
-        )
+    ) -> Option<Vec<String>> {
+        let hosts = determine_hosts(host_override, existing);
+        if hosts.is_empty() {
+            cross_log!(
+                info,
+                "No existing host found for {}; skipping publish",
+                pubky
+            );
+            return None;
+        }
+        cross_log!(
+            info,
+            "Selected hosts {:?} for `_pubky` publish of {}",
+            hosts,
+            pubky
+        );
+        Some(hosts)
     }
 
     fn should_skip_due_to_age(
@@ -372,21 +636,31 @@ impl Pkdns {
         &self,
         keypair: &Keypair,
         pubky: &PublicKey,
-        host: &str,
+        hosts: &[String],
         existing: Option<SignedPacket>,
-    ) -> Result<()> {
-        for attempt in 1..=3 {
+    ) -> Result<PublishResult> {
+        let mut attempt = 1;
+        loop {
             cross_log!(
                 info,
-                "Publishing homeserver for {} (attempt {attempt}) -> host {}",
+                "Publishing homeserver for {} (attempt {attempt}) -> hosts {:?}",
                 pubky,
-                host
+                hosts
             );
             match self
-                .publish_homeserver_inner(keypair, host, existing.clone())
+                .publish_homeserver_inner(keypair, hosts, existing.clone())
                 .await
             {
-                Ok(()) => return Ok(()),
+                Ok(published) => {
+                    // A record this client published is a migration it chose.
+                    if let (Some(pins), Some(primary)) = (
+                        &self.client.record_pins,
+                        hosts.first().and_then(|h| PublicKey::try_from_z32(h).ok()),
+                    ) {
+                        pins.store.pin(pubky, &primary);
+                    }
+                    return Ok(published);
+                }
                 Err(err) if Self::should_retry(&err, attempt) => {
                     cross_log!(
                         warn,
@@ -394,6 +668,7 @@ impl Pkdns {
                         pubky,
                         err
                     );
+                    attempt += 1;
                 }
                 Err(err) => {
                     cross_log!(error, "Failed to publish homeserver for {}: {}", pubky, err);
@@ -401,8 +676,6 @@ impl Pkdns {
                 }
             }
         }
-
-        Ok(())
     }
 
     const fn should_retry(err: &Error, attempt: u32) -> bool {
@@ -411,26 +684,31 @@ impl Pkdns {
 
     fn build_homeserver_packet(
         keypair: &Keypair,
-        host: &str,
+        hosts: &[String],
         existing: Option<&SignedPacket>,
     ) -> Result<SignedPacket> {
-        // Keep previous records that are *not* `_pubky.*`, then write `_pubky` HTTPS/SVCB.
+        // Keep previous records that are *not* `_pubky` itself, then write one `_pubky`
+        // HTTPS record per host with its list index as priority.
         let mut builder = SignedPacket::builder();
         if let Some(packet) = existing {
+            let previous: Vec<_> = packet.resource_records("_pubky").collect();
             for record in packet.all_resource_records() {
-                if !record.name.to_string().starts_with("_pubky") {
+                if !previous.contains(&record) {
                     builder = builder.record(record.to_owned());
                 }
             }
         }
 
-        let svcb = SVCB::new(0, host.try_into().map_err(PkarrError::from)?);
-        let pubky_name = "_pubky".try_into().map_err(PkarrError::from)?;
+        for (priority, host) in (0u16..).zip(hosts) {
+            let svcb = SVCB::new(
+                priority,
+                host.as_str().try_into().map_err(PkarrError::from)?,
+            );
+            let pubky_name = "_pubky".try_into().map_err(PkarrError::from)?;
+            builder = builder.https(pubky_name, svcb, 60 * 60);
+        }
 
-        Ok(builder
-            .https(pubky_name, svcb, 60 * 60)
-            .sign(keypair)
-            .map_err(PkarrError::from)?)
+        Ok(builder.sign(keypair).map_err(PkarrError::from)?)
     }
 }
 
@@ -454,54 +732,128 @@ fn most_recent_packet(
     }
 }
 
-/// Pick a host to publish: explicit override or the one found in the DHT packet.
-fn determine_host(
+/// Pick the hosts to publish: explicit override or the ones found in the DHT packet.
+fn determine_hosts(
     override_host: Option<&PublicKey>,
     dht_packet: Option<&SignedPacket>,
-) -> Option<String> {
+) -> Vec<String> {
     if let Some(host) = override_host {
         cross_log!(info, "Using override host {} for `_pubky` publish", host);
-        return Some(host.z32());
+        return vec![host.z32()];
     }
-    cross_log!(debug, "Deriving publish host from existing `_pubky` record");
-    dht_packet.and_then(extract_host_from_packet)
+    cross_log!(
+        debug,
+        "Deriving publish hosts from existing `_pubky` record"
+    );
+    dht_packet
+        .map(extract_hosts_from_packet)
+        .unwrap_or_default()
 }
 
-/// Extract `_pubky` SVCB/HTTPS target from a signed Pkarr packet.
-pub fn extract_host_from_packet(packet: &SignedPacket) -> Option<String> {
+/// Owned copies of all records of `record_type` in a signed Pkarr packet.
+fn records_of_type(packet: &SignedPacket, record_type: TYPE) -> Vec<ResourceRecord<'static>> {
     packet
+        .all_resource_records()
+        .filter(|rr| rr.rdata.type_code() == record_type)
+        .map(|rr| rr.clone().into_owned())
+        .collect()
+}
+
+/// Extract the primary `_pubky` SVCB/HTTPS target from a signed Pkarr packet.
+pub fn extract_host_from_packet(packet: &SignedPacket) -> Option<String> {
+    extract_hosts_from_packet(packet).into_iter().next()
+}
+
+/// Extract all `_pubky` SVCB/HTTPS targets from a signed Pkarr packet, ordered by
+/// `SvcPriority` (lowest first, ties in packet order), without duplicates.
+pub fn extract_hosts_from_packet(packet: &SignedPacket) -> Vec<String> {
+    let mut targets: Vec<(u16, String)> = packet
         .resource_records("_pubky")
-        .find_map(|rr| match &rr.rdata {
-            RData::SVCB(svcb) => Some(svcb.target.to_string()),
-            RData::HTTPS(https) => Some(https.0.target.to_string()),
+        .filter_map(|rr| match &rr.rdata {
+            RData::SVCB(svcb) => Some((svcb.priority, svcb.target.to_string())),
+            RData::HTTPS(https) => Some((https.0.priority, https.0.target.to_string())),
             _ => None,
         })
+        .collect();
+    targets.sort_by_key(|(priority, _)| *priority);
+
+    let mut hosts: Vec<String> = Vec::with_capacity(targets.len());
+    for (_, host) in targets {
+        if !hosts.contains(&host) {
+            hosts.push(host);
+        }
+    }
+    hosts
 }
 
 #[cfg(test)]
 mod tests {
+    use std::num::NonZeroUsize;
+    use std::sync::Arc;
+
     use super::*;
     use pkarr::dns::rdata::TXT;
+    use pkarr::{Cache, InMemoryCache};
 
     #[tokio::test]
-    async fn require_homeserver_of_returns_validation_when_unresolved() {
-        let client = PubkyHttpClient::builder()
+    async fn require_homeserver_of_tells_missing_records_from_resolve_failures() {
+        let cache = Arc::new(InMemoryCache::new(NonZeroUsize::MIN));
+        let mut builder = PubkyHttpClient::builder();
+        builder
             .isolated_pkarr_test()
-            .build()
-            .expect("client");
-        let pkdns = Pkdns::with_client(client);
-        let user = Keypair::random().public_key();
+            .pkarr(|b| b.cache(Arc::<InMemoryCache>::clone(&cache)));
+        let pkdns = Pkdns::with_client(builder.build().expect("client"));
 
+        // A packet without a `_pubky` record names no homeserver.
+        let keypair = Keypair::random();
+        let packet = SignedPacket::builder()
+            .txt("_app".try_into().expect("name"), TXT::new(), 30)
+            .sign(&keypair)
+            .expect("signed packet");
+        cache.put(&keypair.public_key().into_inner().into(), &packet);
         let err = pkdns
-            .require_homeserver_of(&user)
+            .require_homeserver_of(&keypair.public_key())
             .await
-            .expect_err("missing homeserver should be a validation error");
-
+            .expect_err("a record without a homeserver should be an error");
         assert!(matches!(
             err,
-            Error::Request(crate::errors::RequestError::Validation { message })
-                if message == format!("could not resolve homeserver for {}", user.z32())
+            Error::RecordNotFound { public_key } if *public_key == keypair.public_key()
         ));
+
+        // Without a network to ask, the record is unknown rather than missing.
+        let err = pkdns
+            .require_homeserver_of(&Keypair::random().public_key())
+            .await
+            .expect_err("an unresolvable record should be an error");
+        assert!(matches!(err, Error::Pkarr(_)), "got {err:?}");
+    }
+
+    #[test]
+    fn records_of_type_filters_by_type_across_names() {
+        let keypair = Keypair::random();
+        let mut txt = TXT::new();
+        txt.add_string("hello=world").expect("valid txt string");
+
+        let packet = SignedPacket::builder()
+            .address(
+                "_derp.iroh".try_into().expect("name"),
+                [1, 1, 1, 1].into(),
+                30,
+            )
+            .address("www".try_into().expect("name"), [2, 2, 2, 2].into(), 30)
+            .txt("_app".try_into().expect("name"), txt, 30)
+            .sign(&keypair)
+            .expect("signed packet");
+
+        let a_records = records_of_type(&packet, TYPE::A);
+        assert_eq!(a_records.len(), 2);
+        assert!(a_records.iter().all(|rr| matches!(rr.rdata, RData::A(_))));
+
+        let txt_records = records_of_type(&packet, TYPE::TXT);
+        assert_eq!(txt_records.len(), 1);
+        assert!(txt_records[0].name.to_string().starts_with("_app."));
+
+        assert!(records_of_type(&packet, TYPE::AAAA).is_empty());
     }
 
     #[test]
@@ -536,9 +888,12 @@ mod tests {
 
         let new_host = Keypair::random().public_key().z32();
 
-        let republished =
-            Pkdns::build_homeserver_packet(&keypair, &new_host, Some(&existing_packet))
-                .expect("republished packet");
+        let republished = Pkdns::build_homeserver_packet(
+            &keypair,
+            std::slice::from_ref(&new_host),
+            Some(&existing_packet),
+        )
+        .expect("republished packet");
 
         assert_eq!(
             extract_host_from_packet(&republished),
@@ -597,8 +952,9 @@ mod tests {
 
         let existing = most_recent_packet(Some(network_packet), Some(cached_packet))
             .expect("most recent packet");
-        let republished = Pkdns::build_homeserver_packet(&keypair, &host, Some(&existing))
-            .expect("republished packet");
+        let republished =
+            Pkdns::build_homeserver_packet(&keypair, std::slice::from_ref(&host), Some(&existing))
+                .expect("republished packet");
 
         assert!(
             republished
@@ -607,4 +963,76 @@ mod tests {
             "records from the newer cached packet should be preserved"
         );
     }
+
+    #[test]
+    fn multiple_homeservers_round_trip_in_priority_order() {
+        let keypair = Keypair::random();
+        let hosts: Vec<String> = (0..3)
+            .map(|_| Keypair::random().public_key().z32())
+            .collect();
+
+        let packet = Pkdns::build_homeserver_packet(&keypair, &hosts, None).expect("packet");
+        assert_eq!(extract_hosts_from_packet(&packet), hosts);
+        assert_eq!(extract_host_from_packet(&packet), Some(hosts[0].clone()));
+
+        // Republishing without an override keeps every host; an override replaces them.
+        assert_eq!(determine_hosts(None, Some(&packet)), hosts);
+        let override_host = Keypair::random().public_key();
+        assert_eq!(
+            determine_hosts(Some(&override_host), Some(&packet)),
+            vec![override_host.z32()]
+        );
+        assert!(determine_hosts(None, None).is_empty());
+    }
+
+    #[test]
+    fn publish_result_describes_the_packet() {
+        let keypair = Keypair::random();
+        let host = Keypair::random().public_key().z32();
+        let packet = Pkdns::build_homeserver_packet(&keypair, &[host], None).expect("packet");
+
+        let result = PublishResult::new(&packet, 7);
+        assert_eq!(result.ttl, Duration::from_secs(60 * 60));
+        assert_eq!(result.dht_nodes, 7);
+        assert_eq!(
+            result
+                .record_timestamp
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_micros(),
+            u128::from(packet.timestamp().as_u64())
+        );
+    }
+
+    #[test]
+    fn extract_hosts_orders_by_priority_and_dedupes() {
+        let keypair = Keypair::random();
+        let primary = Keypair::random().public_key().z32();
+        let secondary = Keypair::random().public_key().z32();
+        let record = |priority, host: &str| {
+            SVCB::new(priority, host.try_into().expect("host name conversion")).into_owned()
+        };
+
+        // Written out of order and with a duplicate.
+        let packet = SignedPacket::builder()
+            .https(
+                "_pubky".try_into().expect("_pubky name"),
+                record(5, &secondary),
+                3600,
+            )
+            .https(
+                "_pubky".try_into().expect("_pubky name"),
+                record(1, &primary),
+                3600,
+            )
+            .https(
+                "_pubky".try_into().expect("_pubky name"),
+                record(9, &primary),
+                3600,
+            )
+            .sign(&keypair)
+            .expect("signed packet");
+
+        assert_eq!(extract_hosts_from_packet(&packet), vec![primary, secondary]);
+    }
 }
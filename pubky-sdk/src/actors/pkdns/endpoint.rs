@@ -0,0 +1,531 @@
+//! Typed view of the HTTPS endpoints a homeserver publishes in its Pkarr packet.
+//!
+//! A homeserver publishes an `HTTPS` record pointing at itself (target `.`, served over
+//! `PubkyTLS` on its public IP) and, when it has an ICANN domain, a lower-priority record
+//! pointing at that domain for browsers. Local testnets additionally carry their plain-HTTP
+//! port in the reserved [`HTTP_PORT`] service parameter.
+//!
+//! How the records' priorities map to the order clients try endpoints in is documented
+//! on [`Pkdns::resolve_endpoints`].
+
+use std::collections::HashSet;
+use std::net::{IpAddr, SocketAddr};
+
+use pkarr::dns::rdata::{RData, SVCB, SVCParam};
+use pkarr::{ResolvePolicy, SignedPacket};
+use pubky_common::constants::reserved_param_keys::HTTP_PORT;
+
+use super::Pkdns;
+use crate::{PublicKey, cross_log};
+
+/// How many Pkarr packets one resolution follows through public-key targets, matching
+/// pkarr's default recursion depth.
+const MAX_CHAIN_DEPTH: usize = 7;
+
+/// How many names one resolution expands in total, across all branches.
+const MAX_RESOLUTIONS: usize = 16;
+
+/// How many endpoints one resolution returns at most.
+const MAX_ENDPOINTS: usize = 32;
+
+/// One endpoint of a homeserver, as parsed from its Pkarr `HTTPS`/`SVCB` records.
+///
+/// See [`Pkdns::resolve_endpoint`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HomeserverEndpoint {
+    /// Host to connect to: the ICANN domain, or the homeserver's z32 public key for a
+    /// direct `PubkyTLS` endpoint.
+    pub host: String,
+    /// `SvcPriority` of the `ServiceMode` record this endpoint comes from, or `0` for an
+    /// ICANN domain named by an `AliasMode` record.
+    pub priority: u16,
+    /// Port of the HTTPS (or `PubkyTLS`) listener, if the record sets one.
+    pub https_port: Option<u16>,
+    /// Plain-HTTP port from the reserved [`HTTP_PORT`] service parameter. Only local
+    /// testnets publish it.
+    pub http_port: Option<u16>,
+    /// Socket addresses advertised for a direct endpoint. Empty for ICANN domains, which
+    /// are resolved through regular DNS.
+    pub addrs: Vec<SocketAddr>,
+}
+
+impl HomeserverEndpoint {
+    /// Whether this endpoint is reached through an ICANN domain rather than `PubkyTLS`.
+    #[must_use]
+    pub fn is_icann(&self) -> bool {
+        PublicKey::try_from_z32(&self.host).is_err()
+    }
+}
+
+/// The `HTTPS` (or else `SVCB`) records at one name, by RFC 9460 mode.
+#[derive(Debug, PartialEq, Eq)]
+enum RecordSet {
+    /// Target of the first `AliasMode` record.
+    Alias(Target),
+    /// `ServiceMode` records, lowest priority first.
+    Service(Vec<ServiceRecord>),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Target {
+    /// `.`: the owner name itself.
+    Root,
+    /// Another Pkarr key, z32 encoded.
+    Key(String),
+    /// An ICANN domain.
+    Domain(String),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct ServiceRecord {
+    priority: u16,
+    target: Target,
+    port: Option<u16>,
+    http_port: Option<u16>,
+}
+
+impl From<&SVCB<'_>> for Target {
+    fn from(svcb: &SVCB<'_>) -> Self {
+        let target = svcb.target.to_string();
+        let target = target.trim_end_matches('.');
+        if target.is_empty() {
+            Self::Root
+        } else if PublicKey::try_from_z32(target).is_ok() {
+            Self::Key(target.to_string())
+        } else {
+            Self::Domain(target.to_string())
+        }
+    }
+}
+
+impl From<&SVCB<'_>> for ServiceRecord {
+    fn from(svcb: &SVCB<'_>) -> Self {
+        let port = svcb.iter_params().find_map(|param| match param {
+            SVCParam::Port(port) => Some(*port),
+            _ => None,
+        });
+        Self {
+            priority: svcb.priority,
+            target: Target::from(svcb),
+            port,
+            http_port: http_port(
+                svcb.iter_params()
+                    .find(|param| param.key_code() == HTTP_PORT),
+            ),
+        }
+    }
+}
+
+/// Value of the reserved [`HTTP_PORT`] service parameter.
+pub(crate) fn http_port(param: Option<&SVCParam<'_>>) -> Option<u16> {
+    match param? {
+        SVCParam::Unknown(_, bytes) => <[u8; 2]>::try_from(bytes.as_ref())
+            .ok()
+            .map(u16::from_be_bytes),
+        _ => None,
+    }
+}
+
+/// Read the records at `name` in `packet`. `None` if there are none.
+fn record_set(packet: &SignedPacket, name: &str) -> Option<RecordSet> {
+    let of_type = |https: bool| -> Vec<&SVCB<'_>> {
+        packet
+            .resource_records(name)
+            .filter_map(|rr| match &rr.rdata {
+                RData::HTTPS(record) if https => Some(&record.0),
+                RData::SVCB(record) if !https => Some(record),
+                _ => None,
+            })
+            .collect()
+    };
+    let mut records = of_type(true);
+    if records.is_empty() {
+        records = of_type(false);
+    }
+    if records.is_empty() {
+        return None;
+    }
+    if let Some(alias) = records.iter().find(|svcb| svcb.priority == 0) {
+        return Some(RecordSet::Alias(Target::from(*alias)));
+    }
+    let mut services: Vec<ServiceRecord> = records.into_iter().map(ServiceRecord::from).collect();
+    services.sort_by_key(|record| record.priority);
+    Some(RecordSet::Service(services))
+}
+
+/// `A`/`AAAA` addresses at the apex of `packet`, where a `.` target is served.
+fn apex_addrs(packet: &SignedPacket, port: u16) -> Vec<SocketAddr> {
+    let mut addrs: Vec<SocketAddr> = Vec::new();
+    for rr in packet.resource_records("@") {
+        let ip = match &rr.rdata {
+            RData::A(a) => IpAddr::from(std::net::Ipv4Addr::from(a.address)),
+            RData::AAAA(aaaa) => IpAddr::from(std::net::Ipv6Addr::from(aaaa.address)),
+            _ => continue,
+        };
+        let addr = SocketAddr::new(ip, port);
+        if !addrs.contains(&addr) {
+            addrs.push(addr);
+        }
+    }
+    addrs
+}
+
+/// Resolve the endpoints published for `qname` (`<z32>` or `<label>.<z32>`) in the order
+/// described on [`Pkdns::resolve_endpoints`].
+///
+/// Every name is expanded once, so records targeting their own key or keys targeting each
+/// other cannot multiply the work, and duplicate endpoints are dropped. At most
+/// [`MAX_RESOLUTIONS`] names are expanded and [`MAX_ENDPOINTS`] endpoints returned.
+pub(crate) async fn resolve_https_endpoints(
+    pkarr: &pkarr::Client,
+    qname: &str,
+) -> Vec<HomeserverEndpoint> {
+    enum Pending {
+        Name(String, usize),
+        Ready(HomeserverEndpoint),
+    }
+
+    let mut endpoints: Vec<HomeserverEndpoint> = Vec::new();
+    let mut expanded: HashSet<String> = HashSet::new();
+    // Popped from the end, so entries are pushed in reverse to keep their order.
+    let mut stack = vec![Pending::Name(qname.trim_end_matches('.').to_string(), 0)];
+    while let Some(pending) = stack.pop() {
+        if endpoints.len() >= MAX_ENDPOINTS {
+            cross_log!(
+                warn,
+                "Too many endpoints for {qname}, keeping the first {MAX_ENDPOINTS}"
+            );
+            break;
+        }
+        let (name, depth) = match pending {
+            Pending::Ready(endpoint) => {
+                if !endpoints.contains(&endpoint) {
+                    endpoints.push(endpoint);
+                }
+                continue;
+            }
+            Pending::Name(name, depth) => (name.to_ascii_lowercase(), depth),
+        };
+        let zone = name.rsplit('.').next().unwrap_or_default();
+        let Ok(key) = PublicKey::try_from_z32(zone) else {
+            continue;
+        };
+        if depth >= MAX_CHAIN_DEPTH {
+            cross_log!(
+                warn,
+                "Endpoint chain of {qname} is too deep, stopping at {name}"
+            );
+            continue;
+        }
+        if expanded.contains(&name) {
+            continue;
+        }
+        if expanded.len() >= MAX_RESOLUTIONS {
+            cross_log!(
+                warn,
+                "Endpoints of {qname} span too many names, stopping at {name}"
+            );
+            break;
+        }
+        expanded.insert(name.clone());
+        let Ok(packet) = pkarr.resolve(&key, ResolvePolicy::CacheFirst).await else {
+            continue;
+        };
+        match record_set(&packet, &name) {
+            None | Some(RecordSet::Alias(Target::Root)) => {}
+            Some(RecordSet::Alias(Target::Key(target))) => {
+                stack.push(Pending::Name(target, depth + 1));
+            }
+            Some(RecordSet::Alias(Target::Domain(domain))) => {
+                stack.push(Pending::Ready(HomeserverEndpoint {
+                    host: domain,
+                    priority: 0,
+                    https_port: None,
+                    http_port: None,
+                    addrs: Vec::new(),
+                }));
+            }
+            Some(RecordSet::Service(records)) => {
+                for record in records.into_iter().rev() {
+                    let (host, addrs) = match record.target {
+                        Target::Key(target) => {
+                            stack.push(Pending::Name(target, depth + 1));
+                            continue;
+                        }
+                        Target::Root => (
+                            key.z32(),
+                            apex_addrs(&packet, record.port.unwrap_or_default()),
+                        ),
+                        Target::Domain(domain) => (domain, Vec::new()),
+                    };
+                    stack.push(Pending::Ready(HomeserverEndpoint {
+                        host,
+                        priority: record.priority,
+                        https_port: record.port,
+                        http_port: record.http_port,
+                        addrs,
+                    }));
+                }
+            }
+        }
+    }
+    endpoints
+}
+
+impl Pkdns {
+    /// Resolve every HTTPS endpoint published by `homeserver`, in the order clients try
+    /// them.
+    ///
+    /// Returns an empty list if the homeserver's packet can't be resolved or has no
+    /// `HTTPS` records. To look up a user's endpoints, resolve their homeserver with
+    /// [`Self::get_homeserver_of`] first.
+    ///
+    /// # Priorities
+    ///
+    /// Records are read as RFC 9460 `HTTPS` records (`SVCB` records when a name has no
+    /// `HTTPS` records), and their `SvcPriority` is how operators order the ways to reach
+    /// a homeserver:
+    ///
+    /// - **Priority 0 (`AliasMode`)** delegates the name to the record's target, and any
+    ///   other records next to it are ignored. A public-key target is resolved in turn, an
+    ///   ICANN domain becomes a single endpoint, and `.` means there is no service.
+    ///   Publish at most one; if there are several, the first one wins.
+    /// - **Priority 1 and up (`ServiceMode`)** are endpoints, lowest priority first, ties
+    ///   in packet order. A `.` target is the key itself over `PubkyTLS`, at the key's
+    ///   `A`/`AAAA` addresses. A public-key target is replaced by that key's own
+    ///   endpoints. Any other target is an ICANN domain.
+    ///
+    /// Chains of public-key targets are followed up to 7 packets deep.
+    ///
+    /// Native clients connect through the first endpoint in this order: `PubkyTLS`
+    /// endpoints listed before an ICANN domain are probed, and the domain is used if none
+    /// answers. So a homeserver publishing its direct endpoint at priority 1 and its
+    /// domain at 10 is reached over `PubkyTLS` when possible, while swapping the two
+    /// priorities makes clients use the domain. Browsers only use ICANN domains. With [`read_failover`](crate::PubkyHttpClientBuilder::read_failover),
+    /// a user's homeservers are tried in their `_pubky` priority order, each reached
+    /// through its endpoints as above.
+    pub async fn resolve_endpoints(&self, homeserver: &PublicKey) -> Vec<HomeserverEndpoint> {
+        let qname = homeserver.z32();
+        let endpoints = resolve_https_endpoints(self.client.pkarr(), &qname).await;
+        cross_log!(
+            debug,
+            "Resolved {} endpoint(s) for homeserver {}",
+            endpoints.len(),
+            qname
+        );
+        endpoints
+    }
+
+    /// Resolve the endpoint this client would use to reach `homeserver`.
+    ///
+    /// On native targets this is the first published endpoint. Browsers can't speak
+    /// `PubkyTLS`, so on WASM it is the first endpoint with an ICANN domain.
+    ///
+    /// Returns `None` if no usable endpoint is published.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async fn example(homeserver: pubky::PublicKey) -> pubky::Result<()> {
+    /// let pkdns = pubky::Pkdns::new()?;
+    /// if let Some(endpoint) = pkdns.resolve_endpoint(&homeserver).await {
+    ///     println!("{}:{:?} (http {:?})", endpoint.host, endpoint.https_port, endpoint.http_port);
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub async fn resolve_endpoint(&self, homeserver: &PublicKey) -> Option<HomeserverEndpoint> {
+        self.resolve_endpoints(homeserver)
+            .await
+            .into_iter()
+            .find(|endpoint| cfg!(not(target_arch = "wasm32")) || endpoint.is_icann())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+    use std::net::Ipv4Addr;
+    use std::num::NonZeroUsize;
+    use std::sync::Arc;
+
+    use pkarr::{Cache, InMemoryCache};
+
+    use super::*;
+    use crate::{Keypair, PubkyHttpClient};
+
+    #[tokio::test]
+    async fn resolve_endpoints_parses_ports_and_hosts() {
+        let cache = Arc::new(InMemoryCache::new(NonZeroUsize::new(8).unwrap()));
+        let client = PubkyHttpClient::builder()
+            .isolated_pkarr_test()
+            .pkarr(|builder| builder.cache(Arc::clone(&cache) as Arc<dyn Cache>))
+            .build()
+            .unwrap();
+
+        let keypair = Keypair::random();
+        let mut direct = SVCB::new(1, ".".try_into().unwrap());
+        direct.set_port(6287);
+        let mut icann = SVCB::new(10, "localhost".try_into().unwrap());
+        icann.set_port(443);
+        icann.set_param(SVCParam::Unknown(
+            HTTP_PORT,
+            Cow::Owned(6286u16.to_be_bytes().to_vec()),
+        ));
+        let packet = SignedPacket::builder()
+            .https(".".try_into().unwrap(), direct, 3600)
+            .https(".".try_into().unwrap(), icann, 3600)
+            .address(".".try_into().unwrap(), Ipv4Addr::LOCALHOST.into(), 3600)
+            .sign(&keypair)
+            .unwrap();
+        cache.put(&packet.public_key().into(), &packet);
+
+        let pkdns = Pkdns::with_client(client);
+        let endpoints = pkdns.resolve_endpoints(&keypair.public_key()).await;
+        assert_eq!(
+            endpoints,
+            vec![
+                HomeserverEndpoint {
+                    host: keypair.public_key().z32(),
+                    priority: 1,
+                    https_port: Some(6287),
+                    http_port: None,
+                    addrs: vec![SocketAddr::from((Ipv4Addr::LOCALHOST, 6287))],
+                },
+                HomeserverEndpoint {
+                    host: "localhost".to_string(),
+                    priority: 10,
+                    https_port: Some(443),
+                    http_port: Some(6286),
+                    addrs: Vec::new(),
+                },
+            ]
+        );
+
+        assert!(
+            pkdns
+                .resolve_endpoint(&keypair.public_key())
+                .await
+                .is_some()
+        );
+        let unknown = Keypair::random().public_key();
+        assert!(pkdns.resolve_endpoints(&unknown).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn resolve_endpoints_orders_by_priority_and_follows_aliases() {
+        let cache = Arc::new(InMemoryCache::new(NonZeroUsize::new(8).unwrap()));
+        let client = PubkyHttpClient::builder()
+            .isolated_pkarr_test()
+            .pkarr(|builder| builder.cache(Arc::clone(&cache) as Arc<dyn Cache>))
+            .build()
+            .unwrap();
+        let publish = |keypair: &Keypair, records: Vec<SVCB<'static>>| {
+            let mut builder = SignedPacket::builder();
+            for svcb in records {
+                builder = builder.https(".".try_into().unwrap(), svcb, 3600);
+            }
+            let packet = builder.sign(keypair).unwrap();
+            cache.put(&packet.public_key().into(), &packet);
+        };
+        let svcb = |priority: u16, target: &str| {
+            SVCB::new(priority, target.try_into().unwrap()).into_owned()
+        };
+
+        // Service mode: lowest priority first, ties in packet order, key targets expanded in place.
+        let backup = Keypair::random();
+        publish(&backup, vec![svcb(1, "backup.example.com")]);
+        let homeserver = Keypair::random();
+        let backup_z32 = backup.public_key().z32();
+        publish(
+            &homeserver,
+            vec![
+                svcb(20, "c.example.com"),
+                svcb(15, &backup_z32),
+                svcb(5, "a.example.com"),
+                svcb(5, "b.example.com"),
+            ],
+        );
+        let pkdns = Pkdns::with_client(client);
+        let endpoints = pkdns.resolve_endpoints(&homeserver.public_key()).await;
+        let hosts: Vec<(&str, u16)> = endpoints
+            .iter()
+            .map(|endpoint| (endpoint.host.as_str(), endpoint.priority))
+            .collect();
+        assert_eq!(
+            hosts,
+            vec![
+                ("a.example.com", 5),
+                ("b.example.com", 5),
+                ("backup.example.com", 1),
+                ("c.example.com", 20),
+            ]
+        );
+
+        // Alias mode wins over service records next to it and is followed to its target.
+        let alias = Keypair::random();
+        let homeserver_z32 = homeserver.public_key().z32();
+        publish(
+            &alias,
+            vec![svcb(1, "ignored.example.com"), svcb(0, &homeserver_z32)],
+        );
+        assert_eq!(
+            pkdns.resolve_endpoints(&alias.public_key()).await,
+            endpoints
+        );
+
+        // An alias to an ICANN domain is a single endpoint, and `.` means no service.
+        let domain = Keypair::random();
+        publish(&domain, vec![svcb(0, "alias.example.com")]);
+        let resolved = pkdns.resolve_endpoints(&domain.public_key()).await;
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(
+            (resolved[0].host.as_str(), resolved[0].priority),
+            ("alias.example.com", 0)
+        );
+        let disabled = Keypair::random();
+        publish(
+            &disabled,
+            vec![svcb(0, "."), svcb(1, "ignored.example.com")],
+        );
+        assert!(
+            pkdns
+                .resolve_endpoints(&disabled.public_key())
+                .await
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn resolve_endpoints_expands_each_name_once() {
+        let cache = Arc::new(InMemoryCache::new(NonZeroUsize::new(8).unwrap()));
+        let client = PubkyHttpClient::builder()
+            .isolated_pkarr_test()
+            .pkarr(|builder| builder.cache(Arc::clone(&cache) as Arc<dyn Cache>))
+            .build()
+            .unwrap();
+
+        // Records pointing back at their own key, next to a real endpoint.
+        let keypair = Keypair::random();
+        let own_z32 = keypair.public_key().z32();
+        let mut builder = SignedPacket::builder();
+        for priority in 1..=12 {
+            let svcb = SVCB::new(priority, own_z32.as_str().try_into().unwrap());
+            builder = builder.https(".".try_into().unwrap(), svcb, 3600);
+        }
+        let icann = SVCB::new(20, "example.com".try_into().unwrap());
+        let packet = builder
+            .https(".".try_into().unwrap(), icann, 3600)
+            .sign(&keypair)
+            .unwrap();
+        cache.put(&packet.public_key().into(), &packet);
+
+        let pkdns = Pkdns::with_client(client);
+        let endpoints = tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            pkdns.resolve_endpoints(&keypair.public_key()),
+        )
+        .await
+        .expect("self-referencing records must not blow up the resolution");
+        let hosts: Vec<&str> = endpoints.iter().map(|e| e.host.as_str()).collect();
+        assert_eq!(hosts, vec!["example.com"]);
+    }
+}
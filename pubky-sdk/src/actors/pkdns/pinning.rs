@@ -0,0 +1,174 @@
+//! Trust-on-first-use pinning of the homeservers users' `_pubky` records point to.
+
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Display};
+use std::sync::{Arc, PoisonError, RwLock};
+
+use crate::{Error, PublicKey, Result, cross_log};
+
+/// Storage for pinned homeservers, consulted on every homeserver resolution of
+/// [`crate::Pkdns`] once installed with
+/// [`PubkyHttpClientBuilder::pin_records`](crate::PubkyHttpClientBuilder::pin_records).
+///
+/// The first homeserver resolved for a user is pinned. Later resolutions are compared
+/// against the pin; when the record names another homeserver, [`Self::record_changed`]
+/// is called and, under [`PinPolicy::Strict`], the resolution fails with
+/// [`Error::RecordChanged`]. The pin only moves through [`Self::pin`], e.g. from
+/// [`crate::Pkdns::accept_homeserver`] once the user confirms a migration.
+///
+/// Implement it to persist pins across restarts; [`MemoryPinStore`] keeps them in
+/// memory for the lifetime of the process.
+pub trait RecordPinStore: Debug + Send + Sync {
+    /// The homeserver pinned for `user`, if any.
+    fn pinned(&self, user: &PublicKey) -> Option<PublicKey>;
+
+    /// Pin `homeserver` for `user`, replacing any previous pin.
+    fn pin(&self, user: &PublicKey, homeserver: &PublicKey);
+
+    /// Called when a user's record names another homeserver than the pinned one.
+    ///
+    /// The pin is left untouched, so this fires on every resolution until the change
+    /// is accepted. Logs a warning by default.
+    fn record_changed(&self, change: &RecordChanged) {
+        cross_log!(warn, "{change}");
+    }
+}
+
+/// [`RecordPinStore`] keeping pins in memory.
+#[derive(Debug, Default)]
+pub struct MemoryPinStore {
+    pins: RwLock<HashMap<PublicKey, PublicKey>>,
+}
+
+impl MemoryPinStore {
+    /// Create an empty store.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl RecordPinStore for MemoryPinStore {
+    fn pinned(&self, user: &PublicKey) -> Option<PublicKey> {
+        self.pins
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(user)
+            .cloned()
+    }
+
+    fn pin(&self, user: &PublicKey, homeserver: &PublicKey) {
+        self.pins
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(user.clone(), homeserver.clone());
+    }
+}
+
+/// A user's `_pubky` record names another homeserver than the one pinned for them.
+///
+/// Either a legitimate migration or someone who got hold of the user's key publishing
+/// a record that points elsewhere.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecordChanged {
+    /// The user whose record changed.
+    pub user: PublicKey,
+    /// The pinned homeserver.
+    pub old: PublicKey,
+    /// The homeserver the record names now.
+    pub new: PublicKey,
+}
+
+impl Display for RecordChanged {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Homeserver of {} changed from pinned {} to {}",
+            self.user, self.old, self.new
+        )
+    }
+}
+
+/// What a resolution does when the record no longer matches the pin.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PinPolicy {
+    /// Report the change through [`RecordPinStore::record_changed`] and use the new
+    /// homeserver.
+    #[default]
+    Warn,
+    /// Report the change and fail the resolution with [`Error::RecordChanged`].
+    Strict,
+}
+
+/// The pin store and policy installed on a [`crate::PubkyHttpClient`].
+#[derive(Clone, Debug)]
+pub(crate) struct RecordPins {
+    pub(crate) store: Arc<dyn RecordPinStore>,
+    pub(crate) policy: PinPolicy,
+}
+
+impl RecordPins {
+    /// Compare a resolved homeserver against the pin of `user`, pinning it if there is none.
+    ///
+    /// # Errors
+    /// - [`Error::RecordChanged`] if it differs from the pin under [`PinPolicy::Strict`].
+    pub(crate) fn check(&self, user: &PublicKey, homeserver: &PublicKey) -> Result<()> {
+        let Some(old) = self.store.pinned(user) else {
+            cross_log!(debug, "Pinning homeserver {} for {}", homeserver, user);
+            self.store.pin(user, homeserver);
+            return Ok(());
+        };
+        if &old == homeserver {
+            return Ok(());
+        }
+        let change = RecordChanged {
+            user: user.clone(),
+            old,
+            new: homeserver.clone(),
+        };
+        self.store.record_changed(&change);
+        match self.policy {
+            PinPolicy::Warn => Ok(()),
+            PinPolicy::Strict => Err(Error::RecordChanged(Box::new(change))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Keypair;
+
+    #[test]
+    fn pins_on_first_use_and_reports_changes() {
+        let store = Arc::new(MemoryPinStore::new());
+        let pins = RecordPins {
+            store: Arc::clone(&store) as Arc<dyn RecordPinStore>,
+            policy: PinPolicy::Strict,
+        };
+        let user = Keypair::random().public_key();
+        let first = Keypair::random().public_key();
+        let second = Keypair::random().public_key();
+
+        pins.check(&user, &first).unwrap();
+        assert_eq!(store.pinned(&user), Some(first.clone()));
+        pins.check(&user, &first).unwrap();
+
+        let Err(Error::RecordChanged(change)) = pins.check(&user, &second) else {
+            panic!("expected a record change");
+        };
+        assert_eq!(change.old, first);
+        assert_eq!(change.new, second);
+        assert_eq!(store.pinned(&user), Some(first.clone()));
+
+        let warn = RecordPins {
+            policy: PinPolicy::Warn,
+            ..pins.clone()
+        };
+        warn.check(&user, &second).unwrap();
+        assert_eq!(store.pinned(&user), Some(first));
+
+        store.pin(&user, &second);
+        pins.check(&user, &second).unwrap();
+    }
+}
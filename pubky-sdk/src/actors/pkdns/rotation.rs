@@ -0,0 +1,393 @@
+//! Key rotation: publish and follow signed successor links between Pkarr keys.
+
+use std::collections::HashSet;
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use pkarr::{
+    ResolvePolicy, SignedPacket,
+    dns::rdata::{RData, TXT},
+};
+use pubky_common::crypto::Signature;
+use reqwest::StatusCode;
+
+use super::{Pkdns, most_recent_packet};
+use crate::{
+    Keypair, PubkySigner, PublicKey, cross_log,
+    errors::{Error, PkarrError, RequestError, Result},
+};
+
+/// Name of the TXT record through which a rotated key points to its successor.
+pub const SUCCESSOR_RECORD_NAME: &str = "_pubky-successor";
+
+/// Maximum number of successor links [`Pkdns::resolve_current_key`] follows.
+pub const MAX_ROTATION_HOPS: usize = 16;
+
+const SUCCESSOR_VERSION: &str = "1";
+const SUCCESSOR_RECORD_TTL: u32 = 60 * 60;
+
+/// A verified link from a rotated key to its successor.
+///
+/// Rotating from an old key to a new one ([`PubkySigner::rotate_to`]) publishes a TXT
+/// record named [`SUCCESSOR_RECORD_NAME`] in the **old** key's signed packet:
+///
+/// ```text
+/// _pubky-successor  TXT  "v=1" "pk=<new key z32>" "sig=<base64url signature>"
+/// ```
+///
+/// The packet signature proves the old key authorized the link. `sig` is the new
+/// key's Ed25519 signature over `pubky-successor:v1:<old z32>:<new z32>` and proves
+/// the new key accepted it, so nobody can name someone else's key as their successor.
+/// Links failing either check are ignored.
+///
+/// Full rotation semantics (moving data, revoking the old key) are out of scope: the
+/// link only tells other clients which key to follow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuccessorLink {
+    predecessor: PublicKey,
+    successor: PublicKey,
+    signature: Signature,
+}
+
+impl SuccessorLink {
+    /// Create the link from `predecessor` to `successor`, signed by the successor.
+    #[must_use]
+    pub fn new(predecessor: &PublicKey, successor: &Keypair) -> Self {
+        let successor_key = successor.public_key();
+        let signature = successor.sign(&Self::message(predecessor, &successor_key));
+        Self {
+            predecessor: predecessor.clone(),
+            successor: successor_key,
+            signature,
+        }
+    }
+
+    /// The rotated key.
+    #[must_use]
+    pub const fn predecessor(&self) -> &PublicKey {
+        &self.predecessor
+    }
+
+    /// The key that replaced [`Self::predecessor`].
+    #[must_use]
+    pub const fn successor(&self) -> &PublicKey {
+        &self.successor
+    }
+
+    /// Find the successor link in `packet` and verify it.
+    ///
+    /// Returns `None` if the packet has no link, or none that parses and verifies.
+    #[must_use]
+    pub fn from_packet(packet: &SignedPacket) -> Option<Self> {
+        let predecessor = PublicKey::from(packet.public_key());
+        packet
+            .resource_records(SUCCESSOR_RECORD_NAME)
+            .find_map(|rr| match &rr.rdata {
+                RData::TXT(txt) => Self::from_txt(&predecessor, txt),
+                _ => None,
+            })
+    }
+
+    fn from_txt(predecessor: &PublicKey, txt: &TXT) -> Option<Self> {
+        let attributes = txt.attributes();
+        let attribute = |key: &str| attributes.get(key).cloned().flatten();
+        if attribute("v")? != SUCCESSOR_VERSION {
+            return None;
+        }
+        let successor = PublicKey::try_from_z32(&attribute("pk")?).ok()?;
+        let signature_bytes: [u8; 64] = URL_SAFE_NO_PAD
+            .decode(attribute("sig")?)
+            .ok()?
+            .try_into()
+            .ok()?;
+        let signature = Signature::from_bytes(&signature_bytes);
+        successor
+            .verify(&Self::message(predecessor, &successor), &signature)
+            .ok()?;
+        Some(Self {
+            predecessor: predecessor.clone(),
+            successor,
+            signature,
+        })
+    }
+
+    fn to_txt(&self) -> Result<TXT<'static>> {
+        let version = format!("v={SUCCESSOR_VERSION}");
+        let successor = format!("pk={}", self.successor.z32());
+        let signature = format!("sig={}", URL_SAFE_NO_PAD.encode(self.signature.to_bytes()));
+        let txt = TXT::new()
+            .with_string(&version)
+            .and_then(|txt| txt.with_string(&successor))
+            .and_then(|txt| txt.with_string(&signature))
+            .map_err(PkarrError::from)?;
+        Ok(txt.into_owned())
+    }
+
+    fn message(predecessor: &PublicKey, successor: &PublicKey) -> Vec<u8> {
+        format!(
+            "pubky-successor:v{SUCCESSOR_VERSION}:{}:{}",
+            predecessor.z32(),
+            successor.z32()
+        )
+        .into_bytes()
+    }
+}
+
+impl Pkdns {
+    /// Resolve the verified successor that `public_key` rotated to, if any.
+    ///
+    /// # Errors
+    /// - Returns [`crate::errors::Error::Pkarr`] if resolution fails for any reason other
+    ///   than the packet not being found.
+    pub async fn get_successor_of(&self, public_key: &PublicKey) -> Result<Option<PublicKey>> {
+        match self
+            .client
+            .pkarr()
+            .resolve(public_key, ResolvePolicy::CacheFirst)
+            .await
+        {
+            Ok(packet) => Ok(SuccessorLink::from_packet(&packet).map(|link| link.successor)),
+            Err(pkarr::errors::ResolveError::NotFound) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Follow successor links from `original` to the key currently in use.
+    ///
+    /// Returns `original` itself if it never rotated.
+    ///
+    /// # Errors
+    /// - Returns [`crate::errors::RequestError::Validation`] if the links form a loop or
+    ///   exceed [`MAX_ROTATION_HOPS`].
+    /// - Returns [`crate::errors::Error::Pkarr`] if resolving a key along the chain fails.
+    pub async fn resolve_current_key(&self, original: &PublicKey) -> Result<PublicKey> {
+        let mut current = original.clone();
+        let mut seen = HashSet::from([current.clone()]);
+        for _ in 0..MAX_ROTATION_HOPS {
+            let Some(successor) = self.get_successor_of(&current).await? else {
+                return Ok(current);
+            };
+            cross_log!(debug, "Key {} rotated to {}", current, successor);
+            if !seen.insert(successor.clone()) {
+                return Err(RequestError::Validation {
+                    message: format!("key rotation loop detected at {}", successor.z32()),
+                }
+                .into());
+            }
+            current = successor;
+        }
+        Err(RequestError::Validation {
+            message: format!(
+                "key rotation chain from {} exceeds {MAX_ROTATION_HOPS} hops",
+                original.z32()
+            ),
+        }
+        .into())
+    }
+
+    /// Publish a link from this actor's key to `successor`, keeping all other records.
+    async fn publish_successor(&self, successor: &Keypair) -> Result<()> {
+        let keypair = self.keypair_ref()?;
+        let public_key = keypair.public_key();
+        let resolved = self
+            .client
+            .pkarr()
+            .resolve(&public_key, ResolvePolicy::NetworkOnly)
+            .await
+            .ok();
+        let cached = self
+            .client
+            .pkarr()
+            .resolve(&public_key, ResolvePolicy::CacheOnly)
+            .await
+            .ok();
+        let existing = most_recent_packet(resolved, cached);
+
+        let link = SuccessorLink::new(&public_key, successor);
+        let packet = build_successor_packet(keypair, &link, existing.as_ref())?;
+        self.client
+            .pkarr()
+            .publish(&packet)
+            .await
+            .map_err(PkarrError::from)?;
+        cross_log!(
+            info,
+            "Published successor link {} -> {}",
+            public_key,
+            link.successor
+        );
+        Ok(())
+    }
+}
+
+/// Keep all records of `existing` except a previous link, then add `link`.
+fn build_successor_packet(
+    keypair: &Keypair,
+    link: &SuccessorLink,
+    existing: Option<&SignedPacket>,
+) -> Result<SignedPacket> {
+    let mut builder = SignedPacket::builder();
+    if let Some(packet) = existing {
+        let previous: Vec<_> = packet.resource_records(SUCCESSOR_RECORD_NAME).collect();
+        for record in packet.all_resource_records() {
+            if !previous.contains(&record) {
+                builder = builder.record(record.to_owned());
+            }
+        }
+    }
+    let name = SUCCESSOR_RECORD_NAME.try_into().map_err(PkarrError::from)?;
+    Ok(builder
+        .txt(name, link.to_txt()?, SUCCESSOR_RECORD_TTL)
+        .sign(keypair)
+        .map_err(PkarrError::from)?)
+}
+
+impl PubkySigner {
+    /// Rotate this identity to `new_keypair`.
+    ///
+    /// Creates an account for the new key on this key's homeserver (reusing it if it
+    /// already exists, so a failed rotation can be retried), then publishes a
+    /// [`SuccessorLink`] in this key's Pkarr packet. Other clients follow the link with
+    /// [`Pkdns::resolve_current_key`].
+    ///
+    /// Data stored under the old key is **not** copied, and existing sessions of the old
+    /// key stay valid. Sign in with the returned signer to get a session for the new key.
+    ///
+    /// # Errors
+    /// - Returns [`crate::errors::Error::RecordNotFound`] if this key has no resolvable
+    ///   homeserver.
+    /// - Propagates failures while creating the new account (e.g. a missing signup token).
+    /// - Returns [`crate::errors::Error::Pkarr`] if publishing the successor link fails.
+    pub async fn rotate_to(
+        &self,
+        new_keypair: Keypair,
+        signup_token: Option<&str>,
+    ) -> Result<Self> {
+        let homeserver = self
+            .pkdns()
+            .require_homeserver_of(&self.public_key())
+            .await?;
+        cross_log!(
+            info,
+            "Rotating {} to {} on homeserver {}",
+            self.public_key(),
+            new_keypair.public_key(),
+            homeserver
+        );
+
+        let successor = Self {
+            client: self.client.clone(),
+            keypair: new_keypair,
+        };
+        match successor.signup(&homeserver, signup_token).await {
+            Ok(()) => {}
+            Err(Error::Request(RequestError::Server { status, .. }))
+                if status == StatusCode::CONFLICT =>
+            {
+                successor
+                    .pkdns()
+                    .publish_homeserver_force(Some(&homeserver))
+                    .await?;
+            }
+            Err(err) => return Err(err),
+        }
+
+        self.pkdns().publish_successor(&successor.keypair).await?;
+        Ok(successor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pkarr::dns::rdata::SVCB;
+
+    use super::*;
+    use crate::actors::pkdns::extract_host_from_packet;
+
+    fn packet_with_link(keypair: &Keypair, link: &SuccessorLink) -> SignedPacket {
+        build_successor_packet(keypair, link, None).unwrap()
+    }
+
+    #[test]
+    fn successor_link_roundtrips_through_packet() {
+        let old = Keypair::random();
+        let new = Keypair::random();
+        let link = SuccessorLink::new(&old.public_key(), &new);
+
+        let parsed = SuccessorLink::from_packet(&packet_with_link(&old, &link)).unwrap();
+
+        assert_eq!(parsed, link);
+        assert_eq!(parsed.predecessor(), &old.public_key());
+        assert_eq!(parsed.successor(), &new.public_key());
+    }
+
+    #[test]
+    fn successor_link_requires_successor_signature() {
+        let old = Keypair::random();
+        let victim = Keypair::random();
+        // Signed by the old key's packet, but the named successor never agreed to it.
+        let forged = SuccessorLink {
+            predecessor: old.public_key(),
+            successor: victim.public_key(),
+            signature: old.sign(&SuccessorLink::message(
+                &old.public_key(),
+                &victim.public_key(),
+            )),
+        };
+
+        assert_eq!(
+            SuccessorLink::from_packet(&packet_with_link(&old, &forged)),
+            None
+        );
+    }
+
+    #[test]
+    fn successor_link_is_bound_to_its_predecessor() {
+        let old = Keypair::random();
+        let other = Keypair::random();
+        let new = Keypair::random();
+        // A link the successor signed for `old`, replayed in another key's packet.
+        let replayed = SuccessorLink::new(&old.public_key(), &new);
+
+        assert_eq!(
+            SuccessorLink::from_packet(&packet_with_link(&other, &replayed)),
+            None
+        );
+    }
+
+    #[test]
+    fn successor_packet_keeps_other_records_and_replaces_link() {
+        let old = Keypair::random();
+        let host = Keypair::random().public_key().z32();
+        let existing = SignedPacket::builder()
+            .https(
+                "_pubky".try_into().unwrap(),
+                SVCB::new(0, host.as_str().try_into().unwrap()),
+                3600,
+            )
+            .sign(&old)
+            .unwrap();
+        let first = SuccessorLink::new(&old.public_key(), &Keypair::random());
+        let second = SuccessorLink::new(&old.public_key(), &Keypair::random());
+
+        let packet = build_successor_packet(&old, &first, Some(&existing)).unwrap();
+        let packet = build_successor_packet(&old, &second, Some(&packet)).unwrap();
+
+        assert_eq!(extract_host_from_packet(&packet), Some(host));
+        assert_eq!(packet.resource_records(SUCCESSOR_RECORD_NAME).count(), 1);
+        assert_eq!(SuccessorLink::from_packet(&packet), Some(second));
+    }
+
+    #[test]
+    fn homeserver_republish_keeps_successor_link() {
+        let old = Keypair::random();
+        let link = SuccessorLink::new(&old.public_key(), &Keypair::random());
+        let packet = packet_with_link(&old, &link);
+        let host = Keypair::random().public_key().z32();
+
+        let republished =
+            Pkdns::build_homeserver_packet(&old, std::slice::from_ref(&host), Some(&packet))
+                .unwrap();
+
+        assert_eq!(SuccessorLink::from_packet(&republished), Some(link));
+    }
+}
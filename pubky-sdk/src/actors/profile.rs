@@ -0,0 +1,98 @@
+//! In-memory cache of user profiles read through [`crate::Pubky::profile`].
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use pubky_common::profile::Profile;
+use web_time::Instant;
+
+use crate::PublicKey;
+
+/// How long [`crate::Pubky::profile`] reuses a fetched profile, including the
+/// answer that a user has none.
+pub const PROFILE_CACHE_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Maximum number of users whose profile is cached at once.
+const PROFILE_CACHE_CAPACITY: usize = 1024;
+
+/// A fetched profile; `None` if the user has not published one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CachedProfile {
+    fetched: Instant,
+    pub(crate) profile: Option<Profile>,
+}
+
+impl CachedProfile {
+    fn is_fresh(&self) -> bool {
+        self.fetched.elapsed() < PROFILE_CACHE_TTL
+    }
+}
+
+/// Profiles keyed by user, shared by all clones of a [`crate::PubkyHttpClient`].
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ProfileCache {
+    entries: Arc<Mutex<HashMap<PublicKey, CachedProfile>>>,
+}
+
+impl ProfileCache {
+    /// The cached profile of `user`, if it was fetched less than [`PROFILE_CACHE_TTL`] ago.
+    pub(crate) fn get(&self, user: &PublicKey) -> Option<CachedProfile> {
+        let entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        entries
+            .get(user)
+            .filter(|cached| cached.is_fresh())
+            .cloned()
+    }
+
+    /// Cache `profile` for `user`, evicting expired entries (then the oldest) when full.
+    pub(crate) fn insert(&self, user: PublicKey, profile: Option<Profile>) {
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        if entries.len() >= PROFILE_CACHE_CAPACITY && !entries.contains_key(&user) {
+            entries.retain(|_, cached| cached.is_fresh());
+            if entries.len() >= PROFILE_CACHE_CAPACITY {
+                let oldest = entries
+                    .iter()
+                    .min_by_key(|(_, cached)| cached.fetched)
+                    .map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    entries.remove(&oldest);
+                }
+            }
+        }
+        let fetched = Instant::now();
+        entries.insert(user, CachedProfile { fetched, profile });
+    }
+
+    /// Drop the cached profile of `user`.
+    pub(crate) fn remove(&self, user: &PublicKey) {
+        self.entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(user);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Keypair;
+
+    #[test]
+    fn stays_within_capacity() {
+        let cache = ProfileCache::default();
+        let users: Vec<_> = (0..=PROFILE_CACHE_CAPACITY)
+            .map(|_| Keypair::random().public_key())
+            .collect();
+        for user in &users {
+            cache.insert(user.clone(), None);
+        }
+
+        assert_eq!(cache.entries.lock().unwrap().len(), PROFILE_CACHE_CAPACITY);
+        let cached = cache.get(&users[PROFILE_CACHE_CAPACITY]).unwrap();
+        assert_eq!(cached.profile, None);
+
+        cache.remove(&users[PROFILE_CACHE_CAPACITY]);
+        assert!(cache.get(&users[PROFILE_CACHE_CAPACITY]).is_none());
+    }
+}
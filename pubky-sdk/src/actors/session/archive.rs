@@ -0,0 +1,887 @@
+//! Account export to a single archive, and import of it into another account.
+//!
+//! An archive is a tar file. Its first entry, [`MANIFEST_NAME`], is a JSON manifest
+//! listing every exported file with its BLAKE3 hash, size and content type, see
+//! [`pubky_common::storage::ArchiveManifest`]. The files follow in manifest order,
+//! named by their path without the leading `/` (e.g. `pub/my-cool-app/a.txt`).
+//! Files are hashed against the manifest when they are exported, and the homeserver
+//! checks them again before it imports them.
+
+use std::collections::HashSet;
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use futures_util::StreamExt;
+use pubky_common::crypto::{Hash, Hasher, PublicKey};
+use pubky_common::storage::{
+    ARCHIVE_MANIFEST_NAME, ARCHIVE_MANIFEST_VERSION, ArchiveManifest, ArchiveManifestEntry,
+    ImportEntryStatus, ImportResponse,
+};
+use reqwest::Method;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use super::core::PubkySession;
+use crate::actors::storage::verbs::decode_hash;
+use crate::errors::{Error, RequestError};
+use crate::util::check_http_status;
+use crate::{CancelToken, PubkyResponse, ResourcePath, Result, cross_log};
+
+/// Name of the manifest entry, the first entry of every account archive.
+pub const MANIFEST_NAME: &str = ARCHIVE_MANIFEST_NAME;
+
+/// Path of the homeserver's import endpoint, relative to the user.
+const IMPORT_PATH: &str = "/import";
+
+/// Size in bytes after which [`PubkySession::import_account`] sends the files read so
+/// far, well below the homeserver's request body limit.
+const IMPORT_BATCH_BYTES: usize = 16 * 1024 * 1024;
+
+/// Size of a tar block; headers and padded entry data are multiples of it.
+const BLOCK: usize = 512;
+
+/// Largest file [`PubkySession::import_account`] reads into memory to send it.
+/// Together with [`IMPORT_BATCH_BYTES`] it keeps every batch below the homeserver's
+/// 100 MB limit on imported archives. Larger files are rejected without being read.
+const MAX_ENTRY_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Largest GNU long-name entry accepted when reading an archive.
+const MAX_NAME_BYTES: u64 = 64 * 1024;
+
+/// Container format of an account archive.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ArchiveFormat {
+    /// A POSIX tar archive, with GNU long-name entries for long paths.
+    #[default]
+    Tar,
+}
+
+/// Files of an account archive, written as its first entry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccountManifest {
+    /// The user the archive was exported from.
+    pub user: PublicKey,
+    /// Every exported file, in archive order.
+    pub entries: Vec<ManifestEntry>,
+}
+
+/// One file of an [`AccountManifest`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ManifestEntry {
+    /// Absolute path of the file, e.g. `/pub/my-cool-app/a.txt`.
+    pub path: ResourcePath,
+    /// BLAKE3 hash of the file content.
+    pub content_hash: Hash,
+    /// Size of the file in bytes.
+    pub size: u64,
+    /// Content type the homeserver stored the file with, if known.
+    pub content_type: Option<String>,
+}
+
+/// How far a [`PubkySession::export_account_cancellable`] got.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccountExport {
+    /// Manifest written at the start of the archive.
+    pub manifest: AccountManifest,
+    /// Number of manifest entries written to the archive so far.
+    pub exported: usize,
+    /// Whether the export stopped early because its [`CancelToken`] was cancelled.
+    ///
+    /// When `true`, the archive ends after the last exported file and lacks the tar
+    /// trailer. Pass this value to [`PubkySession::export_account_cancellable`] with a
+    /// writer appending to the same archive to complete it.
+    pub cancelled: bool,
+}
+
+impl AccountExport {
+    /// Rebuild the state of a stopped export from the archive it wrote, to resume it
+    /// with [`PubkySession::export_account_cancellable`] when the returned
+    /// [`AccountExport`] was lost, e.g. because the app was closed.
+    ///
+    /// Reads the manifest and counts the files after it, without holding them in
+    /// memory. The result is always [`cancelled`](Self::cancelled), even if the
+    /// archive is complete.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async fn ex(session: pubky::PubkySession) -> pubky::Result<()> {
+    /// use pubky::{AccountExport, ArchiveFormat, CancelToken};
+    /// use tokio::fs::{File, OpenOptions};
+    ///
+    /// let partial = File::open("account.tar").await.expect("readable archive");
+    /// let export = AccountExport::from_archive(partial).await?;
+    /// let file = OpenOptions::new().append(true).open("account.tar").await.expect("archive");
+    /// session
+    ///     .export_account_cancellable(file, ArchiveFormat::Tar, Some(&export), &CancelToken::new())
+    ///     .await?;
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// - [`RequestError::Validation`] if the archive is malformed, ends within a file,
+    ///   or its files do not follow the manifest order.
+    pub async fn from_archive<R>(mut reader: R) -> Result<Self>
+    where
+        R: AsyncRead + Unpin,
+    {
+        let manifest = read_manifest(&mut reader).await?;
+        let mut exported = 0;
+        while let Some((name, size)) = read_header(&mut reader).await? {
+            let path = archived_path(&name)?;
+            match manifest.entries.get(exported) {
+                Some(entry) if entry.path == path && entry.size == size => {}
+                _ => {
+                    return Err(invalid_archive(format!(
+                        "{path} does not follow the manifest order"
+                    )));
+                }
+            }
+            skip_content(&mut reader, size).await?;
+            exported += 1;
+        }
+        Ok(Self {
+            manifest,
+            exported,
+            cancelled: true,
+        })
+    }
+}
+
+/// What a [`PubkySession::import_account`] restored.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    /// Files written to the session's account, in archive order.
+    pub imported: Vec<ResourcePath>,
+    /// Files the account already held with the same content, which were left as is.
+    pub unchanged: Vec<ResourcePath>,
+    /// Files of the manifest the archive does not contain, e.g. because its export
+    /// was cancelled.
+    pub missing: Vec<ResourcePath>,
+    /// Files that were not written, e.g. because they do not match their manifest
+    /// hash, the session cannot write them, they exceed the storage quota or they are
+    /// larger than 64 MiB.
+    pub rejected: Vec<RejectedImport>,
+}
+
+/// A file of an archive the homeserver did not import, see [`ImportReport::rejected`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RejectedImport {
+    /// Path of the file.
+    pub path: ResourcePath,
+    /// Why the homeserver rejected the file.
+    pub reason: String,
+}
+
+impl ManifestEntry {
+    fn to_wire(&self) -> ArchiveManifestEntry {
+        ArchiveManifestEntry {
+            path: self.path.to_string(),
+            content_hash: STANDARD.encode(self.content_hash.as_bytes()),
+            size: self.size,
+            content_type: self.content_type.clone(),
+        }
+    }
+}
+
+impl AccountManifest {
+    fn to_json(&self) -> Result<Vec<u8>> {
+        manifest_json(&self.user, self.entries.iter())
+    }
+
+    fn from_json(json: &[u8]) -> Result<Self> {
+        let manifest: ArchiveManifest =
+            serde_json::from_slice(json).map_err(|e| RequestError::DecodeJson {
+                message: format!("invalid account manifest: {e}"),
+            })?;
+        if manifest.version != ARCHIVE_MANIFEST_VERSION {
+            return Err(invalid_archive(format!(
+                "unsupported manifest version {}",
+                manifest.version
+            )));
+        }
+        let user = PublicKey::try_from_z32(&manifest.user)
+            .map_err(|e| invalid_archive(format!("invalid user in manifest: {e}")))?;
+        let entries = manifest
+            .entries
+            .into_iter()
+            .map(|entry| {
+                Ok(ManifestEntry {
+                    path: archived_path(entry.path.trim_start_matches('/'))?,
+                    content_hash: decode_hash(&entry.content_hash)?,
+                    size: entry.size,
+                    content_type: entry.content_type,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { user, entries })
+    }
+}
+
+/// JSON manifest of `user` listing `entries`.
+fn manifest_json<'a>(
+    user: &PublicKey,
+    entries: impl Iterator<Item = &'a ManifestEntry>,
+) -> Result<Vec<u8>> {
+    let manifest = ArchiveManifest {
+        version: ARCHIVE_MANIFEST_VERSION,
+        user: user.z32(),
+        entries: entries.map(ManifestEntry::to_wire).collect(),
+    };
+    serde_json::to_vec_pretty(&manifest).map_err(|e| {
+        RequestError::Validation {
+            message: format!("failed to encode the manifest: {e}"),
+        }
+        .into()
+    })
+}
+
+impl PubkySession {
+    /// Write every file of this account to `writer` as a single archive, with a
+    /// manifest of their paths, hashes and content types.
+    ///
+    /// Exports `/pub/`, and `/priv/` if the session's capabilities can read it. Files
+    /// are streamed into the archive one at a time and checked against the hash in the
+    /// manifest, so a file replaced during the export fails it; export again. Restore the archive with
+    /// [`Self::import_account`], into this account or another one on any homeserver.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async fn ex(session: pubky::PubkySession) -> pubky::Result<()> {
+    /// use pubky::ArchiveFormat;
+    ///
+    /// let file = tokio::fs::File::create("account.tar").await.expect("writable file");
+    /// let manifest = session.export_account(file, ArchiveFormat::Tar).await?;
+    /// println!("exported {} files", manifest.entries.len());
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// - [`crate::errors::Error::Request`] on HTTP transport failures or non-success
+    ///   statuses while listing or downloading files.
+    /// - [`RequestError::Validation`] if a file changed during the export, or if
+    ///   writing to `writer` fails. Part of that file may already be written.
+    pub async fn export_account<W>(
+        &self,
+        writer: W,
+        format: ArchiveFormat,
+    ) -> Result<AccountManifest>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let export = self
+            .export_account_cancellable(writer, format, None, &CancelToken::new())
+            .await?;
+        Ok(export.manifest)
+    }
+
+    /// Like [`Self::export_account`], but stops between two files once `cancel` fires,
+    /// and can resume a stopped export.
+    ///
+    /// To resume, open the partial archive for appending and pass the [`AccountExport`]
+    /// the stopped call returned, or one rebuilt with [`AccountExport::from_archive`],
+    /// as `resume`: the manifest is not written again and the
+    /// remaining files are appended. The archive must end after the last exported file,
+    /// as a cancelled export leaves it.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async fn ex(session: pubky::PubkySession, cancel: pubky::CancelToken) -> pubky::Result<()> {
+    /// use pubky::ArchiveFormat;
+    /// use tokio::fs::OpenOptions;
+    ///
+    /// let file = tokio::fs::File::create("account.tar").await.expect("writable file");
+    /// let mut export = session
+    ///     .export_account_cancellable(file, ArchiveFormat::Tar, None, &cancel)
+    ///     .await?;
+    /// while export.cancelled {
+    ///     // Later, e.g. after the app is back in the foreground:
+    ///     let file = OpenOptions::new().append(true).open("account.tar").await.expect("archive");
+    ///     export = session
+    ///         .export_account_cancellable(file, ArchiveFormat::Tar, Some(&export), &pubky::CancelToken::new())
+    ///         .await?;
+    /// }
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// Same as [`Self::export_account`]. Cancellation is not an error.
+    pub async fn export_account_cancellable<W>(
+        &self,
+        mut writer: W,
+        format: ArchiveFormat,
+        resume: Option<&AccountExport>,
+        cancel: &CancelToken,
+    ) -> Result<AccountExport>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let ArchiveFormat::Tar = format;
+        let mut archive = tar::Builder::new(Vec::new());
+        let mut export = if let Some(resume) = resume {
+            AccountExport {
+                cancelled: false,
+                ..resume.clone()
+            }
+        } else {
+            let manifest = self.account_manifest().await?;
+            append_entry(&mut archive, MANIFEST_NAME, &manifest.to_json()?)?;
+            write_drained(&mut writer, &mut archive).await?;
+            AccountExport {
+                manifest,
+                exported: 0,
+                cancelled: false,
+            }
+        };
+
+        let storage = self.storage();
+        while let Some(entry) = export.manifest.entries.get(export.exported) {
+            if cancel.is_cancelled() {
+                cross_log!(
+                    info,
+                    "Cancelled the account export after {} of {} files",
+                    export.exported,
+                    export.manifest.entries.len()
+                );
+                export.cancelled = true;
+                writer.flush().await.map_err(|e| write_error(&e))?;
+                return Ok(export);
+            }
+            let response = storage.get(&entry.path).await?;
+            // The header holds the manifest size; the content is checked as it streams.
+            let mut header = tar::Header::new_gnu();
+            header.set_size(entry.size);
+            header.set_mode(0o644);
+            archive
+                .append_data(&mut header, entry_name(&entry.path), std::io::empty())
+                .map_err(|e| write_error(&e))?;
+            write_drained(&mut writer, &mut archive).await?;
+            write_content(&mut writer, response, entry).await?;
+            export.exported += 1;
+        }
+
+        archive.finish().map_err(|e| write_error(&e))?;
+        write_drained(&mut writer, &mut archive).await?;
+        writer.flush().await.map_err(|e| write_error(&e))?;
+        Ok(export)
+    }
+
+    /// Restore an archive written by [`Self::export_account`] into this account.
+    ///
+    /// The archive may come from another user or homeserver. Its files are sent to the
+    /// homeserver's import endpoint in batches of a few MiB, and the homeserver checks
+    /// each against its manifest hash, the session's capabilities and the storage quota
+    /// before writing it over any file at the same path. Files the account already holds
+    /// with the same content are not written again. A file the homeserver rejects does
+    /// not stop the import; the report tells what happened to every manifest entry.
+    /// On a [`Self::scoped`] session, files outside its scope are rejected locally.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async fn ex(session: pubky::PubkySession) -> pubky::Result<()> {
+    /// let file = tokio::fs::File::open("account.tar").await.expect("readable file");
+    /// let report = session.import_account(file).await?;
+    /// println!("imported {} files", report.imported.len());
+    /// for rejected in &report.rejected {
+    ///     eprintln!("{}: {}", rejected.path, rejected.reason);
+    /// }
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// - [`RequestError::Validation`] if the archive is malformed, has no manifest, or
+    ///   holds a file that is not in the manifest. Batches sent before are already
+    ///   imported.
+    /// - [`crate::errors::Error::Request`] on HTTP transport failures or non-success
+    ///   statuses of the import endpoint.
+    pub async fn import_account<R>(&self, mut reader: R) -> Result<ImportReport>
+    where
+        R: AsyncRead + Unpin,
+    {
+        let manifest = read_manifest(&mut reader).await?;
+
+        let storage = self.storage();
+        let mut report = ImportReport::default();
+        let mut read = HashSet::new();
+        let mut batch = Vec::new();
+        let mut batch_bytes = 0;
+        while let Some((name, size)) = read_header(&mut reader).await? {
+            let path = archived_path(&name)?;
+            let Some(entry) = manifest.entries.iter().find(|entry| entry.path == path) else {
+                return Err(invalid_archive(format!("{path} is not in the manifest")));
+            };
+            read.insert(path.clone());
+            // The homeserver only knows the full credential's capabilities.
+            let rejection = if self.credential.is_scoped()
+                && let Err(err) = storage.ensure_permitted(&Method::PUT, &path)
+            {
+                Some(err.to_string())
+            } else if size > MAX_ENTRY_BYTES {
+                Some(format!(
+                    "{size} bytes is more than the {MAX_ENTRY_BYTES} bytes an import accepts"
+                ))
+            } else {
+                None
+            };
+            if let Some(reason) = rejection {
+                skip_content(&mut reader, size).await?;
+                report.rejected.push(RejectedImport { path, reason });
+                continue;
+            }
+            let content = read_content(&mut reader, size).await?;
+            batch_bytes += content.len();
+            batch.push((entry, content));
+            if batch_bytes >= IMPORT_BATCH_BYTES {
+                self.import_batch(&manifest.user, &batch, &mut report)
+                    .await?;
+                batch.clear();
+                batch_bytes = 0;
+            }
+        }
+        if !batch.is_empty() {
+            self.import_batch(&manifest.user, &batch, &mut report)
+                .await?;
+        }
+        report.missing.extend(
+            manifest
+                .entries
+                .into_iter()
+                .map(|entry| entry.path)
+                .filter(|path| !read.contains(path)),
+        );
+        cross_log!(
+            info,
+            "Imported {} files from an archive of {} ({} unchanged, {} missing, {} rejected)",
+            report.imported.len(),
+            manifest.user,
+            report.unchanged.len(),
+            report.missing.len(),
+            report.rejected.len()
+        );
+        Ok(report)
+    }
+
+    /// Send `files` to the import endpoint as an archive of their own, adding the
+    /// outcome of each to `report`.
+    async fn import_batch(
+        &self,
+        user: &PublicKey,
+        files: &[(&ManifestEntry, Vec<u8>)],
+        report: &mut ImportReport,
+    ) -> Result<()> {
+        let mut archive = tar::Builder::new(Vec::new());
+        let json = manifest_json(user, files.iter().map(|(entry, _)| *entry))?;
+        append_entry(&mut archive, MANIFEST_NAME, &json)?;
+        for (entry, content) in files {
+            append_entry(&mut archive, &entry_name(&entry.path), content)?;
+        }
+        let body = archive.into_inner().map_err(|e| write_error(&e))?;
+
+        let storage = self.storage();
+        let url = storage.url_for(&ResourcePath::parse(IMPORT_PATH)?)?;
+        let rb = self
+            .client
+            .cross_request(Method::POST, url)
+            .await?
+            .body(body);
+        let rb = storage.attach_credential(rb).await?;
+        let response = check_http_status(self.client.send(rb).await?).await?;
+        let imported: ImportResponse = response.json().await?;
+        for result in imported.entries {
+            let path = ResourcePath::parse_encoded(&result.path)?;
+            match result.status {
+                ImportEntryStatus::Imported => report.imported.push(path),
+                ImportEntryStatus::Unchanged => report.unchanged.push(path),
+                ImportEntryStatus::Missing => report.missing.push(path),
+                ImportEntryStatus::Rejected => report.rejected.push(RejectedImport {
+                    path,
+                    reason: result.message.unwrap_or_default(),
+                }),
+            }
+        }
+        Ok(())
+    }
+
+    /// Manifest of every file the session can read in `/pub/` and `/priv/`.
+    async fn account_manifest(&self) -> Result<AccountManifest> {
+        let storage = self.storage();
+        let info = self.info();
+        let mut entries = Vec::new();
+        for root in ["/pub/", "/priv/"] {
+            if !info.can_read(root) {
+                continue;
+            }
+            let root = ResourcePath::parse(root)?;
+            for path in storage.remote_files(&root).await?.into_values() {
+                let metadata = storage.metadata(&path).await?;
+                // Deleted since it was listed.
+                let (true, Some(content_hash), Some(size)) =
+                    (metadata.exists, metadata.content_hash, metadata.size)
+                else {
+                    continue;
+                };
+                entries.push(ManifestEntry {
+                    path,
+                    content_hash,
+                    size,
+                    content_type: metadata.content_type,
+                });
+            }
+        }
+        Ok(AccountManifest {
+            user: info.public_key().clone(),
+            entries,
+        })
+    }
+}
+
+/// Read the manifest, the first entry of an archive.
+async fn read_manifest<R: AsyncRead + Unpin>(reader: &mut R) -> Result<AccountManifest> {
+    let Some((name, size)) = read_header(reader).await? else {
+        return Err(invalid_archive("the archive is empty"));
+    };
+    if name != MANIFEST_NAME {
+        return Err(invalid_archive(format!(
+            "the first entry is {name}, not {MANIFEST_NAME}"
+        )));
+    }
+    if size > MAX_ENTRY_BYTES {
+        return Err(invalid_archive("the manifest is too large"));
+    }
+    AccountManifest::from_json(&read_content(reader, size).await?)
+}
+
+/// Archive entry name of the file at `path`.
+fn entry_name(path: &ResourcePath) -> String {
+    path.as_str().trim_start_matches('/').to_string()
+}
+
+/// The path of the file archived as `name`, which must be in `/pub/` or `/priv/`.
+fn archived_path(name: &str) -> Result<ResourcePath> {
+    let path = ResourcePath::parse_encoded(&format!("/{name}"))?;
+    let is_file = !path.as_str().ends_with('/');
+    if !is_file || !(path.as_str().starts_with("/pub/") || path.as_str().starts_with("/priv/")) {
+        return Err(invalid_archive(format!(
+            "{name} is not a file in /pub/ or /priv/"
+        )));
+    }
+    Ok(path)
+}
+
+fn append_entry(archive: &mut tar::Builder<Vec<u8>>, name: &str, content: &[u8]) -> Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(content.len() as u64);
+    header.set_mode(0o644);
+    archive
+        .append_data(&mut header, name, content)
+        .map_err(|e| write_error(&e))
+}
+
+/// Move what `archive` buffered so far to `writer`.
+async fn write_drained<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    archive: &mut tar::Builder<Vec<u8>>,
+) -> Result<()> {
+    let buffered = std::mem::take(archive.get_mut());
+    writer
+        .write_all(&buffered)
+        .await
+        .map_err(|e| write_error(&e))
+}
+
+/// Stream the content of `entry` from `response` to `writer`, padded to a whole tar
+/// block, failing if it does not match the manifest.
+async fn write_content<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    response: PubkyResponse,
+    entry: &ManifestEntry,
+) -> Result<()> {
+    let changed = || -> Error {
+        RequestError::Validation {
+            message: format!("{} changed during the export", entry.path),
+        }
+        .into()
+    };
+    let mut hasher = Hasher::new();
+    let mut written = 0u64;
+    // Bytes written into the last, partial tar block.
+    let mut block_fill = 0;
+    let mut chunks = std::pin::pin!(response.stream());
+    while let Some(chunk) = chunks.next().await {
+        let chunk = chunk?;
+        written += chunk.len() as u64;
+        block_fill = (block_fill + chunk.len()) % BLOCK;
+        // Never write past the size in the header.
+        if written > entry.size {
+            return Err(changed());
+        }
+        hasher.update(&chunk);
+        writer
+            .write_all(&chunk)
+            .await
+            .map_err(|e| write_error(&e))?;
+    }
+    if written != entry.size || hasher.finalize() != entry.content_hash {
+        return Err(changed());
+    }
+    let padding = (BLOCK - block_fill) % BLOCK;
+    writer
+        .write_all(&[0; BLOCK][..padding])
+        .await
+        .map_err(|e| write_error(&e))
+}
+
+/// Name and size of the next file entry, or `None` at the end of the archive. The
+/// reader is left at the start of the entry content, see [`read_content`] and
+/// [`skip_content`].
+///
+/// Resolves GNU long-name entries and skips directories and other entry types.
+async fn read_header<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Option<(String, u64)>> {
+    let mut long_name = None;
+    loop {
+        let mut block = [0; BLOCK];
+        match reader.read_exact(&mut block).await {
+            Ok(_) => {}
+            // A cancelled export ends without the trailer.
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(read_error(&e)),
+        }
+        if block.iter().all(|byte| *byte == 0) {
+            return Ok(None);
+        }
+        let header = tar::Header::from_byte_slice(&block);
+        let size = header
+            .entry_size()
+            .map_err(|e| invalid_archive(format!("invalid entry size: {e}")))?;
+
+        match header.entry_type() {
+            tar::EntryType::GNULongName => {
+                if size > MAX_NAME_BYTES {
+                    return Err(invalid_archive("entry name too long"));
+                }
+                let data = read_content(reader, size).await?;
+                let name = data.split(|byte| *byte == 0).next().unwrap_or_default();
+                long_name = Some(String::from_utf8_lossy(name).into_owned());
+            }
+            tar::EntryType::Regular => {
+                let name = match long_name.take() {
+                    Some(name) => name,
+                    None => String::from_utf8_lossy(&header.path_bytes()).into_owned(),
+                };
+                return Ok(Some((name, size)));
+            }
+            _ => {
+                skip_content(reader, size).await?;
+                long_name = None;
+            }
+        }
+    }
+}
+
+/// Read the `size` bytes of content of the entry [`read_header`] stopped at, and the
+/// padding after them. Callers bound `size`.
+async fn read_content<R: AsyncRead + Unpin>(reader: &mut R, size: u64) -> Result<Vec<u8>> {
+    let mut data =
+        vec![0; usize::try_from(size).map_err(|_err| invalid_archive("entry too large"))?];
+    reader
+        .read_exact(&mut data)
+        .await
+        .map_err(|e| read_error(&e))?;
+    let padding = (BLOCK - data.len() % BLOCK) % BLOCK;
+    reader
+        .read_exact(&mut [0; BLOCK][..padding])
+        .await
+        .map_err(|e| read_error(&e))?;
+    Ok(data)
+}
+
+/// Skip the `size` bytes of content of the entry [`read_header`] stopped at, and the
+/// padding after them, without holding them in memory.
+async fn skip_content<R: AsyncRead + Unpin>(reader: &mut R, size: u64) -> Result<()> {
+    let padded = size.div_ceil(BLOCK as u64) * BLOCK as u64;
+    let skipped = tokio::io::copy(&mut reader.take(padded), &mut tokio::io::sink())
+        .await
+        .map_err(|e| read_error(&e))?;
+    if skipped != padded {
+        return Err(invalid_archive("the archive ends within an entry"));
+    }
+    Ok(())
+}
+
+fn invalid_archive(message: impl std::fmt::Display) -> Error {
+    RequestError::Validation {
+        message: format!("invalid account archive: {message}"),
+    }
+    .into()
+}
+
+fn write_error(e: &std::io::Error) -> Error {
+    RequestError::Validation {
+        message: format!("failed to write the account archive: {e}"),
+    }
+    .into()
+}
+
+fn read_error(e: &std::io::Error) -> Error {
+    RequestError::Validation {
+        message: format!("failed to read the account archive: {e}"),
+    }
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pubky_common::crypto::{Keypair, hash};
+
+    /// The next file entry of `reader` with its whole content.
+    async fn read_entry(reader: &mut &[u8]) -> Option<(String, Vec<u8>)> {
+        let (name, size) = read_header(reader).await.unwrap()?;
+        Some((name, read_content(reader, size).await.unwrap()))
+    }
+
+    fn manifest_entry(path: &str, content: &[u8]) -> ManifestEntry {
+        ManifestEntry {
+            path: ResourcePath::parse(path).unwrap(),
+            content_hash: hash(content),
+            size: content.len() as u64,
+            content_type: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn oversized_entries_are_not_read_into_memory() {
+        let mut header = tar::Header::new_gnu();
+        header.set_path("pub/app/huge.bin").unwrap();
+        header.set_size(u64::MAX / 2);
+        header.set_cksum();
+        let mut reader = &header.as_bytes()[..];
+
+        let (name, size) = read_header(&mut reader).await.unwrap().unwrap();
+        assert_eq!((name.as_str(), size), ("pub/app/huge.bin", u64::MAX / 2));
+        let err = skip_content(&mut reader, size).await.unwrap_err();
+        assert!(err.to_string().contains("ends within an entry"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn export_state_is_rebuilt_from_a_partial_archive() {
+        let manifest = AccountManifest {
+            user: Keypair::random().public_key(),
+            entries: vec![
+                manifest_entry("/pub/app/a.txt", b"hello"),
+                manifest_entry("/pub/app/b.txt", b"world"),
+            ],
+        };
+        let mut archive = tar::Builder::new(Vec::new());
+        append_entry(&mut archive, MANIFEST_NAME, &manifest.to_json().unwrap()).unwrap();
+        append_entry(&mut archive, "pub/app/a.txt", b"hello").unwrap();
+        // A cancelled export ends without the trailer.
+        let partial = std::mem::take(archive.get_mut());
+
+        let export = AccountExport::from_archive(partial.as_slice())
+            .await
+            .unwrap();
+        assert_eq!(
+            export,
+            AccountExport {
+                manifest: manifest.clone(),
+                exported: 1,
+                cancelled: true,
+            }
+        );
+
+        let mut archive = tar::Builder::new(Vec::new());
+        append_entry(&mut archive, MANIFEST_NAME, &manifest.to_json().unwrap()).unwrap();
+        append_entry(&mut archive, "pub/app/b.txt", b"world").unwrap();
+        let out_of_order = archive.into_inner().unwrap();
+        let err = AccountExport::from_archive(out_of_order.as_slice())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("manifest order"), "{err}");
+
+        // Cut within the file.
+        let truncated = &partial[..partial.len() - BLOCK / 2];
+        let err = AccountExport::from_archive(truncated).await.unwrap_err();
+        assert!(err.to_string().contains("ends within an entry"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn entries_roundtrip_through_the_tar_format() {
+        let long = format!("pub/app/{}.txt", "a".repeat(150));
+        let mut archive = tar::Builder::new(Vec::new());
+        append_entry(&mut archive, MANIFEST_NAME, b"{}").unwrap();
+        append_entry(&mut archive, &long, b"hello").unwrap();
+        archive.finish().unwrap();
+        let bytes = archive.into_inner().unwrap();
+
+        let mut reader = bytes.as_slice();
+        let (name, data) = read_entry(&mut reader).await.unwrap();
+        assert_eq!(
+            (name.as_str(), data.as_slice()),
+            (MANIFEST_NAME, &b"{}"[..])
+        );
+        let (name, data) = read_entry(&mut reader).await.unwrap();
+        assert_eq!((name, data.as_slice()), (long, &b"hello"[..]));
+        assert!(read_entry(&mut reader).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn streamed_content_is_checked_against_the_manifest() {
+        let response = |body: &'static [u8]| {
+            PubkyResponse::from(reqwest::Response::from(http::Response::new(body)))
+        };
+        let entry = ManifestEntry {
+            path: ResourcePath::parse("/pub/app/a.txt").unwrap(),
+            content_hash: hash(b"hello"),
+            size: 5,
+            content_type: None,
+        };
+
+        let mut archive = tar::Builder::new(Vec::new());
+        let mut header = tar::Header::new_gnu();
+        header.set_size(entry.size);
+        archive
+            .append_data(&mut header, "pub/app/a.txt", std::io::empty())
+            .unwrap();
+        let mut bytes = std::mem::take(archive.get_mut());
+        write_content(&mut bytes, response(b"hello"), &entry)
+            .await
+            .unwrap();
+        let (name, data) = read_entry(&mut bytes.as_slice()).await.unwrap();
+        assert_eq!(
+            (name.as_str(), data.as_slice()),
+            ("pub/app/a.txt", &b"hello"[..])
+        );
+
+        for changed in [&b"hellO"[..], b"hell", b"hello!"] {
+            let err = write_content(&mut Vec::new(), response(changed), &entry)
+                .await
+                .unwrap_err();
+            assert!(
+                err.to_string().contains("changed during the export"),
+                "{err}"
+            );
+        }
+    }
+
+    #[test]
+    fn manifest_roundtrips_and_rejects_foreign_paths() {
+        let manifest = AccountManifest {
+            user: Keypair::random().public_key(),
+            entries: vec![ManifestEntry {
+                path: ResourcePath::parse("/pub/app/My File.txt").unwrap(),
+                content_hash: hash(b"hello"),
+                size: 5,
+                content_type: Some("text/plain".into()),
+            }],
+        };
+        let json = manifest.to_json().unwrap();
+        assert_eq!(AccountManifest::from_json(&json).unwrap(), manifest);
+
+        assert_eq!(
+            archived_path("pub/app/a.txt").unwrap().as_str(),
+            "/pub/app/a.txt"
+        );
+        for name in ["session", "pub/app/", "pub/../priv/a", "other/a.txt"] {
+            assert!(archived_path(name).is_err(), "{name}");
+        }
+    }
+}
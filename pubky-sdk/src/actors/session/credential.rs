@@ -96,6 +96,15 @@ pub(crate) trait SessionCredential: Debug + Send + Sync {
         user: &PublicKey,
     ) -> Result<Option<SessionInfo>>;
 
+    /// Whether [`Self::info`] reports narrower capabilities than the homeserver
+    /// enforces for this credential, so reads must be checked locally as well.
+    ///
+    /// `false` for credentials minted by the homeserver; `true` for the views
+    /// returned by [`crate::PubkySession::scoped`].
+    fn is_scoped(&self) -> bool {
+        false
+    }
+
     /// Type-erased accessor for downcasting to a concrete credential. Each
     /// impl returns `self`; callers use [`Any::downcast_ref`] to recover the
     /// concrete type. This keeps the trait ignorant of concrete credential
@@ -1,6 +1,13 @@
 //! Minimal, auth-agnostic session metadata.
 
-use pubky_common::{capabilities::Capability, crypto::PublicKey};
+use percent_encoding::percent_decode_str;
+use pubky_common::{
+    capabilities::{Action, Capability},
+    crypto::PublicKey,
+    storage::{PRIVATE_ROOT, PUBLIC_ROOT},
+};
+
+use crate::actors::storage::resource::{IntoResourcePath, ResourcePath};
 
 /// Minimal, auth-agnostic session metadata.
 ///
@@ -40,4 +47,98 @@ impl SessionInfo {
     pub fn capabilities(&self) -> &[Capability] {
         &self.capabilities
     }
+
+    /// Whether the homeserver will let this session read `path`.
+    ///
+    /// Mirrors the homeserver's read authorization:
+    /// - `/pub/` is world-readable, so this is always `true`.
+    /// - `/priv/` requires a capability whose scope covers `path` with read access.
+    ///   A session holding **only write access** to a private path can write it but
+    ///   not read it back (the homeserver answers `403`), so this returns `false`.
+    /// - Anything else (or an invalid path) is never readable.
+    #[must_use]
+    pub fn can_read<P: IntoResourcePath>(&self, path: P) -> bool {
+        let Ok(path) = path.into_abs_path() else {
+            return false;
+        };
+        if path.as_str().starts_with(PUBLIC_ROOT) {
+            return true;
+        }
+        path.as_str().starts_with(PRIVATE_ROOT) && self.has_action(&path, Action::Read)
+    }
+
+    /// Whether the homeserver will let this session write `path`.
+    ///
+    /// Mirrors the homeserver's write authorization: `path` must be under `/pub/` or
+    /// `/priv/` and covered by a capability with write access. Write access does not
+    /// imply read access, see [`can_read`](Self::can_read).
+    #[must_use]
+    pub fn can_write<P: IntoResourcePath>(&self, path: P) -> bool {
+        let Ok(path) = path.into_abs_path() else {
+            return false;
+        };
+        let in_root = [PUBLIC_ROOT, PRIVATE_ROOT]
+            .iter()
+            .any(|root| path.as_str().starts_with(root));
+        in_root && self.has_action(&path, Action::Write)
+    }
+
+    fn has_action(&self, path: &ResourcePath, action: Action) -> bool {
+        // Capability scopes are matched against the decoded path, as on the homeserver.
+        let path = percent_decode_str(path.as_str()).decode_utf8_lossy();
+        self.capabilities
+            .iter()
+            .any(|cap| cap.allows(&path, action))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Keypair;
+
+    fn info(capabilities: Vec<Capability>) -> SessionInfo {
+        SessionInfo::new(Keypair::random().public_key(), capabilities)
+    }
+
+    #[test]
+    fn pub_is_always_readable() {
+        let session = info(vec![]);
+        assert!(session.can_read("/pub/app/file"));
+        assert!(!session.can_write("/pub/app/file"));
+    }
+
+    #[test]
+    fn priv_read_requires_read_capability() {
+        let session = info(vec![Capability::read("/priv/app/")]);
+        assert!(session.can_read("/priv/app/file"));
+        assert!(session.can_read("/priv/app/"));
+        assert!(!session.can_read("/priv/other/file"));
+        assert!(!session.can_write("/priv/app/file"));
+    }
+
+    #[test]
+    fn write_only_session_cannot_read_private_path() {
+        let session = info(vec![Capability::write("/priv/app/")]);
+        assert!(session.can_write("/priv/app/draft"));
+        assert!(!session.can_read("/priv/app/draft"));
+    }
+
+    #[test]
+    fn root_capability_reads_and_writes_both_roots() {
+        let session = info(vec![Capability::root()]);
+        for path in ["/pub/app/file", "/priv/app/file"] {
+            assert!(session.can_read(path));
+            assert!(session.can_write(path));
+        }
+        assert!(!session.can_read("/other/file"));
+        assert!(!session.can_write("/other/file"));
+    }
+
+    #[test]
+    fn scopes_match_decoded_paths() {
+        let session = info(vec![Capability::write("/pub/my app/")]);
+        assert!(session.can_write("/pub/my app/file"));
+        assert!(!session.can_write("/pub/other app/file"));
+    }
 }
@@ -1,5 +1,8 @@
+#[cfg(all(feature = "archive", not(target_arch = "wasm32")))]
+pub(crate) mod archive;
 pub(crate) mod core;
 pub(crate) mod credential;
 mod info;
+mod scoped;
 
 pub use info::SessionInfo;
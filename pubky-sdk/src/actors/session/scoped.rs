@@ -0,0 +1,122 @@
+//! Capability-scoped views of a session, see [`PubkySession::scoped`].
+
+use std::any::Any;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use pubky_common::capabilities::{Capabilities, Capability};
+use pubky_common::crypto::PublicKey;
+use reqwest::RequestBuilder;
+
+use super::SessionInfo;
+use super::credential::SessionCredential;
+use crate::errors::{AuthError, Result};
+use crate::{PubkyHttpClient, PubkySession, cross_log};
+
+/// [`SessionCredential`] adapter that authenticates with the wrapped credential but
+/// reports only a subset of its capabilities.
+///
+/// The homeserver still authorizes requests against the full credential; the narrower
+/// scope is enforced by [`crate::SessionStorage`] before anything is sent.
+#[derive(Debug)]
+struct ScopedCredential {
+    inner: Arc<dyn SessionCredential>,
+    capabilities: Vec<Capability>,
+}
+
+impl ScopedCredential {
+    fn scope(&self, info: &SessionInfo) -> SessionInfo {
+        SessionInfo::new(info.public_key().clone(), self.capabilities.clone())
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl SessionCredential for ScopedCredential {
+    fn info(&self) -> SessionInfo {
+        self.scope(&self.inner.info())
+    }
+
+    async fn signout(&self, client: &PubkyHttpClient) -> Result<()> {
+        self.inner.signout(client).await
+    }
+
+    async fn attach(&self, rb: RequestBuilder, client: &PubkyHttpClient) -> Result<RequestBuilder> {
+        self.inner.attach(rb, client).await
+    }
+
+    async fn can_attach_to(&self, homeserver: &PublicKey) -> bool {
+        self.inner.can_attach_to(homeserver).await
+    }
+
+    async fn revalidate(
+        &self,
+        client: &PubkyHttpClient,
+        user: &PublicKey,
+    ) -> Result<Option<SessionInfo>> {
+        let info = self.inner.revalidate(client, user).await?;
+        Ok(info.map(|info| self.scope(&info)))
+    }
+
+    fn is_scoped(&self) -> bool {
+        true
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl PubkySession {
+    /// Derive a view of this session restricted to `capabilities`, e.g. to hand to a
+    /// plugin or sub-agent that should only touch its own directory.
+    ///
+    /// The view shares this session's credential, so no new session is created on the
+    /// homeserver and signing either out ends both. Its [`SessionInfo`] reports only
+    /// `capabilities`, and its [`storage`](Self::storage) checks every read and write
+    /// against them before sending, failing with
+    /// [`RequestError::Forbidden`](crate::errors::RequestError::Forbidden). The
+    /// narrowing is client-side only: the homeserver still accepts anything the full
+    /// credential allows, so it confines cooperating code rather than untrusted code
+    /// holding the secret. The credential views [`Self::as_grant`] and
+    /// [`Self::as_cookie`] are not available on a scoped session.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async fn ex(session: pubky::PubkySession) -> pubky::Result<()> {
+    /// use pubky::Capabilities;
+    ///
+    /// let plugin = session.scoped(Capabilities::builder().read_write("/pub/my-plugin/").finish())?;
+    /// plugin.storage().put("/pub/my-plugin/state.json", "{}").await?;
+    /// assert!(plugin.storage().put("/pub/other-app/file", "").await.is_err());
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// - [`AuthError::Validation`] if `capabilities` grants anything this session does
+    ///   not, see [`Capabilities::is_subset_of`].
+    pub fn scoped(&self, capabilities: impl Into<Capabilities>) -> Result<Self> {
+        let capabilities = capabilities.into().normalize();
+        let granted = Capabilities::from(self.info().capabilities().to_vec());
+        if !capabilities.is_subset_of(&granted) {
+            return Err(AuthError::Validation(format!(
+                "Capabilities {capabilities} are not a subset of the session's {granted}"
+            ))
+            .into());
+        }
+        cross_log!(
+            debug,
+            "Scoping session of {} to {}",
+            self.info().public_key(),
+            capabilities
+        );
+        let credential = ScopedCredential {
+            inner: Arc::clone(self.credential()),
+            capabilities: capabilities.iter().cloned().collect(),
+        };
+        Ok(Self::from_credential(
+            self.client.clone(),
+            Arc::new(credential),
+        ))
+    }
+}
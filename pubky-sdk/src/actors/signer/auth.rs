@@ -38,6 +38,9 @@ impl PubkySigner {
     ///   [`DeepLink::SigninGrant`], or [`DeepLink::SignupGrant`].
     /// - Channel is derived as `<relay>/<base64url(hash(secret))>`.
     ///
+    /// All requested capabilities are granted, optional ones included. Use
+    /// [`Self::approve_auth_with_optional`] to let the user decline optional ones.
+    ///
     /// Use [`Self::handle_deeplink`] for direct signup links.
     ///
     /// # Errors
@@ -47,10 +50,44 @@ impl PubkySigner {
     /// - Propagates transport failures when posting to the relay or if the
     ///   relay responds with a non-success status.
     pub async fn approve_auth(&self, pubkyauth_url: impl AsRef<str>) -> Result<()> {
-        self.approve_auth_deeplink(Self::parse_deeplink(pubkyauth_url)?)
+        self.approve_auth_deeplink(Self::parse_deeplink(pubkyauth_url)?, None)
             .await
     }
 
+    /// Like [`Self::approve_auth`], but grants only the optional capabilities in
+    /// `accepted_optional`, next to all required ones.
+    ///
+    /// Read the link's required and optional capabilities with
+    /// [`DeepLink::capability_request`] to build the consent screen. Entries of
+    /// `accepted_optional` the app did not request are ignored.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async fn ex(signer: pubky::PubkySigner, url: &str) -> pubky::Result<()> {
+    /// use pubky::{Capabilities, deep_links::DeepLink};
+    ///
+    /// let deep_link: DeepLink = url.parse().expect("valid pubkyauth URL");
+    /// let request = deep_link.capability_request().unwrap_or_default();
+    /// // ... let the user pick among `request.optional` ...
+    /// let accepted = Capabilities::default();
+    /// signer.approve_auth_with_optional(url, &accepted).await?;
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// - See [`Self::approve_auth`].
+    pub async fn approve_auth_with_optional(
+        &self,
+        pubkyauth_url: impl AsRef<str>,
+        accepted_optional: &Capabilities,
+    ) -> Result<()> {
+        self.approve_auth_deeplink(
+            Self::parse_deeplink(pubkyauth_url)?,
+            Some(accepted_optional),
+        )
+        .await
+    }
+
     /// Executes the action represented by a `pubkyauth://` deep link.
     ///
     /// Authentication links are approved through the relay, while a
@@ -72,7 +109,7 @@ impl PubkySigner {
                 "handle_deeplink does not handle seed_export deep links".into(),
             )
             .into()),
-            deep_link => self.approve_auth_deeplink(deep_link).await,
+            deep_link => self.approve_auth_deeplink(deep_link, None).await,
         }
     }
 
@@ -85,7 +122,15 @@ impl PubkySigner {
             })
     }
 
-    async fn approve_auth_deeplink(&self, deep_link: DeepLink) -> Result<()> {
+    async fn approve_auth_deeplink(
+        &self,
+        deep_link: DeepLink,
+        accepted_optional: Option<&Capabilities>,
+    ) -> Result<()> {
+        let granted = deep_link
+            .capability_request()
+            .map(|request| request.grant(accepted_optional.unwrap_or(&request.optional)))
+            .unwrap_or_default();
         let (relay, client_secret, encrypted_payload) =
             match &deep_link {
                 DeepLink::Signin(d) => {
@@ -94,10 +139,9 @@ impl PubkySigner {
                         info,
                         "Approving legacy signin via relay {} (caps={:?})",
                         params.relay,
-                        params.capabilities
+                        granted
                     );
-                    let payload =
-                        self.build_encrypted_token(params.capabilities.clone(), &params.secret);
+                    let payload = self.build_encrypted_token(granted, &params.secret);
                     (params.relay.clone(), params.secret, payload)
                 }
                 DeepLink::Signup(d) => {
@@ -106,10 +150,9 @@ impl PubkySigner {
                         info,
                         "Approving legacy signup via relay {} (caps={:?})",
                         params.relay,
-                        params.capabilities
+                        granted
                     );
-                    let payload =
-                        self.build_encrypted_token(params.capabilities.clone(), &params.secret);
+                    let payload = self.build_encrypted_token(granted, &params.secret);
                     (params.relay.clone(), params.secret, payload)
                 }
                 DeepLink::DirectSignup(_) => return Err(AuthError::Validation(
@@ -124,10 +167,10 @@ impl PubkySigner {
                         "Approving grant signin via relay {} (client_id={}, caps={:?})",
                         params.relay,
                         params.client_id,
-                        params.capabilities
+                        granted
                     );
                     let payload = self.build_encrypted_grant(
-                        &params.capabilities,
+                        &granted,
                         params.client_id.clone(),
                         params.client_pk.clone(),
                         &params.secret,
@@ -141,10 +184,10 @@ impl PubkySigner {
                         "Approving grant signup via relay {} (client_id={}, caps={:?})",
                         params.relay,
                         params.client_id,
-                        params.capabilities
+                        granted
                     );
                     let payload = self.build_encrypted_grant(
-                        &params.capabilities,
+                        &granted,
                         params.client_id.clone(),
                         params.client_pk.clone(),
                         &params.secret,
@@ -166,13 +209,12 @@ impl PubkySigner {
             callback_url
         );
 
-        let response = self
+        let rb = self
             .client
             .cross_request(Method::POST, callback_url)
             .await?
-            .body(encrypted_payload)
-            .send()
-            .await?;
+            .body(encrypted_payload);
+        let response = self.client.send(rb).await?;
 
         check_http_status(response).await?;
         cross_log!(info, "Auth payload delivered successfully");
@@ -186,9 +228,7 @@ impl PubkySigner {
         client_pk: PublicKey,
         client_secret: &[u8; 32],
     ) -> Vec<u8> {
-        let now = web_time::SystemTime::now()
-            .duration_since(web_time::UNIX_EPOCH)
-            .map_or(0, |d| d.as_secs());
+        let now = self.client.time_source().now_secs();
         let claims = GrantClaims {
             iss: self.keypair.public_key(),
             client_id,
@@ -207,7 +247,8 @@ impl PubkySigner {
         capabilities: Capabilities,
         client_secret: &[u8; 32],
     ) -> Vec<u8> {
-        let token = AuthToken::sign(&self.keypair, capabilities);
+        let token =
+            AuthToken::sign_with_clock(&self.keypair, capabilities, self.client.time_source());
         encrypt(&token.serialize(), client_secret)
     }
 
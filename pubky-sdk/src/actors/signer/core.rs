@@ -1,6 +1,10 @@
+use pubky_common::crypto::{Signature, sign_message};
+
 use crate::{BuildError, Keypair, PubkyHttpClient, PublicKey};
 
 /// Key holder and signer.
+///
+/// The keypair's secret is zeroized on drop and never shows up in `Debug` output.
 #[derive(Debug, Clone)]
 pub struct PubkySigner {
     pub(crate) client: PubkyHttpClient,
@@ -42,4 +46,46 @@ impl PubkySigner {
     pub const fn keypair(&self) -> &Keypair {
         &self.keypair
     }
+
+    /// Sign an application message, e.g. to prove control of this key to a third party.
+    ///
+    /// `domain_separator` names the protocol the proof is for (such as
+    /// `"example.com/link-account"`) and is mixed into the signed bytes, so the
+    /// signature can't be reused by another application, nor be mistaken for an auth
+    /// token, grant or Pkarr record. Verify it with [`crate::verify_message`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use pubky::{Keypair, PubkySigner, verify_message};
+    /// let signer = PubkySigner::new(Keypair::random())?;
+    /// let proof = signer.sign_message("example.com/link-account", b"account 42");
+    ///
+    /// assert!(verify_message(
+    ///     &signer.public_key(),
+    ///     "example.com/link-account",
+    ///     b"account 42",
+    ///     &proof,
+    /// ));
+    /// # Ok::<_, pubky::BuildError>(())
+    /// ```
+    #[must_use]
+    pub fn sign_message(&self, domain_separator: &str, message: &[u8]) -> Signature {
+        sign_message(&self.keypair, domain_separator, message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_does_not_leak_the_secret() {
+        let signer = PubkySigner::new(Keypair::from_secret(&[42; 32])).unwrap();
+        let secret = signer.keypair().secret();
+        let debug = format!("{signer:?} {signer:#?}");
+
+        assert!(!debug.contains(&"2a".repeat(32)));
+        assert!(!debug.contains(&format!("{secret:?}")));
+        assert!(debug.contains(&signer.public_key().z32()));
+    }
 }
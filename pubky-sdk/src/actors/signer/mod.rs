@@ -2,6 +2,9 @@
 
 pub mod auth;
 pub mod core;
+pub mod selector;
 pub mod session;
 
 pub use core::PubkySigner;
+pub use selector::{FixedSelector, HomeserverSelector, LatencySelector};
+pub use session::SignupOptions;
@@ -0,0 +1,202 @@
+//! Homeserver selection strategies for [`PubkySigner::signup_with_selector`].
+//!
+//! A [`HomeserverSelector`] decides which homeserver a new account is created on.
+//! Two strategies ship with the SDK:
+//!
+//! - [`FixedSelector`] always returns the same homeserver.
+//! - [`LatencySelector`] probes a list of candidates concurrently and picks the
+//!   fastest one that answers.
+//!
+//! Apps with their own policy (allowlists, quotas, region pinning) implement the
+//! trait themselves.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures_util::future::join_all;
+use reqwest::Method;
+use url::Url;
+use web_time::Instant;
+
+use super::PubkySigner;
+use crate::{
+    PubkyHttpClient, PublicKey, Result, cross_log,
+    errors::{Error, RequestError},
+    util::check_http_status,
+};
+
+/// Default per-candidate probe timeout of [`LatencySelector`].
+const DEFAULT_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Picks the homeserver an account is created on.
+///
+/// # Examples
+/// ```no_run
+/// # use pubky::{HomeserverSelector, PubkyHttpClient, PublicKey};
+/// struct FirstOf(Vec<PublicKey>);
+///
+/// #[async_trait::async_trait]
+/// impl HomeserverSelector for FirstOf {
+///     async fn select(&self, _client: &PubkyHttpClient) -> pubky::Result<PublicKey> {
+///         Ok(self.0[0].clone())
+///     }
+/// }
+/// ```
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+pub trait HomeserverSelector: Send + Sync {
+    /// Choose a homeserver, using `client` for any probing.
+    ///
+    /// # Errors
+    /// - Returns an error if no acceptable homeserver could be chosen.
+    async fn select(&self, client: &PubkyHttpClient) -> Result<PublicKey>;
+}
+
+/// Always selects the same homeserver, without probing it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixedSelector(pub PublicKey);
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl HomeserverSelector for FixedSelector {
+    async fn select(&self, _client: &PubkyHttpClient) -> Result<PublicKey> {
+        Ok(self.0.clone())
+    }
+}
+
+/// Selects the candidate homeserver that answers a health probe the fastest.
+///
+/// Every candidate is probed concurrently with a `GET /`. Candidates that fail to
+/// resolve, answer with a non-success status, or exceed the probe timeout are skipped.
+#[derive(Debug, Clone)]
+pub struct LatencySelector {
+    candidates: Vec<PublicKey>,
+    timeout: Duration,
+}
+
+impl LatencySelector {
+    /// Probe `candidates` with the default timeout of 5 seconds per probe.
+    #[must_use]
+    pub fn new(candidates: impl IntoIterator<Item = PublicKey>) -> Self {
+        Self {
+            candidates: candidates.into_iter().collect(),
+            timeout: DEFAULT_PROBE_TIMEOUT,
+        }
+    }
+
+    /// Set how long a single probe may take before the candidate is skipped.
+    #[must_use]
+    pub const fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// The homeservers this selector probes.
+    #[must_use]
+    pub fn candidates(&self) -> &[PublicKey] {
+        &self.candidates
+    }
+
+    async fn probe(&self, client: &PubkyHttpClient, homeserver: &PublicKey) -> Result<Duration> {
+        let url = Url::parse(&format!("https://{}/", homeserver.z32()))?;
+        let start = Instant::now();
+        let rb = client
+            .cross_request(Method::GET, url)
+            .await?
+            .timeout(self.timeout);
+        check_http_status(client.send(rb).await?).await?;
+        Ok(start.elapsed())
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl HomeserverSelector for LatencySelector {
+    async fn select(&self, client: &PubkyHttpClient) -> Result<PublicKey> {
+        let probes = self
+            .candidates
+            .iter()
+            .map(|homeserver| self.probe(client, homeserver));
+        let mut fastest: Option<(&PublicKey, Duration)> = None;
+        for (homeserver, probe) in self.candidates.iter().zip(join_all(probes).await) {
+            match probe {
+                Ok(latency) => {
+                    cross_log!(debug, "Homeserver {} answered in {:?}", homeserver, latency);
+                    if fastest.is_none_or(|(_, best)| latency < best) {
+                        fastest = Some((homeserver, latency));
+                    }
+                }
+                Err(e) => {
+                    cross_log!(debug, "Skipping homeserver {}: {}", homeserver, e);
+                }
+            }
+        }
+        fastest
+            .map(|(homeserver, _)| homeserver.clone())
+            .ok_or_else(|| {
+                Error::from(RequestError::Validation {
+                    message: format!(
+                        "none of the {} candidate homeservers is reachable",
+                        self.candidates.len()
+                    ),
+                })
+            })
+    }
+}
+
+impl PubkySigner {
+    /// Create an account on the homeserver chosen by `selector`.
+    ///
+    /// Same as [`Self::signup`], with the homeserver picked at call time.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use pubky::{LatencySelector, PubkySigner, PublicKey};
+    /// # async fn ex(signer: PubkySigner, a: PublicKey, b: PublicKey) -> pubky::Result<()> {
+    /// let homeserver = signer
+    ///     .signup_with_selector(&LatencySelector::new([a, b]), None)
+    ///     .await?;
+    /// println!("signed up on {homeserver}");
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// - Propagates errors of [`HomeserverSelector::select`].
+    /// - Propagates every error of [`Self::signup`].
+    pub async fn signup_with_selector<S: HomeserverSelector + ?Sized>(
+        &self,
+        selector: &S,
+        signup_token: Option<&str>,
+    ) -> Result<PublicKey> {
+        let homeserver = selector.select(&self.client).await?;
+        self.signup(&homeserver, signup_token).await?;
+        Ok(homeserver)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Keypair;
+
+    #[tokio::test]
+    async fn fixed_selector_returns_its_homeserver() {
+        let homeserver = Keypair::random().public_key();
+        let client = PubkyHttpClient::new().unwrap();
+        let selected = FixedSelector(homeserver.clone())
+            .select(&client)
+            .await
+            .unwrap();
+        assert_eq!(selected, homeserver);
+    }
+
+    #[tokio::test]
+    async fn latency_selector_without_candidates_errors() {
+        let client = PubkyHttpClient::new().unwrap();
+        let err = LatencySelector::new([]).select(&client).await.unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Request(RequestError::Validation { .. })
+        ));
+    }
+}
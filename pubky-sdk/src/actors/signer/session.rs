@@ -1,4 +1,7 @@
 use std::sync::Arc;
+use std::time::Duration;
+
+use futures_util::future::join_all;
 
 use pubky_common::auth::{
     AuthToken,
@@ -11,20 +14,41 @@ use url::Url;
 
 use super::PubkySigner;
 use crate::{
-    Capabilities, Capability, PubkySession, PublicKey, Result,
+    Capabilities, Capability, PubkySession, PublicKey, PublishResult, Result,
     actors::auth::{
         cookie::CookieCredential,
-        grant::constants::DEFAULT_GRANT_LIFETIME_SECS,
+        grant::constants::{
+            DEFAULT_GRANT_LIFETIME_SECS, MAX_GRANT_LIFETIME_SECS, MIN_GRANT_LIFETIME_SECS,
+        },
         grant::grant_exchange::{credential_from_grant_exchange, signup_account_from_grant},
         grant::pop_signer::GrantPopSigner,
     },
     cross_log,
-    util::check_http_status,
+    util::check_auth_attempt_status,
 };
 
 const SIGNUP_CLIENT_ID: &str = "pubky.signup";
 const SIGNUP_GRANT_LIFETIME_SECS: u64 = 5 * 60;
 
+/// Options for [`PubkySigner::signup_into_session`].
+#[derive(Debug, Clone, Default)]
+pub struct SignupOptions {
+    /// Token for homeservers that gate signups.
+    pub signup_token: Option<String>,
+    /// How long the returned session stays usable. Defaults to 2 years.
+    ///
+    /// Homeservers only accept lifetimes between [`MIN_GRANT_LIFETIME_SECS`] (1 minute)
+    /// and [`MAX_GRANT_LIFETIME_SECS`] (2 years). Sub-second precision is ignored.
+    pub session_ttl: Option<Duration>,
+    /// Capabilities of the returned session. Defaults to root.
+    pub capabilities: Option<Capabilities>,
+    /// Create the account without first checking that the homeserver is reachable and
+    /// runs a supported version, see [`crate::Pubky::check_homeserver`].
+    ///
+    /// For callers that already checked, or target homeservers that hide their version.
+    pub skip_homeserver_check: bool,
+}
+
 #[derive(Debug, Clone, Copy)]
 enum PublishMode {
     Background,
@@ -41,29 +65,174 @@ impl PubkySigner {
     /// - Uses a short-lived root grant + `PoP` proof (sufficient for signup).
     ///
     /// # Errors
+    /// - Returns [`crate::errors::RequestError::TooManyAttempts`] if the homeserver locked this
+    ///   client out after repeated failed attempts.
     /// - Returns [`crate::errors::Error::Parse`] if the homeserver URL cannot be constructed.
     /// - Propagates transport failures while creating the account or publishing the homeserver record.
     /// - Propagates validation errors from the session hydration step.
     pub async fn signup(&self, homeserver: &PublicKey, signup_token: Option<&str>) -> Result<()> {
-        cross_log!(info, "Signing up new account on homeserver {}", homeserver);
+        self.create_account(homeserver, signup_token).await?;
+        self.publish_signup_homeserver(homeserver).await?;
+        Ok(())
+    }
 
-        let client_keypair = Keypair::random();
-        let (grant_jws, grant_claims) = self.signup_grant(&client_keypair)?;
-        let client_signer = GrantPopSigner::local(client_keypair);
-        signup_account_from_grant(
-            &self.client,
-            &grant_jws,
-            &grant_claims,
-            &client_signer,
-            homeserver,
-            signup_token,
-        )
-        .await?;
+    /// Create an account on a homeserver and return a session for it.
+    ///
+    /// Same as [`Self::signup`] followed by a sign in, except that the session is bound to
+    /// `homeserver` directly and its lifetime and capabilities can be chosen through
+    /// [`SignupOptions`].
+    ///
+    /// Before anything is written, `homeserver` is checked to be a reachable homeserver
+    /// of a supported version, so a wrong key fails with one clear error instead of a
+    /// failed account creation. Set [`SignupOptions::skip_homeserver_check`] to skip it.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use pubky::{ClientId, PubkySigner, PublicKey, SignupOptions};
+    /// # async fn ex(signer: PubkySigner, homeserver: PublicKey) -> pubky::Result<()> {
+    /// let options = SignupOptions {
+    ///     session_ttl: Some(Duration::from_secs(15 * 60)),
+    ///     ..SignupOptions::default()
+    /// };
+    /// let client_id = ClientId::new("my-cool-app.example").expect("valid client id");
+    /// let session = signer
+    ///     .signup_into_session(&homeserver, client_id, options)
+    ///     .await?;
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// - Returns [`crate::errors::AuthError::Validation`] if `session_ttl` is outside the
+    ///   accepted bounds.
+    /// - Returns [`crate::errors::Error::NotAHomeserver`] or
+    ///   [`crate::errors::Error::IncompatibleVersion`] if the homeserver check fails.
+    /// - Propagates every error of [`Self::signup`].
+    /// - Propagates transport failures and server errors during the session exchange.
+    pub async fn signup_into_session(
+        &self,
+        homeserver: &PublicKey,
+        client_id: ClientId,
+        options: SignupOptions,
+    ) -> Result<PubkySession> {
+        let (capabilities, lifetime_secs) = session_params(&options)?;
+        self.check_signup_homeserver(homeserver, &options).await?;
+        self.signup(homeserver, options.signup_token.as_deref())
+            .await?;
+        self.session_on(homeserver, client_id, &capabilities, lifetime_secs)
+            .await
+    }
 
-        self.publish_signup_homeserver(homeserver).await?;
+    /// Create an account on several homeservers concurrently and return a session for each.
+    ///
+    /// Every homeserver gets its own entry in the returned list, in input order, so a
+    /// failure on one homeserver does not affect the others. `options` apply to all of them.
+    ///
+    /// Side effects:
+    /// - Publishes the `_pubky` pkarr record listing every homeserver that succeeded, in
+    ///   input order (see [`crate::Pkdns::publish_homeservers`]). The first of them is
+    ///   the *primary*; the others are returned by [`crate::Pkdns::get_homeservers_of`].
+    ///
+    /// Every returned session is valid on its own homeserver, but [`PubkySession::storage`]
+    /// locates the user through PKDNS and therefore always talks to the primary.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use pubky::{ClientId, PubkySigner, PublicKey, SignupOptions};
+    /// # async fn ex(signer: PubkySigner, primary: PublicKey, backup: PublicKey) {
+    /// let client_id = ClientId::new("my-cool-app.example").expect("valid client id");
+    /// let results = signer
+    ///     .signup_multi(&[primary, backup], client_id, SignupOptions::default())
+    ///     .await;
+    /// for (homeserver, result) in results {
+    ///     if let Err(e) = result {
+    ///         eprintln!("signup on {homeserver} failed: {e}");
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    ///
+    /// Each entry fails with the errors of [`Self::signup_into_session`]. If publishing the
+    /// `_pubky` record fails, the primary's entry carries that error instead of its session.
+    pub async fn signup_multi(
+        &self,
+        homeservers: &[PublicKey],
+        client_id: ClientId,
+        options: SignupOptions,
+    ) -> Vec<(PublicKey, Result<PubkySession>)> {
+        let signups = homeservers.iter().map(|homeserver| {
+            let client_id = client_id.clone();
+            let options = &options;
+            async move {
+                let (capabilities, lifetime_secs) = session_params(options)?;
+                self.check_signup_homeserver(homeserver, options).await?;
+                self.create_account(homeserver, options.signup_token.as_deref())
+                    .await?;
+                self.session_on(homeserver, client_id, &capabilities, lifetime_secs)
+                    .await
+            }
+        });
+        let mut results: Vec<_> = homeservers
+            .iter()
+            .cloned()
+            .zip(join_all(signups).await)
+            .collect();
+
+        let successful: Vec<PublicKey> = results
+            .iter()
+            .filter(|(_, result)| result.is_ok())
+            .map(|(homeserver, _)| homeserver.clone())
+            .collect();
+        if !successful.is_empty()
+            && let Err(e) = self.pkdns().publish_homeservers(&successful).await
+            && let Some((_, result)) = results.iter_mut().find(|(_, result)| result.is_ok())
+        {
+            *result = Err(e);
+        }
+        results
+    }
+
+    /// Check `homeserver` before creating an account on it, unless `options` opt out.
+    async fn check_signup_homeserver(
+        &self,
+        homeserver: &PublicKey,
+        options: &SignupOptions,
+    ) -> Result<()> {
+        if !options.skip_homeserver_check {
+            self.client.check_homeserver(homeserver).await?;
+        }
         Ok(())
     }
 
+    /// Make sure the `_pubky` record points to `homeserver`, republishing it only when
+    /// needed.
+    ///
+    /// Repairs an onboarding that stopped between creating the account and publishing
+    /// the record, after which nobody can find the user. The record is resolved first and
+    /// published again, with `homeserver` as the only host, if it is missing or names
+    /// another primary homeserver. A record that already points to `homeserver` is
+    /// republished as is once it is older than [`crate::DEFAULT_STALE_AFTER`]. Retryable
+    /// DHT errors are retried, like every publish.
+    ///
+    /// Idempotent and cheap when nothing is wrong, so apps can call it on every launch.
+    ///
+    /// Returns what was published, or `None` if the record was already up to date.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async fn ex(signer: pubky::PubkySigner, homeserver: pubky::PublicKey) -> pubky::Result<()> {
+    /// if let Some(published) = signer.ensure_published(&homeserver).await? {
+    ///     println!("repaired `_pubky` record on {} DHT nodes", published.dht_nodes);
+    /// }
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// - Returns [`crate::errors::Error::Pkarr`] if publishing fails after the retries.
+    pub async fn ensure_published(&self, homeserver: &PublicKey) -> Result<Option<PublishResult>> {
+        self.pkdns().ensure_homeserver(homeserver).await
+    }
+
     // All of these methods use root capabilities
 
     /// Sign in to the users homeserver by locally signing a root-capability token.
@@ -74,6 +243,8 @@ impl PubkySigner {
     /// Prefer this signin for best user experience, it returns fast.
     ///
     /// # Errors
+    /// - Returns [`crate::errors::RequestError::TooManyAttempts`] if the homeserver locked this
+    ///   client out after repeated failed attempts.
     /// - Propagates transport failures during the session exchange.
     /// - Propagates validation errors from the session exchange or PKDNS publishing.
     pub async fn signin(&self, client_id: ClientId) -> Result<PubkySession> {
@@ -88,6 +259,8 @@ impl PubkySigner {
     /// it returns slow (~3-5 seconds).
     ///
     /// # Errors
+    /// - Returns [`crate::errors::RequestError::TooManyAttempts`] if the homeserver locked this
+    ///   client out after repeated failed attempts.
     /// - Propagates transport failures during the session exchange.
     /// - Propagates validation errors from the session exchange or PKDNS publishing.
     pub async fn signin_blocking(&self, client_id: ClientId) -> Result<PubkySession> {
@@ -130,6 +303,8 @@ impl PubkySigner {
     /// Legacy cookie signup. Prefer [`Self::signup`] plus [`Self::signin`].
     ///
     /// # Errors
+    /// - Returns [`crate::errors::RequestError::TooManyAttempts`] if the homeserver locked this
+    ///   client out after repeated failed attempts.
     /// - Returns [`crate::errors::Error::Parse`] if the homeserver URL cannot be constructed.
     /// - Propagates transport failures while creating the account or publishing the homeserver record.
     /// - Propagates validation errors while hydrating the cookie session.
@@ -156,6 +331,8 @@ impl PubkySigner {
     /// Legacy cookie signin. Prefer [`Self::signin`].
     ///
     /// # Errors
+    /// - Returns [`crate::errors::RequestError::TooManyAttempts`] if the homeserver locked this
+    ///   client out after repeated failed attempts.
     /// - Propagates transport failures during the session exchange.
     /// - Propagates validation errors while creating the cookie credential.
     pub async fn signin_cookie(&self) -> Result<PubkySession> {
@@ -166,6 +343,8 @@ impl PubkySigner {
     /// Legacy cookie signin with blocking PKDNS refresh. Prefer [`Self::signin_blocking`].
     ///
     /// # Errors
+    /// - Returns [`crate::errors::RequestError::TooManyAttempts`] if the homeserver locked this
+    ///   client out after repeated failed attempts.
     /// - Propagates transport failures during the session exchange.
     /// - Propagates failures while refreshing the homeserver record.
     pub async fn signin_cookie_blocking(&self) -> Result<PubkySession> {
@@ -225,6 +404,53 @@ impl PubkySigner {
         Ok(())
     }
 
+    /// Create the account on `homeserver` without publishing the `_pubky` record.
+    async fn create_account(
+        &self,
+        homeserver: &PublicKey,
+        signup_token: Option<&str>,
+    ) -> Result<()> {
+        cross_log!(info, "Signing up new account on homeserver {}", homeserver);
+
+        let client_keypair = Keypair::random();
+        let (grant_jws, grant_claims) = self.signup_grant(&client_keypair)?;
+        let client_signer = GrantPopSigner::local(client_keypair);
+        signup_account_from_grant(
+            &self.client,
+            &grant_jws,
+            &grant_claims,
+            &client_signer,
+            homeserver,
+            signup_token,
+        )
+        .await
+    }
+
+    /// Exchange a freshly signed grant for a session bound to `homeserver`.
+    async fn session_on(
+        &self,
+        homeserver: &PublicKey,
+        client_id: ClientId,
+        capabilities: &Capabilities,
+        lifetime_secs: u64,
+    ) -> Result<PubkySession> {
+        let client_keypair = Keypair::random();
+        let claims = self.grant_claims(client_id, &client_keypair, capabilities, lifetime_secs);
+        let grant_jws = claims.sign(&self.keypair, GRANT_JWS_TYP);
+        let credential = credential_from_grant_exchange(
+            &self.client,
+            grant_jws,
+            claims,
+            GrantPopSigner::local(client_keypair),
+            homeserver.clone(),
+        )
+        .await?;
+        Ok(PubkySession::from_grant_credential(
+            self.client.clone(),
+            credential,
+        ))
+    }
+
     fn build_signup_url(homeserver: &PublicKey, signup_token: Option<&str>) -> Result<Url> {
         let mut url = Url::parse(&format!("https://{}", homeserver.z32()))?;
         url.set_path("/signup");
@@ -234,15 +460,27 @@ impl PubkySigner {
         Ok(url)
     }
 
+    fn root_capabilities() -> Capabilities {
+        Capabilities::builder().cap(Capability::root()).finish()
+    }
+
     fn root_capability_token(&self) -> AuthToken {
-        let capabilities = Capabilities::builder().cap(Capability::root()).finish();
-        AuthToken::sign(&self.keypair, capabilities)
+        AuthToken::sign_with_clock(
+            &self.keypair,
+            Self::root_capabilities(),
+            self.client.time_source(),
+        )
     }
 
     fn signup_grant(&self, client_keypair: &Keypair) -> Result<(String, GrantClaims)> {
         let client_id = ClientId::new(SIGNUP_CLIENT_ID)
             .map_err(|e| crate::errors::AuthError::Validation(e.to_string()))?;
-        let claims = self.grant_claims(client_id, client_keypair, SIGNUP_GRANT_LIFETIME_SECS);
+        let claims = self.grant_claims(
+            client_id,
+            client_keypair,
+            &Self::root_capabilities(),
+            SIGNUP_GRANT_LIFETIME_SECS,
+        );
         let jws = claims.sign(&self.keypair, GRANT_JWS_TYP);
         Ok((jws, claims))
     }
@@ -252,7 +490,12 @@ impl PubkySigner {
         client_id: ClientId,
         client_keypair: &Keypair,
     ) -> (String, GrantClaims) {
-        let claims = self.grant_claims(client_id, client_keypair, DEFAULT_GRANT_LIFETIME_SECS);
+        let claims = self.grant_claims(
+            client_id,
+            client_keypair,
+            &Self::root_capabilities(),
+            DEFAULT_GRANT_LIFETIME_SECS,
+        );
         let jws = claims.sign(&self.keypair, GRANT_JWS_TYP);
         (jws, claims)
     }
@@ -261,18 +504,14 @@ impl PubkySigner {
         &self,
         client_id: ClientId,
         client_keypair: &Keypair,
+        capabilities: &Capabilities,
         lifetime_secs: u64,
     ) -> GrantClaims {
-        let now = web_time::SystemTime::now()
-            .duration_since(web_time::UNIX_EPOCH)
-            .map_or(0, |d| d.as_secs());
+        let now = self.client.time_source().now_secs();
         GrantClaims {
             iss: self.keypair.public_key(),
             client_id,
-            caps: Capabilities::builder()
-                .cap(Capability::root())
-                .finish()
-                .to_vec(),
+            caps: capabilities.to_vec(),
             cnf: client_keypair.public_key(),
             jti: GrantId::generate(),
             iat: now,
@@ -281,16 +520,15 @@ impl PubkySigner {
     }
 
     async fn send_signup_request(&self, url: Url, body: Vec<u8>) -> Result<reqwest::Response> {
-        let response = self
+        let rb = self
             .client
             .cross_request(Method::POST, url)
             .await?
-            .body(body)
-            .send()
-            .await?;
+            .body(body);
+        let response = self.client.send(rb).await?;
 
         // Map non-2xx into our error type; keep body/headers intact for the caller.
-        check_http_status(response).await
+        check_auth_attempt_status(&self.client, response).await
     }
 
     async fn publish_signup_homeserver(&self, homeserver: &PublicKey) -> Result<()> {
@@ -312,3 +550,48 @@ impl PubkySigner {
         Ok(())
     }
 }
+
+/// Resolve the session capabilities and grant lifetime requested by `options`.
+fn session_params(options: &SignupOptions) -> Result<(Capabilities, u64)> {
+    let lifetime_secs = match options.session_ttl {
+        Some(ttl) => validate_session_ttl(ttl)?,
+        None => DEFAULT_GRANT_LIFETIME_SECS,
+    };
+    let capabilities = options
+        .capabilities
+        .clone()
+        .unwrap_or_else(PubkySigner::root_capabilities);
+    Ok((capabilities, lifetime_secs))
+}
+
+/// Check a requested session lifetime against the homeserver bounds, in whole seconds.
+fn validate_session_ttl(ttl: Duration) -> Result<u64> {
+    let secs = ttl.as_secs();
+    if !(MIN_GRANT_LIFETIME_SECS..=MAX_GRANT_LIFETIME_SECS).contains(&secs) {
+        return Err(crate::errors::AuthError::Validation(format!(
+            "session ttl must be between {MIN_GRANT_LIFETIME_SECS} and {MAX_GRANT_LIFETIME_SECS} seconds, got {secs}"
+        ))
+        .into());
+    }
+    Ok(secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_ttl_must_be_within_bounds() {
+        for secs in [MIN_GRANT_LIFETIME_SECS - 1, MAX_GRANT_LIFETIME_SECS + 1] {
+            let err = validate_session_ttl(Duration::from_secs(secs)).unwrap_err();
+            assert!(matches!(
+                err,
+                crate::Error::Authentication(crate::errors::AuthError::Validation(_))
+            ));
+        }
+        assert_eq!(
+            validate_session_ttl(Duration::from_millis(90_500)).unwrap(),
+            90
+        );
+    }
+}
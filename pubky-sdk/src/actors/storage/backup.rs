@@ -0,0 +1,95 @@
+//! Crash-safe replacement of a resource that keeps the previous version as a backup.
+
+use reqwest::{Method, Response, StatusCode};
+
+use super::core::SessionStorage;
+use super::resource::{IntoResourcePath, ResourcePath};
+use crate::{Result, errors::RequestError, util::check_http_status};
+
+impl SessionStorage {
+    /// Replace the resource at an **absolute path**, keeping the previous version at
+    /// `{path}{backup_suffix}` until the new one is in place.
+    ///
+    /// The current version is streamed from the homeserver to the backup path, then the
+    /// new body overwrites the resource. The resource is never missing, and the backup
+    /// is deleted once the new version is in place. In browsers, which can't stream
+    /// request bodies, the current version is buffered in memory instead.
+    ///
+    /// If the process dies midway, the previous version is still available, either as
+    /// the resource itself or at the backup path. A resource that does not exist yet
+    /// is simply written.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async fn ex(session: pubky::PubkySession) -> pubky::Result<()> {
+    /// session
+    ///     .storage()
+    ///     .put_with_backup("/pub/my-cool-app/config.json", r#"{"theme":"dark"}"#, ".bak")
+    ///     .await?;
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// - [`crate::errors::Error::Request`] with [`RequestError::Validation`] if `path` ends
+    ///   with `/` or `backup_suffix` is empty or contains `/`.
+    /// - [`crate::errors::Error::Request`] on HTTP transport failures or when any of the
+    ///   underlying requests fails with a non-success status. A backup written before the
+    ///   failure is left in place.
+    /// - [`crate::errors::Error::Parse`] if `path` cannot be converted into a valid
+    ///   resource/URL.
+    pub async fn put_with_backup<P, B>(
+        &self,
+        path: P,
+        body: B,
+        backup_suffix: &str,
+    ) -> Result<Response>
+    where
+        P: IntoResourcePath,
+        B: Into<reqwest::Body>,
+    {
+        let path = path.into_abs_path()?;
+        if path.as_str().ends_with('/') {
+            return Err(RequestError::Validation {
+                message: "cannot replace a directory; the path must not end with `/`".into(),
+            }
+            .into());
+        }
+        if backup_suffix.is_empty() || backup_suffix.contains('/') {
+            return Err(RequestError::Validation {
+                message: "backup suffix must be non-empty and must not contain `/`".into(),
+            }
+            .into());
+        }
+        let backup = ResourcePath::parse(format!("{path}{backup_suffix}"))?;
+
+        let rb = self.build_request(Method::GET, &path).await?;
+        let current = self.client.send(rb).await?;
+        let had_previous = !matches!(current.status(), StatusCode::NOT_FOUND | StatusCode::GONE);
+        if had_previous {
+            let previous = response_body(check_http_status(current).await?).await?;
+            self.put_response(&backup, previous).await?;
+        }
+
+        let response = self.put_response(&path, body).await?;
+        if had_previous {
+            self.delete(&backup).await?;
+        }
+        Ok(response)
+    }
+}
+
+/// The body of `response` as a request body, streamed where the platform allows it.
+#[cfg(not(target_arch = "wasm32"))]
+#[allow(
+    clippy::unused_async,
+    reason = "keep async signature aligned with the WASM build"
+)]
+async fn response_body(response: Response) -> Result<reqwest::Body> {
+    Ok(reqwest::Body::wrap_stream(response.bytes_stream()))
+}
+
+/// The body of `response` as a request body, streamed where the platform allows it.
+#[cfg(target_arch = "wasm32")]
+async fn response_body(response: Response) -> Result<reqwest::Body> {
+    Ok(response.bytes().await?.into())
+}
@@ -0,0 +1,232 @@
+//! In-memory `ETag` response cache for public reads, see
+//! [`PublicStorage::with_response_cache`].
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use bytes::Bytes;
+use reqwest::header::{ETAG, HeaderMap, HeaderValue, IF_NONE_MATCH};
+use reqwest::{Method, Response, ResponseBuilderExt, StatusCode};
+
+use super::core::PublicStorage;
+use super::resource::PubkyResource;
+use crate::{Result, cross_log, util::check_http_status};
+
+/// Default largest body [`PublicStorage::with_response_cache`] keeps, in bytes.
+pub const DEFAULT_MAX_CACHED_RESPONSE_BYTES: u64 = 256 * 1024;
+
+/// Counters of a response cache, see [`PublicStorage::response_cache_stats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResponseCacheStats {
+    /// `GET`s answered with `304 Not Modified` and served from the cache.
+    pub hits: u64,
+    /// `GET`s that downloaded the body.
+    pub misses: u64,
+    /// Responses currently cached.
+    pub entries: usize,
+    /// Total size of the cached bodies in bytes.
+    pub bytes: u64,
+}
+
+#[derive(Debug)]
+struct CachedResponse {
+    headers: HeaderMap,
+    body: Bytes,
+    last_used: u64,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    entries: HashMap<String, CachedResponse>,
+    /// Incremented on every access, so the smallest `last_used` is the LRU entry.
+    tick: u64,
+    hits: u64,
+    misses: u64,
+}
+
+/// LRU map of `pubky://` URL to the last `200 OK` headers and body, shared by all
+/// clones of a [`PublicStorage`].
+#[derive(Debug, Clone)]
+pub(crate) struct ResponseCache {
+    capacity: usize,
+    max_entry_bytes: u64,
+    state: Arc<Mutex<State>>,
+}
+
+impl ResponseCache {
+    pub(crate) fn new(capacity: usize, max_entry_bytes: u64) -> Self {
+        Self {
+            capacity,
+            max_entry_bytes,
+            state: Arc::default(),
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// `ETag` to revalidate `key` with, if it is cached.
+    fn etag(&self, key: &str) -> Option<HeaderValue> {
+        self.state().entries.get(key)?.headers.get(ETAG).cloned()
+    }
+
+    /// The cached response of `key` after a `304`, counted as a hit.
+    fn hit(&self, key: &str) -> Option<(HeaderMap, Bytes)> {
+        let mut state = self.state();
+        state.tick += 1;
+        let tick = state.tick;
+        let cached = state.entries.get_mut(key)?;
+        cached.last_used = tick;
+        let response = (cached.headers.clone(), cached.body.clone());
+        state.hits += 1;
+        Some(response)
+    }
+
+    /// Whether a body of `length` bytes is small enough to cache.
+    fn accepts(&self, length: Option<u64>) -> bool {
+        length.is_some_and(|length| length <= self.max_entry_bytes)
+    }
+
+    /// Count a downloaded body, caching it under `key` if it has an `ETag`.
+    fn store(&self, key: &str, headers: &HeaderMap, body: Option<&Bytes>) {
+        let mut state = self.state();
+        state.misses += 1;
+        let Some(body) = body.filter(|_| headers.contains_key(ETAG)) else {
+            state.entries.remove(key);
+            return;
+        };
+        if state.entries.len() >= self.capacity && !state.entries.contains_key(key) {
+            let oldest = state
+                .entries
+                .iter()
+                .min_by_key(|(_, cached)| cached.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                state.entries.remove(&oldest);
+            }
+        }
+        state.tick += 1;
+        let last_used = state.tick;
+        state.entries.insert(
+            key.to_string(),
+            CachedResponse {
+                headers: headers.clone(),
+                body: body.clone(),
+                last_used,
+            },
+        );
+    }
+
+    fn stats(&self) -> ResponseCacheStats {
+        let state = self.state();
+        ResponseCacheStats {
+            hits: state.hits,
+            misses: state.misses,
+            entries: state.entries.len(),
+            bytes: state
+                .entries
+                .values()
+                .map(|cached| cached.body.len() as u64)
+                .sum(),
+        }
+    }
+}
+
+/// A `200 OK` response carrying `headers` and `body`, as if received from `url`.
+fn rebuild(url: &url::Url, headers: HeaderMap, body: Bytes) -> Result<Response> {
+    let mut response = http::Response::builder()
+        .status(StatusCode::OK)
+        .url(url.clone())
+        .body(body)
+        .map_err(|e| crate::errors::RequestError::Validation {
+            message: format!("cannot rebuild cached response: {e}"),
+        })?;
+    *response.headers_mut() = headers;
+    Ok(response.into())
+}
+
+impl PublicStorage {
+    /// Cache the bodies of up to `capacity` small public resources in memory, and
+    /// revalidate them with `If-None-Match` instead of downloading them again.
+    ///
+    /// Every [`get`](Self::get) of a cached resource still asks the homeserver, but an
+    /// unchanged resource is answered with `304 Not Modified` and served from memory,
+    /// which saves the bandwidth of hot resources like configs and avatars. Only
+    /// responses with an `ETag` and a `Content-Length` of at most
+    /// [`DEFAULT_MAX_CACHED_RESPONSE_BYTES`] are kept; see
+    /// [`Self::with_response_cache_limits`] to change that. The least recently used
+    /// entry is evicted when the cache is full.
+    ///
+    /// The cache lives in this handle and is shared by its clones, so keep the handle
+    /// around rather than calling [`crate::Pubky::public_storage`] for every read.
+    /// Reads retried on other homeservers through
+    /// [`read_failover`](crate::PubkyHttpClientBuilder::read_failover) bypass the
+    /// cache. A `capacity` of `0` disables it.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async fn ex(pubky: pubky::Pubky) -> pubky::Result<()> {
+    /// let storage = pubky.public_storage().with_response_cache(100);
+    /// for _ in 0..2 {
+    ///     let config = storage.get("{other_pk}/pub/my-cool-app/config.json").await?;
+    ///     let _bytes = config.bytes().await?;
+    /// }
+    /// let stats = storage.response_cache_stats().unwrap();
+    /// println!("{} hits, {} misses", stats.hits, stats.misses);
+    /// # Ok(()) }
+    /// ```
+    #[must_use]
+    pub fn with_response_cache(self, capacity: usize) -> Self {
+        self.with_response_cache_limits(capacity, DEFAULT_MAX_CACHED_RESPONSE_BYTES)
+    }
+
+    /// Like [`Self::with_response_cache`], caching bodies of up to `max_entry_bytes`.
+    #[must_use]
+    pub fn with_response_cache_limits(mut self, capacity: usize, max_entry_bytes: u64) -> Self {
+        self.cache = (capacity > 0).then(|| ResponseCache::new(capacity, max_entry_bytes));
+        self
+    }
+
+    /// Hit and miss counters of the cache set up with [`Self::with_response_cache`],
+    /// or `None` without one.
+    #[must_use]
+    pub fn response_cache_stats(&self) -> Option<ResponseCacheStats> {
+        self.cache.as_ref().map(ResponseCache::stats)
+    }
+
+    /// `GET` of `resource` through `cache`.
+    pub(crate) async fn get_cached(
+        &self,
+        cache: &ResponseCache,
+        resource: &PubkyResource,
+    ) -> Result<Response> {
+        let key = resource.to_pubky_url();
+        let mut rb = self.request(Method::GET, resource).await?;
+        if let Some(etag) = cache.etag(&key) {
+            rb = rb.header(IF_NONE_MATCH, etag);
+        }
+        let mut resp = self.client.send(rb).await?;
+
+        if resp.status() == StatusCode::NOT_MODIFIED {
+            if let Some((headers, body)) = cache.hit(&key) {
+                cross_log!(debug, "Serving {key} from the response cache");
+                return rebuild(resp.url(), headers, body);
+            }
+            // Evicted by a concurrent read since the request was built.
+            let rb = self.request(Method::GET, resource).await?;
+            resp = self.client.send(rb).await?;
+        }
+
+        let resp = check_http_status(resp).await?;
+        if !cache.accepts(resp.content_length()) {
+            cache.store(&key, resp.headers(), None);
+            return Ok(resp);
+        }
+        let url = resp.url().clone();
+        let headers = resp.headers().clone();
+        let body = resp.bytes().await?;
+        cache.store(&key, &headers, Some(&body));
+        rebuild(&url, headers, body)
+    }
+}
@@ -0,0 +1,80 @@
+//! Cooperative cancellation for long-running bulk storage operations.
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+
+use tokio::sync::Notify;
+
+/// Signal asking a bulk storage operation to stop early.
+///
+/// Clones share the same signal, so a token can be handed to an operation while
+/// another task (e.g. an app lifecycle hook) keeps a clone to call
+/// [`cancel`](Self::cancel). Operations check the token between requests, so a
+/// request that is already in flight completes before they stop.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    inner: Arc<CancelState>,
+}
+
+#[derive(Debug, Default)]
+struct CancelState {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl CancelToken {
+    /// Create a token that is not cancelled.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel every operation holding this token or a clone of it.
+    ///
+    /// Cancelling is idempotent and cannot be undone.
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::Release);
+        self.inner.notify.notify_waiters();
+    }
+
+    /// Whether [`cancel`](Self::cancel) has been called.
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::Acquire)
+    }
+
+    /// Wait until the token is cancelled.
+    pub async fn cancelled(&self) {
+        loop {
+            // Register before checking the flag so a concurrent `cancel` is not missed.
+            let notified = self.inner.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn clones_share_the_signal() {
+        let token = CancelToken::new();
+        let waiter = token.clone();
+        assert!(!waiter.is_cancelled());
+
+        let waiting = tokio::spawn(async move { waiter.cancelled().await });
+        tokio::task::yield_now().await;
+        token.cancel();
+        waiting.await.unwrap();
+
+        assert!(token.is_cancelled());
+        // An already cancelled token resolves immediately.
+        token.cancelled().await;
+    }
+}
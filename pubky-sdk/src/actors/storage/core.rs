@@ -1,8 +1,11 @@
 use crate::PublicKey;
 use crate::actors::session::credential::SessionCredential;
+use pubky_common::capabilities::Action;
 use reqwest::{Method, RequestBuilder};
 use std::sync::Arc;
+use url::Url;
 
+use super::permission::required_action;
 use super::resource::{IntoPubkyResource, IntoResourcePath, PubkyResource, ResourcePath};
 use crate::{
     PubkyHttpClient, PubkySession, cross_log,
@@ -23,6 +26,8 @@ pub struct SessionStorage {
     /// the parent session is cheap and gives the storage layer access to the
     /// latest authentication material (with auto-refresh for grant credentials).
     pub(crate) credential: Arc<dyn SessionCredential>,
+    /// Homeserver addressed through `/dav/` paths, see [`Self::on_homeserver`].
+    pub(crate) homeserver: Option<PublicKey>,
 }
 
 impl SessionStorage {
@@ -35,35 +40,91 @@ impl SessionStorage {
             client: session.client.clone(),
             user: session.info().public_key().clone(),
             credential: Arc::clone(session.credential()),
+            homeserver: None,
         }
     }
 
+    /// Send requests of this handle to `homeserver`, e.g. a mirror of the user's data.
+    ///
+    /// By default requests go to the homeserver the user's PKDNS record points to.
+    /// With an explicit homeserver, they are addressed as
+    /// `https://<homeserver>/dav/<user>/<path>` instead, so the user is named in the
+    /// path rather than in the `pubky-host` header.
+    ///
+    /// The session credential is still attached, so `homeserver` must accept it.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async fn ex(session: pubky::PubkySession, mirror: pubky::PublicKey) -> pubky::Result<()> {
+    /// let profile = session
+    ///     .storage()
+    ///     .on_homeserver(mirror)
+    ///     .get("/pub/app/profile.json")
+    ///     .await?;
+    /// # Ok(()) }
+    /// ```
+    #[must_use]
+    pub fn on_homeserver(mut self, homeserver: PublicKey) -> Self {
+        self.homeserver = Some(homeserver);
+        self
+    }
+
     /// Convenience: unauthenticated public reader using the same client.
     #[must_use]
     pub fn public(&self) -> PublicStorage {
         PublicStorage {
             client: self.client.clone(),
+            #[cfg(not(target_arch = "wasm32"))]
+            cache: None,
         }
     }
 
+    /// Override the client's response body cap for requests made through this handle,
+    /// e.g. for a download expected to be large. `0` removes the cap.
+    ///
+    /// See [`PubkyHttpClientBuilder::max_response_bytes`](crate::PubkyHttpClientBuilder::max_response_bytes).
+    #[cfg(not(target_arch = "wasm32"))]
+    #[must_use]
+    pub fn with_max_response_bytes(mut self, bytes: u64) -> Self {
+        self.client = self.client.with_max_response_bytes(bytes);
+        self
+    }
+
     /// Build a request for this storage.
     ///
     /// - Paths are **absolute** (session-scoped).
+    /// - Writes the session's capabilities do not cover fail locally with
+    ///   [`RequestError::Forbidden`], see [`Self::check_permission`]. Reads are
+    ///   checked too on a [`PubkySession::scoped`] session.
     /// - The session credential attaches the right authentication header
     ///   (cookie or bearer token) and refreshes the grant credential proactively if needed.
-    pub(crate) async fn request<P: IntoResourcePath>(
+    pub(crate) async fn build_request<P: IntoResourcePath>(
         &self,
         method: Method,
         path: P,
     ) -> Result<RequestBuilder> {
         let path: ResourcePath = path.into_abs_path()?;
-        let resource = PubkyResource::new(self.user.clone(), path.as_str())?;
-        let url = resource.to_transport_url()?;
+        match required_action(&method) {
+            Some(Action::Write) => self.ensure_permitted(&method, &path)?,
+            Some(Action::Read) => self.ensure_scoped_read(&path)?,
+            _ => {}
+        }
+        let url = self.url_for(&path)?;
         cross_log!(debug, "Session storage {} request {}", method, url);
         let rb = self.client.cross_request(method, url).await?;
         self.attach_credential(rb).await
     }
 
+    /// URL of `path` for this storage: on the user's homeserver, or under `/dav/` on
+    /// the one chosen with [`Self::on_homeserver`].
+    pub(crate) fn url_for(&self, path: &ResourcePath) -> Result<Url> {
+        let resource = PubkyResource::new(self.user.clone(), path.as_str())?;
+        match &self.homeserver {
+            Some(homeserver) => resource.to_dav_url(homeserver),
+            None => resource.to_transport_url(),
+        }
+    }
+
     /// Attach the session credential to a request builder.
     pub(crate) async fn attach_credential(&self, rb: RequestBuilder) -> Result<RequestBuilder> {
         self.credential.attach(rb, &self.client).await
@@ -73,10 +134,14 @@ impl SessionStorage {
 /// Storage that reads **public data for any user** (unauthenticated).
 ///
 /// Accepts **addressed resources** (`PubkyResource`: user + absolute path).
-/// Writes are not available.
+/// Writes are not available, and private (`/priv/`) paths are rejected up front
+/// since they are never readable without a session.
 #[derive(Debug, Clone)]
 pub struct PublicStorage {
     pub(crate) client: PubkyHttpClient,
+    /// Set by [`Self::with_response_cache`].
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) cache: Option<super::cache::ResponseCache>,
 }
 
 impl PublicStorage {
@@ -90,9 +155,32 @@ impl PublicStorage {
     pub fn new() -> Result<Self> {
         Ok(Self {
             client: PubkyHttpClient::new()?,
+            #[cfg(not(target_arch = "wasm32"))]
+            cache: None,
         })
     }
 
+    /// Override the client's response body cap for requests made through this handle,
+    /// e.g. to fetch a large file from a user you trust. `0` removes the cap.
+    ///
+    /// ```no_run
+    /// # async fn ex(pubky: pubky::Pubky) -> pubky::Result<()> {
+    /// let video = pubky
+    ///     .public_storage()
+    ///     .with_max_response_bytes(0)
+    ///     .get("pubky://o4dksfbqk85ogzdb5osziw6befigbuxmuxkuxq8434q89uj56uyy/pub/app/video.mp4")
+    ///     .await?;
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// See [`PubkyHttpClientBuilder::max_response_bytes`](crate::PubkyHttpClientBuilder::max_response_bytes).
+    #[cfg(not(target_arch = "wasm32"))]
+    #[must_use]
+    pub fn with_max_response_bytes(mut self, bytes: u64) -> Self {
+        self.client = self.client.with_max_response_bytes(bytes);
+        self
+    }
+
     /// Build a request for this public storage (no cookies).
     pub(crate) async fn request<A: IntoPubkyResource>(
         &self,
@@ -100,6 +188,7 @@ impl PublicStorage {
         addr: A,
     ) -> Result<RequestBuilder> {
         let resource: PubkyResource = addr.into_pubky_resource()?;
+        reject_private(&resource)?;
         let url = resource.to_transport_url()?;
         cross_log!(debug, "Public storage {} request {}", method, url);
         let rb = self.client.cross_request(method, url).await?;
@@ -114,3 +203,20 @@ pub fn dir_trailing_slash_error() -> RequestError {
         message: "directory listings must end with `/`".into(),
     }
 }
+
+/// Helper: reject private (`/priv/`) resources in unauthenticated storage.
+#[inline]
+pub fn reject_private(resource: &PubkyResource) -> std::result::Result<(), RequestError> {
+    if resource.path.is_private() {
+        return Err(private_path_error());
+    }
+    Ok(())
+}
+
+/// Helper: validation error for private (`/priv/`) paths in unauthenticated storage.
+#[inline]
+pub fn private_path_error() -> RequestError {
+    RequestError::Validation {
+        message: "private (`/priv/`) paths require an authenticated `SessionStorage`".into(),
+    }
+}
@@ -0,0 +1,280 @@
+//! Preview of a one-way sync: compare a local directory with a remote one by content.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use base64::Engine;
+use pubky_common::crypto::{Hash, Hasher};
+use reqwest::StatusCode;
+use tokio::io::AsyncReadExt;
+
+use super::core::{SessionStorage, dir_trailing_slash_error};
+use super::resource::{IntoResourcePath, ResourcePath};
+use crate::errors::{Error, RequestError};
+use crate::{Result, cross_log};
+
+/// Page size [`SessionStorage::diff`] requests when listing the remote directory.
+const DIFF_LIST_PAGE: u16 = 100;
+
+/// Buffer size used when hashing local files.
+const HASH_CHUNK: usize = 64 * 1024;
+
+/// Differences between a local directory and a remote directory, as returned by
+/// [`SessionStorage::diff`].
+///
+/// Every path is relative to both directories, uses `/` as separator and the lists
+/// are sorted. Join a path onto the local directory or append it to the remote
+/// prefix to address the file on either side.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DiffReport {
+    /// Files that exist locally but not remotely.
+    pub added: Vec<String>,
+    /// Files that exist remotely but not locally.
+    pub removed: Vec<String>,
+    /// Files that exist on both sides with different content.
+    pub modified: Vec<String>,
+}
+
+impl DiffReport {
+    /// Whether both sides hold the same files with the same content.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+impl SessionStorage {
+    /// Compare the files below `local_dir` with the files below the remote directory
+    /// `remote_prefix`, without transferring any content.
+    ///
+    /// Both sides are walked recursively. A file present on both sides is
+    /// [modified](DiffReport::modified) if its size differs, or else if its BLAKE3 hash
+    /// differs from the content hash the homeserver reports for it. Hashes are
+    /// normalized before comparing, so hex and base64 encodings of the same digest are
+    /// equal; a remote file whose hash cannot be read counts as modified. Symbolic
+    /// links below `local_dir` are skipped.
+    ///
+    /// Only files present on both sides cost a `HEAD` request, and only their local
+    /// copies are hashed. A missing remote directory means every local file is added.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async fn ex(session: pubky::PubkySession) -> pubky::Result<()> {
+    /// let report = session.storage().diff("./site", "/pub/my-site/").await?;
+    /// for path in &report.added {
+    ///     println!("+ {path}");
+    /// }
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// - Returns [`RequestError::Validation`] if `remote_prefix` does not end with `/`,
+    ///   or if `local_dir` cannot be read or holds a file name that is not valid UTF-8.
+    /// - [`crate::errors::Error::Request`] on HTTP transport failures or when listing or
+    ///   inspecting the remote directory fails with a non-success status.
+    /// - [`crate::errors::Error::Parse`] if `remote_prefix` cannot be converted into a
+    ///   valid resource/URL.
+    pub async fn diff<L, P>(&self, local_dir: L, remote_prefix: P) -> Result<DiffReport>
+    where
+        L: AsRef<Path>,
+        P: IntoResourcePath,
+    {
+        let prefix: ResourcePath = remote_prefix.into_abs_path()?;
+        if !prefix.as_str().ends_with('/') {
+            return Err(dir_trailing_slash_error().into());
+        }
+        let local = local_files(local_dir.as_ref()).await?;
+        let remote = self.remote_files(&prefix).await?;
+
+        let mut report = DiffReport::default();
+        for (relative, local_path) in &local {
+            match remote.get(relative) {
+                None => report.added.push(relative.clone()),
+                Some(remote_path) => {
+                    if !self.same_content(local_path, remote_path).await? {
+                        report.modified.push(relative.clone());
+                    }
+                }
+            }
+        }
+        report.removed = remote
+            .keys()
+            .filter(|relative| !local.contains_key(*relative))
+            .cloned()
+            .collect();
+        cross_log!(
+            debug,
+            "Diffed {} against {}: {} added, {} removed, {} modified",
+            local_dir.as_ref().display(),
+            prefix,
+            report.added.len(),
+            report.removed.len(),
+            report.modified.len()
+        );
+        Ok(report)
+    }
+
+    /// Every file below `prefix`, keyed by its path relative to `prefix`.
+    pub(crate) async fn remote_files(
+        &self,
+        prefix: &ResourcePath,
+    ) -> Result<BTreeMap<String, ResourcePath>> {
+        let mut files = BTreeMap::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let mut builder = self.list(prefix)?.limit(DIFF_LIST_PAGE);
+            if let Some(cursor) = &cursor {
+                builder = builder.cursor(cursor);
+            }
+            let page = match builder.send_page().await {
+                Ok(page) => page,
+                Err(Error::Request(RequestError::Server { status, .. }))
+                    if status == StatusCode::NOT_FOUND && cursor.is_none() =>
+                {
+                    break;
+                }
+                Err(err) => return Err(err),
+            };
+            let Some(next) = page.next_cursor() else {
+                break;
+            };
+            for entry in page.entries {
+                if let Some(relative) = entry.path.as_str().strip_prefix(prefix.as_str())
+                    && !relative.is_empty()
+                    && !relative.ends_with('/')
+                {
+                    files.insert(relative.to_string(), entry.path);
+                }
+            }
+            cursor = Some(next);
+        }
+        Ok(files)
+    }
+
+    /// Whether the local file at `local` holds the same content as the remote file at
+    /// `remote`.
+    async fn same_content(&self, local: &Path, remote: &ResourcePath) -> Result<bool> {
+        let Some(stats) = self.stats(remote).await? else {
+            return Ok(false);
+        };
+        let local_len = tokio::fs::metadata(local)
+            .await
+            .map_err(|e| local_error(local, &e))?
+            .len();
+        if stats.content_length.is_some_and(|len| len != local_len) {
+            return Ok(false);
+        }
+        let Some(remote_hash) = stats.etag.as_deref().and_then(normalize_hash) else {
+            return Ok(false);
+        };
+        Ok(hash_file(local).await? == remote_hash)
+    }
+}
+
+/// Every regular file below `dir`, keyed by its `/`-separated path relative to `dir`.
+async fn local_files(dir: &Path) -> Result<BTreeMap<String, PathBuf>> {
+    let mut files = BTreeMap::new();
+    let mut pending = vec![(dir.to_path_buf(), String::new())];
+    while let Some((path, relative)) = pending.pop() {
+        let mut entries = tokio::fs::read_dir(&path)
+            .await
+            .map_err(|e| local_error(&path, &e))?;
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .map_err(|e| local_error(&path, &e))?
+        {
+            let entry_path = entry.path();
+            let file_type = entry
+                .file_type()
+                .await
+                .map_err(|e| local_error(&entry_path, &e))?;
+            let name = entry.file_name();
+            let Some(name) = name.to_str() else {
+                return Err(RequestError::Validation {
+                    message: format!("file name is not valid UTF-8: {}", entry_path.display()),
+                }
+                .into());
+            };
+            let entry_relative = format!("{relative}{name}");
+            if file_type.is_dir() {
+                pending.push((entry_path, format!("{entry_relative}/")));
+            } else if file_type.is_file() {
+                files.insert(entry_relative, entry_path);
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// BLAKE3 hash of the local file at `path`.
+async fn hash_file(path: &Path) -> Result<Hash> {
+    let mut file = tokio::fs::File::open(path)
+        .await
+        .map_err(|e| local_error(path, &e))?;
+    let mut hasher = Hasher::new();
+    let mut buf = vec![0; HASH_CHUNK];
+    loop {
+        let read = file
+            .read(&mut buf)
+            .await
+            .map_err(|e| local_error(path, &e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buf[..read]);
+    }
+    Ok(hasher.finalize())
+}
+
+/// Parse a content hash given as hex or as standard or URL-safe base64, with or
+/// without padding and surrounding `ETag` quotes.
+fn normalize_hash(raw: &str) -> Option<Hash> {
+    let raw = raw.trim();
+    let raw = raw.strip_prefix("W/").unwrap_or(raw);
+    let raw = raw.trim_matches('"');
+    if let Ok(hash) = Hash::from_hex(raw) {
+        return Some(hash);
+    }
+    let engines = [
+        base64::engine::general_purpose::STANDARD,
+        base64::engine::general_purpose::STANDARD_NO_PAD,
+        base64::engine::general_purpose::URL_SAFE,
+        base64::engine::general_purpose::URL_SAFE_NO_PAD,
+    ];
+    engines.iter().find_map(|engine| {
+        let bytes: [u8; 32] = engine.decode(raw).ok()?.try_into().ok()?;
+        Some(Hash::from_bytes(bytes))
+    })
+}
+
+fn local_error(path: &Path, err: &std::io::Error) -> Error {
+    RequestError::Validation {
+        message: format!("failed to read {}: {err}", path.display()),
+    }
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_encodings_are_normalized() {
+        let hash = pubky_common::crypto::hash(b"pubky");
+        let standard = base64::engine::general_purpose::STANDARD.encode(hash.as_bytes());
+        let url_safe = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(hash.as_bytes());
+        for encoded in [
+            hash.to_hex().to_string(),
+            hash.to_hex().to_uppercase(),
+            standard.clone(),
+            format!("\"{standard}\""),
+            format!("W/\"{standard}\""),
+            url_safe,
+        ] {
+            assert_eq!(normalize_hash(&encoded), Some(hash), "{encoded}");
+        }
+        assert_eq!(normalize_hash("not a hash"), None);
+        assert_eq!(normalize_hash("AAAA"), None);
+    }
+}
@@ -22,12 +22,11 @@ impl SessionStorage {
         P: IntoResourcePath + Send,
         T: serde::de::DeserializeOwned,
     {
-        let resp = self
-            .request(reqwest::Method::GET, path)
+        let rb = self
+            .build_request(reqwest::Method::GET, path)
             .await?
-            .header(reqwest::header::ACCEPT, "application/json")
-            .send()
-            .await?;
+            .header(reqwest::header::ACCEPT, "application/json");
+        let resp = self.client.send(rb).await?;
         let resp = check_http_status(resp).await?;
         Ok(resp.json::<T>().await?)
     }
@@ -44,12 +43,11 @@ impl SessionStorage {
         P: IntoResourcePath + Send,
         B: serde::Serialize + Sync + ?Sized,
     {
-        let resp = self
-            .request(reqwest::Method::PUT, path)
+        let rb = self
+            .build_request(reqwest::Method::PUT, path)
             .await?
-            .json(body)
-            .send()
-            .await?;
+            .json(body);
+        let resp = self.client.send(rb).await?;
         check_http_status(resp).await
     }
 }
@@ -69,12 +67,11 @@ impl PublicStorage {
         A: IntoPubkyResource + Send,
         T: serde::de::DeserializeOwned,
     {
-        let resp = self
+        let rb = self
             .request(reqwest::Method::GET, addr)
             .await?
-            .header(reqwest::header::ACCEPT, "application/json")
-            .send()
-            .await?;
+            .header(reqwest::header::ACCEPT, "application/json");
+        let resp = self.client.send(rb).await?;
         let resp = check_http_status(resp).await?;
         Ok(resp.json::<T>().await?)
     }
@@ -1,12 +1,26 @@
-use reqwest::Method;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use futures_util::{Stream, StreamExt, stream};
+use pubky_common::storage::LIST_SNAPSHOT_HEADER;
+use reqwest::{Method, StatusCode};
 use url::Url;
 
-use super::core::{PublicStorage, SessionStorage, dir_trailing_slash_error};
+use super::cancel::CancelToken;
+use super::core::{
+    PublicStorage, SessionStorage, dir_trailing_slash_error, private_path_error, reject_private,
+};
 use crate::actors::storage::resource::{
     IntoPubkyResource, IntoResourcePath, PubkyResource, ResourcePath,
 };
+use crate::errors::RequestError;
 use crate::util::check_http_status;
-use crate::{Result, cross_log};
+use crate::{PublicKey, Result, cross_log};
+
+/// Maximum number of users [`PublicStorage::list_across_users`] lists at the same time.
+pub const LIST_ACROSS_USERS_CONCURRENCY: usize = 8;
+
+/// Page size [`PublicStorage::list_across_users`] requests per user.
+const LIST_ACROSS_USERS_PAGE: u16 = 100;
 
 impl SessionStorage {
     /// Directory listing **as me** (authenticated).
@@ -40,8 +54,8 @@ impl SessionStorage {
         if !path.as_str().ends_with('/') {
             return Err(dir_trailing_slash_error().into());
         }
-        let resource = PubkyResource::new(self.user.clone(), path.as_str())?;
-        let url = resource.to_transport_url()?;
+        self.ensure_scoped_read(&path)?;
+        let url = self.url_for(&path)?;
         Ok(ListBuilder::session(self, url))
     }
 }
@@ -62,9 +76,122 @@ impl PublicStorage {
         if !resource.path.as_str().ends_with('/') {
             return Err(dir_trailing_slash_error().into());
         }
+        reject_private(&resource)?;
         let url = resource.to_transport_url()?;
         Ok(ListBuilder::public(self, url))
     }
+
+    /// List the same directory across many users, merged into one stream.
+    ///
+    /// Every user's homeserver is resolved and paged through on its own, at most
+    /// [`LIST_ACROSS_USERS_CONCURRENCY`] users at a time. Entries are yielded as they arrive, tagged with their
+    /// user, so entries of different users interleave; each user's entries keep their
+    /// listing order. A failing user (unresolvable, unreachable, erroring) yields its
+    /// error inline and the stream moves on to the others.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use futures_util::StreamExt;
+    ///
+    /// # async fn example(pubky: pubky::Pubky, users: Vec<pubky::PublicKey>) -> pubky::Result<()> {
+    /// let mut posts = pubky
+    ///     .public_storage()
+    ///     .list_across_users(users, "/pub/my-cool-app/posts/")?;
+    /// while let Some((user, entry)) = posts.next().await {
+    ///     match entry {
+    ///         Ok(entry) => println!("{}", entry.to_pubky_url()),
+    ///         Err(err) => eprintln!("skipping {user}: {err}"),
+    ///     }
+    /// }
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// - Returns [`crate::errors::RequestError::Validation`] if `prefix` is not an absolute,
+    ///   public directory path ending with `/`.
+    pub fn list_across_users<I>(
+        &self,
+        users: I,
+        prefix: &str,
+    ) -> Result<impl Stream<Item = (PublicKey, Result<PubkyResource>)> + use<I>>
+    where
+        I: IntoIterator<Item = PublicKey>,
+    {
+        let prefix: ResourcePath = prefix.into_abs_path()?;
+        if !prefix.as_str().ends_with('/') {
+            return Err(dir_trailing_slash_error().into());
+        }
+        if prefix.is_private() {
+            return Err(private_path_error().into());
+        }
+
+        let storage = self.clone();
+        Ok(stream::iter(users)
+            .map(move |user| Box::pin(storage.clone().list_user(user, prefix.clone())))
+            .flatten_unordered(LIST_ACROSS_USERS_CONCURRENCY))
+    }
+
+    /// Like [`list_across_users`](Self::list_across_users), but the stream ends cleanly
+    /// once `cancel` is cancelled.
+    ///
+    /// Page requests still in flight are dropped and the stream yields `None`, so the
+    /// entries received so far are the progress made. Dropping the stream has the same
+    /// effect; the token is for cancelling from a task that does not own the stream.
+    ///
+    /// # Errors
+    /// Same as [`list_across_users`](Self::list_across_users).
+    pub fn list_across_users_cancellable<I>(
+        &self,
+        users: I,
+        prefix: &str,
+        cancel: &CancelToken,
+    ) -> Result<impl Stream<Item = (PublicKey, Result<PubkyResource>)> + use<I>>
+    where
+        I: IntoIterator<Item = PublicKey>,
+    {
+        let cancel = cancel.clone();
+        Ok(self
+            .list_across_users(users, prefix)?
+            .take_until(async move { cancel.cancelled().await }))
+    }
+
+    /// Page through one user's directory, yielding its entries and stopping after the
+    /// first error.
+    fn list_user(
+        self,
+        user: PublicKey,
+        prefix: ResourcePath,
+    ) -> impl Stream<Item = (PublicKey, Result<PubkyResource>)> {
+        let tag = user.clone();
+        let pages = stream::unfold(Some(None), move |cursor: Option<Option<String>>| {
+            let storage = self.clone();
+            let resource = PubkyResource {
+                owner: user.clone(),
+                path: prefix.clone(),
+            };
+            async move {
+                let cursor = cursor?;
+                let page = async {
+                    let mut builder = storage.list(resource)?.limit(LIST_ACROSS_USERS_PAGE);
+                    if let Some(cursor) = &cursor {
+                        builder = builder.cursor(cursor);
+                    }
+                    builder.send_page().await
+                };
+                match page.await {
+                    Ok(page) if page.entries.is_empty() => None,
+                    Ok(page) => {
+                        let next = page.next_cursor();
+                        Some((page.entries.into_iter().map(Ok).collect(), Some(next)))
+                    }
+                    Err(err) => Some((vec![Err(err)], None)),
+                }
+            }
+        });
+        pages
+            .flat_map(stream::iter)
+            .map(move |entry| (tag.clone(), entry))
+    }
 }
 
 /// Internal scope for a listing request.
@@ -76,8 +203,9 @@ enum ListScope<'a> {
 
 /// Unified builder for homeserver `LIST` queries (works for session & public).
 ///
-/// Configure optional flags like `reverse`, `shallow`, `limit`, and `cursor`,
-/// then call [`send`](Self::send) to perform the request.
+/// Configure optional flags like `reverse`, `shallow`, `limit`, `cursor` and
+/// `modified_since`, then call [`send`](Self::send) to perform the request, or
+/// [`send_page`](Self::send_page) when paginating with a [snapshot](Self::snapshot).
 ///
 /// Returned entries are [`PubkyResource`] values.
 ///
@@ -93,6 +221,36 @@ pub struct ListBuilder<'a> {
     shallow: bool,
     limit: Option<u16>,
     cursor: Option<String>,
+    modified_since: Option<SystemTime>,
+    snapshot: Option<ListSnapshot>,
+    query: Vec<(String, String)>,
+}
+
+/// Snapshot mode of a listing request.
+#[derive(Debug)]
+enum ListSnapshot {
+    New,
+    Resume(String),
+}
+
+/// One page of a directory listing, as returned by [`ListBuilder::send_page`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListPage {
+    /// Addressed entries of this page.
+    pub entries: Vec<PubkyResource>,
+    /// Snapshot token this page was served from, if a snapshot was requested.
+    ///
+    /// Pass it to [`ListBuilder::resume_snapshot`] to fetch the next page
+    /// against the same point in time.
+    pub snapshot: Option<String>,
+}
+
+impl ListPage {
+    /// Cursor for the page after this one, or `None` if this page is empty.
+    #[must_use]
+    pub fn next_cursor(&self) -> Option<String> {
+        self.entries.last().map(PubkyResource::to_pubky_url)
+    }
 }
 
 impl<'a> ListBuilder<'a> {
@@ -105,6 +263,9 @@ impl<'a> ListBuilder<'a> {
             shallow: false,
             limit: None,
             cursor: None,
+            modified_since: None,
+            snapshot: None,
+            query: Vec::new(),
         }
     }
 
@@ -142,12 +303,118 @@ impl<'a> ListBuilder<'a> {
         self
     }
 
+    /// Only list files last modified at or after `since` (inclusive).
+    ///
+    /// The homeserver filters on the modification time it records for every write, at
+    /// microsecond resolution; `since` is truncated to whole microseconds, which can
+    /// only widen the filter. In a [shallow](Self::shallow) listing a directory is
+    /// listed if it contains at least one matching file. Works together with
+    /// [`cursor`](Self::cursor), so an incremental sync pages through the changes only.
+    ///
+    /// Deleted files are not listed; follow the event stream to see deletions.
+    /// Modification times come from the homeserver's clock, so take the next `since`
+    /// from the homeserver as well (e.g. [`DirStats::newest_modified`](super::stats::DirStats::newest_modified)),
+    /// not from the local clock.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # async fn example(session: pubky::PubkySession, last_sync: std::time::SystemTime) -> pubky::Result<()> {
+    /// let changed = session
+    ///     .storage()
+    ///     .list("/pub/my-cool-app/")?
+    ///     .modified_since(last_sync)
+    ///     .send()
+    ///     .await?;
+    /// # Ok(()) }
+    /// ```
+    pub const fn modified_since(mut self, since: SystemTime) -> Self {
+        self.modified_since = Some(since);
+        self
+    }
+
+    /// Open a new point-in-time snapshot and serve this page from it.
+    ///
+    /// Without a snapshot every page reads the live directory, so a full iteration
+    /// over a directory that is written concurrently may skip or repeat entries.
+    /// With a snapshot, the homeserver pins the directory state at the first page and
+    /// serves every later page requested with [`resume_snapshot`](Self::resume_snapshot)
+    /// from that same state. The token is returned in [`ListPage::snapshot`].
+    ///
+    /// Snapshots are short-lived. A snapshot expires when no page was requested for
+    /// about a minute, after at most ten minutes in total, or when the homeserver
+    /// restarts. Requests against an expired snapshot fail with
+    /// [`RequestError::SnapshotExpired`]; restart the listing with a new snapshot.
+    /// Opening a snapshot requires a session on the homeserver, which answers `401`
+    /// otherwise; resuming one does not. Homeservers only keep a few snapshots open at
+    /// a time, per user and in total, and may answer with `503 Service Unavailable`
+    /// when they are all in use.
+    pub fn snapshot(mut self) -> Self {
+        self.snapshot = Some(ListSnapshot::New);
+        self
+    }
+
+    /// Serve this page from a snapshot opened by an earlier [`snapshot`](Self::snapshot) request.
+    ///
+    /// Combine with [`cursor`](Self::cursor) set to [`ListPage::next_cursor`] of the previous page.
+    pub fn resume_snapshot(mut self, token: &str) -> Self {
+        self.snapshot = Some(ListSnapshot::Resume(token.to_string()));
+        self
+    }
+
+    /// Append query parameters the builder does not model, e.g. filters of a newer
+    /// homeserver.
+    ///
+    /// Keys and values are passed unencoded and are form-encoded exactly once, after the
+    /// listing's own parameters. Set those with their typed methods instead.
+    pub fn query<K: AsRef<str>, V: AsRef<str>>(mut self, params: &[(K, V)]) -> Self {
+        self.query.extend(
+            params
+                .iter()
+                .map(|(key, value)| (key.as_ref().to_string(), value.as_ref().to_string())),
+        );
+        self
+    }
+
     /// Execute the LIST request and return addressed entries.
     ///
     /// # Errors
     /// - Propagates transport failures while issuing the HTTP request.
     /// - Returns [`crate::errors::RequestError::Validation`] if any resource line returned by the server is invalid.
+    /// - Returns [`crate::errors::RequestError::SnapshotExpired`] if the resumed snapshot has expired.
     pub async fn send(self) -> Result<Vec<PubkyResource>> {
+        Ok(self.send_page().await?.entries)
+    }
+
+    /// Execute the LIST request and return the page with its snapshot token.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # async fn example(session: pubky::PubkySession) -> pubky::Result<()> {
+    /// let storage = session.storage();
+    /// let mut page = storage.list("/pub/my-cool-app/")?.snapshot().send_page().await?;
+    /// loop {
+    ///     for entry in &page.entries {
+    ///         println!("{}", entry.to_pubky_url());
+    ///     }
+    ///     let (Some(cursor), Some(snapshot)) = (page.next_cursor(), page.snapshot.as_deref())
+    ///     else {
+    ///         break;
+    ///     };
+    ///     page = storage
+    ///         .list("/pub/my-cool-app/")?
+    ///         .resume_snapshot(snapshot)
+    ///         .cursor(&cursor)
+    ///         .send_page()
+    ///         .await?;
+    /// }
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// - Propagates transport failures while issuing the HTTP request.
+    /// - Returns [`crate::errors::RequestError::Validation`] if any resource line returned by the server is invalid.
+    /// - Returns [`crate::errors::RequestError::SnapshotExpired`] if the resumed snapshot has expired.
+    pub async fn send_page(self) -> Result<ListPage> {
         // 1) Build query params
         let mut url = self.url;
         {
@@ -164,35 +431,62 @@ impl<'a> ListBuilder<'a> {
             if let Some(cursor) = self.cursor {
                 q.append_pair("cursor", &cursor);
             }
+            if let Some(since) = self.modified_since {
+                let micros = since
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |elapsed| elapsed.as_micros());
+                q.append_pair("modified_since", &micros.to_string());
+            }
+            match &self.snapshot {
+                Some(ListSnapshot::New) => {
+                    q.append_pair("snapshot", "new");
+                }
+                Some(ListSnapshot::Resume(token)) => {
+                    q.append_pair("snapshot", token);
+                }
+                None => {}
+            }
+            for (key, value) in &self.query {
+                q.append_pair(key, value);
+            }
         }
 
         // 2) Build request per scope
-        let rb = match self.scope {
+        let (client, rb) = match self.scope {
             ListScope::Public(storage) => {
-                storage
+                let rb = storage
                     .client
                     .cross_request(Method::GET, url.clone())
-                    .await?
+                    .await?;
+                (&storage.client, rb)
             }
             ListScope::Session(storage) => {
                 let rb = storage
                     .client
                     .cross_request(Method::GET, url.clone())
                     .await?;
-                storage.attach_credential(rb).await?
+                (&storage.client, storage.attach_credential(rb).await?)
             }
         };
 
         // 3) Send and parse
-        let resp = rb.send().await?;
+        let resp = client.send(rb).await?;
         cross_log!(
             debug,
             "Request completed with status {} (LIST {})",
             resp.status(),
             resp.url()
         );
+        if resp.status() == StatusCode::GONE && self.snapshot.is_some() {
+            return Err(RequestError::SnapshotExpired.into());
+        }
         let resp = check_http_status(resp).await?;
 
+        let snapshot = resp
+            .headers()
+            .get(LIST_SNAPSHOT_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
         let bytes = resp.bytes().await?;
         let mut out = Vec::new();
         for line in String::from_utf8_lossy(&bytes).lines() {
@@ -203,7 +497,10 @@ impl<'a> ListBuilder<'a> {
 
             out.push(Self::parse_resource_line(trimmed)?);
         }
-        Ok(out)
+        Ok(ListPage {
+            entries: out,
+            snapshot,
+        })
     }
 
     fn parse_resource_line(line: &str) -> Result<PubkyResource> {
@@ -1,7 +1,15 @@
+pub mod backup;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod cache;
+pub mod cancel;
 pub mod core;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod diff;
 #[cfg(feature = "json")]
 pub mod json;
 pub mod list;
+pub mod permission;
+pub mod request;
 pub mod resource;
 pub mod stats;
 pub mod verbs;
@@ -0,0 +1,81 @@
+use pubky_common::capabilities::Action;
+use pubky_common::storage::{PRIVATE_ROOT, PUBLIC_ROOT};
+use reqwest::Method;
+
+use super::core::SessionStorage;
+use super::resource::{IntoResourcePath, ResourcePath};
+use crate::errors::{RequestError, Result};
+
+impl SessionStorage {
+    /// Check locally whether this session's capabilities let it send `method` to `path`.
+    ///
+    /// Applies the homeserver's authorization rules (see
+    /// [`SessionInfo::can_read`](crate::SessionInfo::can_read) and
+    /// [`SessionInfo::can_write`](crate::SessionInfo::can_write)) without a round trip:
+    /// `GET`/`HEAD` need read access, `PUT`/`POST`/`PATCH`/`DELETE` need write
+    /// access, and other methods are not checked. Writes through this storage run the
+    /// same check before sending.
+    ///
+    /// # Errors
+    /// - [`RequestError::Forbidden`] if the homeserver would answer `403`.
+    /// - [`RequestError::Validation`] if `path` is not a valid absolute path.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async fn ex(session: pubky::PubkySession) -> pubky::Result<()> {
+    /// use pubky::Method;
+    ///
+    /// let storage = session.storage();
+    /// if storage.check_permission(&Method::PUT, "/pub/my-app/settings.json").is_ok() {
+    ///     storage.put("/pub/my-app/settings.json", "{}").await?;
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub fn check_permission<P: IntoResourcePath>(&self, method: &Method, path: P) -> Result<()> {
+        let path = path.into_abs_path()?;
+        self.ensure_permitted(method, &path)
+    }
+
+    /// On a [`crate::PubkySession::scoped`] session, fail unless it may read `path`.
+    ///
+    /// The homeserver authorizes reads against the full credential, so only the
+    /// narrowed scope needs a local check.
+    pub(crate) fn ensure_scoped_read(&self, path: &ResourcePath) -> Result<()> {
+        if self.credential.is_scoped() {
+            self.ensure_permitted(&Method::GET, path)?;
+        }
+        Ok(())
+    }
+
+    /// Fail with [`RequestError::Forbidden`] unless the session may send `method` to `path`.
+    pub(crate) fn ensure_permitted(&self, method: &Method, path: &ResourcePath) -> Result<()> {
+        let info = self.credential.info();
+        let (allowed, access, outside_roots) = match required_action(method) {
+            Some(Action::Read) => (info.can_read(path), "read", "Reading from"),
+            Some(Action::Write) => (info.can_write(path), "write", "Writing to"),
+            _ => return Ok(()),
+        };
+        if allowed {
+            return Ok(());
+        }
+        // Same messages as the homeserver's 403s.
+        let in_root = [PUBLIC_ROOT, PRIVATE_ROOT]
+            .iter()
+            .any(|root| path.as_str().starts_with(root));
+        let message = if in_root {
+            format!("Session does not have {access} access to {path}")
+        } else {
+            format!("{outside_roots} directories other than '/pub/' and '/priv/' is forbidden")
+        };
+        Err(RequestError::Forbidden { message }.into())
+    }
+}
+
+/// The capability action the homeserver requires for `method`, if it checks one.
+pub(crate) fn required_action(method: &Method) -> Option<Action> {
+    match *method {
+        Method::GET | Method::HEAD => Some(Action::Read),
+        Method::PUT | Method::POST | Method::PATCH | Method::DELETE => Some(Action::Write),
+        _ => None,
+    }
+}
@@ -0,0 +1,127 @@
+//! Session-scoped requests with an arbitrary HTTP method.
+
+use reqwest::{Method, RequestBuilder};
+
+use super::core::SessionStorage;
+use super::resource::IntoResourcePath;
+use crate::{PubkyHttpClient, PubkyResponse, Result};
+
+impl SessionStorage {
+    /// Start a request with any HTTP `method` for an **absolute path**, as me.
+    ///
+    /// Use this for verbs without a dedicated method, like `OPTIONS` or `WebDAV`'s
+    /// `PROPFIND` and `MKCOL`. The session credential is attached exactly as for
+    /// [`Self::get`] or [`Self::put`], and writes the session's capabilities do not
+    /// cover fail locally, see [`Self::check_permission`].
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async fn ex(session: pubky::PubkySession) -> pubky::Result<()> {
+    /// use pubky::Method;
+    ///
+    /// let propfind = Method::from_bytes(b"PROPFIND").expect("valid method");
+    /// let resp = session
+    ///     .storage()
+    ///     .request(propfind, "/pub/my-cool-app/")
+    ///     .await?
+    ///     .header("Depth", "1")
+    ///     .send()
+    ///     .await?;
+    /// println!("{}", resp.status());
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// - [`crate::errors::Error::Parse`] if `path` cannot be converted into a valid resource.
+    /// - [`crate::errors::RequestError::Forbidden`] if the session may not write to `path`.
+    /// - Propagates failures while resolving the homeserver or refreshing the credential.
+    pub async fn request<P: IntoResourcePath>(
+        &self,
+        method: Method,
+        path: P,
+    ) -> Result<SessionRequest> {
+        Ok(SessionRequest {
+            client: self.client.clone(),
+            inner: self.build_request(method, path).await?,
+            query: Vec::new(),
+        })
+    }
+}
+
+/// A session-scoped request returned by [`SessionStorage::request`].
+///
+/// Sent through the same client as every other storage call, so timeouts,
+/// transfer limits and custom transports apply.
+#[derive(Debug)]
+#[must_use = "a request does nothing until it is sent"]
+pub struct SessionRequest {
+    client: PubkyHttpClient,
+    inner: RequestBuilder,
+    query: Vec<(String, String)>,
+}
+
+impl SessionRequest {
+    /// Add a header to the request.
+    ///
+    /// An invalid name or value makes [`Self::send`] fail.
+    pub fn header(mut self, key: impl AsRef<str>, value: impl AsRef<str>) -> Self {
+        self.inner = self.inner.header(key.as_ref(), value.as_ref());
+        self
+    }
+
+    /// Append query parameters to the request URL.
+    ///
+    /// Keys and values are passed unencoded and are form-encoded exactly once, after
+    /// any parameters already in the URL. Use it for parameters the SDK does not model
+    /// yet, e.g. filters of a newer homeserver.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async fn ex(session: pubky::PubkySession) -> pubky::Result<()> {
+    /// use pubky::Method;
+    ///
+    /// let resp = session
+    ///     .storage()
+    ///     .request(Method::GET, "/pub/my-cool-app/feed")
+    ///     .await?
+    ///     .query(&[("tag", "rust & wasm"), ("after", "2024-01-01")])
+    ///     .send()
+    ///     .await?;
+    /// # Ok(()) }
+    /// ```
+    pub fn query<K: AsRef<str>, V: AsRef<str>>(mut self, params: &[(K, V)]) -> Self {
+        self.query.extend(
+            params
+                .iter()
+                .map(|(key, value)| (key.as_ref().to_string(), value.as_ref().to_string())),
+        );
+        self
+    }
+
+    /// Set the request body.
+    pub fn body<T: Into<reqwest::Body>>(mut self, body: T) -> Self {
+        self.inner = self.inner.body(body);
+        self
+    }
+
+    /// Send the request.
+    ///
+    /// The status is **not** checked: `OPTIONS` and `WebDAV` verbs use statuses like
+    /// `207 Multi-Status` or `405 Method Not Allowed` that callers interpret themselves.
+    ///
+    /// # Errors
+    /// - [`crate::errors::Error::Request`] on transport failures.
+    pub async fn send(self) -> Result<PubkyResponse> {
+        let mut rb = self.inner;
+        if !self.query.is_empty() {
+            let (http, request) = rb.build_split();
+            let mut request = request?;
+            request
+                .url_mut()
+                .query_pairs_mut()
+                .extend_pairs(&self.query);
+            rb = RequestBuilder::from_parts(http, request);
+        }
+        Ok(self.client.send(rb).await?.into())
+    }
+}
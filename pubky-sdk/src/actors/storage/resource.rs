@@ -12,15 +12,25 @@
 //!   It is used by *public* (unauthenticated) operations and any API that must
 //!   target **another user’s** data. Example: `public.get("pubky<pk>/pub/site/index.html")`.
 //!
+//! Paths live under one of two storage roots: `/pub/` is world-readable, while `/priv/`
+//! is app-private and only reachable through an authenticated session whose capabilities
+//! cover the path (see [`ResourcePath::is_private`]).
+//!
 //! Keeping these distinct eliminates ambiguity and makes IDE auto-completion
 //! tell you exactly what each method expects.
 //!
 //! We intentionally do **not** accept `https://_pubky.<pk>/...` here; higher-level
-//! APIs handle resolution and URL formation for you.
+//! APIs handle resolution and URL formation for you. To compare links users paste in
+//! any of these shapes, reduce them to one form with [`canonicalize_url`].
 
-use std::{fmt, str::FromStr};
+use std::{
+    fmt::{self, Write},
+    str::FromStr,
+};
 
 use crate::PublicKey;
+use percent_encoding::percent_decode_str;
+use pubky_common::storage::{DEFAULT_MAX_PATH_LENGTH, DEFAULT_MAX_PATH_SEGMENTS};
 use url::Url;
 
 use crate::{Error, errors::RequestError};
@@ -42,6 +52,8 @@ fn invalid(msg: impl Into<String>) -> Error {
 /// - Always normalized to start with `/`.
 /// - Rejects `.` and `..` segments (no path traversal).
 /// - Rejects empty **internal** segments (i.e., `//`); preserves a trailing `/`.
+/// - Rejects paths longer than [`DEFAULT_MAX_PATH_LENGTH`] bytes (percent-decoded) or with
+///   more than [`DEFAULT_MAX_PATH_SEGMENTS`] segments, the homeserver's default limits.
 /// - Percent-encodes segments using `url::Url` rules (UTF-8).
 ///
 /// Accepts both `"pub/my-cool-app/file"` and `"/pub/my-cool-app/file"` and normalizes to an
@@ -69,6 +81,7 @@ fn invalid(msg: impl Into<String>) -> Error {
 /// - empty input
 /// - `//` within the path
 /// - `.` or `..` segments
+/// - paths exceeding the length or segment limits
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct ResourcePath(String);
 
@@ -79,9 +92,19 @@ impl ResourcePath {
     ///
     /// # Errors
     /// - Returns [`Error::Request`] when the input is empty, contains `.`/`..`, or has empty segments (`//`).
+    /// - Returns [`Error::Request`] when the path is too long or has too many segments.
     /// - Returns [`Error::Request`] if internal URL handling fails while normalizing the path.
     pub fn parse<S: AsRef<str>>(s: S) -> Result<Self, Error> {
-        let raw = s.as_ref();
+        Self::normalize(s.as_ref(), false)
+    }
+
+    /// Like [`Self::parse`], but for a path that is already percent-encoded (taken from a
+    /// URL), so existing `%XX` escapes are kept instead of being encoded again.
+    pub(crate) fn parse_encoded(s: &str) -> Result<Self, Error> {
+        Self::normalize(s, true)
+    }
+
+    fn normalize(raw: &str, encoded: bool) -> Result<Self, Error> {
         if raw.is_empty() {
             return Err(invalid("path cannot be empty"));
         }
@@ -106,6 +129,7 @@ impl ResourcePath {
             segs.clear();
 
             let mut parts = input.trim_start_matches('/').split('/').peekable();
+            let mut count = 0;
             while let Some(seg) = parts.next() {
                 if seg.is_empty() {
                     // Only allow the final empty segment (trailing slash)
@@ -114,16 +138,32 @@ impl ResourcePath {
                     }
                     return Err(invalid("path contains empty segment ('//')"));
                 }
-                if seg == "." || seg == ".." {
+                if seg == "." || seg == ".." || (encoded && is_encoded_dot_segment(seg)) {
                     return Err(invalid("path cannot contain '.' or '..'"));
                 }
+                count += 1;
+                if count > DEFAULT_MAX_PATH_SEGMENTS {
+                    return Err(invalid(format!(
+                        "path has more than {DEFAULT_MAX_PATH_SEGMENTS} segments"
+                    )));
+                }
                 segs.push(seg);
             }
             if wants_trailing {
                 segs.push(""); // encode trailing slash
             }
         }
+        if encoded {
+            // Segments are validated; let the URL parser keep existing escapes.
+            u.set_path(&input);
+        }
 
+        let length = percent_decode_str(u.path()).count();
+        if length > DEFAULT_MAX_PATH_LENGTH {
+            return Err(invalid(format!(
+                "path is {length} bytes long, the maximum is {DEFAULT_MAX_PATH_LENGTH}"
+            )));
+        }
         Ok(Self(u.path().to_string()))
     }
 
@@ -133,6 +173,101 @@ impl ResourcePath {
     pub fn as_str(&self) -> &str {
         &self.0
     }
+
+    /// Whether the path is under the private `/priv/` root.
+    ///
+    /// Private paths are neither world-readable nor world-writable: reads and writes
+    /// require a session whose capabilities cover the path.
+    #[inline]
+    #[must_use]
+    pub fn is_private(&self) -> bool {
+        pubky_common::storage::is_private_path(&self.0)
+    }
+
+    /// Deterministic path for storing `bytes` under the directory `prefix`, keyed by
+    /// their content hash.
+    ///
+    /// The key is the lowercase hex BLAKE3 hash, the same hash the homeserver reports
+    /// as [`PutResult::content_hash`](crate::PutResult) and in event `content_hash`es.
+    /// Paths are sharded by the first two bytes of the hash to keep directories small:
+    /// `{prefix}/{hex[0..2]}/{hex[2..4]}/{hex}`. Identical content always maps to the
+    /// same path, so checking [`SessionStorage::exists`](crate::SessionStorage::exists)
+    /// before a `put` deduplicates uploads.
+    ///
+    /// # Examples
+    /// ```
+    /// # use pubky::ResourcePath;
+    /// let path = ResourcePath::content_addressed("/pub/my-cool-app/blobs/", b"hello")?;
+    /// assert_eq!(
+    ///     path.as_str(),
+    ///     "/pub/my-cool-app/blobs/ea/8f/ea8f163db38682925e4491c5e58d4bb3506ef8c14eb78a86e908c5624a67200f"
+    /// );
+    /// # Ok::<(), pubky::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    /// - Returns [`Error::Request`] if `prefix` is not a valid path, or the resulting
+    ///   path exceeds the length or segment limits.
+    pub fn content_addressed<P: IntoResourcePath>(
+        prefix: P,
+        bytes: impl AsRef<[u8]>,
+    ) -> Result<Self, Error> {
+        Self::for_content_hash(prefix, &pubky_common::crypto::hash(bytes.as_ref()))
+    }
+
+    /// [`Self::content_addressed`] for content read from `reader`, hashed in chunks
+    /// without buffering it (native only).
+    ///
+    /// # Errors
+    /// - Returns [`Error::Request`] if reading from `reader` fails, or for the same
+    ///   reasons as [`Self::content_addressed`].
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn content_addressed_stream<P, R>(prefix: P, mut reader: R) -> Result<Self, Error>
+    where
+        P: IntoResourcePath,
+        R: tokio::io::AsyncRead + Unpin,
+    {
+        use tokio::io::AsyncReadExt;
+
+        let mut hasher = pubky_common::crypto::Hasher::new();
+        let mut buf = vec![0; 64 * 1024];
+        loop {
+            let read = reader
+                .read(&mut buf)
+                .await
+                .map_err(|e| invalid(format!("failed to read content: {e}")))?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buf[..read]);
+        }
+        Self::for_content_hash(prefix, &hasher.finalize())
+    }
+
+    /// The [`Self::content_addressed`] path for content hashing to `hash`.
+    ///
+    /// # Errors
+    /// - Returns [`Error::Request`] for the same reasons as [`Self::content_addressed`].
+    pub fn for_content_hash<P: IntoResourcePath>(
+        prefix: P,
+        hash: &pubky_common::crypto::Hash,
+    ) -> Result<Self, Error> {
+        let prefix = prefix.into_abs_path()?;
+        let hex = hash.to_hex();
+        Self::parse(format!(
+            "{}/{}/{}/{hex}",
+            prefix.as_str().trim_end_matches('/'),
+            &hex[0..2],
+            &hex[2..4]
+        ))
+    }
+}
+
+/// Whether a percent-encoded segment decodes to `.` or `..`.
+fn is_encoded_dot_segment(seg: &str) -> bool {
+    let lower = seg.to_ascii_lowercase();
+    let dots = lower.replace("%2e", ".");
+    dots == "." || dots == ".."
 }
 
 impl FromStr for ResourcePath {
@@ -224,6 +359,25 @@ impl PubkyResource {
         Ok(Url::parse(&https)?)
     }
 
+    /// Render as `https://<homeserver>/dav/<owner>/<abs-path>`.
+    ///
+    /// Unlike [`Self::to_transport_url`], the owner is part of the path, so the URL
+    /// addresses the owner's data on that specific `homeserver` (e.g. a mirror) and
+    /// needs no `pubky-host` header.
+    ///
+    /// # Errors
+    /// - Returns [`Error::Request`] if the constructed URL is invalid.
+    pub fn to_dav_url(&self, homeserver: &PublicKey) -> Result<Url, Error> {
+        let rel = self.path.as_str().trim_start_matches('/');
+        let https = format!(
+            "https://{}/dav/{}/{}",
+            homeserver.z32(),
+            self.owner.z32(),
+            rel
+        );
+        Ok(Url::parse(&https)?)
+    }
+
     /// Construct a [`PubkyResource`] from a homeserver transport URL.
     ///
     /// Accepts either `https://_pubky.<owner>/...` or `http://_pubky.<owner>/...`
@@ -268,22 +422,14 @@ impl FromStr for PubkyResource {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         // 1) pubky://<user>/<path>
         if let Some(rest) = s.strip_prefix("pubky://") {
-            let (user_str, path) = rest
-                .split_once('/')
-                .ok_or_else(|| invalid("missing `<user>/<path>`"))?;
-            if PublicKey::is_pubky_prefixed(user_str) {
-                return Err(invalid(
-                    "unexpected `pubky` prefix in user id; use raw z32 after `pubky://`",
-                ));
-            }
-            let user = PublicKey::try_from_z32(user_str)
-                .map_err(|_err| invalid(format!("invalid user public key: {user_str}")))?;
+            let (user, path) = split_pubky_url(rest)?;
             return Self::new(user, path);
         }
 
         // 2) pubky<user>/<path>
         if let Some(rest) = s.strip_prefix("pubky") {
-            if let Some((user_id, path)) = rest.split_once('/') {
+            if let Some(slash) = rest.find('/') {
+                let (user_id, path) = rest.split_at(slash);
                 if PublicKey::is_pubky_prefixed(user_id) {
                     return Err(invalid(
                         "unexpected `pubky` prefix in user id; use raw z32 after `pubky`",
@@ -305,12 +451,180 @@ impl FromStr for PubkyResource {
     }
 }
 
+/// Split the part of a `pubky://` URL after the scheme into owner and absolute path.
+fn split_pubky_url(rest: &str) -> Result<(PublicKey, &str), Error> {
+    let slash = rest
+        .find('/')
+        .ok_or_else(|| invalid("missing `<user>/<path>`"))?;
+    let (user_str, path) = rest.split_at(slash);
+    if PublicKey::is_pubky_prefixed(user_str) {
+        return Err(invalid(
+            "unexpected `pubky` prefix in user id; use raw z32 after `pubky://`",
+        ));
+    }
+    let user = PublicKey::try_from_z32(user_str)
+        .map_err(|_err| invalid(format!("invalid user public key: {user_str}")))?;
+    Ok((user, path))
+}
+
 impl fmt::Display for PubkyResource {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_str(&self.to_identifier())
     }
 }
 
+// ============================================================================
+// PubkyUrl
+// ============================================================================
+
+/// A validated, canonical `pubky://<owner>/<abs-path>` URL.
+///
+/// Prefer this over assembling `format!("pubky://{}/{}", ..)` strings by hand: the
+/// path goes through [`ResourcePath`] normalization, so a missing leading slash is
+/// added, non-ASCII characters are percent-encoded, and `//`, `.` or `..` segments
+/// are rejected instead of producing a URL that silently 404s.
+///
+/// Storage APIs accept it directly (it implements [`IntoPubkyResource`]).
+///
+/// ### Examples
+/// ```
+/// # use pubky::{Keypair, PubkyUrl};
+/// let user = Keypair::random().public_key();
+///
+/// let url = PubkyUrl::builder(user.clone()).path("pub/app/x.txt").build()?;
+/// assert_eq!(url.as_str(), format!("pubky://{}/pub/app/x.txt", user.z32()));
+///
+/// let parsed = PubkyUrl::parse(url.as_str())?;
+/// assert_eq!(parsed, url);
+/// assert_eq!(parsed.owner(), &user);
+/// assert_eq!(parsed.path().as_str(), "/pub/app/x.txt");
+/// # Ok::<(), pubky::Error>(())
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PubkyUrl {
+    resource: PubkyResource,
+    url: Url,
+}
+
+impl PubkyUrl {
+    /// Start building a URL for a resource owned by `owner`.
+    pub fn builder(owner: PublicKey) -> PubkyUrlBuilder {
+        PubkyUrlBuilder {
+            owner,
+            path: String::from("/"),
+        }
+    }
+
+    /// Parse `pubky://<owner>/<abs-path>` (or the identifier form `pubky<owner>/<abs-path>`)
+    /// into owner and path.
+    ///
+    /// The path of a `pubky://` URL is taken as already percent-encoded, so
+    /// `PubkyUrl::parse(url.as_str())` returns `url` unchanged. The identifier form
+    /// takes a plain path, like [`PubkyResource`].
+    ///
+    /// # Errors
+    /// - Returns [`Error::Request`] if the input has neither form, the owner is not a valid
+    ///   public key, or the path cannot be normalized into a [`ResourcePath`].
+    pub fn parse<S: AsRef<str>>(s: S) -> Result<Self, Error> {
+        let s = s.as_ref();
+        let resource = match s.strip_prefix("pubky://") {
+            Some(rest) => {
+                let (owner, path) = split_pubky_url(rest)?;
+                PubkyResource {
+                    owner,
+                    path: ResourcePath::parse_encoded(path)?,
+                }
+            }
+            None => PubkyResource::from_str(s)?,
+        };
+        resource.try_into()
+    }
+
+    /// The resource owner's public key.
+    #[must_use]
+    pub const fn owner(&self) -> &PublicKey {
+        &self.resource.owner
+    }
+
+    /// The normalized absolute path.
+    #[must_use]
+    pub const fn path(&self) -> &ResourcePath {
+        &self.resource.path
+    }
+
+    /// Borrow as a [`Url`].
+    #[must_use]
+    pub const fn as_url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Borrow the URL as `&str`.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        self.url.as_str()
+    }
+
+    /// Convert into the addressed resource (owner + path).
+    #[must_use]
+    pub fn into_resource(self) -> PubkyResource {
+        self.resource
+    }
+}
+
+impl TryFrom<PubkyResource> for PubkyUrl {
+    type Error = Error;
+
+    fn try_from(resource: PubkyResource) -> Result<Self, Self::Error> {
+        let url = Url::parse(&resource.to_pubky_url())?;
+        Ok(Self { resource, url })
+    }
+}
+
+impl From<PubkyUrl> for Url {
+    fn from(url: PubkyUrl) -> Self {
+        url.url
+    }
+}
+
+impl FromStr for PubkyUrl {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for PubkyUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Builds a [`PubkyUrl`] from an owner and a path; see [`PubkyUrl::builder`].
+///
+/// The path defaults to the root `/`. Validation happens in [`Self::build`].
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct PubkyUrlBuilder {
+    owner: PublicKey,
+    path: String,
+}
+
+impl PubkyUrlBuilder {
+    /// Set the path, with or without a leading `/` (e.g. `"/pub/app/x.txt"`).
+    pub fn path<S: Into<String>>(mut self, path: S) -> Self {
+        self.path = path.into();
+        self
+    }
+
+    /// Validate and canonicalize into a [`PubkyUrl`].
+    ///
+    /// # Errors
+    /// - Returns [`Error::Request`] if the path is empty or contains `//`, `.` or `..` segments.
+    pub fn build(self) -> Result<PubkyUrl, Error> {
+        PubkyResource::new(self.owner, self.path)?.try_into()
+    }
+}
+
 /// Resolve a Pubky identifier (either `pubky://` or `pubky<pk>/…`) into a transport URL.
 ///
 /// Returns the same URL as [`PubkyResource::to_transport_url`], making it easy to
@@ -324,6 +638,110 @@ pub fn resolve_pubky<S: AsRef<str>>(input: S) -> Result<Url, Error> {
     resource.to_transport_url()
 }
 
+/// Canonicalize a link to a user's resource into a single `pubky://<owner>/<abs-path>` form,
+/// so equivalent links can be deduplicated or used as cache keys.
+///
+/// Accepted inputs, with any scheme letter case:
+/// - `pubky://<owner>/<path>` and the identifier form `pubky<owner>/<path>`
+/// - `https://<owner>/<path>`, `https://_pubky.<owner>/<path>` and their `http://` variants
+/// - a bare `<owner>/<path>` or `<owner>`
+///
+/// The owner may be given as raw z32 or with its `pubky` prefix. It is lowercased, runs of `/` in the path are collapsed, and the path is
+/// percent-normalized: escapes of unreserved characters (`A-Z a-z 0-9 - . _ ~`) are
+/// decoded, other escapes get uppercase hex digits, and characters that need escaping
+/// are encoded. A trailing `/` is kept, an empty path becomes `/`, and any query or
+/// fragment is dropped since it does not address a different resource.
+///
+/// ### Examples
+/// ```
+/// # use pubky::{Keypair, canonicalize_url};
+/// let z32 = Keypair::random().public_key().z32();
+/// let canonical = canonicalize_url(&format!("pubky://{z32}/pub/app/x.txt"))?;
+/// for link in [
+///     format!("https://{z32}/pub//app/x.txt"),
+///     format!("HTTPS://_pubky.{}/pub/app/%78.txt", z32.to_uppercase()),
+///     format!("{z32}/pub/app/x.txt#top"),
+/// ] {
+///     assert_eq!(canonicalize_url(&link)?, canonical);
+/// }
+/// # Ok::<(), pubky::Error>(())
+/// ```
+///
+/// # Errors
+/// - Returns [`Error::Request`] if the input has none of the accepted forms, the owner
+///   is not a valid public key, or the path contains `.`/`..` segments or exceeds the
+///   path limits of [`ResourcePath`].
+pub fn canonicalize_url(input: &str) -> Result<Url, Error> {
+    let input = input.trim();
+    let rest = match input.split_once("://") {
+        Some((scheme, rest)) => match scheme.to_ascii_lowercase().as_str() {
+            "pubky" => rest,
+            "http" | "https" => strip_pubky_subdomain(rest),
+            _ => return Err(invalid(format!("unsupported URL scheme: {scheme}"))),
+        },
+        None => input,
+    };
+    let rest = rest.split(['?', '#']).next().unwrap_or_default();
+    let (owner, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+    let mut owner = owner.to_ascii_lowercase();
+    if PublicKey::is_pubky_prefixed(&owner) {
+        owner.drain(..5);
+    }
+    let owner = PublicKey::try_from_z32(&owner)
+        .map_err(|_err| invalid(format!("invalid user public key: {owner}")))?;
+
+    let mut collapsed = String::from("/");
+    for c in path.chars() {
+        if !(c == '/' && collapsed.ends_with('/')) {
+            collapsed.push(c);
+        }
+    }
+    let path = ResourcePath::parse_encoded(&normalize_escapes(&collapsed))?;
+    Ok(PubkyUrl::try_from(PubkyResource { owner, path })?.into())
+}
+
+/// Drop the `_pubky.` label (any case) in front of a transport URL's host.
+fn strip_pubky_subdomain(rest: &str) -> &str {
+    match rest.get(..7) {
+        Some(label) if label.eq_ignore_ascii_case("_pubky.") => &rest[7..],
+        _ => rest,
+    }
+}
+
+/// Decode `%XX` escapes of unreserved characters and uppercase the hex digits of the
+/// others (RFC 3986 §6.2.2). A `%` that starts no valid escape is escaped itself.
+fn normalize_escapes(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut out = String::with_capacity(path.len());
+    let mut i = 0;
+    while let Some(offset) = path[i..].find('%') {
+        out.push_str(&path[i..i + offset]);
+        i += offset;
+        let hex = bytes.get(i + 1..i + 3).and_then(|hex| {
+            let hex = std::str::from_utf8(hex).ok()?;
+            u8::from_str_radix(hex, 16)
+                .ok()
+                .filter(|_| hex.bytes().all(|b| b.is_ascii_hexdigit()))
+        });
+        match hex {
+            Some(byte) if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) => {
+                out.push(char::from(byte));
+                i += 3;
+            }
+            Some(byte) => {
+                let _ = write!(out, "%{byte:02X}");
+                i += 3;
+            }
+            None => {
+                out.push_str("%25");
+                i += 1;
+            }
+        }
+    }
+    out.push_str(&path[i..]);
+    out
+}
+
 // ============================================================================
 // Conversion traits
 // ============================================================================
@@ -390,6 +808,7 @@ impl IntoResourcePath for &String {
 /// Implementations:
 /// - `PubkyResource` / `&PubkyResource` (pass-through / clone)
 /// - `&str`, `String`, `&String` parsed as `pubky<pk>/<abs-path>` or `pubky://<pk>/<abs-path>`
+/// - `PubkyUrl` / `&PubkyUrl` (its owner and path)
 /// - `(PublicKey, P: AsRef<str>)` and `(&PublicKey, P: AsRef<str>)` to pair a key with a path
 ///
 /// This trait is used by *public* storage methods (`PublicStorage`) and any API that must
@@ -446,6 +865,18 @@ impl IntoPubkyResource for &String {
         PubkyResource::from_str(self.as_str())
     }
 }
+impl IntoPubkyResource for PubkyUrl {
+    #[inline]
+    fn into_pubky_resource(self) -> Result<PubkyResource, Error> {
+        Ok(self.resource)
+    }
+}
+impl IntoPubkyResource for &PubkyUrl {
+    #[inline]
+    fn into_pubky_resource(self) -> Result<PubkyResource, Error> {
+        Ok(self.resource.clone())
+    }
+}
 impl<P: AsRef<str>> IntoPubkyResource for (PublicKey, P) {
     fn into_pubky_resource(self) -> Result<PubkyResource, Error> {
         PubkyResource::new(self.0, self.1.as_ref())
@@ -462,6 +893,18 @@ mod tests {
     use super::*;
     use crate::Keypair;
 
+    #[test]
+    fn private_paths() {
+        assert!(
+            ResourcePath::parse("/priv/app/secret")
+                .unwrap()
+                .is_private()
+        );
+        assert!(ResourcePath::parse("priv/app/").unwrap().is_private());
+        assert!(!ResourcePath::parse("/pub/app/file").unwrap().is_private());
+        assert!(!ResourcePath::parse("/private/file").unwrap().is_private());
+    }
+
     #[test]
     fn file_path_normalization_and_rejections() {
         // Normalize relative
@@ -486,6 +929,56 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn path_length_and_segment_limits() {
+        let deep = "/a".repeat(DEFAULT_MAX_PATH_SEGMENTS);
+        ResourcePath::parse(&deep).unwrap();
+        ResourcePath::parse(format!("{deep}/")).unwrap();
+        assert!(matches!(
+            ResourcePath::parse(format!("{deep}/a")),
+            Err(Error::Request(RequestError::Validation { .. }))
+        ));
+
+        // The length is counted after percent-decoding, as on the homeserver.
+        let long = format!("/pub/{}", "a".repeat(DEFAULT_MAX_PATH_LENGTH - 5));
+        ResourcePath::parse(&long).unwrap();
+        let spaces = format!("/pub/{}", " ".repeat(DEFAULT_MAX_PATH_LENGTH - 5));
+        ResourcePath::parse(&spaces).unwrap();
+        assert!(matches!(
+            ResourcePath::parse(format!("{long}b")),
+            Err(Error::Request(RequestError::Validation { .. }))
+        ));
+    }
+
+    #[tokio::test]
+    async fn content_addressed_paths_are_sharded_by_hash() {
+        let content = b"content addressed".repeat(10_000);
+        let hash = pubky_common::crypto::hash(&content);
+        let hex = hash.to_hex();
+
+        let path = ResourcePath::content_addressed("pub/app/blobs", &content).unwrap();
+        assert_eq!(
+            path.as_str(),
+            format!("/pub/app/blobs/{}/{}/{hex}", &hex[0..2], &hex[2..4])
+        );
+        // A trailing slash on the prefix and the streaming variant give the same path.
+        assert_eq!(
+            ResourcePath::for_content_hash("/pub/app/blobs/", &hash).unwrap(),
+            path
+        );
+        assert_eq!(
+            ResourcePath::content_addressed_stream("/pub/app/blobs/", content.as_slice())
+                .await
+                .unwrap(),
+            path
+        );
+
+        assert!(matches!(
+            ResourcePath::content_addressed("/pub//blobs", &content),
+            Err(Error::Request(RequestError::Validation { .. }))
+        ));
+    }
+
     #[test]
     fn parse_addressed_user_both_forms() {
         let kp = Keypair::random();
@@ -598,4 +1091,151 @@ mod tests {
         let parsed_http = PubkyResource::from_transport_url(&http_url).unwrap();
         assert_eq!(parsed_http, resource);
     }
+
+    #[test]
+    fn dav_url_names_the_owner_in_the_path() {
+        let owner = Keypair::random().public_key();
+        let homeserver = Keypair::random().public_key();
+        let resource = PubkyResource::new(owner.clone(), "/pub/site/index.html").unwrap();
+        assert_eq!(
+            resource.to_dav_url(&homeserver).unwrap().as_str(),
+            format!(
+                "https://{}/dav/{}/pub/site/index.html",
+                homeserver.z32(),
+                owner.z32()
+            )
+        );
+    }
+
+    #[test]
+    fn pubky_url_build_and_parse_round_trip() {
+        let user = Keypair::random().public_key();
+        let expected = format!("pubky://{}/pub/app/My%20File.txt", user.z32());
+
+        for path in ["/pub/app/My File.txt", "pub/app/My File.txt"] {
+            let built = PubkyUrl::builder(user.clone()).path(path).build().unwrap();
+            assert_eq!(built.as_str(), expected);
+
+            let parsed = PubkyUrl::parse(built.as_str()).unwrap();
+            assert_eq!(parsed, built);
+            assert_eq!(PubkyUrl::parse(parsed.to_string()).unwrap(), built);
+        }
+
+        // Unencoded characters in a `pubky://` URL are encoded once.
+        let raw = format!("pubky://{}/pub/app/My File.txt", user.z32());
+        assert_eq!(PubkyUrl::parse(raw).unwrap().as_str(), expected);
+
+        // Identifier form parses to the same URL; directories and the root survive.
+        let parsed = PubkyUrl::parse(format!("{user}/pub/app/My File.txt")).unwrap();
+        assert_eq!(parsed.as_str(), expected);
+        for path in ["/pub/app/", "/"] {
+            let built = PubkyUrl::builder(user.clone()).path(path).build().unwrap();
+            assert_eq!(built.path().as_str(), path);
+            assert_eq!(PubkyUrl::parse(built.to_string()).unwrap(), built);
+        }
+        assert_eq!(
+            PubkyUrl::builder(user.clone())
+                .build()
+                .unwrap()
+                .path()
+                .as_str(),
+            "/"
+        );
+
+        let url: Url = parsed.clone().into();
+        assert_eq!(url.scheme(), "pubky");
+        assert_eq!(
+            parsed.into_pubky_resource().unwrap(),
+            PubkyResource::new(user, "/pub/app/My File.txt").unwrap()
+        );
+    }
+
+    #[test]
+    fn pubky_url_rejects_malformed_input() {
+        let user = Keypair::random().public_key();
+        for path in ["", "/pub//app", "/pub/../priv/x"] {
+            assert!(matches!(
+                PubkyUrl::builder(user.clone()).path(path).build(),
+                Err(Error::Request(RequestError::Validation { .. }))
+            ));
+        }
+        for input in [
+            "/pub/app/x.txt".to_string(),
+            "https://example.com/pub/app/x.txt".to_string(),
+            "pubky://not-a-key/pub/app/x.txt".to_string(),
+            format!("pubky://{}", user.z32()),
+            format!("pubky://{}/pub//x.txt", user.z32()),
+            format!("pubky://{}/pub/%2E%2e/x.txt", user.z32()),
+        ] {
+            assert!(matches!(
+                PubkyUrl::parse(&input),
+                Err(Error::Request(RequestError::Validation { .. }))
+            ));
+        }
+    }
+
+    #[test]
+    fn canonicalize_url_unifies_equivalent_links() {
+        let user = Keypair::random().public_key();
+        let z32 = user.z32();
+        let upper = z32.to_uppercase();
+        let canonical = format!("pubky://{z32}/pub/app/caf%C3%A9%20x~1.txt");
+        for input in [
+            format!("pubky://{z32}/pub/app/café x~1.txt"),
+            format!("PUBKY://{upper}/pub/app/caf%c3%a9%20x%7E1.txt"),
+            format!("pubky{z32}/pub/app/caf%C3%A9%20x~1.txt"),
+            format!("pubky://pubky{z32}/pub/app/caf%C3%A9%20x~1.txt"),
+            format!("https://{z32}/pub//app/caf%C3%A9%20x~1.txt"),
+            format!("Http://_PUBKY.{z32}//pub/app/caf%C3%A9%20%78~1.txt"),
+            format!("{upper}/pub/app///caf%C3%A9%20x~1.txt?download#top"),
+            format!("  {z32}/pub/app/caf%C3%A9%20x~1.txt  "),
+        ] {
+            assert_eq!(
+                canonicalize_url(&input).unwrap().as_str(),
+                canonical,
+                "{input}"
+            );
+        }
+
+        // Reserved escapes stay escaped, trailing slashes stay, empty paths become `/`.
+        assert_eq!(
+            canonicalize_url(&format!("{z32}/pub/a%2fb/"))
+                .unwrap()
+                .as_str(),
+            format!("pubky://{z32}/pub/a%2Fb/")
+        );
+        assert_eq!(
+            canonicalize_url(&format!("{z32}/pub/100%"))
+                .unwrap()
+                .as_str(),
+            format!("pubky://{z32}/pub/100%25")
+        );
+        for root in [
+            z32.clone(),
+            format!("pubky://{z32}"),
+            format!("https://{z32}/"),
+        ] {
+            assert_eq!(
+                canonicalize_url(&root).unwrap().as_str(),
+                format!("pubky://{z32}/"),
+                "{root}"
+            );
+        }
+
+        for input in [
+            "/pub/app/x.txt".to_string(),
+            "https://example.com/pub/app/x.txt".to_string(),
+            format!("ftp://{z32}/pub/app/x.txt"),
+            format!("pubky://{z32}/pub/../x.txt"),
+            format!("pubky://{z32}/pub/%2E%2e/x.txt"),
+        ] {
+            assert!(
+                matches!(
+                    canonicalize_url(&input),
+                    Err(Error::Request(RequestError::Validation { .. }))
+                ),
+                "{input}"
+            );
+        }
+    }
 }
@@ -1,5 +1,17 @@
+use pubky_common::crypto::Hash;
+use pubky_common::storage::{DirStatsResponse, FileMetadataResponse};
 use reqwest::header::{CONTENT_LENGTH, CONTENT_TYPE, ETAG, HeaderMap, LAST_MODIFIED};
-use std::time::SystemTime;
+use reqwest::{Method, RequestBuilder, StatusCode};
+use std::time::{Duration, SystemTime};
+use url::Url;
+
+use super::core::{PublicStorage, SessionStorage, dir_trailing_slash_error, reject_private};
+use super::resource::{IntoPubkyResource, IntoResourcePath, PubkyResource, ResourcePath};
+use super::verbs::decode_hash;
+use crate::client::response::content_disposition_filename;
+use crate::errors::RequestError;
+use crate::util::check_http_status;
+use crate::{PubkyHttpClient, Result, cross_log};
 
 /// Typed metadata for a stored object (from a `HEAD` request).
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -12,6 +24,9 @@ pub struct ResourceStats {
     pub last_modified: Option<SystemTime>,
     /// `ETag` string.
     pub etag: Option<String>,
+    /// Download filename from `Content-Disposition`, see
+    /// [`SessionStorage::put_as_attachment`].
+    pub filename: Option<String>,
 }
 
 impl ResourceStats {
@@ -34,15 +49,244 @@ impl ResourceStats {
 
         let etag = h.get(ETAG).and_then(|v| v.to_str().ok()).map(clean_etag);
 
+        let filename = content_disposition_filename(h);
+
         Self {
             content_length,
             content_type,
             last_modified,
             etag,
+            filename,
+        }
+    }
+}
+
+/// Aggregate metadata for a directory, computed by the homeserver.
+///
+/// Returned by [`SessionStorage::dir_stats`] and [`PublicStorage::dir_stats`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DirStats {
+    /// Sum of the sizes of all counted files, in bytes.
+    pub total_bytes: u64,
+    /// Number of counted files.
+    pub file_count: u64,
+    /// Modification time of the least recently modified file.
+    pub oldest_modified: Option<SystemTime>,
+    /// Modification time of the most recently modified file.
+    pub newest_modified: Option<SystemTime>,
+}
+
+impl From<DirStatsResponse> for DirStats {
+    fn from(response: DirStatsResponse) -> Self {
+        let to_time = |micros: u64| SystemTime::UNIX_EPOCH + Duration::from_micros(micros);
+        Self {
+            total_bytes: response.total_bytes,
+            file_count: response.file_count,
+            oldest_modified: response.oldest_modified_at.map(to_time),
+            newest_modified: response.newest_modified_at.map(to_time),
         }
     }
 }
 
+/// Metadata of a file as stored by the homeserver, including its content hash.
+///
+/// Returned by [`SessionStorage::metadata`] and [`PublicStorage::metadata`]. All
+/// fields other than `exists` are `None` when the file does not exist.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FileMetadata {
+    /// Whether the file exists.
+    pub exists: bool,
+    /// Size of the content in bytes.
+    pub size: Option<u64>,
+    /// Content type stored with the file.
+    pub content_type: Option<String>,
+    /// BLAKE3 hash of the content.
+    pub content_hash: Option<Hash>,
+    /// Modification time of the file.
+    pub modified: Option<SystemTime>,
+}
+
+impl TryFrom<FileMetadataResponse> for FileMetadata {
+    type Error = crate::Error;
+
+    fn try_from(response: FileMetadataResponse) -> Result<Self> {
+        Ok(Self {
+            exists: response.exists,
+            size: response.size,
+            content_type: response.content_type,
+            content_hash: response
+                .content_hash
+                .as_deref()
+                .map(decode_hash)
+                .transpose()?,
+            modified: response
+                .modified
+                .map(|micros| SystemTime::UNIX_EPOCH + Duration::from_micros(micros)),
+        })
+    }
+}
+
+impl SessionStorage {
+    /// Metadata of a file **as me**, including the content hash the homeserver stores.
+    ///
+    /// Unlike [`Self::stats`], which reads the headers of a `HEAD` request, this
+    /// reports the stored hash directly and tells a missing file apart in the same
+    /// response, so checking whether a local copy is up to date costs one request
+    /// and no download.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async fn ex(session: pubky::PubkySession) -> pubky::Result<()> {
+    /// let storage = session.storage();
+    /// let stored = storage.put("/pub/my-app/hello.txt", "hello").await?;
+    /// // Later: has another device replaced the file since?
+    /// let remote = storage.metadata("/pub/my-app/hello.txt").await?;
+    /// if remote.content_hash != Some(stored.content_hash) {
+    ///     println!("changed remotely (exists: {})", remote.exists);
+    /// }
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// - Returns [`RequestError::Validation`] if `path` ends with `/`, or if the
+    ///   homeserver reports a malformed content hash.
+    /// - Propagates transport failures, non-success statuses or JSON decoding errors from
+    ///   the underlying HTTP request.
+    pub async fn metadata<P: IntoResourcePath>(&self, path: P) -> Result<FileMetadata> {
+        let path: ResourcePath = path.into_abs_path()?;
+        if path.as_str().ends_with('/') {
+            return Err(file_path_error().into());
+        }
+        self.ensure_scoped_read(&path)?;
+        let url = metadata_url(self.url_for(&path)?);
+        let rb = self.client.cross_request(Method::GET, url).await?;
+        send_metadata(&self.client, self.attach_credential(rb).await?).await
+    }
+
+    /// Aggregate stats (total size, file count, modification time range) of a directory **as me**.
+    ///
+    /// Computed by the homeserver from its index, so no listing is downloaded.
+    /// With `shallow`, only files directly inside the directory are counted;
+    /// otherwise the whole subtree is, mirroring [`ListBuilder::shallow`](super::list::ListBuilder::shallow).
+    ///
+    /// Returns `None` if the directory does not exist.
+    ///
+    /// # Errors
+    /// - Returns [`crate::errors::RequestError::Validation`] if `path` does not end with `/`.
+    /// - Propagates transport failures or JSON decoding errors from the underlying HTTP request.
+    pub async fn dir_stats<P: IntoResourcePath>(
+        &self,
+        path: P,
+        shallow: bool,
+    ) -> Result<Option<DirStats>> {
+        let path: ResourcePath = path.into_abs_path()?;
+        if !path.as_str().ends_with('/') {
+            return Err(dir_trailing_slash_error().into());
+        }
+        self.ensure_scoped_read(&path)?;
+        let url = dir_stats_url(self.url_for(&path)?, shallow);
+        let rb = self.client.cross_request(Method::GET, url).await?;
+        send_dir_stats(&self.client, self.attach_credential(rb).await?).await
+    }
+}
+
+impl PublicStorage {
+    /// Metadata of an addressed file, including the content hash the homeserver stores.
+    ///
+    /// See [`SessionStorage::metadata`].
+    ///
+    /// # Errors
+    /// - Returns [`RequestError::Validation`] if `addr` ends with `/`, addresses a
+    ///   private path, or if the homeserver reports a malformed content hash.
+    /// - Propagates transport failures, non-success statuses or JSON decoding errors from
+    ///   the underlying HTTP request.
+    pub async fn metadata<A: IntoPubkyResource>(&self, addr: A) -> Result<FileMetadata> {
+        let resource: PubkyResource = addr.into_pubky_resource()?;
+        if resource.path.as_str().ends_with('/') {
+            return Err(file_path_error().into());
+        }
+        reject_private(&resource)?;
+        let url = metadata_url(resource.to_transport_url()?);
+        let rb = self.client.cross_request(Method::GET, url).await?;
+        send_metadata(&self.client, rb).await
+    }
+
+    /// Aggregate stats (total size, file count, modification time range) of an addressed directory.
+    ///
+    /// See [`SessionStorage::dir_stats`] for the meaning of `shallow`.
+    ///
+    /// Returns `None` if the directory does not exist.
+    ///
+    /// # Errors
+    /// - Returns [`crate::errors::RequestError::Validation`] if `addr` does not end with `/`.
+    /// - Propagates transport failures or JSON decoding errors from the underlying HTTP request.
+    pub async fn dir_stats<A: IntoPubkyResource>(
+        &self,
+        addr: A,
+        shallow: bool,
+    ) -> Result<Option<DirStats>> {
+        let resource: PubkyResource = addr.into_pubky_resource()?;
+        if !resource.path.as_str().ends_with('/') {
+            return Err(dir_trailing_slash_error().into());
+        }
+        reject_private(&resource)?;
+        let url = dir_stats_url(resource.to_transport_url()?, shallow);
+        let rb = self.client.cross_request(Method::GET, url).await?;
+        send_dir_stats(&self.client, rb).await
+    }
+}
+
+fn dir_stats_url(mut url: Url, shallow: bool) -> Url {
+    {
+        let mut q = url.query_pairs_mut();
+        q.append_key_only("stats");
+        if shallow {
+            q.append_key_only("shallow");
+        }
+    }
+    url
+}
+
+async fn send_dir_stats(client: &PubkyHttpClient, rb: RequestBuilder) -> Result<Option<DirStats>> {
+    let resp = client.send(rb).await?;
+    cross_log!(
+        debug,
+        "Request completed with status {} (DIR STATS {})",
+        resp.status(),
+        resp.url()
+    );
+    if resp.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    let resp = check_http_status(resp).await?;
+    let response: DirStatsResponse = resp.json().await?;
+    Ok(Some(response.into()))
+}
+
+fn metadata_url(mut url: Url) -> Url {
+    url.query_pairs_mut().append_key_only("metadata");
+    url
+}
+
+async fn send_metadata(client: &PubkyHttpClient, rb: RequestBuilder) -> Result<FileMetadata> {
+    let resp = client.send(rb).await?;
+    cross_log!(
+        debug,
+        "Request completed with status {} (METADATA {})",
+        resp.status(),
+        resp.url()
+    );
+    let resp = check_http_status(resp).await?;
+    let response: FileMetadataResponse = resp.json().await?;
+    response.try_into()
+}
+
+fn file_path_error() -> RequestError {
+    RequestError::Validation {
+        message: "file metadata paths must not end with `/`".into(),
+    }
+}
+
 fn clean_etag(raw: &str) -> String {
     let s = raw.trim();
 
@@ -1,9 +1,35 @@
-use reqwest::{Method, RequestBuilder, Response, StatusCode};
+use std::time::Duration;
 
-use super::core::{PublicStorage, SessionStorage};
-use super::resource::{IntoPubkyResource, IntoResourcePath};
+use base64::Engine;
+use futures_util::future::{Either, select};
+use futures_util::{Stream, StreamExt};
+use pubky_common::crypto::Hash;
+use pubky_common::storage::{
+    APPEND_OFFSET_HEADER, DeleteDirResponse, PutResponse, TTL_HEADER, attachment_disposition,
+};
+use reqwest::{
+    Method, RequestBuilder, Response, StatusCode,
+    header::{CONTENT_DISPOSITION, CONTENT_RANGE},
+};
+
+use super::cancel::CancelToken;
+use super::core::{PublicStorage, SessionStorage, dir_trailing_slash_error};
+use super::resource::{IntoPubkyResource, IntoResourcePath, PubkyResource, PubkyUrl, ResourcePath};
 use super::stats::ResourceStats;
-use crate::{Result, cross_log, util::check_http_status};
+use crate::{
+    Pkdns, PubkyHttpClient, PubkyResponse, Result,
+    client::response::DEFAULT_MAX_LINE_BYTES,
+    cross_log,
+    errors::{Error, RequestError},
+    util::check_http_status,
+};
+
+/// Maximum number of deletes [`SessionStorage::delete_many`] sends at the same time.
+pub const DELETE_MANY_CONCURRENCY: usize = 8;
+
+/// Part of the `400 Bad Request` message homeservers without directory deletes answer
+/// a `DELETE` of a directory path with, as their delete route only takes file paths.
+const DIR_DELETE_UNSUPPORTED: &str = "target path must be a file";
 
 /// Interpret the result of a `HEAD` request into a shared outcome used by both
 /// session and public storage clients.
@@ -20,16 +46,177 @@ async fn interpret_head(resp: Response) -> Result<Option<Response>> {
     }
 }
 
+/// What a successful [`SessionStorage::put`] stored.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PutResult {
+    /// Canonical `pubky://<user>/<path>` URL of the written resource.
+    pub url: PubkyUrl,
+    /// BLAKE3 hash of the stored content.
+    pub content_hash: Hash,
+    /// Size of the stored content in bytes.
+    pub size: u64,
+}
+
+/// How far a [`SessionStorage::delete_dir_cancellable`] got.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DeleteDirProgress {
+    /// Number of files deleted before the operation finished or stopped.
+    pub deleted: u64,
+    /// Whether the operation stopped early because its [`CancelToken`] was cancelled.
+    ///
+    /// When `true`, files that were not deleted yet are still in place and calling
+    /// [`SessionStorage::delete_dir_cancellable`] again picks up where it stopped.
+    pub cancelled: bool,
+}
+
+/// Decode a base64 content hash as reported by the homeserver.
+pub(crate) fn decode_hash(content_hash: &str) -> Result<Hash> {
+    let invalid = || RequestError::Validation {
+        message: format!("invalid content hash reported by the homeserver: {content_hash}"),
+    };
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(content_hash)
+        .map_err(|_err| invalid())?;
+    let bytes: [u8; 32] = bytes.try_into().map_err(|_err| invalid())?;
+    Ok(Hash::from_bytes(bytes))
+}
+
+/// Chunk size used when streaming a reader into a request body.
+#[cfg(not(target_arch = "wasm32"))]
+const STREAM_CHUNK: usize = 64 * 1024;
+
+/// Running BLAKE3 hash and byte count of a streamed body.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Default)]
+struct StreamDigest {
+    state: std::sync::Arc<std::sync::Mutex<(pubky_common::crypto::Hasher, u64)>>,
+    /// Set once a [`teed_body`] was read to the end and flushed locally.
+    complete: std::sync::Arc<std::sync::atomic::AtomicBool>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl StreamDigest {
+    fn update(&self, chunk: &[u8]) {
+        let mut state = self
+            .state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        state.0.update(chunk);
+        state.1 += chunk.len() as u64;
+    }
+
+    fn finish(&self) -> (Hash, u64) {
+        let state = self
+            .state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        (state.0.finalize(), state.1)
+    }
+
+    fn set_complete(&self) {
+        self.complete
+            .store(true, std::sync::atomic::Ordering::Release);
+    }
+
+    fn is_complete(&self) -> bool {
+        self.complete.load(std::sync::atomic::Ordering::Acquire)
+    }
+}
+
+/// Wrap `reader` into a streaming request body that feeds every chunk sent through
+/// the returned [`StreamDigest`].
+#[cfg(not(target_arch = "wasm32"))]
+fn hashed_body<R>(reader: R) -> (reqwest::Body, StreamDigest)
+where
+    R: tokio::io::AsyncRead + Send + 'static,
+{
+    use tokio::io::AsyncReadExt;
+
+    let digest = StreamDigest::default();
+    let sink = digest.clone();
+    let chunks = futures_util::stream::try_unfold(Box::pin(reader), move |mut reader| {
+        let sink = sink.clone();
+        async move {
+            let mut buf = bytes::BytesMut::with_capacity(STREAM_CHUNK);
+            if reader.read_buf(&mut buf).await? == 0 {
+                return Ok::<_, std::io::Error>(None);
+            }
+            sink.update(&buf);
+            Ok(Some((buf.freeze(), reader)))
+        }
+    });
+    (reqwest::Body::wrap_stream(chunks), digest)
+}
+
+/// Like [`hashed_body`], also writing every chunk to `file` (at `path`, for errors)
+/// before it is sent. A failed local write fails the body, aborting the upload.
+#[cfg(not(target_arch = "wasm32"))]
+fn teed_body<R>(
+    reader: R,
+    file: tokio::fs::File,
+    path: std::path::PathBuf,
+) -> (reqwest::Body, StreamDigest)
+where
+    R: tokio::io::AsyncRead + Send + 'static,
+{
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let digest = StreamDigest::default();
+    let sink = digest.clone();
+    let state = (Box::pin(reader), file);
+    let chunks = futures_util::stream::try_unfold(state, move |(mut reader, mut file)| {
+        let sink = sink.clone();
+        let path = path.clone();
+        async move {
+            let local = |e: std::io::Error| {
+                std::io::Error::new(e.kind(), format!("writing {}: {e}", path.display()))
+            };
+            let mut buf = bytes::BytesMut::with_capacity(STREAM_CHUNK);
+            if reader.read_buf(&mut buf).await? == 0 {
+                file.flush().await.map_err(local)?;
+                file.sync_all().await.map_err(local)?;
+                sink.set_complete();
+                return Ok::<_, std::io::Error>(None);
+            }
+            file.write_all(&buf).await.map_err(local)?;
+            sink.update(&buf);
+            Ok(Some((buf.freeze(), (reader, file))))
+        }
+    });
+    (reqwest::Body::wrap_stream(chunks), digest)
+}
+
+/// Validation error for a failed write of the local copy at `path`.
+#[cfg(not(target_arch = "wasm32"))]
+fn local_write_error(path: &std::path::Path, err: &std::io::Error) -> Error {
+    RequestError::Validation {
+        message: format!("failed to write {}: {err}", path.display()),
+    }
+    .into()
+}
+
 /// Send a prepared request and ensure the HTTP status indicates success.
-async fn send_checked(rb: RequestBuilder) -> Result<Response> {
-    let resp = rb.send().await?;
+async fn send_checked(client: &PubkyHttpClient, rb: RequestBuilder) -> Result<Response> {
+    let resp = client.send(rb).await?;
     cross_log!(debug, "Request completed with status {}", resp.status());
     check_http_status(resp).await
 }
 
+/// Current length of the file from the `Content-Range: bytes */<length>` header of a
+/// conflicting append.
+fn conflicting_length(resp: &Response) -> Option<u64> {
+    resp.headers()
+        .get(CONTENT_RANGE)?
+        .to_str()
+        .ok()?
+        .strip_prefix("bytes */")?
+        .parse()
+        .ok()
+}
+
 /// Send a prepared `HEAD` request and interpret the outcome.
-async fn send_head(rb: RequestBuilder) -> Result<Option<Response>> {
-    let resp = rb.send().await?;
+async fn send_head(client: &PubkyHttpClient, rb: RequestBuilder) -> Result<Option<Response>> {
+    let resp = client.send(rb).await?;
     cross_log!(
         debug,
         "HEAD request completed with status {}",
@@ -60,19 +247,66 @@ impl SessionStorage {
     ///   responds with a non-success status (the server message is captured).
     /// - [`crate::errors::Error::Parse`] if `path` cannot be converted into a valid
     ///   resource/URL.
-    pub async fn get<P: IntoResourcePath>(&self, path: P) -> Result<Response> {
-        let rb = self.request(Method::GET, path).await?;
-        send_checked(rb).await
+    pub async fn get<P: IntoResourcePath>(&self, path: P) -> Result<PubkyResponse> {
+        let rb = self.build_request(Method::GET, path).await?;
+        Ok(send_checked(&self.client, rb).await?.into())
+    }
+
+    /// `GET` a text resource at an **absolute path** and stream it line by line, e.g.
+    /// NDJSON feeds or logs too large to hold in memory.
+    ///
+    /// Lines longer than [`DEFAULT_MAX_LINE_BYTES`] end the stream with an error; use
+    /// [`PubkyResponse::lines`] on the response of [`Self::get`] to choose another limit.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async fn ex(session: pubky::PubkySession) -> pubky::Result<()> {
+    /// use futures_util::StreamExt;
+    ///
+    /// let lines = session.storage().get_lines("/pub/my-cool-app/log.ndjson").await?;
+    /// futures_util::pin_mut!(lines);
+    /// while let Some(line) = lines.next().await {
+    ///     let line = line?;
+    ///     if let Ok(entry) = serde_json::from_str::<serde_json::Value>(&line) {
+    ///         println!("{entry}");
+    ///     }
+    /// }
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// - Same as [`Self::get`] for the request itself.
+    /// - The stream yields the errors of [`PubkyResponse::lines`].
+    pub async fn get_lines<P: IntoResourcePath>(
+        &self,
+        path: P,
+    ) -> Result<impl Stream<Item = Result<String>> + use<P>> {
+        Ok(self.get(path).await?.lines(DEFAULT_MAX_LINE_BYTES))
     }
 
     /// Lightweight existence check (HEAD) for an **absolute path**.
     ///
+    /// Returns `true` on a 2xx and `false` on `404 Not Found` or `410 Gone`. No body is
+    /// downloaded, so this is the cheap way to implement lazy-create patterns.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async fn ex(session: pubky::PubkySession) -> pubky::Result<()> {
+    /// let storage = session.storage();
+    /// if !storage.exists("/pub/my-cool-app/settings.json").await? {
+    ///     storage.put("/pub/my-cool-app/settings.json", "{}").await?;
+    /// }
+    /// # Ok(()) }
+    /// ```
+    ///
     /// # Errors
     /// - Propagates transport failures while issuing the `HEAD` request.
+    /// - [`crate::errors::Error::Request`] if the server responds with any other non-success
+    ///   status (e.g. `403 Forbidden`).
     /// - Returns [`crate::errors::Error::Parse`] if `path` cannot be converted into a valid resource.
     pub async fn exists<P: IntoResourcePath>(&self, path: P) -> Result<bool> {
-        let rb = self.request(Method::HEAD, path).await?;
-        Ok(send_head(rb).await?.is_some())
+        let rb = self.build_request(Method::HEAD, path).await?;
+        Ok(send_head(&self.client, rb).await?.is_some())
     }
 
     /// Retrieve metadata via `HEAD` for an **absolute path** (no body).
@@ -81,8 +315,8 @@ impl SessionStorage {
     /// - Propagates transport failures while issuing the `HEAD` request.
     /// - Returns [`crate::errors::Error::Parse`] if `path` cannot be converted into a valid resource.
     pub async fn stats<P: IntoResourcePath>(&self, path: P) -> Result<Option<ResourceStats>> {
-        let rb = self.request(Method::HEAD, path).await?;
-        Ok(send_head(rb)
+        let rb = self.build_request(Method::HEAD, path).await?;
+        Ok(send_head(&self.client, rb)
             .await?
             .map(|resp| ResourceStats::from_headers(resp.headers())))
     }
@@ -91,18 +325,462 @@ impl SessionStorage {
     ///
     /// Requires a valid session; this handle is authenticated already.
     ///
+    /// Returns the canonical URL, content hash and size of what was stored, ready to
+    /// reference the upload elsewhere. Homeservers that do not report them in the `PUT`
+    /// response cost one extra `HEAD` request.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async fn ex(session: pubky::PubkySession) -> pubky::Result<()> {
+    /// let stored = session
+    ///     .storage()
+    ///     .put("/pub/my-cool-app/avatar.png", vec![0u8; 128])
+    ///     .await?;
+    /// println!("{} ({} bytes)", stored.url, stored.size);
+    /// # Ok(()) }
+    /// ```
+    ///
     /// # Errors
     /// - [`crate::errors::Error::Request`] on HTTP transport failures or when the server
     ///   responds with a non-success status (the server message is captured).
+    /// - [`crate::errors::RequestError::UnsupportedMediaType`] if the homeserver's content
+    ///   policy refuses the content type or size.
     /// - [`crate::errors::Error::Parse`] if `path` cannot be converted into a valid
     ///   resource/URL.
-    pub async fn put<P, B>(&self, path: P, body: B) -> Result<Response>
+    pub async fn put<P, B>(&self, path: P, body: B) -> Result<PutResult>
     where
         P: IntoResourcePath,
         B: Into<reqwest::Body>,
     {
-        let rb = self.request(Method::PUT, path).await?.body(body);
-        send_checked(rb).await
+        let path: ResourcePath = path.into_abs_path()?;
+        let resp = self.put_response(&path, body).await?;
+        self.put_result(&path, resp).await
+    }
+
+    /// Like [`Self::put`], but returns the raw response.
+    pub(crate) async fn put_response<B: Into<reqwest::Body>>(
+        &self,
+        path: &ResourcePath,
+        body: B,
+    ) -> Result<Response> {
+        let rb = self.build_request(Method::PUT, path).await?.body(body);
+        send_checked(&self.client, rb).await
+    }
+
+    /// Read what was stored from a `PUT` response, falling back to a `HEAD` request
+    /// against homeservers that answer with an empty body.
+    async fn put_result(&self, path: &ResourcePath, resp: Response) -> Result<PutResult> {
+        let url = PubkyUrl::try_from(PubkyResource::new(self.user.clone(), path.as_str())?)?;
+        let body = resp.bytes().await?;
+        let (content_hash, size) = if body.is_empty() {
+            self.stats(path)
+                .await?
+                .map(|stats| (stats.etag, stats.content_length))
+                .unwrap_or_default()
+        } else {
+            let response: PutResponse =
+                serde_json::from_slice(&body).map_err(|e| RequestError::DecodeJson {
+                    message: e.to_string(),
+                })?;
+            (Some(response.content_hash), Some(response.content_length))
+        };
+        let (Some(content_hash), Some(size)) = (content_hash, size) else {
+            return Err(RequestError::Validation {
+                message: format!("homeserver did not report the hash and size of {url}"),
+            }
+            .into());
+        };
+        Ok(PutResult {
+            url,
+            content_hash: decode_hash(&content_hash)?,
+            size,
+        })
+    }
+
+    /// HTTP `PUT` (write) for an **absolute path** that expires after `ttl`.
+    ///
+    /// Until the deadline the resource behaves like any other. Afterwards the homeserver
+    /// answers `404 Not Found` for it, hides it from listings and eventually deletes it.
+    /// `ttl` is rounded up to whole seconds. Overwriting the resource with a plain
+    /// [`Self::put`] makes it permanent again.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async fn ex(session: pubky::PubkySession) -> pubky::Result<()> {
+    /// use std::time::Duration;
+    ///
+    /// session
+    ///     .storage()
+    ///     .put_with_ttl("/pub/my-cool-app/status.txt", "online", Duration::from_secs(300))
+    ///     .await?;
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// - [`crate::errors::Error::Request`] with [`RequestError::Validation`] if `ttl` is zero.
+    /// - [`crate::errors::Error::Request`] on HTTP transport failures or when the server
+    ///   responds with a non-success status (the server message is captured).
+    /// - [`crate::errors::Error::Parse`] if `path` cannot be converted into a valid
+    ///   resource/URL.
+    pub async fn put_with_ttl<P, B>(&self, path: P, body: B, ttl: Duration) -> Result<PutResult>
+    where
+        P: IntoResourcePath,
+        B: Into<reqwest::Body>,
+    {
+        if ttl.is_zero() {
+            return Err(RequestError::Validation {
+                message: "ttl must be greater than zero".into(),
+            }
+            .into());
+        }
+        let path: ResourcePath = path.into_abs_path()?;
+        let seconds = ttl.as_secs() + u64::from(ttl.subsec_nanos() > 0);
+        let rb = self
+            .build_request(Method::PUT, &path)
+            .await?
+            .header(TTL_HEADER, seconds)
+            .body(body);
+        let resp = send_checked(&self.client, rb).await?;
+        self.put_result(&path, resp).await
+    }
+
+    /// HTTP `PUT` (write) for an **absolute path** that downloads as `filename`.
+    ///
+    /// The homeserver stores the name and sends it back as
+    /// `Content-Disposition: attachment`, so browsers save the file under it; readers
+    /// get it from [`PubkyResponse::filename`] or [`ResourceStats::filename`].
+    /// Directory parts and control characters are stripped from `filename`, and
+    /// non-ASCII names are sent RFC 5987 encoded. Overwriting the resource with a
+    /// plain [`Self::put`] drops the name again.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async fn ex(session: pubky::PubkySession, pdf: Vec<u8>) -> pubky::Result<()> {
+    /// session
+    ///     .storage()
+    ///     .put_as_attachment("/pub/my-cool-app/files/3f9a", pdf, "Résumé 2026.pdf")
+    ///     .await?;
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// - [`crate::errors::Error::Request`] with [`RequestError::Validation`] if nothing
+    ///   usable remains of `filename` after sanitizing.
+    /// - [`crate::errors::Error::Request`] on HTTP transport failures or when the server
+    ///   responds with a non-success status (the server message is captured).
+    /// - [`crate::errors::Error::Parse`] if `path` cannot be converted into a valid
+    ///   resource/URL.
+    pub async fn put_as_attachment<P, B>(
+        &self,
+        path: P,
+        body: B,
+        filename: &str,
+    ) -> Result<PutResult>
+    where
+        P: IntoResourcePath,
+        B: Into<reqwest::Body>,
+    {
+        let disposition =
+            attachment_disposition(filename).ok_or_else(|| RequestError::Validation {
+                message: format!("{filename:?} is not a usable filename"),
+            })?;
+        let path: ResourcePath = path.into_abs_path()?;
+        let rb = self
+            .build_request(Method::PUT, &path)
+            .await?
+            .header(CONTENT_DISPOSITION, disposition)
+            .body(body);
+        let resp = send_checked(&self.client, rb).await?;
+        self.put_result(&path, resp).await
+    }
+
+    /// HTTP `PUT` (write) for an **absolute path**, streaming the body from `reader`
+    /// (native only).
+    ///
+    /// The BLAKE3 content hash and size are computed while the body is uploaded, so the
+    /// data is read once, never buffered in full, and no `HEAD` request follows. The hash
+    /// is the one the homeserver reports as the `content_hash` of the write event; if the
+    /// homeserver reports a different hash or size the write is considered failed.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async fn ex(session: pubky::PubkySession) -> pubky::Result<()> {
+    /// let file = tokio::fs::File::open("video.mp4").await.expect("readable file");
+    /// let stored = session
+    ///     .storage()
+    ///     .put_stream_hashed("/pub/my-cool-app/video.mp4", file)
+    ///     .await?;
+    /// println!("{} {} ({} bytes)", stored.url, stored.content_hash, stored.size);
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// - [`crate::errors::Error::Request`] on HTTP transport failures, including errors
+    ///   reading from `reader`, or when the server responds with a non-success status.
+    /// - [`crate::errors::RequestError::Validation`] if the homeserver reports a hash or
+    ///   size different from what was uploaded.
+    /// - [`crate::errors::Error::Parse`] if `path` cannot be converted into a valid
+    ///   resource/URL.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn put_stream_hashed<P, R>(&self, path: P, reader: R) -> Result<PutResult>
+    where
+        P: IntoResourcePath,
+        R: tokio::io::AsyncRead + Send + 'static,
+    {
+        let path: ResourcePath = path.into_abs_path()?;
+        let (body, digest) = hashed_body(reader);
+        let resp = self.put_response(&path, body).await?;
+        self.hashed_put_result(&path, resp, &digest).await
+    }
+
+    /// Upload `reader` to an **absolute path** while saving the same bytes to the local
+    /// file `local`, in a single pass (native only).
+    ///
+    /// Offline-first apps can keep a copy of what they upload without reading the
+    /// source twice or buffering it. Like [`Self::put_stream_hashed`], the returned
+    /// content hash is computed over the bytes as they stream through, and a hash or size
+    /// mismatch reported by the homeserver fails the write.
+    ///
+    /// The local copy is written to a `.partial` file next to `local` and renamed into
+    /// place only once the upload succeeded. If reading, uploading or writing locally
+    /// fails, the upload is aborted and the partial file removed, so `local` is never
+    /// left half-written; an existing file at `local` is only replaced on success.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async fn ex(session: pubky::PubkySession) -> pubky::Result<()> {
+    /// let photo = tokio::fs::File::open("camera/1.jpg").await.expect("readable file");
+    /// let stored = session
+    ///     .storage()
+    ///     .put_tee("/pub/my-cool-app/photos/1.jpg", photo, "cache/photos/1.jpg")
+    ///     .await?;
+    /// println!("uploaded and cached {}", stored.content_hash);
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// - [`crate::errors::RequestError::Validation`] if the local file cannot be
+    ///   created, written or moved into place, or if the homeserver reports a hash or
+    ///   size different from what was uploaded.
+    /// - Otherwise the same as [`Self::put_stream_hashed`].
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn put_tee<P, R, L>(&self, path: P, reader: R, local: L) -> Result<PutResult>
+    where
+        P: IntoResourcePath,
+        R: tokio::io::AsyncRead + Send + 'static,
+        L: AsRef<std::path::Path>,
+    {
+        let path: ResourcePath = path.into_abs_path()?;
+        let local = local.as_ref();
+        let mut partial = local.as_os_str().to_owned();
+        partial.push(".partial");
+        let partial = std::path::PathBuf::from(partial);
+
+        let result = async {
+            let file = tokio::fs::File::create(&partial)
+                .await
+                .map_err(|e| local_write_error(&partial, &e))?;
+            let (body, digest) = teed_body(reader, file, partial.clone());
+            let resp = self.put_response(&path, body).await?;
+            let put = self.hashed_put_result(&path, resp, &digest).await?;
+            if !digest.is_complete() {
+                let incomplete =
+                    std::io::Error::other("the upload ended before the copy was flushed");
+                return Err(local_write_error(&partial, &incomplete));
+            }
+            tokio::fs::rename(&partial, local)
+                .await
+                .map_err(|e| local_write_error(local, &e))?;
+            Ok(put)
+        }
+        .await;
+        if result.is_err() {
+            cross_log!(debug, "Removing partial local copy {}", partial.display());
+            let _ = tokio::fs::remove_file(&partial).await;
+        }
+        result
+    }
+
+    /// Check what the homeserver reports for a streamed `PUT` against `digest`.
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn hashed_put_result(
+        &self,
+        path: &ResourcePath,
+        resp: Response,
+        digest: &StreamDigest,
+    ) -> Result<PutResult> {
+        let (content_hash, size) = digest.finish();
+        let url = PubkyUrl::try_from(PubkyResource::new(self.user.clone(), path.as_str())?)?;
+
+        let reported = resp.bytes().await?;
+        if !reported.is_empty() {
+            let reported: PutResponse =
+                serde_json::from_slice(&reported).map_err(|e| RequestError::DecodeJson {
+                    message: e.to_string(),
+                })?;
+            if decode_hash(&reported.content_hash)? != content_hash
+                || reported.content_length != size
+            {
+                return Err(RequestError::Validation {
+                    message: format!(
+                        "homeserver stored {} bytes hashing to {} at {url}, but {size} bytes hashing to {content_hash} were sent",
+                        reported.content_length, reported.content_hash
+                    ),
+                }
+                .into());
+            }
+        }
+        Ok(PutResult {
+            url,
+            content_hash,
+            size,
+        })
+    }
+
+    /// Upload a `multipart/form-data` form to an **absolute path** (native only), the
+    /// way HTML forms and upload widgets do.
+    ///
+    /// `fields` are sent as text parts and `files` as `(name, filename, body)` file
+    /// parts, in that order. The body is streamed part by part, so file bodies are never
+    /// buffered. The homeserver stores the file part named `"file"` as the resource,
+    /// with its filename as download filename (see [`Self::put_as_attachment`]), and
+    /// ignores every other part. Prefer [`Self::put`] unless you are reusing an existing form.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async fn ex(session: pubky::PubkySession) -> pubky::Result<()> {
+    /// let photo = tokio::fs::read("holiday.jpg").await.expect("readable file");
+    /// session
+    ///     .storage()
+    ///     .put_multipart(
+    ///         "/pub/my-cool-app/photos/1.jpg",
+    ///         [("caption", "At the beach")],
+    ///         [("file", "holiday.jpg", photo)],
+    ///     )
+    ///     .await?;
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// - [`crate::errors::Error::Request`] with status `400 Bad Request` if no file part
+    ///   is named `"file"`, or on other non-success statuses and HTTP
+    ///   transport failures.
+    /// - [`crate::errors::RequestError::UnsupportedMediaType`] if the homeserver's content
+    ///   policy refuses the content type or size of the file.
+    /// - [`crate::errors::Error::Parse`] if `path` cannot be converted into a valid
+    ///   resource/URL.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn put_multipart<P, K, V, N, F, B>(
+        &self,
+        path: P,
+        fields: impl IntoIterator<Item = (K, V)>,
+        files: impl IntoIterator<Item = (N, F, B)>,
+    ) -> Result<PutResult>
+    where
+        P: IntoResourcePath,
+        K: Into<String>,
+        V: Into<String>,
+        N: Into<String>,
+        F: Into<String>,
+        B: Into<reqwest::Body>,
+    {
+        use reqwest::multipart::{Form, Part};
+
+        let path: ResourcePath = path.into_abs_path()?;
+        let form = fields.into_iter().fold(Form::new(), |form, (name, value)| {
+            form.text(name.into(), value.into())
+        });
+        let form = files
+            .into_iter()
+            .fold(form, |form, (name, filename, body)| {
+                form.part(name.into(), Part::stream(body).file_name(filename.into()))
+            });
+        let rb = self
+            .build_request(Method::POST, &path)
+            .await?
+            .multipart(form);
+        let resp = send_checked(&self.client, rb).await?;
+        self.put_result(&path, resp).await
+    }
+
+    /// Bump the modification time of the file at an **absolute path** without uploading
+    /// its content again (HTTP `PATCH`).
+    ///
+    /// The homeserver emits a `PUT` event for the file with its unchanged content hash,
+    /// and the file shows up again in [`modified_since`](super::list::ListBuilder::modified_since)
+    /// listings. Useful for heartbeats, where clients signal liveness through a known
+    /// resource. Touching never creates a file: a missing file is an error, so write it
+    /// once with [`Self::put`] first.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async fn ex(session: pubky::PubkySession) -> pubky::Result<()> {
+    /// session.storage().touch("/pub/my-cool-app/presence").await?;
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// - [`crate::errors::Error::Request`] with status `404 Not Found` if the file does
+    ///   not exist, or on other non-success statuses and HTTP transport failures.
+    /// - [`crate::errors::Error::Parse`] if `path` cannot be converted into a valid
+    ///   resource/URL.
+    pub async fn touch<P: IntoResourcePath>(&self, path: P) -> Result<Response> {
+        let rb = self.build_request(Method::PATCH, path).await?;
+        send_checked(&self.client, rb).await
+    }
+
+    /// Append `body` to the file at an **absolute path** if it is exactly `offset` bytes
+    /// long, returning the new length of the file (HTTP `POST`).
+    ///
+    /// The homeserver checks the length and appends atomically, so writers sharing an
+    /// append-only log never lose or interleave records. If another writer appended
+    /// first, nothing is written and the call fails with [`RequestError::Conflict`],
+    /// carrying the current length to retry at. A missing file has length 0, so
+    /// appending at offset 0 creates it.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use pubky::errors::{Error, RequestError};
+    /// # async fn ex(session: pubky::PubkySession) -> pubky::Result<()> {
+    /// let storage = session.storage();
+    /// let record = "{\"event\":\"joined\"}\n";
+    /// let mut offset = 0;
+    /// let length = loop {
+    ///     match storage.append_at("/pub/my-cool-app/log.jsonl", offset, record).await {
+    ///         Err(Error::Request(RequestError::Conflict { current_length })) => {
+    ///             offset = current_length;
+    ///         }
+    ///         result => break result?,
+    ///     }
+    /// };
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// - [`RequestError::Conflict`] if the file is not `offset` bytes long.
+    /// - [`crate::errors::Error::Request`] on HTTP transport failures or when the server
+    ///   responds with another non-success status (the server message is captured).
+    /// - [`crate::errors::Error::Parse`] if `path` cannot be converted into a valid
+    ///   resource/URL.
+    pub async fn append_at<P, B>(&self, path: P, offset: u64, body: B) -> Result<u64>
+    where
+        P: IntoResourcePath,
+        B: Into<reqwest::Body>,
+    {
+        let rb = self
+            .build_request(Method::POST, path)
+            .await?
+            .header(APPEND_OFFSET_HEADER, offset)
+            .body(body);
+        let resp = self.client.send(rb).await?;
+        cross_log!(debug, "Append completed with status {}", resp.status());
+        if resp.status() == StatusCode::CONFLICT
+            && let Some(current_length) = conflicting_length(&resp)
+        {
+            return Err(RequestError::Conflict { current_length }.into());
+        }
+        let written: PutResponse = check_http_status(resp).await?.json().await?;
+        Ok(written.content_length)
     }
 
     /// HTTP `DELETE` for an **absolute path**.
@@ -113,8 +791,211 @@ impl SessionStorage {
     /// - [`crate::errors::Error::Parse`] if `path` cannot be converted into a valid
     ///   resource/URL.
     pub async fn delete<P: IntoResourcePath>(&self, path: P) -> Result<Response> {
-        let rb = self.request(Method::DELETE, path).await?;
-        send_checked(rb).await
+        let rb = self.build_request(Method::DELETE, path).await?;
+        send_checked(&self.client, rb).await
+    }
+
+    /// Delete every file below the directory at an **absolute path**, returning how
+    /// many files were deleted.
+    ///
+    /// The homeserver deletes the whole subtree in a single transaction, so either all
+    /// files are gone or none is. Older homeservers reject directory deletes with `400`;
+    /// against those the directory is listed and its files are deleted one by one,
+    /// which can leave the directory partially deleted if a request fails.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async fn ex(session: pubky::PubkySession) -> pubky::Result<()> {
+    /// let deleted = session.storage().delete_dir("/pub/my-cool-app/cache/").await?;
+    /// println!("deleted {deleted} files");
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// - Returns [`crate::errors::RequestError::Validation`] if `path` does not end with `/`.
+    /// - [`crate::errors::Error::Request`] on HTTP transport failures or when the server
+    ///   responds with a non-success status (the server message is captured).
+    /// - [`crate::errors::Error::Parse`] if `path` cannot be converted into a valid
+    ///   resource/URL.
+    pub async fn delete_dir<P: IntoResourcePath>(&self, path: P) -> Result<u64> {
+        let progress = self
+            .delete_dir_cancellable(path, &CancelToken::new())
+            .await?;
+        Ok(progress.deleted)
+    }
+
+    /// Like [`delete_dir`](Self::delete_dir), but stops early once `cancel` is cancelled
+    /// and reports how far it got.
+    ///
+    /// The atomic directory delete is a single request: it is skipped if `cancel` is
+    /// already cancelled and otherwise runs to completion. In the file-by-file fallback
+    /// `cancel` is checked between requests, so the files deleted so far are reported in
+    /// [`DeleteDirProgress::deleted`] and the remaining files stay untouched.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async fn ex(session: pubky::PubkySession, cancel: pubky::CancelToken) -> pubky::Result<()> {
+    /// let progress = session
+    ///     .storage()
+    ///     .delete_dir_cancellable("/pub/my-cool-app/cache/", &cancel)
+    ///     .await?;
+    /// if progress.cancelled {
+    ///     println!("stopped after deleting {} files", progress.deleted);
+    /// }
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// Same as [`delete_dir`](Self::delete_dir). Cancellation is not an error.
+    pub async fn delete_dir_cancellable<P: IntoResourcePath>(
+        &self,
+        path: P,
+        cancel: &CancelToken,
+    ) -> Result<DeleteDirProgress> {
+        let path: ResourcePath = path.into_abs_path()?;
+        if !path.as_str().ends_with('/') {
+            return Err(dir_trailing_slash_error().into());
+        }
+        if cancel.is_cancelled() {
+            return Ok(DeleteDirProgress {
+                deleted: 0,
+                cancelled: true,
+            });
+        }
+        let rb = self.build_request(Method::DELETE, &path).await?;
+        let resp = self.client.send(rb).await?;
+        cross_log!(
+            debug,
+            "Request completed with status {} (DELETE DIR {})",
+            resp.status(),
+            resp.url()
+        );
+        let resp = match check_http_status(resp).await {
+            Ok(resp) => resp,
+            Err(Error::Request(RequestError::Server { status, message }))
+                if status == StatusCode::BAD_REQUEST
+                    && message.contains(DIR_DELETE_UNSUPPORTED) =>
+            {
+                cross_log!(
+                    info,
+                    "Homeserver does not support directory deletes, deleting {} file by file",
+                    path
+                );
+                return self.delete_dir_per_file(&path, cancel).await;
+            }
+            Err(err) => return Err(err),
+        };
+        let response: DeleteDirResponse = resp.json().await?;
+        Ok(DeleteDirProgress {
+            deleted: response.deleted,
+            cancelled: false,
+        })
+    }
+
+    /// Delete an explicit list of files at **absolute paths**, returning one result per path.
+    ///
+    /// Unlike [`delete_dir`](Self::delete_dir), the set can be arbitrary, e.g. posts the
+    /// user selected. Every path is checked against the session's capabilities before
+    /// anything is sent, so nothing is deleted if one of them is out of scope. The deletes
+    /// then run with at most [`DELETE_MANY_CONCURRENCY`] requests in flight, and a failed
+    /// delete does not stop the others. Results are returned in input order.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async fn ex(session: pubky::PubkySession) -> pubky::Result<()> {
+    /// let results = session
+    ///     .storage()
+    ///     .delete_many(["/pub/my-app/posts/1", "/pub/my-app/posts/7"])
+    ///     .await?;
+    /// for (path, result) in results {
+    ///     if let Err(err) = result {
+    ///         println!("failed to delete {path}: {err}");
+    ///     }
+    /// }
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// - [`RequestError::Validation`] if any path is not a valid absolute path.
+    /// - [`RequestError::Forbidden`] if the session may not delete one of
+    ///   the paths.
+    ///
+    /// Failures of the individual deletes are reported in the returned results instead.
+    pub async fn delete_many<I, P>(&self, paths: I) -> Result<Vec<(ResourcePath, Result<()>)>>
+    where
+        I: IntoIterator<Item = P>,
+        P: IntoResourcePath,
+    {
+        let paths = paths
+            .into_iter()
+            .map(IntoResourcePath::into_abs_path)
+            .collect::<Result<Vec<_>>>()?;
+        for path in &paths {
+            self.ensure_permitted(&Method::DELETE, path)?;
+        }
+        cross_log!(debug, "Deleting {} file(s)", paths.len());
+        let results = futures_util::stream::iter(paths)
+            .map(|path| async move {
+                let result = self.delete(&path).await.map(drop);
+                (path, result)
+            })
+            .buffered(DELETE_MANY_CONCURRENCY)
+            .collect()
+            .await;
+        Ok(results)
+    }
+
+    /// Delete the files below `dir` one by one, for homeservers without directory deletes.
+    async fn delete_dir_per_file(
+        &self,
+        dir: &ResourcePath,
+        cancel: &CancelToken,
+    ) -> Result<DeleteDirProgress> {
+        let mut progress = DeleteDirProgress {
+            deleted: 0,
+            cancelled: false,
+        };
+        'pages: loop {
+            // Listing has no side effects, so it is abandoned as soon as `cancel` fires.
+            // Deleted files drop out of the listing, so always read the first page.
+            let list = Box::pin(async { self.list(dir)?.send().await });
+            let entries = match select(list, Box::pin(cancel.cancelled())).await {
+                Either::Left((entries, _)) => entries,
+                Either::Right(((), _)) => {
+                    progress.cancelled = true;
+                    break;
+                }
+            };
+            let entries = match entries {
+                Ok(entries) => entries,
+                Err(Error::Request(RequestError::Server { status, .. }))
+                    if status == StatusCode::NOT_FOUND =>
+                {
+                    break;
+                }
+                Err(err) => return Err(err),
+            };
+            if entries.is_empty() {
+                break;
+            }
+            for entry in entries {
+                if cancel.is_cancelled() {
+                    progress.cancelled = true;
+                    break 'pages;
+                }
+                self.delete(&entry.path).await?;
+                progress.deleted += 1;
+            }
+        }
+        if progress.cancelled {
+            cross_log!(
+                info,
+                "Cancelled deleting {} after {} files",
+                dir,
+                progress.deleted
+            );
+        }
+        Ok(progress)
     }
 }
 
@@ -134,24 +1015,90 @@ impl PublicStorage {
     /// # Ok(()) }
     /// ```
     ///
+    /// With [`read_failover`](crate::PubkyHttpClientBuilder::read_failover) enabled, a read
+    /// whose primary homeserver is unreachable is retried on the user's other homeservers
+    /// in priority order. A `404` is final: the file does not exist.
+    ///
+    /// On a handle with a response cache (native only, see `with_response_cache`),
+    /// unchanged small resources are revalidated and served from memory.
+    ///
     /// # Errors
     /// - [`crate::errors::Error::Request`] on HTTP transport failures or when the server
-    ///   responds with a non-success status (the server message is captured).
+    ///   responds with a non-success status (the server message is captured). With
+    ///   failover, the error of the last homeserver tried.
+    /// - [`crate::errors::Error::RecordNotFound`] if the owner has no PKARR record, and
+    ///   [`crate::errors::Error::HomeserverUnreachable`] if their homeserver cannot be
+    ///   connected to.
     /// - [`crate::errors::Error::Parse`] if `addr` cannot be converted into a valid
     ///   addressed resource/URL.
-    pub async fn get<A: IntoPubkyResource>(&self, addr: A) -> Result<Response> {
-        let rb = self.request(Method::GET, addr).await?;
-        send_checked(rb).await
+    pub async fn get<A: IntoPubkyResource>(&self, addr: A) -> Result<PubkyResponse> {
+        let resource = addr.into_pubky_resource()?;
+        let resp = match self.get_from_primary(&resource).await {
+            Err(err) if self.client.read_failover && err.is_unreachable() => {
+                self.get_from_secondaries(&resource, err).await
+            }
+            result => result,
+        }?;
+        Ok(resp.into())
+    }
+
+    /// `GET` from the owner's primary homeserver, through the response cache if one is
+    /// set up with [`Self::with_response_cache`].
+    async fn get_from_primary(&self, resource: &PubkyResource) -> Result<Response> {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(cache) = &self.cache {
+            return self.get_cached(cache, resource).await;
+        }
+        let rb = self.request(Method::GET, resource).await?;
+        send_checked(&self.client, rb).await
+    }
+
+    /// Retry a `GET` on the owner's homeservers after the primary, stopping at the first
+    /// one that answers.
+    async fn get_from_secondaries(
+        &self,
+        resource: &PubkyResource,
+        primary_err: Error,
+    ) -> Result<Response> {
+        let homeservers = Pkdns::with_client(self.client.clone())
+            .get_homeservers_of(&resource.owner)
+            .await;
+        let mut last_err = primary_err;
+        for homeserver in homeservers.iter().skip(1) {
+            cross_log!(
+                info,
+                "Homeserver unreachable for {resource} ({last_err}), trying {homeserver}"
+            );
+            let rb = self
+                .client
+                .cross_request_via_homeserver(
+                    Method::GET,
+                    homeserver,
+                    &resource.owner,
+                    resource.path.as_str(),
+                )
+                .await?;
+            match send_checked(&self.client, rb).await {
+                Err(err) if err.is_unreachable() => last_err = err,
+                result => return result,
+            }
+        }
+        Err(last_err)
     }
 
     /// HEAD existence check for an addressed resource.
     ///
+    /// Returns `true` on a 2xx and `false` on `404 Not Found` or `410 Gone`, without
+    /// downloading the body.
+    ///
     /// # Errors
     /// - Propagates transport failures while issuing the `HEAD` request.
+    /// - [`crate::errors::Error::Request`] if the server responds with any other non-success
+    ///   status.
     /// - Returns [`crate::errors::Error::Parse`] if `addr` cannot be converted into a valid addressed resource.
     pub async fn exists<A: IntoPubkyResource>(&self, addr: A) -> Result<bool> {
         let rb = self.request(Method::HEAD, addr).await?;
-        Ok(send_head(rb).await?.is_some())
+        Ok(send_head(&self.client, rb).await?.is_some())
     }
 
     /// Metadata via `HEAD` for an addressed resource (no body).
@@ -161,7 +1108,7 @@ impl PublicStorage {
     /// - Returns [`crate::errors::Error::Parse`] if `addr` cannot be converted into a valid addressed resource.
     pub async fn stats<A: IntoPubkyResource>(&self, addr: A) -> Result<Option<ResourceStats>> {
         let rb = self.request(Method::HEAD, addr).await?;
-        Ok(send_head(rb)
+        Ok(send_head(&self.client, rb)
             .await?
             .map(|resp| ResourceStats::from_headers(resp.headers())))
     }
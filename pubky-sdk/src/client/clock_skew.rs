@@ -0,0 +1,252 @@
+//! Detection (and optional correction) of the offset between the local clock and
+//! the clock of the servers the client talks to.
+//!
+//! Homeservers reject auth tokens and grant proofs whose timestamp is more than
+//! [`DEFAULT_CLOCK_SKEW_TOLERANCE`] away from their own clock. A device with a
+//! wrong clock would otherwise only see opaque `401`s, so the `Date` header of every
+//! response from a pubky host is compared against the local clock and the offset is
+//! kept here.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::time::Duration;
+
+use pubky_common::auth::DEFAULT_CLOCK_SKEW_TOLERANCE;
+use pubky_common::clock::TimeSource;
+use pubky_common::timestamp::Timestamp;
+use reqwest::header::{DATE, HeaderMap};
+
+use crate::cross_log;
+
+/// Offsets above this are logged once as a warning.
+const CLOCK_SKEW_WARN_THRESHOLD: Duration = Duration::from_secs(30);
+
+/// Largest offset applied to the local clock when correction is enabled.
+pub const MAX_CLOCK_SKEW_CORRECTION: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug)]
+struct SkewState {
+    /// Server time minus local time, in seconds. Only meaningful once `observed`.
+    offset_secs: AtomicI64,
+    observed: AtomicBool,
+    warned: AtomicBool,
+}
+
+/// Last observed clock offset, shared by all clones of a [`crate::PubkyHttpClient`].
+#[derive(Debug, Clone)]
+pub(crate) struct ClockSkew {
+    /// The clock the offset is measured against, before any correction.
+    local: Arc<dyn TimeSource>,
+    /// Whether the client's time source applies the offset.
+    correct: bool,
+    state: Arc<SkewState>,
+}
+
+impl ClockSkew {
+    pub(crate) fn new(local: Arc<dyn TimeSource>, correct: bool) -> Self {
+        Self {
+            local,
+            correct,
+            state: Arc::new(SkewState {
+                offset_secs: AtomicI64::new(0),
+                observed: AtomicBool::new(false),
+                warned: AtomicBool::new(false),
+            }),
+        }
+    }
+
+    /// Record the offset announced by a response's `Date` header, if any.
+    pub(crate) fn observe(&self, headers: &HeaderMap) {
+        let Some(server_secs) = headers
+            .get(DATE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| httpdate::parse_http_date(value).ok())
+            .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+            .and_then(|since_epoch| i64::try_from(since_epoch.as_secs()).ok())
+        else {
+            return;
+        };
+        let local_secs = i64::try_from(self.local.now_secs()).unwrap_or(i64::MAX);
+        self.record(server_secs.saturating_sub(local_secs));
+    }
+
+    /// Record an offset measured some other way, replacing the last observed one.
+    pub(crate) fn record(&self, offset: i64) {
+        self.state.offset_secs.store(offset, Ordering::Relaxed);
+        self.state.observed.store(true, Ordering::Relaxed);
+
+        if offset.unsigned_abs() > CLOCK_SKEW_WARN_THRESHOLD.as_secs()
+            && !self.state.warned.swap(true, Ordering::Relaxed)
+        {
+            cross_log!(
+                warn,
+                "Local clock is {offset}s off the server's clock; auth requests are rejected beyond {}s",
+                DEFAULT_CLOCK_SKEW_TOLERANCE.as_secs()
+            );
+        }
+    }
+
+    /// The current time of the clock the offset is measured against.
+    pub(crate) fn local_now(&self) -> Timestamp {
+        self.local.now()
+    }
+
+    /// Server time minus local time, in seconds, or `None` before any `Date` header was seen.
+    pub(crate) fn offset_secs(&self) -> Option<i64> {
+        self.state
+            .observed
+            .load(Ordering::Relaxed)
+            .then(|| self.state.offset_secs.load(Ordering::Relaxed))
+    }
+
+    /// The part of the offset applied to the local clock: none unless correction
+    /// is enabled, and at most [`MAX_CLOCK_SKEW_CORRECTION`].
+    fn correction_secs(&self) -> i64 {
+        if !self.correct {
+            return 0;
+        }
+        let max = i64::try_from(MAX_CLOCK_SKEW_CORRECTION.as_secs()).unwrap_or(i64::MAX);
+        self.offset_secs().unwrap_or(0).clamp(-max, max)
+    }
+
+    /// The offset left after correction, if it exceeds [`DEFAULT_CLOCK_SKEW_TOLERANCE`].
+    pub(crate) fn excessive_offset_secs(&self) -> Option<i64> {
+        let offset = self.offset_secs()?;
+        let residual = offset - self.correction_secs();
+        (residual.unsigned_abs() > DEFAULT_CLOCK_SKEW_TOLERANCE.as_secs()).then_some(offset)
+    }
+}
+
+/// Offset between the local clock and a homeserver's clock, measured by
+/// [`crate::Pubky::measure_clock_skew`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockSkewMeasurement {
+    /// Server time minus local time, in microseconds. Negative when the local clock is
+    /// ahead.
+    pub offset_micros: i64,
+    /// Round trip of the measuring request. The offset is accurate to about half of it.
+    pub round_trip: Duration,
+}
+
+impl ClockSkewMeasurement {
+    /// Estimate the offset from a request sent at local time `sent` that came back
+    /// after `round_trip` with the server time `server`, assuming the server read its
+    /// clock halfway through.
+    pub(crate) fn estimate(sent: Timestamp, round_trip: Duration, server: Timestamp) -> Self {
+        let half_trip = u64::try_from(round_trip.as_micros() / 2).unwrap_or(u64::MAX);
+        let halfway = sent.as_u64().saturating_add(half_trip);
+        let offset = i128::from(server.as_u64()) - i128::from(halfway);
+        let saturated = if offset < 0 { i64::MIN } else { i64::MAX };
+        Self {
+            offset_micros: i64::try_from(offset).unwrap_or(saturated),
+            round_trip,
+        }
+    }
+
+    /// The offset rounded to whole seconds, as reported by
+    /// [`crate::PubkyHttpClient::clock_skew_secs`].
+    #[must_use]
+    pub fn offset_secs(&self) -> i64 {
+        self.offset_micros
+            .saturating_add(500_000)
+            .div_euclid(1_000_000)
+    }
+}
+
+/// [`TimeSource`] shifting the local clock by the observed offset, within
+/// [`MAX_CLOCK_SKEW_CORRECTION`].
+#[derive(Debug)]
+pub(crate) struct CorrectedClock(pub(crate) ClockSkew);
+
+impl TimeSource for CorrectedClock {
+    fn now(&self) -> Timestamp {
+        let now = self.0.local.now();
+        let correction = self.0.correction_secs();
+        let micros = correction.unsigned_abs() * 1_000_000;
+        if correction >= 0 {
+            now + micros
+        } else {
+            now - micros
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pubky_common::clock::MockClock;
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+
+    fn date_headers(secs: u64) -> HeaderMap {
+        let time = std::time::UNIX_EPOCH + Duration::from_secs(secs);
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            DATE,
+            HeaderValue::from_str(&httpdate::fmt_http_date(time)).unwrap(),
+        );
+        headers
+    }
+
+    #[test]
+    fn measures_and_corrects_offset() {
+        let local = Arc::new(MockClock::new(Timestamp::from(1_700_000_000_000_000)));
+        let skew = ClockSkew::new(local, true);
+        let clock = CorrectedClock(skew.clone());
+        assert_eq!(skew.offset_secs(), None);
+
+        skew.observe(&HeaderMap::new());
+        assert_eq!(skew.offset_secs(), None);
+
+        skew.observe(&date_headers(1_700_000_600));
+        assert_eq!(skew.offset_secs(), Some(600));
+        assert_eq!(skew.excessive_offset_secs(), None);
+        assert_eq!(clock.now_secs(), 1_700_000_600);
+
+        // Corrections are bounded.
+        skew.observe(&date_headers(1_700_000_000 - 2 * 60 * 60));
+        assert_eq!(skew.offset_secs(), Some(-2 * 60 * 60));
+        assert_eq!(skew.excessive_offset_secs(), Some(-2 * 60 * 60));
+        assert_eq!(clock.now_secs(), 1_700_000_000 - 60 * 60);
+    }
+
+    #[test]
+    fn estimates_offset_at_the_midpoint() {
+        let sent = Timestamp::from(1_700_000_000_000_000);
+        let round_trip = Duration::from_millis(400);
+
+        // The server read its clock 200ms after the request was sent.
+        let measured = ClockSkewMeasurement::estimate(
+            sent,
+            round_trip,
+            Timestamp::from(1_700_000_000_200_000 + 90_000_000),
+        );
+        assert_eq!(measured.offset_micros, 90_000_000);
+        assert_eq!(measured.offset_secs(), 90);
+        assert_eq!(measured.round_trip, round_trip);
+
+        let measured = ClockSkewMeasurement::estimate(
+            sent,
+            round_trip,
+            Timestamp::from(1_700_000_000_200_000 - 1_600_000),
+        );
+        assert_eq!(measured.offset_micros, -1_600_000);
+        assert_eq!(measured.offset_secs(), -2);
+
+        let skew = ClockSkew::new(Arc::new(MockClock::new(sent)), false);
+        skew.record(measured.offset_secs());
+        assert_eq!(skew.offset_secs(), Some(-2));
+    }
+
+    #[test]
+    fn reports_uncorrected_offset() {
+        let local = Arc::new(MockClock::new(Timestamp::from(1_700_000_000_000_000)));
+        let skew = ClockSkew::new(local, false);
+
+        skew.observe(&date_headers(1_700_000_100));
+        assert_eq!(skew.excessive_offset_secs(), None);
+
+        skew.observe(&date_headers(1_700_000_600));
+        assert_eq!(skew.excessive_offset_secs(), Some(600));
+    }
+}
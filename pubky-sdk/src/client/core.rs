@@ -1,16 +1,196 @@
 use std::borrow::Cow;
 use std::fmt::Debug;
+#[cfg(not(target_arch = "wasm32"))]
+use std::num::NonZeroU64;
+use std::sync::Arc;
 use std::time::Duration;
 
-use crate::{cross_log, errors::BuildError};
+use pubky_common::clock::{SystemClock, TimeSource};
+
+use super::clock_skew::{ClockSkew, CorrectedClock};
+use super::http::HttpClient;
+#[cfg(not(target_arch = "wasm32"))]
+use super::ip_preference::{IpVersionPreference, PreferenceResolver};
+#[cfg(not(target_arch = "wasm32"))]
+use super::size_limit::SizeLimitedBody;
+#[cfg(not(target_arch = "wasm32"))]
+use super::throttle::{RateLimiter, ThrottledBody};
+use crate::actors::pkdns::pinning::{PinPolicy, RecordPinStore, RecordPins};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::errors::{RequestError, TimeoutPhase};
+use crate::{PublicKey, cross_log, errors::BuildError};
+#[cfg(not(target_arch = "wasm32"))]
+use reqwest::dns::Resolve;
 
 const DEFAULT_USER_AGENT: &str = concat!("pubky.org", "@", env!("CARGO_PKG_VERSION"),);
 
+/// Pkarr relays of a local Pubky testnet, as set by [`PubkyHttpClientBuilder::testnet`].
+///
+/// Override them with [`PubkyHttpClientBuilder::testnet_relays`] when the relay
+/// runs on another host or port.
+pub const DEFAULT_TESTNET_RELAYS: &[&str] = &["http://localhost:15411/"];
+
 #[cfg(not(target_arch = "wasm32"))]
 #[derive(Debug, Clone, Default)]
 struct NativeHttpConfig {
-    request_timeout: Option<Duration>,
+    timeouts: HttpTimeouts,
     pool_max_idle_per_host: Option<usize>,
+    http_version: HttpVersion,
+    http2_keep_alive_interval: Option<Duration>,
+    redirect_policy: RedirectPolicy,
+    ip_version_preference: IpVersionPreference,
+    upload_rate_limit: Option<NonZeroU64>,
+    download_rate_limit: Option<NonZeroU64>,
+    max_response_bytes: Option<NonZeroU64>,
+}
+
+/// Per-phase HTTP timeouts, kept on the client to classify timeout errors.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct HttpTimeouts {
+    connect: Option<Duration>,
+    read: Option<Duration>,
+    total: Option<Duration>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl HttpTimeouts {
+    fn apply(self, mut builder: reqwest::ClientBuilder) -> reqwest::ClientBuilder {
+        if let Some(timeout) = self.connect {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(timeout) = self.read {
+            builder = builder.read_timeout(timeout);
+        }
+        if let Some(timeout) = self.total {
+            builder = builder.timeout(timeout);
+        }
+        builder
+    }
+
+    /// Tell which configured timeout a reqwest timeout error came from.
+    ///
+    /// reqwest reports read and total timeouts identically, so the time spent
+    /// in the request decides between them.
+    fn phase(self, err: &reqwest::Error, elapsed: Duration) -> TimeoutPhase {
+        if err.is_connect() {
+            TimeoutPhase::Connect
+        } else if self.read.is_some() && self.total.is_none_or(|total| elapsed < total) {
+            TimeoutPhase::Read
+        } else {
+            TimeoutPhase::Total
+        }
+    }
+}
+
+/// HTTP protocol versions the native clients may speak, see
+/// [`PubkyHttpClientBuilder::http_version`].
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HttpVersion {
+    /// Offer HTTP/2 and HTTP/1.1 via TLS ALPN and use whatever the server picks.
+    /// Servers without ALPN, and plain-HTTP endpoints, get HTTP/1.1.
+    #[default]
+    Auto,
+    /// Only speak HTTP/1.1. Use this for servers or proxies that mishandle HTTP/2.
+    Http1,
+    /// Only speak HTTP/2, without negotiating (prior knowledge).
+    Http2,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl HttpVersion {
+    fn alpn_protocols(self) -> Vec<Vec<u8>> {
+        match self {
+            Self::Auto => vec![b"h2".to_vec(), b"http/1.1".to_vec()],
+            Self::Http1 => vec![b"http/1.1".to_vec()],
+            Self::Http2 => vec![b"h2".to_vec()],
+        }
+    }
+
+    fn apply(self, builder: reqwest::ClientBuilder) -> reqwest::ClientBuilder {
+        match self {
+            Self::Auto => builder,
+            Self::Http1 => builder.http1_only(),
+            Self::Http2 => builder.http2_prior_knowledge(),
+        }
+    }
+}
+
+/// Default number of redirects followed on plain HTTPS, see [`RedirectPolicy::Limited`].
+#[cfg(not(target_arch = "wasm32"))]
+pub const DEFAULT_MAX_REDIRECTS: usize = 5;
+
+/// How many HTTP redirects the native clients follow on plain (ICANN) HTTPS, see
+/// [`PubkyHttpClientBuilder::redirect_policy`].
+///
+/// Requests over `PubkyTLS` are not affected.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedirectPolicy {
+    /// Never follow redirects; the `3xx` response is returned as is.
+    None,
+    /// Follow at most this many redirects, then fail the request.
+    Limited(usize),
+    /// Follow any number of redirects, failing only on a redirect loop.
+    Follow,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Default for RedirectPolicy {
+    fn default() -> Self {
+        Self::Limited(DEFAULT_MAX_REDIRECTS)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl RedirectPolicy {
+    /// Build the reqwest policy. With `same_origin`, redirects to another scheme, host
+    /// or port than the original request are rejected.
+    fn to_reqwest(self, same_origin: bool) -> reqwest::redirect::Policy {
+        if self == Self::None {
+            return reqwest::redirect::Policy::none();
+        }
+        reqwest::redirect::Policy::custom(move |attempt| {
+            let previous = attempt.previous();
+            if same_origin
+                && previous
+                    .first()
+                    .is_some_and(|first| first.origin() != attempt.url().origin())
+            {
+                let message = format!("redirect to {} leaves the origin", attempt.url());
+                return attempt.error(message);
+            }
+            match self {
+                Self::Limited(max) if previous.len() > max => {
+                    attempt.error(format!("more than {max} redirects"))
+                }
+                Self::Follow if previous.contains(attempt.url()) => attempt.error("redirect loop"),
+                _ => attempt.follow(),
+            }
+        })
+    }
+}
+
+/// Where a pubky host resolution was served from, see [`CacheInfo`].
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResolutionSource {
+    /// A cached, non-expired decision was reused without touching the network.
+    Cache,
+    /// The host was resolved via the DHT or a Pkarr relay.
+    Network,
+}
+
+/// How the most recent pubky host resolution of a [`PubkyHttpClient`] was served,
+/// see [`PubkyHttpClient::last_resolution_source`].
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheInfo {
+    /// Cache hit or network round trip.
+    pub source: ResolutionSource,
+    /// Age of the cached entry on a hit, zero for a network resolution.
+    pub age: Duration,
 }
 
 #[derive(Debug, Clone)]
@@ -23,11 +203,25 @@ struct NativeHttpConfig {
 ///
 /// # Defaults
 /// - Pkarr relays: [`crate::pkarr::DEFAULT_RELAYS`]
-/// - HTTP request timeout (native only): reqwest default unless set via
-///   [`Self::request_timeout`]
+/// - HTTP timeouts (native only): none unless set via [`Self::connect_timeout`],
+///   [`Self::read_timeout`] or [`Self::request_timeout`]
 /// - User-agent: `pubky.org@<crate-version>` plus any [`Self::user_agent_extra`]
 /// - Idle keep-alive connections per host (native only): reqwest default unless set via
 ///   [`Self::pool_max_idle_per_host`]
+/// - HTTP version (native only): [`HttpVersion::Auto`], preferring HTTP/2 and falling back
+///   to HTTP/1.1, unless set via [`Self::http_version`]
+/// - HTTP/2 keep-alive pings (native only): disabled unless set via
+///   [`Self::http2_keep_alive_interval`]
+/// - Redirects on plain HTTPS (native only): up to [`DEFAULT_MAX_REDIRECTS`], unless set via
+///   [`Self::redirect_policy`]
+/// - Bandwidth (native only): unlimited unless set via [`Self::upload_rate_limit`] or
+///   [`Self::download_rate_limit`]
+/// - Response body size (native only): unlimited unless set via [`Self::max_response_bytes`]
+/// - Read failover to other homeservers: disabled unless set via [`Self::read_failover`]
+/// - Time source: the system clock unless set via [`Self::time_source`]
+/// - Clock skew correction: disabled unless set via [`Self::correct_clock_skew`]
+/// - Homeserver pinning: disabled unless set via [`Self::pin_records`]
+/// - HTTP transport: built-in reqwest clients unless set via [`Self::with_http`]
 /// # Example
 /// ```no_run
 /// # #[cfg(not(target_arch = "wasm32"))]
@@ -35,6 +229,7 @@ struct NativeHttpConfig {
 /// use std::time::Duration;
 /// # use pubky::{PubkyHttpClient, PubkyHttpClientBuilder};
 /// let client = PubkyHttpClient::builder()
+///     .connect_timeout(Duration::from_secs(2))
 ///     .request_timeout(Duration::from_secs(10))
 ///     .user_agent_extra("myapp/1.2.3")
 ///     .pool_max_idle_per_host(10)
@@ -59,6 +254,21 @@ pub struct PubkyHttpClientBuilder {
     /// Optional user-agent segment appended to the default UA for app-level telemetry.
     user_agent_extra: Option<String>,
 
+    /// Clock used for token timestamps and session expiry checks.
+    time_source: Option<Arc<dyn TimeSource>>,
+
+    /// Custom transport replacing the built-in reqwest clients.
+    custom_http: Option<Arc<dyn HttpClient>>,
+
+    /// Retry public reads on a user's other homeservers when the primary is unreachable.
+    read_failover: bool,
+
+    /// Shift the time source by the clock offset observed from server responses.
+    correct_clock_skew: bool,
+
+    /// Pinned homeservers checked on resolution, see [`Self::pin_records`].
+    record_pins: Option<RecordPins>,
+
     #[cfg(not(target_arch = "wasm32"))]
     native_http: NativeHttpConfig,
 
@@ -68,7 +278,7 @@ pub struct PubkyHttpClientBuilder {
 }
 
 impl PubkyHttpClientBuilder {
-    #[cfg(test)]
+    #[cfg(any(test, feature = "test-util"))]
     pub(crate) fn isolated_pkarr_test(&mut self) -> &mut Self {
         self.pkarr
             .no_default_network()
@@ -81,7 +291,8 @@ impl PubkyHttpClientBuilder {
     ///
     /// Concretely:
     /// - **DHT bootstrap** to the local testnet node at: `"localhost:6881"`
-    /// - **PKARR relay** base URL: `"http://localhost:15411"`
+    /// - **PKARR relay** base URL: [`DEFAULT_TESTNET_RELAYS`], see
+    ///   [`testnet_relays`](Self::testnet_relays) to override it
     /// - **WASM builds** additionally remember the hostname when resolving `_pubky.<pk>` targets.
     ///
     /// # Examples
@@ -159,6 +370,34 @@ impl PubkyHttpClientBuilder {
         self
     }
 
+    /// Replace the Pkarr relays, e.g. after [`testnet`](Self::testnet) to reach a testnet
+    /// relay on a non-default port. An empty slice disables relays.
+    ///
+    /// # Examples
+    /// ```
+    /// use pubky::PubkyHttpClient;
+    /// use url::Url;
+    ///
+    /// let relay = Url::parse("http://localhost:25411/")?;
+    /// let client = PubkyHttpClient::builder()
+    ///     .testnet()
+    ///     .testnet_relays(&[relay])
+    ///     .build()?;
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Panics
+    /// If Pkarr rejects one of the relay URLs.
+    pub fn testnet_relays(&mut self, relays: &[url::Url]) -> &mut Self {
+        self.pkarr.no_relays();
+        if !relays.is_empty() {
+            self.pkarr
+                .relays(relays)
+                .expect("testnet relays should be valid relay URLs");
+        }
+        self
+    }
+
     /// Allows mutating the internal [`pkarr::ClientBuilder`] with a callback function.
     ///
     /// Use this to influence PKARR resolution inputs (relays, bootstrap nodes,
@@ -193,6 +432,79 @@ impl PubkyHttpClientBuilder {
         self
     }
 
+    /// Override the clock used for token timestamps and session expiry checks.
+    ///
+    /// Defaults to the system clock. Tests can inject a
+    /// [`MockClock`](pubky_common::clock::MockClock) and advance it to exercise
+    /// expiry paths deterministically.
+    pub fn time_source(&mut self, time_source: Arc<dyn TimeSource>) -> &mut Self {
+        self.time_source = Some(time_source);
+        self
+    }
+
+    /// Execute every request through a custom [`HttpClient`] transport instead of the
+    /// built-in reqwest clients.
+    ///
+    /// The SDK still prepares every request (URLs, headers, credentials); sending it,
+    /// and on native targets resolving public-key hosts, is up to the transport.
+    /// Useful for routing through your own networking stack or for answering requests
+    /// from a mock in tests. Pass an `Arc` to keep a handle on it.
+    pub fn with_http(&mut self, http: impl HttpClient + 'static) -> &mut Self {
+        self.custom_http = Some(Arc::new(http));
+        self
+    }
+
+    /// Let [`PublicStorage::get`](crate::PublicStorage::get) fall back to a user's other
+    /// homeservers, in priority order, when the primary one is unreachable.
+    ///
+    /// Only unreachable servers trigger failover: transport failures, timeouts and
+    /// gateway errors (`502`, `503`, `504`). Any other answer, a `404` included, is
+    /// returned as is. Defaults to `false`.
+    pub fn read_failover(&mut self, enabled: bool) -> &mut Self {
+        self.read_failover = enabled;
+        self
+    }
+
+    /// Shift the time source by the offset between it and the servers' clocks.
+    ///
+    /// The offset is taken from the `Date` header of the latest response from a
+    /// pubky host, see
+    /// [`PubkyHttpClient::clock_skew_secs`], and applied up to
+    /// [`MAX_CLOCK_SKEW_CORRECTION`](crate::MAX_CLOCK_SKEW_CORRECTION). Enable it on
+    /// devices whose clock cannot be trusted, so their auth tokens are not rejected
+    /// as expired. Requests sent before the first response arrives still use the
+    /// uncorrected clock. Defaults to `false`.
+    pub fn correct_clock_skew(&mut self, enabled: bool) -> &mut Self {
+        self.correct_clock_skew = enabled;
+        self
+    }
+
+    /// Pin the homeserver of each user on first resolution and check later resolutions
+    /// against it, to detect records hijacked to point elsewhere.
+    ///
+    /// Applies wherever [`Pkdns`](crate::Pkdns) resolves a user's homeserver (sign-in,
+    /// session restores, event streams and read failover) and to every request
+    /// addressed to a user's `_pubky.<user>` host, which covers storage reads and
+    /// writes and [`Pubky::fetch`](crate::Pubky::fetch). Changes are reported through
+    /// [`RecordPinStore::record_changed`] and, under [`PinPolicy::Strict`], fail the
+    /// resolution with [`crate::Error::RecordChanged`]. Records this client publishes
+    /// itself update the pin. Disabled by default.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::sync::Arc;
+    /// use pubky::{MemoryPinStore, PinPolicy, PubkyHttpClient};
+    ///
+    /// let client = PubkyHttpClient::builder()
+    ///     .pin_records(Arc::new(MemoryPinStore::new()), PinPolicy::Strict)
+    ///     .build()?;
+    /// # Ok::<_, pubky::BuildError>(())
+    /// ```
+    pub fn pin_records(&mut self, store: Arc<dyn RecordPinStore>, policy: PinPolicy) -> &mut Self {
+        self.record_pins = Some(RecordPins { store, policy });
+        self
+    }
+
     /// Build a [`PubkyHttpClient`].
     ///
     /// # Errors
@@ -222,10 +534,13 @@ impl PubkyHttpClientBuilder {
         #[cfg(not(target_arch = "wasm32"))]
         cross_log!(
             info,
-            "Building PubkyHttpClient (timeout: {:?}, user_agent: {}, pool_max_idle_per_host: {:?})",
-            self.native_http.request_timeout,
+            "Building PubkyHttpClient (timeouts: {:?}, user_agent: {}, pool_max_idle_per_host: {:?}, http_version: {:?}, redirect_policy: {:?}, ip_version_preference: {:?})",
+            self.native_http.timeouts,
             user_agent,
-            self.native_http.pool_max_idle_per_host
+            self.native_http.pool_max_idle_per_host,
+            self.native_http.http_version,
+            self.native_http.redirect_policy,
+            self.native_http.ip_version_preference
         );
         #[cfg(target_arch = "wasm32")]
         cross_log!(
@@ -234,52 +549,126 @@ impl PubkyHttpClientBuilder {
             user_agent
         );
 
+        // Same as `reqwest::ClientBuilder::from(pkarr)`, but with our ALPN protocols.
         #[cfg(not(target_arch = "wasm32"))]
-        let mut http_builder =
-            reqwest::ClientBuilder::from(pkarr.clone()).user_agent(user_agent.as_ref());
+        let mut http_builder = {
+            let mut tls_config = rustls::ClientConfig::from(pkarr.clone());
+            tls_config.alpn_protocols = self.native_http.http_version.alpn_protocols();
+            reqwest::Client::builder()
+                .dns_resolver(self.native_http.resolver(Some(Arc::new(pkarr.clone()))))
+                .tls_backend_preconfigured(tls_config)
+                .user_agent(user_agent.as_ref())
+        };
 
         #[cfg(target_arch = "wasm32")]
         let http_builder = reqwest::Client::builder().user_agent(user_agent.as_ref());
 
         #[cfg(not(target_arch = "wasm32"))]
-        let mut icann_http_builder = reqwest::Client::builder()
-            .user_agent(user_agent.as_ref())
-            .tls_backend_preconfigured(icann_tls_config_without_revocation_check());
+        if let Some(max) = self.native_http.pool_max_idle_per_host {
+            http_builder = http_builder.pool_max_idle_per_host(max);
+        }
 
-        // TODO: change this after Reqwest publish a release with timeout in wasm
         #[cfg(not(target_arch = "wasm32"))]
-        if let Some(timeout) = self.native_http.request_timeout {
-            http_builder = http_builder.timeout(timeout);
-            icann_http_builder = icann_http_builder.timeout(timeout);
+        if let Some(interval) = self.native_http.http2_keep_alive_interval {
+            http_builder = http_builder.http2_keep_alive_interval(interval);
         }
 
+        // TODO: change this after Reqwest publish a release with timeout in wasm
         #[cfg(not(target_arch = "wasm32"))]
-        if let Some(max) = self.native_http.pool_max_idle_per_host {
-            http_builder = http_builder.pool_max_idle_per_host(max);
-            icann_http_builder = icann_http_builder.pool_max_idle_per_host(max);
-        }
+        let http_builder = self
+            .native_http
+            .http_version
+            .apply(self.native_http.timeouts.apply(http_builder));
+
+        let local_clock = self
+            .time_source
+            .clone()
+            .unwrap_or_else(|| Arc::new(SystemClock));
+        let clock_skew = ClockSkew::new(Arc::clone(&local_clock), self.correct_clock_skew);
 
         Ok(PubkyHttpClient {
             pkarr,
             http: http_builder.build()?,
 
             #[cfg(not(target_arch = "wasm32"))]
-            icann_http: icann_http_builder.build()?,
+            icann_http: self.icann_http_builder(&user_agent, false).build()?,
+
+            #[cfg(not(target_arch = "wasm32"))]
+            icann_fallback_http: self.icann_http_builder(&user_agent, true).build()?,
+
+            #[cfg(not(target_arch = "wasm32"))]
+            transport: super::http_targets::native::TransportResolver::new(
+                self.native_http.ip_version_preference,
+            ),
+
+            #[cfg(not(target_arch = "wasm32"))]
+            timeouts: self.native_http.timeouts,
+
+            #[cfg(not(target_arch = "wasm32"))]
+            upload_limiter: self
+                .native_http
+                .upload_rate_limit
+                .map(|rate| Arc::new(RateLimiter::new(rate))),
+
+            #[cfg(not(target_arch = "wasm32"))]
+            download_limiter: self
+                .native_http
+                .download_rate_limit
+                .map(|rate| Arc::new(RateLimiter::new(rate))),
 
             #[cfg(not(target_arch = "wasm32"))]
-            transport: super::http_targets::native::TransportResolver::new(),
+            max_response_bytes: self.native_http.max_response_bytes,
 
             #[cfg(target_arch = "wasm32")]
             testnet_host: self.testnet_host.clone(),
+
+            time_source: if self.correct_clock_skew {
+                Arc::new(CorrectedClock(clock_skew.clone()))
+            } else {
+                local_clock
+            },
+
+            clock_skew,
+
+            record_pins: self.record_pins.clone(),
+
+            custom_http: self.custom_http.clone(),
+
+            read_failover: self.read_failover,
+
+            profiles: crate::actors::profile::ProfileCache::default(),
         })
     }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
 impl PubkyHttpClientBuilder {
-    /// Set HTTP requests timeout.
+    /// Set the total timeout for each HTTP request, from connecting until the
+    /// response body has been read.
+    ///
+    /// Hitting it before the response headers arrive fails with
+    /// [`RequestError::Timeout`] and [`TimeoutPhase::Total`].
     pub fn request_timeout(&mut self, timeout: Duration) -> &mut Self {
-        self.native_http.request_timeout = Some(timeout);
+        self.native_http.timeouts.total = Some(timeout);
+        self
+    }
+
+    /// Set the timeout for establishing a connection, TLS handshake included.
+    ///
+    /// Keep it short to fail fast on unreachable hosts. Hitting it fails with
+    /// [`RequestError::Timeout`] and [`TimeoutPhase::Connect`].
+    pub fn connect_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.native_http.timeouts.connect = Some(timeout);
+        self
+    }
+
+    /// Set how long a request may wait for the next piece of data from the server.
+    ///
+    /// The timer restarts after every successful read, so slow but steady downloads
+    /// of large bodies are not cut off. Hitting it before the response headers arrive
+    /// fails with [`RequestError::Timeout`] and [`TimeoutPhase::Read`].
+    pub fn read_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.native_http.timeouts.read = Some(timeout);
         self
     }
 
@@ -288,6 +677,125 @@ impl PubkyHttpClientBuilder {
         self.native_http.pool_max_idle_per_host = Some(max);
         self
     }
+
+    /// Choose the HTTP version, for homeservers and ICANN hosts alike.
+    ///
+    /// Defaults to [`HttpVersion::Auto`]. Force [`HttpVersion::Http1`] for servers
+    /// behind reverse proxies that break HTTP/2 negotiation.
+    pub fn http_version(&mut self, version: HttpVersion) -> &mut Self {
+        self.native_http.http_version = version;
+        self
+    }
+
+    /// Send HTTP/2 ping frames at this interval to keep connections alive.
+    /// Disabled by default; has no effect on HTTP/1.1 connections.
+    pub fn http2_keep_alive_interval(&mut self, interval: Duration) -> &mut Self {
+        self.native_http.http2_keep_alive_interval = Some(interval);
+        self
+    }
+
+    /// Restrict or order the IP versions used to connect, for homeservers and ICANN
+    /// hosts alike.
+    ///
+    /// Defaults to [`IpVersionPreference::Auto`], which races IPv4 and IPv6 with happy
+    /// eyeballs. Force [`IpVersionPreference::V4Only`] on networks where a homeserver's
+    /// IPv6 address is advertised but unreachable (or [`IpVersionPreference::V6Only`]
+    /// for the opposite case). Hosts whose name resolves to no address of the allowed
+    /// version fail to connect.
+    ///
+    /// The preference applies to the addresses the client resolves itself. URLs with an
+    /// IP literal as host are used as is. Through a proxy, it only governs the
+    /// connection to the proxy, which picks the IP version towards the homeserver.
+    pub fn ip_version_preference(&mut self, preference: IpVersionPreference) -> &mut Self {
+        self.native_http.ip_version_preference = preference;
+        self
+    }
+
+    /// Choose how many HTTP redirects to follow on plain (ICANN) HTTPS.
+    ///
+    /// Defaults to [`RedirectPolicy::Limited`] with [`DEFAULT_MAX_REDIRECTS`]. When a
+    /// pubky host falls back to its ICANN domain, redirects that leave that domain's
+    /// origin are always rejected, whatever the policy.
+    pub fn redirect_policy(&mut self, policy: RedirectPolicy) -> &mut Self {
+        self.native_http.redirect_policy = policy;
+        self
+    }
+
+    /// Cap the upload speed of request bodies, in bytes per second. `0` removes the cap.
+    ///
+    /// The cap is shared by all concurrent requests of the built client, and is
+    /// independent of [`Self::download_rate_limit`]. Bodies are released in small chunks
+    /// from a token bucket holding one second worth of bytes, so small requests are not
+    /// delayed. Throttled request bodies are streamed, and cannot be replayed on
+    /// `307`/`308` redirects.
+    pub fn upload_rate_limit(&mut self, bytes_per_sec: u64) -> &mut Self {
+        self.native_http.upload_rate_limit = NonZeroU64::new(bytes_per_sec);
+        self
+    }
+
+    /// Cap the download speed of response bodies, in bytes per second. `0` removes the cap.
+    ///
+    /// The cap is shared by all concurrent requests of the built client, and is
+    /// independent of [`Self::upload_rate_limit`]. Time spent waiting for the cap does
+    /// not count towards [`Self::read_timeout`], but it does towards
+    /// [`Self::request_timeout`]: keep the total timeout long enough for throttled
+    /// downloads.
+    pub fn download_rate_limit(&mut self, bytes_per_sec: u64) -> &mut Self {
+        self.native_http.download_rate_limit = NonZeroU64::new(bytes_per_sec);
+        self
+    }
+
+    /// Abort downloads of response bodies larger than `bytes` with
+    /// [`Error::ResponseTooLarge`](crate::Error::ResponseTooLarge). `0` removes the cap,
+    /// which is the default.
+    ///
+    /// A `Content-Length` above the cap fails the request before the body is read;
+    /// chunked bodies fail while streaming, once they grow past it. Use this when
+    /// fetching untrusted users' data. Lift or raise the cap for single downloads with
+    /// [`PubkyHttpClient::with_max_response_bytes`] or the storage handles'
+    /// `with_max_response_bytes`.
+    pub fn max_response_bytes(&mut self, bytes: u64) -> &mut Self {
+        self.native_http.max_response_bytes = NonZeroU64::new(bytes);
+        self
+    }
+
+    /// Builder for an ICANN HTTP client. `homeserver_fallback` restricts redirects to the
+    /// original origin, for requests that reach a pubky host through its ICANN domain.
+    fn icann_http_builder(
+        &self,
+        user_agent: &str,
+        homeserver_fallback: bool,
+    ) -> reqwest::ClientBuilder {
+        let config = &self.native_http;
+        let mut builder = reqwest::Client::builder()
+            .user_agent(user_agent)
+            .tls_backend_preconfigured(icann_tls_config_without_revocation_check(
+                config.http_version,
+            ))
+            .redirect(config.redirect_policy.to_reqwest(homeserver_fallback));
+        if let Some(max) = config.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        if let Some(interval) = config.http2_keep_alive_interval {
+            builder = builder.http2_keep_alive_interval(interval);
+        }
+        if config.ip_version_preference != IpVersionPreference::Auto {
+            builder = builder.dns_resolver(config.resolver(None));
+        }
+        config.http_version.apply(config.timeouts.apply(builder))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl NativeHttpConfig {
+    /// DNS resolver applying the IP version preference to `inner`, or to the system
+    /// resolver when `inner` is `None`. With no preference, `inner` is used as is.
+    fn resolver(&self, inner: Option<Arc<dyn Resolve>>) -> Arc<dyn Resolve> {
+        match (self.ip_version_preference, inner) {
+            (IpVersionPreference::Auto, Some(inner)) => inner,
+            (preference, inner) => Arc::new(PreferenceResolver::new(inner, preference)),
+        }
+    }
 }
 
 #[cfg(target_arch = "wasm32")]
@@ -314,7 +822,7 @@ impl PubkyHttpClientBuilder {
 /// falsely reject valid certificates ("invalid peer certificate: Revoked"). Only the ICANN
 /// client uses this; the homeserver raw-public-key client keeps its pkarr-derived TLS.
 #[cfg(not(target_arch = "wasm32"))]
-fn icann_tls_config_without_revocation_check() -> rustls::ClientConfig {
+fn icann_tls_config_without_revocation_check(version: HttpVersion) -> rustls::ClientConfig {
     let mut root_store = rustls::RootCertStore::empty();
     root_store.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
     let mut tls_config = rustls::ClientConfig::builder_with_provider(std::sync::Arc::new(
@@ -324,7 +832,7 @@ fn icann_tls_config_without_revocation_check() -> rustls::ClientConfig {
     .expect("aws-lc-rs provides safe default protocol versions")
     .with_root_certificates(root_store)
     .with_no_client_auth();
-    tls_config.alpn_protocols = vec![b"http/1.1".to_vec()];
+    tls_config.alpn_protocols = version.alpn_protocols();
     tls_config
 }
 
@@ -422,6 +930,11 @@ pub struct PubkyHttpClient {
     #[cfg(not(target_arch = "wasm32"))]
     pub(crate) icann_http: reqwest::Client,
 
+    /// ICANN client for pubky hosts reached through their ICANN domain; rejects
+    /// redirects that leave the original origin.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) icann_fallback_http: reqwest::Client,
+
     /// Resolves and caches per-host transport decisions (`PubkyTLS` vs ICANN fallback).
     #[cfg(not(target_arch = "wasm32"))]
     pub(crate) transport: super::http_targets::native::TransportResolver,
@@ -429,6 +942,38 @@ pub struct PubkyHttpClient {
     /// The hostname to use for testnet URL transformations (WASM only).
     #[cfg(target_arch = "wasm32")]
     pub(crate) testnet_host: Option<String>,
+
+    /// Clock used for token timestamps and session expiry checks.
+    pub(crate) time_source: Arc<dyn TimeSource>,
+
+    /// Offset between the local clock and the servers' clocks.
+    pub(crate) clock_skew: ClockSkew,
+
+    /// Set via [`PubkyHttpClientBuilder::pin_records`].
+    pub(crate) record_pins: Option<RecordPins>,
+
+    /// Custom transport set via [`PubkyHttpClientBuilder::with_http`].
+    pub(crate) custom_http: Option<Arc<dyn HttpClient>>,
+
+    /// Set via [`PubkyHttpClientBuilder::read_failover`].
+    pub(crate) read_failover: bool,
+
+    /// Profiles fetched by [`crate::Pubky::profile`].
+    pub(crate) profiles: crate::actors::profile::ProfileCache,
+
+    /// Timeouts the reqwest clients were built with.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) timeouts: HttpTimeouts,
+
+    /// Bandwidth caps shared by all requests, see [`PubkyHttpClientBuilder::upload_rate_limit`].
+    #[cfg(not(target_arch = "wasm32"))]
+    upload_limiter: Option<Arc<RateLimiter>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    download_limiter: Option<Arc<RateLimiter>>,
+
+    /// Response body cap, see [`PubkyHttpClientBuilder::max_response_bytes`].
+    #[cfg(not(target_arch = "wasm32"))]
+    max_response_bytes: Option<NonZeroU64>,
 }
 
 impl PubkyHttpClient {
@@ -504,15 +1049,281 @@ impl PubkyHttpClient {
     pub const fn pkarr(&self) -> &pkarr::Client {
         &self.pkarr
     }
+
+    /// Returns the clock used for token timestamps and session expiry checks.
+    #[must_use]
+    pub fn time_source(&self) -> &dyn TimeSource {
+        self.time_source.as_ref()
+    }
+
+    /// Returns how far the servers' clocks are ahead of the local one, in seconds,
+    /// as announced by the `Date` header of the latest response from a pubky host.
+    /// Responses from other ICANN servers are ignored. Negative when the local
+    /// clock is ahead.
+    ///
+    /// Measured against the configured time source before any correction from
+    /// [`PubkyHttpClientBuilder::correct_clock_skew`]. Returns `None` until a
+    /// response carrying a `Date` header was received.
+    #[must_use]
+    pub fn clock_skew_secs(&self) -> Option<i64> {
+        self.clock_skew.offset_secs()
+    }
+
+    /// Returns how the most recent pubky host resolution was served: from the
+    /// per-host cache, or by a DHT/relay round trip.
+    ///
+    /// Useful to verify cache hit rates while debugging performance. Returns `None`
+    /// until the first request to a pubky host made through the SDK actors, and always
+    /// with a custom transport, which resolves hosts on its own.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async fn ex(pubky: pubky::Pubky) -> pubky::Result<()> {
+    /// use pubky::ResolutionSource;
+    ///
+    /// let url = "pubky://o4dksfbqk85ogzdb5osziw6befigbuxmuxkuxq8434q89uj56uyy/pub/app/data.json";
+    /// pubky.public_storage().get(url).await?;
+    /// if let Some(info) = pubky.client().last_resolution_source() {
+    ///     match info.source {
+    ///         ResolutionSource::Cache => println!("cache hit, {:?} old", info.age),
+    ///         ResolutionSource::Network => println!("resolved over the network"),
+    ///     }
+    /// }
+    /// # Ok(()) }
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    #[must_use]
+    pub fn last_resolution_source(&self) -> Option<CacheInfo> {
+        self.transport.last_resolution()
+    }
+
+    /// A copy of this client with a different response body cap, for downloads that
+    /// are expected to be larger (or must be smaller) than the one set with
+    /// [`PubkyHttpClientBuilder::max_response_bytes`]. `0` removes the cap.
+    ///
+    /// The copy shares connections, caches and rate limits with this client.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[must_use]
+    pub fn with_max_response_bytes(&self, bytes: u64) -> Self {
+        let mut client = self.clone();
+        client.max_response_bytes = NonZeroU64::new(bytes);
+        client
+    }
+
+    // === Sending ===
+
+    /// Send a request prepared by `cross_request(..)` and friends.
+    ///
+    /// Every SDK request goes through here, so this is the single point where the
+    /// HTTP transport is invoked: the custom [`HttpClient`] if one was installed,
+    /// otherwise the reqwest client the request was built with.
+    ///
+    /// On native builds the exchange runs in a `pubky.request` span carrying the
+    /// `http.method`, `server.address` and `http.status_code` semantic-convention fields.
+    ///
+    /// A request to a pubky host that cannot connect fails with
+    /// [`crate::Error::RecordNotFound`] or [`crate::Error::HomeserverUnreachable`],
+    /// see [`Self::explain_connect_failure`].
+    pub(crate) async fn send(
+        &self,
+        rb: reqwest::RequestBuilder,
+    ) -> crate::Result<reqwest::Response> {
+        let (client, request) = rb.build_split();
+        let request = request?;
+        let target = pubky_target(&request);
+        #[cfg(not(target_arch = "wasm32"))]
+        let response = {
+            use tracing::Instrument;
+
+            let span = tracing::debug_span!(
+                "pubky.request",
+                otel.kind = "client",
+                otel.status_code = tracing::field::Empty,
+                http.method = %request.method(),
+                server.address = request.url().host_str().unwrap_or_default(),
+                http.status_code = tracing::field::Empty,
+            );
+            let response = self.execute(client, request).instrument(span.clone()).await;
+            match &response {
+                Ok(response) => {
+                    span.record("http.status_code", response.status().as_u16());
+                    if response.status().is_client_error() || response.status().is_server_error() {
+                        span.record("otel.status_code", "ERROR");
+                    }
+                }
+                Err(_) => {
+                    span.record("otel.status_code", "ERROR");
+                }
+            }
+            response
+        };
+        #[cfg(target_arch = "wasm32")]
+        let response = match &self.custom_http {
+            Some(http) => http.execute(request).await,
+            None => client.execute(request).await.map_err(Into::into),
+        };
+        let from_pubky_host = target.is_some();
+        let response = match (response, target) {
+            (Ok(response), _) => response,
+            (Err(err), Some(public_key)) if err.is_connect_failure() => {
+                return Err(self.explain_connect_failure(public_key, err).await);
+            }
+            (Err(err), _) => return Err(err),
+        };
+        // Only pubky hosts sign our auth tokens, so only their clocks matter.
+        if from_pubky_host {
+            self.clock_skew.observe(response.headers());
+        }
+        Ok(response)
+    }
+
+    /// Tell apart why a request to `public_key` could not connect: the key has no
+    /// PKARR record, so there is no homeserver to connect to, or its record resolves
+    /// and the homeserver does not answer.
+    ///
+    /// Returns `err` as is if the record cannot be resolved for another reason, such as
+    /// the DHT being unreachable too.
+    pub(crate) async fn explain_connect_failure(
+        &self,
+        public_key: PublicKey,
+        err: crate::Error,
+    ) -> crate::Error {
+        match self
+            .pkarr
+            .resolve(&public_key, pkarr::ResolvePolicy::CacheFirst)
+            .await
+        {
+            Err(pkarr::errors::ResolveError::NotFound) => crate::Error::RecordNotFound {
+                public_key: Box::new(public_key),
+            },
+            Ok(_) => match err {
+                crate::Error::Request(source) => crate::Error::HomeserverUnreachable {
+                    public_key: Box::new(public_key),
+                    source,
+                },
+                err => err,
+            },
+            Err(_) => err,
+        }
+    }
+
+    /// Run `request` through the custom transport or `client`, applying the
+    /// bandwidth limits and the response size cap.
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn execute(
+        &self,
+        client: reqwest::Client,
+        request: reqwest::Request,
+    ) -> crate::Result<reqwest::Response> {
+        let request = self.throttle_upload(request);
+        let response = if let Some(http) = &self.custom_http {
+            http.execute(request).await?
+        } else {
+            let start = std::time::Instant::now();
+            client.execute(request).await.map_err(|err| {
+                if err.is_timeout() {
+                    let phase = self.timeouts.phase(&err, start.elapsed());
+                    crate::Error::from(RequestError::Timeout {
+                        phase,
+                        source: crate::errors::redact_url(err),
+                    })
+                } else {
+                    err.into()
+                }
+            })?
+        };
+        self.guard_download(response)
+    }
+
+    /// Stream the request body through the upload limiter, if any.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn throttle_upload(&self, mut request: reqwest::Request) -> reqwest::Request {
+        if let Some(limiter) = &self.upload_limiter
+            && let Some(body) = request.body_mut().take()
+        {
+            let throttled = ThrottledBody::new(body, Arc::clone(limiter));
+            *request.body_mut() = Some(reqwest::Body::wrap(throttled));
+        }
+        request
+    }
+
+    /// Reject responses announcing a body above the size cap, then stream the body
+    /// through the download limiter and the size cap, if any.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn guard_download(&self, response: reqwest::Response) -> crate::Result<reqwest::Response> {
+        use reqwest::ResponseBuilderExt;
+
+        if let Some(limit) = self.max_response_bytes
+            && response
+                .content_length()
+                .is_some_and(|len| len > limit.get())
+        {
+            cross_log!(
+                warn,
+                "Rejecting response from {}: body exceeds {limit} bytes",
+                response.url()
+            );
+            return Err(crate::Error::ResponseTooLarge { limit: limit.get() });
+        }
+        if self.download_limiter.is_none() && self.max_response_bytes.is_none() {
+            return Ok(response);
+        }
+        let url = response.url().clone();
+        let (parts, mut body) = ::http::Response::from(response).into_parts();
+        if let Some(limiter) = &self.download_limiter {
+            body = reqwest::Body::wrap(ThrottledBody::new(body, Arc::clone(limiter)));
+        }
+        if let Some(limit) = self.max_response_bytes {
+            body = reqwest::Body::wrap(SizeLimitedBody::new(body, limit));
+        }
+        let mut builder = ::http::Response::builder()
+            .status(parts.status)
+            .version(parts.version)
+            .url(url);
+        if let Some(headers) = builder.headers_mut() {
+            *headers = parts.headers;
+        }
+        if let Some(extensions) = builder.extensions_mut() {
+            extensions.extend(parts.extensions);
+        }
+        Ok(builder
+            .body(body)
+            .expect("status and version come from a valid response")
+            .into())
+    }
+}
+
+/// The key a request is addressed to: its `_pubky.<key>` or `<key>` host, or else its
+/// `pubky-host` header when it goes to the homeserver's domain.
+fn pubky_target(request: &reqwest::Request) -> Option<PublicKey> {
+    let host = request.url().host_str().unwrap_or_default();
+    let host = host.strip_prefix("_pubky.").unwrap_or(host);
+    PublicKey::try_from_z32(host).ok().or_else(|| {
+        let header = request.headers().get("pubky-host")?.to_str().ok()?;
+        PublicKey::try_from_z32(header).ok()
+    })
 }
 
 #[cfg(test)]
 mod test {
+    use std::collections::HashMap;
+
     use httpmock::MockServer;
     use reqwest::{Method, StatusCode};
 
     use super::*;
 
+    #[test]
+    fn default_testnet_relays_use_the_testnet_relay_port() {
+        assert_eq!(
+            DEFAULT_TESTNET_RELAYS,
+            [format!(
+                "http://localhost:{}/",
+                pubky_common::constants::testnet_ports::PKARR_RELAY
+            )]
+        );
+    }
+
     #[tokio::test]
     async fn test_fetch() {
         let server = MockServer::start();
@@ -531,4 +1342,329 @@ mod test {
         assert_eq!(response.status(), StatusCode::OK);
         mock.assert();
     }
+
+    #[tokio::test]
+    async fn clock_skew_is_only_observed_from_pubky_hosts() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method("GET").path("/");
+            then.status(200)
+                .header("date", "Sat, 01 Jan 2000 00:00:00 GMT");
+        });
+
+        let client = PubkyHttpClient::new().unwrap();
+        client
+            .send(client.request(Method::GET, &server.url("/")))
+            .await
+            .unwrap();
+        assert_eq!(client.clock_skew_secs(), None);
+
+        let public_key = pubky_common::crypto::Keypair::random().public_key();
+        let request = client
+            .request(Method::GET, &server.url("/"))
+            .header("pubky-host", public_key.to_z32());
+        client.send(request).await.unwrap();
+        assert!(client.clock_skew_secs().is_some_and(|skew| skew < 0));
+    }
+
+    /// Span name, parent span name and recorded fields, keyed by span id.
+    type RecordedSpans =
+        HashMap<u64, (&'static str, Option<&'static str>, HashMap<String, String>)>;
+
+    /// Records every span with its parent and fields.
+    #[derive(Clone, Default)]
+    struct SpanRecorder(Arc<std::sync::Mutex<RecordedSpans>>);
+
+    struct FieldVisitor<'a>(&'a mut HashMap<String, String>);
+
+    impl tracing::field::Visit for FieldVisitor<'_> {
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            self.0
+                .insert(field.name().to_string(), format!("{value:?}"));
+        }
+
+        fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+            self.0.insert(field.name().to_string(), value.to_string());
+        }
+    }
+
+    impl<S> tracing_subscriber::Layer<S> for SpanRecorder
+    where
+        S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+    {
+        fn on_new_span(
+            &self,
+            attrs: &tracing::span::Attributes<'_>,
+            id: &tracing::span::Id,
+            ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            let mut fields = HashMap::new();
+            attrs.record(&mut FieldVisitor(&mut fields));
+            let parent = ctx
+                .span(id)
+                .and_then(|span| span.parent())
+                .map(|parent| parent.name());
+            self.0
+                .lock()
+                .unwrap()
+                .insert(id.into_u64(), (attrs.metadata().name(), parent, fields));
+        }
+
+        fn on_record(
+            &self,
+            id: &tracing::span::Id,
+            values: &tracing::span::Record<'_>,
+            _ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            if let Some((_, _, fields)) = self.0.lock().unwrap().get_mut(&id.into_u64()) {
+                values.record(&mut FieldVisitor(fields));
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn send_records_a_request_span() {
+        use tracing::Instrument;
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method("GET").path("/missing");
+            then.status(404);
+        });
+        let recorder = SpanRecorder::default();
+        let _guard =
+            tracing::subscriber::set_default(tracing_subscriber::registry().with(recorder.clone()));
+        // Callsites hit earlier by other tests cached "never" while no subscriber was set.
+        tracing::callsite::rebuild_interest_cache();
+
+        let client = PubkyHttpClient::new().unwrap();
+        let url = url::Url::parse(&server.url("/missing")).unwrap();
+        async {
+            let rb = client.cross_request(Method::GET, url).await.unwrap();
+            client.send(rb).await.unwrap();
+        }
+        .instrument(tracing::info_span!("app"))
+        .await;
+
+        let spans = recorder.0.lock().unwrap();
+        let (_, parent, fields) = spans
+            .values()
+            .find(|(name, _, _)| *name == "pubky.request")
+            .expect("a pubky.request span");
+        assert_eq!(*parent, Some("app"));
+        assert_eq!(fields["otel.kind"], "client");
+        assert_eq!(fields["http.method"], "GET");
+        assert_eq!(fields["server.address"], "127.0.0.1");
+        assert_eq!(fields["http.status_code"], "404");
+        assert_eq!(fields["otel.status_code"], "ERROR");
+    }
+
+    async fn timeout_phase(client: &PubkyHttpClient, url: &str) -> TimeoutPhase {
+        let url = url::Url::parse(url).unwrap();
+        let rb = client.cross_request(Method::GET, url).await.unwrap();
+        match client.send(rb).await {
+            Err(crate::Error::Request(RequestError::Timeout { phase, .. })) => phase,
+            other => panic!("expected a timeout, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn timeouts_report_their_phase() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method("GET").path("/slow");
+            then.status(200).delay(Duration::from_secs(2));
+        });
+        let slow = server.url("/slow");
+
+        let client = PubkyHttpClient::builder()
+            .read_timeout(Duration::from_millis(100))
+            .request_timeout(Duration::from_secs(10))
+            .build()
+            .unwrap();
+        assert_eq!(timeout_phase(&client, &slow).await, TimeoutPhase::Read);
+
+        let client = PubkyHttpClient::builder()
+            .read_timeout(Duration::from_secs(10))
+            .request_timeout(Duration::from_millis(100))
+            .build()
+            .unwrap();
+        assert_eq!(timeout_phase(&client, &slow).await, TimeoutPhase::Total);
+
+        // Accepts TCP connections but never answers the TLS handshake.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let stalled = format!("https://{}/", listener.local_addr().unwrap());
+        let client = PubkyHttpClient::builder()
+            .connect_timeout(Duration::from_millis(100))
+            .request_timeout(Duration::from_secs(10))
+            .build()
+            .unwrap();
+        assert_eq!(
+            timeout_phase(&client, &stalled).await,
+            TimeoutPhase::Connect
+        );
+    }
+
+    /// `/hop/<n>` redirects to `/hop/<n - 1>`; `/hop/0` answers `200`.
+    fn redirect_chain(server: &MockServer, len: usize) {
+        for n in 1..=len {
+            server.mock(|when, then| {
+                when.method("GET").path(format!("/hop/{n}"));
+                then.status(302)
+                    .header("location", format!("/hop/{}", n - 1));
+            });
+        }
+        server.mock(|when, then| {
+            when.method("GET").path("/hop/0");
+            then.status(200);
+        });
+    }
+
+    #[tokio::test]
+    async fn redirect_policy_limits_hops() {
+        let server = MockServer::start();
+        redirect_chain(&server, DEFAULT_MAX_REDIRECTS + 1);
+        let get = |client: &PubkyHttpClient, hops: usize| {
+            client
+                .request(Method::GET, &server.url(format!("/hop/{hops}")))
+                .send()
+        };
+
+        let client = PubkyHttpClient::new().unwrap();
+        let response = get(&client, DEFAULT_MAX_REDIRECTS).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let err = get(&client, DEFAULT_MAX_REDIRECTS + 1).await.unwrap_err();
+        assert!(err.is_redirect());
+
+        let client = PubkyHttpClient::builder()
+            .redirect_policy(RedirectPolicy::None)
+            .build()
+            .unwrap();
+        let response = get(&client, 1).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FOUND);
+
+        let client = PubkyHttpClient::builder()
+            .redirect_policy(RedirectPolicy::Follow)
+            .build()
+            .unwrap();
+        let response = get(&client, DEFAULT_MAX_REDIRECTS + 1).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn rate_limits_throttle_both_directions() {
+        let server = MockServer::start();
+        let payload = vec![b'x'; 3_000];
+        let download = server.mock(|when, then| {
+            when.method("GET").path("/file");
+            then.status(200).body(payload.clone());
+        });
+        let upload = server.mock(|when, then| {
+            when.method("PUT")
+                .path("/file")
+                .body(String::from_utf8(payload.clone()).unwrap());
+            then.status(201);
+        });
+        let url = url::Url::parse(&server.url("/file")).unwrap();
+
+        // 2 000 bytes pass with the initial burst, the remaining 1 000 take half a second.
+        let client = PubkyHttpClient::builder()
+            .download_rate_limit(2_000)
+            .build()
+            .unwrap();
+        let start = std::time::Instant::now();
+        let rb = client
+            .cross_request(Method::GET, url.clone())
+            .await
+            .unwrap();
+        let response = client.send(rb).await.unwrap();
+        assert_eq!(response.url(), &url);
+        assert_eq!(response.bytes().await.unwrap(), payload);
+        assert!(start.elapsed() >= Duration::from_millis(450));
+        download.assert();
+
+        let client = PubkyHttpClient::builder()
+            .upload_rate_limit(2_000)
+            .build()
+            .unwrap();
+        let start = std::time::Instant::now();
+        let rb = client
+            .cross_request(Method::PUT, url)
+            .await
+            .unwrap()
+            .body(payload);
+        let response = client.send(rb).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert!(start.elapsed() >= Duration::from_millis(450));
+        upload.assert();
+    }
+
+    #[tokio::test]
+    async fn max_response_bytes_rejects_large_bodies_up_front() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method("GET").path("/big");
+            then.status(200).body(vec![b'x'; 2_000]);
+        });
+        let url = url::Url::parse(&server.url("/big")).unwrap();
+
+        let client = PubkyHttpClient::builder()
+            .max_response_bytes(1_000)
+            .build()
+            .unwrap();
+        let rb = client
+            .cross_request(Method::GET, url.clone())
+            .await
+            .unwrap();
+        assert!(matches!(
+            client.send(rb).await,
+            Err(crate::Error::ResponseTooLarge { limit: 1_000 })
+        ));
+
+        let unbounded = client.with_max_response_bytes(0);
+        let rb = unbounded.cross_request(Method::GET, url).await.unwrap();
+        let response = unbounded.send(rb).await.unwrap();
+        assert_eq!(response.bytes().await.unwrap().len(), 2_000);
+    }
+
+    #[tokio::test]
+    async fn homeserver_fallback_rejects_cross_origin_redirects() {
+        let target = MockServer::start();
+        target.mock(|when, then| {
+            when.method("GET").path("/elsewhere");
+            then.status(200);
+        });
+        let origin = MockServer::start();
+        origin.mock(|when, then| {
+            when.method("GET").path("/leave");
+            then.status(302)
+                .header("location", target.url("/elsewhere"));
+        });
+        redirect_chain(&origin, 1);
+
+        let client = PubkyHttpClient::new().unwrap();
+        let response = client
+            .icann_http
+            .get(origin.url("/leave"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let err = client
+            .icann_fallback_http
+            .get(origin.url("/leave"))
+            .send()
+            .await
+            .unwrap_err();
+        assert!(err.is_redirect());
+        let response = client
+            .icann_fallback_http
+            .get(origin.url("/hop/1"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
 }
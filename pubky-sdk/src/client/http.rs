@@ -0,0 +1,161 @@
+//! Pluggable HTTP transport used by [`PubkyHttpClient`](super::core::PubkyHttpClient).
+//!
+//! By default requests are executed by the reqwest clients that `PubkyHttpClient`
+//! builds itself (`PubkyTLS` for public-key hosts, X.509 for ICANN hosts). Install a
+//! custom [`HttpClient`] with
+//! [`PubkyHttpClientBuilder::with_http`](super::core::PubkyHttpClientBuilder::with_http)
+//! to route every SDK request through your own networking stack, or to answer
+//! requests from a mock in tests.
+
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use reqwest::{Request, Response};
+
+use crate::Result;
+
+/// Executes fully prepared HTTP requests on behalf of the SDK.
+///
+/// Requests reach the transport fully prepared, with session credentials attached.
+/// On native targets the SDK does not resolve or probe public-key hosts for a custom
+/// transport: requests keep their public-key host (`https://_pubky.<public-key>/…`),
+/// and the transport is responsible for resolving it and verifying the `PubkyTLS`
+/// (raw public key) connection if it talks to real homeservers. On WASM, URLs are
+/// rewritten to the resolved endpoint as usual.
+///
+/// # Example
+/// ```
+/// use pubky::{HttpClient, PubkyHttpClient, Result};
+///
+/// #[derive(Debug)]
+/// struct Offline;
+///
+/// #[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+/// #[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+/// impl HttpClient for Offline {
+///     async fn execute(&self, request: reqwest::Request) -> Result<reqwest::Response> {
+///         let body = format!("offline: {}", request.url());
+///         Ok(reqwest::Response::from(http::Response::new(body)))
+///     }
+/// }
+///
+/// let client = PubkyHttpClient::builder().with_http(Offline).build()?;
+/// # Ok::<_, pubky::BuildError>(())
+/// ```
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+pub trait HttpClient: Debug + Send + Sync {
+    /// Send `request` and return the response.
+    ///
+    /// Non-success status codes are not errors at this layer; the SDK inspects them.
+    ///
+    /// # Errors
+    /// Returns an error if the request could not be delivered or no response was received.
+    async fn execute(&self, request: Request) -> Result<Response>;
+}
+
+/// The default transport.
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl HttpClient for reqwest::Client {
+    async fn execute(&self, request: Request) -> Result<Response> {
+        Ok(Self::execute(self, request).await?)
+    }
+}
+
+/// Lets callers keep a handle on a transport they installed (e.g. to inspect a mock).
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<T: HttpClient + ?Sized> HttpClient for Arc<T> {
+    async fn execute(&self, request: Request) -> Result<Response> {
+        T::execute(self, request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use reqwest::{Method, StatusCode};
+
+    use super::*;
+    use crate::{Pubky, PubkyHttpClient, PublicStorage, errors::RequestError};
+
+    /// Answers every request with a canned status and body and records the URLs.
+    #[derive(Debug)]
+    struct CannedHttp {
+        status: StatusCode,
+        body: &'static str,
+        seen: Mutex<Vec<(Method, String)>>,
+    }
+
+    #[async_trait]
+    impl HttpClient for CannedHttp {
+        async fn execute(&self, request: Request) -> Result<Response> {
+            self.seen
+                .lock()
+                .unwrap()
+                .push((request.method().clone(), request.url().to_string()));
+            let response = http::Response::builder()
+                .status(self.status)
+                .body(self.body)
+                .unwrap();
+            Ok(response.into())
+        }
+    }
+
+    fn storage_over(status: StatusCode, body: &'static str) -> (PublicStorage, Arc<CannedHttp>) {
+        let http = Arc::new(CannedHttp {
+            status,
+            body,
+            seen: Mutex::new(Vec::new()),
+        });
+        let client = PubkyHttpClient::builder()
+            .isolated_pkarr_test()
+            .with_http(Arc::clone(&http))
+            .build()
+            .unwrap();
+        (Pubky::with_client(client).public_storage(), http)
+    }
+
+    const USER: &str = "o4dksfbqk85ogzdb5osziw6befigbuxmuxkuxq8434q89uj56uyy";
+
+    #[tokio::test]
+    async fn custom_transport_serves_requests() {
+        let (storage, http) = storage_over(StatusCode::OK, "hello");
+
+        let text = storage
+            .get(format!("pubky{USER}/pub/app/hello.txt"))
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+
+        assert_eq!(text, "hello");
+        let seen = http.seen.lock().unwrap();
+        assert_eq!(
+            *seen,
+            vec![(
+                Method::GET,
+                format!("https://_pubky.{USER}/pub/app/hello.txt")
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn custom_transport_status_is_checked() {
+        let (storage, _) = storage_over(StatusCode::NOT_FOUND, "missing");
+
+        let err = storage
+            .get(format!("pubky{USER}/pub/app/hello.txt"))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            crate::Error::Request(RequestError::Server { status, .. }) if status == StatusCode::NOT_FOUND
+        ));
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{PublicKey, Result};
+use crate::{Error, HomeserverEndpoint, Pkdns, PubkyHttpClient, PublicKey, Result};
 use url::Url;
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -6,15 +6,65 @@ pub mod native;
 #[cfg(target_arch = "wasm32")]
 pub mod wasm;
 
+impl PubkyHttpClient {
+    /// Check the `_pubky.<user>` → homeserver hop of `url` against the record pins, if
+    /// any are installed, before the transport resolves it on its own.
+    ///
+    /// # Errors
+    /// - [`Error::RecordChanged`] if the user's record names another homeserver than the
+    ///   pinned one under [`PinPolicy::Strict`](crate::PinPolicy::Strict). Resolution
+    ///   failures are left to the transport to report.
+    async fn check_record_pin(&self, url: &Url) -> Result<()> {
+        if self.record_pins.is_none() {
+            return Ok(());
+        }
+        let Some(user) = url
+            .host_str()
+            .and_then(|host| host.strip_prefix("_pubky."))
+            .and_then(|z32| PublicKey::try_from_z32(z32).ok())
+        else {
+            return Ok(());
+        };
+        match Pkdns::with_client(self.clone())
+            .require_homeserver_of(&user)
+            .await
+        {
+            Err(err @ Error::RecordChanged(_)) => Err(err),
+            _ => Ok(()),
+        }
+    }
+}
+
 fn homeserver_url(homeserver: &PublicKey, path: &str) -> Result<Url> {
-    let path = if path.starts_with('/') {
-        path.to_string()
-    } else {
-        format!("/{path}")
-    };
     Ok(Url::parse(&format!(
         "https://{}{}",
         homeserver.z32(),
-        path
+        absolute(path)
     ))?)
 }
+
+/// URL of `path` on the ICANN domain of `endpoint`.
+///
+/// On a `testnet` domain the plain-HTTP port is used if the endpoint publishes one.
+fn domain_url(endpoint: &HomeserverEndpoint, path: &str, testnet: bool) -> Result<Url> {
+    let mut url = Url::parse(&format!("https://{}{}", endpoint.host, absolute(path)))?;
+    let port = match endpoint.http_port {
+        Some(port) if testnet => {
+            url.set_scheme("http")
+                .map_err(|_err| url::ParseError::RelativeUrlWithCannotBeABaseBase)?;
+            Some(port)
+        }
+        _ => endpoint.https_port,
+    };
+    url.set_port(port)
+        .map_err(|_err| url::ParseError::InvalidPort)?;
+    Ok(url)
+}
+
+fn absolute(path: &str) -> String {
+    if path.starts_with('/') {
+        path.to_string()
+    } else {
+        format!("/{path}")
+    }
+}
@@ -2,12 +2,14 @@ use std::collections::HashMap;
 use std::sync::{Arc, Mutex, PoisonError, RwLock};
 use std::time::{Duration, Instant};
 
-use super::homeserver_url;
-use futures_util::StreamExt;
+use super::{domain_url, homeserver_url};
 use tokio::net::TcpStream;
 
+use crate::actors::pkdns::resolve_https_endpoints;
+use crate::client::core::{CacheInfo, ResolutionSource};
+use crate::client::ip_preference::IpVersionPreference;
 use crate::errors::RequestError;
-use crate::{PubkyHttpClient, PublicKey, Result, cross_log};
+use crate::{HomeserverEndpoint, PubkyHttpClient, PublicKey, Result, cross_log};
 use reqwest::{IntoUrl, Method, RequestBuilder};
 use url::Url;
 
@@ -20,6 +22,16 @@ pub(crate) enum ResolvedTransport {
     Icann { domain: String, port: Option<u16> },
 }
 
+impl ResolvedTransport {
+    /// Short name recorded on tracing spans.
+    const fn as_str(&self) -> &'static str {
+        match self {
+            Self::PubkyTls => "pubky-tls",
+            Self::Icann { .. } => "icann",
+        }
+    }
+}
+
 /// Resolves and caches per-host transport decisions (`PubkyTLS` vs ICANN).
 ///
 /// Accepts a `&pkarr::Client` reference when resolution is needed — does not
@@ -28,36 +40,78 @@ pub(crate) enum ResolvedTransport {
 pub(crate) struct TransportResolver {
     cache: Arc<RwLock<HashMap<String, (Instant, ResolvedTransport)>>>,
     guards: Arc<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>>,
+    /// How the most recent [`Self::resolve`] call was served.
+    last: Arc<Mutex<Option<CacheInfo>>>,
+    /// Only direct endpoints of the allowed IP versions are probed.
+    ip_preference: IpVersionPreference,
 }
 
 impl TransportResolver {
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(ip_preference: IpVersionPreference) -> Self {
         Self {
             cache: Arc::new(RwLock::new(HashMap::new())),
             guards: Arc::new(Mutex::new(HashMap::new())),
+            last: Arc::new(Mutex::new(None)),
+            ip_preference,
         }
     }
 
+    /// How the most recent [`Self::resolve`] call was served, if any.
+    pub(crate) fn last_resolution(&self) -> Option<CacheInfo> {
+        *self.last.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
     /// Look up the transport for `qname`, resolving via PKARR on cache miss.
+    ///
+    /// Runs in a `pubky.resolve` span recording where the answer came from and
+    /// which transport was picked.
+    #[tracing::instrument(
+        name = "pubky.resolve",
+        level = "debug",
+        skip_all,
+        fields(
+            server.address = qname,
+            pubky.resolution = tracing::field::Empty,
+            pubky.transport = tracing::field::Empty,
+        )
+    )]
     pub(crate) async fn resolve(&self, qname: &str, pkarr: &pkarr::Client) -> ResolvedTransport {
-        if let Some(t) = self.cached(qname) {
-            return t;
-        }
-        self.resolve_and_cache(qname, pkarr).await
+        let (t, info) = match self.cached(qname) {
+            Some(hit) => hit,
+            None => self.resolve_and_cache(qname, pkarr).await,
+        };
+        let span = tracing::Span::current();
+        let resolution = match info.source {
+            ResolutionSource::Cache => "cache",
+            ResolutionSource::Network => "network",
+        };
+        span.record("pubky.resolution", resolution);
+        span.record("pubky.transport", t.as_str());
+        *self.last.lock().unwrap_or_else(PoisonError::into_inner) = Some(info);
+        t
     }
 
-    /// Fast path: return a cached, non-expired transport decision.
-    fn cached(&self, qname: &str) -> Option<ResolvedTransport> {
+    /// Fast path: return a cached, non-expired transport decision and its age.
+    fn cached(&self, qname: &str) -> Option<(ResolvedTransport, CacheInfo)> {
         let cache = self.cache.read().unwrap_or_else(PoisonError::into_inner);
-        cache
-            .get(qname)
-            .filter(|(ts, _)| ts.elapsed() < TRANSPORT_CACHE_TTL)
-            .map(|(_, t)| t.clone())
+        let (ts, t) = cache.get(qname)?;
+        let age = ts.elapsed();
+        (age < TRANSPORT_CACHE_TTL).then(|| {
+            let info = CacheInfo {
+                source: ResolutionSource::Cache,
+                age,
+            };
+            (t.clone(), info)
+        })
     }
 
     /// Slow path: acquire a per-qname guard, double-check the cache, resolve,
     /// and store the result.
-    async fn resolve_and_cache(&self, qname: &str, pkarr: &pkarr::Client) -> ResolvedTransport {
+    async fn resolve_and_cache(
+        &self,
+        qname: &str,
+        pkarr: &pkarr::Client,
+    ) -> (ResolvedTransport, CacheInfo) {
         let guard = {
             let mut guards = self.guards.lock().unwrap_or_else(PoisonError::into_inner);
             Arc::clone(guards.entry(qname.to_string()).or_default())
@@ -65,46 +119,48 @@ impl TransportResolver {
         let _lock = guard.lock().await;
 
         // Another task may have resolved while we waited for the guard.
-        if let Some(t) = self.cached(qname) {
-            return t;
+        if let Some(hit) = self.cached(qname) {
+            return hit;
         }
 
-        let t = Self::resolve_from_pkarr(pkarr, qname).await;
+        let t = Self::resolve_from_pkarr(pkarr, qname, self.ip_preference).await;
         self.cache
             .write()
             .unwrap_or_else(PoisonError::into_inner)
             .insert(qname.to_string(), (Instant::now(), t.clone()));
-        t
+        let info = CacheInfo {
+            source: ResolutionSource::Network,
+            age: Duration::ZERO,
+        };
+        (t, info)
     }
 
     /// Inspect PKARR endpoints and probe reachability to pick a transport.
-    async fn resolve_from_pkarr(pkarr: &pkarr::Client, qname: &str) -> ResolvedTransport {
-        let stream = pkarr.resolve_https_endpoints(qname);
-        futures_util::pin_mut!(stream);
-
-        let mut has_direct = false;
-        let mut direct_addrs = Vec::new();
-        let mut icann: Option<(String, Option<u16>)> = None;
-
-        while let Some(ep) = stream.next().await {
-            if let Some(domain) = ep.domain() {
-                if icann.is_none() {
-                    icann = Some((domain.to_string(), ep.port()));
-                }
-            } else {
-                has_direct = true;
-                direct_addrs.extend(ep.to_socket_addrs());
-            }
-        }
-
-        let Some((domain, port)) = icann else {
+    ///
+    /// Endpoints are taken in priority order (see [`crate::Pkdns::resolve_endpoints`]):
+    /// `PubkyTLS` endpoints listed before the first ICANN domain are probed, and the
+    /// domain is used if none of them answers.
+    async fn resolve_from_pkarr(
+        pkarr: &pkarr::Client,
+        qname: &str,
+        ip_preference: IpVersionPreference,
+    ) -> ResolvedTransport {
+        let endpoints = resolve_https_endpoints(pkarr, qname).await;
+        let Some(icann) = endpoints.iter().position(HomeserverEndpoint::is_icann) else {
             return ResolvedTransport::PubkyTls;
         };
-        if !has_direct {
+        let domain = endpoints[icann].host.clone();
+        let port = endpoints[icann].https_port;
+        if icann == 0 {
             return ResolvedTransport::Icann { domain, port };
         }
 
-        // Both exist — probe direct endpoint reachability.
+        // Direct endpoints take priority — probe their reachability.
+        let direct_addrs = ip_preference.apply(
+            endpoints[..icann]
+                .iter()
+                .flat_map(|endpoint| endpoint.addrs.iter().copied()),
+        );
         if probe_reachable(&direct_addrs, PROBE_TIMEOUT).await {
             ResolvedTransport::PubkyTls
         } else {
@@ -117,9 +173,19 @@ impl TransportResolver {
     }
 }
 
+/// TCP-probe `addrs` in order, inside a `pubky.connect` span.
+#[tracing::instrument(
+    name = "pubky.connect",
+    level = "debug",
+    skip_all,
+    fields(network.peer.address = tracing::field::Empty, pubky.reachable = false)
+)]
 async fn probe_reachable(addrs: &[std::net::SocketAddr], timeout: Duration) -> bool {
+    let span = tracing::Span::current();
     for addr in addrs {
+        span.record("network.peer.address", tracing::field::display(addr));
         if let Ok(Ok(_)) = tokio::time::timeout(timeout, TcpStream::connect(addr)).await {
+            span.record("pubky.reachable", true);
             return true;
         }
     }
@@ -167,9 +233,14 @@ impl PubkyHttpClient {
         method: Method,
         mut url: Url,
     ) -> Result<RequestBuilder> {
+        self.check_record_pin(&url).await?;
         let Some(pk) = self.prepare_request(&mut url).await? else {
             return Ok(self.request(method, &url));
         };
+        // A custom transport does its own host resolution; skip probing.
+        if self.custom_http.is_some() {
+            return Ok(self.http.request(method, url.as_str()));
+        }
         // Resolve the transport with the full host: for `_pubky.<pk>` hosts the
         // endpoints live under that qname, not the bare key apex. `pk` is only
         // the `pubky-host` header value.
@@ -198,7 +269,11 @@ impl PubkyHttpClient {
         let url = homeserver_url(homeserver, path)?;
         let homeserver_z32 = homeserver.z32();
         let pubky_host_z32 = pubky_host.z32();
-        let transport = self.transport.resolve(&homeserver_z32, &self.pkarr).await;
+        let transport = if self.custom_http.is_some() {
+            ResolvedTransport::PubkyTls
+        } else {
+            self.transport.resolve(&homeserver_z32, &self.pkarr).await
+        };
 
         match transport {
             ResolvedTransport::PubkyTls => Ok(self
@@ -211,6 +286,28 @@ impl PubkyHttpClient {
         }
     }
 
+    /// Route through the ICANN domain `endpoint` while addressing `pubky_host`.
+    ///
+    /// The connection is verified with the domain's X.509 certificate. Local testnets
+    /// serving on `localhost` are reached over their plain-HTTP port.
+    #[allow(
+        clippy::unused_async,
+        reason = "keep async signature aligned with WASM build"
+    )]
+    pub(crate) async fn cross_request_via_domain(
+        &self,
+        method: Method,
+        endpoint: &HomeserverEndpoint,
+        pubky_host: &PublicKey,
+        path: &str,
+    ) -> Result<RequestBuilder> {
+        let url = domain_url(endpoint, path, endpoint.host == "localhost")?;
+        Ok(self
+            .icann_fallback_http
+            .request(method, url.as_str())
+            .header("pubky-host", pubky_host.z32()))
+    }
+
     /// Build a [`RequestBuilder`] for a resolved pubky host transport.
     fn build_pubky_request(
         &self,
@@ -229,9 +326,8 @@ impl PubkyHttpClient {
                         .set_port(Some(*p))
                         .map_err(|_err| url::ParseError::InvalidPort)?;
                 }
-                cross_log!(debug, "ICANN fallback for {pk} via {domain}");
                 Ok(self
-                    .icann_http
+                    .icann_fallback_http
                     .request(method, icann_url.as_str())
                     .header("pubky-host", pk))
             }
@@ -351,7 +447,7 @@ mod tests {
         let mut builder = PubkyHttpClient::builder();
         builder
             .isolated_pkarr_test()
-            .pkarr(|b| b.cache(cache.clone()));
+            .pkarr(|b| b.cache(Arc::<InMemoryCache>::clone(&cache)));
         let client = builder.build().unwrap();
         let cache_key: pkarr::CacheKey = keypair.public_key().into();
         cache.put(&cache_key, packet);
@@ -395,7 +491,12 @@ mod tests {
             .unwrap();
         let pkarr = pkarr_with_packet(&kp, &packet);
 
-        let t = TransportResolver::resolve_from_pkarr(&pkarr, &kp.public_key().to_string()).await;
+        let t = TransportResolver::resolve_from_pkarr(
+            &pkarr,
+            &kp.public_key().to_string(),
+            IpVersionPreference::Auto,
+        )
+        .await;
         assert!(matches!(t, ResolvedTransport::PubkyTls));
     }
 
@@ -409,13 +510,68 @@ mod tests {
             .unwrap();
         let pkarr = pkarr_with_packet(&kp, &packet);
 
-        let t = TransportResolver::resolve_from_pkarr(&pkarr, &kp.public_key().to_string()).await;
+        let t = TransportResolver::resolve_from_pkarr(
+            &pkarr,
+            &kp.public_key().to_string(),
+            IpVersionPreference::Auto,
+        )
+        .await;
         assert!(matches!(t, ResolvedTransport::Icann { .. }));
         if let ResolvedTransport::Icann { domain, .. } = t {
             assert_eq!(domain, "example.com");
         }
     }
 
+    #[tokio::test]
+    async fn resolve_transport_prefers_lower_priority_icann_over_direct() {
+        let kp = Keypair::random();
+        let icann = SVCB::new(1, "example.com".try_into().unwrap());
+        let mut direct = SVCB::new(10, ".".try_into().unwrap());
+        direct.set_port(6881);
+        let packet = SignedPacket::builder()
+            .https(".".try_into().unwrap(), direct, 3600)
+            .https(".".try_into().unwrap(), icann, 3600)
+            .address(".".try_into().unwrap(), "127.0.0.1".parse().unwrap(), 3600)
+            .sign(&kp)
+            .unwrap();
+        let pkarr = pkarr_with_packet(&kp, &packet);
+
+        let t = TransportResolver::resolve_from_pkarr(
+            &pkarr,
+            &kp.public_key().to_string(),
+            IpVersionPreference::Auto,
+        )
+        .await;
+        assert!(
+            matches!(t, ResolvedTransport::Icann { ref domain, .. } if domain == "example.com"),
+            "expected the priority 1 ICANN endpoint, got {t:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn resolve_records_cache_hits_and_misses() {
+        let kp = Keypair::random();
+        let svcb = SVCB::new(1, "example.com".try_into().unwrap());
+        let packet = SignedPacket::builder()
+            .https(".".try_into().unwrap(), svcb, 3600)
+            .sign(&kp)
+            .unwrap();
+        let pkarr = pkarr_with_packet(&kp, &packet);
+        let resolver = TransportResolver::new(IpVersionPreference::Auto);
+        let qname = kp.public_key().to_string();
+        assert_eq!(resolver.last_resolution(), None);
+
+        resolver.resolve(&qname, &pkarr).await;
+        let miss = resolver.last_resolution().unwrap();
+        assert_eq!(miss.source, ResolutionSource::Network);
+        assert_eq!(miss.age, Duration::ZERO);
+
+        resolver.resolve(&qname, &pkarr).await;
+        let hit = resolver.last_resolution().unwrap();
+        assert_eq!(hit.source, ResolutionSource::Cache);
+        assert!(hit.age < TRANSPORT_CACHE_TTL);
+    }
+
     /// Regression test: requests to `https://_pubky.<user>/...` must apply the
     /// ICANN fallback. The user's endpoints are published under the
     /// `_pubky.<user>` qname (an alias to the homeserver key), so the
@@ -448,7 +604,7 @@ mod tests {
         let mut builder = PubkyHttpClient::builder();
         builder
             .isolated_pkarr_test()
-            .pkarr(|b| b.cache(cache.clone()));
+            .pkarr(|b| b.cache(Arc::<InMemoryCache>::clone(&cache)));
         let client = builder.build().unwrap();
         cache.put(&homeserver.public_key().into(), &homeserver_packet);
         cache.put(&user.public_key().into(), &user_packet);
@@ -485,7 +641,7 @@ mod tests {
         let mut builder = PubkyHttpClient::builder();
         builder
             .isolated_pkarr_test()
-            .pkarr(|b| b.cache(cache.clone()));
+            .pkarr(|b| b.cache(Arc::<InMemoryCache>::clone(&cache)));
         let client = builder.build().unwrap();
         cache.put(&homeserver.public_key().into(), &homeserver_packet);
         let homeserver_pk = PublicKey::try_from_z32(&homeserver.public_key().to_string()).unwrap();
@@ -517,7 +673,12 @@ mod tests {
             .unwrap();
         let pkarr = pkarr_with_packet(&kp, &packet);
 
-        let t = TransportResolver::resolve_from_pkarr(&pkarr, &kp.public_key().to_string()).await;
+        let t = TransportResolver::resolve_from_pkarr(
+            &pkarr,
+            &kp.public_key().to_string(),
+            IpVersionPreference::Auto,
+        )
+        .await;
         assert!(
             matches!(t, ResolvedTransport::Icann { ref domain, .. } if domain == "example.com"),
             "expected ICANN fallback, got {t:?}"
@@ -1,12 +1,10 @@
 //! HTTP methods that support `https://` with Pkarr domains, including `_pubky.<pk>` URLs
 
-use super::homeserver_url;
-use crate::PublicKey;
+use super::{domain_url, homeserver_url};
+use crate::actors::pkdns::resolve_https_endpoints;
 use crate::errors::{PkarrError, RequestError, Result};
+use crate::{HomeserverEndpoint, PublicKey};
 use crate::{PubkyHttpClient, cross_log};
-use futures_lite::StreamExt;
-use pkarr::dns::rdata::SVCParam;
-use pkarr::extra::endpoints::Endpoint;
 use reqwest::{IntoUrl, Method, RequestBuilder};
 use url::Url;
 
@@ -55,6 +53,28 @@ impl PubkyHttpClient {
             .header("pubky-host", pubky_host.z32()))
     }
 
+    /// Route through the ICANN domain `endpoint` while addressing `pubky_host`.
+    #[allow(
+        clippy::unused_async,
+        reason = "keep async signature aligned with native build"
+    )]
+    pub(crate) async fn cross_request_via_domain(
+        &self,
+        method: Method,
+        endpoint: &HomeserverEndpoint,
+        pubky_host: &PublicKey,
+        path: &str,
+    ) -> Result<RequestBuilder> {
+        let testnet = endpoint.host == "localhost"
+            || self.testnet_host.as_deref() == Some(endpoint.host.as_str());
+        let url = domain_url(endpoint, path, testnet)?;
+        Ok(self
+            .http
+            .request(method, url)
+            .fetch_credentials_omit()
+            .header("pubky-host", pubky_host.z32()))
+    }
+
     async fn cross_request_with_credentials<T: IntoUrl>(
         &self,
         method: Method,
@@ -64,6 +84,7 @@ impl PubkyHttpClient {
         let original_url = url.as_str();
         let mut url = Url::parse(original_url)?;
 
+        self.check_record_pin(&url).await?;
         let pubky_host = self.prepare_request(&mut url).await?;
 
         let request = self.http.request(method, url.clone());
@@ -86,6 +107,7 @@ impl PubkyHttpClient {
     ///
     /// # Errors
     /// - Returns [`crate::errors::PkarrError`] when PKARR resolution fails or produces invalid endpoints.
+    /// - Returns [`crate::errors::Error::RecordNotFound`] when the host's key has no PKARR record.
     pub async fn prepare_request(&self, url: &mut Url) -> Result<Option<String>> {
         let host = url.host_str().unwrap_or("").to_string();
 
@@ -125,23 +147,28 @@ impl PubkyHttpClient {
         let qname = original_url.host_str().unwrap_or("").to_string();
         cross_log!(debug, "Prepare WASM request {}", url.as_str());
 
-        let stream = self.pkarr.resolve_https_endpoints(&qname);
+        let endpoints = resolve_https_endpoints(&self.pkarr, &qname).await;
 
-        self.transform_url_with_stream(url, &qname, &original_url, stream)
-            .await
+        let result = self.transform_url_with_endpoints(url, &qname, &original_url, &endpoints);
+        // No endpoints may mean there is no record at all, which callers need to know.
+        let host = qname.strip_prefix("_pubky.").unwrap_or(&qname);
+        match (result, PublicKey::try_from_z32(host)) {
+            (Err(err), Ok(public_key)) => Err(self.explain_connect_failure(public_key, err).await),
+            (result, _) => result,
+        }
     }
 
-    async fn transform_url_with_stream<S>(
+    /// Point `url` at the first ICANN domain among `endpoints`, which are in the order
+    /// described on [`crate::Pkdns::resolve_endpoints`]. Browsers can't speak `PubkyTLS`,
+    /// so direct endpoints are skipped.
+    fn transform_url_with_endpoints(
         &self,
         url: &mut Url,
         qname: &str,
         original_url: &Url,
-        mut stream: S,
-    ) -> Result<()>
-    where
-        S: futures_lite::Stream<Item = Endpoint> + Unpin,
-    {
-        let Some(endpoint) = Self::select_first_usable_endpoint(&mut stream).await else {
+        endpoints: &[HomeserverEndpoint],
+    ) -> Result<()> {
+        let Some(endpoint) = endpoints.iter().find(|endpoint| endpoint.is_icann()) else {
             cross_log!(error, "Could not resolve host {qname}");
             let host_display = if qname.is_empty() {
                 "<empty host>".to_string()
@@ -156,67 +183,37 @@ impl PubkyHttpClient {
             .into());
         };
 
-        self.apply_endpoint_to_url(url, &endpoint)?;
+        self.apply_endpoint_to_url(url, endpoint)?;
 
         cross_log!(debug, "Transformed URL to {}", url.as_str());
 
         Ok(())
     }
 
-    async fn select_first_usable_endpoint<S>(stream: &mut S) -> Option<Endpoint>
-    where
-        S: futures_lite::Stream<Item = Endpoint> + Unpin,
-    {
-        while let Some(endpoint) = stream.next().await {
-            if endpoint.domain().is_some() {
-                return Some(endpoint);
-            }
-        }
-
-        None
-    }
-
-    fn apply_endpoint_to_url(&self, url: &mut Url, endpoint: &Endpoint) -> Result<()> {
-        let is_testnet_domain = endpoint.domain().is_some_and(|domain| {
-            if domain == "localhost" {
-                return true;
-            }
-            if let Some(test_host) = &self.testnet_host {
-                return domain == test_host;
-            }
-            false
-        });
+    fn apply_endpoint_to_url(&self, url: &mut Url, endpoint: &HomeserverEndpoint) -> Result<()> {
+        let is_testnet_domain = endpoint.host == "localhost"
+            || self.testnet_host.as_deref() == Some(endpoint.host.as_str());
 
         if is_testnet_domain {
             url.set_scheme("http")
                 .map_err(|_err| url::ParseError::RelativeUrlWithCannotBeABaseBase)?;
 
-            let http_port = endpoint
-                .get_param(pubky_common::constants::reserved_param_keys::HTTP_PORT)
-                .and_then(|param| match param {
-                    SVCParam::Unknown(_, bytes) => <[u8; 2]>::try_from(bytes.as_ref()).ok(),
-                    SVCParam::Port(port) => Some(port.to_be_bytes()),
-                    _ => None,
-                })
-                .map(u16::from_be_bytes)
-                .ok_or_else(|| {
-                    PkarrError::InvalidRecord(
-                        "Pkarr record missing required HTTP_PORT parameter for testnet endpoint"
-                            .to_string(),
-                    )
-                })?;
+            let http_port = endpoint.http_port.ok_or_else(|| {
+                PkarrError::InvalidRecord(
+                    "Pkarr record missing required HTTP_PORT parameter for testnet endpoint"
+                        .to_string(),
+                )
+            })?;
 
             url.set_port(Some(http_port))
                 .map_err(|_err| url::ParseError::InvalidPort)?;
-        } else if let Some(port) = endpoint.port() {
+        } else if let Some(port) = endpoint.https_port {
             url.set_port(Some(port))
                 .map_err(|_err| url::ParseError::InvalidPort)?;
         }
 
-        if let Some(domain) = endpoint.domain() {
-            url.set_host(Some(domain))
-                .map_err(|_err| url::ParseError::SetHostOnCannotBeABaseUrl)?;
-        }
+        url.set_host(Some(&endpoint.host))
+            .map_err(|_err| url::ParseError::SetHostOnCannotBeABaseUrl)?;
 
         Ok(())
     }
@@ -226,42 +223,67 @@ impl PubkyHttpClient {
 mod tests {
     use super::*;
     use crate::Keypair;
-    use futures_lite::stream;
     use wasm_bindgen_test::*;
 
     wasm_bindgen_test_configure!(run_in_browser);
 
+    fn endpoint(host: String, https_port: Option<u16>) -> HomeserverEndpoint {
+        HomeserverEndpoint {
+            host,
+            priority: 1,
+            https_port,
+            http_port: None,
+            addrs: Vec::new(),
+        }
+    }
+
     #[wasm_bindgen_test(async)]
     async fn transform_url_errors_when_no_domain_is_found() {
         let client = PubkyHttpClient::new().unwrap();
         let pk = Keypair::random().public_key().z32();
         let mut url = Url::parse(&format!("https://_pubky.{pk}/pub/app/file.txt")).unwrap();
         let original = url.clone();
+        let direct = [endpoint(pk.clone(), Some(6287))];
 
-        let result = client
-            .transform_url_with_stream(
+        for endpoints in [&[][..], &direct[..]] {
+            let result = client.transform_url_with_endpoints(
                 &mut url,
-                url.host_str().unwrap(),
+                original.host_str().unwrap(),
                 &original,
-                stream::empty(),
-            )
-            .await;
+                endpoints,
+            );
 
-        let err = result.expect_err("transform_url should fail when no endpoint is resolved");
+            let err = result.expect_err("transform_url should fail without an ICANN endpoint");
 
-        let crate::errors::Error::Pkarr(PkarrError::InvalidRecord(message)) = err else {
-            panic!("expected pkarr invalid record error, got {err:?}");
-        };
+            let crate::errors::Error::Pkarr(PkarrError::InvalidRecord(message)) = err else {
+                panic!("expected pkarr invalid record error, got {err:?}");
+            };
 
-        assert!(message.contains("No HTTPS endpoints found"));
+            assert!(message.contains("No HTTPS endpoints found"));
+        }
     }
 
     #[wasm_bindgen_test(async)]
-    async fn select_first_usable_endpoint_is_none_for_empty_stream() {
-        let mut empty = stream::empty();
-
-        let result = PubkyHttpClient::select_first_usable_endpoint(&mut empty).await;
+    async fn transform_url_uses_the_first_domain_in_priority_order() {
+        let client = PubkyHttpClient::new().unwrap();
+        let pk = Keypair::random().public_key().z32();
+        let mut url = Url::parse(&format!("https://_pubky.{pk}/pub/app/file.txt")).unwrap();
+        let original = url.clone();
+        let endpoints = [
+            endpoint(pk, Some(6287)),
+            endpoint("first.example".into(), Some(8443)),
+            endpoint("second.example".into(), None),
+        ];
+
+        client
+            .transform_url_with_endpoints(
+                &mut url,
+                original.host_str().unwrap(),
+                &original,
+                &endpoints,
+            )
+            .unwrap();
 
-        assert!(result.is_none());
+        assert_eq!(url.as_str(), "https://first.example:8443/pub/app/file.txt");
     }
 }
@@ -0,0 +1,137 @@
+//! Restricting or ordering the IP versions native clients connect over.
+//!
+//! Some networks reach a host over one IP version only, while its DNS or Pkarr
+//! records advertise both. Happy eyeballs may then keep picking the broken one, so
+//! resolved addresses are filtered or reordered before the connector sees them.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+
+/// Which IP versions native clients connect over, see
+/// [`PubkyHttpClientBuilder::ip_version_preference`](crate::PubkyHttpClientBuilder::ip_version_preference).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IpVersionPreference {
+    /// Use every resolved address in the order the resolver returns them, racing
+    /// IPv4 and IPv6 with happy eyeballs.
+    #[default]
+    Auto,
+    /// Only connect over IPv4.
+    V4Only,
+    /// Only connect over IPv6.
+    V6Only,
+    /// Try IPv6 addresses first, falling back to IPv4.
+    PreferV6,
+}
+
+impl IpVersionPreference {
+    /// Filter and order `addrs`, keeping the resolver's order within each IP version.
+    pub(crate) fn apply(self, addrs: impl IntoIterator<Item = SocketAddr>) -> Vec<SocketAddr> {
+        let addrs = addrs.into_iter();
+        match self {
+            Self::Auto => addrs.collect(),
+            Self::V4Only => addrs.filter(SocketAddr::is_ipv4).collect(),
+            Self::V6Only => addrs.filter(SocketAddr::is_ipv6).collect(),
+            Self::PreferV6 => {
+                let mut addrs: Vec<_> = addrs.collect();
+                addrs.sort_by_key(SocketAddr::is_ipv4);
+                addrs
+            }
+        }
+    }
+}
+
+/// Resolver applying an [`IpVersionPreference`] to the addresses of `inner`, or of the
+/// system resolver when `inner` is `None`.
+pub(crate) struct PreferenceResolver {
+    inner: Option<Arc<dyn Resolve>>,
+    preference: IpVersionPreference,
+}
+
+impl PreferenceResolver {
+    pub(crate) fn new(inner: Option<Arc<dyn Resolve>>, preference: IpVersionPreference) -> Self {
+        Self { inner, preference }
+    }
+}
+
+impl Resolve for PreferenceResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let inner = self.inner.clone();
+        let preference = self.preference;
+        Box::pin(async move {
+            let host = name.as_str().to_string();
+            let addrs: Vec<SocketAddr> = match inner {
+                Some(inner) => inner.resolve(name).await?.collect(),
+                None => tokio::net::lookup_host((host.as_str(), 0)).await?.collect(),
+            };
+            let addrs = preference.apply(addrs);
+            if addrs.is_empty() {
+                return Err(format!("no address of {host} matches {preference:?}").into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn addrs() -> Vec<SocketAddr> {
+        [
+            "10.0.0.1:443",
+            "[2001:db8::1]:443",
+            "10.0.0.2:443",
+            "[2001:db8::2]:443",
+        ]
+        .iter()
+        .map(|addr| addr.parse().unwrap())
+        .collect()
+    }
+
+    #[test]
+    fn filters_and_orders_addresses() {
+        let all = addrs();
+        assert_eq!(IpVersionPreference::Auto.apply(all.clone()), all);
+        assert_eq!(
+            IpVersionPreference::V4Only.apply(all.clone()),
+            vec![all[0], all[2]]
+        );
+        assert_eq!(
+            IpVersionPreference::V6Only.apply(all.clone()),
+            vec![all[1], all[3]]
+        );
+        assert_eq!(
+            IpVersionPreference::PreferV6.apply(all.clone()),
+            vec![all[1], all[3], all[0], all[2]]
+        );
+    }
+
+    struct Fixed(Vec<SocketAddr>);
+
+    impl Resolve for Fixed {
+        fn resolve(&self, _name: Name) -> Resolving {
+            let addrs = self.0.clone();
+            Box::pin(async move { Ok(Box::new(addrs.into_iter()) as Addrs) })
+        }
+    }
+
+    #[tokio::test]
+    async fn resolver_wraps_the_inner_resolver() {
+        let v4: Vec<SocketAddr> = vec!["10.0.0.1:443".parse().unwrap()];
+        let inner: Arc<dyn Resolve> = Arc::new(Fixed(v4.clone()));
+        let name = || Name::from_str("example.com").unwrap();
+
+        let v4_only =
+            PreferenceResolver::new(Some(Arc::clone(&inner)), IpVersionPreference::V4Only);
+        let resolved: Vec<_> = v4_only.resolve(name()).await.unwrap().collect();
+        assert_eq!(resolved, v4);
+
+        let v6_only = PreferenceResolver::new(Some(inner), IpVersionPreference::V6Only);
+        let err = v6_only.resolve(name()).await.err().unwrap();
+        assert!(err.to_string().contains("example.com"), "{err}");
+    }
+}
@@ -1,2 +1,14 @@
+pub(crate) mod clock_skew;
 pub mod core;
+pub mod http;
 mod http_targets;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod ip_preference;
+pub mod response;
+pub mod server_version;
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) mod service;
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) mod size_limit;
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) mod throttle;
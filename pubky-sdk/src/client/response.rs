@@ -0,0 +1,434 @@
+//! Platform-independent HTTP response returned by storage reads.
+
+use bytes::{Bytes, BytesMut};
+use futures_util::{Stream, StreamExt};
+use pubky_common::storage::disposition_filename;
+use reqwest::{
+    StatusCode,
+    header::{CONTENT_DISPOSITION, HeaderMap},
+};
+use url::Url;
+
+use crate::{Result, errors::RequestError};
+
+/// Default line length limit of [`SessionStorage::get_lines`](crate::SessionStorage::get_lines)
+/// (1 MiB).
+pub const DEFAULT_MAX_LINE_BYTES: usize = 1024 * 1024;
+
+/// Response to a Pubky read, with the same API on native and WASM.
+///
+/// Returned by [`SessionStorage::get`](crate::SessionStorage::get),
+/// [`PublicStorage::get`](crate::PublicStorage::get) and [`Pubky::fetch`](crate::Pubky::fetch).
+/// Body accessors consume the response and report failures as [`crate::Error`].
+///
+/// # Examples
+/// ```no_run
+/// # async fn ex(storage: pubky::PublicStorage) -> pubky::Result<()> {
+/// let resp = storage.get("{other_pk}/pub/my-cool-app/file.txt").await?;
+/// let content_type = resp.headers().get("content-type").cloned();
+/// let text = resp.text().await?;
+/// # Ok(()) }
+/// ```
+#[derive(Debug)]
+pub struct PubkyResponse {
+    inner: reqwest::Response,
+}
+
+impl PubkyResponse {
+    /// HTTP status code of the response.
+    #[must_use]
+    pub fn status(&self) -> StatusCode {
+        self.inner.status()
+    }
+
+    /// Response headers.
+    #[must_use]
+    pub fn headers(&self) -> &HeaderMap {
+        self.inner.headers()
+    }
+
+    /// Final transport URL of the response, after redirects.
+    #[must_use]
+    pub fn url(&self) -> &Url {
+        self.inner.url()
+    }
+
+    /// Body size announced by the server, if known.
+    #[must_use]
+    pub fn content_length(&self) -> Option<u64> {
+        self.inner.content_length()
+    }
+
+    /// Filename the resource was uploaded with, from the `Content-Disposition` header.
+    ///
+    /// Set by [`SessionStorage::put_as_attachment`](crate::SessionStorage::put_as_attachment).
+    /// The name is sanitized, so it never contains path separators or control characters.
+    #[must_use]
+    pub fn filename(&self) -> Option<String> {
+        content_disposition_filename(self.headers())
+    }
+
+    /// Read the full body.
+    ///
+    /// # Errors
+    /// - [`crate::errors::Error::Request`] if the transfer fails.
+    pub async fn bytes(self) -> Result<Bytes> {
+        Ok(self.inner.bytes().await?)
+    }
+
+    /// Read the full body as text, decoded with the charset of the `Content-Type`
+    /// header (UTF-8 by default).
+    ///
+    /// # Errors
+    /// - [`crate::errors::Error::Request`] if the transfer fails.
+    pub async fn text(self) -> Result<String> {
+        Ok(self.inner.text().await?)
+    }
+
+    /// Read the full body as text, decoded strictly with the charset of the
+    /// `Content-Type` header.
+    ///
+    /// A byte order mark takes precedence over the header, and a missing or unknown
+    /// charset falls back to UTF-8. Unlike [`Self::text`], bytes that are invalid in
+    /// the charset are an error instead of being replaced; use
+    /// [`Self::text_with_encoding_lossy`] to replace them with `U+FFFD`.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async fn ex(storage: pubky::PublicStorage) -> pubky::Result<()> {
+    /// // Stored with `Content-Type: text/plain; charset=Shift_JIS`.
+    /// let resp = storage.get("{other_pk}/pub/legacy-app/notes.txt").await?;
+    /// let text = resp.text_with_encoding().await?;
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// - [`crate::errors::Error::Request`] if the transfer fails.
+    /// - [`RequestError::DecodeText`] if the body is not valid in the charset.
+    #[cfg(feature = "encoding")]
+    pub async fn text_with_encoding(self) -> Result<String> {
+        let encoding = charset_encoding(self.headers());
+        let body = self.bytes().await?;
+        let (encoding, body) = strip_bom(encoding, &body);
+        encoding
+            .decode_without_bom_handling_and_without_replacement(body)
+            .map(std::borrow::Cow::into_owned)
+            .ok_or_else(|| {
+                RequestError::DecodeText {
+                    encoding: encoding.name().to_string(),
+                }
+                .into()
+            })
+    }
+
+    /// Like [`Self::text_with_encoding`], but replaces invalid bytes with `U+FFFD`
+    /// instead of failing.
+    ///
+    /// # Errors
+    /// - [`crate::errors::Error::Request`] if the transfer fails.
+    #[cfg(feature = "encoding")]
+    pub async fn text_with_encoding_lossy(self) -> Result<String> {
+        let encoding = charset_encoding(self.headers());
+        let body = self.bytes().await?;
+        let (encoding, body) = strip_bom(encoding, &body);
+        Ok(encoding.decode_without_bom_handling(body).0.into_owned())
+    }
+
+    /// Read the full body and deserialize it as JSON.
+    ///
+    /// # Errors
+    /// - [`crate::errors::Error::Request`] if the transfer fails.
+    /// - [`RequestError::DecodeJson`] if the body is not valid JSON for `T`.
+    pub async fn json<T: serde::de::DeserializeOwned>(self) -> Result<T> {
+        let body = self.bytes().await?;
+        serde_json::from_slice(&body).map_err(|e| {
+            RequestError::DecodeJson {
+                message: e.to_string(),
+            }
+            .into()
+        })
+    }
+
+    /// Stream the body in chunks as they arrive.
+    ///
+    /// The stream is `Send` on native targets.
+    pub fn stream(self) -> impl Stream<Item = Result<Bytes>> {
+        self.inner
+            .bytes_stream()
+            .map(|chunk| chunk.map_err(crate::Error::from))
+    }
+
+    /// Stream the body as UTF-8 lines, without reading it into memory first.
+    ///
+    /// Lines are split on `\n`; the terminator and a preceding `\r` are removed, and a
+    /// last line without terminator is yielded too. Multi-byte characters split across
+    /// chunks are reassembled. The stream ends after the first error.
+    ///
+    /// # Errors
+    /// The stream yields:
+    /// - [`RequestError::LineTooLong`] once a line exceeds `max_line_bytes`, before
+    ///   buffering more of it.
+    /// - [`RequestError::DecodeText`] for a line that is not valid UTF-8.
+    /// - [`crate::errors::Error::Request`] if the transfer fails.
+    pub fn lines(self, max_line_bytes: usize) -> impl Stream<Item = Result<String>> {
+        split_lines(self.stream(), max_line_bytes)
+    }
+
+    /// The underlying `reqwest` response, for APIs not covered here.
+    #[must_use]
+    pub fn into_inner(self) -> reqwest::Response {
+        self.inner
+    }
+}
+
+/// Frame a stream of body chunks into lines, see [`PubkyResponse::lines`].
+fn split_lines<S>(chunks: S, max_line_bytes: usize) -> impl Stream<Item = Result<String>>
+where
+    S: Stream<Item = Result<Bytes>> + Unpin,
+{
+    struct Lines<S> {
+        chunks: S,
+        buf: BytesMut,
+        /// Bytes of `buf` already known not to contain `\n`.
+        scanned: usize,
+        done: bool,
+    }
+
+    let state = Lines {
+        chunks,
+        buf: BytesMut::new(),
+        scanned: 0,
+        done: false,
+    };
+    futures_util::stream::unfold(state, move |mut lines| async move {
+        loop {
+            if lines.done {
+                return None;
+            }
+            if let Some(pos) = lines.buf[lines.scanned..].iter().position(|b| *b == b'\n') {
+                let mut line = lines.buf.split_to(lines.scanned + pos + 1);
+                lines.scanned = 0;
+                line.truncate(line.len() - 1);
+                let line = decode_line(line, max_line_bytes);
+                lines.done = line.is_err();
+                return Some((line, lines));
+            }
+            lines.scanned = lines.buf.len();
+            // One extra byte for a `\r` whose `\n` is still in flight.
+            if lines.buf.len() > max_line_bytes + 1 {
+                lines.done = true;
+                let err = RequestError::LineTooLong {
+                    limit: max_line_bytes,
+                };
+                return Some((Err(err.into()), lines));
+            }
+            match lines.chunks.next().await {
+                Some(Ok(chunk)) => lines.buf.extend_from_slice(&chunk),
+                Some(Err(err)) => {
+                    lines.done = true;
+                    return Some((Err(err), lines));
+                }
+                None => {
+                    lines.done = true;
+                    if lines.buf.is_empty() {
+                        return None;
+                    }
+                    let line = decode_line(lines.buf.split(), max_line_bytes);
+                    return Some((line, lines));
+                }
+            }
+        }
+    })
+}
+
+/// A line without its `\n`, with any trailing `\r` removed and decoded as UTF-8.
+fn decode_line(mut line: BytesMut, max_line_bytes: usize) -> Result<String> {
+    if line.last() == Some(&b'\r') {
+        line.truncate(line.len() - 1);
+    }
+    if line.len() > max_line_bytes {
+        return Err(RequestError::LineTooLong {
+            limit: max_line_bytes,
+        }
+        .into());
+    }
+    String::from_utf8(line.to_vec()).map_err(|_err| {
+        RequestError::DecodeText {
+            encoding: "UTF-8".to_string(),
+        }
+        .into()
+    })
+}
+
+/// Filename of an `attachment` `Content-Disposition` response header, if any.
+pub(crate) fn content_disposition_filename(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(CONTENT_DISPOSITION)
+        .and_then(|value| std::str::from_utf8(value.as_bytes()).ok())
+        .and_then(disposition_filename)
+}
+
+/// Encoding named by the `charset` parameter of the `Content-Type` header, or UTF-8
+/// if it is missing or unknown.
+#[cfg(feature = "encoding")]
+fn charset_encoding(headers: &HeaderMap) -> &'static encoding_rs::Encoding {
+    headers
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|content_type| {
+            content_type.split(';').skip(1).find_map(|param| {
+                let (name, value) = param.split_once('=')?;
+                name.trim()
+                    .eq_ignore_ascii_case("charset")
+                    .then(|| value.trim().trim_matches('"'))
+            })
+        })
+        .and_then(|label| encoding_rs::Encoding::for_label(label.as_bytes()))
+        .unwrap_or(encoding_rs::UTF_8)
+}
+
+/// Let a byte order mark at the start of `body` override `encoding`, and drop it.
+#[cfg(feature = "encoding")]
+fn strip_bom<'a>(
+    encoding: &'static encoding_rs::Encoding,
+    body: &'a [u8],
+) -> (&'static encoding_rs::Encoding, &'a [u8]) {
+    match encoding_rs::Encoding::for_bom(body) {
+        Some((bom_encoding, bom_len)) => (bom_encoding, &body[bom_len..]),
+        None => (encoding, body),
+    }
+}
+
+impl From<reqwest::Response> for PubkyResponse {
+    fn from(inner: reqwest::Response) -> Self {
+        Self { inner }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+
+    fn response(body: &'static str) -> PubkyResponse {
+        let response = http::Response::builder()
+            .status(200)
+            .header("content-type", "application/json")
+            .body(body)
+            .unwrap();
+        reqwest::Response::from(response).into()
+    }
+
+    #[tokio::test]
+    async fn exposes_status_headers_and_body() {
+        let resp = response(r#"{"name":"alice"}"#);
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers()["content-type"], "application/json");
+
+        let value: serde_json::Value = resp.json().await.unwrap();
+        assert_eq!(value["name"], "alice");
+
+        let chunks: Vec<Bytes> = response("streamed")
+            .stream()
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await;
+        assert_eq!(chunks.concat(), b"streamed");
+    }
+
+    #[test]
+    fn filename_comes_from_content_disposition() {
+        assert_eq!(response("").filename(), None);
+
+        let with_disposition = http::Response::builder()
+            .header(
+                "content-disposition",
+                "attachment; filename=\"cafe.txt\"; filename*=UTF-8''caf%C3%A9.txt",
+            )
+            .body("")
+            .unwrap();
+        let resp = PubkyResponse::from(reqwest::Response::from(with_disposition));
+        assert_eq!(resp.filename().as_deref(), Some("café.txt"));
+    }
+
+    #[cfg(feature = "encoding")]
+    fn encoded(content_type: &str, body: &'static [u8]) -> PubkyResponse {
+        let response = http::Response::builder()
+            .header("content-type", content_type)
+            .body(body)
+            .unwrap();
+        reqwest::Response::from(response).into()
+    }
+
+    #[cfg(feature = "encoding")]
+    #[tokio::test]
+    async fn text_with_encoding_follows_the_charset() {
+        // "café" in Latin-1 and "日本" in Shift_JIS.
+        let latin1 = encoded("text/plain; charset=ISO-8859-1", b"caf\xe9");
+        assert_eq!(latin1.text_with_encoding().await.unwrap(), "café");
+        let sjis = encoded("text/plain;Charset=\"shift_jis\"", b"\x93\xfa\x96\x7b");
+        assert_eq!(sjis.text_with_encoding().await.unwrap(), "日本");
+
+        // No or an unknown charset means UTF-8, and a BOM wins over the header.
+        let plain = encoded("text/plain", "café".as_bytes());
+        assert_eq!(plain.text_with_encoding().await.unwrap(), "café");
+        let unknown = encoded("text/plain; charset=klingon", "café".as_bytes());
+        assert_eq!(unknown.text_with_encoding().await.unwrap(), "café");
+        let bom = encoded("text/plain; charset=ISO-8859-1", b"\xef\xbb\xbfcaf\xc3\xa9");
+        assert_eq!(bom.text_with_encoding().await.unwrap(), "café");
+    }
+
+    #[cfg(feature = "encoding")]
+    #[tokio::test]
+    async fn text_with_encoding_rejects_or_replaces_invalid_bytes() {
+        let invalid = || encoded("text/plain; charset=utf-8", b"caf\xe9");
+        let err = invalid().text_with_encoding().await.unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Request(RequestError::DecodeText { ref encoding }) if encoding == "UTF-8"
+        ));
+        assert_eq!(
+            invalid().text_with_encoding_lossy().await.unwrap(),
+            "caf\u{fffd}"
+        );
+    }
+
+    async fn lines_of(chunks: &[&'static [u8]], max_line_bytes: usize) -> Vec<Result<String>> {
+        let chunks = chunks.iter().map(|chunk| Ok(Bytes::from_static(chunk)));
+        split_lines(futures_util::stream::iter(chunks), max_line_bytes)
+            .collect()
+            .await
+    }
+
+    #[tokio::test]
+    async fn lines_are_framed_across_chunks() {
+        // "é" is split between chunks, one line ends in CRLF and the last has no terminator.
+        let lines = lines_of(&[b"{\"a\":1}\ncaf\xc3", b"\xa9\r", b"\n\nlast"], 16).await;
+        let lines: Vec<String> = lines.into_iter().map(Result::unwrap).collect();
+        assert_eq!(lines, ["{\"a\":1}", "café", "", "last"]);
+
+        let lines = lines_of(&[b"short\n", b"far too long", b" line\nnever read\n"], 8).await;
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].as_deref().unwrap(), "short");
+        assert!(matches!(
+            lines[1],
+            Err(Error::Request(RequestError::LineTooLong { limit: 8 }))
+        ));
+
+        let lines = lines_of(&[b"caf\xe9\nok\n"], 16).await;
+        assert_eq!(lines.len(), 1);
+        assert!(matches!(
+            lines[0],
+            Err(Error::Request(RequestError::DecodeText { .. }))
+        ));
+    }
+
+    #[tokio::test]
+    async fn json_decode_failure_is_reported() {
+        let err = response("not json").json::<serde_json::Value>().await;
+        assert!(matches!(
+            err,
+            Err(Error::Request(RequestError::DecodeJson { .. }))
+        ));
+    }
+}
@@ -0,0 +1,167 @@
+//! Version a homeserver announces on its root route.
+//!
+//! The homeserver's `/` answers with a `Server: pubky.org@<version>` header. Clients
+//! can compare the parsed [`SemanticVersion`] to gate features that older servers lack.
+
+use std::fmt;
+
+use reqwest::{Method, header::SERVER};
+use url::Url;
+
+use crate::{Error, PubkyHttpClient, PublicKey, Result, util::check_http_status};
+
+/// Product name a homeserver puts in front of its version, as in `pubky.org@0.9.3`.
+const SERVER_PRODUCT: &str = "pubky.org@";
+
+/// Oldest homeserver version accounts are created on, see
+/// [`Pubky::check_homeserver`](crate::Pubky::check_homeserver).
+pub const MIN_HOMESERVER_VERSION: SemanticVersion = SemanticVersion::new(0, 9, 0);
+
+/// A `major.minor.patch` version, as announced by a homeserver.
+///
+/// Versions order numerically, so `SemanticVersion::new(0, 10, 0)` is newer than
+/// `SemanticVersion::new(0, 9, 3)`. Pre-release and build suffixes are dropped while
+/// parsing, so `0.10.0-rc.1` compares equal to `0.10.0`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SemanticVersion {
+    /// Incremented on breaking changes.
+    pub major: u64,
+    /// Incremented on backwards compatible additions.
+    pub minor: u64,
+    /// Incremented on fixes.
+    pub patch: u64,
+}
+
+impl SemanticVersion {
+    /// Create a version from its parts.
+    #[must_use]
+    pub const fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Parse a version such as `0.9.3`, `v0.9.3` or `0.10.0-rc.1+build.5`.
+    ///
+    /// A missing patch (`1.2`) counts as `0`. Returns `None` for anything else
+    /// instead of guessing.
+    #[must_use]
+    pub fn parse(raw: &str) -> Option<Self> {
+        let raw = raw.trim();
+        let raw = raw.strip_prefix(['v', 'V']).unwrap_or(raw);
+        let core = raw.split(['-', '+']).next()?;
+        let mut parts = core.split('.').map(parse_number);
+        let major = parts.next()??;
+        let minor = parts.next()??;
+        let patch = parts.next().unwrap_or(Some(0))?;
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(Self::new(major, minor, patch))
+    }
+
+    /// Parse the version out of a `Server` header or root body, e.g.
+    /// `pubky.org@0.9.3` or `pubky.org@0.9.3 (linux)`.
+    pub(crate) fn from_server(value: &str) -> Option<Self> {
+        value
+            .split_whitespace()
+            .find_map(|token| token.strip_prefix(SERVER_PRODUCT))
+            .and_then(Self::parse)
+    }
+}
+
+impl fmt::Display for SemanticVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl PubkyHttpClient {
+    /// Version announced on the root route of `homeserver`: the `Server` header,
+    /// falling back to the response body. `None` if it announces none.
+    pub(crate) async fn homeserver_version(
+        &self,
+        homeserver: &PublicKey,
+    ) -> Result<Option<SemanticVersion>> {
+        let url = Url::parse(&format!("https://{}/", homeserver.z32()))?;
+        let rb = self.cross_request_anonymous(Method::GET, url).await?;
+        let resp = check_http_status(self.send(rb).await?).await?;
+        let from_header = resp
+            .headers()
+            .get(SERVER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(SemanticVersion::from_server);
+        if from_header.is_some() {
+            return Ok(from_header);
+        }
+        Ok(SemanticVersion::from_server(&resp.text().await?))
+    }
+
+    /// Fail unless `homeserver` answers as a homeserver of at least
+    /// [`MIN_HOMESERVER_VERSION`].
+    ///
+    /// Failing to reach it, including timeouts, is returned unchanged: it says nothing
+    /// about what the key points to.
+    pub(crate) async fn check_homeserver(&self, homeserver: &PublicKey) -> Result<SemanticVersion> {
+        let not_a_homeserver = |reason: String| Error::NotAHomeserver {
+            public_key: Box::new(homeserver.clone()),
+            reason,
+        };
+        let version = match self.homeserver_version(homeserver).await {
+            Ok(version) => version,
+            Err(e) if e.is_unreachable() || e.is_timeout() => return Err(e),
+            Err(e) => return Err(not_a_homeserver(e.to_string())),
+        }
+        .ok_or_else(|| not_a_homeserver("it does not announce a Pubky version".into()))?;
+        if version < MIN_HOMESERVER_VERSION {
+            return Err(Error::IncompatibleVersion {
+                public_key: Box::new(homeserver.clone()),
+                version,
+                minimum: MIN_HOMESERVER_VERSION,
+            });
+        }
+        Ok(version)
+    }
+}
+
+/// Parse one numeric component, rejecting signs and empty strings `u64::from_str`
+/// would otherwise accept or report differently.
+fn parse_number(part: &str) -> Option<u64> {
+    if part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    part.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_versions() {
+        let v = SemanticVersion::new;
+        assert_eq!(SemanticVersion::parse("0.9.3"), Some(v(0, 9, 3)));
+        assert_eq!(SemanticVersion::parse(" v1.2 "), Some(v(1, 2, 0)));
+        assert_eq!(SemanticVersion::parse("0.10.0-rc.1+b5"), Some(v(0, 10, 0)));
+        for weird in ["", "1", "1.x.3", "1.2.3.4", "+1.2.3", "1..3", "-", "pubky"] {
+            assert_eq!(SemanticVersion::parse(weird), None, "{weird}");
+        }
+        assert!(v(0, 10, 0) > v(0, 9, 3));
+        assert_eq!(v(0, 9, 3).to_string(), "0.9.3");
+    }
+
+    #[test]
+    fn parses_server_tokens() {
+        let expected = Some(SemanticVersion::new(0, 9, 3));
+        assert_eq!(SemanticVersion::from_server("pubky.org@0.9.3"), expected);
+        assert_eq!(
+            SemanticVersion::from_server("axum pubky.org@0.9.3 (linux)"),
+            expected
+        );
+        assert_eq!(SemanticVersion::from_server("nginx/1.25.3"), None);
+        assert_eq!(SemanticVersion::from_server("pubky.org@latest"), None);
+        assert_eq!(SemanticVersion::from_server("Pubky Homeserver"), None);
+    }
+}
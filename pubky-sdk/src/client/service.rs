@@ -0,0 +1,155 @@
+//! `tower::Service` implementation for [`PubkyHttpClient`] (native only).
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use reqwest::{Request, Response};
+use tower_service::Service;
+
+use super::core::PubkyHttpClient;
+use crate::errors::{Error, Result};
+
+/// Future returned by [`PubkyHttpClient`]'s [`Service::call`].
+pub type ServiceFuture = Pin<Box<dyn Future<Output = Result<Response>> + Send>>;
+
+/// [`PubkyHttpClient`] as a `tower` service, so timeouts, retries, concurrency limits and
+/// other middleware from the `tower` ecosystem compose around it:
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use pubky::PubkyHttpClient;
+/// use tower::{ServiceBuilder, ServiceExt};
+///
+/// # async fn example() -> Result<(), tower::BoxError> {
+/// let client = PubkyHttpClient::new()?;
+/// let service = ServiceBuilder::new()
+///     .concurrency_limit(16)
+///     .timeout(Duration::from_secs(10))
+///     .service(client);
+///
+/// let url = "https://_pubky.o4dksfbqk85ogzdb5osziw6befigbuxmuxkuxq8434q89uj56uyy/pub/app/hello.txt";
+/// let request = reqwest::Request::new(reqwest::Method::GET, url.parse()?);
+/// let response = service.oneshot(request).await?;
+/// println!("{}", response.status());
+/// # Ok(()) }
+/// ```
+impl Service<Request> for PubkyHttpClient {
+    type Response = Response;
+    type Error = Error;
+    type Future = ServiceFuture;
+
+    /// The client is always ready; connection pooling happens inside reqwest.
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    /// Send `req` the way every SDK request is sent: public-key hosts are resolved and
+    /// verified over `PubkyTLS`, ICANN hosts use regular TLS, and the custom transport and
+    /// bandwidth limits of the builder apply.
+    ///
+    /// Non-success status codes are returned as responses, not errors.
+    fn call(&mut self, mut req: Request) -> Self::Future {
+        let client = self.clone();
+        Box::pin(async move {
+            let body = req.body_mut().take();
+            let timeout = req.timeout().copied();
+            let version = req.version();
+            let headers = std::mem::take(req.headers_mut());
+            let (method, url) = (req.method().clone(), req.url().clone());
+
+            let mut builder = client
+                .cross_request(method, url)
+                .await?
+                .headers(headers)
+                .version(version);
+            if let Some(body) = body {
+                builder = builder.body(body);
+            }
+            if let Some(timeout) = timeout {
+                builder = builder.timeout(timeout);
+            }
+            client.send(builder).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use async_trait::async_trait;
+    use reqwest::Method;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::HttpClient;
+
+    #[derive(Debug, PartialEq)]
+    struct Seen {
+        method: Method,
+        url: String,
+        header: Option<String>,
+        body: Vec<u8>,
+    }
+
+    /// Records the requests it receives and answers `204`.
+    #[derive(Debug, Default)]
+    struct Recorder {
+        seen: Mutex<Vec<Seen>>,
+    }
+
+    #[async_trait]
+    impl HttpClient for Recorder {
+        async fn execute(&self, request: Request) -> Result<Response> {
+            let header = request
+                .headers()
+                .get("x-test")
+                .map(|value| value.to_str().unwrap().to_string());
+            let body = request
+                .body()
+                .and_then(reqwest::Body::as_bytes)
+                .unwrap_or_default()
+                .to_vec();
+            self.seen.lock().unwrap().push(Seen {
+                method: request.method().clone(),
+                url: request.url().to_string(),
+                header,
+                body,
+            });
+            let response = http::Response::builder().status(204).body("").unwrap();
+            Ok(response.into())
+        }
+    }
+
+    #[tokio::test]
+    async fn service_forwards_method_url_headers_and_body() {
+        let recorder = Arc::new(Recorder::default());
+        let client = PubkyHttpClient::builder()
+            .isolated_pkarr_test()
+            .with_http(Arc::clone(&recorder))
+            .build()
+            .unwrap();
+
+        let url = "https://_pubky.o4dksfbqk85ogzdb5osziw6befigbuxmuxkuxq8434q89uj56uyy/pub/a.txt";
+        let mut request = Request::new(Method::PUT, url.parse().unwrap());
+        request
+            .headers_mut()
+            .insert("x-test", "yes".parse().unwrap());
+        *request.body_mut() = Some("hello".into());
+
+        let response = client.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), 204);
+
+        let seen = recorder.seen.lock().unwrap();
+        assert_eq!(
+            *seen,
+            vec![Seen {
+                method: Method::PUT,
+                url: url.to_string(),
+                header: Some("yes".to_string()),
+                body: b"hello".to_vec(),
+            }]
+        );
+    }
+}
@@ -0,0 +1,126 @@
+//! Response body size cap (native only).
+//!
+//! See [`PubkyHttpClientBuilder::max_response_bytes`](super::core::PubkyHttpClientBuilder::max_response_bytes).
+
+use std::num::NonZeroU64;
+use std::pin::Pin;
+use std::task::{Context, Poll, ready};
+
+use bytes::Bytes;
+use http_body::{Body, Frame, SizeHint};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Body error raised once a response grows past the configured cap.
+///
+/// Surfaces inside a `reqwest::Error`; the conversion into [`crate::Error`] turns it into
+/// [`crate::Error::ResponseTooLarge`].
+#[derive(Debug, thiserror::Error)]
+#[error("response body exceeds the limit of {limit} bytes")]
+pub(crate) struct ResponseTooLarge {
+    pub(crate) limit: u64,
+}
+
+/// Body wrapper failing once more than `limit` bytes were received, for chunked
+/// responses whose size is not known up front.
+pub(crate) struct SizeLimitedBody<B> {
+    inner: B,
+    limit: NonZeroU64,
+    received: u64,
+}
+
+impl<B> SizeLimitedBody<B> {
+    pub(crate) const fn new(inner: B, limit: NonZeroU64) -> Self {
+        Self {
+            inner,
+            limit,
+            received: 0,
+        }
+    }
+}
+
+impl<B> Body for SizeLimitedBody<B>
+where
+    B: Body<Data = Bytes> + Unpin,
+    B::Error: Into<BoxError>,
+{
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, BoxError>>> {
+        let this = self.get_mut();
+        let frame = match ready!(Pin::new(&mut this.inner).poll_frame(cx)) {
+            Some(Ok(frame)) => frame,
+            Some(Err(err)) => return Poll::Ready(Some(Err(err.into()))),
+            None => return Poll::Ready(None),
+        };
+        if let Some(data) = frame.data_ref() {
+            this.received = this.received.saturating_add(data.len() as u64);
+            if this.received > this.limit.get() {
+                let limit = this.limit.get();
+                return Poll::Ready(Some(Err(Box::new(ResponseTooLarge { limit }))));
+            }
+        }
+        Poll::Ready(Some(Ok(frame)))
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+/// Find a [`ResponseTooLarge`] anywhere in the source chain of `err`.
+pub(crate) fn find_response_too_large(err: &reqwest::Error) -> Option<u64> {
+    let mut source = std::error::Error::source(err);
+    while let Some(err) = source {
+        if let Some(too_large) = err.downcast_ref::<ResponseTooLarge>() {
+            return Some(too_large.limit);
+        }
+        source = err.source();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::stream;
+
+    use super::*;
+
+    fn chunked(chunks: &[&'static [u8]]) -> reqwest::Body {
+        let chunks: Vec<Result<Bytes, std::io::Error>> = chunks
+            .iter()
+            .map(|chunk| Ok(Bytes::from_static(chunk)))
+            .collect();
+        reqwest::Body::wrap_stream(stream::iter(chunks))
+    }
+
+    fn limited(body: reqwest::Body, limit: u64) -> reqwest::Response {
+        let body = SizeLimitedBody::new(body, NonZeroU64::new(limit).unwrap());
+        http::Response::new(reqwest::Body::wrap(body)).into()
+    }
+
+    #[tokio::test]
+    async fn passes_bodies_within_the_limit() {
+        let response = limited(chunked(&[b"hello", b" world"]), 11);
+        assert_eq!(response.bytes().await.unwrap(), "hello world");
+    }
+
+    #[tokio::test]
+    async fn fails_once_the_limit_is_exceeded() {
+        let response = limited(chunked(&[b"hello", b" world"]), 10);
+        let err = response.bytes().await.unwrap_err();
+        assert_eq!(find_response_too_large(&err), Some(10));
+        assert!(matches!(
+            crate::Error::from(err),
+            crate::Error::ResponseTooLarge { limit: 10 }
+        ));
+    }
+}
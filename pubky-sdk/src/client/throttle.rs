@@ -0,0 +1,184 @@
+//! Token-bucket bandwidth limiting for HTTP bodies (native only).
+//!
+//! See [`PubkyHttpClientBuilder::upload_rate_limit`](super::core::PubkyHttpClientBuilder::upload_rate_limit)
+//! and [`PubkyHttpClientBuilder::download_rate_limit`](super::core::PubkyHttpClientBuilder::download_rate_limit).
+
+use std::num::NonZeroU64;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::task::{Context, Poll, ready};
+
+use bytes::Bytes;
+use http_body::{Body, Frame, SizeHint};
+use tokio::time::{Duration, Instant, Sleep, sleep_until};
+
+/// Largest slice of a body released at once, so big frames are spread out evenly.
+const MAX_CHUNK: u64 = 16 * 1024;
+
+/// How many bytes a rate limiter lets through at once, in seconds worth of transfer.
+const BURST: Duration = Duration::from_secs(1);
+
+/// Token bucket shared by every body throttled in one direction.
+///
+/// The bucket holds [`BURST`] worth of bytes. It is tracked as the instant at which it
+/// would be full again: every reservation pushes that instant back by the time its
+/// bytes take at the configured rate, and must wait while it lies more than [`BURST`]
+/// in the future.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    bytes_per_sec: u64,
+    full_at: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(bytes_per_sec: NonZeroU64) -> Self {
+        Self {
+            bytes_per_sec: bytes_per_sec.get(),
+            full_at: Mutex::new(Instant::now()),
+        }
+    }
+
+    fn chunk_size(&self) -> usize {
+        usize::try_from(self.bytes_per_sec.min(MAX_CHUNK)).unwrap_or(usize::MAX)
+    }
+
+    /// Take `len` bytes from the bucket. Returns when they may be released, or `None`
+    /// if they may be released right away.
+    fn reserve(&self, len: usize) -> Option<Instant> {
+        let nanos = len as u128 * 1_000_000_000 / u128::from(self.bytes_per_sec);
+        let cost = Duration::from_nanos(u64::try_from(nanos).unwrap_or(u64::MAX));
+        let now = Instant::now();
+        let mut full_at = self.full_at.lock().unwrap_or_else(PoisonError::into_inner);
+        *full_at = (*full_at).max(now) + cost;
+        let release = full_at.checked_sub(BURST)?;
+        (release > now).then_some(release)
+    }
+}
+
+/// Body wrapper releasing data no faster than its [`RateLimiter`] allows.
+///
+/// The inner body is only polled once the previous chunk was released, so time spent
+/// waiting for tokens never counts towards reqwest's read timeout.
+pub(crate) struct ThrottledBody<B> {
+    inner: B,
+    limiter: Arc<RateLimiter>,
+    /// Data received from `inner` but not released yet.
+    pending: Bytes,
+    /// Chunk waiting for its reservation.
+    delayed: Option<(Bytes, Pin<Box<Sleep>>)>,
+}
+
+impl<B> ThrottledBody<B> {
+    pub(crate) const fn new(inner: B, limiter: Arc<RateLimiter>) -> Self {
+        Self {
+            inner,
+            limiter,
+            pending: Bytes::new(),
+            delayed: None,
+        }
+    }
+
+    fn buffered(&self) -> u64 {
+        let delayed = self.delayed.as_ref().map_or(0, |(chunk, _)| chunk.len());
+        (self.pending.len() + delayed) as u64
+    }
+}
+
+impl<B> Body for ThrottledBody<B>
+where
+    B: Body<Data = Bytes> + Unpin,
+{
+    type Data = Bytes;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, B::Error>>> {
+        let this = self.get_mut();
+        loop {
+            if let Some((_, sleep)) = this.delayed.as_mut() {
+                ready!(sleep.as_mut().poll(cx));
+                let (chunk, _) = this.delayed.take().expect("delayed chunk is set");
+                return Poll::Ready(Some(Ok(Frame::data(chunk))));
+            }
+            if !this.pending.is_empty() {
+                let len = this.pending.len().min(this.limiter.chunk_size());
+                let chunk = this.pending.split_to(len);
+                match this.limiter.reserve(len) {
+                    None => return Poll::Ready(Some(Ok(Frame::data(chunk)))),
+                    Some(deadline) => {
+                        this.delayed = Some((chunk, Box::pin(sleep_until(deadline))));
+                        continue;
+                    }
+                }
+            }
+            match ready!(Pin::new(&mut this.inner).poll_frame(cx)) {
+                Some(Ok(frame)) => match frame.into_data() {
+                    Ok(data) => this.pending = data,
+                    Err(frame) => return Poll::Ready(Some(Ok(frame))),
+                },
+                other => return Poll::Ready(other),
+            }
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.buffered() == 0 && self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        let buffered = self.buffered();
+        let inner = self.inner.size_hint();
+        let mut hint = SizeHint::new();
+        if let Some(upper) = inner.upper() {
+            hint.set_upper(upper + buffered);
+        }
+        hint.set_lower(inner.lower() + buffered);
+        hint
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+
+    /// Drain all data frames of `body`.
+    async fn collect(mut body: ThrottledBody<reqwest::Body>) -> Vec<u8> {
+        let mut frames = futures_util::stream::poll_fn(move |cx| {
+            Pin::new(&mut body)
+                .poll_frame(cx)
+                .map(|frame| frame.map(|f| f.unwrap().into_data().unwrap()))
+        });
+        let mut out = Vec::new();
+        while let Some(chunk) = frames.next().await {
+            out.extend_from_slice(&chunk);
+        }
+        out
+    }
+
+    #[tokio::test]
+    async fn throttles_after_the_initial_burst() {
+        let limiter = Arc::new(RateLimiter::new(NonZeroU64::new(4_000).unwrap()));
+        let data = vec![7u8; 6_000];
+        let body = ThrottledBody::new(reqwest::Body::from(data.clone()), limiter);
+        assert_eq!(body.size_hint().exact(), Some(6_000));
+
+        let start = std::time::Instant::now();
+        assert_eq!(collect(body).await, data);
+        // 4 000 bytes pass with the full bucket, the remaining 2 000 take half a second.
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(450), "took {elapsed:?}");
+        assert!(elapsed < Duration::from_secs(2), "took {elapsed:?}");
+    }
+
+    #[tokio::test]
+    async fn small_bodies_are_not_delayed() {
+        let limiter = Arc::new(RateLimiter::new(NonZeroU64::new(1_000).unwrap()));
+        let start = std::time::Instant::now();
+        let body = ThrottledBody::new(reqwest::Body::from("hello"), limiter);
+        assert_eq!(collect(body).await, b"hello");
+        assert!(start.elapsed() < Duration::from_millis(100));
+    }
+}
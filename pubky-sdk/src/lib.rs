@@ -20,19 +20,44 @@ mod macros;
 
 mod util;
 
+#[cfg(all(feature = "test-util", not(target_arch = "wasm32")))]
+mod mock_homeserver;
+
 pub mod prelude;
 
 // --- PUBLIC API EXPORTS ---
 // SDK facade
 #[doc(inline)]
-pub use pubky::Pubky;
+pub use pubky::{PREWARM_CONCURRENCY, Pubky};
 // Transport
+pub use client::clock_skew::{ClockSkewMeasurement, MAX_CLOCK_SKEW_CORRECTION};
+#[cfg(not(target_arch = "wasm32"))]
+#[doc(inline)]
+pub use client::core::{
+    CacheInfo, DEFAULT_MAX_REDIRECTS, HttpVersion, RedirectPolicy, ResolutionSource,
+};
+#[doc(inline)]
+pub use client::core::{DEFAULT_TESTNET_RELAYS, PubkyHttpClient, PubkyHttpClientBuilder};
+#[doc(inline)]
+pub use client::http::HttpClient;
+#[cfg(not(target_arch = "wasm32"))]
 #[doc(inline)]
-pub use client::core::{PubkyHttpClient, PubkyHttpClientBuilder};
+pub use client::ip_preference::IpVersionPreference;
+#[doc(inline)]
+pub use client::response::{DEFAULT_MAX_LINE_BYTES, PubkyResponse};
+#[doc(inline)]
+pub use client::server_version::{MIN_HOMESERVER_VERSION, SemanticVersion};
+#[cfg(not(target_arch = "wasm32"))]
+#[doc(inline)]
+pub use client::service::ServiceFuture;
+#[cfg(all(feature = "test-util", not(target_arch = "wasm32")))]
+pub use mock_homeserver::{MockHomeserver, RecordedRequest};
 // High level actors
 #[doc(inline)]
 pub use actors::AuthFlowKind;
 #[doc(inline)]
+pub use actors::PROFILE_CACHE_TTL;
+#[doc(inline)]
 pub use actors::Pkdns;
 #[doc(inline)]
 #[allow(deprecated, reason = "Re-exporting deprecated public API")]
@@ -40,46 +65,71 @@ pub use actors::PubkyCookieAuthFlow;
 #[doc(inline)]
 pub use actors::PubkySession;
 #[doc(inline)]
-pub use actors::PubkySigner;
-#[doc(inline)]
 pub use actors::SessionInfo;
 #[doc(inline)]
 pub use actors::deep_links;
 #[doc(inline)]
+pub use actors::pkdns::pinning::{MemoryPinStore, PinPolicy, RecordChanged, RecordPinStore};
+#[doc(inline)]
 pub use actors::{
     CookieCredential, CookieSessionView, DelegatedGrantCredentialState, GrantCredential,
-    GrantManager, GrantSessionView,
+    GrantManager, GrantSessionView, SessionCookie,
 };
 #[doc(inline)]
 pub use actors::{DelegatedGrantAuthFlowState, GrantAuthFlowState, PubkyGrantAuthFlow};
 #[doc(inline)]
-pub use actors::{Event, EventCursor, EventStreamBuilder, EventType};
+pub use actors::{
+    Event, EventCursor, EventExportBuilder, EventSignature, EventStreamBuilder, EventType,
+    StreamEvent,
+};
+#[doc(inline)]
+pub use actors::{FixedSelector, HomeserverSelector, LatencySelector, PubkySigner, SignupOptions};
 #[doc(inline)]
 pub use actors::{PublicStorage, SessionStorage};
 
-// Error and global client
+// Error types
 #[doc(inline)]
 pub use errors::{BuildError, Error, Result};
 
 // Export common types and constants
+#[cfg(all(feature = "archive", not(target_arch = "wasm32")))]
+#[doc(inline)]
+pub use crate::actors::session::archive::{
+    AccountExport, AccountManifest, ArchiveFormat, ImportReport, MANIFEST_NAME, ManifestEntry,
+    RejectedImport,
+};
+#[cfg(not(target_arch = "wasm32"))]
+#[doc(inline)]
+pub use crate::actors::storage::cache::{DEFAULT_MAX_CACHED_RESPONSE_BYTES, ResponseCacheStats};
+#[cfg(not(target_arch = "wasm32"))]
+#[doc(inline)]
+pub use crate::actors::storage::diff::DiffReport;
 #[doc(inline)]
 pub use crate::actors::storage::{
-    list::ListBuilder,
-    resource::{IntoPubkyResource, IntoResourcePath, resolve_pubky},
-    resource::{PubkyResource, ResourcePath},
-    stats::ResourceStats,
+    cancel::CancelToken,
+    list::{LIST_ACROSS_USERS_CONCURRENCY, ListBuilder, ListPage},
+    request::SessionRequest,
+    resource::{IntoPubkyResource, IntoResourcePath, canonicalize_url, resolve_pubky},
+    resource::{PubkyResource, PubkyUrl, PubkyUrlBuilder, ResourcePath},
+    stats::{DirStats, FileMetadata, ResourceStats},
+    verbs::{DELETE_MANY_CONCURRENCY, DeleteDirProgress, PutResult},
 };
 #[doc(inline)]
+pub use actors::BackoffConfig;
+#[doc(inline)]
 #[allow(
     deprecated,
     reason = "Re-exporting deprecated public API for backwards compat"
 )]
 pub use actors::DEFAULT_HTTP_RELAY;
-pub use actors::pkdns::DEFAULT_STALE_AFTER;
+#[doc(inline)]
+pub use actors::pkdns::rotation::{MAX_ROTATION_HOPS, SUCCESSOR_RECORD_NAME, SuccessorLink};
+pub use actors::pkdns::{DEFAULT_STALE_AFTER, HomeserverEndpoint, PublishResult};
 #[doc(inline)]
 pub use actors::{DEFAULT_HTTP_RELAY_INBOX, EncryptedHttpRelayInboxChannel, HttpRelayInboxChannel};
 #[doc(hidden)]
 pub use actors::{DelegatedSignFn, delegated_sign_callback};
+pub use actors::{MAX_GRANT_LIFETIME_SECS, MIN_GRANT_LIFETIME_SECS};
 #[doc(inline)]
 pub use pkarr;
 
@@ -87,14 +137,16 @@ pub use pkarr;
 #[doc(inline)]
 pub use pubky_common::{
     auth::{
-        AuthToken,
+        AuthToken, VerifiedToken,
         grant::GrantClaims,
         grant_session_responses::{GrantInfo, GrantSessionInfo, GrantSessionResponse},
         jws::{ClientId, GRANT_JWS_TYP, GrantId, POP_JWS_TYP, PopNonce},
         pop::PopProofClaims,
     },
-    capabilities::{Capabilities, Capability},
-    crypto::{Keypair, PublicKey},
+    capabilities::{Capabilities, Capability, CapabilityRequest},
+    clock::{MockClock, SystemClock, TimeSource},
+    crypto::{Keypair, PublicKey, Signature, verify_message},
+    profile::{PROFILE_PATH, Profile, ProfileLink},
     recovery_file,
     session::CookieSessionRecord,
 };
@@ -0,0 +1,1392 @@
+//! In-memory homeserver for fast unit tests (`test-util` feature, native only).
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Write;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, SystemTime};
+
+use async_trait::async_trait;
+use base64::{Engine, engine::general_purpose::STANDARD};
+use pubky_common::{
+    capabilities::Capabilities,
+    crypto::{Hash, Keypair, PublicKey, hash},
+    session::CookieSessionRecord,
+    storage::{
+        APPEND_OFFSET_HEADER, DeleteDirResponse, DirStatsResponse, FileMetadataResponse,
+        PutResponse,
+    },
+};
+use reqwest::header::{
+    CONTENT_LENGTH, CONTENT_RANGE, CONTENT_TYPE, COOKIE, ETAG, HeaderMap, HeaderValue,
+    IF_NONE_MATCH, LAST_MODIFIED,
+};
+use reqwest::{Method, Request, Response, StatusCode};
+
+use crate::actors::auth::cookie::CookieCredential;
+use crate::{
+    HttpClient, IntoResourcePath, Pubky, PubkyHttpClient, PubkySession, Result, SessionInfo,
+};
+
+type MockResponse = http::Response<Vec<u8>>;
+
+/// A request received by a [`MockHomeserver`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedRequest {
+    /// HTTP method.
+    pub method: Method,
+    /// Tenant the request addressed, if any (`_pubky.<pk>` host or `pubky-host` header).
+    pub user: Option<PublicKey>,
+    /// Request path, e.g. `/pub/app/file.txt`.
+    pub path: String,
+    /// Raw query string, if any.
+    pub query: Option<String>,
+}
+
+#[derive(Debug)]
+struct StoredFile {
+    bytes: Vec<u8>,
+    hash: Hash,
+    modified: SystemTime,
+}
+
+#[derive(Debug)]
+struct MockEvent {
+    cursor: u64,
+    user: PublicKey,
+    path: String,
+    /// `None` for deletions.
+    hash: Option<Hash>,
+}
+
+#[derive(Debug)]
+struct Failure {
+    method: Method,
+    path: String,
+    status: StatusCode,
+    message: String,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    /// Keyed by `<z32><absolute path>`, so entries of a user sort together.
+    files: BTreeMap<String, StoredFile>,
+    /// Cookie secret to session record.
+    sessions: HashMap<String, CookieSessionRecord>,
+    events: Vec<MockEvent>,
+    requests: Vec<RecordedRequest>,
+    failures: Vec<Failure>,
+}
+
+/// In-memory homeserver for fast unit tests (`test-util` feature, native only).
+///
+/// [`MockHomeserver`] implements [`HttpClient`] and answers storage, session and
+/// event-stream requests from memory, so app logic can be tested without a DHT,
+/// a database or any networking. Clients built by [`MockHomeserver::client`] are
+/// regular [`PubkyHttpClient`]s: code under test does not need to know it talks
+/// to a mock.
+///
+/// Scope of the emulation:
+/// - `GET`/`HEAD`/`PUT`/`DELETE` on `/pub/` and `/priv/` paths, directory listings
+///   (`limit`, `cursor`, `shallow`, `reverse`, `modified_since`), `?stats`, file
+///   `?metadata`, `If-None-Match` on file reads, directory deletes and `PATCH` touches.
+/// - `GET`/`DELETE /session` for sessions created with [`MockHomeserver::session`].
+/// - `/events-stream` history (`user`, cursors, `limit`, `reverse`, `path`) with
+///   `prev_cursor` lines; `live` streams close after the history like a non-live stream.
+///
+/// Auth flows (signup, signin, grants) are not emulated; create sessions directly.
+/// Event streams must target the mock with
+/// [`Pubky::event_stream_for`](crate::Pubky::event_stream_for), since resolving a
+/// user's homeserver needs PKARR.
+///
+/// Cheap to clone; clones share the same state.
+///
+/// # Example
+/// ```
+/// # async fn run() -> pubky::Result<()> {
+/// use pubky::{Capabilities, Capability, Keypair, Method, MockHomeserver};
+///
+/// let homeserver = MockHomeserver::new();
+/// let user = Keypair::random().public_key();
+/// homeserver.put_file(&user, "/pub/app/hello.txt", "hello");
+///
+/// let pubky = homeserver.pubky();
+/// let text = pubky
+///     .public_storage()
+///     .get(format!("pubky{}/pub/app/hello.txt", user.z32()))
+///     .await?
+///     .text()
+///     .await?;
+/// assert_eq!(text, "hello");
+///
+/// let caps = Capabilities::from(vec![Capability::root()]);
+/// let session = homeserver.session(&user, caps);
+/// session.storage().put("/pub/app/new.txt", "new").await?;
+/// homeserver.assert_requested(&Method::PUT, "/pub/app/new.txt");
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone)]
+pub struct MockHomeserver {
+    public_key: PublicKey,
+    state: Arc<Mutex<State>>,
+}
+
+impl Default for MockHomeserver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockHomeserver {
+    /// Create an empty mock homeserver with a random public key.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            public_key: Keypair::random().public_key(),
+            state: Arc::new(Mutex::new(State::default())),
+        }
+    }
+
+    /// Public key this mock homeserver answers for.
+    #[must_use]
+    pub const fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    /// A [`PubkyHttpClient`] that sends every request to this mock.
+    ///
+    /// PKARR is configured without any network, so nothing leaves the process.
+    ///
+    /// # Panics
+    /// If the offline PKARR client cannot be built.
+    #[must_use]
+    pub fn client(&self) -> PubkyHttpClient {
+        PubkyHttpClient::builder()
+            .isolated_pkarr_test()
+            .with_http(self.clone())
+            .build()
+            .expect("offline client configuration is valid")
+    }
+
+    /// A [`Pubky`] facade over [`Self::client`].
+    #[must_use]
+    pub fn pubky(&self) -> Pubky {
+        Pubky::with_client(self.client())
+    }
+
+    /// Create a signed-in session for `user` with `capabilities`.
+    #[must_use]
+    pub fn session(&self, user: &PublicKey, capabilities: Capabilities) -> PubkySession {
+        let record = CookieSessionRecord::new(user, capabilities, None);
+        let secret = STANDARD.encode(Keypair::random().public_key().as_bytes());
+        self.lock().sessions.insert(secret.clone(), record.clone());
+        let credential = CookieCredential::new(
+            user.clone(),
+            Some(secret),
+            record,
+            Some(self.public_key.clone()),
+        );
+        PubkySession::from_cookie_credential(self.client(), credential)
+    }
+
+    /// Pre-seed a file, as if `user` had written it. Emits a `PUT` event.
+    ///
+    /// # Panics
+    /// If `path` is not a valid absolute resource path.
+    pub fn put_file<P: IntoResourcePath>(
+        &self,
+        user: &PublicKey,
+        path: P,
+        body: impl Into<Vec<u8>>,
+    ) {
+        let path = path.into_abs_path().expect("valid resource path");
+        self.lock().write(user, path.as_str(), body.into());
+    }
+
+    /// Current contents of a stored file, if any.
+    ///
+    /// # Panics
+    /// If `path` is not a valid absolute resource path.
+    #[must_use]
+    pub fn file<P: IntoResourcePath>(&self, user: &PublicKey, path: P) -> Option<Vec<u8>> {
+        let path = path.into_abs_path().expect("valid resource path");
+        self.lock()
+            .files
+            .get(&file_key(user, path.as_str()))
+            .map(|file| file.bytes.clone())
+    }
+
+    /// All requests received so far, oldest first.
+    #[must_use]
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.lock().requests.clone()
+    }
+
+    /// Assert that a request with `method` to `path` was received.
+    ///
+    /// # Panics
+    /// If no such request was received.
+    pub fn assert_requested(&self, method: &Method, path: &str) {
+        let requests = self.requests();
+        assert!(
+            requests
+                .iter()
+                .any(|r| r.method == *method && r.path == path),
+            "expected a {method} {path} request, received: {requests:#?}"
+        );
+    }
+
+    /// Answer the next `method` request to `path` with `status` instead of handling it.
+    pub fn fail_next(&self, method: Method, path: &str, status: StatusCode) {
+        self.fail_next_with_message(method, path, status, "Simulated failure");
+    }
+
+    /// Like [`Self::fail_next`], answering with `message` as the body.
+    pub fn fail_next_with_message(
+        &self,
+        method: Method,
+        path: &str,
+        status: StatusCode,
+        message: &str,
+    ) {
+        self.lock().failures.push(Failure {
+            method,
+            path: path.to_string(),
+            status,
+            message: message.to_string(),
+        });
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn respond(&self, request: &Request) -> MockResponse {
+        let url = request.url();
+        let path = url.path().to_string();
+        let user = self.tenant(request);
+        let mut state = self.lock();
+        state.requests.push(RecordedRequest {
+            method: request.method().clone(),
+            user: user.clone(),
+            path: path.clone(),
+            query: url.query().map(str::to_string),
+        });
+
+        if let Some(i) = state
+            .failures
+            .iter()
+            .position(|f| f.method == *request.method() && f.path == path)
+        {
+            let failure = state.failures.remove(i);
+            return text(failure.status, &failure.message);
+        }
+
+        let query: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+
+        if path == "/events-stream" {
+            return state.events_stream(request.headers(), &query);
+        }
+        let Some(user) = user else {
+            return text(StatusCode::NOT_FOUND, "Unknown host");
+        };
+        let session = state.session_for(request.headers(), &user);
+        if path == "/session" {
+            return match (request.method(), session) {
+                (_, None) => text(StatusCode::UNAUTHORIZED, "No session"),
+                (&Method::GET, Some((_, record))) => ok(record.serialize()),
+                (&Method::DELETE, Some((secret, _))) => {
+                    state.sessions.remove(&secret);
+                    ok(Vec::new())
+                }
+                _ => text(StatusCode::METHOD_NOT_ALLOWED, "Method not allowed"),
+            };
+        }
+        if !path.starts_with("/pub/") && !path.starts_with("/priv/") {
+            return text(StatusCode::NOT_FOUND, "Not found");
+        }
+
+        let info = session.map(|(_, r)| SessionInfo::new(user.clone(), r.capabilities().to_vec()));
+        let method = request.method();
+        if *method == Method::GET || *method == Method::HEAD {
+            if path.starts_with("/priv/") && !info.as_ref().is_some_and(|i| i.can_read(&*path)) {
+                return text(StatusCode::FORBIDDEN, "Forbidden");
+            }
+            let mut response = if path.ends_with('/') {
+                if query.iter().any(|(k, _)| k == "stats") {
+                    state.dir_stats(&user, &path, flag(&query, "shallow"))
+                } else {
+                    state.list(&user, &path, &query)
+                }
+            } else if query.iter().any(|(k, _)| k == "metadata") {
+                state.metadata(&user, &path)
+            } else {
+                state.get(&user, &path, request.headers())
+            };
+            if *method == Method::HEAD {
+                response.body_mut().clear();
+            }
+            return response;
+        }
+
+        let Some(info) = info else {
+            return text(StatusCode::UNAUTHORIZED, "No session");
+        };
+        if !info.can_write(&*path) {
+            return text(StatusCode::FORBIDDEN, "Forbidden");
+        }
+        if path.ends_with('/') {
+            if *method == Method::DELETE {
+                return state.delete_dir(&user, &path);
+            }
+            return text(StatusCode::BAD_REQUEST, "Directories cannot be written");
+        }
+        match *method {
+            Method::PUT => {
+                let body = request
+                    .body()
+                    .and_then(reqwest::Body::as_bytes)
+                    .unwrap_or_default()
+                    .to_vec();
+                state.put(&user, &path, body)
+            }
+            Method::POST => {
+                let body = request
+                    .body()
+                    .and_then(reqwest::Body::as_bytes)
+                    .unwrap_or_default();
+                state.append(&user, &path, request.headers(), body)
+            }
+            Method::PATCH => state.touch(&user, &path),
+            Method::DELETE => {
+                if state.files.remove(&file_key(&user, &path)).is_none() {
+                    return text(StatusCode::NOT_FOUND, "Not found");
+                }
+                state.push_event(&user, &path, None);
+                status(StatusCode::NO_CONTENT)
+            }
+            _ => text(StatusCode::METHOD_NOT_ALLOWED, "Method not allowed"),
+        }
+    }
+
+    /// The tenant a request addresses: `_pubky.<pk>` host, or `pubky-host` on our own host.
+    fn tenant(&self, request: &Request) -> Option<PublicKey> {
+        let host = request.url().host_str()?;
+        if let Some(pk) = host.strip_prefix("_pubky.") {
+            return PublicKey::try_from_z32(pk).ok();
+        }
+        if host == self.public_key.z32() {
+            let header = request.headers().get("pubky-host")?.to_str().ok()?;
+            return PublicKey::try_from_z32(header).ok();
+        }
+        None
+    }
+}
+
+impl State {
+    /// The cookie session `headers` carry for `user`, with its secret.
+    fn session_for(
+        &self,
+        headers: &HeaderMap,
+        user: &PublicKey,
+    ) -> Option<(String, CookieSessionRecord)> {
+        let name = user.z32();
+        headers
+            .get_all(COOKIE)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(';'))
+            .filter_map(|pair| pair.trim().split_once('='))
+            .filter(|(k, _)| *k == name)
+            .find_map(|(_, secret)| {
+                self.sessions
+                    .get(secret)
+                    .filter(|record| record.public_key() == user)
+                    .map(|record| (secret.to_string(), record.clone()))
+            })
+    }
+
+    fn write(&mut self, user: &PublicKey, path: &str, bytes: Vec<u8>) -> Hash {
+        let file = StoredFile {
+            hash: hash(&bytes),
+            bytes,
+            modified: SystemTime::now(),
+        };
+        let content_hash = file.hash;
+        self.files.insert(file_key(user, path), file);
+        self.push_event(user, path, Some(content_hash));
+        content_hash
+    }
+
+    fn put(&mut self, user: &PublicKey, path: &str, bytes: Vec<u8>) -> MockResponse {
+        let content_length = bytes.len() as u64;
+        let content_hash = STANDARD.encode(self.write(user, path, bytes).as_bytes());
+        let etag = header(&format!("\"{content_hash}\""));
+        let put = PutResponse {
+            content_hash,
+            content_length,
+        };
+        let mut response = ok(serde_json::to_vec(&put).expect("put serialize"));
+        *response.status_mut() = StatusCode::CREATED;
+        let headers = response.headers_mut();
+        headers.insert(CONTENT_TYPE, header("application/json"));
+        headers.insert(ETAG, etag);
+        response
+    }
+
+    fn append(
+        &mut self,
+        user: &PublicKey,
+        path: &str,
+        headers: &HeaderMap,
+        bytes: &[u8],
+    ) -> MockResponse {
+        let Some(offset) = headers
+            .get(APPEND_OFFSET_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok())
+        else {
+            return text(StatusCode::BAD_REQUEST, "Missing append offset");
+        };
+        let mut content = self
+            .files
+            .get(&file_key(user, path))
+            .map(|file| file.bytes.clone())
+            .unwrap_or_default();
+        let current_length = content.len() as u64;
+        if current_length != offset {
+            let mut response = text(StatusCode::CONFLICT, "Length mismatch");
+            response
+                .headers_mut()
+                .insert(CONTENT_RANGE, header(&format!("bytes */{current_length}")));
+            return response;
+        }
+        content.extend_from_slice(bytes);
+        let mut response = self.put(user, path, content);
+        *response.status_mut() = StatusCode::OK;
+        response
+    }
+
+    fn touch(&mut self, user: &PublicKey, path: &str) -> MockResponse {
+        let Some(file) = self.files.get_mut(&file_key(user, path)) else {
+            return text(StatusCode::NOT_FOUND, "Not found");
+        };
+        file.modified = SystemTime::now();
+        let (content_hash, modified) = (file.hash, file.modified);
+        self.push_event(user, path, Some(content_hash));
+        let mut response = status(StatusCode::NO_CONTENT);
+        response
+            .headers_mut()
+            .insert(LAST_MODIFIED, header(&httpdate::fmt_http_date(modified)));
+        response
+    }
+
+    fn push_event(&mut self, user: &PublicKey, path: &str, hash: Option<Hash>) {
+        let cursor = self.events.len() as u64 + 1;
+        self.events.push(MockEvent {
+            cursor,
+            user: user.clone(),
+            path: path.to_string(),
+            hash,
+        });
+    }
+
+    fn get(&self, user: &PublicKey, path: &str, request: &HeaderMap) -> MockResponse {
+        let Some(file) = self.files.get(&file_key(user, path)) else {
+            return text(StatusCode::NOT_FOUND, "Not found");
+        };
+        let etag = header(&format!("\"{}\"", STANDARD.encode(file.hash.as_bytes())));
+        if request.get(IF_NONE_MATCH) == Some(&etag) {
+            let mut response = status(StatusCode::NOT_MODIFIED);
+            response.headers_mut().insert(ETAG, etag);
+            return response;
+        }
+        let mut response = ok(file.bytes.clone());
+        let headers = response.headers_mut();
+        headers.insert(CONTENT_LENGTH, header(&file.bytes.len().to_string()));
+        headers.insert(CONTENT_TYPE, header("application/octet-stream"));
+        headers.insert(ETAG, etag);
+        headers.insert(
+            LAST_MODIFIED,
+            header(&httpdate::fmt_http_date(file.modified)),
+        );
+        response
+    }
+
+    fn metadata(&self, user: &PublicKey, path: &str) -> MockResponse {
+        let metadata = match self.files.get(&file_key(user, path)) {
+            Some(file) => FileMetadataResponse {
+                exists: true,
+                size: Some(file.bytes.len() as u64),
+                content_type: Some("application/octet-stream".into()),
+                content_hash: Some(STANDARD.encode(file.hash.as_bytes())),
+                modified: Some(micros(file.modified)),
+            },
+            None => FileMetadataResponse::default(),
+        };
+        let mut response = ok(serde_json::to_vec(&metadata).expect("metadata serialize"));
+        response
+            .headers_mut()
+            .insert(CONTENT_TYPE, header("application/json"));
+        response
+    }
+
+    /// Stored paths under directory `dir` of `user`, in order.
+    fn paths_under<'a>(
+        &'a self,
+        user: &PublicKey,
+        dir: &str,
+    ) -> impl Iterator<Item = (&'a str, &'a StoredFile)> {
+        let prefix = file_key(user, dir);
+        let user_len = user.z32().len();
+        self.files
+            .range(prefix.clone()..)
+            .take_while(move |(key, _)| key.starts_with(&prefix))
+            .map(move |(key, file)| (&key[user_len..], file))
+    }
+
+    fn list(&self, user: &PublicKey, dir: &str, query: &[(String, String)]) -> MockResponse {
+        if self.paths_under(user, dir).next().is_none() {
+            return text(StatusCode::NOT_FOUND, "Directory Not Found");
+        }
+        let since = param(query, "modified_since")
+            .and_then(|micros| micros.parse().ok())
+            .map(|micros| SystemTime::UNIX_EPOCH + Duration::from_micros(micros));
+        let files = self
+            .paths_under(user, dir)
+            .filter(|(_, file)| since.is_none_or(|since| file.modified >= since));
+        let mut paths: Vec<String> = if flag(query, "shallow") {
+            files
+                .map(|(path, _)| match path[dir.len()..].find('/') {
+                    Some(i) => path[..=dir.len() + i].to_string(),
+                    None => path.to_string(),
+                })
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect()
+        } else {
+            files.map(|(path, _)| path.to_string()).collect()
+        };
+        let reverse = flag(query, "reverse");
+        if reverse {
+            paths.reverse();
+        }
+        if let Some(cursor) = param(query, "cursor") {
+            let cursor = cursor.trim_start_matches("pubky://");
+            let cursor = cursor.strip_prefix(&user.z32()).unwrap_or(cursor);
+            paths.retain(|p| {
+                if reverse {
+                    &**p < cursor
+                } else {
+                    &**p > cursor
+                }
+            });
+        }
+        if let Some(limit) = param(query, "limit").and_then(|l| l.parse().ok()) {
+            paths.truncate(limit);
+        }
+        let body = paths
+            .iter()
+            .map(|path| format!("pubky://{}{path}", user.z32()))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let mut response = ok(body.into_bytes());
+        response
+            .headers_mut()
+            .insert(CONTENT_TYPE, header("text/plain"));
+        response
+    }
+
+    fn delete_dir(&mut self, user: &PublicKey, dir: &str) -> MockResponse {
+        let paths: Vec<String> = self
+            .paths_under(user, dir)
+            .map(|(path, _)| path.to_string())
+            .collect();
+        for path in &paths {
+            self.files.remove(&file_key(user, path));
+            self.push_event(user, path, None);
+        }
+        let deleted = DeleteDirResponse {
+            deleted: paths.len() as u64,
+        };
+        let mut response = ok(serde_json::to_vec(&deleted).expect("delete serialize"));
+        response
+            .headers_mut()
+            .insert(CONTENT_TYPE, header("application/json"));
+        response
+    }
+
+    fn dir_stats(&self, user: &PublicKey, dir: &str, shallow: bool) -> MockResponse {
+        let files: Vec<&StoredFile> = self
+            .paths_under(user, dir)
+            .filter(|(path, _)| !shallow || !path[dir.len()..].contains('/'))
+            .map(|(_, file)| file)
+            .collect();
+        if self.paths_under(user, dir).next().is_none() {
+            return text(StatusCode::NOT_FOUND, "Directory Not Found");
+        }
+        let stats = DirStatsResponse {
+            total_bytes: files.iter().map(|f| f.bytes.len() as u64).sum(),
+            file_count: files.len() as u64,
+            oldest_modified_at: files.iter().map(|f| micros(f.modified)).min(),
+            newest_modified_at: files.iter().map(|f| micros(f.modified)).max(),
+        };
+        let mut response = ok(serde_json::to_vec(&stats).expect("stats serialize"));
+        response
+            .headers_mut()
+            .insert(CONTENT_TYPE, header("application/json"));
+        response
+    }
+
+    fn events_stream(&self, headers: &HeaderMap, query: &[(String, String)]) -> MockResponse {
+        let mut users: HashMap<String, Option<u64>> = HashMap::new();
+        for (_, value) in query.iter().filter(|(k, _)| k == "user") {
+            let (user, cursor) = match value.split_once(':') {
+                Some((user, cursor)) => (user, cursor.parse().ok()),
+                None => (value.as_str(), None),
+            };
+            users.insert(user.to_string(), cursor);
+        }
+        let paths: Vec<&str> = query
+            .iter()
+            .filter(|(k, _)| k == "path")
+            .map(|(_, v)| v.as_str())
+            .collect();
+
+        let matches = |e: &&MockEvent| {
+            (paths.is_empty() || paths.iter().any(|p| e.path.starts_with(p)))
+                && (e.path.starts_with("/pub/") || self.session_for(headers, &e.user).is_some())
+        };
+        let mut events: Vec<&MockEvent> = self
+            .events
+            .iter()
+            .filter(|e| {
+                users
+                    .get(&e.user.z32())
+                    .is_some_and(|cursor| e.cursor > cursor.unwrap_or(0))
+            })
+            .filter(matches)
+            .collect();
+        let reverse = flag(query, "reverse");
+        if reverse {
+            events.reverse();
+        }
+        if let Some(limit) = param(query, "limit").and_then(|l| l.parse().ok()) {
+            events.truncate(limit);
+        }
+
+        let mut body = String::new();
+        for event in events {
+            let kind = if event.hash.is_some() { "PUT" } else { "DEL" };
+            let _ = write!(
+                body,
+                "event: {kind}\ndata: pubky://{}{}\ndata: cursor: {}\n",
+                event.user.z32(),
+                event.path,
+                event.cursor
+            );
+            if let Some(hash) = event.hash {
+                let _ = writeln!(
+                    body,
+                    "data: content_hash: {}",
+                    STANDARD.encode(hash.as_bytes())
+                );
+            }
+            let prev = self
+                .events
+                .iter()
+                .filter(|e| e.user == event.user && e.cursor < event.cursor)
+                .filter(matches)
+                .map(|e| e.cursor)
+                .max();
+            if let Some(prev) = prev.filter(|_| !reverse) {
+                let _ = writeln!(body, "data: prev_cursor: {prev}");
+            }
+            body.push('\n');
+        }
+        let mut response = ok(body.into_bytes());
+        response
+            .headers_mut()
+            .insert(CONTENT_TYPE, header("text/event-stream"));
+        response
+    }
+}
+
+#[async_trait]
+impl HttpClient for MockHomeserver {
+    async fn execute(&self, request: Request) -> Result<Response> {
+        Ok(self.respond(&request).into())
+    }
+}
+
+fn file_key(user: &PublicKey, path: &str) -> String {
+    format!("{}{path}", user.z32())
+}
+
+/// `time` as Unix microseconds, the wire format of modification times.
+fn micros(time: SystemTime) -> u64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |d| u64::try_from(d.as_micros()).unwrap_or(u64::MAX))
+}
+
+fn param<'a>(query: &'a [(String, String)], key: &str) -> Option<&'a str> {
+    query
+        .iter()
+        .find(|(k, _)| k == key)
+        .map(|(_, v)| v.as_str())
+}
+
+fn flag(query: &[(String, String)], key: &str) -> bool {
+    param(query, key).is_some_and(|v| v.is_empty() || v == "true")
+}
+
+fn header(value: &str) -> HeaderValue {
+    HeaderValue::from_str(value).expect("valid header value")
+}
+
+fn status(status: StatusCode) -> MockResponse {
+    let mut response = MockResponse::new(Vec::new());
+    *response.status_mut() = status;
+    response
+}
+
+fn ok(body: Vec<u8>) -> MockResponse {
+    MockResponse::new(body)
+}
+
+fn text(code: StatusCode, message: &str) -> MockResponse {
+    let mut response = status(code);
+    *response.body_mut() = message.as_bytes().to_vec();
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::StreamExt;
+    use pubky_common::capabilities::Capability;
+
+    use super::*;
+    use crate::{EventType, errors::RequestError};
+
+    /// How homeservers without directory deletes reject a `DELETE` of a directory path.
+    const OLD_DIR_DELETE_REJECTION: &str = "Invalid URL: target path must be a file";
+
+    fn root() -> Capabilities {
+        Capabilities::from(vec![Capability::root()])
+    }
+
+    #[tokio::test]
+    async fn storage_roundtrip() {
+        let homeserver = MockHomeserver::new();
+        let user = Keypair::random().public_key();
+        let session = homeserver.session(&user, root());
+        let storage = session.storage();
+
+        let written = storage.put("/pub/app/a.txt", "a").await.unwrap();
+        assert_eq!(
+            written.url.as_str(),
+            format!("pubky://{}/pub/app/a.txt", user.z32())
+        );
+        assert_eq!((written.content_hash, written.size), (hash(b"a"), 1));
+        storage.put("/pub/app/dir/b.txt", "bb").await.unwrap();
+        assert_eq!(homeserver.file(&user, "/pub/app/a.txt").unwrap(), b"a");
+        assert!(storage.exists("/pub/app/a.txt").await.unwrap());
+
+        let entries = storage.list("/pub/app/").unwrap().send().await.unwrap();
+        let paths: Vec<_> = entries.iter().map(|e| e.path.to_string()).collect();
+        assert_eq!(paths, vec!["/pub/app/a.txt", "/pub/app/dir/b.txt"]);
+        let shallow = storage
+            .list("/pub/app/")
+            .unwrap()
+            .shallow(true)
+            .send()
+            .await
+            .unwrap();
+        let paths: Vec<_> = shallow.iter().map(|e| e.path.to_string()).collect();
+        assert_eq!(paths, vec!["/pub/app/a.txt", "/pub/app/dir/"]);
+
+        let stats = storage
+            .dir_stats("/pub/app/", false)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!((stats.file_count, stats.total_bytes), (2, 3));
+
+        storage.delete("/pub/app/a.txt").await.unwrap();
+        assert!(homeserver.file(&user, "/pub/app/a.txt").is_none());
+        homeserver.assert_requested(&Method::DELETE, "/pub/app/a.txt");
+    }
+
+    #[tokio::test]
+    async fn listing_modified_since_skips_older_files() {
+        let homeserver = MockHomeserver::new();
+        let user = Keypair::random().public_key();
+        let storage = homeserver.session(&user, root()).storage();
+        storage.put("/pub/app/old.txt", "x").await.unwrap();
+        storage.put("/pub/app/dir/old.txt", "x").await.unwrap();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        let since = SystemTime::now();
+        storage.put("/pub/app/new.txt", "x").await.unwrap();
+
+        let list = |shallow| {
+            storage
+                .list("/pub/app/")
+                .unwrap()
+                .shallow(shallow)
+                .modified_since(since)
+                .send()
+        };
+        let paths: Vec<_> = list(false)
+            .await
+            .unwrap()
+            .iter()
+            .map(|e| e.path.to_string())
+            .collect();
+        assert_eq!(paths, vec!["/pub/app/new.txt"]);
+        let paths: Vec<_> = list(true)
+            .await
+            .unwrap()
+            .iter()
+            .map(|e| e.path.to_string())
+            .collect();
+        assert_eq!(paths, vec!["/pub/app/new.txt"]);
+    }
+
+    #[tokio::test]
+    async fn touch_bumps_modified_time_and_emits_put() {
+        let homeserver = MockHomeserver::new();
+        let user = Keypair::random().public_key();
+        let storage = homeserver.session(&user, root()).storage();
+        storage.put("/pub/app/presence", "here").await.unwrap();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        let since = SystemTime::now();
+
+        storage.touch("/pub/app/presence").await.unwrap();
+        assert_eq!(
+            homeserver.file(&user, "/pub/app/presence").unwrap(),
+            b"here"
+        );
+        let touched = storage
+            .list("/pub/app/")
+            .unwrap()
+            .modified_since(since)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(touched.len(), 1);
+        let events: Vec<_> = homeserver.lock().events.iter().map(|e| e.hash).collect();
+        assert_eq!(events, vec![Some(hash(b"here")); 2]);
+
+        let err = storage.touch("/pub/app/missing").await.unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::Request(RequestError::Server { status, .. }) if status == StatusCode::NOT_FOUND
+        ));
+    }
+
+    #[tokio::test]
+    async fn append_at_only_appends_at_the_current_length() {
+        let homeserver = MockHomeserver::new();
+        let user = Keypair::random().public_key();
+        let storage = homeserver.session(&user, root()).storage();
+
+        assert_eq!(
+            storage.append_at("/pub/app/log", 0, "one\n").await.unwrap(),
+            4
+        );
+        assert_eq!(
+            storage.append_at("/pub/app/log", 4, "two\n").await.unwrap(),
+            8
+        );
+        let err = storage
+            .append_at("/pub/app/log", 4, "late\n")
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::Request(RequestError::Conflict { current_length: 8 })
+        ));
+        assert_eq!(
+            homeserver.file(&user, "/pub/app/log").unwrap(),
+            b"one\ntwo\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn metadata_reports_the_stored_hash() {
+        let homeserver = MockHomeserver::new();
+        let user = Keypair::random().public_key();
+        let storage = homeserver.session(&user, root()).storage();
+        let stored = storage.put("/pub/app/a.txt", "hello").await.unwrap();
+
+        let metadata = storage.metadata("/pub/app/a.txt").await.unwrap();
+        assert!(metadata.exists);
+        assert_eq!(metadata.size, Some(5));
+        assert_eq!(metadata.content_hash, Some(stored.content_hash));
+        assert!(metadata.modified.is_some());
+
+        let missing = storage.metadata("/pub/app/missing.txt").await.unwrap();
+        assert_eq!(missing, crate::FileMetadata::default());
+        let err = storage.metadata("/pub/app/").await.unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::Request(RequestError::Validation { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn delete_dir_falls_back_to_per_file_deletes() {
+        let homeserver = MockHomeserver::new();
+        let user = Keypair::random().public_key();
+        let storage = homeserver.session(&user, root()).storage();
+        for path in ["/pub/app/a.txt", "/pub/app/dir/b.txt", "/pub/other.txt"] {
+            storage.put(path, "x").await.unwrap();
+        }
+
+        assert_eq!(storage.delete_dir("/pub/app/dir/").await.unwrap(), 1);
+        assert!(homeserver.file(&user, "/pub/app/dir/b.txt").is_none());
+
+        // Older homeservers reject directory paths.
+        homeserver.fail_next_with_message(
+            Method::DELETE,
+            "/pub/app/",
+            StatusCode::BAD_REQUEST,
+            OLD_DIR_DELETE_REJECTION,
+        );
+        assert_eq!(storage.delete_dir("/pub/app/").await.unwrap(), 1);
+        assert!(homeserver.file(&user, "/pub/app/a.txt").is_none());
+        homeserver.assert_requested(&Method::DELETE, "/pub/app/a.txt");
+        assert!(homeserver.file(&user, "/pub/other.txt").is_some());
+
+        // Other rejections are errors, without deleting anything file by file.
+        storage.put("/pub/app/c.txt", "x").await.unwrap();
+        homeserver.fail_next(Method::DELETE, "/pub/app/", StatusCode::BAD_REQUEST);
+        assert!(matches!(
+            storage.delete_dir("/pub/app/").await,
+            Err(crate::Error::Request(RequestError::Server { status, .. }))
+                if status == StatusCode::BAD_REQUEST
+        ));
+        assert!(homeserver.file(&user, "/pub/app/c.txt").is_some());
+
+        assert!(matches!(
+            storage.delete_dir("/pub/app").await,
+            Err(crate::Error::Request(RequestError::Validation { .. }))
+        ));
+    }
+
+    #[tokio::test]
+    async fn cancelled_bulk_operations_stop_cleanly() {
+        let homeserver = MockHomeserver::new();
+        let user = Keypair::random().public_key();
+        let storage = homeserver.session(&user, root()).storage();
+        for path in ["/pub/app/a.txt", "/pub/app/b.txt"] {
+            storage.put(path, "x").await.unwrap();
+        }
+
+        let cancel = crate::CancelToken::new();
+        cancel.cancel();
+        homeserver.fail_next_with_message(
+            Method::DELETE,
+            "/pub/app/",
+            StatusCode::BAD_REQUEST,
+            OLD_DIR_DELETE_REJECTION,
+        );
+        let progress = storage
+            .delete_dir_cancellable("/pub/app/", &cancel)
+            .await
+            .unwrap();
+        assert_eq!(progress.deleted, 0);
+        assert!(progress.cancelled);
+        assert!(homeserver.file(&user, "/pub/app/a.txt").is_some());
+
+        let entries: Vec<_> = homeserver
+            .pubky()
+            .public_storage()
+            .list_across_users_cancellable([user.clone()], "/pub/app/", &cancel)
+            .unwrap()
+            .collect()
+            .await;
+        assert!(entries.is_empty());
+
+        homeserver.fail_next_with_message(
+            Method::DELETE,
+            "/pub/app/",
+            StatusCode::BAD_REQUEST,
+            OLD_DIR_DELETE_REJECTION,
+        );
+        let progress = storage
+            .delete_dir_cancellable("/pub/app/", &crate::CancelToken::new())
+            .await
+            .unwrap();
+        assert_eq!(progress.deleted, 2);
+        assert!(!progress.cancelled);
+    }
+
+    #[tokio::test]
+    async fn diff_compares_local_files_with_remote_content() {
+        let homeserver = MockHomeserver::new();
+        let user = Keypair::random().public_key();
+        let storage = homeserver.session(&user, root()).storage();
+        for (path, body) in [
+            ("/pub/site/same.txt", "same"),
+            ("/pub/site/nested/changed.txt", "old"),
+            ("/pub/site/resized.txt", "short"),
+            ("/pub/site/gone.txt", "gone"),
+            ("/pub/other.txt", "outside"),
+        ] {
+            storage.put(path, body).await.unwrap();
+        }
+
+        let local = std::env::temp_dir().join(format!("pubky-diff-{}", user.z32()));
+        std::fs::create_dir_all(local.join("nested")).unwrap();
+        for (path, body) in [
+            ("same.txt", "same"),
+            ("nested/changed.txt", "new"),
+            ("resized.txt", "longer"),
+            ("nested/new.txt", "new"),
+        ] {
+            std::fs::write(local.join(path), body).unwrap();
+        }
+
+        let report = storage.diff(&local, "/pub/site/").await.unwrap();
+        assert_eq!(report.added, vec!["nested/new.txt"]);
+        assert_eq!(report.removed, vec!["gone.txt"]);
+        assert_eq!(report.modified, vec!["nested/changed.txt", "resized.txt"]);
+
+        // A missing remote directory means everything is new.
+        let report = storage.diff(&local, "/pub/empty/").await.unwrap();
+        assert_eq!(report.added.len(), 4);
+        assert!(report.removed.is_empty() && report.modified.is_empty());
+
+        std::fs::remove_dir_all(&local).unwrap();
+    }
+
+    #[tokio::test]
+    async fn response_cache_revalidates_with_etag() {
+        let homeserver = MockHomeserver::new();
+        let user = Keypair::random().public_key();
+        homeserver.put_file(&user, "/pub/app/config.json", "v1");
+        homeserver.put_file(&user, "/pub/app/big.bin", vec![0; 64]);
+        let storage = homeserver
+            .pubky()
+            .public_storage()
+            .with_response_cache_limits(1, 16);
+        let config = format!("pubky{}/pub/app/config.json", user.z32());
+        let read = |addr: String| {
+            let storage = storage.clone();
+            async move { storage.get(addr).await.unwrap().bytes().await.unwrap() }
+        };
+
+        assert_eq!(read(config.clone()).await, "v1");
+        assert_eq!(read(config.clone()).await, "v1");
+        let stats = storage.response_cache_stats().unwrap();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 1, 1));
+        assert_eq!(stats.bytes, 2);
+
+        // A changed resource is downloaded again.
+        homeserver.put_file(&user, "/pub/app/config.json", "v2");
+        assert_eq!(read(config.clone()).await, "v2");
+        assert_eq!(storage.response_cache_stats().unwrap().misses, 2);
+
+        // Bodies over the entry limit are served but not cached.
+        read(format!("pubky{}/pub/app/big.bin", user.z32())).await;
+        read(config).await;
+        let stats = storage.response_cache_stats().unwrap();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (2, 3, 1));
+        assert!(
+            homeserver
+                .pubky()
+                .public_storage()
+                .response_cache_stats()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn seeded_files_are_public() {
+        let homeserver = MockHomeserver::new();
+        let user = Keypair::random().public_key();
+        homeserver.put_file(&user, "/pub/app/seed.txt", "seed");
+        homeserver.put_file(&user, "/priv/app/secret.txt", "secret");
+
+        let storage = homeserver.pubky().public_storage();
+        let text = storage
+            .get(format!("pubky{}/pub/app/seed.txt", user.z32()))
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert_eq!(text, "seed");
+
+        let session = homeserver.session(&user, root());
+        let secret = session
+            .storage()
+            .get("/priv/app/secret.txt")
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert_eq!(secret, "secret");
+    }
+
+    #[tokio::test]
+    async fn exists_maps_head_statuses() {
+        let homeserver = MockHomeserver::new();
+        let user = Keypair::random().public_key();
+        homeserver.put_file(&user, "/pub/app/seed.txt", "seed");
+
+        let storage = homeserver.pubky().public_storage();
+        let seed = format!("pubky{}/pub/app/seed.txt", user.z32());
+        assert!(storage.exists(seed.as_str()).await.unwrap());
+        assert!(
+            !storage
+                .exists(format!("pubky{}/pub/app/missing.txt", user.z32()))
+                .await
+                .unwrap()
+        );
+
+        homeserver.fail_next(
+            Method::HEAD,
+            "/pub/app/seed.txt",
+            StatusCode::INTERNAL_SERVER_ERROR,
+        );
+        let err = storage.exists(seed.as_str()).await.unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::Request(RequestError::Server { status, .. }) if status == StatusCode::INTERNAL_SERVER_ERROR
+        ));
+        assert!(
+            homeserver
+                .requests()
+                .iter()
+                .all(|r| r.method == Method::HEAD),
+            "exists must not issue a GET"
+        );
+    }
+
+    #[tokio::test]
+    async fn custom_method_requests_carry_the_session() {
+        let homeserver = MockHomeserver::new();
+        let user = Keypair::random().public_key();
+        let session = homeserver.session(&user, root());
+        let storage = session.storage();
+
+        let resp = storage
+            .request(Method::OPTIONS, "/pub/app/a.txt")
+            .await
+            .unwrap()
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+        homeserver.assert_requested(&Method::OPTIONS, "/pub/app/a.txt");
+
+        let resp = storage
+            .request(Method::GET, "/session")
+            .await
+            .unwrap()
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK, "Session cookie is attached");
+
+        let read_only = homeserver.session(
+            &user,
+            Capabilities::from(vec![Capability::read("/pub/app/")]),
+        );
+        let err = read_only
+            .storage()
+            .request(Method::PATCH, "/pub/app/a.txt")
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::Request(RequestError::Forbidden { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn query_params_are_encoded_once() {
+        let homeserver = MockHomeserver::new();
+        let user = Keypair::random().public_key();
+        let session = homeserver.session(&user, root());
+        let storage = session.storage();
+        homeserver.put_file(&user, "/pub/app/a.txt", "a");
+
+        let resp = storage
+            .request(Method::GET, "/pub/app/a.txt")
+            .await
+            .unwrap()
+            .query(&[("tag", "rust & wasm"), ("pct", "100%")])
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let entries = storage
+            .list("/pub/app/")
+            .unwrap()
+            .limit(10)
+            .query(&[("filter", "a=b")])
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(entries.len(), 1);
+
+        let queries: Vec<Option<String>> = homeserver
+            .requests()
+            .into_iter()
+            .filter(|r| r.method == Method::GET)
+            .map(|r| r.query)
+            .collect();
+        assert_eq!(
+            queries,
+            vec![
+                Some("tag=rust+%26+wasm&pct=100%25".to_string()),
+                Some("limit=10&filter=a%3Db".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn profile_is_fetched_and_cached() {
+        let homeserver = MockHomeserver::new();
+        let pubky = homeserver.pubky();
+        let user = Keypair::random().public_key();
+        let without = Keypair::random().public_key();
+        homeserver.put_file(&user, crate::PROFILE_PATH, r#"{"name":"Alice"}"#);
+
+        let profile = pubky.profile(&user).await.unwrap().unwrap();
+        assert_eq!(profile.name, "Alice");
+        assert_eq!(pubky.profile(&without).await.unwrap(), None);
+
+        // Served from the cache until invalidated.
+        homeserver.put_file(&user, crate::PROFILE_PATH, r#"{"name":"Alicia"}"#);
+        pubky.profile(&without).await.unwrap();
+        assert_eq!(pubky.profile(&user).await.unwrap().unwrap().name, "Alice");
+        let profile_gets = || {
+            homeserver
+                .requests()
+                .iter()
+                .filter(|r| r.method == Method::GET && r.path == crate::PROFILE_PATH)
+                .count()
+        };
+        assert_eq!(profile_gets(), 2);
+
+        pubky.invalidate_profile(&user);
+        assert_eq!(pubky.profile(&user).await.unwrap().unwrap().name, "Alicia");
+        assert_eq!(profile_gets(), 3);
+
+        homeserver.put_file(&without, crate::PROFILE_PATH, "not json");
+        pubky.invalidate_profile(&without);
+        assert!(matches!(
+            pubky.profile(&without).await,
+            Err(crate::Error::Request(RequestError::DecodeJson { .. }))
+        ));
+    }
+
+    #[tokio::test]
+    async fn enforces_sessions_and_capabilities() {
+        let homeserver = MockHomeserver::new();
+        let user = Keypair::random().public_key();
+        let read_only = homeserver.session(
+            &user,
+            Capabilities::from(vec![Capability::read("/pub/app/")]),
+        );
+
+        // Writes outside the session's capabilities fail before reaching the server.
+        let storage = read_only.storage();
+        storage
+            .check_permission(&Method::GET, "/pub/app/a.txt")
+            .unwrap();
+        let err = storage.put("/pub/app/a.txt", "a").await.unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::Request(RequestError::Forbidden { .. })
+        ));
+        assert!(
+            homeserver
+                .requests()
+                .iter()
+                .all(|r| r.method != Method::PUT)
+        );
+
+        assert!(read_only.revalidate().await.unwrap().is_some());
+        read_only.signout().await.unwrap();
+        assert!(homeserver.lock().sessions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn debug_cookies_redact_unless_dangerous() {
+        let homeserver = MockHomeserver::new();
+        let user = Keypair::random().public_key();
+        let session = homeserver.session(&user, Capabilities::from(vec![Capability::root()]));
+        let cookie_session = session.as_cookie().unwrap();
+
+        let cookies = cookie_session.debug_cookies();
+        assert_eq!(cookies.len(), 1);
+        assert_eq!(cookies[0].name, user.z32());
+        assert_eq!(cookies[0].value, "<redacted>");
+        assert!(!cookies[0].is_expired(), "Unknown expiry is not expired");
+
+        let cookies = cookie_session.debug_cookies_dangerous();
+        assert!(homeserver.lock().sessions.contains_key(&cookies[0].value));
+    }
+
+    #[tokio::test]
+    async fn simulated_failures() {
+        let homeserver = MockHomeserver::new();
+        let user = Keypair::random().public_key();
+        let storage = homeserver.session(&user, root()).storage();
+        homeserver.fail_next(
+            Method::PUT,
+            "/pub/app/a.txt",
+            StatusCode::SERVICE_UNAVAILABLE,
+        );
+
+        let err = storage.put("/pub/app/a.txt", "a").await.unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::Request(RequestError::Server { status, .. }) if status == StatusCode::SERVICE_UNAVAILABLE
+        ));
+        storage.put("/pub/app/a.txt", "a").await.unwrap();
+        assert_eq!(
+            homeserver
+                .requests()
+                .iter()
+                .filter(|r| r.method == Method::PUT)
+                .count(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn event_stream_replays_writes() {
+        let homeserver = MockHomeserver::new();
+        let user = Keypair::random().public_key();
+        let storage = homeserver.session(&user, root()).storage();
+        storage.put("/pub/app/a.txt", "a").await.unwrap();
+        storage.put("/priv/app/b.txt", "b").await.unwrap();
+        storage.delete("/pub/app/a.txt").await.unwrap();
+
+        let events: Vec<_> = homeserver
+            .pubky()
+            .event_stream_for(homeserver.public_key())
+            .add_users([(&user, None)])
+            .unwrap()
+            .subscribe()
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        let events: Vec<_> = events.into_iter().map(Result::unwrap).collect();
+
+        assert_eq!(events.len(), 2);
+        assert!(matches!(events[0].event_type, EventType::Put { .. }));
+        assert_eq!(events[0].resource.path.as_str(), "/pub/app/a.txt");
+        assert!(matches!(events[1].event_type, EventType::Delete));
+        assert_eq!(events[0].prev_cursor, None);
+        assert_eq!(events[1].prev_cursor, Some(events[0].cursor));
+    }
+}
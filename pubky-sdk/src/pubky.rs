@@ -52,18 +52,33 @@
 
 use std::str::FromStr;
 
+use pubky_common::clock::{SERVER_TIME_PATH, ServerTimeResponse};
+use pubky_common::profile::{PROFILE_PATH, Profile};
+use pubky_common::timestamp::Timestamp;
+use reqwest::{Method, StatusCode};
+use url::Url;
+
+use futures_util::StreamExt;
+
 use crate::PublicKey;
 
+/// Maximum number of users [`Pubky::prewarm`] resolves at the same time.
+pub const PREWARM_CONCURRENCY: usize = 8;
+
 #[allow(deprecated, reason = "Internal use of deprecated public API")]
 use crate::PubkyCookieAuthFlow;
 use crate::{
-    Capabilities, ClientId, DelegatedGrantCredentialState, EventCursor, EventStreamBuilder,
-    GrantCredential, Pkdns, PubkyGrantAuthFlow, PubkyHttpClient, PubkySession, PubkySigner,
-    PublicStorage, Result, actors::AuthFlowKind, deep_links::DeepLink, errors::AuthError,
+    Capabilities, ClientId, ClockSkewMeasurement, DelegatedGrantCredentialState, EventCursor,
+    EventExportBuilder, EventStreamBuilder, GrantCredential, IntoPubkyResource, Pkdns,
+    PubkyGrantAuthFlow, PubkyHttpClient, PubkyResource, PubkyResponse, PubkySession, PubkySigner,
+    PublicStorage, Result, SemanticVersion,
+    actors::AuthFlowKind,
+    cross_log,
+    deep_links::DeepLink,
+    errors::{AuthError, Error, RequestError},
+    util::check_http_status,
 };
 
-#[cfg(not(target_arch = "wasm32"))]
-use crate::errors::RequestError;
 #[cfg(not(target_arch = "wasm32"))]
 use std::path::Path;
 
@@ -200,9 +215,73 @@ impl Pubky {
     pub fn public_storage(&self) -> PublicStorage {
         PublicStorage {
             client: self.client.clone(),
+            #[cfg(not(target_arch = "wasm32"))]
+            cache: None,
         }
     }
 
+    /// List the same public directory across many users, merged into one stream of
+    /// `(user, entry)` pairs with per-user errors inline.
+    ///
+    /// See [`PublicStorage::list_across_users`].
+    ///
+    /// # Errors
+    /// - Returns [`crate::errors::RequestError::Validation`] if `prefix` is not an absolute,
+    ///   public directory path ending with `/`.
+    pub fn list_across_users<I>(
+        &self,
+        users: I,
+        prefix: &str,
+    ) -> Result<impl futures_util::Stream<Item = (PublicKey, Result<PubkyResource>)> + use<I>>
+    where
+        I: IntoIterator<Item = PublicKey>,
+    {
+        self.public_storage().list_across_users(users, prefix)
+    }
+
+    /// Resolve and `GET` a Pubky URL in one call.
+    ///
+    /// Accepts:
+    /// - addressed resources: `pubky://<user>/<path>` or `pubky<user>/<path>`
+    /// - transport URLs: `https://_pubky.<user>/<path>`
+    /// - pkarr-TLD URLs: `https://<public-key>/<path>`
+    ///
+    /// The user's homeserver is resolved via Pkarr and the request routed to it.
+    /// Resolutions are cached by the underlying [`PubkyHttpClient`] (Pkarr record cache
+    /// plus per-host transport cache), so repeated fetches for the same user are fast.
+    ///
+    /// The request is unauthenticated; use [`PubkySession::storage`] for private data.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # async fn ex(pubky: pubky::Pubky) -> pubky::Result<()> {
+    /// let profile = pubky
+    ///     .fetch("pubky://o1gg96ewuojmopcjbz8895478wdtxtzzuxnfjjz8o8e77csa1ngo/pub/pubky.app/profile.json")
+    ///     .await?
+    ///     .text()
+    ///     .await?;
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// - Returns [`crate::errors::RequestError::Validation`] if `url` is not a valid Pubky or HTTP(S) URL.
+    /// - [`crate::errors::Error::Request`] on HTTP transport failures or when the server
+    ///   responds with a non-success status (the server message is captured).
+    pub async fn fetch(&self, url: &str) -> Result<PubkyResponse> {
+        let url = if url.starts_with("pubky") {
+            url.parse::<PubkyResource>()?.to_transport_url()?
+        } else {
+            Url::parse(url)?
+        };
+        let rb = self
+            .client
+            .cross_request_anonymous(Method::GET, url)
+            .await?;
+        let resp = self.client.send(rb).await?;
+        cross_log!(debug, "Fetch completed with status {}", resp.status());
+        Ok(check_http_status(resp).await?.into())
+    }
+
     /// Read-only [`Pkdns`] actor (resolve `_pubky` records) using this facade’s client.
     #[must_use]
     pub fn pkdns(&self) -> Pkdns {
@@ -218,6 +297,315 @@ impl Pubky {
             .await
     }
 
+    /// `GET` an addressed resource through an ICANN domain of the owner's homeserver,
+    /// instead of connecting to the homeserver's public key.
+    ///
+    /// Homeservers with a configured domain publish it in their Pkarr record. The owner's
+    /// homeserver is resolved first, and `domain` must be one of the domains that
+    /// homeserver publishes. The request is then sent to `https://<domain>/<path>` with
+    /// the owner in the `pubky-host` header.
+    ///
+    /// # Trust model
+    /// A regular read connects with `PubkyTLS`, which authenticates the homeserver's key
+    /// itself. Here the TLS connection is verified with the domain's X.509 certificate,
+    /// so it authenticates whoever controls the domain and its certificate. The signed
+    /// records tie the domain to the owner: the owner's record names the homeserver,
+    /// whose record names the domain, and other domains are rejected. Still, a
+    /// compromised DNS entry or certificate for that domain can serve arbitrary answers.
+    /// Prefer [`PublicStorage::get`] unless the domain is needed, e.g. on networks that
+    /// only allow regular HTTPS.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async fn run(pubky: pubky::Pubky, user: pubky::PublicKey) -> pubky::Result<()> {
+    /// let body = pubky
+    ///     .fetch_via_domain("homeserver.example.com", format!("{user}/pub/app/file.txt"))
+    ///     .await?
+    ///     .text()
+    ///     .await?;
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// - [`crate::errors::Error::RecordNotFound`] if the owner has no record naming a
+    ///   homeserver.
+    /// - [`RequestError::Validation`] if the owner's homeserver does not publish `domain`.
+    /// - [`crate::errors::Error::Request`] on HTTP transport failures or when the server
+    ///   responds with a non-success status.
+    /// - [`crate::errors::Error::Parse`] if `addr` is not a valid addressed resource.
+    pub async fn fetch_via_domain<A: IntoPubkyResource>(
+        &self,
+        domain: &str,
+        addr: A,
+    ) -> Result<PubkyResponse> {
+        let resource = addr.into_pubky_resource()?;
+        let pkdns = self.pkdns();
+        let homeserver = pkdns.require_homeserver_of(&resource.owner).await?;
+        let domain = domain.trim_end_matches('.');
+        let endpoint = pkdns
+            .resolve_endpoints(&homeserver)
+            .await
+            .into_iter()
+            .find(|endpoint| endpoint.is_icann() && endpoint.host.eq_ignore_ascii_case(domain))
+            .ok_or_else(|| RequestError::Validation {
+                message: format!(
+                    "{domain} is not a domain of {homeserver}, the homeserver of {}",
+                    resource.owner
+                ),
+            })?;
+        cross_log!(debug, "Fetching {resource} via {domain}");
+        let rb = self
+            .client
+            .cross_request_via_domain(
+                Method::GET,
+                &endpoint,
+                &resource.owner,
+                resource.path.as_str(),
+            )
+            .await?;
+        let resp = check_http_status(self.client.send(rb).await?).await?;
+        Ok(resp.into())
+    }
+
+    /// Ask a homeserver which version it runs.
+    ///
+    /// Reads the `pubky.org@<version>` token from the `Server` header of the
+    /// homeserver's root route, falling back to the response body. Use it to enable
+    /// features newer homeservers support, e.g. only when the version is at least
+    /// `SemanticVersion::new(0, 10, 0)`.
+    ///
+    /// Returns `None` if the homeserver cannot be reached, answers with an error, or
+    /// does not announce a version in a recognizable format.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # async fn ex(pubky: pubky::Pubky, user: pubky::PublicKey) {
+    /// use pubky::SemanticVersion;
+    ///
+    /// let Some(homeserver) = pubky.get_homeserver_of(&user).await else { return };
+    /// if let Some(version) = pubky.homeserver_version(&homeserver).await {
+    ///     let supports_new_feature = version >= SemanticVersion::new(0, 10, 0);
+    ///     println!("{homeserver} runs {version}: {supports_new_feature}");
+    /// }
+    /// # }
+    /// ```
+    pub async fn homeserver_version(&self, homeserver: &PublicKey) -> Option<SemanticVersion> {
+        match self.client.homeserver_version(homeserver).await {
+            Ok(version) => version,
+            Err(e) => {
+                cross_log!(debug, "Could not get the version of {homeserver}: {e}");
+                None
+            }
+        }
+    }
+
+    /// Check that `homeserver` is a reachable Pubky homeserver this SDK can create
+    /// accounts on, i.e. it announces a version of at least
+    /// [`MIN_HOMESERVER_VERSION`](crate::MIN_HOMESERVER_VERSION).
+    ///
+    /// [`PubkySigner::signup_into_session`] and [`PubkySigner::signup_multi`] run this
+    /// check before creating an account, unless
+    /// [`SignupOptions::skip_homeserver_check`](crate::SignupOptions::skip_homeserver_check)
+    /// is set. Returns the announced version.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # async fn ex(pubky: pubky::Pubky, homeserver: pubky::PublicKey) {
+    /// match pubky.check_homeserver(&homeserver).await {
+    ///     Ok(version) => println!("{homeserver} runs {version}"),
+    ///     Err(e) => eprintln!("cannot sign up there: {e}"),
+    /// }
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    /// - [`Error::NotAHomeserver`] if `homeserver` does not resolve, answers with an
+    ///   error, or does not announce a version.
+    /// - Propagates transport failures, timeouts and gateway errors unchanged, as the
+    ///   server could not be asked.
+    /// - [`Error::IncompatibleVersion`] if it runs a version older than
+    ///   [`MIN_HOMESERVER_VERSION`](crate::MIN_HOMESERVER_VERSION).
+    pub async fn check_homeserver(&self, homeserver: &PublicKey) -> Result<SemanticVersion> {
+        self.client.check_homeserver(homeserver).await
+    }
+
+    /// Ask a homeserver for its current time, from its
+    /// [`SERVER_TIME_PATH`](pubky_common::clock::SERVER_TIME_PATH) endpoint.
+    ///
+    /// The answer is already off by the request's travel time; use
+    /// [`Self::measure_clock_skew`] to compare it with the local clock.
+    ///
+    /// # Errors
+    /// - [`crate::errors::Error::Request`] on HTTP transport failures, when the server
+    ///   responds with a non-success status (e.g. `404` from homeservers predating the
+    ///   endpoint), or with [`RequestError::DecodeJson`] if the answer is malformed.
+    pub async fn homeserver_time(&self, homeserver: &PublicKey) -> Result<Timestamp> {
+        let url = Url::parse(&format!("https://{}{SERVER_TIME_PATH}", homeserver.z32()))?;
+        let rb = self
+            .client
+            .cross_request_anonymous(Method::GET, url)
+            .await?;
+        let resp = check_http_status(self.client.send(rb).await?).await?;
+        let body = resp.bytes().await?;
+        let time: ServerTimeResponse =
+            serde_json::from_slice(&body).map_err(|e| RequestError::DecodeJson {
+                message: e.to_string(),
+            })?;
+        Ok(Timestamp::from(time.now))
+    }
+
+    /// Measure how far the local clock is off a homeserver's clock.
+    ///
+    /// Reads [`Self::homeserver_time`] twice: the first request resolves the homeserver
+    /// and opens the connection, the second is timed and its answer compared with the
+    /// local clock halfway through the round trip. The result replaces the offset the
+    /// client keeps from `Date` headers, see
+    /// [`PubkyHttpClient::clock_skew_secs`], so with
+    /// [`correct_clock_skew`](crate::PubkyHttpClientBuilder::correct_clock_skew)
+    /// enabled the next auth tokens are signed with the corrected time. Call it before
+    /// signing in when the device clock is suspect, e.g. after an
+    /// [`AuthError::ClockSkewDetected`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// # async fn ex(pubky: pubky::Pubky, homeserver: pubky::PublicKey) -> pubky::Result<()> {
+    /// let skew = pubky.measure_clock_skew(&homeserver).await?;
+    /// println!("{}s off (±{:?})", skew.offset_secs(), skew.round_trip / 2);
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// - Same as [`Self::homeserver_time`].
+    pub async fn measure_clock_skew(&self, homeserver: &PublicKey) -> Result<ClockSkewMeasurement> {
+        self.homeserver_time(homeserver).await?;
+
+        let sent = self.client.clock_skew.local_now();
+        let started = web_time::Instant::now();
+        let server = self.homeserver_time(homeserver).await?;
+        let measurement = ClockSkewMeasurement::estimate(sent, started.elapsed(), server);
+
+        self.client.clock_skew.record(measurement.offset_secs());
+        Ok(measurement)
+    }
+
+    /// Fetch a user's public profile from [`PROFILE_PATH`](crate::PROFILE_PATH).
+    ///
+    /// Returns `None` if the user has not published one. Answers are cached for
+    /// [`PROFILE_CACHE_TTL`](crate::PROFILE_CACHE_TTL) by the underlying
+    /// [`PubkyHttpClient`], so rendering many posts by the same author fetches their
+    /// profile once. Call [`Self::invalidate_profile`] to see an update sooner.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # async fn ex(pubky: pubky::Pubky, user: pubky::PublicKey) -> pubky::Result<()> {
+    /// if let Some(profile) = pubky.profile(&user).await? {
+    ///     println!("{} ({:?})", profile.name, profile.image);
+    /// }
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    /// - [`crate::errors::Error::Request`] on HTTP transport failures, when the server
+    ///   responds with an error other than `404`, or with
+    ///   [`crate::errors::RequestError::DecodeJson`] if the stored profile is not a valid
+    ///   [`crate::Profile`]. Errors are not cached.
+    pub async fn profile(&self, user: &PublicKey) -> Result<Option<Profile>> {
+        if let Some(cached) = self.client.profiles.get(user) {
+            return Ok(cached.profile);
+        }
+        let resource = PubkyResource::new(user.clone(), PROFILE_PATH)?;
+        let profile = match self.public_storage().get(resource).await {
+            Ok(resp) => {
+                let bytes = resp.bytes().await?;
+                let profile = serde_json::from_slice::<Profile>(&bytes).map_err(|err| {
+                    RequestError::DecodeJson {
+                        message: format!("invalid profile of {user}: {err}"),
+                    }
+                })?;
+                Some(profile)
+            }
+            Err(Error::Request(RequestError::Server { status, .. }))
+                if status == StatusCode::NOT_FOUND =>
+            {
+                None
+            }
+            Err(err) => return Err(err),
+        };
+        self.client.profiles.insert(user.clone(), profile.clone());
+        Ok(profile)
+    }
+
+    /// Drop the cached profile of `user`, so the next [`Self::profile`] call fetches it
+    /// again.
+    pub fn invalidate_profile(&self, user: &PublicKey) {
+        self.client.profiles.remove(user);
+    }
+
+    /// Warm up the caches for users whose data will likely be fetched soon.
+    ///
+    /// Resolves each user's homeserver and transport in the background, so the next
+    /// request for their data skips the Pkarr lookup. Entries that are still fresh in the
+    /// resolution cache are not looked up again. At most [`PREWARM_CONCURRENCY`] users
+    /// are resolved at once.
+    ///
+    /// Fire-and-forget: this returns immediately and failures are only logged. On native
+    /// targets it must be called from within a Tokio runtime, otherwise it does nothing.
+    ///
+    /// Use [`Self::prewarm_and_connect`] to also open connections ahead of time.
+    pub fn prewarm(&self, users: &[PublicKey]) {
+        self.spawn_prewarm(users, false);
+    }
+
+    /// Like [`Self::prewarm`], but also opens a connection to each user's homeserver
+    /// (with a `HEAD /` request) so it sits in the connection pool when it's needed.
+    pub fn prewarm_and_connect(&self, users: &[PublicKey]) {
+        self.spawn_prewarm(users, true);
+    }
+
+    fn spawn_prewarm(&self, users: &[PublicKey], connect: bool) {
+        let mut users = users.to_vec();
+        users.sort_by_key(PublicKey::z32);
+        users.dedup();
+        let client = self.client.clone();
+
+        let fut = async move {
+            cross_log!(debug, "Prewarming {} user(s)", users.len());
+            futures_util::stream::iter(users)
+                .for_each_concurrent(PREWARM_CONCURRENCY, |user| {
+                    Self::prewarm_user(&client, user, connect)
+                })
+                .await;
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => drop(handle.spawn(fut)),
+            Err(_) => {
+                cross_log!(warn, "Skipping prewarm: not inside a Tokio runtime");
+            }
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        wasm_bindgen_futures::spawn_local(fut);
+    }
+
+    /// Resolve (and optionally connect to) one user's homeserver, logging failures.
+    async fn prewarm_user(client: &PubkyHttpClient, user: PublicKey, connect: bool) {
+        let result = async {
+            // Building the request resolves and caches the transport for `_pubky.<user>`.
+            let url = Url::parse(&format!("https://_pubky.{}/", user.z32()))?;
+            let request = client.cross_request_anonymous(Method::HEAD, url).await?;
+            if connect {
+                client.send(request).await?;
+            }
+            Ok::<_, crate::Error>(())
+        }
+        .await;
+        if let Err(e) = result {
+            cross_log!(debug, "Prewarm failed for {user}: {e}");
+        }
+    }
+
     /// Create an event stream builder for a single user.
     ///
     /// This is the simplest way to subscribe to events for one user. The homeserver
@@ -289,6 +677,16 @@ impl Pubky {
         EventStreamBuilder::for_homeserver(self.client.clone(), homeserver)
     }
 
+    /// Create a builder exporting a homeserver's full event history as JSON lines.
+    ///
+    /// `admin_url` is the base URL of the homeserver's admin server (e.g.
+    /// `http://127.0.0.1:6288`) and `admin_password` its configured password.
+    /// See [`crate::actors::event_export`] for the line format.
+    #[must_use]
+    pub fn event_export(&self, admin_url: &str, admin_password: &str) -> EventExportBuilder {
+        EventExportBuilder::new(self.client.clone(), admin_url, admin_password)
+    }
+
     // ------ Persistance helpers ----------
 
     /// Restore a session from a `.sess` secret file.
@@ -327,6 +725,15 @@ impl Pubky {
         PubkySession::import_secret(token, Some(self.client.clone())).await
     }
 
+    /// Restore a session handed over by another process with
+    /// [`PubkySession::export_handoff`].
+    ///
+    /// # Errors
+    /// - See [`PubkySession::import_handoff`].
+    pub async fn import_session_handoff(&self, blob: &str) -> Result<PubkySession> {
+        PubkySession::import_handoff(blob, Some(self.client.clone())).await
+    }
+
     /// Restore an origin-bound delegated browser grant session.
     ///
     /// This uses non-secret metadata plus a browser-held non-extractable key.
@@ -1,12 +1,16 @@
-use reqwest::Response;
+use std::time::Duration;
 
-use crate::errors::{Error, RequestError, Result};
+use reqwest::{Response, StatusCode, header::RETRY_AFTER};
+
+use crate::PubkyHttpClient;
+use crate::errors::{AuthError, Error, RequestError, Result};
 
 /// Convert non-2xx responses into a structured error that includes the server body.
 ///
 /// If the status is successful (2xx), the original response is returned.
 /// If the status is an error (4xx or 5xx), the response body is consumed
 /// to create a `PubkyError::Request(RequestError::Server)` and returned as an `Err`.
+/// A `415 Unsupported Media Type` becomes [`RequestError::UnsupportedMediaType`].
 pub async fn check_http_status(response: Response) -> Result<Response> {
     if response.status().is_success() {
         return Ok(response);
@@ -20,5 +24,119 @@ pub async fn check_http_status(response: Response) -> Result<Response> {
             .to_string()
     });
 
+    if status == StatusCode::UNSUPPORTED_MEDIA_TYPE {
+        return Err(Error::from(RequestError::UnsupportedMediaType { message }));
+    }
     Err(Error::from(RequestError::Server { status, message }))
 }
+
+/// Like [`check_http_status`], for signup and signin requests.
+///
+/// A `429 Too Many Requests` carrying a delay-seconds `Retry-After` header (the
+/// homeserver's failed-attempt lockout or a request-count rate limit) becomes
+/// [`RequestError::TooManyAttempts`]. A 429 without one stays a [`RequestError::Server`].
+///
+/// A `400` or `401` received while the client's clock is off the server's by more
+/// than the default tolerance becomes [`AuthError::ClockSkewDetected`].
+pub async fn check_auth_attempt_status(
+    client: &PubkyHttpClient,
+    response: Response,
+) -> Result<Response> {
+    if matches!(
+        response.status(),
+        StatusCode::BAD_REQUEST | StatusCode::UNAUTHORIZED
+    ) && let Some(offset_secs) = client.clock_skew.excessive_offset_secs()
+    {
+        return Err(AuthError::ClockSkewDetected { offset_secs }.into());
+    }
+
+    let retry_after = (response.status() == StatusCode::TOO_MANY_REQUESTS)
+        .then(|| response.headers().get(RETRY_AFTER))
+        .flatten()
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse().ok())
+        .map(Duration::from_secs);
+
+    match retry_after {
+        Some(retry_after) => Err(Error::from(RequestError::TooManyAttempts { retry_after })),
+        None => check_http_status(response).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Error parsing reads statuses, headers and plain-text bodies only, so these tests
+    // pass with and without the `json` feature; CI runs them in both configurations.
+
+    fn response(status: StatusCode, retry_after: Option<&str>, body: &str) -> Response {
+        let mut builder = http::Response::builder().status(status);
+        if let Some(retry_after) = retry_after {
+            builder = builder.header(RETRY_AFTER, retry_after);
+        }
+        builder.body(body.to_string()).unwrap().into()
+    }
+
+    #[tokio::test]
+    async fn check_http_status_keeps_plain_text_messages() {
+        let err = check_http_status(response(
+            StatusCode::INSUFFICIENT_STORAGE,
+            None,
+            "Disk space quota exceeded",
+        ))
+        .await
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Request(RequestError::Server { status, ref message })
+                if status == StatusCode::INSUFFICIENT_STORAGE
+                    && message == "Disk space quota exceeded"
+        ));
+
+        let err = check_http_status(response(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            None,
+            "image/png is not allowed",
+        ))
+        .await
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Request(RequestError::UnsupportedMediaType { ref message })
+                if message == "image/png is not allowed"
+        ));
+    }
+
+    #[tokio::test]
+    async fn check_auth_attempt_status_reads_retry_after_header() {
+        let client = PubkyHttpClient::builder()
+            .isolated_pkarr_test()
+            .build()
+            .unwrap();
+
+        let limited = response(
+            StatusCode::TOO_MANY_REQUESTS,
+            Some("30"),
+            "Rate limit exceeded",
+        );
+        let err = check_auth_attempt_status(&client, limited)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Request(RequestError::TooManyAttempts { retry_after })
+                if retry_after == Duration::from_secs(30)
+        ));
+
+        let without_header = response(StatusCode::TOO_MANY_REQUESTS, None, "Rate limit exceeded");
+        let err = check_auth_attempt_status(&client, without_header)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Request(RequestError::Server { status, .. })
+                if status == StatusCode::TOO_MANY_REQUESTS
+        ));
+    }
+}
@@ -67,6 +67,12 @@ pub struct EphemeralTestnet {
 ///     .build()
 ///     .await?;
 ///
+/// // Override single config fields
+/// let testnet = EphemeralTestnet::builder()
+///     .configure(|config| config.general.signup_mode = SignupMode::TokenRequired)
+///     .build()
+///     .await?;
+///
 /// // Custom keypair
 /// let testnet = EphemeralTestnet::builder()
 ///     .keypair(Keypair::random())
@@ -107,6 +113,31 @@ impl EphemeralTestnetBuilder {
         self
     }
 
+    /// Override individual homeserver config fields.
+    ///
+    /// `f` is applied to the config set with [`Self::config()`], or to
+    /// [`ConfigToml::minimal_test_config()`] if none was set yet. Calls can be chained;
+    /// a later call to [`Self::config()`] replaces all earlier overrides.
+    ///
+    /// ```ignore
+    /// let testnet = EphemeralTestnet::builder()
+    ///     .configure(|config| {
+    ///         config.general.signup_mode = SignupMode::TokenRequired;
+    ///         config.admin.enabled = true;
+    ///     })
+    ///     .build()
+    ///     .await?;
+    /// ```
+    pub fn configure(mut self, f: impl FnOnce(&mut ConfigToml)) -> Self {
+        let mut config = self
+            .homeserver_config
+            .take()
+            .unwrap_or_else(ConfigToml::minimal_test_config);
+        f(&mut config);
+        self.homeserver_config = Some(config);
+        self
+    }
+
     /// Set a specific keypair for the homeserver.
     pub fn keypair(mut self, keypair: Keypair) -> Self {
         self.homeserver_keypair = Some(keypair);
@@ -456,6 +487,31 @@ mod test {
         );
     }
 
+    #[tokio::test]
+    async fn test_builder_configure_overrides() {
+        let network = EphemeralTestnet::builder()
+            .config(ConfigToml::default_test_config())
+            .configure(|config| config.admin.enabled = false)
+            .build()
+            .await
+            .unwrap();
+        assert!(
+            network.homeserver_app().admin_server().is_none(),
+            "Overrides should apply on top of the custom config"
+        );
+
+        // Without a base config, overrides apply to the minimal test config.
+        let network = EphemeralTestnet::builder()
+            .configure(|config| config.admin.listen_socket = ([127, 0, 0, 1], 0).into())
+            .configure(|config| config.admin.enabled = true)
+            .build()
+            .await
+            .unwrap();
+        let homeserver = network.homeserver_app();
+        assert!(homeserver.admin_server().is_some());
+        assert!(homeserver.metrics_server().is_none());
+    }
+
     #[tokio::test]
     async fn test_builder_with_custom_keypair() {
         // Verify custom keypair is used
@@ -210,15 +210,9 @@ impl Testnet {
                 .dht_report_policy(pkarr::dht::ReportPolicy::testnet())
                 // 100ms timeout for requests. This makes network-only resolution fast
                 // because it doesn't need to wait the default 2s which would slow down the tests.
-                .request_timeout(Duration::from_millis(100));
-            if relays.is_empty() {
-                builder.no_relays()
-            } else {
-                builder
-                    .relays(&relays)
-                    .expect("testnet relays should be valid urls")
-            }
+                .request_timeout(Duration::from_millis(100))
         });
+        builder.testnet_relays(&relays);
 
         builder
     }